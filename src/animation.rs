@@ -0,0 +1,195 @@
+use raylib::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::{document::{Document, DocumentInfo}, export::{self, ImageFormat}, gif};
+
+/// How neighboring frames are previewed behind the current frame
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct OnionSkin {
+    /// How many frames preceding the current frame are shown
+    pub frames_before: u8,
+
+    /// How many frames following the current frame are shown
+    pub frames_after: u8,
+
+    /// The tint applied to preceding frames
+    pub color_before: Color,
+
+    /// The tint applied to following frames
+    pub color_after: Color,
+
+    /// The opacity of the nearest neighboring frames
+    ///
+    /// Each frame further away is proportionally more transparent
+    pub opacity: f32,
+}
+
+impl Default for OnionSkin {
+    #[inline]
+    fn default() -> Self {
+        Self::default_onion_skin()
+    }
+}
+
+impl OnionSkin {
+    /// The onion skin used when the user hasn't customized it
+    pub const fn default_onion_skin() -> Self {
+        Self {
+            frames_before: 1,
+            frames_after: 1,
+            color_before: Color::new(255, 64, 64, 255),
+            color_after: Color::new(64, 160, 255, 255),
+            opacity: 0.35,
+        }
+    }
+}
+
+/// Animation settings of a [`Document`]
+///
+/// Every artboard in the document is a frame, played in the order the artboards are stored
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Animation {
+    /// Playback speed in frames per second
+    pub fps: f32,
+
+    /// How neighboring frames are previewed while editing
+    pub onion_skin: OnionSkin,
+
+    /// The format frames are exported as
+    #[serde(default)]
+    pub format: ImageFormat,
+
+    /// Whether frames are exported as one looping GIF rather than one file of [`Animation::format`] each
+    #[serde(default)]
+    pub is_gif: bool,
+}
+
+impl Default for Animation {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Animation {
+    /// Slowest allowed playback speed
+    pub const MIN_FPS: f32 = 1.0;
+
+    /// Fastest allowed playback speed
+    pub const MAX_FPS: f32 = 60.0;
+
+    /// Construct animation settings at 12 frames per second
+    pub const fn new() -> Self {
+        Self {
+            fps: 12.0,
+            onion_skin: OnionSkin::default_onion_skin(),
+            format: ImageFormat::Png,
+            is_gif: false,
+        }
+    }
+}
+
+/// The animation mode state of an [`Editor`][`crate::editor::Editor`]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Playback {
+    /// Index of the artboard currently being shown
+    pub frame: u32,
+
+    /// Whether frames are being advanced automatically
+    pub is_playing: bool,
+
+    /// Seconds accumulated since the frame last changed
+    elapsed: f32,
+}
+
+impl Playback {
+    /// Construct a paused playback on the first frame
+    pub const fn new() -> Self {
+        Self {
+            frame: 0,
+            is_playing: false,
+            elapsed: 0.0,
+        }
+    }
+
+    /// Advance playback by `dt` seconds, looping back to the first frame after the last
+    pub fn tick(&mut self, dt: f32, fps: f32, num_frames: u32) {
+        if !self.is_playing || num_frames == 0 {
+            return;
+        }
+        let frame_time = 1.0 / fps.clamp(Animation::MIN_FPS, Animation::MAX_FPS);
+        self.elapsed += dt;
+        while self.elapsed >= frame_time {
+            self.elapsed -= frame_time;
+            self.frame = (self.frame + 1) % num_frames;
+        }
+    }
+
+    /// Move `delta` frames forward (or backward if negative), wrapping around the ends
+    ///
+    /// Pauses playback
+    pub fn step(&mut self, delta: i32, num_frames: u32) {
+        self.is_playing = false;
+        self.elapsed = 0.0;
+        if num_frames != 0 {
+            self.frame = (self.frame as i64 + delta as i64).rem_euclid(num_frames as i64) as u32;
+        }
+    }
+
+    /// Iterate over the frames that should be drawn as onion skin, along with their tint
+    ///
+    /// Does not wrap around the ends; the first frame has no preceding frames
+    pub fn onion_skin_frames(&self, onion_skin: &OnionSkin, num_frames: u32) -> impl Iterator<Item = (u32, Color)> {
+        let frame = self.frame;
+        let before = (1..=onion_skin.frames_before as u32)
+            .filter_map(move |n| Some((frame.checked_sub(n)?, n, onion_skin.frames_before, onion_skin.color_before)));
+        let after = (1..=onion_skin.frames_after as u32)
+            .filter_map(move |n| Some((frame.checked_add(n).filter(|&i| i < num_frames)?, n, onion_skin.frames_after, onion_skin.color_after)));
+        let opacity = onion_skin.opacity;
+        before.chain(after)
+            .map(move |(i, n, count, color)| {
+                let falloff = 1.0 - (n - 1) as f32 / count as f32;
+                (i, color.alpha(opacity * falloff))
+            })
+    }
+}
+
+/// Describes a frame sequence written by an [`ExportJob`][`crate::export::ExportJob`]
+#[derive(Serialize)]
+struct ExportManifest<'a> {
+    title: &'a str,
+    info: &'a DocumentInfo,
+    fps: f32,
+    /// File names of the frames, in order
+    frames: &'a [String],
+}
+
+/// The file name of frame `index` of a sequence exported as `format`, e.g. `<title>_0000.png`
+pub fn frame_file_name(document: &Document, index: usize, format: ImageFormat) -> String {
+    format!("{}_{index:04}.{}", export::clean_file_name(&document.title), format.extension())
+}
+
+/// The name of the folder a sequence is exported into, `<title>_frames`
+pub fn frames_directory_name(document: &Document) -> String {
+    format!("{}_frames", export::clean_file_name(&document.title))
+}
+
+/// The file name of the document's animation exported as a GIF, `<title>.gif`
+pub fn gif_file_name(document: &Document) -> String {
+    format!("{}.{}", export::clean_file_name(&document.title), gif::EXTENSION)
+}
+
+/// The file name of a sequence's manifest, `<title>_manifest.json`
+pub fn manifest_file_name(document: &Document) -> String {
+    format!("{}_manifest.json", export::clean_file_name(&document.title))
+}
+
+/// Serialize the manifest listing `frames` along with the document's [info][`DocumentInfo`]
+pub fn manifest_json(document: &Document, frames: &[String]) -> Result<String, String> {
+    let manifest = ExportManifest {
+        title: &document.title,
+        info: &document.info,
+        fps: document.animation.fps,
+        frames,
+    };
+    serde_json::to_string_pretty(&manifest).map_err(|e| e.to_string())
+}
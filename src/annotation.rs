@@ -0,0 +1,120 @@
+use raylib::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::ui::widgets::measure_text;
+
+/// Background of sticky notes
+const NOTE_COLOR: Color = Color::new(255, 228, 120, 240);
+
+/// Text and outline of sticky notes
+const NOTE_TEXT_COLOR: Color = Color::new(48, 40, 16, 255);
+
+/// Freehand markup and arrows
+const MARKUP_COLOR: Color = Color::new(230, 40, 90, 255);
+
+/// Screenspace thickness of markup and arrow lines
+const LINE_THICKNESS: f32 = 2.0;
+
+/// Screenspace length of the sides of an arrowhead
+const ARROWHEAD_SIZE: f32 = 10.0;
+
+/// Screenspace space around the text of a sticky note
+const NOTE_PADDING: f32 = 6.0;
+
+/// What an [`Annotation`] shows
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum AnnotationKind {
+    /// A sticky note of text, with its top-left corner at the annotation's position
+    Note { text: String },
+
+    /// Freehand lines drawn over the artwork, through these worldspace points
+    Markup { points: Vec<Vector2> },
+
+    /// An arrow from the annotation's position pointing at a worldspace position
+    Arrow { target: Vector2 },
+}
+
+/// A design review note drawn over the artwork
+///
+/// Annotations are stored with the document apart from its layers, so exporters never see them.
+/// Notes and arrowheads stay the same size on screen at any zoom
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Annotation {
+    /// The worldspace position the annotation is tied to
+    pub position: Vector2,
+
+    pub kind: AnnotationKind,
+}
+
+impl Annotation {
+    /// The screenspace rectangle of a sticky note with `text` at the screenspace `corner`
+    fn note_rect(corner: Vector2, text: &str, font_size: i32) -> Rectangle {
+        let size = Vector2::new(measure_text(text, font_size) as f32, font_size as f32) + Vector2::one() * (NOTE_PADDING * 2.0);
+        Rectangle::new(corner.x, corner.y, size.x, size.y)
+    }
+
+    /// Whether the screenspace `point` is on the annotation as seen through `camera`
+    ///
+    /// Lines count as hit within `tolerance` pixels
+    pub fn is_hit(&self, point: Vector2, camera: &Camera2D, font_size: i32, tolerance: f32) -> bool {
+        let to_screen = |world: Vector2| (world - camera.target) * camera.zoom + camera.offset;
+        let is_near_segment = |a: Vector2, b: Vector2| {
+            let ab = b - a;
+            let t = if ab.length_sqr() > f32::EPSILON { ((point - a).dot(ab) / ab.length_sqr()).clamp(0.0, 1.0) } else { 0.0 };
+            point.distance_to(a + ab * t) <= tolerance
+        };
+        match &self.kind {
+            AnnotationKind::Note { text } => Self::note_rect(to_screen(self.position), text, font_size).check_collision_point_rec(point),
+            AnnotationKind::Markup { points } => points.windows(2).any(|pair| is_near_segment(to_screen(pair[0]), to_screen(pair[1]))),
+            AnnotationKind::Arrow { target } => is_near_segment(to_screen(self.position), to_screen(*target)),
+        }
+    }
+
+    /// Draw the annotation in screenspace as seen through `camera`
+    pub fn draw(&self, d: &mut impl RaylibDraw, camera: &Camera2D, font_size: i32) {
+        let to_screen = |world: Vector2| (world - camera.target) * camera.zoom + camera.offset;
+        match &self.kind {
+            AnnotationKind::Note { text } => {
+                let rect = Self::note_rect(to_screen(self.position), text, font_size);
+                d.draw_rectangle_rec(rect, NOTE_COLOR);
+                d.draw_rectangle_lines_ex(rect, 1.0, NOTE_TEXT_COLOR);
+                d.draw_text(text, (rect.x + NOTE_PADDING) as i32, (rect.y + NOTE_PADDING) as i32, font_size, NOTE_TEXT_COLOR);
+            }
+
+            AnnotationKind::Markup { points } => {
+                for pair in points.windows(2) {
+                    d.draw_line_ex(to_screen(pair[0]), to_screen(pair[1]), LINE_THICKNESS, MARKUP_COLOR);
+                }
+            }
+
+            AnnotationKind::Arrow { target } => {
+                let (tail, head) = (to_screen(self.position), to_screen(*target));
+                d.draw_line_ex(tail, head, LINE_THICKNESS, MARKUP_COLOR);
+                let direction = (head - tail).normalized();
+                if direction.length_sqr() > 0.5 {
+                    let back = head - direction * ARROWHEAD_SIZE;
+                    let side = Vector2::new(-direction.y, direction.x) * (ARROWHEAD_SIZE * 0.5);
+                    // counter-clockwise, as raylib expects
+                    d.draw_triangle(head, back - side, back + side, MARKUP_COLOR);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_annotation_hit() {
+        let camera = Camera2D { offset: Vector2::new(100.0, 100.0), target: Vector2::zero(), rotation: 0.0, zoom: 2.0 };
+        let arrow = Annotation { position: Vector2::zero(), kind: AnnotationKind::Arrow { target: Vector2::new(50.0, 0.0) } };
+        assert!(arrow.is_hit(Vector2::new(150.0, 102.0), &camera, 10, 4.0));
+        assert!(!arrow.is_hit(Vector2::new(150.0, 110.0), &camera, 10, 4.0));
+        assert!(!arrow.is_hit(Vector2::new(210.0, 100.0), &camera, 10, 4.0), "arrows should end at their target");
+
+        let markup = Annotation { position: Vector2::zero(), kind: AnnotationKind::Markup { points: vec![Vector2::zero(), Vector2::new(0.0, 20.0)] } };
+        assert!(markup.is_hit(Vector2::new(101.0, 130.0), &camera, 10, 4.0));
+        assert!(!markup.is_hit(Vector2::new(101.0, 150.0), &camera, 10, 4.0));
+    }
+}
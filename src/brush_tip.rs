@@ -0,0 +1,223 @@
+use std::{path::{Path, PathBuf}, rc::Rc};
+use raylib::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::{export, preferences};
+
+/// How the stamps of a [`BrushTip`] are laid along a raster brush stroke
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TipOptions {
+    /// The distance between stamps, as a fraction of the brush diameter
+    pub spacing: f32,
+
+    /// The farthest a stamp strays from the stroke, as a fraction of the brush radius
+    ///
+    /// Stamps that don't follow the stroke are turned randomly by up to this fraction of a half turn
+    pub jitter: f32,
+
+    /// Whether stamps turn to follow the direction of the stroke
+    pub is_following: bool,
+}
+
+impl Default for TipOptions {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TipOptions {
+    /// Smallest spacing, so slow strokes don't stamp thousands of times
+    pub const MIN_SPACING: f32 = 0.05;
+
+    /// Largest spacing
+    pub const MAX_SPACING: f32 = 2.0;
+
+    /// Closely spaced, unjittered stamps that don't turn
+    pub const fn new() -> Self {
+        Self {
+            spacing: 0.1,
+            jitter: 0.0,
+            is_following: false,
+        }
+    }
+}
+
+/// A grayscale stamp the raster brush paints with instead of a circle
+#[derive(Debug)]
+pub struct BrushTip {
+    /// The display name of the tip (the file name without extension, for tips loaded from files)
+    pub name: String,
+
+    /// White, with the coverage of the tip in the alpha channel, so it can be tinted with the brush color
+    ///
+    /// Shared so a stroke can hold onto the tip while the library is borrowed elsewhere
+    pub texture: Rc<Texture2D>,
+}
+
+/// The [tips][`BrushTip`] the raster brush can paint with: a built-in set, followed by every image in a user folder
+#[derive(Debug)]
+pub struct BrushTipLibrary {
+    /// The folder tips are loaded from and added to
+    ///
+    /// [`None`] if there is no folder, so only the built-in tips are available
+    pub directory: Option<PathBuf>,
+
+    /// The built-in tips, then the loaded tips sorted by name
+    pub tips: Vec<BrushTip>,
+}
+
+impl BrushTipLibrary {
+    /// The width and height of the built-in tips, in pixels
+    const BUILT_IN_SIZE: i32 = 64;
+
+    /// The names of the built-in tips, each generated by [`built_in_coverage`]
+    const BUILT_IN: [&str; 4] = ["Soft", "Square", "Flat", "Chalk"];
+
+    /// Construct a library without any tips, before [loading][`BrushTipLibrary::load`] is possible
+    pub const fn new() -> Self {
+        Self {
+            directory: None,
+            tips: Vec::new(),
+        }
+    }
+
+    /// The folder used when the user hasn't chosen one
+    ///
+    /// [`None`] if the home folder can't be determined
+    pub fn default_directory() -> Option<PathBuf> {
+        Some(preferences::app_directory()?.join("brush_tips"))
+    }
+
+    /// Generate the built-in tips and load every image in `directory`, creating the folder if it doesn't exist
+    ///
+    /// Files that fail to load are skipped
+    pub fn load(rl: &mut RaylibHandle, thread: &RaylibThread, directory: Option<PathBuf>) -> Result<Self, String> {
+        let mut library = Self {
+            directory,
+            tips: Vec::new(),
+        };
+        for name in Self::BUILT_IN {
+            let mut image = Image::gen_image_color(Self::BUILT_IN_SIZE, Self::BUILT_IN_SIZE, Color::BLANK);
+            for y in 0..Self::BUILT_IN_SIZE {
+                for x in 0..Self::BUILT_IN_SIZE {
+                    // from -1 to 1 across the tip, sampled at pixel centers
+                    let u = (x as f32 + 0.5) / Self::BUILT_IN_SIZE as f32 * 2.0 - 1.0;
+                    let v = (y as f32 + 0.5) / Self::BUILT_IN_SIZE as f32 * 2.0 - 1.0;
+                    let coverage = built_in_coverage(name, u, v, (x, y));
+                    image.draw_pixel(x, y, Color::WHITE.alpha(coverage.clamp(0.0, 1.0)));
+                }
+            }
+            let texture = rl.load_texture_from_image(thread, &image)?;
+            library.tips.push(BrushTip { name: name.to_string(), texture: Rc::new(texture) });
+        }
+
+        if let Some(directory) = library.directory.clone() {
+            std::fs::create_dir_all(&directory).map_err(|e| e.to_string())?;
+            let entries = std::fs::read_dir(&directory).map_err(|e| e.to_string())?;
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if let Err(e) = library.load_tip(rl, thread, &path) {
+                    eprintln!("failed to load brush tip {}: {e}", path.display());
+                }
+            }
+        }
+        Ok(library)
+    }
+
+    /// Load the image at `path` as a tip, where darker pixels paint more, replacing any loaded tip with the same name
+    ///
+    /// Returns the name of the tip, or [`None`] without loading anything if the file isn't a PNG
+    fn load_tip(&mut self, rl: &mut RaylibHandle, thread: &RaylibThread, path: &Path) -> Result<Option<String>, String> {
+        if !path.extension().and_then(|ext| ext.to_str()).is_some_and(|ext| ext.eq_ignore_ascii_case("png")) {
+            return Ok(None);
+        }
+        let source = Image::load_image(path.to_str().ok_or("brush tip path should be valid UTF-8")?)?;
+        let mut image = Image::gen_image_color(source.width, source.height, Color::BLANK);
+        let pixels = source.get_image_data();
+        for (y, row) in pixels.chunks(source.width.max(1) as usize).enumerate() {
+            for (x, color) in row.iter().enumerate() {
+                let luminance = 0.299 * f32::from(color.r) + 0.587 * f32::from(color.g) + 0.114 * f32::from(color.b);
+                let coverage = (1.0 - luminance / 255.0) * f32::from(color.a) / 255.0;
+                image.draw_pixel(x as i32, y as i32, Color::WHITE.alpha(coverage));
+            }
+        }
+        let texture = rl.load_texture_from_image(thread, &image)?;
+
+        let name = path.file_stem().unwrap_or_default().to_string_lossy().into_owned();
+        let built_in = Self::BUILT_IN.len().min(self.tips.len());
+        if let Some(existing) = self.tips[built_in..].iter().position(|tip| tip.name == name) {
+            self.tips.remove(built_in.saturating_add(existing));
+        }
+        let index = built_in.saturating_add(self.tips[built_in..].partition_point(|tip| tip.name < name));
+        self.tips.insert(index, BrushTip { name: name.clone(), texture: Rc::new(texture) });
+        Ok(Some(name))
+    }
+
+    /// Copy the image at `path` into the library's folder and load it as a tip
+    ///
+    /// The copy is numbered (`name 2`, `name 3`, and so on) rather than replace a file in the folder or share a name with another tip.
+    /// Returns the name of the tip. Without a folder, the tip is loaded from `path` and only lasts until the application exits
+    pub fn add(&mut self, rl: &mut RaylibHandle, thread: &RaylibThread, path: &Path) -> Result<String, String> {
+        let extension = path.extension().and_then(|ext| ext.to_str()).unwrap_or_default();
+        if !extension.eq_ignore_ascii_case("png") {
+            return Err(format!("unsupported brush tip file: {}", path.display()));
+        }
+        let path = match &self.directory {
+            Some(directory) if path.parent() != Some(directory.as_path()) => {
+                let stem = export::clean_file_name(&path.file_stem().ok_or("brush tip path should name a file")?.to_string_lossy());
+                let name = unused_tip_name(&stem, |name| self.get(name).is_some() || directory.join(format!("{name}.{extension}")).exists());
+                let copy = directory.join(format!("{name}.{extension}"));
+                std::fs::copy(path, &copy).map_err(|e| e.to_string())?;
+                copy
+            }
+            _ => path.to_path_buf(),
+        };
+        self.load_tip(rl, thread, &path)?.ok_or_else(|| format!("unsupported brush tip file: {}", path.display()))
+    }
+
+    /// Get the tip named `name`
+    ///
+    /// A loaded tip is found before a built-in tip of the same name, so files in the folder aren't hidden by the built-in set
+    pub fn get(&self, name: &str) -> Option<&BrushTip> {
+        self.tips.iter().rev().find(|tip| tip.name == name)
+    }
+}
+
+/// `name`, or the first of `name 2`, `name 3`, and so on that isn't `is_taken`
+fn unused_tip_name(name: &str, is_taken: impl Fn(&str) -> bool) -> String {
+    std::iter::once(name.to_owned())
+        .chain((2_u32..).map(|n| format!("{name} {n}")))
+        .find(|name| !is_taken(name))
+        .expect("should find an unused name before running out of numbers")
+}
+
+/// How much of the pixel at `(x, y)`, whose center is `(u, v)` (from -1 to 1 across the tip), the built-in tip `name` covers
+fn built_in_coverage(name: &str, u: f32, v: f32, (x, y): (i32, i32)) -> f32 {
+    let distance = u.hypot(v);
+    match name {
+        "Soft" => (1.0 - distance).max(0.0).powi(2),
+        "Square" => if u.abs().max(v.abs()) <= 0.9 { 1.0 } else { 0.0 },
+        // an ellipse a quarter as tall as it is wide, like a flat brush
+        "Flat" => if u.hypot(v * 4.0) <= 1.0 { 1.0 } else { 0.0 },
+        "Chalk" => {
+            // a grainy circle, patchier toward the edge
+            let hash = (x.wrapping_mul(73_856_093) ^ y.wrapping_mul(19_349_663)).wrapping_mul(83_492_791);
+            let grain = hash.wrapping_shr(8).rem_euclid(1000) as f32 / 1000.0;
+            if distance <= 1.0 && grain > distance * 0.6 { 1.0 } else { 0.0 }
+        }
+        _ => 0.0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unused_tip_name() {
+        assert_eq!(unused_tip_name("Grain", |_| false), "Grain");
+        let taken = ["Soft", "Soft 2"];
+        assert_eq!(unused_tip_name("Soft", |name| taken.contains(&name)), "Soft 3");
+    }
+}
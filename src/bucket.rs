@@ -0,0 +1,286 @@
+use std::collections::{HashMap, VecDeque};
+use raylib::prelude::*;
+use crate::curve::{self, Curve, CurveBuilder};
+
+/// Most cells across either side of the grid regions are found in, however small the cells would otherwise be
+const MAX_CELLS: f32 = 1024.0;
+
+/// Cells of open space around the paths, so the flood can tell it has escaped them
+const MARGIN: f32 = 2.0;
+
+/// Outlines enclosing fewer cells than this are specks left between paths, and are dropped
+const MIN_LOOP_CELLS: f32 = 4.0;
+
+/// A region enclosed by paths, found by [`find_region`]
+#[derive(Debug)]
+pub struct Region {
+    /// The closed outlines of the region, filled with the even-odd rule: its outer boundary and the boundaries of any holes in it
+    pub outlines: Vec<Curve>,
+
+    /// The index of the lowest of the given paths bordering the region
+    pub bottom: usize,
+}
+
+/// Find the region around the worldspace `point` enclosed by the `paths`, given as polylines from the bottom up
+///
+/// The region is found on a grid of `cell_size` worldspace pixels, coarsened on large documents, and reaches under
+/// the paths bordering it up to their centerlines so no sliver shows between the fill and the line art. Gaps between
+/// paths up to `gap` wide are bridged. [`None`] if `point` is on a path or isn't enclosed by them
+pub fn find_region(paths: &[Vec<na::Vector2<f32>>], point: Vector2, gap: f32, cell_size: f32) -> Option<Region> {
+    let mut points = paths.iter().flatten();
+    let first = *points.next()?;
+    let (min, max) = points.fold((first, first), |(min, max), p| (min.inf(p), max.sup(p)));
+    if point.x <= min.x || point.y <= min.y || point.x >= max.x || point.y >= max.y {
+        return None;
+    }
+
+    let radius = gap.max(0.0) * 0.5;
+    let extent = (max - min).max() + radius * 2.0;
+    let cell = cell_size.max(extent / MAX_CELLS);
+    // every cell a path passes through is blocked, however the path crosses it
+    let reach = radius + cell;
+    let origin = min - na::Vector2::repeat(reach + cell * MARGIN);
+    let span = max - min + na::Vector2::repeat((reach + cell * MARGIN) * 2.0);
+    let grid = Grid {
+        columns: (span.x / cell).ceil() as usize,
+        rows: (span.y / cell).ceil() as usize,
+    };
+
+    // the lowest path blocking each cell
+    let mut blocked = vec![None::<usize>; grid.columns.saturating_mul(grid.rows)];
+    for (index, path) in paths.iter().enumerate() {
+        for segment in path.windows(2) {
+            let (a, b) = (segment[0], segment[1]);
+            let low = grid.cell_at((a.inf(&b) - origin) / cell - na::Vector2::repeat(reach / cell));
+            let high = grid.cell_at((a.sup(&b) - origin) / cell + na::Vector2::repeat(reach / cell));
+            for y in low.1..=high.1 {
+                for x in low.0..=high.0 {
+                    let center = origin + na::Vector2::new(x as f32 + 0.5, y as f32 + 0.5) * cell;
+                    if curve::distance_to_segment(center, a, b) <= reach
+                        && let Some(owner) = blocked.get_mut(grid.index(x, y))
+                    {
+                        *owner = Some(owner.map_or(index, |owner| owner.min(index)));
+                    }
+                }
+            }
+        }
+    }
+
+    // flood the open cells around the point, giving up if it escapes to the edge of the grid
+    let start = grid.cell_at((na::Vector2::new(point.x, point.y) - origin) / cell);
+    if blocked[grid.index(start.0, start.1)].is_some() {
+        return None;
+    }
+    let mut is_filled = vec![false; blocked.len()];
+    is_filled[grid.index(start.0, start.1)] = true;
+    let mut queue = VecDeque::from([start]);
+    let mut border = Vec::new();
+    let mut bottom = None::<usize>;
+    while let Some((x, y)) = queue.pop_front() {
+        for (nx, ny) in grid.neighbors(x, y) {
+            let Some((nx, ny)) = nx.zip(ny) else { return None };
+            let i = grid.index(nx, ny);
+            if let Some(owner) = blocked[i] {
+                bottom = Some(bottom.map_or(owner, |bottom| bottom.min(owner)));
+                border.push((x, y));
+            } else if !is_filled[i] {
+                is_filled[i] = true;
+                queue.push_back((nx, ny));
+            }
+        }
+    }
+
+    // grow back under the paths, which kept the flood `reach` away from their centerlines
+    let grow = (reach / cell).ceil() as usize;
+    let grow_squared = (reach / cell).powi(2);
+    let mut is_grown = is_filled.clone();
+    for &(x, y) in &border {
+        for gy in y.saturating_sub(grow)..=y.saturating_add(grow).min(grid.rows.saturating_sub(1)) {
+            for gx in x.saturating_sub(grow)..=x.saturating_add(grow).min(grid.columns.saturating_sub(1)) {
+                let offset = na::Vector2::new(gx as f32 - x as f32, gy as f32 - y as f32);
+                let i = grid.index(gx, gy);
+                if offset.norm_squared() <= grow_squared && blocked[i].is_some() {
+                    is_grown[i] = true;
+                }
+            }
+        }
+    }
+
+    let outlines = grid.trace(&is_grown)
+        .into_iter()
+        .filter(|outline| signed_area(outline).abs() >= MIN_LOOP_CELLS)
+        .map(|mut outline| {
+            outline.push(outline[0]);
+            let kept = curve::simplify_polyline(&outline, 1.0);
+            // the first corner comes back around at the end
+            kept[..kept.len().saturating_sub(1)].iter()
+                .fold(CurveBuilder::new(), |builder, &i| builder.corner(origin + outline[i] * cell))
+                .cycle()
+        })
+        .collect::<Vec<_>>();
+    if outlines.is_empty() {
+        return None;
+    }
+    Some(Region { outlines, bottom: bottom? })
+}
+
+/// The dimensions of the grid regions are found in, which is stored row by row
+#[derive(Debug, Clone, Copy)]
+struct Grid {
+    columns: usize,
+    rows: usize,
+}
+
+impl Grid {
+    /// The index of the cell in `column` and `row`
+    const fn index(self, column: usize, row: usize) -> usize {
+        row.saturating_mul(self.columns).saturating_add(column)
+    }
+
+    /// The column and row of the cell containing `position`, measured in cells from the corner of the grid and clamped inside it
+    fn cell_at(self, position: na::Vector2<f32>) -> (usize, usize) {
+        (
+            (position.x.max(0.0) as usize).min(self.columns.saturating_sub(1)),
+            (position.y.max(0.0) as usize).min(self.rows.saturating_sub(1)),
+        )
+    }
+
+    /// The cells beside the cell in `column` and `row`, [`None`] where they would be outside the grid
+    fn neighbors(self, column: usize, row: usize) -> [(Option<usize>, Option<usize>); 4] {
+        let inside = |column: Option<usize>, row: Option<usize>| (
+            column.filter(|&column| column < self.columns),
+            row.filter(|&row| row < self.rows),
+        );
+        [
+            inside(column.checked_sub(1), Some(row)),
+            inside(column.checked_add(1), Some(row)),
+            inside(Some(column), row.checked_sub(1)),
+            inside(Some(column), row.checked_add(1)),
+        ]
+    }
+
+    /// The outlines of the `filled` cells, as loops of cell corners clockwise on screen around filled cells
+    fn trace(self, filled: &[bool]) -> Vec<Vec<na::Vector2<f32>>> {
+        let is_filled = |column: Option<usize>, row: Option<usize>| column.zip(row)
+            .is_some_and(|(column, row)| column < self.columns && row < self.rows && filled[self.index(column, row)]);
+
+        // each side of a filled cell facing an empty one, leading from corner to corner with the filled cell on its right
+        let mut edges = HashMap::<(usize, usize), Vec<(usize, usize)>>::new();
+        for row in 0..self.rows {
+            for column in 0..self.columns {
+                if !filled[self.index(column, row)] {
+                    continue;
+                }
+                let (left, top) = (column, row);
+                let (right, bottom) = (column.saturating_add(1), row.saturating_add(1));
+                for (is_open, from, to) in [
+                    (!is_filled(Some(column), row.checked_sub(1)), (left, top), (right, top)),
+                    (!is_filled(column.checked_add(1), Some(row)), (right, top), (right, bottom)),
+                    (!is_filled(Some(column), row.checked_add(1)), (right, bottom), (left, bottom)),
+                    (!is_filled(column.checked_sub(1), Some(row)), (left, bottom), (left, top)),
+                ] {
+                    if is_open {
+                        edges.entry(from).or_default().push(to);
+                    }
+                }
+            }
+        }
+
+        // every corner has as many sides leading out as in, so following them always comes back around
+        let mut loops = Vec::new();
+        while let Some(&start) = edges.keys().next() {
+            let mut outline = Vec::new();
+            let mut corner = start;
+            while let Some(next) = edges.get_mut(&corner).and_then(Vec::pop) {
+                if edges.get(&corner).is_some_and(Vec::is_empty) {
+                    edges.remove(&corner);
+                }
+                outline.push(na::Vector2::new(corner.0 as f32, corner.1 as f32));
+                corner = next;
+            }
+            edges.remove(&corner);
+            loops.push(outline);
+        }
+        loops
+    }
+}
+
+/// The area inside the closed polygon `outline`, positive if it runs clockwise on screen
+fn signed_area(outline: &[na::Vector2<f32>]) -> f32 {
+    outline.iter()
+        .zip(outline.iter().cycle().skip(1))
+        .map(|(a, b)| a.x * b.y - b.x * a.y)
+        .sum::<f32>() * 0.5
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{document::Document, editor::{Editor, Selection}, layer::LayerContent};
+    use super::*;
+
+    /// A closed polyline through `corners`
+    fn polygon(corners: &[(f32, f32)]) -> Vec<na::Vector2<f32>> {
+        corners.iter().chain(&corners[..1]).map(|&(x, y)| na::Vector2::new(x, y)).collect()
+    }
+
+    #[test]
+    fn test_find_region() {
+        // a square crossed by a line, with a small ring in its lower half
+        let paths = [
+            polygon(&[(0.0, 0.0), (100.0, 0.0), (100.0, 100.0), (0.0, 100.0)]),
+            vec![na::Vector2::new(-10.0, 40.0), na::Vector2::new(110.0, 40.0)],
+            polygon(&[(40.0, 60.0), (60.0, 60.0), (60.0, 80.0), (40.0, 80.0)]),
+        ];
+        let top = find_region(&paths, Vector2::new(50.0, 20.0), 0.0, 1.0).unwrap();
+        assert_eq!(top.outlines.len(), 1);
+        assert_eq!(top.bottom, 0);
+        let bounds = top.outlines[0].control_bounds().unwrap();
+        assert!(bounds.x.abs() < 2.0 && bounds.y.abs() < 2.0 && (bounds.width - 100.0).abs() < 3.0 && (bounds.height - 40.0).abs() < 3.0,
+            "the fill should reach the centerlines of the paths around it, but covers {bounds:?}");
+
+        let lower = find_region(&paths, Vector2::new(20.0, 70.0), 0.0, 1.0).unwrap();
+        assert_eq!(lower.outlines.len(), 2, "the ring should be a hole");
+
+        let ring = find_region(&paths, Vector2::new(50.0, 70.0), 0.0, 1.0).unwrap();
+        assert_eq!(ring.bottom, 2);
+
+        assert!(find_region(&paths, Vector2::new(50.0, 40.0), 0.0, 1.0).is_none(), "clicking a path shouldn't fill anything");
+        assert!(find_region(&paths, Vector2::new(105.0, 20.0), 0.0, 1.0).is_none(), "the outside isn't enclosed");
+    }
+
+    #[test]
+    fn test_find_region_gaps() {
+        // a square with a 6 pixel gap in its top side
+        let paths = [vec![
+            na::Vector2::new(47.0, 0.0),
+            na::Vector2::new(0.0, 0.0),
+            na::Vector2::new(0.0, 100.0),
+            na::Vector2::new(100.0, 100.0),
+            na::Vector2::new(100.0, 0.0),
+            na::Vector2::new(53.0, 0.0),
+        ]];
+        assert!(find_region(&paths, Vector2::new(50.0, 50.0), 2.0, 1.0).is_none(), "a gap wider than the tolerance should leak");
+        assert!(find_region(&paths, Vector2::new(50.0, 50.0), 8.0, 1.0).is_some(), "a gap within the tolerance should be closed");
+    }
+
+    #[test]
+    fn test_bucket_fill() {
+        let mut editor = Editor::new_default(Document::new("bucket".to_owned()));
+        editor.add_curve_layer("background".to_owned(), crate::make_curve!((0,0) -> (200,0) -> (200,200) -> (0,200) -> cycle));
+        editor.add_curve_layer("ink".to_owned(), crate::make_curve!((50,50) -> (150,50) -> (150,150) -> (50,150) -> cycle));
+
+        editor.bucket_fill_at(Vector2::new(100.0, 100.0), 0.0);
+        assert_eq!(editor.document.layers.len(), 3);
+        assert_eq!(editor.document.layers[1].name, "Bucket fill", "the fill should go beneath the line art around it");
+        assert!(matches!(editor.document.layers[1].content, LayerContent::Curve(_)));
+        assert!(matches!(editor.selection(), Selection::Layers(paths) if paths == &[[1]]));
+
+        // the ink is a hole in the background's region
+        editor.bucket_fill_at(Vector2::new(25.0, 100.0), 0.0);
+        assert_eq!(editor.document.layers.len(), 4);
+        assert!(matches!(&editor.document.layers[0].content, LayerContent::Compound(compound) if compound.subpaths.len() == 2));
+
+        editor.bucket_fill_at(Vector2::new(250.0, 100.0), 0.0);
+        assert_eq!(editor.document.layers.len(), 4, "the outside shouldn't be filled");
+    }
+}
@@ -0,0 +1,84 @@
+use raylib::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::{curve::Curve, style::{WidthProfile, WidthProfileControl, WidthProfileVertex}};
+
+/// Width profile controls placed along each segment of a calligraphic stroke
+const CONTROLS_PER_SEGMENT: u16 = 4;
+
+/// The pen the vector brush draws with in calligraphy mode: a flat nib held at a fixed angle,
+/// so strokes are broad across the nib and thin along it
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Nib {
+    /// Whether the vector brush draws with the nib instead of an even width
+    pub is_enabled: bool,
+
+    /// The angle (in degrees, clockwise on screen) of the broad edge of the nib
+    pub angle: f32,
+
+    /// The thickness of the nib relative to its breadth, from 0 (a hairline edge) to 1 (round)
+    pub roundness: f32,
+}
+
+impl Default for Nib {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Nib {
+    /// Largest angle; the nib is symmetric, so larger angles repeat smaller ones
+    pub const MAX_ANGLE: f32 = 180.0;
+
+    /// A disabled nib at the traditional 45 degree pen angle
+    pub const fn new() -> Self {
+        Self {
+            is_enabled: false,
+            angle: 45.0,
+            roundness: 0.1,
+        }
+    }
+
+    /// Half the width of the mark a nib `size` wide leaves moving in `direction`
+    ///
+    /// Widest moving across the broad edge and narrowest (`roundness` times as wide) moving along it
+    pub fn half_width(&self, size: f32, direction: na::Vector2<f32>) -> f32 {
+        let breadth = size * 0.5;
+        let thickness = breadth * self.roundness.clamp(0.0, 1.0);
+        let relative = direction.y.atan2(direction.x) - self.angle.to_radians();
+        (breadth * relative.sin()).hypot(thickness * relative.cos())
+    }
+
+    /// A width profile for `curve` as if it were drawn with a nib `size` wide
+    pub fn width_profile(&self, curve: &Curve, size: f32) -> WidthProfile {
+        let mut controls = curve.pos_vel_iter::<CONTROLS_PER_SEGMENT>()
+            .filter_map(|(i, t, _, v)| {
+                let direction = v.try_normalize(f32::EPSILON)?;
+                let vertex = WidthProfileVertex::flat(self.half_width(size, direction));
+                Some(WidthProfileControl::new_even(i as f32 + t, vertex))
+            })
+            .collect::<Vec<_>>();
+        // samples stop short of the end of the last segment
+        if let Some(&last) = controls.last() {
+            controls.push(WidthProfileControl { t: curve.segment_count() as f32, ..last });
+        }
+        WidthProfile::Variable(controls)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_half_width() {
+        let nib = Nib { is_enabled: true, angle: 0.0, roundness: 0.25 };
+        // along the broad edge only the thickness of the nib shows
+        assert!((nib.half_width(8.0, na::Vector2::new(1.0, 0.0)) - 1.0).abs() < 1e-4);
+        // across it the whole breadth does
+        assert!((nib.half_width(8.0, na::Vector2::new(0.0, 1.0)) - 4.0).abs() < 1e-4);
+        let round = Nib { roundness: 1.0, ..nib };
+        assert!((round.half_width(8.0, na::Vector2::new(0.6, 0.8)) - 4.0).abs() < 1e-4);
+    }
+}
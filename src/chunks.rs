@@ -0,0 +1,196 @@
+use std::{collections::{HashMap, HashSet}, fs::OpenOptions, io::Write, path::{Path, PathBuf}};
+use serde::{de::{DeserializeOwned, IgnoredAny}, Deserialize, Serialize};
+
+/// The first line of every chunk file, telling it apart from a plain JSON document
+const MAGIC: &str = "ava-chunks 1";
+
+/// A file is rewritten from scratch instead of appended to once it would grow past this many times
+/// the size of its current chunks
+const COMPACT_RATIO: usize = 2;
+
+/// One line of a chunk file
+#[derive(Serialize, Deserialize)]
+enum ChunkLine<D> {
+    /// The contents of the chunk named `key`, replacing any written before it once committed
+    Chunk { key: String, data: D },
+
+    /// Makes every chunk written before it current, in the order of `keys`
+    ///
+    /// Chunks not listed are dropped
+    Commit { keys: Vec<String> },
+}
+
+/// The chunks current as of the last commit in a chunk file
+struct Committed<'a> {
+    /// Each chunk's key and the line it was written on, in commit order
+    lines: Vec<(String, &'a str)>,
+
+    /// Bytes up to the end of the last commit; anything after it is an interrupted write
+    len: usize,
+}
+
+/// Find the lines of the committed chunks in `text`
+///
+/// [`None`] if `text` isn't a chunk file
+fn committed(text: &str) -> Option<Committed<'_>> {
+    let mut lines = text.split_inclusive('\n');
+    let first = lines.next()?;
+    if first.trim_end() != MAGIC {
+        return None;
+    }
+    let mut offset = first.len();
+    let mut result = Committed { lines: Vec::new(), len: offset };
+    let mut written = HashMap::new();
+    let mut pending = Vec::new();
+    for line in lines {
+        offset += line.len();
+        // a line without its newline was cut off mid-write
+        let Some(json) = line.strip_suffix('\n') else { break };
+        let Ok(parsed) = serde_json::from_str::<ChunkLine<IgnoredAny>>(json) else { break };
+        match parsed {
+            ChunkLine::Chunk { key, .. } => pending.push((key, json)),
+            ChunkLine::Commit { keys } => {
+                written.extend(pending.drain(..));
+                result.lines = keys.into_iter()
+                    .filter_map(|key| {
+                        let line = *written.get(&key)?;
+                        Some((key, line))
+                    })
+                    .collect();
+                result.len = offset;
+            }
+        }
+    }
+    Some(result)
+}
+
+/// Read the committed chunks of the chunk file `text`, in order
+///
+/// [`None`] if `text` isn't a chunk file. Chunks written after the last commit are ignored
+pub fn parse<D: DeserializeOwned>(text: &str) -> Result<Option<Vec<(String, D)>>, String> {
+    let Some(committed) = committed(text) else { return Ok(None) };
+    committed.lines.into_iter()
+        .map(|(key, line)| match serde_json::from_str::<ChunkLine<D>>(line).map_err(|e| e.to_string())? {
+            ChunkLine::Chunk { data, .. } => Ok((key, data)),
+            ChunkLine::Commit { .. } => Err("commit in place of chunk".to_owned()),
+        })
+        .collect::<Result<_, String>>()
+        .map(Some)
+}
+
+/// A file of named JSON chunks that is saved by appending the chunks that changed
+///
+/// Each save ends with a commit line listing the current chunks, so a save interrupted partway
+/// leaves the file as it was after the last one. Superseded chunks pile up until the file is
+/// [compacted][`COMPACT_RATIO`] by rewriting it
+#[derive(Debug)]
+pub struct ChunkFile {
+    path: PathBuf,
+
+    /// The contents of the file before writing to it, or [`None`] if it doesn't exist yet
+    text: Option<String>,
+}
+
+impl ChunkFile {
+    /// Read the file at `path` to be written to, if it exists
+    pub fn open(path: &Path) -> Result<Self, String> {
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => Some(text),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => return Err(e.to_string()),
+        };
+        Ok(Self { path: path.to_path_buf(), text })
+    }
+
+    /// The committed data of the chunk `key`
+    ///
+    /// [`None`] if there is no such chunk, or the file isn't a chunk file
+    pub fn chunk<D: DeserializeOwned>(&self, key: &str) -> Option<D> {
+        let committed = committed(self.text.as_deref()?)?;
+        let (_, line) = committed.lines.into_iter().find(|(k, _)| k == key)?;
+        match serde_json::from_str::<ChunkLine<D>>(line).ok()? {
+            ChunkLine::Chunk { data, .. } => Some(data),
+            ChunkLine::Commit { .. } => None,
+        }
+    }
+
+    /// Make `chunks` the contents of the file, in order, and flush it to disk
+    ///
+    /// Only chunks that differ from the file's current ones are appended. The file is rewritten
+    /// instead if it isn't a chunk file yet or would grow too large, replacing it only once the
+    /// new one is completely written
+    pub fn write<D: Serialize>(self, chunks: &[(String, D)]) -> Result<(), String> {
+        let lines = chunks.iter()
+            .map(|(key, data)| serde_json::to_string(&ChunkLine::Chunk { key: key.clone(), data }))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+        let commit = serde_json::to_string(&ChunkLine::<()>::Commit { keys: chunks.iter().map(|(key, _)| key.clone()).collect() })
+            .map_err(|e| e.to_string())?;
+        let live_len = MAGIC.len() + 1 + lines.iter().chain([&commit]).map(|line| line.len() + 1).sum::<usize>();
+
+        if let Some(committed) = self.text.as_deref().and_then(committed) {
+            let current = committed.lines.iter().map(|&(_, line)| line).collect::<HashSet<_>>();
+            let mut appended = String::new();
+            for line in lines.iter().filter(|line| !current.contains(line.as_str())).chain([&commit]) {
+                appended.push_str(line);
+                appended.push('\n');
+            }
+            if committed.len + appended.len() <= live_len * COMPACT_RATIO {
+                let mut file = OpenOptions::new().append(true).open(&self.path).map_err(|e| e.to_string())?;
+                // drop whatever an interrupted save left after the last commit
+                file.set_len(committed.len as u64).map_err(|e| e.to_string())?;
+                file.write_all(appended.as_bytes()).map_err(|e| e.to_string())?;
+                file.sync_data().map_err(|e| e.to_string())?;
+                return Ok(());
+            }
+        }
+
+        let mut text = String::with_capacity(live_len);
+        for line in [MAGIC].into_iter().chain(lines.iter().map(String::as_str)).chain([commit.as_str()]) {
+            text.push_str(line);
+            text.push('\n');
+        }
+        let name = self.path.file_name().unwrap_or_default().to_string_lossy();
+        let temp_path = self.path.with_file_name(format!("{name}.tmp"));
+        let mut file = std::fs::File::create(&temp_path).map_err(|e| e.to_string())?;
+        file.write_all(text.as_bytes()).map_err(|e| e.to_string())?;
+        file.sync_all().map_err(|e| e.to_string())?;
+        std::fs::rename(&temp_path, &self.path).map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunk_file() {
+        let path = std::env::temp_dir().join(format!("chunk_file_test_{}.ava", std::process::id()));
+        let read = || parse::<u32>(&std::fs::read_to_string(&path).unwrap()).unwrap().unwrap();
+        let chunks = |values: &[(&str, u32)]| values.iter().map(|&(key, value)| (key.to_owned(), value)).collect::<Vec<_>>();
+
+        ChunkFile::open(&path).unwrap().write(&chunks(&[("a", 1), ("b", 2), ("c", 3)])).unwrap();
+        let full_len = std::fs::metadata(&path).unwrap().len();
+        ChunkFile::open(&path).unwrap().write(&chunks(&[("a", 1), ("b", 20), ("c", 3)])).unwrap();
+        assert_eq!(read(), chunks(&[("a", 1), ("b", 20), ("c", 3)]));
+        assert!(std::fs::metadata(&path).unwrap().len() < full_len * 2, "only the changed chunk should be appended");
+
+        // a save cut off partway is ignored, and cleaned up by the next one
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(b"{\"Chunk\":{\"key\":\"a\",\"data\":100}}\n{\"Commit\":{\"ke").unwrap();
+        drop(file);
+        assert_eq!(read(), chunks(&[("a", 1), ("b", 20), ("c", 3)]));
+        let file = ChunkFile::open(&path).unwrap();
+        assert_eq!(file.chunk::<u32>("b"), Some(20));
+        file.write(&chunks(&[("c", 3), ("a", 10)])).unwrap();
+        assert_eq!(read(), chunks(&[("c", 3), ("a", 10)]));
+
+        // many small saves are compacted back down
+        for i in 0..100 {
+            ChunkFile::open(&path).unwrap().write(&chunks(&[("c", 3), ("a", i)])).unwrap();
+        }
+        assert_eq!(read(), chunks(&[("c", 3), ("a", 99)]));
+        assert!(std::fs::metadata(&path).unwrap().len() <= full_len * COMPACT_RATIO as u64);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,55 @@
+use std::{io::Write, path::PathBuf, process::{Command, Stdio}};
+
+/// Place the PNG file `png` on the system clipboard as an image
+///
+/// raylib only reaches the clipboard's text, so images are handed to the platform's own tools:
+/// `wl-copy` or `xclip` on Linux, AppleScript on macOS, and PowerShell on Windows
+pub fn set_png(png: &[u8]) -> Result<(), String> {
+    // the path is handed over as an argument or environment variable rather than pasted into the script,
+    // so quotes and other special characters in it can't break out of the script
+    if cfg!(target_os = "macos") {
+        let path = write_temp_png(png)?;
+        let script = ["on run argv", "set the clipboard to (read (POSIX file (item 1 of argv)) as «class PNGf»)", "end run"];
+        run(Command::new("osascript").args(script.into_iter().flat_map(|line| ["-e", line])).arg(&path), None)
+    } else if cfg!(target_os = "windows") {
+        let path = write_temp_png(png)?;
+        let script = "Add-Type -AssemblyName System.Windows.Forms, System.Drawing; \
+            [System.Windows.Forms.Clipboard]::SetImage([System.Drawing.Image]::FromFile($env:CLIPBOARD_PNG))";
+        run(Command::new("powershell").args(["-NoProfile", "-STA", "-Command", script]).env("CLIPBOARD_PNG", &path), None)
+    } else if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        run(Command::new("wl-copy").args(["--type", "image/png"]), Some(png))
+    } else {
+        run(Command::new("xclip").args(["-selection", "clipboard", "-target", "image/png", "-in"]), Some(png))
+    }
+}
+
+/// Write `png` to a temporary file for tools that only read images from files
+///
+/// The file is overwritten by the next copy rather than deleted, since the clipboard may read it lazily
+fn write_temp_png(png: &[u8]) -> Result<PathBuf, String> {
+    let path = std::env::temp_dir().join(format!("{}_clipboard.png", env!("CARGO_PKG_NAME")));
+    std::fs::write(&path, png).map_err(|e| e.to_string())?;
+    Ok(path)
+}
+
+/// Run `command` to completion, writing `input` to its standard input
+fn run(command: &mut Command, input: Option<&[u8]>) -> Result<(), String> {
+    let program = command.get_program().to_string_lossy().into_owned();
+    let mut child = command
+        .stdin(if input.is_some() { Stdio::piped() } else { Stdio::null() })
+        .stdout(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("failed to run {program}: {e}"))?;
+    if let Some(input) = input
+        && let Some(mut stdin) = child.stdin.take()
+    {
+        // dropping stdin closes it, so the tool knows the image is complete
+        stdin.write_all(input).map_err(|e| e.to_string())?;
+    }
+    let status = child.wait().map_err(|e| e.to_string())?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("{program} exited with {status}"))
+    }
+}
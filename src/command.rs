@@ -0,0 +1,343 @@
+use crate::locale;
+
+/// A command that can be run by its [keyboard shortcut][`crate::preferences::Keymap`] or from the command palette
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EditorAction {
+    PointSelect,
+    Select,
+    RasterBrush,
+    VectorBrush,
+    Copy,
+    Paste,
+    ToggleAnimation,
+    PreviousFrame,
+    NextFrame,
+    PlayPause,
+    ExportFrames,
+    ToggleLibrary,
+    SaveSnippet,
+    DefineGraphicStyle,
+    /// Apply the graphic style at the index, counting from 0
+    ApplyGraphicStyle(u8),
+    /// Redefine the graphic style at the index, counting from 0, from the selection
+    RedefineGraphicStyle(u8),
+    DocumentInfo,
+    Preferences,
+    TrimRasters,
+    ToggleStylePanel,
+    CompareWithSaved,
+    CycleArtboardBackground,
+    RenameDocument,
+    CommandPalette,
+    DecreaseBrushSize,
+    IncreaseBrushSize,
+    SwapFillStroke,
+    ResetFillStroke,
+    ClearColor,
+    ToggleNavigator,
+    ToggleLayerPanel,
+    ExportLastSettings,
+    Export,
+    RenameArtboards,
+    ToggleSmartGuides,
+    ToggleEraser,
+    DecreaseEraserHardness,
+    IncreaseEraserHardness,
+    CyclePixelSnap,
+    Simplify,
+    MakeCompoundPath,
+    ReleaseCompoundPath,
+    Lasso,
+    MagicWand,
+    ScatterAlongPath,
+    ToggleProfiler,
+    SaveTrace,
+    SelectNextLayer,
+    SelectPreviousLayer,
+    ScaleSelection,
+    ToggleWidthPanel,
+    ExportPalette,
+    CopyAsPng,
+    CopyAsSvg,
+    ToggleArtboardCoordinates,
+    ToggleArtboardPanel,
+    ArrangeArtboards,
+    MarkAsAsset,
+    ExportAllAssets,
+    Connect,
+    ToggleConnectorRouting,
+    Rectangle,
+    RepairLayers,
+    SaveView,
+    NextView,
+    PreviousView,
+    ToggleAnnotations,
+    AddNote,
+    AddArrow,
+    ToggleMarkup,
+    RemoveAnnotation,
+    GridRepeat,
+    ScaleDocument,
+    ToggleTrimView,
+    MakeTemplate,
+    ReleaseTemplates,
+    Bucket,
+}
+
+impl EditorAction {
+    /// How many graphic styles have their own [`EditorAction::ApplyGraphicStyle`] and [`EditorAction::RedefineGraphicStyle`] shortcuts
+    pub const GRAPHIC_STYLE_SHORTCUTS: usize = 9;
+
+    /// Every action, in the order they are listed in the UI
+    pub const ALL: [Self; 93] = [
+        Self::PointSelect,
+        Self::Select,
+        Self::RasterBrush,
+        Self::VectorBrush,
+        Self::Copy,
+        Self::Paste,
+        Self::ToggleAnimation,
+        Self::PreviousFrame,
+        Self::NextFrame,
+        Self::PlayPause,
+        Self::ExportFrames,
+        Self::ToggleLibrary,
+        Self::SaveSnippet,
+        Self::DefineGraphicStyle,
+        Self::ApplyGraphicStyle(0),
+        Self::ApplyGraphicStyle(1),
+        Self::ApplyGraphicStyle(2),
+        Self::ApplyGraphicStyle(3),
+        Self::ApplyGraphicStyle(4),
+        Self::ApplyGraphicStyle(5),
+        Self::ApplyGraphicStyle(6),
+        Self::ApplyGraphicStyle(7),
+        Self::ApplyGraphicStyle(8),
+        Self::RedefineGraphicStyle(0),
+        Self::RedefineGraphicStyle(1),
+        Self::RedefineGraphicStyle(2),
+        Self::RedefineGraphicStyle(3),
+        Self::RedefineGraphicStyle(4),
+        Self::RedefineGraphicStyle(5),
+        Self::RedefineGraphicStyle(6),
+        Self::RedefineGraphicStyle(7),
+        Self::RedefineGraphicStyle(8),
+        Self::DocumentInfo,
+        Self::Preferences,
+        Self::TrimRasters,
+        Self::ToggleStylePanel,
+        Self::CompareWithSaved,
+        Self::CycleArtboardBackground,
+        Self::RenameDocument,
+        Self::CommandPalette,
+        Self::DecreaseBrushSize,
+        Self::IncreaseBrushSize,
+        Self::SwapFillStroke,
+        Self::ResetFillStroke,
+        Self::ClearColor,
+        Self::ToggleNavigator,
+        Self::ToggleLayerPanel,
+        Self::ExportLastSettings,
+        Self::Export,
+        Self::RenameArtboards,
+        Self::ToggleSmartGuides,
+        Self::ToggleEraser,
+        Self::DecreaseEraserHardness,
+        Self::IncreaseEraserHardness,
+        Self::CyclePixelSnap,
+        Self::Simplify,
+        Self::MakeCompoundPath,
+        Self::ReleaseCompoundPath,
+        Self::Lasso,
+        Self::MagicWand,
+        Self::ScatterAlongPath,
+        Self::ToggleProfiler,
+        Self::SaveTrace,
+        Self::SelectNextLayer,
+        Self::SelectPreviousLayer,
+        Self::ScaleSelection,
+        Self::ToggleWidthPanel,
+        Self::ExportPalette,
+        Self::CopyAsPng,
+        Self::CopyAsSvg,
+        Self::ToggleArtboardCoordinates,
+        Self::ToggleArtboardPanel,
+        Self::ArrangeArtboards,
+        Self::MarkAsAsset,
+        Self::ExportAllAssets,
+        Self::Connect,
+        Self::ToggleConnectorRouting,
+        Self::Rectangle,
+        Self::RepairLayers,
+        Self::SaveView,
+        Self::NextView,
+        Self::PreviousView,
+        Self::ToggleAnnotations,
+        Self::AddNote,
+        Self::AddArrow,
+        Self::ToggleMarkup,
+        Self::RemoveAnnotation,
+        Self::GridRepeat,
+        Self::ScaleDocument,
+        Self::ToggleTrimView,
+        Self::MakeTemplate,
+        Self::ReleaseTemplates,
+        Self::Bucket,
+    ];
+
+    /// The [localization][`crate::locale`] key of the action's name
+    pub const fn key(self) -> &'static str {
+        match self {
+            Self::PointSelect => "action.point_select",
+            Self::Select => "action.select",
+            Self::RasterBrush => "action.raster_brush",
+            Self::VectorBrush => "action.vector_brush",
+            Self::Copy => "action.copy",
+            Self::Paste => "action.paste",
+            Self::ToggleAnimation => "action.toggle_animation",
+            Self::PreviousFrame => "action.previous_frame",
+            Self::NextFrame => "action.next_frame",
+            Self::PlayPause => "action.play_pause",
+            Self::ExportFrames => "action.export_frames",
+            Self::ToggleLibrary => "action.toggle_library",
+            Self::SaveSnippet => "action.save_snippet",
+            Self::DefineGraphicStyle => "action.define_graphic_style",
+            Self::ApplyGraphicStyle(_) => "action.apply_graphic_style",
+            Self::RedefineGraphicStyle(_) => "action.redefine_graphic_style",
+            Self::DocumentInfo => "action.document_info",
+            Self::Preferences => "action.preferences",
+            Self::TrimRasters => "action.trim_rasters",
+            Self::ToggleStylePanel => "action.toggle_style_panel",
+            Self::CompareWithSaved => "action.compare_with_saved",
+            Self::CycleArtboardBackground => "action.cycle_artboard_background",
+            Self::RenameDocument => "action.rename_document",
+            Self::CommandPalette => "action.command_palette",
+            Self::DecreaseBrushSize => "action.decrease_brush_size",
+            Self::IncreaseBrushSize => "action.increase_brush_size",
+            Self::SwapFillStroke => "action.swap_fill_stroke",
+            Self::ResetFillStroke => "action.reset_fill_stroke",
+            Self::ClearColor => "action.clear_color",
+            Self::ToggleNavigator => "action.toggle_navigator",
+            Self::ToggleLayerPanel => "action.toggle_layer_panel",
+            Self::ExportLastSettings => "action.export_last_settings",
+            Self::Export => "action.export",
+            Self::RenameArtboards => "action.rename_artboards",
+            Self::ToggleSmartGuides => "action.toggle_smart_guides",
+            Self::ToggleEraser => "action.toggle_eraser",
+            Self::DecreaseEraserHardness => "action.decrease_eraser_hardness",
+            Self::IncreaseEraserHardness => "action.increase_eraser_hardness",
+            Self::CyclePixelSnap => "action.cycle_pixel_snap",
+            Self::Simplify => "action.simplify",
+            Self::MakeCompoundPath => "action.make_compound_path",
+            Self::ReleaseCompoundPath => "action.release_compound_path",
+            Self::Lasso => "action.lasso",
+            Self::MagicWand => "action.magic_wand",
+            Self::ScatterAlongPath => "action.scatter_along_path",
+            Self::ToggleProfiler => "action.toggle_profiler",
+            Self::SaveTrace => "action.save_trace",
+            Self::SelectNextLayer => "action.select_next_layer",
+            Self::SelectPreviousLayer => "action.select_previous_layer",
+            Self::ScaleSelection => "action.scale_selection",
+            Self::ToggleWidthPanel => "action.toggle_width_panel",
+            Self::ExportPalette => "action.export_palette",
+            Self::CopyAsPng => "action.copy_as_png",
+            Self::CopyAsSvg => "action.copy_as_svg",
+            Self::ToggleArtboardCoordinates => "action.toggle_artboard_coordinates",
+            Self::ToggleArtboardPanel => "action.toggle_artboard_panel",
+            Self::ArrangeArtboards => "action.arrange_artboards",
+            Self::MarkAsAsset => "action.mark_as_asset",
+            Self::ExportAllAssets => "action.export_all_assets",
+            Self::Connect => "action.connect",
+            Self::ToggleConnectorRouting => "action.toggle_connector_routing",
+            Self::Rectangle => "action.rectangle",
+            Self::RepairLayers => "action.repair_layers",
+            Self::SaveView => "action.save_view",
+            Self::NextView => "action.next_view",
+            Self::PreviousView => "action.previous_view",
+            Self::ToggleAnnotations => "action.toggle_annotations",
+            Self::AddNote => "action.add_note",
+            Self::AddArrow => "action.add_arrow",
+            Self::ToggleMarkup => "action.toggle_markup",
+            Self::RemoveAnnotation => "action.remove_annotation",
+            Self::GridRepeat => "action.grid_repeat",
+            Self::ScaleDocument => "action.scale_document",
+            Self::ToggleTrimView => "action.toggle_trim_view",
+            Self::MakeTemplate => "action.make_template",
+            Self::ReleaseTemplates => "action.release_templates",
+            Self::Bucket => "action.bucket",
+        }
+    }
+
+    /// The localized display name of the action
+    pub fn name(self) -> String {
+        match self {
+            // numbered like their default shortcuts
+            Self::ApplyGraphicStyle(index) | Self::RedefineGraphicStyle(index) => locale::tr_format(self.key(), &[&index.saturating_add(1)]),
+            _ => locale::tr(self.key()),
+        }
+    }
+}
+
+/// Score how well `query` matches `text`, higher is better
+///
+/// Every non-whitespace character of the query must appear in the text in the same order, ignoring case.
+/// Characters at the start of a word and runs of consecutive characters score higher
+///
+/// [`None`] if the query doesn't match
+pub fn fuzzy_score(query: &str, text: &str) -> Option<u32> {
+    let mut score = 0u32;
+    let mut text_chars = text.chars();
+    let mut prev = None::<char>;
+    let mut is_prev_matched = false;
+    for q in query.chars().filter(|c| !c.is_whitespace()) {
+        loop {
+            let c = text_chars.next()?;
+            let is_word_start = prev.is_none_or(|p| !p.is_alphanumeric());
+            prev = Some(c);
+            if c.eq_ignore_ascii_case(&q) {
+                let bonus = match (is_word_start, is_prev_matched) {
+                    (true, true) => 4,
+                    (true, false) => 3,
+                    (false, true) => 2,
+                    (false, false) => 1,
+                };
+                score = score.saturating_add(bonus);
+                is_prev_matched = true;
+                break;
+            }
+            is_prev_matched = false;
+        }
+    }
+    Some(score)
+}
+
+/// Get the actions whose names match `query`, best match first
+///
+/// Every action matches an empty query, in [`EditorAction::ALL`] order
+pub fn search(query: &str) -> Vec<EditorAction> {
+    let mut matches = EditorAction::ALL.iter()
+        .filter_map(|&action| Some((fuzzy_score(query, &action.name())?, action)))
+        .collect::<Vec<_>>();
+    // stable, so ties keep their listed order
+    matches.sort_by(|(a, _), (b, _)| b.cmp(a));
+    matches.into_iter().map(|(_, action)| action).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_score_requires_order() {
+        assert!(fuzzy_score("tsp", "Toggle style panel").is_some());
+        assert!(fuzzy_score("pst", "Toggle style panel").is_none());
+        assert_eq!(fuzzy_score("", "Copy"), Some(0));
+    }
+
+    #[test]
+    fn test_search_ranks_word_starts_first() {
+        assert_eq!(search("tsp").first(), Some(&EditorAction::ToggleStylePanel));
+        assert_eq!(search("pref").first(), Some(&EditorAction::Preferences));
+        assert_eq!(search("").len(), EditorAction::ALL.len());
+    }
+}
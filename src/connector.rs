@@ -0,0 +1,230 @@
+use std::collections::HashSet;
+use raylib::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::{curve::{Curve, CurvePoint, WeakCurve}, layer};
+
+/// How a [`Connector`] travels between its ends
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ConnectorRouting {
+    /// One straight line, leaving shapes toward the other end
+    #[default]
+    Straight,
+
+    /// Horizontal and vertical lines with a bend halfway, leaving shapes from the middle of a side
+    Elbow,
+}
+
+impl ConnectorRouting {
+    /// The other routing
+    pub const fn toggled(self) -> Self {
+        match self {
+            Self::Straight => Self::Elbow,
+            Self::Elbow => Self::Straight,
+        }
+    }
+}
+
+/// What one end of a [`Connector`] is attached to
+#[derive(Debug, Clone, Default)]
+pub enum ConnectorEnd {
+    /// Nothing; the end stays wherever the connector's curve ends
+    #[default]
+    Free,
+
+    /// An anchor of a curve
+    Anchor {
+        /// Weakly references a curve stored at the [`Document`][`crate::document::Document`] level
+        curve: WeakCurve,
+
+        /// Index into the curve's [points][`Curve::points`]
+        index: usize,
+    },
+
+    /// The edge of the rectangle containing some artwork, such as every curve of a layer
+    ///
+    /// Weakly references curves stored at the [`Document`][`crate::document::Document`] level
+    Bounds(Vec<WeakCurve>),
+}
+
+impl ConnectorEnd {
+    /// The curves the end is attached to
+    pub fn targets(&self) -> &[WeakCurve] {
+        match self {
+            Self::Free => &[],
+            Self::Anchor { curve, .. } => std::slice::from_ref(curve),
+            Self::Bounds(curves) => curves,
+        }
+    }
+
+    /// Where the end is attached, as a point or a rectangle
+    ///
+    /// [`None`] if the end is free, or all of its targets are gone or no longer drawn by any layer
+    fn target(&self, drawn: &HashSet<*const ()>) -> Option<Target> {
+        let is_drawn = |curve: &WeakCurve| drawn.contains(&(curve.as_ptr() as *const ()));
+        match self {
+            Self::Free => None,
+            Self::Anchor { curve, index } => {
+                let strong_curve = curve.upgrade().filter(|_| is_drawn(curve))?;
+                let curve_lock = strong_curve.lock();
+                let p = curve_lock.borrow().points.get(*index)?.p;
+                Some(Target::Point(Vector2::new(p.x, p.y)))
+            }
+            Self::Bounds(curves) => curves.iter()
+                .filter(|curve| is_drawn(curve))
+                .filter_map(|curve| {
+                    let strong_curve = curve.upgrade()?;
+                    let curve_lock = strong_curve.lock();
+                    let bounds = curve_lock.borrow().control_bounds();
+                    bounds
+                })
+                .reduce(layer::union)
+                .map(Target::Rect),
+        }
+    }
+}
+
+/// Resolved position of a [`ConnectorEnd`]
+#[derive(Debug, Clone, Copy)]
+enum Target {
+    Point(Vector2),
+    Rect(Rectangle),
+}
+
+impl Target {
+    /// The point the other end of the connector aims for
+    fn center(self) -> Vector2 {
+        match self {
+            Self::Point(point) => point,
+            Self::Rect(rect) => Vector2::new(rect.x + rect.width * 0.5, rect.y + rect.height * 0.5),
+        }
+    }
+
+    /// Where a connector aiming at `toward` leaves the target
+    fn exit(self, toward: Vector2, routing: ConnectorRouting) -> Vector2 {
+        let Self::Rect(rect) = self else { return self.center() };
+        let center = self.center();
+        let offset = toward - center;
+        let (half_width, half_height) = (rect.width * 0.5, rect.height * 0.5);
+        match routing {
+            ConnectorRouting::Straight => {
+                // scale the offset until it reaches the nearest edge along its direction
+                let scale_x = if offset.x == 0.0 { f32::INFINITY } else { half_width / offset.x.abs() };
+                let scale_y = if offset.y == 0.0 { f32::INFINITY } else { half_height / offset.y.abs() };
+                center + offset * scale_x.min(scale_y).min(1.0)
+            }
+            ConnectorRouting::Elbow => if offset.x.abs() >= offset.y.abs() {
+                Vector2::new(center.x + half_width.copysign(offset.x), center.y)
+            } else {
+                Vector2::new(center.x, center.y + half_height.copysign(offset.y))
+            },
+        }
+    }
+}
+
+/// A line between two points that stays attached to the artwork at its ends, re-routing when that artwork moves
+///
+/// Useful for the arrows of diagrams and flowcharts. See [`Document::route_connectors`][`crate::document::Document::route_connectors`]
+#[derive(Debug)]
+pub struct Connector {
+    /// The path drawn between the ends, rewritten whenever the connector is routed
+    ///
+    /// Weakly references a curve stored at the [`Document`][`crate::document::Document`] level
+    pub curve: WeakCurve,
+
+    /// What the first anchor of the curve is attached to
+    pub start: ConnectorEnd,
+
+    /// What the last anchor of the curve is attached to
+    pub end: ConnectorEnd,
+
+    /// How the curve travels between the ends
+    pub routing: ConnectorRouting,
+}
+
+impl Connector {
+    /// Rewrite the curve to run between the current positions of its ends
+    ///
+    /// `drawn` holds the addresses of every curve drawn by a layer; ends attached to anything else,
+    /// such as the curves of deleted layers, are left where they are as if free
+    ///
+    /// Returns whether the curve changed
+    pub fn route(&self, drawn: &HashSet<*const ()>) -> bool {
+        let Some(strong_curve) = self.curve.upgrade() else { return false };
+        let (start_target, end_target) = (self.start.target(drawn), self.end.target(drawn));
+        let curve_lock = strong_curve.lock();
+        let current = {
+            let curve_borrow = curve_lock.borrow();
+            curve_borrow.points.first().zip(curve_borrow.points.last())
+                .map(|(first, last)| (Vector2::new(first.p.x, first.p.y), Vector2::new(last.p.x, last.p.y)))
+        };
+        let Some((current_start, current_end)) = current else { return false };
+
+        let start_center = start_target.map_or(current_start, Target::center);
+        let end_center = end_target.map_or(current_end, Target::center);
+        let start = start_target.map_or(current_start, |target| target.exit(end_center, self.routing));
+        let end = end_target.map_or(current_end, |target| target.exit(start_center, self.routing));
+        let routed = route(start, end, self.routing);
+
+        let mut curve_borrow = curve_lock.borrow_mut();
+        if curve_borrow.points == routed.points && !curve_borrow.is_closed {
+            return false;
+        }
+        *curve_borrow = routed;
+        true
+    }
+}
+
+/// The curve of a connector running from `start` to `end`
+pub fn route(start: Vector2, end: Vector2, routing: ConnectorRouting) -> Curve {
+    let corner = |x: f32, y: f32| CurvePoint { c_in: na::Vector2::zeros(), p: na::Vector2::new(x, y), c_out: na::Vector2::zeros() };
+    let points = match routing {
+        ConnectorRouting::Straight => vec![corner(start.x, start.y), corner(end.x, end.y)],
+        ConnectorRouting::Elbow => {
+            let middle = (start + end) * 0.5;
+            if (end.x - start.x).abs() >= (end.y - start.y).abs() {
+                vec![corner(start.x, start.y), corner(middle.x, start.y), corner(middle.x, end.y), corner(end.x, end.y)]
+            } else {
+                vec![corner(start.x, start.y), corner(start.x, middle.y), corner(end.x, middle.y), corner(end.x, end.y)]
+            }
+        }
+    };
+    Curve { points, is_closed: false }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, sync::Arc};
+    use parking_lot::ReentrantMutex;
+    use super::*;
+
+    #[test]
+    fn test_route_connector() {
+        let shape = Arc::new(ReentrantMutex::new(RefCell::new(Curve::from(Rectangle::new(0.0, 0.0, 100.0, 50.0)))));
+        let line = Arc::new(ReentrantMutex::new(RefCell::new(route(Vector2::zero(), Vector2::new(300.0, 25.0), ConnectorRouting::Straight))));
+        let mut connector = Connector {
+            curve: Arc::downgrade(&line),
+            start: ConnectorEnd::Bounds(vec![Arc::downgrade(&shape)]),
+            end: ConnectorEnd::Free,
+            routing: ConnectorRouting::Straight,
+        };
+        let drawn = HashSet::from([Arc::as_ptr(&shape) as *const ()]);
+        assert!(connector.route(&drawn));
+        assert_eq!(line.lock().borrow().points[0].p, na::Vector2::new(100.0, 25.0));
+        assert!(!connector.route(&drawn), "routing again should change nothing");
+
+        shape.lock().borrow_mut().translate(na::Vector2::new(0.0, 100.0));
+        connector.routing = ConnectorRouting::Elbow;
+        assert!(connector.route(&drawn));
+        let points = line.lock().borrow().points.iter().map(|point| point.p).collect::<Vec<_>>();
+        assert_eq!(points, [
+            na::Vector2::new(100.0, 125.0),
+            na::Vector2::new(200.0, 125.0),
+            na::Vector2::new(200.0, 25.0),
+            na::Vector2::new(300.0, 25.0),
+        ]);
+
+        // ends attached to artwork no layer draws stay put
+        shape.lock().borrow_mut().translate(na::Vector2::new(0.0, 100.0));
+        assert!(!connector.route(&HashSet::new()));
+    }
+}
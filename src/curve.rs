@@ -21,16 +21,15 @@ pub struct CurvePoint {
 
 pub struct CurveIter<'a> {
     iter: std::slice::Iter<'a, CurvePoint>,
-    first: Option<&'a CurvePoint>,
-    is_closed: bool,
+    /// The first point, repeated after the last if the curve is closed
+    wrap: Option<&'a CurvePoint>,
 }
 
 impl<'a> CurveIter<'a> {
     fn new(iter: std::slice::Iter<'a, CurvePoint>, is_closed: bool) -> Self {
         Self {
+            wrap: if is_closed { iter.as_slice().first() } else { None },
             iter,
-            first: None,
-            is_closed,
         }
     }
 
@@ -46,44 +45,47 @@ impl<'a> Iterator for CurveIter<'a> {
     type Item = &'a CurvePoint;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if let Some(pp) = self.iter.next() {
-            if self.is_closed && self.first.is_none() {
-                self.first = Some(pp);
-            }
-            Some(pp)
-        } else {
-            self.first.take()
-        }
+        self.iter.next().or_else(|| self.wrap.take())
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
-        let base_len = self.iter.len();
-        let len = if base_len == 0 {
-            self.first.is_some() as usize
-        } else {
-            base_len + self.is_closed as usize
-        };
+        let len = self.iter.len().saturating_add(self.wrap.is_some() as usize);
         (len, Some(len))
     }
 }
 
+impl DoubleEndedIterator for CurveIter<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.wrap.take().or_else(|| self.iter.next_back())
+    }
+}
+
 impl ExactSizeIterator for CurveIter<'_> {}
 
+impl std::iter::FusedIterator for CurveIter<'_> {}
+
 pub struct FlatCurveIter<'a> {
     iter: CurveIter<'a>,
-    buffer: [na::Vector2<f32>; 3],
-    offset: u8,
+    /// The rest of the point most recently taken from the front
+    front: Option<std::array::IntoIter<na::Vector2<f32>, 3>>,
+    /// The rest of the point most recently taken from the back
+    back: Option<std::array::IntoIter<na::Vector2<f32>, 3>>,
 }
 
 impl<'a> FlatCurveIter<'a> {
     fn new(iter: CurveIter<'a>) -> Self {
         Self {
             iter,
-            buffer: Default::default(),
-            offset: 3,
+            front: None,
+            back: None,
         }
     }
 
+    /// `{c,p,c}` into `[c,p,c]`, with the controls made absolute
+    fn flatten(item: &CurvePoint) -> std::array::IntoIter<na::Vector2<f32>, 3> {
+        [item.p + item.c_in, item.p, item.p + item.c_out].into_iter()
+    }
+
     /// Group cubic bezier points into segments with overlapping anchor points
     ///
     /// `[c,p,c, c,p,c, ...]` into `[[p1,c2,c3,p4], [p4,c5,c6,p7], ...]`
@@ -96,37 +98,63 @@ impl<'a> Iterator for FlatCurveIter<'a> {
     type Item = na::Vector2<f32>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        debug_assert!(self.offset <= 3);
-        if self.offset == 3 {
-            let item = self.iter.next()?;
-            self.buffer = [item.p + item.c_in, item.p, item.p + item.c_out];
-            self.offset = 0;
+        loop {
+            if let Some(item) = self.front.as_mut().and_then(Iterator::next) {
+                return Some(item);
+            }
+            match self.iter.next() {
+                Some(item) => self.front = Some(Self::flatten(item)),
+                // the back may have started on the last point
+                None => return self.back.as_mut()?.next(),
+            }
         }
-        let idx = self.offset;
-        self.offset += 1;
-        Some(self.buffer[idx as usize])
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
-        let len = (self.iter.len() + 1) * 3 - self.offset as usize;
+        let len = self.iter.len()
+            .saturating_mul(3)
+            .saturating_add(self.front.as_ref().map_or(0, ExactSizeIterator::len))
+            .saturating_add(self.back.as_ref().map_or(0, ExactSizeIterator::len));
         (len, Some(len))
     }
 }
 
+impl DoubleEndedIterator for FlatCurveIter<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(item) = self.back.as_mut().and_then(DoubleEndedIterator::next_back) {
+                return Some(item);
+            }
+            match self.iter.next_back() {
+                Some(item) => self.back = Some(Self::flatten(item)),
+                // the front may have started on the last point
+                None => return self.front.as_mut()?.next_back(),
+            }
+        }
+    }
+}
+
 impl ExactSizeIterator for FlatCurveIter<'_> {}
 
+impl std::iter::FusedIterator for FlatCurveIter<'_> {}
+
 pub struct SplineWindows<'a> {
-    is_first: bool,
     iter: FlatCurveIter<'a>,
-    buffer: [na::Vector2<f32>; 4],
+    /// Number of windows not yet returned from either end
+    len: usize,
+    /// The anchor the next window from the front starts at, [`None`] before the first
+    front_anchor: Option<na::Vector2<f32>>,
+    /// The anchor the next window from the back ends at, [`None`] before the first
+    back_anchor: Option<na::Vector2<f32>>,
 }
 
 impl<'a> SplineWindows<'a> {
     fn new(iter: FlatCurveIter<'a>) -> Self {
         Self {
-            is_first: true,
+            len: (iter.len() / 3).saturating_sub(1),
             iter,
-            buffer: Default::default(),
+            front_anchor: None,
+            back_anchor: None,
         }
     }
 
@@ -142,33 +170,73 @@ impl<'a> Iterator for SplineWindows<'a> {
     type Item = [na::Vector2<f32>; 4];
 
     fn next(&mut self) -> Option<Self::Item> {
-        // skip from `c_in` to `p`
-        if self.is_first {
-            _ = self.iter.next();
-            self.is_first = false;
-            self.buffer[3] = self.iter.next()?;
+        if self.len == 0 {
+            return None;
         }
-        self.buffer[0] = self.buffer[3];
-        self.buffer[1] = self.iter.next()?;
-        self.buffer[2] = self.iter.next()?;
-        self.buffer[3] = self.iter.next()?;
-        Some(self.buffer.clone())
+        let p1 = match self.front_anchor {
+            Some(p1) => p1,
+            None => {
+                // skip from `c_in` to `p`
+                _ = self.iter.next();
+                self.iter.next()?
+            }
+        };
+        let c2 = self.iter.next()?;
+        let c3 = self.iter.next()?;
+        // once the back has taken the anchor, this is the last window left
+        let p4 = self.iter.next().or(self.back_anchor)?;
+        self.front_anchor = Some(p4);
+        self.len = self.len.saturating_sub(1);
+        Some([p1, c2, c3, p4])
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
-        let len = self.iter.iter.len().saturating_sub(1);
-        (len, Some(len))
+        (self.len, Some(self.len))
+    }
+}
+
+impl DoubleEndedIterator for SplineWindows<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        let p4 = match self.back_anchor {
+            Some(p4) => p4,
+            None => {
+                // skip from `c_out` to `p`
+                _ = self.iter.next_back();
+                self.iter.next_back()?
+            }
+        };
+        let c3 = self.iter.next_back()?;
+        let c2 = self.iter.next_back()?;
+        // once the front has taken the anchor, this is the last window left
+        let p1 = self.iter.next_back().or(self.front_anchor)?;
+        self.back_anchor = Some(p1);
+        self.len = self.len.saturating_sub(1);
+        Some([p1, c2, c3, p4])
     }
 }
 
 impl ExactSizeIterator for SplineWindows<'_> {}
 
+impl std::iter::FusedIterator for SplineWindows<'_> {}
+
+/// A window being sampled by a [`Sampled`]
+#[derive(Debug, Clone, Default)]
+struct SampledWindow {
+    mat: na::Matrix2x4<f32>,
+    index: u32,
+    /// The samples not yet returned
+    segments: std::ops::Range<u16>,
+}
+
 pub struct Sampled<'a, const RES: u16> {
     iter: SplineWindows<'a>,
-    mat: na::Matrix2x4<f32>,
-    spline_index: u32,
-    is_initialized: bool,
-    segment: u16,
+    /// Number of windows taken from the front of `iter`
+    front_windows: u32,
+    front: SampledWindow,
+    back: SampledWindow,
 }
 
 impl<'a, const RES: u16> Sampled<'a, RES> {
@@ -178,10 +246,9 @@ impl<'a, const RES: u16> Sampled<'a, RES> {
     fn new(iter: SplineWindows<'a>) -> Self {
         Self {
             iter,
-            mat: Default::default(),
-            spline_index: 0,
-            is_initialized: false,
-            segment: Self::RESOLUTION,
+            front_windows: 0,
+            front: SampledWindow::default(),
+            back: SampledWindow::default(),
         }
     }
 
@@ -190,7 +257,14 @@ impl<'a, const RES: u16> Sampled<'a, RES> {
     /// The buffer reflects the state of the current iteration
     /// (whatever was most recently returned by [`Sampled::next()`])
     fn mat(&self) -> &na::Matrix2x4<f32> {
-        &self.mat
+        &self.front.mat
+    }
+
+    /// Get the buffered matrix of bezier control points from the back
+    ///
+    /// The buffer reflects whatever was most recently returned by [`Sampled::next_back()`]
+    fn back_mat(&self) -> &na::Matrix2x4<f32> {
+        &self.back.mat
     }
 }
 
@@ -198,29 +272,62 @@ impl<'a, const RES: u16> Iterator for Sampled<'a, RES> {
     type Item = (u32, f32);
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.segment == Self::RESOLUTION {
-            if self.is_initialized {
-                self.spline_index += 1;
+        loop {
+            if let Some(segment) = self.front.segments.next() {
+                return Some((self.front.index, segment as f32 * Self::STEP));
+            }
+            if let Some(vecs) = self.iter.next() {
+                self.front = SampledWindow {
+                    mat: na::Matrix::from_columns(&vecs),
+                    index: self.front_windows,
+                    segments: 0..Self::RESOLUTION,
+                };
+                self.front_windows = self.front_windows.saturating_add(1);
+            } else if self.back.segments.is_empty() {
+                return None;
             } else {
-                self.is_initialized = true;
+                // the back took the last window, so finish it from the front
+                self.front = std::mem::take(&mut self.back);
             }
-            self.segment = 0;
-            let vecs = self.iter.next()?;
-            self.mat = na::Matrix::from_columns(&vecs);
         }
-        let t = self.segment as f32 * Self::STEP;
-        self.segment += 1;
-        Some((self.spline_index, t))
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
-        let len = (self.iter.len() + 1) * Self::RESOLUTION as usize - self.segment as usize;
+        let len = self.iter.len()
+            .saturating_mul(Self::RESOLUTION as usize)
+            .saturating_add(self.front.segments.len())
+            .saturating_add(self.back.segments.len());
         (len, Some(len))
     }
 }
 
+impl<const RES: u16> DoubleEndedIterator for Sampled<'_, RES> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(segment) = self.back.segments.next_back() {
+                return Some((self.back.index, segment as f32 * Self::STEP));
+            }
+            if let Some(vecs) = self.iter.next_back() {
+                self.back = SampledWindow {
+                    mat: na::Matrix::from_columns(&vecs),
+                    // every window before this one is either still in `iter` or was taken from the front
+                    index: self.front_windows.saturating_add(self.iter.len() as u32),
+                    segments: 0..Self::RESOLUTION,
+                };
+            } else if self.front.segments.is_empty() {
+                return None;
+            } else {
+                // the front took the last window, so finish it from the back
+                self.back = std::mem::take(&mut self.front);
+            }
+        }
+    }
+}
+
 impl<const RES: u16> ExactSizeIterator for Sampled<'_, RES> {}
 
+impl<const RES: u16> std::iter::FusedIterator for Sampled<'_, RES> {}
+
 trait SamplingHelper: Sized + Iterator {
     const RES: u16;
     type Sampled: ExactSizeIterator<Item = (u32, f32)>;
@@ -231,6 +338,9 @@ trait SamplingHelper: Sized + Iterator {
     /// ```
     fn mat(&self) -> &na::Matrix2x4<f32>;
 
+    /// [`SamplingHelper::mat`] of the item most recently returned from the back
+    fn back_mat(&self) -> &na::Matrix2x4<f32>;
+
     /// Get the output of the latest [`Sampled`] in the chain,
     /// regardless of iterator nesting
     fn item_sample(item: &Self::Item) -> <Self::Sampled as Iterator>::Item;
@@ -245,6 +355,11 @@ impl<'a, const RES: u16> SamplingHelper for Sampled<'a, RES> {
         self.mat()
     }
 
+    #[inline]
+    fn back_mat(&self) -> &na::Matrix2x4<f32> {
+        self.back_mat()
+    }
+
     #[inline]
     fn item_sample(item: &Self::Item) -> <Self::Sampled as Iterator>::Item {
         *item
@@ -279,21 +394,46 @@ impl<I> Positions<I> {
     }
 }
 
+/// The number of evenly spaced samples the segment with control points `window` needs to stay within `tolerance` of its polyline
+///
+/// Uses Wang's formula, which bounds the distance by how sharply the control polygon bends
+fn segment_sample_count(window: &[na::Vector2<f32>; 4], tolerance: f32) -> u16 {
+    let [p1, c2, c3, p4] = window;
+    // the segment stays within its control points, so if they hug the chord, so does the segment
+    if distance_to_segment(*c2, *p1, *p4) <= tolerance && distance_to_segment(*c3, *p1, *p4) <= tolerance {
+        return 1;
+    }
+    let bend = (p1 - c2 * 2.0 + c3).norm().max((c2 - c3 * 2.0 + p4).norm());
+    let count = (0.75 * bend / tolerance).sqrt().ceil();
+    if count.is_nan() {
+        // no tolerance at all
+        Curve::MAX_SEGMENT_SAMPLES
+    } else {
+        (count as u16).clamp(1, Curve::MAX_SEGMENT_SAMPLES)
+    }
+}
+
+/// The position at `t` along the segment `mat`
+#[allow(non_snake_case)]
+fn segment_position(mat: &na::Matrix2x4<f32>, t: f32) -> na::Vector2<f32> {
+    const P_COEFS: na::Matrix4<f32> = na::Matrix4::new(
+        -1.0,  3.0, -3.0,  1.0,
+         3.0, -6.0,  3.0,  0.0,
+        -3.0,  3.0,  0.0,  0.0,
+         1.0,  0.0,  0.0,  0.0,
+    );
+    let T = na::Vector4::new(t*t*t, t*t, t, 1.0);
+    mat*P_COEFS*T
+}
+
 impl<I: SamplingHelper> Iterator for Positions<I> {
     type Item = (I::Item, na::Vector2<f32>);
 
-    #[allow(non_snake_case)]
     fn next(&mut self) -> Option<Self::Item> {
-        const P_COEFS: na::Matrix4<f32> = na::Matrix4::new(
-            -1.0,  3.0, -3.0,  1.0,
-             3.0, -6.0,  3.0,  0.0,
-            -3.0,  3.0,  0.0,  0.0,
-             1.0,  0.0,  0.0,  0.0,
-        );
         let item = self.iter.next()?;
         let (_, t) = I::item_sample(&item);
-        let T = na::Vector4::new(t*t*t, t*t, t, 1.0);
-        Some((item, (self.mat()*P_COEFS*T)))
+        let p = segment_position(self.iter.mat(), t);
+        Some((item, p))
     }
 
     #[inline]
@@ -302,8 +442,19 @@ impl<I: SamplingHelper> Iterator for Positions<I> {
     }
 }
 
+impl<I: SamplingHelper + DoubleEndedIterator> DoubleEndedIterator for Positions<I> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let item = self.iter.next_back()?;
+        let (_, t) = I::item_sample(&item);
+        let p = segment_position(self.iter.back_mat(), t);
+        Some((item, p))
+    }
+}
+
 impl<I: SamplingHelper> ExactSizeIterator for Positions<I> {}
 
+impl<I: SamplingHelper + std::iter::FusedIterator> std::iter::FusedIterator for Positions<I> {}
+
 impl<I: SamplingHelper> SamplingHelper for Positions<I> {
     const RES: u16 = I::RES;
     type Sampled = I::Sampled;
@@ -313,6 +464,11 @@ impl<I: SamplingHelper> SamplingHelper for Positions<I> {
         self.iter.mat()
     }
 
+    #[inline]
+    fn back_mat(&self) -> &na::Matrix2x4<f32> {
+        self.iter.back_mat()
+    }
+
     #[inline]
     fn item_sample(item: &Self::Item) -> <Self::Sampled as Iterator>::Item {
         I::item_sample(&item.0)
@@ -329,21 +485,27 @@ impl<I> Velocities<I> {
     }
 }
 
+/// The velocity at `t` along the segment `mat`
+#[allow(non_snake_case)]
+fn segment_velocity(mat: &na::Matrix2x4<f32>, t: f32) -> na::Vector2<f32> {
+    const V_COEFS: na::Matrix4x3<f32> = na::Matrix4x3::new(
+        -3.0,   6.0, -3.0,
+         9.0, -12.0,  3.0,
+        -9.0,   6.0,  0.0,
+         3.0,   0.0,  0.0,
+    );
+    let T = na::Vector3::new(t*t, t, 1.0);
+    mat*V_COEFS*T
+}
+
 impl<I: SamplingHelper> Iterator for Velocities<I> {
     type Item = (I::Item, na::Vector2<f32>);
 
-    #[allow(non_snake_case)]
     fn next(&mut self) -> Option<Self::Item> {
-        const V_COEFS: na::Matrix4x3<f32> = na::Matrix4x3::new(
-            -3.0,   6.0, -3.0,
-             9.0, -12.0,  3.0,
-            -9.0,   6.0,  0.0,
-             3.0,   0.0,  0.0,
-        );
         let item = self.iter.next()?;
         let (_, t) = I::item_sample(&item);
-        let T = na::Vector3::new(t*t, t, 1.0);
-        Some((item, self.mat()*V_COEFS*T))
+        let v = segment_velocity(self.iter.mat(), t);
+        Some((item, v))
     }
 
     #[inline]
@@ -352,6 +514,19 @@ impl<I: SamplingHelper> Iterator for Velocities<I> {
     }
 }
 
+impl<I: SamplingHelper + DoubleEndedIterator> DoubleEndedIterator for Velocities<I> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let item = self.iter.next_back()?;
+        let (_, t) = I::item_sample(&item);
+        let v = segment_velocity(self.iter.back_mat(), t);
+        Some((item, v))
+    }
+}
+
+impl<I: SamplingHelper> ExactSizeIterator for Velocities<I> {}
+
+impl<I: SamplingHelper + std::iter::FusedIterator> std::iter::FusedIterator for Velocities<I> {}
+
 impl<I: SamplingHelper> SamplingHelper for Velocities<I> {
     const RES: u16 = I::RES;
     type Sampled = I::Sampled;
@@ -361,6 +536,11 @@ impl<I: SamplingHelper> SamplingHelper for Velocities<I> {
         self.iter.mat()
     }
 
+    #[inline]
+    fn back_mat(&self) -> &na::Matrix2x4<f32> {
+        self.iter.back_mat()
+    }
+
     #[inline]
     fn item_sample(item: &Self::Item) -> <Self::Sampled as Iterator>::Item {
         I::item_sample(&item.0)
@@ -408,6 +588,15 @@ impl From<Rectangle> for Curve {
 }
 
 impl Curve {
+    /// Number of points per segment that [`Curve::simplified`] picks anchors from
+    pub const SIMPLIFY_RESOLUTION: u16 = 20;
+
+    /// Sharpest turn, in radians, that [`Curve::simplified`] smooths over; anchors turning more than this stay corners
+    pub const CORNER_ANGLE: f32 = std::f32::consts::FRAC_PI_6;
+
+    /// Most points [`Curve::sample_into`] takes along one segment, however small the tolerance
+    pub const MAX_SEGMENT_SAMPLES: u16 = 1024;
+
     pub const fn new() -> Self {
         Self {
             points: Vec::new(),
@@ -415,6 +604,23 @@ impl Curve {
         }
     }
 
+    /// Express the curve as SVG path data (the `d` attribute of a `<path>`), as absolute cubic segments
+    ///
+    /// [`crate::svg::parse_path_data`] reads it back into the same points, up to rounding
+    pub fn to_svg_path_data(&self) -> String {
+        use std::fmt::Write;
+        let mut d = String::new();
+        let Some(first) = self.points.first() else { return d };
+        _ = write!(d, "M{} {}", first.p.x, first.p.y);
+        for [_, c1, c2, p] in self.iter().spline().spline_windows() {
+            _ = write!(d, " C{} {} {} {} {} {}", c1.x, c1.y, c2.x, c2.y, p.x, p.y);
+        }
+        if self.is_closed {
+            d.push_str(" Z");
+        }
+        d
+    }
+
     /// Iterate over points in a [`Curve`]
     ///
     /// Includes the first point a second time,
@@ -457,12 +663,463 @@ impl Curve {
             .with_velocities()
             .map(|(((i, t), p), v)| (i, t, p, v))
     }
+
+    /// The number of bezier patches between consecutive points, including the closing patch of a closed curve
+    pub fn segment_count(&self) -> usize {
+        if self.is_closed { self.points.len() } else { self.points.len().saturating_sub(1) }
+    }
+
+    /// Move every point in the curve by `offset`
+    pub fn translate(&mut self, offset: na::Vector2<f32>) {
+        for point in &mut self.points {
+            point.p += offset;
+        }
+    }
+
+    /// Reverse the direction of the curve, keeping its shape
+    pub fn reverse(&mut self) {
+        self.points.reverse();
+        for point in &mut self.points {
+            std::mem::swap(&mut point.c_in, &mut point.c_out);
+        }
+    }
+
+    /// Rotate the curve `angle` radians (clockwise on screen) about `center`
+    pub fn rotate(&mut self, center: na::Vector2<f32>, angle: f32) {
+        let rotation = na::Rotation2::new(angle);
+        for point in &mut self.points {
+            point.p = center + rotation * (point.p - center);
+            point.c_in = rotation * point.c_in;
+            point.c_out = rotation * point.c_out;
+        }
+    }
+
+    /// Stretch every anchor away from `center` by `factor` along each axis, stretching the control points along with it
+    ///
+    /// A negative factor mirrors the curve across that axis
+    pub fn scale(&mut self, center: na::Vector2<f32>, factor: na::Vector2<f32>) {
+        for point in &mut self.points {
+            point.p = center + (point.p - center).component_mul(&factor);
+            point.c_in = point.c_in.component_mul(&factor);
+            point.c_out = point.c_out.component_mul(&factor);
+        }
+    }
+
+    /// Move every anchor to `round(anchor)`, carrying its control points along with it
+    pub fn round_anchors(&mut self, round: impl Fn(na::Vector2<f32>) -> na::Vector2<f32>) {
+        for point in &mut self.points {
+            point.p = round(point.p);
+        }
+    }
+
+    /// Get points along the curve, `RES` per segment, ending with the last anchor
+    /// (or the first anchor again, if the curve is closed)
+    pub fn polyline<const RES: u16>(&self) -> Vec<na::Vector2<f32>> {
+        let mut points = self.sampled_iter::<RES>()
+            .with_positions()
+            .map(|(_, p)| p)
+            .collect::<Vec<_>>();
+        let end = if self.is_closed { self.points.first() } else { self.points.last() };
+        points.extend(end.map(|point| point.p));
+        points
+    }
+
+    /// Append points along the curve to `positions`, close enough together that the curve strays
+    /// no more than `tolerance` from the lines between them
+    ///
+    /// Like [`Curve::polyline`], ends with the last anchor (or the first anchor again, if the curve is closed).
+    /// Segments are sampled straight from their control points rather than through the [sampling iterators][`Sampling`],
+    /// and each gets only as many points as its bend needs, so a straight segment gets one.
+    /// `positions` is only appended to, so it can be cleared and reused between frames
+    pub fn sample_into(&self, positions: &mut Vec<na::Vector2<f32>>, tolerance: f32) {
+        self.sample_segments(tolerance, |mat, t| positions.push(segment_position(mat, t)));
+    }
+
+    /// [`Curve::sample_into`], also appending the velocity at each point to `velocities`
+    pub fn sample_with_velocities_into(&self, positions: &mut Vec<na::Vector2<f32>>, velocities: &mut Vec<na::Vector2<f32>>, tolerance: f32) {
+        self.sample_segments(tolerance, |mat, t| {
+            positions.push(segment_position(mat, t));
+            velocities.push(segment_velocity(mat, t));
+        });
+    }
+
+    /// Call `sample` with each segment's control points and the t-values to sample it at, finishing at `t = 1` on the last segment
+    fn sample_segments(&self, tolerance: f32, mut sample: impl FnMut(&na::Matrix2x4<f32>, f32)) {
+        let mut last = None;
+        for window in self.iter().spline().spline_windows() {
+            let mat = na::Matrix2x4::from_columns(&window);
+            let count = segment_sample_count(&window, tolerance);
+            let step = 1.0 / f32::from(count);
+            for i in 0..count {
+                sample(&mat, f32::from(i) * step);
+            }
+            last = Some(mat);
+        }
+        match last {
+            Some(mat) => sample(&mat, 1.0),
+            // a lone point has no segments, but still marks its place
+            None => if let Some(point) = self.points.first() {
+                let mat = na::Matrix2x4::from_columns(&[point.p; 4]);
+                sample(&mat, 0.0);
+            },
+        }
+    }
+
+    /// Get `count` points spread evenly by distance along the curve, each with the unit direction of the curve there
+    ///
+    /// Distances are measured along points sampled `RES` times per segment. The points of an open curve
+    /// span from one end to the other, while those of a closed curve are spaced all the way around the loop.
+    /// Empty if the curve has no length
+    pub fn evenly_spaced<const RES: u16>(&self, count: usize) -> Vec<(na::Vector2<f32>, na::Vector2<f32>)> {
+        let polyline = self.polyline::<RES>();
+        let mut distances = Vec::with_capacity(polyline.len());
+        let mut total = 0.0;
+        distances.push(total);
+        for pair in polyline.windows(2) {
+            total += (pair[1] - pair[0]).norm();
+            distances.push(total);
+        }
+        if total <= f32::EPSILON {
+            return Vec::new();
+        }
+
+        let gaps = if self.is_closed { count } else { count.saturating_sub(1).max(1) };
+        let last = polyline.len().saturating_sub(1);
+        (0..count)
+            .map(|i| {
+                let distance = total * i as f32 / gaps as f32;
+                let next = distances.partition_point(|&d| d < distance).clamp(1, last);
+                let prev = next.saturating_sub(1);
+                let (start, end) = (polyline[prev], polyline[next]);
+                let span = distances[next] - distances[prev];
+                let t = if span > f32::EPSILON { (distance - distances[prev]) / span } else { 0.0 };
+                let direction = (end - start).try_normalize(f32::EPSILON).unwrap_or_else(na::Vector2::x);
+                (start + (end - start) * t, direction)
+            })
+            .collect()
+    }
+
+    /// Get the part of the curve between the fractions `start` and `end` of the way along it, as an open curve
+    ///
+    /// Fractions are measured in segments, like the [trim][`crate::style::StrokeTrim`] of a stroke, so `0.5` of a
+    /// curve with two segments is its middle anchor. On a closed curve `end` may go past `1.0` to continue around
+    /// the loop. Empty if `end` is not after `start`
+    pub fn portion(&self, start: f32, end: f32) -> Self {
+        let segments = self.iter().spline().spline_windows().collect::<Vec<_>>();
+        let count = segments.len();
+        if count == 0 || end <= start {
+            return Self::new();
+        }
+        let (start, end) = (start.max(0.0) * count as f32, end * count as f32);
+        let mut points = Vec::<CurvePoint>::new();
+        let mut i = start.floor() as usize;
+        while (i as f32) < end {
+            let index = if self.is_closed { i.checked_rem(count) } else { Some(i) };
+            let Some(&segment) = index.and_then(|index| segments.get(index)) else { break };
+            let from = (start - i as f32).max(0.0);
+            let to = (end - i as f32).min(1.0);
+            if to > from {
+                let [a, b, c, d] = split_segment(split_segment(segment, to).0, from / to).1;
+                match points.last_mut() {
+                    Some(last) => last.c_out = b - a,
+                    None => points.push(CurvePoint { c_in: na::Vector2::zeros(), p: a, c_out: b - a }),
+                }
+                points.push(CurvePoint { c_in: c - d, p: d, c_out: na::Vector2::zeros() });
+            }
+            i = i.saturating_add(1);
+        }
+        Self { points, is_closed: false }
+    }
+
+    /// The position and outward unit direction of each end of the curve, the way [markers][`crate::style::Marker`] point
+    ///
+    /// The start points backward along the curve and the end points forward; both ends of a closed curve are its
+    /// first anchor. Where a handle is retracted, the direction is taken toward the next control point that isn't.
+    /// [`None`] if the curve has no length
+    pub fn ends(&self) -> Option<[(na::Vector2<f32>, na::Vector2<f32>); 2]> {
+        let segments = self.iter().spline().spline_windows().collect::<Vec<_>>();
+        let (first, last) = (segments.first()?, segments.last()?);
+        let (start, end) = (first[0], last[3]);
+        let backward = [first[1], first[2], first[3]].into_iter().find_map(|p| (start - p).try_normalize(f32::EPSILON))?;
+        let forward = [last[2], last[1], last[0]].into_iter().find_map(|p| (end - p).try_normalize(f32::EPSILON))?;
+        Some([(start, backward), (end, forward)])
+    }
+
+    /// Construct a smooth curve with fewer anchors that follows this one
+    ///
+    /// Anchors are picked from points along the curve so that none of the points skipped is farther than
+    /// `tolerance` from the straight line between the anchors around it, then joined with Catmull-Rom handles.
+    /// Anchors where the curve turns by more than [`Curve::CORNER_ANGLE`] keep their corners, with retracted handles.
+    /// The handles round off the rest, so the result can stray slightly farther than `tolerance`
+    pub fn simplified(&self, tolerance: f32) -> Self {
+        let polyline = self.polyline::<{ Self::SIMPLIFY_RESOLUTION }>();
+        let mut indices = simplify_polyline(&polyline, tolerance);
+        // the polyline of a closed curve ends where it starts
+        let ring = if self.is_closed {
+            if indices.len() > 1 {
+                indices.pop();
+            }
+            &polyline[..polyline.len().saturating_sub(1)]
+        } else {
+            &polyline[..]
+        };
+        let anchors = indices.iter().map(|&i| polyline[i]).collect::<Vec<_>>();
+
+        let last = anchors.len().saturating_sub(1);
+        let points = anchors.iter()
+            .zip(&indices)
+            .enumerate()
+            .map(|(i, (&p, &index))| {
+                if turn_angle(ring, index, self.is_closed).is_some_and(|angle| angle > Self::CORNER_ANGLE) {
+                    return CurvePoint { c_in: na::Vector2::zeros(), p, c_out: na::Vector2::zeros() };
+                }
+                let (prev, next) = if self.is_closed {
+                    (anchors[i.checked_sub(1).unwrap_or(last)], anchors[if i == last { 0 } else { i.saturating_add(1) }])
+                } else {
+                    // the ends have nothing beyond them to curve toward
+                    (anchors[i.saturating_sub(1)], anchors[i.saturating_add(1).min(last)])
+                };
+                let tangent = (next - prev) / 6.0;
+                CurvePoint { c_in: -tangent, p, c_out: tangent }
+            })
+            .collect();
+        Self { points, is_closed: self.is_closed }
+    }
+
+    /// Get points along this curve, `RES` per segment, each paired with its distance from the nearest point of `original`
+    pub fn deviation_from<const RES: u16>(&self, original: &Curve) -> Vec<(na::Vector2<f32>, f32)> {
+        let target = original.polyline::<RES>();
+        self.polyline::<RES>()
+            .into_iter()
+            .map(|p| {
+                let distance = match target.as_slice() {
+                    [only] => (p - only).norm(),
+                    _ => target.windows(2)
+                        .map(|pair| distance_to_segment(p, pair[0], pair[1]))
+                        .fold(f32::INFINITY, f32::min),
+                };
+                (p, distance)
+            })
+            .collect()
+    }
+
+    /// Get the rectangle containing every anchor and control point
+    ///
+    /// A bezier curve never leaves the hull of its control points, so this always
+    /// contains the curve, though it may be larger than the tightest bounds
+    ///
+    /// [`None`] if the curve has no points
+    pub fn control_bounds(&self) -> Option<Rectangle> {
+        let (min, max) = self.iter()
+            .spline()
+            .fold(None, |bounds: Option<(na::Vector2<f32>, na::Vector2<f32>)>, p| Some(match bounds {
+                Some((min, max)) => (min.inf(&p), max.sup(&p)),
+                None => (p, p),
+            }))?;
+        Some(Rectangle::new(min.x, min.y, max.x - min.x, max.y - min.y))
+    }
+}
+
+/// Split the cubic bezier segment `[p0, c1, c2, p3]` at `t`, into the segments before and after it
+fn split_segment([p0, c1, c2, p3]: [na::Vector2<f32>; 4], t: f32) -> ([na::Vector2<f32>; 4], [na::Vector2<f32>; 4]) {
+    let lerp = |a: na::Vector2<f32>, b: na::Vector2<f32>| a + (b - a) * t;
+    let (a, b, c) = (lerp(p0, c1), lerp(c1, c2), lerp(c2, p3));
+    let (d, e) = (lerp(a, b), lerp(b, c));
+    let f = lerp(d, e);
+    ([p0, a, d, f], [f, e, c, p3])
+}
+
+/// Get the distance from `p` to the nearest point on the line segment from `a` to `b`
+pub fn distance_to_segment(p: na::Vector2<f32>, a: na::Vector2<f32>, b: na::Vector2<f32>) -> f32 {
+    let ab = b - a;
+    let length_squared = ab.norm_squared();
+    let t = if length_squared > f32::EPSILON { ((p - a).dot(&ab) / length_squared).clamp(0.0, 1.0) } else { 0.0 };
+    (a + ab * t - p).norm()
+}
+
+/// Get the angle, in radians, that `points` turns through at `points[i]`,
+/// measured between the nearest points on either side that aren't on top of it
+///
+/// `points` wraps around if `is_loop`. [`None`] at the ends of an open polyline, or if every point is in one place
+fn turn_angle(points: &[na::Vector2<f32>], i: usize, is_loop: bool) -> Option<f32> {
+    let p = *points.get(i)?;
+    let (head, tail) = (&points[..i], &points[i.saturating_add(1)..]);
+    let (wrap_head, wrap_tail): (&[_], &[_]) = if is_loop { (head, tail) } else { (&[], &[]) };
+    let incoming = head.iter().rev().chain(wrap_tail.iter().rev())
+        .find_map(|&q| (p - q).try_normalize(f32::EPSILON))?;
+    let outgoing = tail.iter().chain(wrap_head)
+        .find_map(|&q| (q - p).try_normalize(f32::EPSILON))?;
+    Some(incoming.dot(&outgoing).clamp(-1.0, 1.0).acos())
+}
+
+/// Get the indices of the `points` to keep so that none of the points dropped is farther than `tolerance`
+/// from the line between the kept points around it, using the Ramer-Douglas-Peucker algorithm
+///
+/// The first and last points are always kept
+pub fn simplify_polyline(points: &[na::Vector2<f32>], tolerance: f32) -> Vec<usize> {
+    let Some(last) = points.len().checked_sub(1) else { return Vec::new() };
+    let mut is_kept = vec![false; points.len()];
+    is_kept[0] = true;
+    is_kept[last] = true;
+    let mut spans = vec![(0, last)];
+    while let Some((start, end)) = spans.pop() {
+        let farthest = (start.saturating_add(1)..end)
+            .map(|i| (i, distance_to_segment(points[i], points[start], points[end])))
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+        if let Some((i, distance)) = farthest && distance > tolerance {
+            is_kept[i] = true;
+            spans.push((start, i));
+            spans.push((i, end));
+        }
+    }
+    is_kept.iter()
+        .enumerate()
+        .filter_map(|(i, &is_kept)| is_kept.then_some(i))
+        .collect()
+}
+
+/// Builds a [`Curve`] one anchor at a time, working out the handles that follow from the ones given
+///
+/// The runtime counterpart of [`make_curve!`]. Handles are relative to their anchors, as in [`CurvePoint`]
+#[derive(Debug, Clone, Default)]
+pub struct CurveBuilder {
+    points: Vec<CurvePoint>,
+    /// Which points get their handles from their neighbors once the curve is built
+    is_auto: Vec<bool>,
+}
+
+impl CurveBuilder {
+    /// Anchors closer than this to the first anchor close a [cycle][`CurveBuilder::cycle`] onto it
+    const CLOSING_DISTANCE: f32 = 1e-3;
+
+    pub const fn new() -> Self {
+        Self {
+            points: Vec::new(),
+            is_auto: Vec::new(),
+        }
+    }
+
+    /// The most recent anchor, which relative positions and [arcs][`CurveBuilder::arc`] start from
+    ///
+    /// The origin before any anchors are added
+    pub fn last_anchor(&self) -> na::Vector2<f32> {
+        self.points.last().map_or_else(na::Vector2::zeros, |point| point.p)
+    }
+
+    /// Add an anchor at `p` with the handles `c_in` and `c_out`
+    pub fn point(mut self, c_in: na::Vector2<f32>, p: na::Vector2<f32>, c_out: na::Vector2<f32>) -> Self {
+        self.points.push(CurvePoint { c_in, p, c_out });
+        self.is_auto.push(false);
+        self
+    }
+
+    /// Add an anchor at `p` without handles
+    pub fn corner(self, p: na::Vector2<f32>) -> Self {
+        self.point(na::Vector2::zeros(), p, na::Vector2::zeros())
+    }
+
+    /// Add an anchor at `p` with the outgoing handle `c_out`, mirrored for the incoming handle
+    pub fn smooth(self, p: na::Vector2<f32>, c_out: na::Vector2<f32>) -> Self {
+        self.point(-c_out, p, c_out)
+    }
+
+    /// Add an anchor at `p` whose handles run parallel to the line between its neighbors, a sixth as long,
+    /// so the curve passes through it smoothly (a Catmull-Rom spline)
+    ///
+    /// The handles are chosen once the curve is built, so they account for anchors added later
+    #[cfg_attr(not(test), allow(dead_code, reason = "shorthand for tests and make_curve!, which the application doesn't need yet"))]
+    pub fn auto(mut self, p: na::Vector2<f32>) -> Self {
+        self = self.corner(p);
+        if let Some(is_auto) = self.is_auto.last_mut() {
+            *is_auto = true;
+        }
+        self
+    }
+
+    /// Continue from the last anchor along an ellipse with `radii`, rotated by `rotation`,
+    /// from the angle `start_angle` around the ellipse to `end_angle` (in radians), like TikZ's `arc`
+    ///
+    /// The ellipse is placed so the angle `start_angle` lands on the last anchor, and the arc is
+    /// split into segments of at most 90 degrees, each ending on a new anchor
+    pub fn arc(mut self, start_angle: f32, end_angle: f32, radii: na::Vector2<f32>, rotation: f32) -> Self {
+        let rotation = na::Rotation2::new(rotation);
+        let position = |angle: f32| rotation * na::Vector2::new(radii.x * angle.cos(), radii.y * angle.sin());
+        let velocity = |angle: f32| rotation * na::Vector2::new(-radii.x * angle.sin(), radii.y * angle.cos());
+
+        if self.points.is_empty() {
+            self = self.corner(na::Vector2::zeros());
+        }
+        let center = self.last_anchor() - position(start_angle);
+        let sweep = end_angle - start_angle;
+        // a hair under 90 degrees, so rounding in the angles doesn't split a quarter turn in two
+        let count = (sweep.abs() / std::f32::consts::FRAC_PI_2 - 1e-4).ceil().max(1.0) as u16;
+        let step = sweep / f32::from(count);
+        // handle length of a unit circle arc spanning `step`
+        let k = 4.0 / 3.0 * (step / 4.0).tan();
+        for i in 0..count {
+            let from = start_angle + step * f32::from(i);
+            let to = from + step;
+            if let Some(last) = self.points.last_mut() {
+                last.c_out = velocity(from) * k;
+            }
+            self = self.point(-velocity(to) * k, center + position(to), na::Vector2::zeros());
+        }
+        self
+    }
+
+    /// Finish an open curve
+    pub fn build(self) -> Curve {
+        self.finish(false)
+    }
+
+    /// Finish a closed curve
+    ///
+    /// If the last anchor returned to the first, as an arc all the way around an ellipse does, the two are merged
+    pub fn cycle(self) -> Curve {
+        self.finish(true)
+    }
+
+    fn finish(mut self, is_closed: bool) -> Curve {
+        if is_closed
+            && self.points.len() > 1
+            && let (Some(first), Some(last)) = (self.points.first(), self.points.last())
+            && (last.p - first.p).norm() < Self::CLOSING_DISTANCE
+            && let Some(last) = self.points.pop()
+        {
+            self.is_auto.pop();
+            if let Some(first) = self.points.first_mut() {
+                first.c_in = last.c_in;
+            }
+        }
+
+        let anchors = self.points.iter().map(|point| point.p).collect::<Vec<_>>();
+        for (i, (point, &is_auto)) in self.points.iter_mut().zip(&self.is_auto).enumerate() {
+            if !is_auto {
+                continue;
+            }
+            let prev = match i.checked_sub(1) {
+                Some(prev) => anchors.get(prev),
+                None if is_closed => anchors.last(),
+                None => None,
+            }.copied().unwrap_or(point.p);
+            let next = match anchors.get(i.saturating_add(1)) {
+                Some(next) => Some(next),
+                None if is_closed => anchors.first(),
+                None => None,
+            }.copied().unwrap_or(point.p);
+            point.c_out = (next - prev) / 6.0;
+            point.c_in = -point.c_out;
+        }
+
+        Curve { points: self.points, is_closed }
+    }
 }
 
 /// Construct a [`CurvePoint`] using Tikz-inspired syntax
 ///
 /// - `(..., ...)` - Anchor point (mandatory)
 /// - `[..., ...]` - Velocity control (optional - defaults to 0,0)
+/// - `~[..., ...]` - Exit velocity control, mirrored for the entry (only valid after the anchor, without an entry control)
 ///
 /// # Example
 /// ```
@@ -478,6 +1135,9 @@ impl Curve {
 ///
 /// let pp = make_curve_point!([0,1] (2,3));
 /// assert_eq!(pp.c_out, na::Vector2::new(0.0, 0.0));
+///
+/// let pp = make_curve_point!((2,3) ~[4,5]);
+/// assert_eq!(pp.c_in, na::Vector2::new(-4.0, -5.0));
 /// ```
 #[macro_export]
 macro_rules! make_curve_point {
@@ -491,6 +1151,9 @@ macro_rules! make_curve_point {
     (($x:expr, $y:expr) [$x_out:expr, $y_out:expr]) => {
         $crate::make_curve_point!([0, 0] ($x, $y) [$x_out, $y_out])
     };
+    (($x:expr, $y:expr) ~[$x_out:expr, $y_out:expr]) => {
+        $crate::make_curve_point!([-($x_out as f32), -($y_out as f32)] ($x, $y) [$x_out, $y_out])
+    };
     ([$x_in:expr, $y_in:expr] ($x:expr, $y:expr)) => {
         $crate::make_curve_point!([$x_in, $y_in] ($x, $y) [0, 0])
     };
@@ -501,8 +1164,13 @@ macro_rules! make_curve_point {
 
 /// Construct a [`Curve`] using Tikz-inspired syntax
 ///
-/// - `(..., ...)` - Anchor point (mandatory)
+/// - `(..., ...)` - Anchor point
+/// - `++(..., ...)` - Anchor point, relative to the previous anchor
 /// - `[..., ...]` - Velocity control (optional - defaults to 0,0)
+/// - `~[..., ...]` - Exit velocity control, mirrored for the entry (only valid after the anchor, without an entry control)
+/// - `~(..., ...)`, `~++(..., ...)` - Anchor point with automatic smooth controls; see [`CurveBuilder::auto`]
+/// - `arc(start, end, radius)`, `arc(start, end, x radius, y radius)` - Elliptical arc continuing from the previous anchor,
+///   between angles in degrees; see [`CurveBuilder::arc`]
 /// - `->` - Separator between controls
 /// - `cycle` - Curve is a closed loop (only valid at end)
 ///
@@ -516,45 +1184,103 @@ macro_rules! make_curve_point {
 ///     make_curve_point!([12,13] (14,15) [16,17]),
 /// ]);
 /// assert!(!curve.is_closed);
+///
+/// # use crate::na;
+/// let curve = make_curve!((0,0) ~[5,0] -> ++(10,0) -> ~++(0,10) -> arc(0, 90, 5) -> cycle);
+/// assert_eq!(curve.points[1].p, na::Vector2::new(10.0, 0.0));
+/// assert!(curve.is_closed);
 /// ```
 #[macro_export]
 macro_rules! make_curve {
-    ($($([$x_in:expr, $y_in:expr])? ($x:expr, $y:expr) $([$x_out:expr, $y_out:expr])?)->* -> cycle) => {
-        $crate::curve::Curve {
-            points: vec![$(
-                $crate::make_curve_point!(
-                    $([$x_in, $y_in])?
-                    ($x, $y)
-                    $([$x_out, $y_out])?
-                ),
-            )*],
-            is_closed: true,
-        }
+    // after each element
+    (@next $builder:expr;) => {
+        $builder.build()
     };
-    ($($([$x_in:expr, $y_in:expr])? ($x:expr, $y:expr) $([$x_out:expr, $y_out:expr])?)->*) => {
-        $crate::curve::Curve {
-            points: vec![$(
-                $crate::make_curve_point!(
-                    $([$x_in, $y_in])?
-                    ($x, $y)
-                    $([$x_out, $y_out])?
-                ),
-            )*],
-            is_closed: false,
-        }
+    (@next $builder:expr; -> cycle) => {
+        $builder.cycle()
+    };
+    (@next $builder:expr; -> $($rest:tt)+) => {
+        $crate::make_curve!(@element $builder; $($rest)+)
+    };
+
+    // arcs
+    (@element $builder:expr; arc($start:expr, $end:expr, $r:expr) $($rest:tt)*) => {
+        $crate::make_curve!(@element $builder; arc($start, $end, $r, $r) $($rest)*)
+    };
+    (@element $builder:expr; arc($start:expr, $end:expr, $rx:expr, $ry:expr) $($rest:tt)*) => {
+        $crate::make_curve!(@next $builder.arc(
+            ($start as f32).to_radians(),
+            ($end as f32).to_radians(),
+            $crate::na::Vector2::new($rx as f32, $ry as f32),
+            0.0,
+        ); $($rest)*)
+    };
+
+    // anchors with automatic controls
+    (@element $builder:expr; ~++($x:expr, $y:expr) $($rest:tt)*) => {
+        $crate::make_curve!(@next {
+            let builder = $builder;
+            let p = builder.last_anchor() + $crate::na::Vector2::new($x as f32, $y as f32);
+            builder.auto(p)
+        }; $($rest)*)
+    };
+    (@element $builder:expr; ~($x:expr, $y:expr) $($rest:tt)*) => {
+        $crate::make_curve!(@next $builder.auto($crate::na::Vector2::new($x as f32, $y as f32)); $($rest)*)
+    };
+
+    // entry control
+    (@element $builder:expr; [$x_in:expr, $y_in:expr] $($rest:tt)+) => {
+        $crate::make_curve!(@anchor $builder; ($x_in, $y_in); $($rest)+)
+    };
+    (@element $builder:expr; $($rest:tt)+) => {
+        $crate::make_curve!(@anchor $builder; (0, 0); $($rest)+)
+    };
+
+    // anchor
+    (@anchor $builder:expr; $c_in:tt; ++($x:expr, $y:expr) $($rest:tt)*) => {
+        $crate::make_curve!(@exit {
+            let builder = $builder;
+            let p = builder.last_anchor() + $crate::na::Vector2::new($x as f32, $y as f32);
+            (builder, p)
+        }; $c_in; $($rest)*)
+    };
+    (@anchor $builder:expr; $c_in:tt; ($x:expr, $y:expr) $($rest:tt)*) => {
+        $crate::make_curve!(@exit ($builder, $crate::na::Vector2::new($x as f32, $y as f32)); $c_in; $($rest)*)
+    };
+
+    // exit control
+    (@exit $builder_p:expr; $c_in:tt; ~[$x_out:expr, $y_out:expr] $($rest:tt)*) => {
+        $crate::make_curve!(@next {
+            let (builder, p) = $builder_p;
+            builder.smooth(p, $crate::na::Vector2::new($x_out as f32, $y_out as f32))
+        }; $($rest)*)
+    };
+    (@exit $builder_p:expr; ($x_in:expr, $y_in:expr); [$x_out:expr, $y_out:expr] $($rest:tt)*) => {
+        $crate::make_curve!(@next {
+            let (builder, p) = $builder_p;
+            builder.point(
+                $crate::na::Vector2::new($x_in as f32, $y_in as f32),
+                p,
+                $crate::na::Vector2::new($x_out as f32, $y_out as f32),
+            )
+        }; $($rest)*)
+    };
+    (@exit $builder_p:expr; ($x_in:expr, $y_in:expr); $($rest:tt)*) => {
+        $crate::make_curve!(@next {
+            let (builder, p) = $builder_p;
+            builder.point($crate::na::Vector2::new($x_in as f32, $y_in as f32), p, $crate::na::Vector2::zeros())
+        }; $($rest)*)
+    };
+
+    ($($body:tt)+) => {
+        $crate::make_curve!(@element $crate::curve::CurveBuilder::new(); $($body)+)
     };
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::make_curve;
-
-    /// Ensure only one visual test can be open at a time
-    fn rl_lock() -> std::sync::MutexGuard<'static, ()> {
-        static RL_MUX: std::sync::Mutex<()> = std::sync::Mutex::new(());
-        RL_MUX.lock().unwrap_or_else(|x| { RL_MUX.clear_poison(); x.into_inner() })
-    }
+    use crate::{golden, make_curve};
 
     macro_rules! vector_arr {
         ($(($x:expr,$y:expr)),* $(,)?) => {
@@ -695,131 +1421,281 @@ mod test {
     }
 
     #[test]
-    fn test_positions_iter_vis() {
+    fn test_velocities_iter() {
         const RES: u16 = 40;
 
-        let mut success = false;
-        {
-            let _lock = rl_lock();
-            let (mut rl, thread) = init()
-                .title("test_positions_iter_vis")
-                .build();
-            rl.set_target_fps(60);
-            let curve = make_curve!([-50,0](60,300)[50,0]->[-50,0](320,100)[50,0]->[-50,0](580,300)[50,0]);
-            let mut positions_actual = Vec::new();
-            while !rl.window_should_close() {
-
-                positions_actual.clear();
-                for (_, p) in curve.iter().spline().spline_windows().sampled::<RES>().with_positions() {
-                    positions_actual.push(Vector2::from(p));
-                }
+        let curve = make_curve!([0,1](2,3)[4,5]->[6,7](8,9)[10,11]->[12,13](14,15)[16,17]);
+        let expected_count = (curve.points.len() - 1) * RES as usize;
 
-                let mut d = rl.begin_drawing(&thread);
-                d.clear_background(Color::RAYWHITE);
-
-                // draw expected
-                d.draw_spline_segment_bezier_cubic(
-                    Vector2::from(curve.points[0].p),
-                    Vector2::from(curve.points[0].p + curve.points[0].c_out),
-                    Vector2::from(curve.points[1].p + curve.points[1].c_in),
-                    Vector2::from(curve.points[1].p),
-                    5.0,
-                    Color::GREEN.alpha(0.5),
-                );
-                d.draw_spline_segment_bezier_cubic(
-                    Vector2::from(curve.points[1].p),
-                    Vector2::from(curve.points[1].p + curve.points[1].c_out),
-                    Vector2::from(curve.points[2].p + curve.points[2].c_in),
-                    Vector2::from(curve.points[2].p),
-                    5.0,
-                    Color::GREEN.alpha(0.5),
-                );
-
-                // draw actual
-                d.draw_line_strip(&positions_actual[..], Color::MAGENTA);
-
-                for point in &curve.points {
-                    let p = Vector2::from(point.p);
-                    let p_in = Vector2::from(point.p + point.c_in);
-                    let p_out = Vector2::from(point.p + point.c_out);
-                    d.draw_line_v(p, p_in, Color::GRAY.alpha(0.5));
-                    d.draw_line_v(p, p_out, Color::GRAY.alpha(0.5));
-                    d.draw_ring(p, 9.0, 11.0, 0.0, 360.0, 30, Color::RED);
-                    d.draw_ring(p_in,  4.0, 6.0, 0.0, 360.0, 20, Color::BLUE);
-                    d.draw_ring(p_out, 4.0, 6.0, 0.0, 360.0, 20, Color::BLUE);
-                }
+        let mut iter = curve.sampled_iter::<RES>().with_positions().with_velocities();
+        assert_eq!(iter.len(), expected_count);
 
-                d.draw_rectangle_rec(Rectangle::new(2.0, 2.0, 21.0, 21.0), if success { Color::GREEN } else { Color::RED }.alpha(0.5));
-                d.gui_check_box(Rectangle::new(5.0, 5.0, 15.0, 15.0), None, &mut success);
-            }
+        _ = iter.next();
+        _ = iter.next_back();
+        assert_eq!(iter.len(), expected_count - 2);
+    }
+
+    #[test]
+    fn test_reversed_iters() {
+        const RES: u16 = 4;
+
+        let curve = make_curve!([0,1](2,3)[4,5]->[6,7](8,9)[10,11]->[12,13](14,15)[16,17]->cycle);
+
+        let mut forward = curve.iter().spline().collect::<Vec<_>>();
+        forward.reverse();
+        assert_eq!(curve.iter().spline().rev().collect::<Vec<_>>(), forward);
+
+        let mut forward = curve.iter().spline().spline_windows().collect::<Vec<_>>();
+        forward.reverse();
+        assert_eq!(curve.iter().spline().spline_windows().rev().collect::<Vec<_>>(), forward);
+
+        let mut forward = curve.pos_vel_iter::<RES>().collect::<Vec<_>>();
+        forward.reverse();
+        let backward = curve.sampled_iter::<RES>().with_positions().with_velocities().rev()
+            .map(|(((i, t), p), v)| (i, t, p, v))
+            .collect::<Vec<_>>();
+        assert_eq!(backward, forward);
+    }
+
+    #[test]
+    fn test_sampled_iter_both_ends() {
+        const RES: u16 = 3;
+
+        let curve = make_curve!([0,1](2,3)[4,5]->[6,7](8,9)[10,11]->[12,13](14,15)[16,17]);
+        let forward = curve.sampled_iter::<RES>().with_positions().collect::<Vec<_>>();
+
+        // alternating ends meets in the middle without skipping or repeating samples
+        let mut iter = curve.sampled_iter::<RES>().with_positions();
+        let mut front = Vec::new();
+        let mut back = Vec::new();
+        while let Some(item) = iter.next() {
+            front.push(item);
+            back.extend(iter.next_back());
+        }
+        assert!(iter.next().is_none() && iter.next_back().is_none());
+        back.reverse();
+        front.append(&mut back);
+        assert_eq!(front, forward);
+    }
+
+    #[test]
+    fn test_sample_into() {
+        let line = make_curve!((0,0)->(10,0)->(10,10));
+        let mut positions = Vec::new();
+        line.sample_into(&mut positions, 0.1);
+        assert_eq!(&positions[..], &vector_arr![(0,0),(10,0),(10,10)]);
+
+        // reusing the buffer appends
+        line.sample_into(&mut positions, 0.1);
+        assert_eq!(positions.len(), 6);
+
+        let curve = make_curve!([0,0](0,0)[0,20]->[0,0](30,10)[0,0]->cycle);
+        let mut positions = Vec::new();
+        let mut velocities = Vec::new();
+        curve.sample_with_velocities_into(&mut positions, &mut velocities, 0.05);
+        assert_eq!(positions.len(), velocities.len());
+        assert_eq!(positions.first(), positions.last());
+
+        // every finely sampled point is near the coarse polyline
+        for p in curve.polyline::<200>() {
+            let distance = positions.windows(2)
+                .map(|pair| distance_to_segment(p, pair[0], pair[1]))
+                .fold(f32::INFINITY, f32::min);
+            assert!(distance <= 0.05 + 1e-4, "{p} is {distance} away");
+        }
+    }
+
+    #[test]
+    fn test_svg_path_data() {
+        let curve = make_curve!([0,1](2,3)[4,5]->[6,7](8,9)[10,11]->[12,13](14,15)[16,17]);
+        assert_eq!(curve.to_svg_path_data(), "M2 3 C6 8 14 16 8 9 C18 20 26 28 14 15");
+        assert_eq!(Curve::new().to_svg_path_data(), "");
+    }
+
+    #[test]
+    fn test_make_curve_shorthand() {
+        let curve = make_curve!((0,0) ~[5,0] -> ++(10,0) -> [1,2] ++(0,10) [3,4] -> cycle);
+        assert_eq!(curve.points, [
+            make_curve_point!([-5,0] (0,0) [5,0]),
+            make_curve_point!((10,0)),
+            make_curve_point!([1,2] (10,10) [3,4]),
+        ]);
+        assert!(curve.is_closed);
+
+        // automatic controls follow the line between the neighbors
+        let curve = make_curve!((0,0) -> ~(6,6) -> (12,0));
+        assert_eq!(curve.points[1], make_curve_point!([-2,0] (6,6) [2,0]));
+        let curve = make_curve!((0,0) -> ~++(6,6) -> ++(6,-6));
+        assert_eq!(curve.points[1], make_curve_point!([-2,0] (6,6) [2,0]));
+    }
+
+    #[test]
+    fn test_curve_builder_arc() {
+        // a full circle closes onto its start, in quarter turns
+        let circle = make_curve!((10,0) -> arc(0, 360, 10) -> cycle);
+        assert_eq!(circle.points.len(), 4);
+        for (point, expected) in circle.points.iter().zip(vector_arr![(10,0),(0,10),(-10,0),(0,-10)]) {
+            assert!((point.p - expected).norm() < 1e-4, "{} should be {expected}", point.p);
+            assert!((point.c_out.norm() - 10.0 * 0.552_284_8).abs() < 1e-3);
+            assert!((point.c_in + point.c_out).norm() < 1e-4);
+        }
+
+        // every sample stays on the ellipse
+        let arc = CurveBuilder::new()
+            .corner(na::Vector2::new(20.0, 0.0))
+            .arc(0.0, -2.0, na::Vector2::new(20.0, 10.0), 0.0)
+            .build();
+        for p in arc.polyline::<20>() {
+            let radius = (p.x / 20.0).hypot(p.y / 10.0);
+            assert!((radius - 1.0).abs() < 1e-3, "{p} is off the ellipse");
+        }
+    }
+
+    #[test]
+    fn test_simplified() {
+        let curve = make_curve!((0,0)->(1,0)->(2,0)->(3,0)->(3,1)->(3,2));
+        let simplified = curve.simplified(0.1);
+        let anchors = simplified.points.iter().map(|point| point.p).collect::<Vec<_>>();
+        assert_eq!(&anchors[..], &vector_arr![(0,0),(3,0),(3,2)]);
+
+        // the corner stays sharp, and the ends stay on the original
+        assert!(simplified.points[1].c_in == na::Vector2::zeros() && simplified.points[1].c_out == na::Vector2::zeros());
+        let deviation = simplified.deviation_from::<10>(&curve);
+        assert!(deviation.first().is_some_and(|&(_, distance)| distance < 1e-4));
+        assert!(deviation.last().is_some_and(|&(_, distance)| distance < 1e-4));
+        assert!(deviation.iter().all(|&(_, distance)| distance < 0.1));
+    }
+
+    #[test]
+    fn test_simplified_keeps_corners() {
+        let rectangle = Curve::from(Rectangle { x: 0.0, y: 0.0, width: 100.0, height: 50.0 });
+        let simplified = rectangle.simplified(0.5);
+        assert!(simplified.is_closed);
+        let anchors = simplified.points.iter().map(|point| point.p).collect::<Vec<_>>();
+        assert_eq!(&anchors[..], &vector_arr![(0,0),(100,0),(100,50),(0,50)]);
+        assert!(simplified.points.iter().all(|point| point.c_in == na::Vector2::zeros() && point.c_out == na::Vector2::zeros()));
+        let deviation = simplified.deviation_from::<10>(&rectangle);
+        assert!(deviation.iter().all(|&(_, distance)| distance < 1e-3));
+
+        // a smooth curve turns too gently anywhere to get corners
+        let arc = make_curve!((10,0) -> arc(0, 90, 10));
+        let simplified = arc.simplified(0.01);
+        assert!(simplified.points.len() > 2);
+        assert!(simplified.points.iter().all(|point| point.c_in != na::Vector2::zeros() || point.c_out != na::Vector2::zeros()));
+    }
+
+    #[test]
+    fn test_portion() {
+        let line = make_curve!((0,0)->(10,0)->(10,10));
+        let anchors = |curve: &Curve| curve.points.iter().map(|point| point.p).collect::<Vec<_>>();
+        assert_eq!(anchors(&line.portion(0.0, 1.0)), anchors(&line));
+        assert_eq!(&anchors(&line.portion(0.25, 0.75))[..], &vector_arr![(5,0),(10,0),(10,5)]);
+        assert!(line.portion(0.5, 0.5).points.is_empty());
+        // an open curve ends at its last anchor
+        assert_eq!(&anchors(&line.portion(0.75, 1.5))[..], &vector_arr![(10,5),(10,10)]);
+
+        // a portion of a closed curve can continue past its first anchor
+        let square = make_curve!((0,0)->(10,0)->(10,10)->(0,10)->cycle);
+        let wrapped = square.portion(0.875, 1.125);
+        assert!(!wrapped.is_closed);
+        assert_eq!(&anchors(&wrapped)[..], &vector_arr![(0,5),(0,0),(5,0)]);
+
+        // the part of a curved segment stays on the curve
+        let arc = make_curve!((10,0) -> arc(0, 90, 10));
+        for p in arc.portion(0.25, 0.75).polyline::<10>() {
+            assert!((p.norm() - 10.0).abs() < 0.05, "{p} is off the arc");
+        }
+    }
+
+    #[test]
+    fn test_ends() {
+        let line = make_curve!((0,0)->(10,0)->(10,10));
+        assert_eq!(line.ends(), Some([(na::Vector2::new(0.0, 0.0), na::Vector2::new(-1.0, 0.0)), (na::Vector2::new(10.0, 10.0), na::Vector2::new(0.0, 1.0))]));
+        // a handle pointing along the curve sets the direction
+        let curve = make_curve!((0,0) [0,5] -> (10,0));
+        assert_eq!(curve.ends().map(|[(_, start), _]| start), Some(na::Vector2::new(0.0, -1.0)));
+        assert_eq!(make_curve!((5,5)).ends(), None);
+    }
+
+    #[test]
+    fn test_evenly_spaced() {
+        let line = make_curve!((0,0)->(10,0));
+        let spaced = line.evenly_spaced::<10>(3);
+        assert_eq!(spaced.len(), 3);
+        for (&(point, direction), expected) in spaced.iter().zip(vector_arr![(0,0),(5,0),(10,0)]) {
+            assert!((point - expected).norm() < 1e-4);
+            assert!((direction - na::Vector2::x()).norm() < 1e-4);
+        }
+
+        // a closed square has its points spaced around all four sides, back to where it started
+        let square = make_curve!((0,0)->(4,0)->(4,4)->(0,4)->cycle);
+        let spaced = square.evenly_spaced::<10>(8);
+        assert_eq!(spaced.len(), 8);
+        for (&(point, _), expected) in spaced.iter().zip(vector_arr![(0,0),(2,0),(4,0),(4,2),(4,4),(2,4),(0,4),(0,2)]) {
+            assert!((point - expected).norm() < 1e-3);
+        }
+        assert!(make_curve!((1,1)).evenly_spaced::<10>(3).is_empty());
+    }
+
+    /// Draw the bezier segments of `curve` and its handles, for comparing samples drawn over them
+    fn draw_expected(d: &mut impl RaylibDraw, curve: &Curve) {
+        d.clear_background(Color::RAYWHITE);
+        for (a, b) in curve.points.iter().zip(curve.points.iter().skip(1)) {
+            d.draw_spline_segment_bezier_cubic(
+                Vector2::from(a.p),
+                Vector2::from(a.p + a.c_out),
+                Vector2::from(b.p + b.c_in),
+                Vector2::from(b.p),
+                5.0,
+                Color::GREEN.alpha(0.5),
+            );
+        }
+        for point in &curve.points {
+            let p = Vector2::from(point.p);
+            let p_in = Vector2::from(point.p + point.c_in);
+            let p_out = Vector2::from(point.p + point.c_out);
+            d.draw_line_v(p, p_in, Color::GRAY.alpha(0.5));
+            d.draw_line_v(p, p_out, Color::GRAY.alpha(0.5));
+            d.draw_ring(p, 9.0, 11.0, 0.0, 360.0, 30, Color::RED);
+            d.draw_ring(p_in,  4.0, 6.0, 0.0, 360.0, 20, Color::BLUE);
+            d.draw_ring(p_out, 4.0, 6.0, 0.0, 360.0, 20, Color::BLUE);
         }
-        assert!(success, "test failed");
     }
 
     #[test]
-    fn test_velocities_iter_vis() {
+    #[ignore = "renders with the GPU against tests/golden"]
+    fn test_positions_iter_golden() {
         const RES: u16 = 40;
 
-        let mut success = false;
-        {
-            let _lock = rl_lock();
-            let (mut rl, thread) = init()
-                .title("test_velocities_iter_vis")
-                .build();
-            rl.set_target_fps(60);
-            let curve = make_curve!([-50,0](60,300)[50,0]->[-50,0](320,100)[50,0]->[-50,0](580,300)[50,0]);
-            let mut positions_actual = Vec::new();
-            let mut velocities_actual = Vec::new();
-            while !rl.window_should_close() {
-                positions_actual.clear();
-                velocities_actual.clear();
-                for ((_, p), v) in curve.iter().spline().spline_windows().sampled::<RES>().with_positions().with_velocities() {
-                    positions_actual.push(Vector2::from(p));
-                    velocities_actual.push(Vector2::from(v));
-                }
+        let curve = make_curve!([-50,0](60,300)[50,0]->[-50,0](320,100)[50,0]->[-50,0](580,300)[50,0]);
+        let positions_actual = curve.iter().spline().spline_windows().sampled::<RES>().with_positions()
+            .map(|(_, p)| Vector2::from(p))
+            .collect::<Vec<_>>();
 
-                let mut d = rl.begin_drawing(&thread);
-                d.clear_background(Color::RAYWHITE);
-
-                // draw expected
-                d.draw_spline_segment_bezier_cubic(
-                    Vector2::from(curve.points[0].p),
-                    Vector2::from(curve.points[0].p + curve.points[0].c_out),
-                    Vector2::from(curve.points[1].p + curve.points[1].c_in),
-                    Vector2::from(curve.points[1].p),
-                    5.0,
-                    Color::GREEN.alpha(0.5),
-                );
-                d.draw_spline_segment_bezier_cubic(
-                    Vector2::from(curve.points[1].p),
-                    Vector2::from(curve.points[1].p + curve.points[1].c_out),
-                    Vector2::from(curve.points[2].p + curve.points[2].c_in),
-                    Vector2::from(curve.points[2].p),
-                    5.0,
-                    Color::GREEN.alpha(0.5),
-                );
-
-                // draw actual
-                d.draw_line_strip(&positions_actual[..], Color::MAGENTA);
-                for (p, v) in positions_actual.iter().zip(velocities_actual.iter()) {
-                    d.draw_line_v(p, *p + *v, Color::ORANGE);
-                }
+        let image = golden::render(640, 400, |d| {
+            draw_expected(d, &curve);
+            d.draw_line_strip(&positions_actual[..], Color::MAGENTA);
+        }).unwrap();
+        golden::assert_matches("positions_iter", &image, golden::Tolerance::default());
+    }
 
-                for point in &curve.points {
-                    let p = Vector2::from(point.p);
-                    let p_in = Vector2::from(point.p + point.c_in);
-                    let p_out = Vector2::from(point.p + point.c_out);
-                    d.draw_line_v(p, p_in, Color::GRAY.alpha(0.5));
-                    d.draw_line_v(p, p_out, Color::GRAY.alpha(0.5));
-                    d.draw_ring(p, 9.0, 11.0, 0.0, 360.0, 30, Color::RED);
-                    d.draw_ring(p_in,  4.0, 6.0, 0.0, 360.0, 20, Color::BLUE);
-                    d.draw_ring(p_out, 4.0, 6.0, 0.0, 360.0, 20, Color::BLUE);
-                }
+    #[test]
+    #[ignore = "renders with the GPU against tests/golden"]
+    fn test_velocities_iter_golden() {
+        const RES: u16 = 40;
 
-                d.draw_rectangle_rec(Rectangle::new(2.0, 2.0, 21.0, 21.0), if success { Color::GREEN } else { Color::RED }.alpha(0.5));
-                d.gui_check_box(Rectangle::new(5.0, 5.0, 15.0, 15.0), None, &mut success);
+        let curve = make_curve!([-50,0](60,300)[50,0]->[-50,0](320,100)[50,0]->[-50,0](580,300)[50,0]);
+        let (positions_actual, velocities_actual): (Vec<_>, Vec<_>) = curve.iter().spline().spline_windows().sampled::<RES>().with_positions().with_velocities()
+            .map(|((_, p), v)| (Vector2::from(p), Vector2::from(v)))
+            .unzip();
+
+        let image = golden::render(640, 400, |d| {
+            draw_expected(d, &curve);
+            d.draw_line_strip(&positions_actual[..], Color::MAGENTA);
+            for (p, v) in positions_actual.iter().zip(velocities_actual.iter()) {
+                d.draw_line_v(p, *p + *v, Color::ORANGE);
             }
-        }
-        assert!(success, "test failed");
+        }).unwrap();
+        golden::assert_matches("velocities_iter", &image, golden::Tolerance::default());
     }
 }
@@ -0,0 +1,2959 @@
+use std::{collections::HashMap, ffi::CString, hash::{DefaultHasher, Hash, Hasher}};
+use raylib::prelude::*;
+use crate::{animation::Animation, annotation::{Annotation, AnnotationKind}, command::{self, EditorAction}, document::{self, ArtboardAlignment, ArtboardRename, Document, DocumentInfo}, editor::{Editor, Selection}, engine::EngineTheme, export::{AssetExport, ExportPreset, ImageFormat}, fill::FillRule, library, locale::{self, tr, tr_c, tr_format, tr_list}, ui::{number_field::NumberSpec, text_field::TextField, widgets::{ListRows, Ui, WidgetId}}, layer::{Layer, LayerContent, SampleQuality}, snapshot::DocumentSnapshot, palette::PaletteFormat, raster::RasterStore, preferences::{AccessibilityPreferences, DisplayPreferences, Keymap, NavigationPreferences, NewObjectStyle, Preferences, TransformPreferences}, repeat::GridRepeatOptions, scatter::ScatterOptions, style::{Marker, MarkerShape, Pattern, Style, TextureWrap, WeakStyle, WidthProfile, WidthProfileControl, WidthProfileVertex}, swatch::{Cmyk, Swatch, SwatchColor}, units::{PixelSnap, Unit}, width_preset::WidthPreset};
+
+/// Copy `text` into a nul-terminated buffer of `capacity` bytes for [`RaylibDrawGui::gui_text_box`]
+///
+/// Text that doesn't fit is truncated
+fn text_buffer(text: &str, capacity: usize) -> Vec<u8> {
+    let mut buffer = text.as_bytes()[..text.len().min(capacity.saturating_sub(1))].to_vec();
+    buffer.resize(capacity, 0);
+    buffer
+}
+
+/// Read the text out of a nul-terminated buffer
+fn buffer_text(buffer: &[u8]) -> String {
+    let end = buffer.iter().position(|&b| b == 0).unwrap_or(buffer.len());
+    String::from_utf8_lossy(&buffer[..end]).into_owned()
+}
+
+/// Format seconds since the Unix epoch as `YYYY-MM-DD HH:MM UTC`
+fn format_timestamp(seconds: u64) -> String {
+    let [year, month, day, hour, minute, _] = document::utc_date_time(seconds);
+    format!("{year:04}-{month:02}-{day:02} {hour:02}:{minute:02} UTC")
+}
+
+/// Lays out rows of labeled controls from the top of a dialog window
+struct RowLayout {
+    bounds: Rectangle,
+    y: f32,
+}
+
+impl RowLayout {
+    /// Height of each row of controls
+    const ROW_HEIGHT: f32 = 24.0;
+
+    /// Padding between the edge of the window and its controls, and between rows
+    const PADDING: f32 = 8.0;
+
+    /// Width of the labels to the left of each control
+    const LABEL_WIDTH: f32 = 120.0;
+
+    /// Start laying out rows below the title bar of the window at `bounds`
+    fn new(bounds: Rectangle) -> Self {
+        Self {
+            bounds,
+            y: bounds.y + Self::ROW_HEIGHT + Self::PADDING,
+        }
+    }
+
+    /// Get the rectangle of the next row, spanning the width of the window
+    fn next_full_row(&mut self) -> Rectangle {
+        let row = Rectangle::new(self.bounds.x + Self::PADDING, self.y, self.bounds.width - Self::PADDING * 2.0, Self::ROW_HEIGHT);
+        self.y += Self::ROW_HEIGHT + Self::PADDING * 0.5;
+        row
+    }
+
+    /// Get the rectangle of the next `count` rows merged into one, spanning the width of the window
+    fn next_rows(&mut self, count: usize) -> Rectangle {
+        let first = self.next_full_row();
+        for _ in 1..count {
+            self.next_full_row();
+        }
+        Rectangle { height: self.y - Self::PADDING * 0.5 - first.y, ..first }
+    }
+
+    /// Get the label and control rectangles of the next row
+    fn next_row(&mut self) -> (Rectangle, Rectangle) {
+        let row = self.next_full_row();
+        let label = Rectangle { width: Self::LABEL_WIDTH, ..row };
+        let control = Rectangle { x: row.x + Self::LABEL_WIDTH, width: row.width - Self::LABEL_WIDTH, ..row };
+        (label, control)
+    }
+
+    /// The height of a window fitting `rows` rows, plus the title bar and the [footer][`dialog_footer`]
+    const fn window_height(rows: usize) -> f32 {
+        Self::ROW_HEIGHT + Self::PADDING + (Self::ROW_HEIGHT + Self::PADDING * 0.5) * rows as f32 + Self::PADDING + Self::ROW_HEIGHT + Self::PADDING
+    }
+}
+
+/// Width raygui reserves to the right of a color picker for its hue bar
+const COLOR_PICKER_HUE_BAR_WIDTH: f32 = 28.0;
+
+/// The labels of [`ImageFormat::ALL`], for toggle groups
+const IMAGE_FORMAT_NAMES: &std::ffi::CStr = c"PNG;QOI;BMP;TGA;ORA;SVG;PDF";
+
+/// Get the rectangle of a dialog of the given size, centered in a window of the given size
+fn centered_bounds(window_width: f32, window_height: f32, width: f32, height: f32) -> Rectangle {
+    Rectangle::new(
+        ((window_width - width) * 0.5).round(),
+        ((window_height - height) * 0.5).round(),
+        width,
+        height,
+    )
+}
+
+/// Draw the Cancel and OK buttons along the bottom of a dialog window
+///
+/// Returns [`Some`] if either was clicked
+fn dialog_footer(d: &mut impl RaylibDraw, bounds: Rectangle) -> Option<DialogResult> {
+    let (padding, height) = (RowLayout::PADDING, RowLayout::ROW_HEIGHT);
+    let button_width = (bounds.width - padding * 3.0) * 0.5;
+    let button_y = bounds.y + bounds.height - padding - height;
+    if d.gui_button(Rectangle::new(bounds.x + padding, button_y, button_width, height), Some(tr_c("dialog.cancel").as_c_str())) {
+        return Some(DialogResult::Cancel);
+    }
+    if d.gui_button(Rectangle::new(bounds.x + padding * 2.0 + button_width, button_y, button_width, height), Some(tr_c("dialog.ok").as_c_str())) {
+        return Some(DialogResult::Confirm);
+    }
+    None
+}
+
+/// Toggle `editing` between `field` and [`None`]
+///
+/// Used when a raygui text or value box is clicked, which is how they enter and leave edit mode
+fn toggle_editing<T: PartialEq>(editing: &mut Option<T>, field: T) {
+    *editing = if editing.as_ref() == Some(&field) { None } else { Some(field) };
+}
+
+/// A field of the [`DocumentInfoDialog`] that accepts keyboard input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DocumentInfoField {
+    Author,
+    Description,
+    Tags,
+    Dpi,
+}
+
+/// How a dialog was closed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DialogResult {
+    /// The changes should be applied
+    Confirm,
+
+    /// The changes should be discarded
+    Cancel,
+}
+
+/// The "Document Info" dialog, for editing a document's [`DocumentInfo`]
+///
+/// Edits are made to a copy and only written back by [`DocumentInfoDialog::apply`]
+#[derive(Debug)]
+pub struct DocumentInfoDialog {
+    author: Vec<u8>,
+    description: Vec<u8>,
+    /// Comma-separated
+    tags: Vec<u8>,
+    units: i32,
+    dpi: i32,
+    pixel_snap: i32,
+    is_aligning_strokes: bool,
+    created: Option<u64>,
+    modified: Option<u64>,
+    editing: Option<DocumentInfoField>,
+}
+
+impl DocumentInfoDialog {
+    /// Width of the dialog window
+    pub const WIDTH: f32 = 360.0;
+
+    /// Height of the dialog window
+    pub const HEIGHT: f32 = RowLayout::window_height(9);
+
+    /// Maximum length in bytes of each text field
+    const TEXT_CAPACITY: usize = 256;
+
+    /// Construct a dialog showing `info`
+    pub fn new(info: &DocumentInfo) -> Self {
+        Self {
+            author: text_buffer(&info.author, Self::TEXT_CAPACITY),
+            description: text_buffer(&info.description, Self::TEXT_CAPACITY),
+            tags: text_buffer(&info.tags.join(", "), Self::TEXT_CAPACITY),
+            units: Unit::ALL.iter().position(|&unit| unit == info.units).unwrap_or(0) as i32,
+            dpi: info.dpi.round() as i32,
+            pixel_snap: PixelSnap::ALL.iter().position(|&snap| snap == info.pixel_snap).unwrap_or(0) as i32,
+            is_aligning_strokes: info.is_aligning_strokes,
+            created: info.created,
+            modified: info.modified,
+            editing: None,
+        }
+    }
+
+    /// The rectangle of the dialog when centered in a window of the given size
+    pub fn bounds(window_width: f32, window_height: f32) -> Rectangle {
+        centered_bounds(window_width, window_height, Self::WIDTH, Self::HEIGHT)
+    }
+
+    /// Whether a text field is capturing keyboard input
+    #[inline]
+    pub fn is_editing(&self) -> bool {
+        self.editing.is_some()
+    }
+
+    /// Write the edited values to `info`
+    pub fn apply(&self, info: &mut DocumentInfo) {
+        info.author = buffer_text(&self.author);
+        info.description = buffer_text(&self.description);
+        info.tags = buffer_text(&self.tags)
+            .split(',')
+            .map(str::trim)
+            .filter(|tag| !tag.is_empty())
+            .map(str::to_owned)
+            .collect();
+        info.units = Unit::ALL.get(self.units as usize).copied().unwrap_or_default();
+        info.dpi = self.dpi as f32;
+        info.pixel_snap = PixelSnap::ALL.get(self.pixel_snap as usize).copied().unwrap_or_default();
+        info.is_aligning_strokes = self.is_aligning_strokes;
+    }
+
+    /// Draw the dialog within `bounds` and handle its input
+    ///
+    /// Returns [`Some`] once the dialog has been closed
+    pub fn draw(&mut self, d: &mut impl RaylibDraw, bounds: Rectangle) -> Option<DialogResult> {
+        if d.gui_window_box(bounds, Some(tr_c("document_info.title").as_c_str())) {
+            return Some(DialogResult::Cancel);
+        }
+
+        let mut rows = RowLayout::new(bounds);
+        for (label_text, field, buffer) in [
+            ("document_info.author", DocumentInfoField::Author, &mut self.author),
+            ("document_info.description", DocumentInfoField::Description, &mut self.description),
+            ("document_info.tags", DocumentInfoField::Tags, &mut self.tags),
+        ] {
+            let (label, control) = rows.next_row();
+            d.gui_label(label, Some(tr_c(label_text).as_c_str()));
+            if d.gui_text_box(control, buffer, self.editing == Some(field)) {
+                toggle_editing(&mut self.editing, field);
+            }
+        }
+
+        let (label, control) = rows.next_row();
+        d.gui_label(label, Some(tr_c("document_info.units").as_c_str()));
+        let unit_width = control.width / Unit::ALL.len() as f32;
+        d.gui_toggle_group(Rectangle { width: unit_width - 1.0, ..control }, Some(c"px;mm;in;pt"), &mut self.units);
+
+        let (label, control) = rows.next_row();
+        d.gui_label(label, Some(tr_c("document_info.dpi").as_c_str()));
+        if d.gui_value_box(control, None, &mut self.dpi, 1, 2400, self.editing == Some(DocumentInfoField::Dpi)) {
+            toggle_editing(&mut self.editing, DocumentInfoField::Dpi);
+        }
+
+        let (label, control) = rows.next_row();
+        d.gui_label(label, Some(tr_c("document_info.pixel_snap").as_c_str()));
+        let snap_width = control.width / PixelSnap::ALL.len() as f32;
+        d.gui_toggle_group(Rectangle { width: snap_width - 1.0, ..control }, Some(tr_list(&[
+            "document_info.pixel_snap.off",
+            "document_info.pixel_snap.whole",
+            "document_info.pixel_snap.half",
+        ]).as_c_str()), &mut self.pixel_snap);
+
+        let (label, control) = rows.next_row();
+        d.gui_label(label, Some(tr_c("document_info.align_strokes").as_c_str()));
+        d.gui_check_box(Rectangle { width: control.height, ..control }, None, &mut self.is_aligning_strokes);
+
+        for (label_text, timestamp) in [("document_info.created", self.created), ("document_info.modified", self.modified)] {
+            let (label, control) = rows.next_row();
+            d.gui_label(label, Some(tr_c(label_text).as_c_str()));
+            let text = CString::new(timestamp.map_or_else(|| tr("document_info.never_saved"), format_timestamp)).unwrap_or_default();
+            d.gui_label(control, Some(text.as_c_str()));
+        }
+
+        dialog_footer(d, bounds)
+    }
+}
+
+/// The "Rename Document" dialog, for changing the title of an editor's document
+#[derive(Debug)]
+pub struct RenameDialog {
+    /// The index of the editor whose document is being renamed
+    editor_index: u32,
+    title: TextField,
+    /// Whether the title field should take focus the next time the dialog is drawn
+    is_focus_pending: bool,
+}
+
+impl RenameDialog {
+    /// Width of the dialog window
+    pub const WIDTH: f32 = 320.0;
+
+    /// Height of the dialog window
+    pub const HEIGHT: f32 = RowLayout::window_height(1);
+
+    /// Construct a dialog renaming the document of the editor at `editor_index`, currently titled `title`
+    ///
+    /// The title field starts out focused, with the old title selected
+    pub fn new(editor_index: u32, title: &str) -> Self {
+        Self {
+            editor_index,
+            title: TextField::new(title),
+            is_focus_pending: true,
+        }
+    }
+
+    /// The rectangle of the dialog when centered in a window of the given size
+    pub fn bounds(window_width: f32, window_height: f32) -> Rectangle {
+        centered_bounds(window_width, window_height, Self::WIDTH, Self::HEIGHT)
+    }
+
+    /// The index of the editor whose document is being renamed
+    #[inline]
+    pub const fn editor_index(&self) -> u32 {
+        self.editor_index
+    }
+
+    /// The edited title, with surrounding whitespace removed
+    ///
+    /// [`None`] if the title is blank
+    pub fn title(&self) -> Option<String> {
+        let title = self.title.text().trim();
+        (!title.is_empty()).then(|| title.to_owned())
+    }
+
+    /// Draw the dialog within `bounds` and handle its input
+    ///
+    /// Returns [`Some`] once the dialog has been closed
+    pub fn draw(&mut self, d: &mut impl RaylibDraw, ui: &mut Ui, bounds: Rectangle) -> Option<DialogResult> {
+        if d.gui_window_box(bounds, Some(tr_c("rename.title").as_c_str())) {
+            return Some(DialogResult::Cancel);
+        }
+
+        let mut rows = RowLayout::new(bounds);
+        let (label, control) = rows.next_row();
+        d.gui_label(label, Some(tr_c("rename.field").as_c_str()));
+        let id = WidgetId::new("rename.field");
+        if std::mem::take(&mut self.is_focus_pending) {
+            ui.set_focus(Some(id));
+        }
+        ui.text_field(d, id, control, &mut self.title);
+
+        dialog_footer(d, bounds)
+    }
+}
+
+/// The "Add Note" dialog, for writing a sticky note [annotation][`crate::annotation::Annotation`]
+#[derive(Debug)]
+pub struct NoteDialog {
+    /// The index of the editor the note is added to
+    editor_index: u32,
+    /// The worldspace position of the note's top-left corner
+    position: Vector2,
+    text: TextField,
+    /// Whether the text field should take focus the next time the dialog is drawn
+    is_focus_pending: bool,
+}
+
+impl NoteDialog {
+    /// Width of the dialog window
+    pub const WIDTH: f32 = 400.0;
+
+    /// Height of the dialog window
+    pub const HEIGHT: f32 = RowLayout::window_height(1);
+
+    /// Construct a dialog adding a note at the worldspace `position` to the document of the editor at `editor_index`
+    pub fn new(editor_index: u32, position: Vector2) -> Self {
+        Self {
+            editor_index,
+            position,
+            text: TextField::new(""),
+            is_focus_pending: true,
+        }
+    }
+
+    /// The rectangle of the dialog when centered in a window of the given size
+    pub fn bounds(window_width: f32, window_height: f32) -> Rectangle {
+        centered_bounds(window_width, window_height, Self::WIDTH, Self::HEIGHT)
+    }
+
+    /// The index of the editor the note is added to
+    #[inline]
+    pub const fn editor_index(&self) -> u32 {
+        self.editor_index
+    }
+
+    /// The note as written
+    ///
+    /// [`None`] if the note is blank
+    pub fn note(&self) -> Option<Annotation> {
+        let text = self.text.text().trim();
+        (!text.is_empty()).then(|| Annotation {
+            position: self.position,
+            kind: AnnotationKind::Note { text: text.to_owned() },
+        })
+    }
+
+    /// Draw the dialog within `bounds` and handle its input
+    ///
+    /// Returns [`Some`] once the dialog has been closed
+    pub fn draw(&mut self, d: &mut impl RaylibDraw, ui: &mut Ui, bounds: Rectangle) -> Option<DialogResult> {
+        if d.gui_window_box(bounds, Some(tr_c("note.title").as_c_str())) {
+            return Some(DialogResult::Cancel);
+        }
+
+        let mut rows = RowLayout::new(bounds);
+        let (label, control) = rows.next_row();
+        d.gui_label(label, Some(tr_c("note.field").as_c_str()));
+        let id = WidgetId::new("note.field");
+        if std::mem::take(&mut self.is_focus_pending) {
+            ui.set_focus(Some(id));
+        }
+        ui.text_field(d, id, control, &mut self.text);
+
+        dialog_footer(d, bounds)
+    }
+}
+
+/// The "Scatter Along Path" dialog, for choosing how copies of the selection are spread along the selected curve
+#[derive(Debug)]
+pub struct ScatterDialog {
+    count: f32,
+    is_rotating: bool,
+    position_jitter: f32,
+    angle_jitter: f32,
+    seed: u64,
+}
+
+impl ScatterDialog {
+    /// Width of the dialog window
+    pub const WIDTH: f32 = 320.0;
+
+    /// Height of the dialog window
+    pub const HEIGHT: f32 = RowLayout::window_height(4);
+
+    /// Construct a dialog starting from `options`
+    pub fn new(options: &ScatterOptions) -> Self {
+        Self {
+            count: options.count as f32,
+            is_rotating: options.is_rotating,
+            position_jitter: options.position_jitter,
+            angle_jitter: options.angle_jitter,
+            seed: options.seed,
+        }
+    }
+
+    /// The rectangle of the dialog when centered in a window of the given size
+    pub fn bounds(window_width: f32, window_height: f32) -> Rectangle {
+        centered_bounds(window_width, window_height, Self::WIDTH, Self::HEIGHT)
+    }
+
+    /// The chosen options
+    pub fn options(&self) -> ScatterOptions {
+        ScatterOptions {
+            count: (self.count as usize).clamp(ScatterOptions::MIN_COUNT, ScatterOptions::MAX_COUNT),
+            is_rotating: self.is_rotating,
+            position_jitter: self.position_jitter,
+            angle_jitter: self.angle_jitter,
+            seed: self.seed,
+        }
+    }
+
+    /// Draw the dialog within `bounds` and handle its input
+    ///
+    /// Returns [`Some`] once the dialog has been closed
+    pub fn draw(&mut self, d: &mut impl RaylibDraw, ui: &mut Ui, bounds: Rectangle) -> Option<DialogResult> {
+        if d.gui_window_box(bounds, Some(tr_c("scatter.title").as_c_str())) {
+            return Some(DialogResult::Cancel);
+        }
+
+        let mut rows = RowLayout::new(bounds);
+        let (label, control) = rows.next_row();
+        d.gui_label(label, Some(tr_c("scatter.count").as_c_str()));
+        let count_spec = NumberSpec::integer(ScatterOptions::MIN_COUNT as f32, ScatterOptions::MAX_COUNT as f32);
+        ui.number_field(d, WidgetId::new("scatter.count"), control, &mut self.count, &count_spec);
+
+        let (label, control) = rows.next_row();
+        d.gui_label(label, Some(tr_c("scatter.rotate").as_c_str()));
+        d.gui_check_box(Rectangle { width: control.height, ..control }, None, &mut self.is_rotating);
+
+        for (label_text, value, max, unit) in [
+            ("scatter.position_jitter", &mut self.position_jitter, ScatterOptions::MAX_POSITION_JITTER, "px"),
+            ("scatter.angle_jitter", &mut self.angle_jitter, ScatterOptions::MAX_ANGLE_JITTER, "deg"),
+        ] {
+            let (label, control) = rows.next_row();
+            d.gui_label(label, Some(tr_c(label_text).as_c_str()));
+            let text = CString::new(format!("{:.0}{unit}", *value)).unwrap_or_default();
+            d.gui_slider_bar(control, None, Some(text.as_c_str()), value, 0.0, max);
+        }
+
+        dialog_footer(d, bounds)
+    }
+}
+
+/// The "Grid Repeat" dialog, for choosing how the selection is copied into a grid
+///
+/// The grid is previewed over the artwork while the dialog is open
+#[derive(Debug)]
+pub struct GridRepeatDialog {
+    columns: f32,
+    rows: f32,
+    /// Worldspace pixels between neighboring columns
+    horizontal_spacing: f32,
+    /// Worldspace pixels between neighboring rows
+    vertical_spacing: f32,
+    /// Degrees
+    rotation_step: f32,
+    /// Percent
+    scale_step: f32,
+    units: Unit,
+    dpi: f32,
+}
+
+impl GridRepeatDialog {
+    /// Width of the dialog window
+    pub const WIDTH: f32 = 320.0;
+
+    /// Height of the dialog window
+    pub const HEIGHT: f32 = RowLayout::window_height(6);
+
+    /// Range of row and column counts accepted
+    const COUNT_SPEC: NumberSpec = NumberSpec::integer(GridRepeatOptions::MIN_COUNT as f32, GridRepeatOptions::MAX_COUNT as f32);
+
+    /// Construct a dialog starting from `options`, measuring spacing in the units of `document`
+    pub fn new(options: &GridRepeatOptions, document: &Document) -> Self {
+        Self {
+            columns: options.columns as f32,
+            rows: options.rows as f32,
+            horizontal_spacing: options.spacing.x,
+            vertical_spacing: options.spacing.y,
+            rotation_step: options.rotation_step,
+            scale_step: options.scale_step * 100.0,
+            units: document.info.units,
+            dpi: document.info.dpi,
+        }
+    }
+
+    /// The rectangle of the dialog when centered in a window of the given size
+    pub fn bounds(window_width: f32, window_height: f32) -> Rectangle {
+        centered_bounds(window_width, window_height, Self::WIDTH, Self::HEIGHT)
+    }
+
+    /// The chosen options
+    pub fn options(&self) -> GridRepeatOptions {
+        GridRepeatOptions {
+            columns: (self.columns as usize).clamp(GridRepeatOptions::MIN_COUNT, GridRepeatOptions::MAX_COUNT),
+            rows: (self.rows as usize).clamp(GridRepeatOptions::MIN_COUNT, GridRepeatOptions::MAX_COUNT),
+            spacing: Vector2::new(self.horizontal_spacing, self.vertical_spacing),
+            rotation_step: self.rotation_step,
+            scale_step: self.scale_step / 100.0,
+        }
+    }
+
+    /// Draw the dialog within `bounds` and handle its input
+    ///
+    /// Returns [`Some`] once the dialog has been closed
+    pub fn draw(&mut self, d: &mut impl RaylibDraw, ui: &mut Ui, bounds: Rectangle) -> Option<DialogResult> {
+        if d.gui_window_box(bounds, Some(tr_c("grid_repeat.title").as_c_str())) {
+            return Some(DialogResult::Cancel);
+        }
+
+        let mut rows = RowLayout::new(bounds);
+        let spacing_spec = NumberSpec::new(-16384.0, 16384.0, 1.0).with_unit(self.units, self.dpi);
+        for (id, value, spec) in [
+            ("grid_repeat.columns", &mut self.columns, &Self::COUNT_SPEC),
+            ("grid_repeat.rows", &mut self.rows, &Self::COUNT_SPEC),
+            ("grid_repeat.horizontal_spacing", &mut self.horizontal_spacing, &spacing_spec),
+            ("grid_repeat.vertical_spacing", &mut self.vertical_spacing, &spacing_spec),
+        ] {
+            let (label, control) = rows.next_row();
+            d.gui_label(label, Some(tr_c(id).as_c_str()));
+            ui.number_field(d, WidgetId::new(id), control, value, spec);
+        }
+
+        for (label_text, value, max, unit) in [
+            ("grid_repeat.rotation_step", &mut self.rotation_step, GridRepeatOptions::MAX_ROTATION_STEP, "deg"),
+            ("grid_repeat.scale_step", &mut self.scale_step, GridRepeatOptions::MAX_SCALE_STEP * 100.0, "%"),
+        ] {
+            let (label, control) = rows.next_row();
+            d.gui_label(label, Some(tr_c(label_text).as_c_str()));
+            let text = CString::new(format!("{:+.0}{unit}", *value)).unwrap_or_default();
+            d.gui_slider_bar(control, None, Some(text.as_c_str()), value, -max, max);
+        }
+
+        dialog_footer(d, bounds)
+    }
+}
+
+/// The "Scale Document" dialog, for resizing everything in a document by a percentage or to a new artboard size
+///
+/// The first artboard's width and height are linked to the percentage, so typing either resizes the document to fit
+#[derive(Debug)]
+pub struct ScaleDocumentDialog {
+    /// Percent
+    percent: f32,
+    /// The worldspace size of the first artboard before scaling
+    ///
+    /// [`None`] if the document has no artboards
+    artboard_size: Option<Vector2>,
+    units: Unit,
+    dpi: f32,
+}
+
+impl ScaleDocumentDialog {
+    /// Width of the dialog window
+    pub const WIDTH: f32 = 320.0;
+
+    /// Height of the dialog window
+    pub const HEIGHT: f32 = RowLayout::window_height(3);
+
+    /// Range of percentages accepted
+    const PERCENT_SPEC: NumberSpec = NumberSpec::new(1.0, 10000.0, 1.0);
+
+    /// Construct a dialog at 100%, sizing the first artboard of `document`
+    pub fn new(document: &Document) -> Self {
+        Self {
+            percent: 100.0,
+            artboard_size: document.artboards.first().map(|artboard| Vector2::new(artboard.rect.width, artboard.rect.height)),
+            units: document.info.units,
+            dpi: document.info.dpi,
+        }
+    }
+
+    /// The rectangle of the dialog when centered in a window of the given size
+    pub fn bounds(window_width: f32, window_height: f32) -> Rectangle {
+        centered_bounds(window_width, window_height, Self::WIDTH, Self::HEIGHT)
+    }
+
+    /// The chosen scale, as a multiplier
+    pub fn factor(&self) -> f32 {
+        self.percent * 0.01
+    }
+
+    /// Draw the dialog within `bounds` and handle its input
+    ///
+    /// Returns [`Some`] once the dialog has been closed
+    pub fn draw(&mut self, d: &mut impl RaylibDraw, ui: &mut Ui, bounds: Rectangle) -> Option<DialogResult> {
+        if d.gui_window_box(bounds, Some(tr_c("scale_document.title").as_c_str())) {
+            return Some(DialogResult::Cancel);
+        }
+
+        let mut rows = RowLayout::new(bounds);
+        let (label, control) = rows.next_row();
+        d.gui_label(label, Some(tr_c("scale_document.percent").as_c_str()));
+        ui.number_field(d, WidgetId::new("scale_document.percent"), control, &mut self.percent, &Self::PERCENT_SPEC);
+
+        if let Some(size) = self.artboard_size {
+            let size_spec = NumberSpec::new(1.0, 1_000_000.0, 1.0).with_unit(self.units, self.dpi);
+            for (id, original) in [("scale_document.width", size.x), ("scale_document.height", size.y)] {
+                let (label, control) = rows.next_row();
+                d.gui_label(label, Some(tr_c(id).as_c_str()));
+                let mut length = original * self.factor();
+                if ui.number_field(d, WidgetId::new(id), control, &mut length, &size_spec) && original > 0.0 {
+                    self.percent = (length / original * 100.0).clamp(Self::PERCENT_SPEC.min, Self::PERCENT_SPEC.max);
+                }
+            }
+        }
+
+        dialog_footer(d, bounds)
+    }
+}
+
+/// The "Scale" dialog, for stretching the selected layers by a percentage
+#[derive(Debug)]
+pub struct ScaleDialog {
+    /// Percent
+    horizontal: f32,
+    /// Percent
+    vertical: f32,
+    is_uniform: bool,
+    is_scaling_strokes: bool,
+}
+
+impl ScaleDialog {
+    /// Width of the dialog window
+    pub const WIDTH: f32 = 320.0;
+
+    /// Height of the dialog window
+    pub const HEIGHT: f32 = RowLayout::window_height(4);
+
+    /// Range of percentages accepted along each axis
+    const PERCENT_SPEC: NumberSpec = NumberSpec::new(1.0, 1000.0, 1.0);
+
+    /// Construct a dialog at 100%, remembering whether strokes were scaled last time from `preferences`
+    pub const fn new(preferences: &TransformPreferences) -> Self {
+        Self {
+            horizontal: 100.0,
+            vertical: 100.0,
+            is_uniform: true,
+            is_scaling_strokes: preferences.is_scaling_strokes,
+        }
+    }
+
+    /// The rectangle of the dialog when centered in a window of the given size
+    pub fn bounds(window_width: f32, window_height: f32) -> Rectangle {
+        centered_bounds(window_width, window_height, Self::WIDTH, Self::HEIGHT)
+    }
+
+    /// The chosen scale along each axis, as a multiplier
+    pub fn factor(&self) -> Vector2 {
+        let vertical = if self.is_uniform { self.horizontal } else { self.vertical };
+        Vector2::new(self.horizontal, vertical) * 0.01
+    }
+
+    /// Whether stroke widths should be scaled along with the artwork
+    #[inline]
+    pub const fn is_scaling_strokes(&self) -> bool {
+        self.is_scaling_strokes
+    }
+
+    /// Draw the dialog within `bounds` and handle its input
+    ///
+    /// Returns [`Some`] once the dialog has been closed
+    pub fn draw(&mut self, d: &mut impl RaylibDraw, ui: &mut Ui, bounds: Rectangle) -> Option<DialogResult> {
+        if d.gui_window_box(bounds, Some(tr_c("scale.title").as_c_str())) {
+            return Some(DialogResult::Cancel);
+        }
+
+        let mut rows = RowLayout::new(bounds);
+        let (label, control) = rows.next_row();
+        d.gui_label(label, Some(tr_c("scale.uniform").as_c_str()));
+        d.gui_check_box(Rectangle { width: control.height, ..control }, None, &mut self.is_uniform);
+
+        let (label, control) = rows.next_row();
+        d.gui_label(label, Some(tr_c(if self.is_uniform { "scale.title" } else { "scale.horizontal" }).as_c_str()));
+        ui.number_field(d, WidgetId::new("scale.horizontal"), control, &mut self.horizontal, &Self::PERCENT_SPEC);
+
+        let (label, control) = rows.next_row();
+        if self.is_uniform {
+            self.vertical = self.horizontal;
+        } else {
+            d.gui_label(label, Some(tr_c("scale.vertical").as_c_str()));
+            ui.number_field(d, WidgetId::new("scale.vertical"), control, &mut self.vertical, &Self::PERCENT_SPEC);
+        }
+
+        let (label, control) = rows.next_row();
+        d.gui_label(label, Some(tr_c("scale.strokes").as_c_str()));
+        d.gui_check_box(Rectangle { width: control.height, ..control }, None, &mut self.is_scaling_strokes);
+
+        dialog_footer(d, bounds)
+    }
+}
+
+/// The "Arrange Artboards" dialog, for reflowing every artboard of a document into a grid
+#[derive(Debug)]
+pub struct ArrangeArtboardsDialog {
+    columns: f32,
+    /// Worldspace pixels between neighboring artboards
+    spacing: f32,
+    units: Unit,
+    dpi: f32,
+}
+
+impl ArrangeArtboardsDialog {
+    /// Width of the dialog window
+    pub const WIDTH: f32 = 320.0;
+
+    /// Height of the dialog window
+    pub const HEIGHT: f32 = RowLayout::window_height(2);
+
+    /// Worldspace pixels between artboards when the dialog opens
+    const DEFAULT_SPACING: f32 = 20.0;
+
+    /// Range of column counts accepted
+    const COLUMNS_SPEC: NumberSpec = NumberSpec::integer(1.0, 256.0);
+
+    /// Construct a dialog arranging the artboards of `document` into a roughly square grid
+    pub fn new(document: &Document) -> Self {
+        Self {
+            columns: (document.artboards.len() as f32).sqrt().ceil().max(1.0),
+            spacing: Self::DEFAULT_SPACING,
+            units: document.info.units,
+            dpi: document.info.dpi,
+        }
+    }
+
+    /// The rectangle of the dialog when centered in a window of the given size
+    pub fn bounds(window_width: f32, window_height: f32) -> Rectangle {
+        centered_bounds(window_width, window_height, Self::WIDTH, Self::HEIGHT)
+    }
+
+    /// The number of artboards in each row of the grid
+    #[inline]
+    pub fn columns(&self) -> usize {
+        self.columns.max(1.0) as usize
+    }
+
+    /// The worldspace distance between neighboring artboards
+    #[inline]
+    pub const fn spacing(&self) -> f32 {
+        self.spacing
+    }
+
+    /// Draw the dialog within `bounds` and handle its input
+    ///
+    /// Returns [`Some`] once the dialog has been closed
+    pub fn draw(&mut self, d: &mut impl RaylibDraw, ui: &mut Ui, bounds: Rectangle) -> Option<DialogResult> {
+        if d.gui_window_box(bounds, Some(tr_c("arrange.title").as_c_str())) {
+            return Some(DialogResult::Cancel);
+        }
+
+        let mut rows = RowLayout::new(bounds);
+        let (label, control) = rows.next_row();
+        d.gui_label(label, Some(tr_c("arrange.columns").as_c_str()));
+        ui.number_field(d, WidgetId::new("arrange.columns"), control, &mut self.columns, &Self::COLUMNS_SPEC);
+
+        let (label, control) = rows.next_row();
+        d.gui_label(label, Some(tr_c("arrange.spacing").as_c_str()));
+        let spacing_spec = NumberSpec::new(0.0, 16384.0, 1.0).with_unit(self.units, self.dpi);
+        ui.number_field(d, WidgetId::new("arrange.spacing"), control, &mut self.spacing, &spacing_spec);
+
+        dialog_footer(d, bounds)
+    }
+}
+
+/// The "Export Palette" dialog, for writing the focused document's swatches to a palette file beside it
+#[derive(Debug, Default)]
+pub struct PaletteExportDialog {
+    /// Index into [`PaletteFormat::ALL`]
+    format: i32,
+}
+
+impl PaletteExportDialog {
+    /// Width of the dialog window
+    pub const WIDTH: f32 = 320.0;
+
+    /// Height of the dialog window
+    pub const HEIGHT: f32 = RowLayout::window_height(1);
+
+    /// Construct a dialog exporting GIMP palettes
+    pub const fn new() -> Self {
+        Self { format: 0 }
+    }
+
+    /// The rectangle of the dialog when centered in a window of the given size
+    pub fn bounds(window_width: f32, window_height: f32) -> Rectangle {
+        centered_bounds(window_width, window_height, Self::WIDTH, Self::HEIGHT)
+    }
+
+    /// The chosen file format
+    pub fn format(&self) -> PaletteFormat {
+        usize::try_from(self.format).ok()
+            .and_then(|i| PaletteFormat::ALL.get(i).copied())
+            .unwrap_or_default()
+    }
+
+    /// Draw the dialog within `bounds` and handle its input
+    ///
+    /// Returns [`Some`] once the dialog has been closed
+    pub fn draw(&mut self, d: &mut impl RaylibDraw, bounds: Rectangle) -> Option<DialogResult> {
+        if d.gui_window_box(bounds, Some(tr_c("palette_export.title").as_c_str())) {
+            return Some(DialogResult::Cancel);
+        }
+
+        let mut rows = RowLayout::new(bounds);
+        let (label, control) = rows.next_row();
+        d.gui_label(label, Some(tr_c("palette.format").as_c_str()));
+        let names = tr_list(&["palette.format.gpl", "palette.format.ase", "palette.format.hex"]);
+        d.gui_toggle_group(Rectangle { width: control.width / 3.0 - 1.0, ..control }, Some(names.as_c_str()), &mut self.format);
+
+        dialog_footer(d, bounds)
+    }
+}
+
+/// The "Swatch" dialog, for choosing whether one of the focused document's swatches is a screen color, a mix of process inks, or a spot ink
+#[derive(Debug)]
+pub struct SwatchDialog {
+    /// The index of the swatch being edited
+    index: usize,
+    name: TextField,
+    /// 0 for a screen color, 1 for process inks, and 2 for a spot ink
+    kind: i32,
+    /// The swatch's screen preview when the dialog opened, which it keeps if it stays a screen color
+    rgb: Color,
+    /// Percent of cyan, magenta, yellow, and black ink; the alternate of spot inks
+    inks: [f32; 4],
+    /// The name of the spot ink
+    ink: TextField,
+}
+
+impl SwatchDialog {
+    /// Width of the dialog window
+    pub const WIDTH: f32 = 320.0;
+
+    /// Height of the dialog window
+    pub const HEIGHT: f32 = RowLayout::window_height(7);
+
+    /// Range of ink percentages accepted
+    const INK_SPEC: NumberSpec = NumberSpec::new(0.0, 100.0, 1.0);
+
+    /// Construct a dialog editing `swatch`, stored at `index` among the document's swatches
+    ///
+    /// Screen colors start out with the process inks that would print them
+    pub fn new(index: usize, swatch: &Swatch) -> Self {
+        let Cmyk { c, m, y, k } = swatch.color.cmyk();
+        let (kind, ink) = match &swatch.color {
+            SwatchColor::Rgb(_) => (0, ""),
+            SwatchColor::Cmyk(_) => (1, ""),
+            SwatchColor::Spot { ink, .. } => (2, ink.as_str()),
+        };
+        Self {
+            index,
+            name: TextField::new(&swatch.name),
+            kind,
+            rgb: swatch.color.preview(),
+            inks: [c, m, y, k].map(|ink| (ink * 100.0).round()),
+            ink: TextField::new(ink),
+        }
+    }
+
+    /// The rectangle of the dialog when centered in a window of the given size
+    pub fn bounds(window_width: f32, window_height: f32) -> Rectangle {
+        centered_bounds(window_width, window_height, Self::WIDTH, Self::HEIGHT)
+    }
+
+    /// The index of the swatch being edited
+    #[inline]
+    pub const fn index(&self) -> usize {
+        self.index
+    }
+
+    /// The edited swatch
+    ///
+    /// [`None`] if the name is blank, or if it is a spot ink without an ink name
+    pub fn swatch(&self) -> Option<Swatch> {
+        let name = self.name.text().trim();
+        let [c, m, y, k] = self.inks.map(|ink| ink * 0.01);
+        let inks = Cmyk::new(c, m, y, k);
+        let color = match self.kind {
+            1 => SwatchColor::Cmyk(inks),
+            2 => {
+                let ink = self.ink.text().trim();
+                if ink.is_empty() {
+                    return None;
+                }
+                SwatchColor::Spot { ink: ink.to_owned(), alternate: inks }
+            }
+            _ => SwatchColor::Rgb(self.rgb),
+        };
+        (!name.is_empty()).then(|| Swatch { name: name.to_owned(), color })
+    }
+
+    /// Draw the dialog within `bounds` and handle its input
+    ///
+    /// Returns [`Some`] once the dialog has been closed
+    pub fn draw(&mut self, d: &mut impl RaylibDraw, ui: &mut Ui, bounds: Rectangle) -> Option<DialogResult> {
+        if d.gui_window_box(bounds, Some(tr_c("swatch_dialog.title").as_c_str())) {
+            return Some(DialogResult::Cancel);
+        }
+
+        let mut rows = RowLayout::new(bounds);
+        let (label, control) = rows.next_row();
+        d.gui_label(label, Some(tr_c("swatch_dialog.name").as_c_str()));
+        ui.text_field(d, WidgetId::new("swatch_dialog.name"), control, &mut self.name);
+
+        let (label, control) = rows.next_row();
+        d.gui_label(label, Some(tr_c("swatch_dialog.kind").as_c_str()));
+        let kinds = tr_list(&["swatch_dialog.kind.rgb", "swatch_dialog.kind.cmyk", "swatch_dialog.kind.spot"]);
+        d.gui_toggle_group(Rectangle { width: control.width / 3.0 - 1.0, ..control }, Some(kinds.as_c_str()), &mut self.kind);
+
+        // screen colors are picked in the style panel, so only inks are edited here
+        for (id, ink) in ["swatch_dialog.cyan", "swatch_dialog.magenta", "swatch_dialog.yellow", "swatch_dialog.black"].into_iter().zip(&mut self.inks) {
+            let (label, control) = rows.next_row();
+            if self.kind != 0 {
+                d.gui_label(label, Some(tr_c(id).as_c_str()));
+                ui.number_field(d, WidgetId::new(id), control, ink, &Self::INK_SPEC);
+            }
+        }
+
+        let (label, control) = rows.next_row();
+        if self.kind == 2 {
+            d.gui_label(label, Some(tr_c("swatch_dialog.ink").as_c_str()));
+            ui.text_field(d, WidgetId::new("swatch_dialog.ink"), control, &mut self.ink);
+        }
+
+        dialog_footer(d, bounds)
+    }
+}
+
+/// The "Export Frames" dialog, for choosing the frame rate of the focused document and how its artboards are exported:
+/// one file per frame, or one looping GIF
+#[derive(Debug)]
+pub struct FrameExportDialog {
+    fps: f32,
+    /// Index into [`ImageFormat::ALL`]
+    format: i32,
+    is_gif: bool,
+}
+
+impl FrameExportDialog {
+    /// Width of the dialog window
+    pub const WIDTH: f32 = 400.0;
+
+    /// Height of the dialog window
+    pub const HEIGHT: f32 = RowLayout::window_height(3);
+
+    /// Range of frame rates accepted
+    const FPS_SPEC: NumberSpec = NumberSpec::new(Animation::MIN_FPS, Animation::MAX_FPS, 1.0);
+
+    /// Construct a dialog starting from the frame rate and export settings of `animation`
+    pub fn new(animation: &Animation) -> Self {
+        Self {
+            fps: animation.fps,
+            format: ImageFormat::ALL.iter().position(|&format| format == animation.format).unwrap_or(0) as i32,
+            is_gif: animation.is_gif,
+        }
+    }
+
+    /// The rectangle of the dialog when centered in a window of the given size
+    pub fn bounds(window_width: f32, window_height: f32) -> Rectangle {
+        centered_bounds(window_width, window_height, Self::WIDTH, Self::HEIGHT)
+    }
+
+    /// The chosen frame rate
+    pub fn fps(&self) -> f32 {
+        self.fps.clamp(Animation::MIN_FPS, Animation::MAX_FPS)
+    }
+
+    /// The chosen file format of each frame
+    pub fn format(&self) -> ImageFormat {
+        usize::try_from(self.format).ok()
+            .and_then(|i| ImageFormat::ALL.get(i).copied())
+            .unwrap_or_default()
+    }
+
+    /// Whether the frames are exported as one GIF instead of one file each
+    pub const fn is_gif(&self) -> bool {
+        self.is_gif
+    }
+
+    /// Draw the dialog within `bounds` and handle its input
+    ///
+    /// Returns [`Some`] once the dialog has been closed
+    pub fn draw(&mut self, d: &mut impl RaylibDraw, ui: &mut Ui, bounds: Rectangle) -> Option<DialogResult> {
+        if d.gui_window_box(bounds, Some(tr_c("export_frames.title").as_c_str())) {
+            return Some(DialogResult::Cancel);
+        }
+
+        let mut rows = RowLayout::new(bounds);
+        let (label, control) = rows.next_row();
+        d.gui_label(label, Some(tr_c("export_frames.fps").as_c_str()));
+        ui.number_field(d, WidgetId::new("export_frames.fps"), control, &mut self.fps, &Self::FPS_SPEC);
+
+        let row = rows.next_full_row();
+        d.gui_check_box(Rectangle { width: row.height, ..row }, Some(tr_c("export_frames.gif").as_c_str()), &mut self.is_gif);
+
+        // a GIF has a format of its own
+        let (label, control) = rows.next_row();
+        if !self.is_gif {
+            d.gui_label(label, Some(tr_c("export.format").as_c_str()));
+            let format_width = control.width / ImageFormat::ALL.len() as f32;
+            d.gui_toggle_group(Rectangle { width: format_width - 1.0, ..control }, Some(IMAGE_FORMAT_NAMES), &mut self.format);
+        }
+
+        dialog_footer(d, bounds)
+    }
+}
+
+/// A field of the [`ExportDialog`] that accepts keyboard input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ExportField {
+    Name,
+    Scale,
+    Directory,
+    Artboards,
+}
+
+/// The "Export" dialog, for exporting artboards and managing a document's [export presets][`ExportPreset`]
+///
+/// Presets saved or deleted in the dialog are only written back to the document by [`ExportDialog::apply`]
+#[derive(Debug)]
+pub struct ExportDialog {
+    /// The index of the editor whose document is being exported
+    editor_index: u32,
+    presets: Vec<ExportPreset>,
+    /// The index of the preset shown in the preset list
+    selected: i32,
+    name: Vec<u8>,
+    format: i32,
+    /// Percent
+    scale: i32,
+    directory: Vec<u8>,
+    /// Comma-separated
+    artboards: Vec<u8>,
+    is_requiring_unique_names: bool,
+    editing: Option<ExportField>,
+}
+
+impl ExportDialog {
+    /// Width of the dialog window
+    pub const WIDTH: f32 = 400.0;
+
+    /// Height of the dialog window
+    pub const HEIGHT: f32 = RowLayout::window_height(7);
+
+    /// Maximum length in bytes of each text field
+    const TEXT_CAPACITY: usize = 256;
+
+    /// Construct a dialog exporting the document of the editor at `editor_index`, starting from its last export settings
+    pub fn new(editor_index: u32, document: &Document) -> Self {
+        let mut dialog = Self {
+            editor_index,
+            presets: document.export_presets.clone(),
+            selected: 0,
+            name: Vec::new(),
+            format: 0,
+            scale: 100,
+            directory: Vec::new(),
+            artboards: Vec::new(),
+            is_requiring_unique_names: true,
+            editing: None,
+        };
+        dialog.load(&document.last_export.clone().unwrap_or_default());
+        dialog
+    }
+
+    /// The rectangle of the dialog when centered in a window of the given size
+    pub fn bounds(window_width: f32, window_height: f32) -> Rectangle {
+        centered_bounds(window_width, window_height, Self::WIDTH, Self::HEIGHT)
+    }
+
+    /// The index of the editor whose document is being exported
+    #[inline]
+    pub const fn editor_index(&self) -> u32 {
+        self.editor_index
+    }
+
+    /// Whether a text field is capturing keyboard input
+    #[inline]
+    pub fn is_editing(&self) -> bool {
+        self.editing.is_some()
+    }
+
+    /// Show the settings of `preset` in the fields
+    fn load(&mut self, preset: &ExportPreset) {
+        self.name = text_buffer(&preset.name, Self::TEXT_CAPACITY);
+        self.format = ImageFormat::ALL.iter().position(|&format| format == preset.format).unwrap_or(0) as i32;
+        self.scale = (preset.scale * 100.0).round() as i32;
+        self.directory = text_buffer(&preset.directory.to_string_lossy(), Self::TEXT_CAPACITY);
+        self.artboards = text_buffer(&preset.artboards.join(", "), Self::TEXT_CAPACITY);
+        self.is_requiring_unique_names = preset.is_requiring_unique_names;
+    }
+
+    /// Fill the artboards field with `names`, for exporting only those artboards with the shown settings
+    pub fn set_artboards(&mut self, names: &[String]) {
+        self.artboards = text_buffer(&names.join(", "), Self::TEXT_CAPACITY);
+    }
+
+    /// The settings in the fields
+    pub fn preset(&self) -> ExportPreset {
+        ExportPreset {
+            name: buffer_text(&self.name).trim().to_owned(),
+            format: ImageFormat::ALL.get(self.format as usize).copied().unwrap_or_default(),
+            scale: (self.scale as f32 / 100.0).clamp(ExportPreset::MIN_SCALE, ExportPreset::MAX_SCALE),
+            directory: buffer_text(&self.directory).trim().into(),
+            artboards: buffer_text(&self.artboards)
+                .split(',')
+                .map(str::trim)
+                .filter(|name| !name.is_empty())
+                .map(str::to_owned)
+                .collect(),
+            is_requiring_unique_names: self.is_requiring_unique_names,
+        }
+    }
+
+    /// Write the presets to `document`, and remember the settings in the fields as its last export
+    pub fn apply(&self, document: &mut Document) {
+        document.export_presets.clone_from(&self.presets);
+        document.last_export = Some(self.preset());
+    }
+
+    /// Save the settings in the fields as a preset, replacing the preset with the same name if there is one
+    ///
+    /// Unnamed settings are given a numbered name
+    fn save_preset(&mut self) {
+        let mut preset = self.preset();
+        if preset.name.is_empty() {
+            preset.name = tr_format("export.numbered_preset", &[&self.presets.len().saturating_add(1)]);
+            self.name = text_buffer(&preset.name, Self::TEXT_CAPACITY);
+        }
+        let index = match self.presets.iter().position(|existing| existing.name == preset.name) {
+            Some(index) => {
+                self.presets[index] = preset;
+                index
+            }
+            None => {
+                self.presets.push(preset);
+                self.presets.len().saturating_sub(1)
+            }
+        };
+        self.selected = index as i32;
+    }
+
+    /// Draw the dialog within `bounds` and handle its input
+    ///
+    /// Returns [`Some`] once the dialog has been closed; [`DialogResult::Confirm`] means the document should be exported
+    pub fn draw(&mut self, d: &mut impl RaylibDraw, bounds: Rectangle) -> Option<DialogResult> {
+        if d.gui_window_box(bounds, Some(tr_c("export.title").as_c_str())) {
+            return Some(DialogResult::Cancel);
+        }
+
+        let mut rows = RowLayout::new(bounds);
+        let (label, control) = rows.next_row();
+        d.gui_label(label, Some(tr_c("export.preset").as_c_str()));
+        let button_width = control.width * 0.25;
+        let list = Rectangle { width: control.width - button_width * 2.0 - RowLayout::PADDING, ..control };
+        if self.presets.is_empty() {
+            d.gui_label(list, Some(tr_c("export.no_presets").as_c_str()));
+        } else {
+            let names = CString::new(self.presets.iter().map(|preset| preset.name.as_str()).collect::<Vec<_>>().join(";")).unwrap_or_default();
+            let prev_selected = self.selected;
+            d.gui_combo_box(list, Some(names.as_c_str()), &mut self.selected);
+            if self.selected != prev_selected && let Some(preset) = self.presets.get(self.selected as usize).cloned() {
+                self.load(&preset);
+            }
+        }
+        let save_button = Rectangle { x: list.x + list.width + RowLayout::PADDING, width: button_width, ..control };
+        if d.gui_button(save_button, Some(tr_c("export.save_preset").as_c_str())) {
+            self.save_preset();
+        }
+        let delete_button = Rectangle { x: save_button.x + button_width, ..save_button };
+        if d.gui_button(delete_button, Some(tr_c("export.delete_preset").as_c_str()))
+            && (self.selected as usize) < self.presets.len()
+        {
+            self.presets.remove(self.selected as usize);
+            self.selected = self.selected.min((self.presets.len() as i32).saturating_sub(1)).max(0);
+        }
+
+        for (label_text, field, buffer) in [
+            ("export.name", ExportField::Name, &mut self.name),
+            ("export.folder", ExportField::Directory, &mut self.directory),
+            ("export.artboards", ExportField::Artboards, &mut self.artboards),
+        ] {
+            let (label, control) = rows.next_row();
+            d.gui_label(label, Some(tr_c(label_text).as_c_str()));
+            if d.gui_text_box(control, buffer, self.editing == Some(field)) {
+                toggle_editing(&mut self.editing, field);
+            }
+        }
+
+        let (label, control) = rows.next_row();
+        d.gui_label(label, Some(tr_c("export.format").as_c_str()));
+        let format_width = control.width / ImageFormat::ALL.len() as f32;
+        d.gui_toggle_group(Rectangle { width: format_width - 1.0, ..control }, Some(IMAGE_FORMAT_NAMES), &mut self.format);
+
+        let (label, control) = rows.next_row();
+        d.gui_label(label, Some(tr_c("export.scale").as_c_str()));
+        let (min_percent, max_percent) = ((ExportPreset::MIN_SCALE * 100.0) as i32, (ExportPreset::MAX_SCALE * 100.0) as i32);
+        if d.gui_value_box(control, None, &mut self.scale, min_percent, max_percent, self.editing == Some(ExportField::Scale)) {
+            toggle_editing(&mut self.editing, ExportField::Scale);
+        }
+
+        let row = rows.next_full_row();
+        d.gui_check_box(Rectangle { width: row.height, ..row }, Some(tr_c("export.require_unique").as_c_str()), &mut self.is_requiring_unique_names);
+
+        dialog_footer(d, bounds)
+    }
+}
+
+/// The "Asset Export" dialog, for marking a layer to be exported on its own by "Export All Assets"
+#[derive(Debug)]
+pub struct AssetExportDialog {
+    /// The index of the editor whose layer is being marked
+    editor_index: u32,
+    /// The path of the layer being marked; see [`Document::layer_at`]
+    path: Vec<usize>,
+    is_exported: bool,
+    name: TextField,
+    /// Index into [`ImageFormat::ALL`]
+    format: i32,
+    /// Comma-separated multipliers, like `1, 2, 3x`
+    scales: TextField,
+}
+
+impl AssetExportDialog {
+    /// Width of the dialog window
+    pub const WIDTH: f32 = 400.0;
+
+    /// Height of the dialog window
+    pub const HEIGHT: f32 = RowLayout::window_height(4);
+
+    /// Construct a dialog marking the layer of `document` at `path`, in the editor at `editor_index`
+    ///
+    /// Layers that aren't assets yet start out named after themselves
+    pub fn new(editor_index: u32, document: &Document, path: Vec<usize>) -> Self {
+        let layer = document.layer_at(&path);
+        let asset = layer.and_then(|layer| layer.export.clone());
+        let is_exported = asset.is_some();
+        let asset = asset.unwrap_or_else(|| AssetExport::new(layer.map(|layer| layer.name.clone()).unwrap_or_default()));
+        let scales = asset.scales.iter().map(f32::to_string).collect::<Vec<_>>().join(", ");
+        Self {
+            editor_index,
+            path,
+            is_exported,
+            name: TextField::new(&asset.name),
+            format: ImageFormat::ALL.iter().position(|&format| format == asset.format).unwrap_or(0) as i32,
+            scales: TextField::new(&scales),
+        }
+    }
+
+    /// The rectangle of the dialog when centered in a window of the given size
+    pub fn bounds(window_width: f32, window_height: f32) -> Rectangle {
+        centered_bounds(window_width, window_height, Self::WIDTH, Self::HEIGHT)
+    }
+
+    /// The index of the editor whose layer is being marked
+    #[inline]
+    pub const fn editor_index(&self) -> u32 {
+        self.editor_index
+    }
+
+    /// The path of the layer being marked; see [`Document::layer_at`]
+    #[inline]
+    pub fn path(&self) -> &[usize] {
+        &self.path
+    }
+
+    /// The export settings in the fields
+    ///
+    /// [`None`] if the layer shouldn't be exported, or has no name to export it as.
+    /// Scales that aren't numbers are skipped, and 1x is used if none are left
+    pub fn asset(&self) -> Option<AssetExport> {
+        let name = self.name.text().trim();
+        if !self.is_exported || name.is_empty() {
+            return None;
+        }
+        let mut scales = self.scales.text()
+            .split(',')
+            .filter_map(|scale| scale.trim().trim_end_matches(['x', 'X']).parse::<f32>().ok())
+            .filter(|scale| scale.is_finite() && *scale > 0.0)
+            .collect::<Vec<_>>();
+        if scales.is_empty() {
+            scales.push(1.0);
+        }
+        Some(AssetExport {
+            name: name.to_owned(),
+            format: ImageFormat::ALL.get(self.format as usize).copied().unwrap_or_default(),
+            scales,
+        })
+    }
+
+    /// Draw the dialog within `bounds` and handle its input
+    ///
+    /// Returns [`Some`] once the dialog has been closed
+    pub fn draw(&mut self, d: &mut impl RaylibDraw, ui: &mut Ui, bounds: Rectangle) -> Option<DialogResult> {
+        if d.gui_window_box(bounds, Some(tr_c("asset.title").as_c_str())) {
+            return Some(DialogResult::Cancel);
+        }
+
+        let mut rows = RowLayout::new(bounds);
+        let (label, control) = rows.next_row();
+        d.gui_label(label, Some(tr_c("asset.exported").as_c_str()));
+        d.gui_check_box(Rectangle { width: control.height, ..control }, None, &mut self.is_exported);
+
+        if !self.is_exported {
+            d.gui_disable();
+        }
+        for (label_text, field) in [("asset.name", &mut self.name), ("asset.scales", &mut self.scales)] {
+            let (label, control) = rows.next_row();
+            d.gui_label(label, Some(tr_c(label_text).as_c_str()));
+            ui.text_field(d, WidgetId::new(label_text), control, field);
+        }
+
+        let (label, control) = rows.next_row();
+        d.gui_label(label, Some(tr_c("asset.format").as_c_str()));
+        let format_width = control.width / ImageFormat::ALL.len() as f32;
+        d.gui_toggle_group(Rectangle { width: format_width - 1.0, ..control }, Some(IMAGE_FORMAT_NAMES), &mut self.format);
+        d.gui_enable();
+
+        dialog_footer(d, bounds)
+    }
+}
+
+/// The "Rename Artboards" dialog, for renaming every artboard of a document at once with an [`ArtboardRename`]
+#[derive(Debug)]
+pub struct ArtboardRenameDialog {
+    /// The index of the editor whose artboards are being renamed
+    editor_index: u32,
+    /// The names of the artboards when the dialog was opened
+    names: Vec<String>,
+    find: TextField,
+    replace: TextField,
+    pattern: TextField,
+    start: f32,
+    digits: f32,
+    /// Whether the dialog refuses to be confirmed while the new names have duplicates
+    is_requiring_unique: bool,
+}
+
+impl ArtboardRenameDialog {
+    /// Width of the dialog window
+    pub const WIDTH: f32 = 400.0;
+
+    /// Height of the dialog window
+    pub const HEIGHT: f32 = RowLayout::window_height(6);
+
+    /// Construct a dialog renaming the artboards of `document`, in the editor at `editor_index`
+    pub fn new(editor_index: u32, document: &Document) -> Self {
+        Self {
+            editor_index,
+            names: document.artboards.iter().map(|artboard| artboard.name.clone()).collect(),
+            find: TextField::default(),
+            replace: TextField::default(),
+            pattern: TextField::new("{name}"),
+            start: 1.0,
+            digits: 1.0,
+            is_requiring_unique: true,
+        }
+    }
+
+    /// The rectangle of the dialog when centered in a window of the given size
+    pub fn bounds(window_width: f32, window_height: f32) -> Rectangle {
+        centered_bounds(window_width, window_height, Self::WIDTH, Self::HEIGHT)
+    }
+
+    /// The index of the editor whose artboards are being renamed
+    #[inline]
+    pub const fn editor_index(&self) -> u32 {
+        self.editor_index
+    }
+
+    /// The renaming described by the fields
+    pub fn rename(&self) -> ArtboardRename {
+        ArtboardRename {
+            find: self.find.text().to_owned(),
+            replace: self.replace.text().to_owned(),
+            pattern: self.pattern.text().to_owned(),
+            start: self.start as usize,
+            digits: (self.digits as usize).max(1),
+        }
+    }
+
+    /// Rename the artboards of `document`
+    pub fn apply(&self, document: &mut Document) {
+        let rename = self.rename();
+        for (index, artboard) in document.artboards.iter_mut().enumerate() {
+            artboard.name = rename.apply(&artboard.name, index);
+        }
+    }
+
+    /// Draw the dialog within `bounds` and handle its input
+    ///
+    /// Returns [`Some`] once the dialog has been closed. While unique names are required,
+    /// the dialog stays open when OK is clicked if any of the new names are duplicates
+    pub fn draw(&mut self, d: &mut impl RaylibDraw, ui: &mut Ui, bounds: Rectangle) -> Option<DialogResult> {
+        if d.gui_window_box(bounds, Some(tr_c("rename_artboards.title").as_c_str())) {
+            return Some(DialogResult::Cancel);
+        }
+
+        let mut rows = RowLayout::new(bounds);
+        for (label_text, field) in [
+            ("rename_artboards.find", &mut self.find),
+            ("rename_artboards.replace", &mut self.replace),
+            ("rename_artboards.pattern", &mut self.pattern),
+        ] {
+            let (label, control) = rows.next_row();
+            d.gui_label(label, Some(tr_c(label_text).as_c_str()));
+            ui.text_field(d, WidgetId::new(label_text), control, field);
+        }
+
+        let (label, control) = rows.next_row();
+        d.gui_label(label, Some(tr_c("rename_artboards.numbering").as_c_str()));
+        let half_width = (control.width - RowLayout::PADDING) * 0.5;
+        let start_box = Rectangle { width: half_width, ..control };
+        ui.number_field(d, WidgetId::new("rename_artboards.start"), start_box, &mut self.start, &NumberSpec::integer(0.0, 99999.0));
+        let digits_label = Rectangle { x: control.x + half_width + RowLayout::PADDING, width: half_width * 0.5, ..control };
+        d.gui_label(digits_label, Some(tr_c("rename_artboards.digits").as_c_str()));
+        let digits_box = Rectangle { x: control.x + control.width - half_width * 0.5, width: half_width * 0.5, ..control };
+        ui.number_field(d, WidgetId::new("rename_artboards.digits"), digits_box, &mut self.digits, &NumberSpec::integer(1.0, 8.0));
+
+        let row = rows.next_full_row();
+        d.gui_check_box(Rectangle { width: row.height, ..row }, Some(tr_c("rename_artboards.require_unique").as_c_str()), &mut self.is_requiring_unique);
+
+        let rename = self.rename();
+        let new_names = self.names.iter()
+            .enumerate()
+            .map(|(index, name)| rename.apply(name, index))
+            .collect::<Vec<_>>();
+        let duplicates = document::duplicate_names(new_names.iter().map(String::as_str));
+        let status = if !duplicates.is_empty() {
+            tr_format("rename_artboards.duplicates", &[&duplicates.join(", ")])
+        } else if let (Some(old), Some(new)) = (self.names.first(), new_names.first()) {
+            let changed = self.names.iter().zip(&new_names).filter(|(before, after)| before != after).count();
+            tr_format("rename_artboards.preview", &[old, new, &changed])
+        } else {
+            tr("rename_artboards.no_artboards")
+        };
+        d.gui_label(rows.next_full_row(), Some(CString::new(status).unwrap_or_default().as_c_str()));
+
+        match dialog_footer(d, bounds) {
+            Some(DialogResult::Confirm) if self.is_requiring_unique && !duplicates.is_empty() => None,
+            result => result,
+        }
+    }
+}
+
+/// The command palette, for running any [`EditorAction`] by searching for its name
+///
+/// Operated entirely from the keyboard: typing filters the list, up and down move the
+/// selection, enter runs the selected action and escape closes the palette
+#[derive(Debug)]
+pub struct CommandPalette {
+    query: Vec<u8>,
+    /// The index of the selected action among the matches
+    selected: usize,
+}
+
+impl Default for CommandPalette {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CommandPalette {
+    /// Width of the palette window
+    pub const WIDTH: f32 = 360.0;
+
+    /// Maximum number of matches listed at once
+    const VISIBLE_MATCHES: usize = 10;
+
+    /// Height of the palette window, fitting the search field and the listed matches
+    pub const HEIGHT: f32 = RowLayout::window_height(Self::VISIBLE_MATCHES + 1);
+
+    /// Maximum length in bytes of the search text
+    const TEXT_CAPACITY: usize = 64;
+
+    /// Construct a palette with an empty search, listing every action
+    pub fn new() -> Self {
+        Self {
+            query: text_buffer("", Self::TEXT_CAPACITY),
+            selected: 0,
+        }
+    }
+
+    /// The rectangle of the palette when centered in a window of the given size
+    pub fn bounds(window_width: f32, window_height: f32) -> Rectangle {
+        centered_bounds(window_width, window_height, Self::WIDTH, Self::HEIGHT)
+    }
+
+    /// The actions matching the search text, best match first
+    ///
+    /// The palette's own action is left out, since it is already open
+    pub fn matches(&self) -> Vec<EditorAction> {
+        let mut matches = command::search(&buffer_text(&self.query));
+        matches.retain(|&action| action != EditorAction::CommandPalette);
+        matches
+    }
+
+    /// The action that enter would run
+    ///
+    /// [`None`] if nothing matches the search text
+    pub fn selected_action(&self) -> Option<EditorAction> {
+        self.matches().get(self.selected).copied()
+    }
+
+    /// Move the selection with the arrow keys and close the palette with enter or escape
+    ///
+    /// Returns [`Some`] once the palette has been closed
+    pub fn handle_keys(&mut self, rl: &RaylibHandle) -> Option<DialogResult> {
+        use KeyboardKey::*;
+        let num_matches = self.matches().len();
+        if rl.is_key_pressed(KEY_ESCAPE) {
+            return Some(DialogResult::Cancel);
+        }
+        if rl.is_key_pressed(KEY_ENTER) || rl.is_key_pressed(KEY_KP_ENTER) {
+            return Some(DialogResult::Confirm);
+        }
+        if rl.is_key_pressed(KEY_DOWN) && num_matches != 0 {
+            self.selected = self.selected.saturating_add(1) % num_matches;
+        } else if rl.is_key_pressed(KEY_UP) && num_matches != 0 {
+            self.selected = self.selected.checked_sub(1).unwrap_or(num_matches.saturating_sub(1));
+        }
+        None
+    }
+
+    /// Draw the palette within `bounds`, listing the bindings from `keymap`
+    ///
+    /// Returns [`Some`] once the palette has been closed, either by its close button or by clicking an action
+    pub fn draw(&mut self, d: &mut impl RaylibDraw, bounds: Rectangle, keymap: &Keymap) -> Option<DialogResult> {
+        if d.gui_window_box(bounds, Some(tr_c("palette.title").as_c_str())) {
+            return Some(DialogResult::Cancel);
+        }
+
+        let mut rows = RowLayout::new(bounds);
+        let previous_query = self.query.clone();
+        // the search field always has focus so the palette can be used without the mouse
+        d.gui_text_box(rows.next_full_row(), &mut self.query, true);
+        if self.query != previous_query {
+            self.selected = 0;
+        }
+
+        let matches = self.matches();
+        // scroll the list so the selection stays visible
+        let first = self.selected.saturating_sub(Self::VISIBLE_MATCHES.saturating_sub(1));
+        for (i, action) in matches.iter().enumerate().skip(first).take(Self::VISIBLE_MATCHES) {
+            let binding = keymap.binding(*action);
+            let text = CString::new(format!("{}    {}", action.name(), binding.label())).unwrap_or_default();
+            let was_selected = i == self.selected;
+            let mut is_selected = was_selected;
+            d.gui_toggle(rows.next_full_row(), Some(text.as_c_str()), &mut is_selected);
+            // clicking a toggle flips it
+            if is_selected != was_selected {
+                self.selected = i;
+                return Some(DialogResult::Confirm);
+            }
+        }
+        None
+    }
+}
+
+/// A page of the [`PreferencesDialog`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PreferencesPage {
+    General,
+    Display,
+    Document,
+    Input,
+    Keymap,
+    Accessibility,
+}
+
+impl PreferencesPage {
+    /// Every page, in the order their tabs are shown
+    const ALL: [Self; 6] = [Self::General, Self::Display, Self::Document, Self::Input, Self::Keymap, Self::Accessibility];
+}
+
+/// A field of the [`PreferencesDialog`] that accepts keyboard input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PreferencesField {
+    FontSize,
+    Autosave,
+    CheckerSize,
+    TargetFps,
+    IdleFps,
+}
+
+/// The preferences window, for editing the application [`Preferences`]
+///
+/// Edits are made to a copy, which is returned by [`PreferencesDialog::into_preferences`] once confirmed
+#[derive(Debug)]
+pub struct PreferencesDialog {
+    preferences: Preferences,
+    page: i32,
+    editing: Option<PreferencesField>,
+    /// The index (in [`EditorAction::ALL`] order) of the action whose binding is waiting for a key press
+    rebinding: Option<usize>,
+    /// How far the keymap page is scrolled down, in pixels
+    keymap_scroll: f32,
+    /// The languages that can be chosen, found when the dialog was opened
+    languages: Vec<String>,
+}
+
+impl PreferencesDialog {
+    /// Width of the dialog window
+    pub const WIDTH: f32 = 400.0;
+
+    /// Height of the dialog window, fitting the page tabs and the longest page (input)
+    ///
+    /// The keymap is longer, but scrolls
+    pub const HEIGHT: f32 = RowLayout::window_height(13);
+
+    /// Number of rows the scrolling list of key bindings spans, filling the page below the tabs
+    const KEYMAP_ROWS: usize = 11;
+
+    /// Number of rows the checkerboard and accessibility color pickers span
+    const CHECKER_PICKER_ROWS: usize = 4;
+
+    /// Construct a dialog editing a copy of `preferences`
+    pub fn new(preferences: &Preferences) -> Self {
+        Self {
+            preferences: preferences.clone(),
+            page: 0,
+            editing: None,
+            rebinding: None,
+            keymap_scroll: 0.0,
+            languages: locale::available_languages(),
+        }
+    }
+
+    /// The rectangle of the dialog when centered in a window of the given size
+    pub fn bounds(window_width: f32, window_height: f32) -> Rectangle {
+        centered_bounds(window_width, window_height, Self::WIDTH, Self::HEIGHT)
+    }
+
+    /// Whether a field is capturing keyboard input
+    #[inline]
+    pub fn is_editing(&self) -> bool {
+        self.editing.is_some() || self.rebinding.is_some()
+    }
+
+    /// Take the edited preferences
+    #[inline]
+    pub fn into_preferences(self) -> Preferences {
+        self.preferences
+    }
+
+    /// If a binding is waiting for a key press, bind it to the next key pressed, along with any modifiers being held
+    ///
+    /// Escape cancels rebinding
+    pub fn capture_rebinding(&mut self, rl: &mut RaylibHandle) {
+        use KeyboardKey::*;
+        const MODIFIERS: [KeyboardKey; 6] = [KEY_LEFT_CONTROL, KEY_RIGHT_CONTROL, KEY_LEFT_SHIFT, KEY_RIGHT_SHIFT, KEY_LEFT_ALT, KEY_RIGHT_ALT];
+
+        let Some(index) = self.rebinding else { return };
+        while let Some(key) = rl.get_key_pressed() {
+            if MODIFIERS.contains(&key) {
+                continue;
+            }
+            self.rebinding = None;
+            if key != KEY_ESCAPE && let Some(&action) = EditorAction::ALL.get(index) {
+                let binding = self.preferences.keymap.binding_mut(action);
+                binding.key = key;
+                binding.ctrl = rl.is_key_down(KEY_LEFT_CONTROL) || rl.is_key_down(KEY_RIGHT_CONTROL);
+                binding.shift = rl.is_key_down(KEY_LEFT_SHIFT) || rl.is_key_down(KEY_RIGHT_SHIFT);
+                binding.alt = rl.is_key_down(KEY_LEFT_ALT) || rl.is_key_down(KEY_RIGHT_ALT);
+            }
+            break;
+        }
+    }
+
+    /// Draw the dialog within `bounds` and handle its input
+    ///
+    /// Returns [`Some`] once the dialog has been closed
+    pub fn draw(&mut self, d: &mut impl RaylibDraw, ui: &mut Ui, bounds: Rectangle) -> Option<DialogResult> {
+        if d.gui_window_box(bounds, Some(tr_c("preferences.title").as_c_str())) {
+            return Some(DialogResult::Cancel);
+        }
+
+        let mut rows = RowLayout::new(bounds);
+        let tabs = rows.next_full_row();
+        let tab_width = tabs.width / PreferencesPage::ALL.len() as f32;
+        d.gui_toggle_group(Rectangle { width: tab_width - 1.0, ..tabs }, Some(tr_list(&[
+            "preferences.page.general",
+            "preferences.page.display",
+            "preferences.page.document",
+            "preferences.page.input",
+            "preferences.page.keymap",
+            "preferences.page.accessibility",
+        ]).as_c_str()), &mut self.page);
+
+        let preferences = &mut self.preferences;
+        match PreferencesPage::ALL.get(self.page as usize).copied().unwrap_or(PreferencesPage::General) {
+            PreferencesPage::General => {
+                let (label, control) = rows.next_row();
+                d.gui_label(label, Some(tr_c("preferences.language").as_c_str()));
+                let language = preferences.language.as_deref().unwrap_or(locale::DEFAULT_LANGUAGE);
+                let mut active = self.languages.iter().position(|l| l == language).unwrap_or(0) as i32;
+                let languages = CString::new(self.languages.join(";")).unwrap_or_default();
+                d.gui_combo_box(control, Some(languages.as_c_str()), &mut active);
+                preferences.language = self.languages.get(active as usize)
+                    .filter(|&l| l != locale::DEFAULT_LANGUAGE)
+                    .cloned();
+
+                let (label, control) = rows.next_row();
+                d.gui_label(label, Some(tr_c("preferences.ui_scale").as_c_str()));
+                let scale_text = CString::new(format!("{:.2}x", preferences.ui_scale)).unwrap_or_default();
+                d.gui_slider_bar(control, None, Some(scale_text.as_c_str()), &mut preferences.ui_scale, Preferences::MIN_UI_SCALE, Preferences::MAX_UI_SCALE);
+
+                let (label, control) = rows.next_row();
+                d.gui_label(label, Some(tr_c("preferences.font_size").as_c_str()));
+                if d.gui_spinner(control, None, &mut preferences.theme.font_size, 6, 48, self.editing == Some(PreferencesField::FontSize)) {
+                    toggle_editing(&mut self.editing, PreferencesField::FontSize);
+                }
+
+                let (label, control) = rows.next_row();
+                d.gui_label(label, Some(tr_c("preferences.autosave").as_c_str()));
+                let mut autosave = preferences.autosave_minutes as i32;
+                if d.gui_spinner(control, None, &mut autosave, 0, 120, self.editing == Some(PreferencesField::Autosave)) {
+                    toggle_editing(&mut self.editing, PreferencesField::Autosave);
+                }
+                preferences.autosave_minutes = autosave.max(0) as u32;
+
+                let (label, control) = rows.next_row();
+                d.gui_label(label, Some(tr_c("preferences.compress_packages").as_c_str()));
+                d.gui_check_box(Rectangle { width: control.height, ..control }, Some(tr_c("preferences.compress_packages_description").as_c_str()), &mut preferences.is_compressing_packages);
+
+                let (label, control) = rows.next_row();
+                d.gui_label(label, Some(tr_c("preferences.curve_quality").as_c_str()));
+                let mut quality = SampleQuality::ALL.iter().position(|&q| q == preferences.sample_quality).unwrap_or(0) as i32;
+                let quality_width = control.width / SampleQuality::ALL.len() as f32;
+                d.gui_toggle_group(Rectangle { width: quality_width - 1.0, ..control }, Some(tr_list(&["preferences.quality.low", "preferences.quality.medium", "preferences.quality.high"]).as_c_str()), &mut quality);
+                preferences.sample_quality = SampleQuality::ALL.get(quality as usize).copied().unwrap_or_default();
+
+                let checkerboard = &mut preferences.checkerboard;
+                let (label, control) = rows.next_row();
+                d.gui_label(label, Some(tr_c("preferences.checker_size").as_c_str()));
+                let mut square_size = checkerboard.square_size.round() as i32;
+                if d.gui_spinner(control, None, &mut square_size, 2, 64, self.editing == Some(PreferencesField::CheckerSize)) {
+                    toggle_editing(&mut self.editing, PreferencesField::CheckerSize);
+                }
+                checkerboard.square_size = square_size as f32;
+
+                let (label, _) = rows.next_row();
+                d.gui_label(label, Some(tr_c("preferences.checker_colors").as_c_str()));
+                let pickers = rows.next_rows(Self::CHECKER_PICKER_ROWS);
+                let picker_width = (pickers.width - RowLayout::PADDING) * 0.5;
+                for (i, color) in checkerboard.colors.iter_mut().enumerate() {
+                    let picker = Rectangle {
+                        x: pickers.x + (picker_width + RowLayout::PADDING) * i as f32,
+                        width: picker_width - COLOR_PICKER_HUE_BAR_WIDTH,
+                        ..pickers
+                    };
+                    *color = d.gui_color_picker(picker, None, *color);
+                }
+            }
+
+            PreferencesPage::Display => {
+                let display = &mut preferences.display;
+                let (label, control) = rows.next_row();
+                d.gui_label(label, Some(tr_c("preferences.vsync").as_c_str()));
+                d.gui_check_box(Rectangle { width: control.height, ..control }, None, &mut display.is_vsync);
+
+                let (label, control) = rows.next_row();
+                d.gui_label(label, Some(tr_c("preferences.power_saving").as_c_str()));
+                d.gui_check_box(Rectangle { width: control.height, ..control }, Some(tr_c("preferences.power_saving_description").as_c_str()), &mut display.is_power_saving);
+
+                // power saving picks the frame rate itself while interacting
+                if display.is_power_saving {
+                    d.gui_disable();
+                }
+                let (label, control) = rows.next_row();
+                d.gui_label(label, Some(tr_c("preferences.target_fps").as_c_str()));
+                let mut target_fps = display.target_fps as i32;
+                if d.gui_spinner(control, None, &mut target_fps, 0, DisplayPreferences::MAX_FPS as i32, self.editing == Some(PreferencesField::TargetFps)) {
+                    toggle_editing(&mut self.editing, PreferencesField::TargetFps);
+                }
+                display.target_fps = target_fps.max(0) as u32;
+                d.gui_enable();
+
+                if !display.is_power_saving {
+                    d.gui_disable();
+                }
+                let (label, control) = rows.next_row();
+                d.gui_label(label, Some(tr_c("preferences.idle_fps").as_c_str()));
+                let mut idle_fps = display.idle_fps as i32;
+                if d.gui_spinner(control, None, &mut idle_fps, DisplayPreferences::MIN_IDLE_FPS as i32, DisplayPreferences::MAX_FPS as i32, self.editing == Some(PreferencesField::IdleFps)) {
+                    toggle_editing(&mut self.editing, PreferencesField::IdleFps);
+                }
+                display.idle_fps = idle_fps.max(0) as u32;
+                d.gui_enable();
+
+                let (label, control) = rows.next_row();
+                d.gui_label(label, Some(tr_c("preferences.gpu_picking").as_c_str()));
+                d.gui_check_box(Rectangle { width: control.height, ..control }, Some(tr_c("preferences.gpu_picking_description").as_c_str()), &mut display.is_gpu_picking);
+
+                let (label, control) = rows.next_row();
+                d.gui_label(label, Some(tr_c("preferences.simplify_zoomed_out").as_c_str()));
+                d.gui_check_box(Rectangle { width: control.height, ..control }, Some(tr_c("preferences.simplify_zoomed_out_description").as_c_str()), &mut display.is_simplifying_zoomed_out);
+            }
+
+            PreferencesPage::Document => {
+                let preset = &mut preferences.default_document;
+                // sizes are shown and typed in the preset's own units
+                let size_spec = NumberSpec::new(1.0, 16384.0, 1.0).with_unit(preset.units, preset.dpi);
+                for (label_text, value, spec) in [
+                    ("preferences.document_width", &mut preset.width, size_spec),
+                    ("preferences.document_height", &mut preset.height, size_spec),
+                    ("preferences.document_dpi", &mut preset.dpi, NumberSpec::integer(1.0, 16384.0)),
+                ] {
+                    let (label, control) = rows.next_row();
+                    d.gui_label(label, Some(tr_c(label_text).as_c_str()));
+                    ui.number_field(d, WidgetId::new(label_text), control, value, &spec);
+                }
+
+                let (label, control) = rows.next_row();
+                d.gui_label(label, Some(tr_c("preferences.document_units").as_c_str()));
+                let mut units = Unit::ALL.iter().position(|&unit| unit == preset.units).unwrap_or(0) as i32;
+                let unit_width = control.width / Unit::ALL.len() as f32;
+                d.gui_toggle_group(Rectangle { width: unit_width - 1.0, ..control }, Some(c"px;mm;in;pt"), &mut units);
+                preset.units = Unit::ALL.get(units as usize).copied().unwrap_or_default();
+
+                let (label, control) = rows.next_row();
+                d.gui_label(label, Some(tr_c("preferences.new_object_style").as_c_str()));
+                let mut new_object_style = NewObjectStyle::ALL.iter().position(|&option| option == preferences.new_object_style).unwrap_or(0) as i32;
+                let option_width = control.width / NewObjectStyle::ALL.len() as f32;
+                d.gui_toggle_group(Rectangle { width: option_width - 1.0, ..control }, Some(tr_list(&["preferences.new_object_style.default", "preferences.new_object_style.last"]).as_c_str()), &mut new_object_style);
+                preferences.new_object_style = NewObjectStyle::ALL.get(new_object_style as usize).copied().unwrap_or_default();
+
+                let (label, control) = rows.next_row();
+                d.gui_label(label, Some(tr_c("preferences.artboard_relative").as_c_str()));
+                d.gui_check_box(Rectangle { width: control.height, ..control }, Some(tr_c("preferences.artboard_relative_description").as_c_str()), &mut preferences.coordinates.is_artboard_relative);
+            }
+
+            PreferencesPage::Input => {
+                let (label, control) = rows.next_row();
+                d.gui_label(label, Some(tr_c("preferences.smart_guides").as_c_str()));
+                d.gui_check_box(Rectangle { width: control.height, ..control }, None, &mut preferences.snapping.is_using_smart_guides);
+
+                let navigation = &mut preferences.navigation;
+                let (label, control) = rows.next_row();
+                d.gui_label(label, Some(tr_c("preferences.zoom_speed").as_c_str()));
+                let zoom_speed_text = CString::new(format!("{:.2}x", navigation.zoom_speed)).unwrap_or_default();
+                d.gui_slider_bar(control, None, Some(zoom_speed_text.as_c_str()), &mut navigation.zoom_speed, NavigationPreferences::MIN_ZOOM_SPEED, NavigationPreferences::MAX_ZOOM_SPEED);
+
+                let (label, control) = rows.next_row();
+                d.gui_label(label, Some(tr_c("preferences.pan_speed").as_c_str()));
+                let pan_speed_text = CString::new(format!("{:.0}px", navigation.pan_speed)).unwrap_or_default();
+                d.gui_slider_bar(control, None, Some(pan_speed_text.as_c_str()), &mut navigation.pan_speed, NavigationPreferences::MIN_PAN_SPEED, NavigationPreferences::MAX_PAN_SPEED);
+
+                // the zoom limits are logarithmic, like the navigator's zoom slider
+                let (label, control) = rows.next_row();
+                d.gui_label(label, Some(tr_c("preferences.min_zoom").as_c_str()));
+                let min_zoom_text = CString::new(format!("{:.0}%", navigation.min_zoom * 100.0)).unwrap_or_default();
+                let mut min_zoom_log = navigation.min_zoom.log2();
+                d.gui_slider_bar(control, None, Some(min_zoom_text.as_c_str()), &mut min_zoom_log, NavigationPreferences::SMALLEST_MIN_ZOOM.log2(), 0.0);
+                navigation.min_zoom = min_zoom_log.exp2();
+
+                let (label, control) = rows.next_row();
+                d.gui_label(label, Some(tr_c("preferences.max_zoom").as_c_str()));
+                let max_zoom_text = CString::new(format!("{:.0}%", navigation.max_zoom * 100.0)).unwrap_or_default();
+                let mut max_zoom_log = navigation.max_zoom.log2();
+                d.gui_slider_bar(control, None, Some(max_zoom_text.as_c_str()), &mut max_zoom_log, 0.0, NavigationPreferences::LARGEST_MAX_ZOOM.log2());
+                navigation.max_zoom = max_zoom_log.exp2();
+
+                let (label, control) = rows.next_row();
+                d.gui_label(label, Some(tr_c("preferences.invert_scroll").as_c_str()));
+                d.gui_check_box(Rectangle { width: control.height, ..control }, None, &mut navigation.is_scroll_inverted);
+
+                let (label, control) = rows.next_row();
+                d.gui_label(label, Some(tr_c("preferences.natural_trackpad").as_c_str()));
+                d.gui_check_box(Rectangle { width: control.height, ..control }, None, &mut navigation.is_natural_trackpad);
+            }
+
+            PreferencesPage::Keymap => {
+                let keymap = &preferences.keymap;
+                let area = rows.next_rows(Self::KEYMAP_ROWS);
+                let list = ui.list(d, WidgetId::new("preferences.keymap"), area, RowLayout::ROW_HEIGHT + RowLayout::PADDING * 0.5, EditorAction::ALL.len(), &mut self.keymap_scroll);
+                // buttons clipped out of the list at its edges shouldn't be clickable
+                let is_in_list = ui.is_mouse_within(list.viewport);
+                let viewport = list.viewport;
+                let mut d = d.begin_scissor_mode(viewport.x as i32, viewport.y as i32, viewport.width as i32, viewport.height as i32);
+                for (i, row) in list.iter() {
+                    let Some(&action) = EditorAction::ALL.get(i) else { continue };
+                    let label = Rectangle { width: RowLayout::LABEL_WIDTH, height: RowLayout::ROW_HEIGHT, ..row };
+                    let control = Rectangle { x: row.x + RowLayout::LABEL_WIDTH, width: row.width - RowLayout::LABEL_WIDTH, ..label };
+                    let name = CString::new(action.name()).unwrap_or_default();
+                    d.gui_label(label, Some(name.as_c_str()));
+                    let text = if self.rebinding == Some(i) { tr("preferences.press_a_key") } else { keymap.binding(action).label() };
+                    let text = CString::new(text).unwrap_or_default();
+                    if d.gui_button(control, Some(text.as_c_str())) && is_in_list {
+                        self.rebinding = Some(i);
+                    }
+                }
+            }
+
+            PreferencesPage::Accessibility => {
+                let accessibility = &mut preferences.accessibility;
+                let (label, control) = rows.next_row();
+                d.gui_label(label, Some(tr_c("preferences.color_blind_safe").as_c_str()));
+                d.gui_check_box(Rectangle { width: control.height, ..control }, Some(tr_c("preferences.replace_accent_colors").as_c_str()), &mut accessibility.is_color_blind_safe);
+
+                if !accessibility.is_color_blind_safe {
+                    d.gui_disable();
+                }
+                let (label, control) = rows.next_row();
+                d.gui_label(label, Some(tr_c("preferences.selection_outline").as_c_str()));
+                let thickness_text = CString::new(format!("{:.1}px", accessibility.selection_thickness)).unwrap_or_default();
+                d.gui_slider_bar(
+                    control,
+                    None,
+                    Some(thickness_text.as_c_str()),
+                    &mut accessibility.selection_thickness,
+                    AccessibilityPreferences::MIN_SELECTION_THICKNESS,
+                    AccessibilityPreferences::MAX_SELECTION_THICKNESS,
+                );
+
+                let labels = rows.next_full_row();
+                let pickers = rows.next_rows(Self::CHECKER_PICKER_ROWS);
+                let picker_width = (pickers.width - RowLayout::PADDING) * 0.5;
+                d.gui_label(Rectangle { width: picker_width, ..labels }, Some(tr_c("preferences.selection_color").as_c_str()));
+                d.gui_label(Rectangle { x: labels.x + picker_width + RowLayout::PADDING, width: picker_width, ..labels }, Some(tr_c("preferences.destructive_color").as_c_str()));
+                for (i, color) in [&mut accessibility.accent, &mut accessibility.destructive].into_iter().enumerate() {
+                    let picker = Rectangle {
+                        x: pickers.x + (picker_width + RowLayout::PADDING) * i as f32,
+                        width: picker_width - COLOR_PICKER_HUE_BAR_WIDTH,
+                        ..pickers
+                    };
+                    *color = d.gui_color_picker(picker, None, *color);
+                }
+                d.gui_enable();
+            }
+        }
+
+        dialog_footer(d, bounds)
+    }
+}
+
+/// What the user asked the [`StylePanel`] to do, besides editing the style directly
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StylePanelAction {
+    /// The panel should be closed
+    Close,
+
+    /// The style should be forked for the selected layers; see [`Editor::break_style_link`][`crate::editor::Editor::break_style_link`]
+    BreakLink,
+
+    /// The style should become the document's default style; see [`Editor::set_default_style`][`crate::editor::Editor::set_default_style`]
+    MakeDefault,
+
+    /// The color should be saved to the document's swatches
+    AddSwatch(Color),
+
+    /// The stroke should be given the width preset at this index; see [`Editor::apply_width_preset`][`crate::editor::Editor::apply_width_preset`]
+    ApplyWidthPreset(usize),
+
+    /// The stroke's width profile should be saved as a new width preset
+    SaveWidthPreset,
+}
+
+/// The style panel, for editing an editor's current style in place
+///
+/// Unlike the dialogs, edits are applied immediately, so every layer sharing the style previews them live
+#[derive(Debug, Default)]
+pub struct StylePanel {
+    /// 0 to edit the fill, 1 to edit the stroke
+    target: i32,
+    /// The shared style the user chose to edit for every layer using it, instead of forking it
+    shared_confirmed: Option<WeakStyle>,
+    /// The index of the width preset shown in the preset list
+    width_preset: i32,
+}
+
+impl StylePanel {
+    /// Width of the panel window
+    pub const WIDTH: f32 = 280.0;
+
+    /// Height of the panel window
+    pub const HEIGHT: f32 = RowLayout::window_height(19);
+
+    /// Number of rows the preview swatch spans
+    const SWATCH_ROWS: usize = 2;
+
+    /// Number of rows the color picker spans
+    const PICKER_ROWS: usize = 6;
+
+    /// Number of rows above the swatch strip: the status, preview swatch, tabs, and color picker
+    const STRIP_ROW: usize = 10;
+
+    /// Space between swatches in the swatch strip
+    const SWATCH_GAP: f32 = 2.0;
+
+    /// Locale keys of the names of [`MarkerShape::BUILT_IN`]
+    const MARKER_NAMES: [&str; 6] = [
+        "style.marker.none",
+        "style.marker.arrow",
+        "style.marker.open_arrow",
+        "style.marker.dot",
+        "style.marker.square",
+        "style.marker.bar",
+    ];
+
+    /// Width left beside each marker scale slider for its value
+    const SCALE_TEXT_WIDTH: f32 = 32.0;
+
+    /// Construct a panel editing the fill
+    pub const fn new() -> Self {
+        Self {
+            target: 0,
+            shared_confirmed: None,
+            width_preset: 0,
+        }
+    }
+
+    /// The rectangle of the panel when docked to the right edge of a window of the given width, below `top`
+    pub fn bounds(window_width: f32, top: f32) -> Rectangle {
+        Rectangle::new(window_width - Self::WIDTH, top, Self::WIDTH, Self::HEIGHT)
+    }
+
+    /// The screenspace rectangles of the first `count` swatches in the swatch strip of the panel at `bounds`
+    ///
+    /// Swatches that don't fit beside the "+" button are left out
+    pub fn swatch_rects(bounds: Rectangle, count: usize) -> impl Iterator<Item = Rectangle> {
+        let mut rows = RowLayout::new(bounds);
+        rows.next_rows(Self::STRIP_ROW);
+        let strip = rows.next_full_row();
+        let size = strip.height;
+        let fit = ((strip.width - size) / (size + Self::SWATCH_GAP)).max(0.0) as usize;
+        (0..count.min(fit)).map(move |i| Rectangle::new(strip.x + (size + Self::SWATCH_GAP) * i as f32, strip.y, size, size))
+    }
+
+    /// Draw the panel and apply edits to `style`
+    ///
+    /// `existing` is the style's reference in the document and the number of layers sharing it,
+    /// or [`None`] if it is not stored in the document yet
+    ///
+    /// Clicking one of the document's `swatches` applies its screen preview to the fill or stroke; right clicking one, handled by the caller, edits how it prints.
+    /// Choosing one of the `width_presets` applies it to the stroke
+    ///
+    /// "Break link" is only enabled when `can_break_link`. If the style is also shared by other layers,
+    /// editing is locked until the user chooses between editing it for every layer and forking it for the selection
+    pub fn draw(&mut self, d: &mut impl RaylibDraw, bounds: Rectangle, style: &mut Style, existing: Option<(&WeakStyle, usize)>, swatches: &[Swatch], width_presets: &[WidthPreset], can_break_link: bool) -> Option<StylePanelAction> {
+        if d.gui_window_box(bounds, Some(tr_c("style.title").as_c_str())) {
+            return Some(StylePanelAction::Close);
+        }
+
+        let mut rows = RowLayout::new(bounds);
+        let status = match existing {
+            None => tr("style.new"),
+            Some((_, 1)) => tr("style.used_once"),
+            Some((_, n)) => tr_format("style.shared", &[&n]),
+        };
+        let status = CString::new(status).unwrap_or_default();
+        let status_row = rows.next_full_row();
+        let is_choosing = can_break_link && existing.is_some_and(|(weak_style, users)| {
+            users > 1 && !self.shared_confirmed.as_ref().is_some_and(|confirmed| confirmed.ptr_eq(weak_style))
+        });
+        if is_choosing && let Some((weak_style, _)) = existing {
+            let quarter = status_row.width * 0.25;
+            d.gui_label(Rectangle { width: quarter * 2.0, ..status_row }, Some(status.as_c_str()));
+            let edit_button = Rectangle { x: status_row.x + quarter * 2.0, width: quarter - 1.0, ..status_row };
+            if d.gui_button(edit_button, Some(tr_c("style.edit_shared").as_c_str())) {
+                self.shared_confirmed = Some(weak_style.clone());
+            }
+            if d.gui_button(Rectangle { x: edit_button.x + quarter, ..edit_button }, Some(tr_c("style.fork").as_c_str())) {
+                return Some(StylePanelAction::BreakLink);
+            }
+            // nothing is edited until the user picks which layers the edit applies to
+            d.gui_lock();
+        } else {
+            d.gui_label(status_row, Some(status.as_c_str()));
+        }
+
+        // preview swatch
+        let swatch = rows.next_rows(Self::SWATCH_ROWS);
+        match &style.fill {
+            Pattern::Solid(color) => d.draw_rectangle_rec(swatch, *color),
+            Pattern::Texture(raster, _) => if let Some(raster) = raster.upgrade() {
+                let raster_lock = raster.lock();
+                let raster_borrow = raster_lock.borrow();
+                let texture = raster_borrow.texture();
+                // render textures are stored upside-down
+                let source = Rectangle::new(0.0, 0.0, texture.width as f32, -texture.height as f32);
+                d.draw_texture_pro(texture, source, swatch, Vector2::zero(), 0.0, Color::WHITE);
+            },
+        }
+        if let Pattern::Solid(color) = &style.stroke.pattern {
+            d.draw_rectangle_lines_ex(swatch, 3.0, *color);
+        }
+
+        let tabs = rows.next_full_row();
+        d.gui_toggle_group(Rectangle { width: tabs.width * 0.5 - 1.0, ..tabs }, Some(tr_list(&["style.fill", "style.stroke"]).as_c_str()), &mut self.target);
+
+        let picker = rows.next_rows(Self::PICKER_ROWS);
+        let pattern = if self.target == 0 { &mut style.fill } else { &mut style.stroke.pattern };
+        let current = match pattern {
+            Pattern::Solid(color) => *color,
+            // picking a color replaces the texture
+            Pattern::Texture(..) => Color::BLANK,
+        };
+        let picked = d.gui_color_picker(Rectangle { width: picker.width - COLOR_PICKER_HUE_BAR_WIDTH, ..picker }, None, current);
+        if picked != current {
+            *pattern = Pattern::Solid(picked);
+        }
+
+        // swatch strip, with a button at the end to save the current color
+        let strip = rows.next_full_row();
+        let add_button = Rectangle { x: strip.x + strip.width - strip.height, width: strip.height, ..strip };
+        if !matches!(pattern, Pattern::Solid(_)) {
+            d.gui_disable();
+        }
+        let is_add_clicked = d.gui_button(add_button, Some(c"+"));
+        d.gui_enable();
+        for (swatch, chip) in swatches.iter().zip(Self::swatch_rects(bounds, swatches.len())) {
+            let preview = swatch.color.preview();
+            if d.gui_button(chip, None) {
+                *pattern = Pattern::Solid(preview);
+            }
+            d.draw_rectangle_rec(Rectangle::new(chip.x + 2.0, chip.y + 2.0, chip.width - 4.0, chip.height - 4.0), preview);
+            if let SwatchColor::Spot { .. } = swatch.color {
+                // marked so spot colors aren't mistaken for process colors with the same preview
+                let (x, y) = ((chip.x + chip.width - 6.0) as i32, (chip.y + chip.height - 6.0) as i32);
+                d.draw_circle(x, y, 3.0, Color::WHITE);
+                d.draw_circle_lines(x, y, 3.0, Color::BLACK);
+            }
+        }
+        if is_add_clicked && let Pattern::Solid(color) = pattern {
+            return Some(StylePanelAction::AddSwatch(*color));
+        }
+
+        let (label, control) = rows.next_row();
+        d.gui_label(label, Some(tr_c("style.fill_rule").as_c_str()));
+        let mut rule = i32::from(style.fill_rule == FillRule::EvenOdd);
+        d.gui_toggle_group(Rectangle { width: control.width * 0.5 - 1.0, ..control }, Some(tr_list(&["style.fill_rule.non_zero", "style.fill_rule.even_odd"]).as_c_str()), &mut rule);
+        style.fill_rule = if rule == 1 { FillRule::EvenOdd } else { FillRule::NonZero };
+
+        // how a texture continues past its edges; placed with the select tool's handles
+        let (label, control) = rows.next_row();
+        d.gui_label(label, Some(tr_c("style.texture_wrap").as_c_str()));
+        let pattern = if self.target == 0 { &mut style.fill } else { &mut style.stroke.pattern };
+        let mut wrap = match pattern {
+            Pattern::Texture(_, transform) => TextureWrap::ALL.iter().position(|&wrap| wrap == transform.wrap).unwrap_or_default() as i32,
+            Pattern::Solid(_) => {
+                d.gui_disable();
+                1
+            }
+        };
+        let names = tr_list(&["style.texture_wrap.clamp", "style.texture_wrap.repeat", "style.texture_wrap.mirror"]);
+        d.gui_toggle_group(Rectangle { width: control.width / 3.0 - 1.0, ..control }, Some(names.as_c_str()), &mut wrap);
+        d.gui_enable();
+        if let Pattern::Texture(_, transform) = pattern
+            && let Some(&new_wrap) = usize::try_from(wrap).ok().and_then(|i| TextureWrap::ALL.get(i))
+        {
+            transform.wrap = new_wrap;
+        }
+
+        let trim = &mut style.stroke.trim;
+        for (label_text, value, min) in [
+            ("style.trim_start", &mut trim.start, 0.0),
+            ("style.trim_end", &mut trim.end, 0.0),
+            ("style.trim_offset", &mut trim.offset, -1.0),
+        ] {
+            let (label, control) = rows.next_row();
+            d.gui_label(label, Some(tr_c(label_text).as_c_str()));
+            let text = CString::new(format!("{:.0}%", *value * 100.0)).unwrap_or_default();
+            d.gui_slider_bar(control, None, Some(text.as_c_str()), value, min, 1.0);
+        }
+
+        for (label_text, marker) in [
+            ("style.start_marker", &mut style.stroke.markers.start),
+            ("style.end_marker", &mut style.stroke.markers.end),
+        ] {
+            let (label, control) = rows.next_row();
+            d.gui_label(label, Some(tr_c(label_text).as_c_str()));
+            let mut names = Self::MARKER_NAMES.to_vec();
+            // custom shapes can't be picked from the list, only kept
+            if let MarkerShape::Custom(_) = marker.shape {
+                names.push("style.marker.custom");
+            }
+            let mut active = MarkerShape::BUILT_IN.iter()
+                .position(|shape| *shape == marker.shape)
+                .unwrap_or(MarkerShape::BUILT_IN.len()) as i32;
+            d.gui_combo_box(Rectangle { width: control.width * 0.5 - 1.0, ..control }, Some(tr_list(&names).as_c_str()), &mut active);
+            if let Some(shape) = usize::try_from(active).ok().and_then(|i| MarkerShape::BUILT_IN.get(i))
+                && *shape != marker.shape
+            {
+                marker.shape = shape.clone();
+            }
+            let slider = Rectangle { x: control.x + control.width * 0.5, width: control.width * 0.5 - Self::SCALE_TEXT_WIDTH, ..control };
+            let text = CString::new(format!("{:.1}x", marker.scale)).unwrap_or_default();
+            d.gui_slider_bar(slider, None, Some(text.as_c_str()), &mut marker.scale, Marker::MIN_SCALE, Marker::MAX_SCALE);
+        }
+
+        let (label, control) = rows.next_row();
+        d.gui_label(label, Some(tr_c("style.width_preset").as_c_str()));
+        let save_width = control.height * 2.0;
+        let names = CString::new(width_presets.iter().map(|preset| preset.name.as_str()).collect::<Vec<_>>().join(";")).unwrap_or_default();
+        let previous = self.width_preset;
+        d.gui_combo_box(Rectangle { width: control.width - save_width - 1.0, ..control }, Some(names.as_c_str()), &mut self.width_preset);
+        let is_save_clicked = d.gui_button(Rectangle { x: control.x + control.width - save_width, width: save_width, ..control }, Some(tr_c("style.save_width_preset").as_c_str()));
+        if is_save_clicked {
+            return Some(StylePanelAction::SaveWidthPreset);
+        }
+        if self.width_preset != previous
+            && let Ok(index) = usize::try_from(self.width_preset)
+        {
+            return Some(StylePanelAction::ApplyWidthPreset(index));
+        }
+
+        let (padding, height) = (RowLayout::PADDING, RowLayout::ROW_HEIGHT);
+        d.gui_unlock();
+        let button_width = (bounds.width - padding * 3.0) * 0.5;
+        let button = Rectangle::new(bounds.x + padding, bounds.y + bounds.height - padding - height, button_width, height);
+        if !can_break_link {
+            d.gui_disable();
+        }
+        let is_break_link_clicked = d.gui_button(button, Some(tr_c("style.break_link").as_c_str()));
+        d.gui_enable();
+        if can_break_link && is_break_link_clicked {
+            return Some(StylePanelAction::BreakLink);
+        }
+        if d.gui_button(Rectangle { x: button.x + button_width + padding, ..button }, Some(tr_c("style.make_default").as_c_str())) {
+            return Some(StylePanelAction::MakeDefault);
+        }
+        None
+    }
+}
+
+/// What the user did in the [`WidthPanel`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WidthPanelAction {
+    /// The panel should be closed
+    Close,
+
+    /// The width profile was changed
+    Edit,
+}
+
+/// The width profile panel, for shaping the current style's stroke width precisely as an envelope graph
+///
+/// t runs along the X axis, with the inner thickness plotted above the center line and the outer thickness below it.
+/// Like the [`StylePanel`], edits are applied immediately to the profile, shared with every style using it
+#[derive(Debug)]
+pub struct WidthPanel {
+    /// The index of the control whose speed handles and fields are shown
+    selected: usize,
+    /// Whether dragging one side of a control moves the other side with it
+    is_linked: bool,
+    /// The thickness at the top and bottom edges of the graph
+    ///
+    /// Only grows, so the graph doesn't rescale under the mouse mid-drag
+    thickness_range: f32,
+}
+
+impl Default for WidthPanel {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WidthPanel {
+    /// Width of the panel window
+    pub const WIDTH: f32 = 320.0;
+
+    /// Height of the panel window
+    pub const HEIGHT: f32 = RowLayout::window_height(10);
+
+    /// Number of rows the graph spans
+    const GRAPH_ROWS: usize = 6;
+
+    /// Number of points along t the envelope is drawn through
+    const ENVELOPE_SAMPLES: u16 = 64;
+
+    /// Radius of the draggable points on the graph
+    const HANDLE_RADIUS: f32 = 5.0;
+
+    /// How far the speed handles reach from their control, as a fraction of the graph's t range
+    const HANDLE_REACH: f32 = 0.08;
+
+    /// Range of thicknesses that can be typed
+    const THICKNESS_SPEC: NumberSpec = NumberSpec::new(0.0, 1000.0, 0.1);
+
+    /// Construct a panel with the sides of each control linked
+    pub const fn new() -> Self {
+        Self {
+            selected: 0,
+            is_linked: true,
+            thickness_range: 0.0,
+        }
+    }
+
+    /// The rectangle of the panel when docked to the right edge of a window of the given width, below `top`
+    pub fn bounds(window_width: f32, top: f32) -> Rectangle {
+        Rectangle::new(window_width - Self::WIDTH, top, Self::WIDTH, Self::HEIGHT)
+    }
+
+    /// Draw the panel and apply edits to `profile`
+    ///
+    /// `profile` is [`None`] if the stroke has no width profile to edit.
+    /// A constant profile can be made variable, spreading it over `length` segments
+    pub fn draw(&mut self, d: &mut impl RaylibDraw, ui: &mut Ui, bounds: Rectangle, profile: Option<&mut WidthProfile>, length: f32) -> Option<WidthPanelAction> {
+        if d.gui_window_box(bounds, Some(tr_c("width.title").as_c_str())) {
+            return Some(WidthPanelAction::Close);
+        }
+
+        let mut rows = RowLayout::new(bounds);
+        let Some(profile) = profile else {
+            d.gui_label(rows.next_full_row(), Some(tr_c("width.none").as_c_str()));
+            return None;
+        };
+        let mut is_edited = false;
+
+        if let WidthProfile::Constant { inner, outer } = &mut *profile {
+            for (label_text, value) in [("width.inner", &mut *inner), ("width.outer", &mut *outer)] {
+                let (label, control) = rows.next_row();
+                d.gui_label(label, Some(tr_c(label_text).as_c_str()));
+                is_edited |= ui.number_field(d, WidgetId::new(label_text), control, value, &Self::THICKNESS_SPEC);
+            }
+            let (inner, outer) = (*inner, *outer);
+            if d.gui_button(rows.next_full_row(), Some(tr_c("width.make_variable").as_c_str())) {
+                let control = |t| WidthProfileControl { t, inner: WidthProfileVertex::flat(inner), outer: WidthProfileVertex::flat(outer) };
+                *profile = WidthProfile::Variable(vec![control(0.0), control(length.max(1.0))]);
+                self.selected = 0;
+                is_edited = true;
+            }
+            return is_edited.then_some(WidthPanelAction::Edit);
+        }
+
+        let theme = *ui.theme();
+        let graph = rows.next_rows(Self::GRAPH_ROWS);
+        let t_span = match &*profile {
+            WidthProfile::Variable(controls) => controls.iter().map(|control| control.t).fold(length, f32::max),
+            WidthProfile::Constant { .. } => length,
+        }.max(f32::EPSILON);
+        self.thickness_range = self.thickness_range.max(profile.max_thickness() * 1.25).max(1.0);
+        let range = self.thickness_range;
+        let mid = graph.y + graph.height * 0.5;
+        let half = graph.height * 0.5 - Self::HANDLE_RADIUS;
+        let to_x = |t: f32| graph.x + t / t_span * graph.width;
+        let to_t = |x: f32| (x - graph.x) / graph.width * t_span;
+        let to_offset = |thick: f32| thick / range * half;
+        let to_thick = |offset: f32| offset / half * range;
+
+        d.draw_rectangle_rec(graph, theme.color_background);
+        d.draw_line_v(Vector2::new(graph.x, mid), Vector2::new(graph.x + graph.width, mid), theme.color_panel_edge);
+        let envelope = (0..=Self::ENVELOPE_SAMPLES)
+            .map(|i| {
+                let t = t_span * f32::from(i) / f32::from(Self::ENVELOPE_SAMPLES);
+                let (inner, outer) = profile.thickness_at(t);
+                (to_x(t), to_offset(inner), to_offset(outer))
+            })
+            .collect::<Vec<_>>();
+        for pair in envelope.windows(2) {
+            let [(x0, inner0, outer0), (x1, inner1, outer1)] = [pair[0], pair[1]];
+            d.draw_line_v(Vector2::new(x0, mid - inner0), Vector2::new(x1, mid - inner1), theme.color_foreground);
+            d.draw_line_v(Vector2::new(x0, mid + outer0), Vector2::new(x1, mid + outer1), theme.color_foreground);
+        }
+
+        let WidthProfile::Variable(controls) = profile else { unreachable!("constant profiles should have returned above") };
+        let count = controls.len();
+        self.selected = self.selected.min(count.saturating_sub(1));
+        let upper = Rectangle { height: mid - graph.y, ..graph };
+        let lower = Rectangle { y: mid, ..upper };
+        for i in 0..count {
+            let min_t = i.checked_sub(1).and_then(|prev| controls.get(prev)).map_or(0.0, |prev| prev.t);
+            let max_t = controls.get(i.saturating_add(1)).map_or(t_span, |next| next.t);
+            let control = &mut controls[i];
+            let mut moved = None;
+            for (side, sign, area) in [(0, -1.0, upper), (1, 1.0, lower)] {
+                let vertex = if side == 0 { &mut control.inner } else { &mut control.outer };
+                let id = WidgetId::new("width.point").child(i.saturating_mul(2).saturating_add(side));
+                let mut position = Vector2::new(to_x(control.t), mid + sign * to_offset(vertex.thick));
+                // controls can't pass their neighbors, so they stay sorted by t
+                let area = Rectangle { x: to_x(min_t), width: to_x(max_t) - to_x(min_t), ..area };
+                if ui.drag_handle(d, id, &mut position, Self::HANDLE_RADIUS, area) {
+                    control.t = to_t(position.x);
+                    vertex.thick = to_thick((position.y - mid) * sign).max(0.0);
+                    moved = Some(vertex.thick);
+                }
+                if ui.is_held(id) {
+                    self.selected = i;
+                }
+            }
+            if let Some(thick) = moved {
+                if self.is_linked {
+                    control.inner.thick = thick;
+                    control.outer.thick = thick;
+                }
+                is_edited = true;
+            }
+        }
+
+        // speed handles of the selected control
+        if let Some(control) = controls.get_mut(self.selected) {
+            let reach = t_span * Self::HANDLE_REACH;
+            let t = control.t;
+            for (side, sign) in [(0, -1.0), (1, 1.0)] {
+                let vertex = if side == 0 { &mut control.inner } else { &mut control.outer };
+                let point = Vector2::new(to_x(t), mid + sign * to_offset(vertex.thick));
+                for (k, direction) in [(0, -1.0), (1, 1.0)] {
+                    let speed = if k == 0 { &mut vertex.speed_in } else { &mut vertex.speed_out };
+                    let thick = vertex.thick + direction * *speed * reach;
+                    let mut position = Vector2::new(to_x(t + direction * reach), mid + sign * to_offset(thick));
+                    d.draw_line_v(point, position, theme.color_accent);
+                    let id = WidgetId::new("width.handle").child(side).child(k);
+                    if ui.drag_handle(d, id, &mut position, Self::HANDLE_RADIUS * 0.75, graph) {
+                        *speed = (to_thick((position.y - mid) * sign) - vertex.thick) * direction / reach;
+                        is_edited = true;
+                    }
+                }
+            }
+        }
+
+        let row = rows.next_full_row();
+        let third = row.width / 3.0;
+        d.gui_check_box(Rectangle { width: row.height, ..row }, Some(tr_c("width.link_sides").as_c_str()), &mut self.is_linked);
+        let add_button = Rectangle { x: row.x + third, width: third - 1.0, ..row };
+        if d.gui_button(add_button, Some(tr_c("width.add_point").as_c_str())) {
+            // halfway to the next control, or the previous one if the last control is selected
+            let neighbor = controls.get(self.selected.saturating_add(1)).or_else(|| self.selected.checked_sub(1).and_then(|prev| controls.get(prev)));
+            let new_control = match (controls.get(self.selected), neighbor) {
+                (Some(a), Some(b)) => WidthProfileControl {
+                    t: (a.t + b.t) * 0.5,
+                    inner: WidthProfileVertex::flat((a.inner.thick + b.inner.thick) * 0.5),
+                    outer: WidthProfileVertex::flat((a.outer.thick + b.outer.thick) * 0.5),
+                },
+                (Some(a), None) => WidthProfileControl { t: length.max(a.t), ..*a },
+                (None, _) => WidthProfileControl::new_even(0.0, WidthProfileVertex::flat(1.0)),
+            };
+            let index = controls.partition_point(|control| control.t <= new_control.t);
+            controls.insert(index, new_control);
+            self.selected = index;
+            is_edited = true;
+        }
+        if count <= 1 {
+            d.gui_disable();
+        }
+        let is_remove_clicked = d.gui_button(Rectangle { x: add_button.x + third, ..add_button }, Some(tr_c("width.remove_point").as_c_str()));
+        d.gui_enable();
+        if is_remove_clicked && count > 1 && self.selected < controls.len() {
+            controls.remove(self.selected);
+            self.selected = self.selected.min(controls.len().saturating_sub(1));
+            is_edited = true;
+        }
+
+        let min_t = self.selected.checked_sub(1).and_then(|prev| controls.get(prev)).map_or(0.0, |prev| prev.t);
+        let max_t = controls.get(self.selected.saturating_add(1)).map_or(f32::MAX, |next| next.t);
+        if let Some(control) = controls.get_mut(self.selected) {
+            let t_spec = NumberSpec::new(min_t, max_t, 0.05);
+            for (label_text, value, spec) in [
+                ("width.position", &mut control.t, &t_spec),
+                ("width.inner", &mut control.inner.thick, &Self::THICKNESS_SPEC),
+                ("width.outer", &mut control.outer.thick, &Self::THICKNESS_SPEC),
+            ] {
+                let (label, field) = rows.next_row();
+                d.gui_label(label, Some(tr_c(label_text).as_c_str()));
+                is_edited |= ui.number_field(d, WidgetId::new(label_text), field, value, spec);
+            }
+        }
+
+        is_edited.then_some(WidthPanelAction::Edit)
+    }
+}
+
+/// What the user did in the [`ArtboardPanel`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ArtboardPanelAction {
+    /// The panel should be closed
+    Close,
+
+    /// The selected artboards should all be given this worldspace size
+    Resize {
+        width: f32,
+        height: f32,
+    },
+
+    /// The selected artboards should be lined up
+    Align(ArtboardAlignment),
+
+    /// The selected artboards should be spaced out evenly, horizontally or vertically
+    Distribute {
+        is_vertical: bool,
+    },
+
+    /// The export dialog should be opened for the selected artboards
+    Export,
+}
+
+/// The artboards panel, for selecting several artboards and editing them together
+///
+/// The selection belongs to the [editor][`crate::editor::Editor::selected_artboards`], so it stays with its document when switching tabs
+#[derive(Debug)]
+pub struct ArtboardPanel {
+    /// How far the artboard list is scrolled down, in pixels
+    scroll: f32,
+    /// The size the selected artboards are resized to, in worldspace pixels
+    width: f32,
+    height: f32,
+}
+
+impl Default for ArtboardPanel {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ArtboardPanel {
+    /// Width of the panel window
+    pub const WIDTH: f32 = 320.0;
+
+    /// Height of the panel window
+    pub const HEIGHT: f32 = RowLayout::window_height(13);
+
+    /// Number of rows the scrolling list of artboards spans
+    const LIST_ROWS: usize = 6;
+
+    /// Construct a panel with an empty size, filled in once an artboard is selected
+    pub const fn new() -> Self {
+        Self {
+            scroll: 0.0,
+            width: 0.0,
+            height: 0.0,
+        }
+    }
+
+    /// The rectangle of the panel when docked to the right edge of a window of the given width, below `top`
+    pub fn bounds(window_width: f32, top: f32) -> Rectangle {
+        Rectangle::new(window_width - Self::WIDTH, top, Self::WIDTH, Self::HEIGHT)
+    }
+
+    /// Draw the panel, selecting and deselecting artboards of `document` in `selected`
+    ///
+    /// Edits to the selected artboards are returned rather than applied, since the document isn't borrowed mutably
+    pub fn draw(&mut self, d: &mut impl RaylibDraw, ui: &mut Ui, bounds: Rectangle, document: &Document, selected: &mut Vec<usize>) -> Option<ArtboardPanelAction> {
+        if d.gui_window_box(bounds, Some(tr_c("artboards.title").as_c_str())) {
+            return Some(ArtboardPanelAction::Close);
+        }
+
+        let mut rows = RowLayout::new(bounds);
+        let prev_selected = selected.clone();
+        let area = rows.next_rows(Self::LIST_ROWS);
+        let list = ui.list(d, WidgetId::new("artboards.list"), area, RowLayout::ROW_HEIGHT + RowLayout::PADDING * 0.5, document.artboards.len(), &mut self.scroll);
+        // toggles clipped out of the list at its edges shouldn't be clickable
+        let is_in_list = ui.is_mouse_within(list.viewport);
+        {
+            let viewport = list.viewport;
+            let mut d = d.begin_scissor_mode(viewport.x as i32, viewport.y as i32, viewport.width as i32, viewport.height as i32);
+            for (i, row) in list.iter() {
+                let Some(artboard) = document.artboards.get(i) else { continue };
+                let was_selected = selected.contains(&i);
+                let mut is_selected = was_selected;
+                let name = CString::new(artboard.name.as_str()).unwrap_or_default();
+                d.gui_toggle(Rectangle { height: RowLayout::ROW_HEIGHT, ..row }, Some(name.as_c_str()), &mut is_selected);
+                if is_selected != was_selected && is_in_list {
+                    if is_selected {
+                        selected.push(i);
+                    } else {
+                        selected.retain(|&index| index != i);
+                    }
+                }
+            }
+        }
+
+        let row = rows.next_full_row();
+        let half = Rectangle { width: (row.width - RowLayout::PADDING) * 0.5, ..row };
+        if d.gui_button(half, Some(tr_c("artboards.select_all").as_c_str())) {
+            *selected = (0..document.artboards.len()).collect();
+        }
+        if d.gui_button(Rectangle { x: half.x + half.width + RowLayout::PADDING, ..half }, Some(tr_c("artboards.select_none").as_c_str())) {
+            selected.clear();
+        }
+
+        // the size fields start out showing the first artboard selected
+        if *selected != prev_selected
+            && let Some(artboard) = selected.first().and_then(|&index| document.artboards.get(index))
+        {
+            (self.width, self.height) = (artboard.rect.width, artboard.rect.height);
+        }
+        let count = selected.iter().filter(|&&index| index < document.artboards.len()).count();
+        let info = &document.info;
+        let size_spec = NumberSpec::new(1.0, 16384.0, 1.0).with_unit(info.units, info.dpi);
+        for (label_text, value) in [("artboards.width", &mut self.width), ("artboards.height", &mut self.height)] {
+            let (label, control) = rows.next_row();
+            d.gui_label(label, Some(tr_c(label_text).as_c_str()));
+            ui.number_field(d, WidgetId::new(label_text), control, value, &size_spec);
+        }
+
+        let mut action = None;
+        if count == 0 {
+            d.gui_disable();
+        }
+        if d.gui_button(rows.next_full_row(), Some(tr_c("artboards.resize").as_c_str())) && !count == 0 {
+            action = Some(ArtboardPanelAction::Resize { width: self.width, height: self.height });
+        }
+        d.gui_enable();
+
+        // aligning a single artboard would line it up with itself
+        if count < 2 {
+            d.gui_disable();
+        }
+        let row = rows.next_full_row();
+        let button_width = row.width / ArtboardAlignment::ALL.len() as f32;
+        for (i, alignment) in ArtboardAlignment::ALL.into_iter().enumerate() {
+            let button = Rectangle { x: row.x + button_width * i as f32, width: button_width - 1.0, ..row };
+            if d.gui_button(button, Some(tr_c(alignment.key()).as_c_str())) && count >= 2 {
+                action = Some(ArtboardPanelAction::Align(alignment));
+            }
+        }
+        d.gui_enable();
+
+        // there are no gaps to even out between fewer than three artboards
+        if count < 3 {
+            d.gui_disable();
+        }
+        let row = rows.next_full_row();
+        let half = Rectangle { width: (row.width - RowLayout::PADDING) * 0.5, ..row };
+        for (i, (label_text, is_vertical)) in [("artboards.distribute_x", false), ("artboards.distribute_y", true)].into_iter().enumerate() {
+            let button = Rectangle { x: half.x + (half.width + RowLayout::PADDING) * i as f32, ..half };
+            if d.gui_button(button, Some(tr_c(label_text).as_c_str())) && count >= 3 {
+                action = Some(ArtboardPanelAction::Distribute { is_vertical });
+            }
+        }
+        d.gui_enable();
+
+        if count == 0 {
+            d.gui_disable();
+        }
+        if d.gui_button(rows.next_full_row(), Some(tr_c("artboards.export").as_c_str())) && !count == 0 {
+            action = Some(ArtboardPanelAction::Export);
+        }
+        d.gui_enable();
+
+        action
+    }
+}
+
+/// What the user did in the [`LayerPanel`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum LayerPanelAction {
+    /// The panel should be closed
+    Close,
+
+    /// The layer at this path should become the selection
+    Select(Vec<usize>),
+
+    /// The document's broken layers should be [repaired][`crate::document::Document::repair_layers`]
+    Repair,
+}
+
+/// A thumbnail cached by the [`LayerPanel`]
+#[derive(Debug)]
+struct LayerThumbnail {
+    texture: RenderTexture2D,
+
+    /// The [fingerprint][`layer_fingerprint`] of the layer when the thumbnail was rendered
+    fingerprint: u64,
+}
+
+/// The layers panel, listing the document's layers from the top down with a thumbnail beside each name
+///
+/// Thumbnails are cached by layer path, and only re-rendered while in view once their layer looks different
+#[derive(Debug)]
+pub struct LayerPanel {
+    /// How far the layer list is scrolled down, in pixels
+    scroll: f32,
+
+    /// The cached thumbnails, by layer path
+    thumbnails: HashMap<Vec<usize>, LayerThumbnail>,
+
+    /// The index of the editor the thumbnails were rendered from
+    editor_index: Option<u32>,
+}
+
+impl Default for LayerPanel {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LayerPanel {
+    /// Width of the panel window
+    pub const WIDTH: f32 = 240.0;
+
+    /// Height of the panel window
+    pub const HEIGHT: f32 = RowLayout::window_height(Self::LIST_ROWS);
+
+    /// Width and height of each thumbnail, in pixels
+    pub const THUMBNAIL_SIZE: u32 = 32;
+
+    /// Number of rows the scrolling list of layers spans
+    const LIST_ROWS: usize = 10;
+
+    /// Height of each layer in the list
+    const ITEM_HEIGHT: f32 = Self::THUMBNAIL_SIZE as f32 + RowLayout::PADDING * 0.5;
+
+    /// How far each level of nesting in groups is indented
+    const INDENT: f32 = 12.0;
+
+    /// Construct a panel without any thumbnails
+    pub fn new() -> Self {
+        Self {
+            scroll: 0.0,
+            thumbnails: HashMap::new(),
+            editor_index: None,
+        }
+    }
+
+    /// The rectangle of the panel with its bottom-left corner at (`left`, `bottom`)
+    pub fn bounds(left: f32, bottom: f32) -> Rectangle {
+        Rectangle::new(left, bottom - Self::HEIGHT, Self::WIDTH, Self::HEIGHT)
+    }
+
+    /// Re-render the thumbnails in view whose layers look different than when they were rendered
+    ///
+    /// `snapshot` is of `editor`'s layers, and `bounds` is where the panel will be drawn
+    pub fn refresh(&mut self, rl: &mut RaylibHandle, thread: &RaylibThread, bounds: Rectangle, editor: &Editor, editor_index: u32, snapshot: &DocumentSnapshot) -> Result<(), String> {
+        if self.editor_index != Some(editor_index) {
+            self.thumbnails.clear();
+            self.editor_index = Some(editor_index);
+        }
+        let document = &editor.document;
+        let paths = layer_paths(&document.layers);
+        self.thumbnails.retain(|path, _| document.layer_at(path).is_some());
+
+        let area = RowLayout::new(bounds).next_rows(Self::LIST_ROWS);
+        let visible = ListRows::new(area, Self::ITEM_HEIGHT, paths.len(), self.scroll).visible;
+        for path in paths.get(visible).unwrap_or_default() {
+            let Some(layer) = document.layer_at(path) else { continue };
+            let fingerprint = layer_fingerprint(layer, snapshot);
+            if self.thumbnails.get(path).is_some_and(|thumbnail| thumbnail.fingerprint == fingerprint) {
+                continue;
+            }
+            let bounds = layer.control_bounds().unwrap_or(Rectangle::new(0.0, 0.0, 1.0, 1.0));
+            let texture = match &layer.content {
+                // drawn a few pixels across, a large raster would only be sampled at a few of its pixels
+                LayerContent::Image { raster, rect } if let Some(raster) = raster.upgrade() => {
+                    let small = RasterStore::downsample(rl, thread, &raster.lock().borrow(), Self::THUMBNAIL_SIZE)?;
+                    let tint = if layer.is_template { Layer::TEMPLATE_TINT } else { Color::WHITE };
+                    library::render_thumbnail(rl, thread, Self::THUMBNAIL_SIZE, bounds, |d| {
+                        // render textures are stored upside-down
+                        let source = Rectangle::new(0.0, 0.0, small.texture.width as f32, -small.texture.height as f32);
+                        d.draw_texture_pro(&small, source, *rect, Vector2::zero(), 0.0, tint);
+                    })?
+                }
+                _ => library::render_thumbnail(rl, thread, Self::THUMBNAIL_SIZE, bounds, |d| {
+                    layer.draw_templates(d, Color::WHITE, SampleQuality::Low, snapshot);
+                    layer.draw(d, Color::WHITE, SampleQuality::Low, snapshot);
+                })?,
+            };
+            self.thumbnails.insert(path.clone(), LayerThumbnail { texture, fingerprint });
+        }
+        Ok(())
+    }
+
+    /// Draw the panel, listing `editor`'s layers beside their thumbnails
+    ///
+    /// [Broken][`Layer::is_broken`] layers have their thumbnail outlined in the destructive color.
+    /// Selecting a layer is returned rather than applied, since the editor isn't borrowed mutably
+    pub fn draw(&mut self, d: &mut impl RaylibDraw, ui: &mut Ui, bounds: Rectangle, theme: &EngineTheme, editor: &Editor) -> Option<LayerPanelAction> {
+        if d.gui_window_box(bounds, Some(tr_c("layers.title").as_c_str())) {
+            return Some(LayerPanelAction::Close);
+        }
+
+        let document = &editor.document;
+        let paths = layer_paths(&document.layers);
+        let selected = match editor.selection() {
+            Selection::Layers(paths) => paths.as_slice(),
+            _ => &[],
+        };
+        let mut rows = RowLayout::new(bounds);
+        let area = rows.next_rows(Self::LIST_ROWS);
+        let list = ui.list(d, WidgetId::new("layers.list"), area, Self::ITEM_HEIGHT, paths.len(), &mut self.scroll);
+        // toggles clipped out of the list at its edges shouldn't be clickable
+        let is_in_list = ui.is_mouse_within(list.viewport);
+        let size = Self::THUMBNAIL_SIZE as f32;
+        // render textures are stored upside-down
+        let source = Rectangle::new(0.0, 0.0, size, -size);
+        let mut action = None;
+        {
+            let viewport = list.viewport;
+            let mut d = d.begin_scissor_mode(viewport.x as i32, viewport.y as i32, viewport.width as i32, viewport.height as i32);
+            for (i, row) in list.iter() {
+                let Some(path) = paths.get(i) else { continue };
+                let Some(layer) = document.layer_at(path) else { continue };
+                let indent = Self::INDENT * path.len().saturating_sub(1) as f32;
+                let thumbnail = Rectangle::new(row.x + indent, row.y, size, size);
+                d.draw_rectangle_rec(thumbnail, document.paper_color);
+                if let Some(cached) = self.thumbnails.get(path) {
+                    d.draw_texture_rec(&cached.texture, source, Vector2::new(thumbnail.x, thumbnail.y), Color::WHITE);
+                }
+                let edge = if layer.is_broken() { theme.color_destructive } else { theme.color_panel_edge };
+                d.draw_rectangle_lines_ex(thumbnail, 1.0, edge);
+
+                let name_x = thumbnail.x + size + RowLayout::PADDING * 0.5;
+                let toggle = Rectangle::new(name_x, row.y + (size - RowLayout::ROW_HEIGHT) * 0.5, row.x + row.width - name_x, RowLayout::ROW_HEIGHT);
+                let was_selected = selected.contains(path);
+                let mut is_selected = was_selected;
+                let name = CString::new(layer.name.as_str()).unwrap_or_default();
+                d.gui_toggle(toggle, Some(name.as_c_str()), &mut is_selected);
+                if is_selected && !was_selected && is_in_list {
+                    action = Some(LayerPanelAction::Select(path.clone()));
+                }
+            }
+        }
+
+        let broken = document.broken_layer_count();
+        if broken == 0 {
+            d.gui_disable();
+        }
+        if d.gui_button(rows.next_full_row(), Some(tr_c("action.repair_layers").as_c_str())) && broken > 0 {
+            action = Some(LayerPanelAction::Repair);
+        }
+        d.gui_enable();
+
+        action
+    }
+}
+
+/// The path of every layer in `layers` from the top of the stack down, each group followed by its contents
+fn layer_paths(layers: &[Layer]) -> Vec<Vec<usize>> {
+    fn visit(layers: &[Layer], prefix: &mut Vec<usize>, out: &mut Vec<Vec<usize>>) {
+        for (i, layer) in layers.iter().enumerate().rev() {
+            prefix.push(i);
+            out.push(prefix.clone());
+            if let LayerContent::Group(group) = &layer.content {
+                visit(&group.layers, prefix, out);
+            }
+            prefix.pop();
+        }
+    }
+
+    let mut paths = Vec::new();
+    visit(layers, &mut Vec::new(), &mut paths);
+    paths
+}
+
+/// A hash of everything that changes how `layer` looks in `snapshot`, to tell when its thumbnail is out of date
+fn layer_fingerprint(layer: &Layer, snapshot: &DocumentSnapshot) -> u64 {
+    fn visit(layer: &Layer, snapshot: &DocumentSnapshot, hasher: &mut DefaultHasher) {
+        // styles can't be compared, but their debug output covers every field
+        if let Some(style) = snapshot.style(&layer.style) {
+            format!("{style:?}").hash(hasher);
+        }
+        match &layer.content {
+            LayerContent::Group(group) => {
+                group.layers.len().hash(hasher);
+                for layer in &group.layers {
+                    visit(layer, snapshot, hasher);
+                }
+            }
+
+            LayerContent::Image { raster, rect } => {
+                raster.as_ptr().hash(hasher);
+                [rect.x, rect.y, rect.width, rect.height].map(f32::to_bits).hash(hasher);
+            }
+
+            LayerContent::Paint(paint) => {
+                paint.revision().hash(hasher);
+                [paint.origin.x, paint.origin.y].map(f32::to_bits).hash(hasher);
+            }
+
+            LayerContent::Curve(_) | LayerContent::Compound(_) | LayerContent::Connector(_) | LayerContent::Rectangle(_) => {
+                for curve in layer.curves().iter().filter_map(|curve| snapshot.curve(curve)) {
+                    curve.is_closed.hash(hasher);
+                    for point in &curve.points {
+                        [point.c_in, point.p, point.c_out].map(|v| [v.x.to_bits(), v.y.to_bits()]).hash(hasher);
+                    }
+                }
+            }
+        }
+    }
+
+    let mut hasher = DefaultHasher::new();
+    visit(layer, snapshot, &mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::layer::Group;
+    use super::*;
+
+    #[test]
+    fn test_layer_paths() {
+        let layer = |name: &str, content| Layer { name: name.to_owned(), content, style: WeakStyle::new(), export: None, is_template: false };
+        let empty = || LayerContent::Group(Group { layers: Vec::new() });
+        let layers = [
+            layer("bottom", empty()),
+            layer("group", LayerContent::Group(Group { layers: vec![layer("inner bottom", empty()), layer("inner top", empty())] })),
+            layer("top", empty()),
+        ];
+        assert_eq!(layer_paths(&layers), [vec![2], vec![1], vec![1, 1], vec![1, 0], vec![0]]);
+        assert!(layer_paths(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_layer_fingerprint() {
+        let mut editor = Editor::new_default(Document::new("thumbnails".to_owned()));
+        editor.add_curve_layer("square".to_owned(), crate::make_curve!((0,0) -> (10,0) -> (10,10) -> (0,10) -> cycle));
+        editor.add_curve_layer("line".to_owned(), crate::make_curve!((0,0) -> (100,0)));
+        let layers = &editor.document.layers;
+        let mut snapshot = DocumentSnapshot::of(layers);
+        let fingerprints = |snapshot: &DocumentSnapshot| [0, 1].map(|i| layer_fingerprint(&layers[i], snapshot));
+        let first = fingerprints(&snapshot);
+        assert_ne!(first[0], first[1]);
+        assert_eq!(fingerprints(&DocumentSnapshot::of(layers)), first, "a layer that hasn't changed should keep its fingerprint");
+
+        layers[0].curves()[0].upgrade().unwrap().lock().borrow_mut().translate(crate::na::Vector2::new(5.0, 0.0));
+        snapshot.update(layers);
+        let moved = fingerprints(&snapshot);
+        assert_ne!(moved[0], first[0], "moving a curve should change its thumbnail");
+        assert_eq!(moved[1], first[1], "other layers' thumbnails should stay cached");
+
+        // both layers were drawn with the same style
+        layers[1].style.upgrade().unwrap().lock().borrow_mut().fill = Pattern::Solid(Color::MAGENTA);
+        snapshot.update(layers);
+        let restyled = fingerprints(&snapshot);
+        assert!(restyled[0] != moved[0] && restyled[1] != moved[1], "restyling should change the thumbnails of every layer with the style");
+    }
+}
@@ -1,33 +1,273 @@
-use std::{cell::RefCell, path::PathBuf, sync::Arc};
+use std::{cell::RefCell, collections::{HashMap, HashSet}, path::PathBuf, sync::{Arc, Weak}, time::SystemTime};
 use parking_lot::ReentrantMutex;
 use raylib::prelude::*;
-use crate::{curve::{Curve, StrongCurve}, layer::{Layer, StrongRenderTexture2D}, style::{StrongStyle, StrongWidthProfile, Style, WidthProfile}};
+use serde::{Deserialize, Serialize};
+use crate::{
+    animation::Animation,
+    annotation::{Annotation, AnnotationKind},
+    connector::{Connector, ConnectorEnd},
+    curve::{Curve, StrongCurve, WeakCurve},
+    export::ExportPreset,
+    layer::{self, CompoundPath, Group, Layer, LayerContent, StrongRenderTexture2D, WeakRenderTexture2D},
+    query::{LayerFilter, LayerQuery},
+    raster::{self, RasterStore},
+    shape::LiveRectangle,
+    style::{Modifier, Pattern, Stroke, StrongStyle, StrongWidthProfile, Style, StyleItem, TextureTransform, WeakStyle, WeakWidthProfile, WidthProfile},
+    swatch::Swatch,
+    units::{PixelSnap, Unit},
+};
+
+/// What is shown behind the artwork of an [`Artboard`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ArtboardBackground {
+    /// The document's [paper color][`Document::paper_color`]
+    #[default]
+    Paper,
+
+    /// Nothing; the viewport shows through, and exports are transparent
+    Transparent,
+
+    /// A checkerboard in the viewport, so transparency is visible; exports are transparent
+    Checkerboard,
+}
+
+impl ArtboardBackground {
+    /// Every background, in the order they are cycled through
+    pub const ALL: [Self; 3] = [Self::Paper, Self::Transparent, Self::Checkerboard];
+
+    /// The background after this one in [`ArtboardBackground::ALL`], wrapping around
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|&background| background == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    /// The color exports of the artboard are cleared to, given the document's `paper_color`
+    pub const fn export_color(self, paper_color: Color) -> Color {
+        match self {
+            Self::Paper => paper_color,
+            Self::Transparent | Self::Checkerboard => Color::BLANK,
+        }
+    }
+}
 
 /// An independently-exported crop region of the document artwork
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Artboard {
     /// The display name of the artboard
     pub name: String,
 
     /// The worldspace rectangle the artboard crops
     pub rect: Rectangle,
+
+    /// What is shown behind the artwork
+    pub background: ArtboardBackground,
 }
 
 impl Artboard {
-    /// Construct a new artboard
+    /// Construct a new artboard on the document's paper
     pub const fn new(name: String, rect: Rectangle) -> Self {
-        Self { name, rect }
+        Self { name, rect, background: ArtboardBackground::Paper }
     }
 }
 
+/// A camera position saved with the document, to quickly return to a detail area
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NamedView {
+    /// The display name of the view
+    pub name: String,
+
+    /// The worldspace point at the center of the viewport
+    pub center: Vector2,
+
+    /// The camera zoom
+    pub zoom: f32,
+
+    /// The camera rotation, in degrees
+    pub rotation: f32,
+}
+
+/// An edge or center line of a group of artboards that they can be lined up along
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArtboardAlignment {
+    Left,
+    CenterX,
+    Right,
+    Top,
+    CenterY,
+    Bottom,
+}
+
+impl ArtboardAlignment {
+    /// Every alignment, in the order they are listed in the UI
+    pub const ALL: [Self; 6] = [Self::Left, Self::CenterX, Self::Right, Self::Top, Self::CenterY, Self::Bottom];
+
+    /// The [localization][`crate::locale`] key of the alignment's button
+    pub const fn key(self) -> &'static str {
+        match self {
+            Self::Left => "artboards.align_left",
+            Self::CenterX => "artboards.align_center_x",
+            Self::Right => "artboards.align_right",
+            Self::Top => "artboards.align_top",
+            Self::CenterY => "artboards.align_center_y",
+            Self::Bottom => "artboards.align_bottom",
+        }
+    }
+
+    /// Move `rect` so it lines up with `bounds`, along one axis
+    pub fn align(self, rect: &mut Rectangle, bounds: Rectangle) {
+        match self {
+            Self::Left => rect.x = bounds.x,
+            Self::CenterX => rect.x = bounds.x + (bounds.width - rect.width) * 0.5,
+            Self::Right => rect.x = bounds.x + bounds.width - rect.width,
+            Self::Top => rect.y = bounds.y,
+            Self::CenterY => rect.y = bounds.y + (bounds.height - rect.height) * 0.5,
+            Self::Bottom => rect.y = bounds.y + bounds.height - rect.height,
+        }
+    }
+}
+
+/// A renaming applied to every artboard at once
+///
+/// Each name first has every occurrence of `find` replaced with `replace`, then is substituted into `pattern`
+#[derive(Debug, Clone, Default)]
+pub struct ArtboardRename {
+    /// Text to replace in each name; nothing is replaced if empty
+    pub find: String,
+
+    /// Text `find` is replaced with
+    pub replace: String,
+
+    /// The new name, where `{name}` is the name after find and replace, and `{n}` is the artboard's number
+    ///
+    /// Empty to keep the name after find and replace
+    pub pattern: String,
+
+    /// The number of the first artboard
+    pub start: usize,
+
+    /// Minimum number of digits of `{n}`, padded with leading zeros
+    pub digits: usize,
+}
+
+impl ArtboardRename {
+    /// The new name of an artboard named `name`, which is the `index`th artboard being renamed
+    pub fn apply(&self, name: &str, index: usize) -> String {
+        let name = if self.find.is_empty() { name.to_owned() } else { name.replace(&self.find, &self.replace) };
+        if self.pattern.is_empty() {
+            return name;
+        }
+        let number = format!("{:0digits$}", self.start.saturating_add(index), digits = self.digits);
+        self.pattern
+            .replace("{name}", &name)
+            .replace("{n}", &number)
+    }
+}
+
+/// Names that appear more than once in `names`, in order of first appearance
+///
+/// Names differing only in case count as duplicates, since they name the same file on case-insensitive file systems
+pub fn duplicate_names<'a>(names: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    let mut seen = HashSet::new();
+    let mut reported = HashSet::new();
+    names.into_iter()
+        .filter(|name| {
+            let key = name.to_lowercase();
+            !seen.insert(key.clone()) && reported.insert(key)
+        })
+        .map(str::to_owned)
+        .collect()
+}
+
+/// Descriptive information about a document that doesn't affect the artwork
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DocumentInfo {
+    /// Who made the document
+    pub author: String,
+
+    /// What the document is about
+    pub description: String,
+
+    /// When the document was first saved, in seconds since the Unix epoch
+    ///
+    /// [`None`] if the document has never been saved
+    pub created: Option<u64>,
+
+    /// When the document was last saved, in seconds since the Unix epoch
+    ///
+    /// [`None`] if the document has never been saved
+    pub modified: Option<u64>,
+
+    /// Keywords for searching and organizing documents
+    pub tags: Vec<String>,
+
+    /// The unit measurements are displayed in
+    pub units: Unit,
+
+    /// How many pixels make up an inch when converting to physical units
+    pub dpi: f32,
+
+    /// How anchors and placed images are rounded to the pixel grid while drawing and editing
+    pub pixel_snap: PixelSnap,
+
+    /// Whether pixel snapping lines up the edges of curves' strokes with the grid, rather than their paths
+    pub is_aligning_strokes: bool,
+}
+
+impl Default for DocumentInfo {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DocumentInfo {
+    /// Construct empty document info measured in pixels at 96 DPI, without pixel snapping
+    pub const fn new() -> Self {
+        Self {
+            author: String::new(),
+            description: String::new(),
+            created: None,
+            modified: None,
+            tags: Vec::new(),
+            units: Unit::Pixels,
+            dpi: 96.0,
+            pixel_snap: PixelSnap::Off,
+            is_aligning_strokes: false,
+        }
+    }
+
+    /// Set the modified time (and created time, if this is the first save) to now
+    pub fn touch(&mut self) {
+        let now = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_secs());
+        self.created.get_or_insert(now);
+        self.modified = Some(now);
+    }
+}
+
+/// The UTC date and time `seconds` after the Unix epoch, as `[year, month, day, hour, minute, second]`
+pub fn utc_date_time(seconds: u64) -> [i64; 6] {
+    // days-to-civil conversion from https://howardhinnant.github.io/date_algorithms.html
+    let days = (seconds / 86400) as i64 + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    let time = (seconds % 86400) as i64;
+    [year, month, day, time / 3600, time % 3600 / 60, time % 60]
+}
+
 /// A self-contained vector artwork document
 #[derive(Debug)]
 pub struct Document {
-    /// Multiple styles can reference the same raster
-    ///
-    /// A raster should be removed when the weak count is 0; the
-    /// document doesn't need a raster if no style references it
-    pub rasters: Vec<StrongRenderTexture2D>,
+    /// Rasters referenced by styles and image layers
+    pub rasters: RasterStore,
 
     /// Multiple styles can reference the same width_profile
     ///
@@ -62,13 +302,49 @@ pub struct Document {
 
     /// Separately exported cropped regions of vector artwork
     pub artboards: Vec<Artboard>,
+
+    /// Author, description, units, and other information about the document
+    pub info: DocumentInfo,
+
+    /// Settings for playing the artboards as frames of an animation
+    pub animation: Animation,
+
+    /// Local copies of [graphic styles][`crate::style_library::GraphicStyle`] applied to this
+    /// document's layers, by graphic style name
+    ///
+    /// Redefining a graphic style overwrites its local copy, updating every layer that uses it
+    pub linked_styles: Vec<(String, WeakStyle)>,
+
+    /// The style new objects are drawn with when [`NewObjectStyle::DocumentDefault`][`crate::preferences::NewObjectStyle::DocumentDefault`] is chosen
+    ///
+    /// [`None`] until one is needed or chosen, at which point it is created from [`Style::default_style`]
+    pub default_style: Option<WeakStyle>,
+
+    /// Named export configurations saved with the document
+    pub export_presets: Vec<ExportPreset>,
+
+    /// The configuration of the most recent export, repeated by "Export with last settings"
+    ///
+    /// [`None`] if the document has never been exported
+    pub last_export: Option<ExportPreset>,
+
+    /// Named colors saved with the document, with their print definitions
+    pub swatches: Vec<Swatch>,
+
+    /// Camera positions saved with the document, in the order they are cycled through
+    pub views: Vec<NamedView>,
+
+    /// Design review notes drawn over the artwork, in the order they were added
+    ///
+    /// Kept apart from `layers` so that nothing exports them
+    pub annotations: Vec<Annotation>,
 }
 
 impl Document {
     /// Construct an empty file without any allocations
     pub const fn new(title: String) -> Self {
         Self {
-            rasters: Vec::new(),
+            rasters: RasterStore::new(),
             width_profiles: Vec::new(),
             styles: Vec::new(),
             curves: Vec::new(),
@@ -78,17 +354,21 @@ impl Document {
             paper_color: Color::GRAY,
             layers: Vec::new(),
             artboards: Vec::new(),
+            info: DocumentInfo::new(),
+            animation: Animation::new(),
+            linked_styles: Vec::new(),
+            default_style: None,
+            export_presets: Vec::new(),
+            last_export: None,
+            swatches: Vec::new(),
+            views: Vec::new(),
+            annotations: Vec::new(),
         }
     }
 
     /// Push a new local raster to the document and get a reference to it
     pub fn create_raster(&mut self, rtex: RenderTexture2D) -> &StrongRenderTexture2D {
-        self.rasters.push(Arc::new(ReentrantMutex::new(RefCell::new(rtex))));
-        #[cfg(debug_assertions)] {
-            self.rasters.last().expect("should have at least one element after push")
-        } #[cfg(not(debug_assertions))] {
-            unsafe { self.rasters.last().unwrap_unchecked() }
-        }
+        self.rasters.push(rtex)
     }
 
     /// Push a new local width profile to the document and get a reference to it
@@ -120,4 +400,963 @@ impl Document {
             unsafe { self.curves.last().unwrap_unchecked() }
         }
     }
+
+    /// Copy an image into a new local raster and get a reference to it
+    pub fn create_raster_from_image(&mut self, rl: &mut RaylibHandle, thread: &RaylibThread, image: &Image) -> Result<&StrongRenderTexture2D, String> {
+        let rtex = raster::render_texture_from_image(rl, thread, image)?;
+        Ok(self.create_raster(rtex))
+    }
+
+    /// Copy a raster (possibly from another document) into a new local raster and get a reference to it
+    pub fn create_raster_copy(&mut self, rl: &mut RaylibHandle, thread: &RaylibThread, raster: &RenderTexture2D) -> Result<&StrongRenderTexture2D, String> {
+        let rtex = raster::copy_render_texture(rl, thread, raster)?;
+        Ok(self.create_raster(rtex))
+    }
+
+    /// Copy an image into a new local raster and place it as a new top layer centered on the worldspace `center`
+    ///
+    /// The image's bounds are rounded to the document's [pixel grid][`DocumentInfo::pixel_snap`]
+    pub fn place_image(&mut self, rl: &mut RaylibHandle, thread: &RaylibThread, name: String, image: &Image, style: WeakStyle, center: Vector2) -> Result<(), String> {
+        let (width, height) = (image.width as f32, image.height as f32);
+        let raster = Arc::downgrade(self.create_raster_from_image(rl, thread, image)?);
+        let mut layer = Layer {
+            name,
+            content: LayerContent::Image {
+                raster,
+                rect: Rectangle::new(center.x - width * 0.5, center.y - height * 0.5, width, height),
+            },
+            style,
+            export: None,
+            is_template: false,
+        };
+        layer.snap_to_pixels(self.info.pixel_snap, false);
+        self.layers.push(layer);
+        Ok(())
+    }
+
+    /// Unload unused rasters and crop the rest to their non-transparent pixels
+    ///
+    /// Image layers and style patterns are adjusted so their pixels stay where they were. Patterns keep a pixel of
+    /// transparency around them so their clamped edges stay clear. Rasters used by a pattern that can't be adjusted
+    /// exactly are left uncropped; see [`TextureTransform::is_croppable`]
+    ///
+    /// Returns the number of bytes of GPU memory freed
+    pub fn trim_rasters(&mut self, rl: &mut RaylibHandle, thread: &RaylibThread) -> Result<usize, String> {
+        fn pattern_transform<'a>(pattern: &'a Pattern, raster: &StrongRenderTexture2D) -> Option<&'a TextureTransform> {
+            match pattern {
+                Pattern::Texture(texture, transform) if std::ptr::eq(texture.as_ptr(), Arc::as_ptr(raster)) => Some(transform),
+                _ => None,
+            }
+        }
+
+        fn adjust_images(layers: &mut [Layer], raster: &StrongRenderTexture2D, crop: Rectangle, width: f32, height: f32) {
+            for layer in layers {
+                match &mut layer.content {
+                    LayerContent::Group(group) => adjust_images(&mut group.layers, raster, crop, width, height),
+                    LayerContent::Image { raster: image_raster, rect } if std::ptr::eq(image_raster.as_ptr(), Arc::as_ptr(raster)) => {
+                        let (scale_x, scale_y) = (rect.width / width, rect.height / height);
+                        *rect = Rectangle::new(
+                            rect.x + crop.x * scale_x,
+                            rect.y + crop.y * scale_y,
+                            crop.width * scale_x,
+                            crop.height * scale_y,
+                        );
+                    }
+                    LayerContent::Image { .. } | LayerContent::Curve(_) | LayerContent::Compound(_) | LayerContent::Connector(_) | LayerContent::Rectangle(_) | LayerContent::Paint(_) => (),
+                }
+            }
+        }
+
+        let before = self.raster_memory_usage();
+        self.rasters.remove_unused();
+        let rasters = self.rasters.iter().cloned().collect::<Vec<_>>();
+        for raster in rasters {
+            let (mut is_pattern, mut is_croppable) = (false, true);
+            for style in &self.styles {
+                let style_lock = style.lock();
+                let style = style_lock.borrow();
+                for transform in style.patterns().filter_map(|pattern| pattern_transform(pattern, &raster)) {
+                    is_pattern = true;
+                    is_croppable &= transform.is_croppable();
+                }
+            }
+            if !is_croppable {
+                continue;
+            }
+            let (width, height) = {
+                let raster_lock = raster.lock();
+                let raster_borrow = raster_lock.borrow();
+                (raster_borrow.texture().width as f32, raster_borrow.texture().height as f32)
+            };
+            let margin = if is_pattern { 1.0 } else { 0.0 };
+            if let Some(crop) = RasterStore::crop_to_content(rl, thread, &raster, margin)? {
+                adjust_images(&mut self.layers, &raster, crop, width, height);
+                let uv_crop = Rectangle::new(crop.x / width, crop.y / height, crop.width / width, crop.height / height);
+                for style in &self.styles {
+                    let style_lock = style.lock();
+                    let mut style = style_lock.borrow_mut();
+                    for pattern in style.patterns_mut() {
+                        if let Pattern::Texture(texture, transform) = pattern
+                            && std::ptr::eq(texture.as_ptr(), Arc::as_ptr(&raster))
+                        {
+                            *transform = transform.cropped(uv_crop);
+                        }
+                    }
+                }
+            }
+        }
+        Ok(before.saturating_sub(self.raster_memory_usage()))
+    }
+
+    /// Approximate GPU memory used by the document's rasters and the tiles of its paint layers (including those in groups), in bytes
+    pub fn raster_memory_usage(&self) -> usize {
+        fn tiles(layers: &[Layer]) -> usize {
+            layers.iter()
+                .map(|layer| match &layer.content {
+                    LayerContent::Group(group) => tiles(&group.layers),
+                    LayerContent::Paint(paint) => paint.memory_usage(),
+                    _ => 0,
+                })
+                .fold(0, usize::saturating_add)
+        }
+        self.rasters.memory_usage().saturating_add(tiles(&self.layers))
+    }
+
+    /// The number of [broken][`Layer::is_broken`] layers, including those in groups
+    pub fn broken_layer_count(&self) -> usize {
+        fn count(layers: &[Layer]) -> usize {
+            layers.iter()
+                .map(|layer| match &layer.content {
+                    LayerContent::Group(group) => count(&group.layers),
+                    _ => usize::from(layer.is_broken()),
+                })
+                .fold(0, usize::saturating_add)
+        }
+        count(&self.layers)
+    }
+
+    /// Fix every [broken][`Layer::is_broken`] layer, including those in groups
+    ///
+    /// Compound paths drop their missing subpaths and are kept if any remain; every other broken layer is removed.
+    /// Groups left empty are kept, since they may have been empty on purpose
+    ///
+    /// Returns the number of layers repaired or removed
+    pub fn repair_layers(&mut self) -> usize {
+        fn repair(layers: &mut Vec<Layer>) -> usize {
+            let mut repaired = 0usize;
+            layers.retain_mut(|layer| {
+                if let LayerContent::Group(group) = &mut layer.content {
+                    repaired = repaired.saturating_add(repair(&mut group.layers));
+                    return true;
+                }
+                if !layer.is_broken() {
+                    return true;
+                }
+                repaired = repaired.saturating_add(1);
+                if let LayerContent::Compound(compound) = &mut layer.content {
+                    compound.subpaths.retain(|subpath| subpath.strong_count() > 0);
+                    return !compound.subpaths.is_empty();
+                }
+                false
+            });
+            repaired
+        }
+        repair(&mut self.layers)
+    }
+
+    /// Give every artboard in `indices` the same worldspace size, keeping their top-left corners in place
+    ///
+    /// Indices past the last artboard are ignored
+    pub fn resize_artboards(&mut self, indices: &[usize], width: f32, height: f32) {
+        for &index in indices {
+            if let Some(artboard) = self.artboards.get_mut(index) {
+                artboard.rect.width = width;
+                artboard.rect.height = height;
+            }
+        }
+    }
+
+    /// Line up every artboard in `indices` along an edge or center of the rectangle containing them all
+    ///
+    /// Indices past the last artboard are ignored
+    pub fn align_artboards(&mut self, indices: &[usize], alignment: ArtboardAlignment) {
+        let Some(bounds) = indices.iter()
+            .filter_map(|&index| self.artboards.get(index))
+            .map(|artboard| artboard.rect)
+            .reduce(layer::union)
+            else { return };
+        for &index in indices {
+            if let Some(artboard) = self.artboards.get_mut(index) {
+                alignment.align(&mut artboard.rect, bounds);
+            }
+        }
+    }
+
+    /// Space every artboard in `indices` out so the gaps between them are equal,
+    /// horizontally or `is_vertical`, keeping the outermost two in place
+    ///
+    /// Indices past the last artboard are ignored
+    pub fn distribute_artboards(&mut self, indices: &[usize], is_vertical: bool) {
+        let span = |rect: &Rectangle| if is_vertical { (rect.y, rect.height) } else { (rect.x, rect.width) };
+        let mut order = indices.iter()
+            .copied()
+            .filter(|&index| index < self.artboards.len())
+            .collect::<Vec<_>>();
+        order.sort_unstable();
+        order.dedup();
+        order.sort_by(|&a, &b| span(&self.artboards[a].rect).0.total_cmp(&span(&self.artboards[b].rect).0));
+        let (Some(&first), Some(&last)) = (order.first(), order.last()) else { return };
+        let start = span(&self.artboards[first].rect).0;
+        let (last_start, last_length) = span(&self.artboards[last].rect);
+        let total_length = order.iter().map(|&index| span(&self.artboards[index].rect).1).sum::<f32>();
+        let gap = (last_start + last_length - start - total_length) / order.len().saturating_sub(1).max(1) as f32;
+        let mut position = start;
+        for index in order {
+            let rect = &mut self.artboards[index].rect;
+            let length = if is_vertical {
+                rect.y = position;
+                rect.height
+            } else {
+                rect.x = position;
+                rect.width
+            };
+            position += length + gap;
+        }
+    }
+
+    /// Reflow every artboard into a grid `columns` wide with `spacing` between cells, starting at the first artboard's top-left
+    ///
+    /// Each column is as wide as its widest artboard and each row as tall as its tallest.
+    /// Top-level layers lying entirely within an artboard move along with it; the rest stay where they are
+    pub fn arrange_artboards(&mut self, columns: usize, spacing: f32) {
+        let columns = columns.max(1);
+        let Some(first) = self.artboards.first() else { return };
+        let origin = Vector2::new(first.rect.x, first.rect.y);
+
+        let mut column_widths = vec![0.0f32; columns];
+        let mut row_heights = vec![0.0f32; self.artboards.len().div_ceil(columns)];
+        for (index, artboard) in self.artboards.iter().enumerate() {
+            let (column, row) = (index % columns, index / columns);
+            column_widths[column] = column_widths[column].max(artboard.rect.width);
+            row_heights[row] = row_heights[row].max(artboard.rect.height);
+        }
+        let offsets = self.artboards.iter()
+            .enumerate()
+            .map(|(index, artboard)| {
+                let (column, row) = (index % columns, index / columns);
+                let x = column_widths[..column].iter().map(|width| width + spacing).sum::<f32>();
+                let y = row_heights[..row].iter().map(|height| height + spacing).sum::<f32>();
+                origin + Vector2::new(x - artboard.rect.x, y - artboard.rect.y)
+            })
+            .collect::<Vec<_>>();
+
+        // layers are matched to artboards before any artboard moves, so artboards moving over each other don't pick up artwork
+        let owners = self.layers.iter()
+            .map(|layer| {
+                let bounds = layer.control_bounds()?;
+                self.artboards.iter().position(|artboard| {
+                    let rect = artboard.rect;
+                    bounds.x >= rect.x && bounds.y >= rect.y
+                        && bounds.x + bounds.width <= rect.x + rect.width
+                        && bounds.y + bounds.height <= rect.y + rect.height
+                })
+            })
+            .collect::<Vec<_>>();
+        for (layer, owner) in self.layers.iter_mut().zip(owners) {
+            if let Some(offset) = owner.and_then(|index| offsets.get(index)) {
+                layer.translate(*offset);
+            }
+        }
+        for (artboard, offset) in self.artboards.iter_mut().zip(offsets) {
+            artboard.rect.x += offset.x;
+            artboard.rect.y += offset.y;
+        }
+    }
+
+    /// Uniformly scale everything in the document by `factor`, keeping the first artboard's top-left in place
+    ///
+    /// Curves, stroke widths, rectangle corners, image and paint placements, artboards, saved views, and annotations
+    /// are all scaled. Paint can't be resampled, so its pixels stay the same size.
+    /// Every curve is scaled exactly once, even if several layers share it
+    pub fn scale(&mut self, factor: f32) {
+        fn scale_placements(layers: &mut [Layer], stretch: &impl Fn(Vector2) -> Vector2, factor: f32) {
+            for layer in layers {
+                match &mut layer.content {
+                    LayerContent::Group(group) => scale_placements(&mut group.layers, stretch, factor),
+
+                    // the curve is rebuilt from the rectangle, so it doesn't matter that it was already scaled
+                    LayerContent::Rectangle(rectangle) => {
+                        let corner = stretch(Vector2::new(rectangle.rect.x, rectangle.rect.y));
+                        rectangle.rect = Rectangle::new(corner.x, corner.y, rectangle.rect.width * factor, rectangle.rect.height * factor);
+                        for radius in &mut rectangle.radii {
+                            *radius *= factor;
+                        }
+                        rectangle.regenerate();
+                    }
+
+                    LayerContent::Image { rect, .. } => {
+                        let corner = stretch(Vector2::new(rect.x, rect.y));
+                        *rect = Rectangle::new(corner.x, corner.y, rect.width * factor, rect.height * factor);
+                    }
+
+                    LayerContent::Paint(paint) => paint.origin = stretch(paint.origin),
+
+                    LayerContent::Curve(_) | LayerContent::Compound(_) | LayerContent::Connector(_) => (),
+                }
+            }
+        }
+
+        if !(factor.is_finite() && factor > 0.0) || factor == 1.0 {
+            return;
+        }
+        let origin = self.artboards.first().map_or(Vector2::zero(), |artboard| Vector2::new(artboard.rect.x, artboard.rect.y));
+        let stretch = |point: Vector2| origin + (point - origin) * factor;
+
+        for curve in &self.curves {
+            curve.lock().borrow_mut().scale(na::Vector2::new(origin.x, origin.y), na::Vector2::new(factor, factor));
+        }
+        for profile in &self.width_profiles {
+            profile.lock().borrow_mut().scale(factor);
+        }
+        scale_placements(&mut self.layers, &stretch, factor);
+
+        for artboard in &mut self.artboards {
+            let corner = stretch(Vector2::new(artboard.rect.x, artboard.rect.y));
+            artboard.rect = Rectangle::new(corner.x, corner.y, artboard.rect.width * factor, artboard.rect.height * factor);
+        }
+        for view in &mut self.views {
+            view.center = stretch(view.center);
+            // the view keeps showing the same artwork
+            view.zoom /= factor;
+        }
+        for annotation in &mut self.annotations {
+            annotation.position = stretch(annotation.position);
+            match &mut annotation.kind {
+                AnnotationKind::Note { .. } => (),
+                AnnotationKind::Markup { points } => for point in points {
+                    *point = stretch(*point);
+                },
+                AnnotationKind::Arrow { target } => *target = stretch(*target),
+            }
+        }
+    }
+
+    /// The worldspace rectangle containing every artboard and the [control bounds][`Layer::control_bounds`] of every layer
+    ///
+    /// [`None`] if the document has no artboards and no artwork
+    pub fn extent(&self) -> Option<Rectangle> {
+        self.artboards.iter()
+            .map(|artboard| artboard.rect)
+            .chain(self.layers.iter().filter_map(Layer::control_bounds))
+            .reduce(|a, b| {
+                let x = a.x.min(b.x);
+                let y = a.y.min(b.y);
+                let right = (a.x + a.width).max(b.x + b.width);
+                let bottom = (a.y + a.height).max(b.y + b.height);
+                Rectangle::new(x, y, right - x, bottom - y)
+            })
+    }
+
+    /// Get the layer at `path`, a list of indices from the top-level layers down through nested groups
+    ///
+    /// [`None`] if `path` is empty or doesn't lead to a layer
+    pub fn layer_at(&self, path: &[usize]) -> Option<&Layer> {
+        let (&last, groups) = path.split_last()?;
+        self.layers_at(groups)?.get(last)
+    }
+
+    /// Get the layer at `path` mutably
+    ///
+    /// See [`Document::layer_at`]
+    pub fn layer_at_mut(&mut self, path: &[usize]) -> Option<&mut Layer> {
+        let (&last, groups) = path.split_last()?;
+        let mut layers = &mut self.layers;
+        for &index in groups {
+            let LayerContent::Group(group) = &mut layers.get_mut(index)?.content else { return None };
+            layers = &mut group.layers;
+        }
+        layers.get_mut(last)
+    }
+
+    /// Whether the layer at `path` or any group containing it is a [template][`Layer::is_template`]
+    pub fn is_in_template(&self, path: &[usize]) -> bool {
+        (1..=path.len()).any(|depth| self.layer_at(&path[..depth]).is_some_and(|layer| layer.is_template))
+    }
+
+    /// The addresses of every curve drawn by a [template][`Layer::is_template`] layer or a layer inside one
+    pub fn template_curves(&self) -> HashSet<*const ()> {
+        let mut curves = HashSet::new();
+        self.visit_layers(|_, layer| if layer.is_template {
+            curves.extend(layer.curves().iter().map(|curve| curve.as_ptr() as *const ()));
+        });
+        curves
+    }
+
+    /// Copy `style` into a new local style and point the layers at `paths` that use it to the copy
+    ///
+    /// Every other layer keeps using the original. [`None`] if `style` no longer exists
+    pub fn fork_style(&mut self, style: &WeakStyle, paths: &[Vec<usize>]) -> Option<WeakStyle> {
+        let strong = style.upgrade()?;
+        let copy = strong.lock().borrow().clone();
+        let fork = Arc::downgrade(self.create_style(copy));
+        for path in paths {
+            if let Some(layer) = self.layer_at_mut(path)
+                && layer.style.ptr_eq(style)
+            {
+                layer.style = fork.clone();
+            }
+        }
+        Some(fork)
+    }
+
+    /// Give the layers at `paths` and everything nested in them copies of their styles with stroke widths multiplied by `factor`
+    ///
+    /// Styles without any width profiles are left shared. Returns each style that was copied along with its copy
+    pub fn scale_strokes(&mut self, paths: &[Vec<usize>], factor: f32) -> Vec<(WeakStyle, WeakStyle)> {
+        /// Call `f` on `layer` and every layer nested inside it
+        fn visit_layers_mut(layer: &mut Layer, f: &mut impl FnMut(&mut Layer)) {
+            f(layer);
+            if let LayerContent::Group(group) = &mut layer.content {
+                for layer in &mut group.layers {
+                    visit_layers_mut(layer, f);
+                }
+            }
+        }
+
+        let mut styles = Vec::<WeakStyle>::new();
+        for path in paths {
+            if let Some(layer) = self.layer_at_mut(path) {
+                visit_layers_mut(layer, &mut |layer| if !styles.iter().any(|style| style.ptr_eq(&layer.style)) {
+                    styles.push(layer.style.clone());
+                });
+            }
+        }
+
+        let forks = styles.into_iter()
+            .filter_map(|style| {
+                let strong_style = style.upgrade()?;
+                let mut fork = strong_style.lock().borrow().clone();
+                let mut is_stroked = false;
+                let widths = std::iter::once(&mut fork.stroke.width)
+                    .chain(fork.items.iter_mut().filter_map(|item| match &mut item.modifier {
+                        Modifier::Stroke(stroke) => Some(&mut stroke.width),
+                        Modifier::Fill(_) => None,
+                    }));
+                for width in widths {
+                    let Some(profile) = width.as_ref().and_then(Weak::upgrade) else { continue };
+                    let mut scaled = profile.lock().borrow().clone();
+                    scaled.scale(factor);
+                    *width = Some(Arc::downgrade(self.create_width_profile(scaled)));
+                    is_stroked = true;
+                }
+                is_stroked.then(|| (style, Arc::downgrade(self.create_style(fork))))
+            })
+            .collect::<Vec<_>>();
+
+        for path in paths {
+            if let Some(layer) = self.layer_at_mut(path) {
+                visit_layers_mut(layer, &mut |layer| if let Some((_, fork)) = forks.iter().find(|(original, _)| original.ptr_eq(&layer.style)) {
+                    layer.style = fork.clone();
+                });
+            }
+        }
+        forks
+    }
+
+    /// Count the layers (including those nested in groups) referencing `style`
+    pub fn style_users(&self, style: &WeakStyle) -> usize {
+        fn count(layers: &[Layer], style: &WeakStyle) -> usize {
+            layers.iter()
+                .map(|layer| {
+                    let nested = match &layer.content {
+                        LayerContent::Group(group) => count(&group.layers, style),
+                        _ => 0,
+                    };
+                    nested + usize::from(layer.style.ptr_eq(style))
+                })
+                .sum()
+        }
+        count(&self.layers, style)
+    }
+
+    /// Call `visit` with the path and layer of every layer, including those nested in groups, in document order
+    ///
+    /// Groups are visited before the layers inside them
+    pub fn visit_layers<'a>(&'a self, mut visit: impl FnMut(&[usize], &'a Layer)) {
+        fn walk<'a>(layers: &'a [Layer], prefix: &mut Vec<usize>, visit: &mut impl FnMut(&[usize], &'a Layer)) {
+            for (index, layer) in layers.iter().enumerate() {
+                prefix.push(index);
+                visit(prefix, layer);
+                if let LayerContent::Group(group) = &layer.content {
+                    walk(&group.layers, prefix, visit);
+                }
+                prefix.pop();
+            }
+        }
+        walk(&self.layers, &mut Vec::new(), &mut visit);
+    }
+
+    /// Start a search of the layers by style, matching every vector layer until narrowed down
+    ///
+    /// See [`LayerQuery`]
+    #[cfg_attr(not(test), allow(dead_code, reason = "part of the query API for scripts, which the application doesn't run"))]
+    pub const fn query(&self) -> LayerQuery<'_> {
+        LayerQuery::new(self, LayerFilter::new())
+    }
+
+    /// Get the layers directly inside the group at `path`, or the top-level layers if `path` is empty
+    ///
+    /// [`None`] if `path` doesn't lead to a group
+    pub fn layers_at(&self, path: &[usize]) -> Option<&[Layer]> {
+        layers_in(&self.layers, path)
+    }
+
+    /// Get the layers directly inside the group at `path` mutably
+    ///
+    /// See [`Document::layers_at`]
+    pub fn layers_at_mut(&mut self, path: &[usize]) -> Option<&mut Vec<Layer>> {
+        let mut layers = &mut self.layers;
+        for &index in path {
+            let LayerContent::Group(group) = &mut layers.get_mut(index)?.content else { return None };
+            layers = &mut group.layers;
+        }
+        Some(layers)
+    }
+
+    /// Copy `layer` with copies of its curves, still sharing its style and any images' rasters
+    ///
+    /// [`None`] for paint layers, whose pixels can only be copied on the GPU; paint inside groups is left out of the copy
+    pub fn instance_layer(&mut self, layer: &Layer) -> Option<Layer> {
+        fn copy_curve(document: &mut Document, curve: &WeakCurve) -> Option<WeakCurve> {
+            let strong = curve.upgrade()?;
+            let curve = strong.lock().borrow().clone();
+            Some(Arc::downgrade(document.create_curve(curve)))
+        }
+        let content = match &layer.content {
+            LayerContent::Group(group) => LayerContent::Group(Group {
+                layers: group.layers.iter().filter_map(|layer| self.instance_layer(layer)).collect(),
+            }),
+            LayerContent::Curve(curve) => LayerContent::Curve(copy_curve(self, curve)?),
+            LayerContent::Compound(compound) => LayerContent::Compound(CompoundPath {
+                subpaths: compound.subpaths.iter().map(|subpath| copy_curve(self, subpath)).collect::<Option<_>>()?,
+            }),
+            // the copy stays attached to the same artwork as the original
+            LayerContent::Connector(connector) => LayerContent::Connector(Connector {
+                curve: copy_curve(self, &connector.curve)?,
+                start: connector.start.clone(),
+                end: connector.end.clone(),
+                routing: connector.routing,
+            }),
+            LayerContent::Rectangle(rectangle) => LayerContent::Rectangle(LiveRectangle {
+                curve: copy_curve(self, &rectangle.curve)?,
+                rect: rectangle.rect,
+                radii: rectangle.radii,
+            }),
+            LayerContent::Image { raster, rect } => LayerContent::Image { raster: raster.clone(), rect: *rect },
+            LayerContent::Paint(_) => return None,
+        };
+        Some(Layer { name: layer.name.clone(), content, style: layer.style.clone(), export: layer.export.clone(), is_template: layer.is_template })
+    }
+
+    /// [Instance][`Document::instance_layer`] each of the layers at `paths`, skipping paths that don't lead to a layer
+    pub fn instance_layers_at(&mut self, paths: &[Vec<usize>]) -> Vec<Layer> {
+        // the copies' curves are created while the layers are read, so the layers are set aside meanwhile
+        let layers = std::mem::take(&mut self.layers);
+        let copies = paths.iter()
+            .filter_map(|path| {
+                let (&last, groups) = path.split_last()?;
+                layers_in(&layers, groups)?.get(last)
+            })
+            .filter_map(|layer| self.instance_layer(layer))
+            .collect();
+        self.layers = layers;
+        copies
+    }
+
+    /// Move all of the layers and resources of `other` into this document,
+    /// translating its artwork by `offset`
+    ///
+    /// The layers are placed above all existing layers
+    pub fn append(&mut self, other: Document, offset: Vector2) {
+        fn translate_images(layers: &mut [Layer], offset: Vector2) {
+            for layer in layers {
+                match &mut layer.content {
+                    LayerContent::Group(group) => translate_images(&mut group.layers, offset),
+                    LayerContent::Image { rect, .. } => {
+                        rect.x += offset.x;
+                        rect.y += offset.y;
+                    }
+                    LayerContent::Paint(paint) => {
+                        paint.origin.x += offset.x;
+                        paint.origin.y += offset.y;
+                    }
+                    // the rectangle's curve is moved along with the rest
+                    LayerContent::Rectangle(rectangle) => {
+                        rectangle.rect.x += offset.x;
+                        rectangle.rect.y += offset.y;
+                    }
+                    LayerContent::Curve(_) | LayerContent::Compound(_) | LayerContent::Connector(_) => (),
+                }
+            }
+        }
+
+        let Document { rasters, width_profiles, styles, curves, mut layers, .. } = other;
+        // curves can be shared between layers, so they are translated once here instead of per-layer
+        for curve in &curves {
+            curve.lock().borrow_mut().translate(na::Vector2::new(offset.x, offset.y));
+        }
+        translate_images(&mut layers, offset);
+        self.rasters.append(rasters);
+        self.width_profiles.extend(width_profiles);
+        self.styles.extend(styles);
+        self.curves.extend(curves);
+        self.layers.extend(layers);
+    }
+
+    /// Re-route every connector to the current positions of the artwork it is attached to
+    ///
+    /// Connectors attached to other connectors are routed after them, so a chain settles in one call;
+    /// connectors attached to each other in a loop are routed in document order.
+    /// Ends attached to curves no layer draws anymore stay where they are
+    ///
+    /// Returns whether any connector changed
+    pub fn route_connectors(&self) -> bool {
+        fn visit<'a>(layers: &'a [Layer], drawn: &mut HashSet<*const ()>, connectors: &mut Vec<&'a Connector>) {
+            for layer in layers {
+                match &layer.content {
+                    LayerContent::Group(group) => visit(&group.layers, drawn, connectors),
+                    LayerContent::Connector(connector) => connectors.push(connector),
+                    _ => (),
+                }
+                drawn.extend(layer.curves().iter().map(|curve| curve.as_ptr() as *const ()));
+            }
+        }
+
+        /// Push `connector` to `order` after every connector it is attached to
+        fn order<'a>(connector: &'a Connector, connectors: &[&'a Connector], visited: &mut HashSet<*const ()>, out: &mut Vec<&'a Connector>) {
+            if !visited.insert(connector.curve.as_ptr() as *const ()) {
+                return;
+            }
+            for target in connector.start.targets().iter().chain(connector.end.targets()) {
+                if let Some(&dependency) = connectors.iter().find(|other| other.curve.ptr_eq(target)) {
+                    order(dependency, connectors, visited, out);
+                }
+            }
+            out.push(connector);
+        }
+
+        let mut drawn = HashSet::new();
+        let mut connectors = Vec::new();
+        visit(&self.layers, &mut drawn, &mut connectors);
+        let mut visited = HashSet::new();
+        let mut ordered = Vec::with_capacity(connectors.len());
+        for &connector in &connectors {
+            order(connector, &connectors, &mut visited, &mut ordered);
+        }
+        ordered.into_iter().fold(false, |is_changed, connector| connector.route(&drawn) || is_changed)
+    }
+
+    /// Deep-clone `layers` (which may belong to another document) into layers referencing this document's resources
+    ///
+    /// Every style, width profile, raster, and curve the layers reference is copied into this
+    /// document. Resources shared between the source layers are copied once and stay shared
+    /// between the clones.
+    ///
+    /// The returned layers are not added to [`Document::layers`]
+    pub fn clone_layers_from(&mut self, rl: &mut RaylibHandle, thread: &RaylibThread, layers: &[Layer]) -> Result<Vec<Layer>, String> {
+        let mut cloner = ResourceCloner {
+            document: self,
+            rl,
+            thread,
+            rasters: HashMap::new(),
+            width_profiles: HashMap::new(),
+            styles: HashMap::new(),
+            curves: HashMap::new(),
+        };
+        layers.iter()
+            .map(|layer| cloner.layer(layer))
+            .collect()
+    }
+
+    /// Copy the resources `style` (which may belong to another document) references into this
+    /// document, and get a style referencing the copies
+    ///
+    /// The returned style is not added to [`Document::styles`]
+    pub fn clone_style_from(&mut self, rl: &mut RaylibHandle, thread: &RaylibThread, style: &Style) -> Result<Style, String> {
+        ResourceCloner {
+            document: self,
+            rl,
+            thread,
+            rasters: HashMap::new(),
+            width_profiles: HashMap::new(),
+            styles: HashMap::new(),
+            curves: HashMap::new(),
+        }.style_content(style)
+    }
+}
+
+/// Get the layers directly inside the group at `path` within `layers`; see [`Document::layers_at`]
+fn layers_in<'a>(mut layers: &'a [Layer], path: &[usize]) -> Option<&'a [Layer]> {
+    for &index in path {
+        let LayerContent::Group(group) = &layers.get(index)?.content else { return None };
+        layers = &group.layers;
+    }
+    Some(layers)
+}
+
+/// Copies resources into a document, mapping each source resource
+/// (by identity) to its copy so shared resources are only copied once
+struct ResourceCloner<'a> {
+    document: &'a mut Document,
+    rl: &'a mut RaylibHandle,
+    thread: &'a RaylibThread,
+    rasters: HashMap<*const (), WeakRenderTexture2D>,
+    width_profiles: HashMap<*const (), WeakWidthProfile>,
+    styles: HashMap<*const (), WeakStyle>,
+    curves: HashMap<*const (), WeakCurve>,
+}
+
+impl ResourceCloner<'_> {
+    fn raster(&mut self, raster: &WeakRenderTexture2D) -> Result<WeakRenderTexture2D, String> {
+        let key = raster.as_ptr() as *const ();
+        if let Some(copy) = self.rasters.get(&key) {
+            return Ok(copy.clone());
+        }
+        let strong = raster.upgrade().ok_or("raster reference is dead")?;
+        let raster_lock = strong.lock();
+        let copy = Arc::downgrade(self.document.create_raster_copy(self.rl, self.thread, &raster_lock.borrow())?);
+        self.rasters.insert(key, copy.clone());
+        Ok(copy)
+    }
+
+    fn width_profile(&mut self, profile: &WeakWidthProfile) -> Result<WeakWidthProfile, String> {
+        let key = profile.as_ptr() as *const ();
+        if let Some(copy) = self.width_profiles.get(&key) {
+            return Ok(copy.clone());
+        }
+        let strong = profile.upgrade().ok_or("width profile reference is dead")?;
+        let profile = strong.lock().borrow().clone();
+        let copy = Arc::downgrade(self.document.create_width_profile(profile));
+        self.width_profiles.insert(key, copy.clone());
+        Ok(copy)
+    }
+
+    fn pattern(&mut self, pattern: &Pattern) -> Result<Pattern, String> {
+        Ok(match pattern {
+            Pattern::Solid(color) => Pattern::Solid(*color),
+            Pattern::Texture(raster, transform) => Pattern::Texture(self.raster(raster)?, *transform),
+        })
+    }
+
+    fn stroke(&mut self, stroke: &Stroke) -> Result<Stroke, String> {
+        Ok(Stroke {
+            pattern: self.pattern(&stroke.pattern)?,
+            width: stroke.width.as_ref().map(|width| self.width_profile(width)).transpose()?,
+            trim: stroke.trim,
+            markers: stroke.markers.clone(),
+        })
+    }
+
+    fn style_content(&mut self, style: &Style) -> Result<Style, String> {
+        Ok(Style {
+            fill: self.pattern(&style.fill)?,
+            fill_rule: style.fill_rule,
+            stroke: self.stroke(&style.stroke)?,
+            items: style.items.iter()
+                .map(|item| -> Result<_, String> { Ok(StyleItem {
+                    name: item.name.clone(),
+                    modifier: match &item.modifier {
+                        Modifier::Fill(pattern) => Modifier::Fill(self.pattern(pattern)?),
+                        Modifier::Stroke(stroke) => Modifier::Stroke(self.stroke(stroke)?),
+                    },
+                }) })
+                .collect::<Result<_, String>>()?,
+        })
+    }
+
+    fn style(&mut self, style: &WeakStyle) -> Result<WeakStyle, String> {
+        let key = style.as_ptr() as *const ();
+        if let Some(copy) = self.styles.get(&key) {
+            return Ok(copy.clone());
+        }
+        let strong = style.upgrade().ok_or("style reference is dead")?;
+        let style_lock = strong.lock();
+        let style = self.style_content(&style_lock.borrow())?;
+        let copy = Arc::downgrade(self.document.create_style(style));
+        self.styles.insert(key, copy.clone());
+        Ok(copy)
+    }
+
+    fn curve(&mut self, curve: &WeakCurve) -> Result<WeakCurve, String> {
+        let key = curve.as_ptr() as *const ();
+        if let Some(copy) = self.curves.get(&key) {
+            return Ok(copy.clone());
+        }
+        let strong = curve.upgrade().ok_or("curve reference is dead")?;
+        let curve = strong.lock().borrow().clone();
+        let copy = Arc::downgrade(self.document.create_curve(curve));
+        self.curves.insert(key, copy.clone());
+        Ok(copy)
+    }
+
+    /// Targets that aren't cloned along with the connector are copied without a layer drawing them,
+    /// so the end stays where it is until it is attached to something else
+    fn connector_end(&mut self, end: &ConnectorEnd) -> Result<ConnectorEnd, String> {
+        Ok(match end {
+            ConnectorEnd::Free => ConnectorEnd::Free,
+            ConnectorEnd::Anchor { curve, index } => ConnectorEnd::Anchor { curve: self.curve(curve)?, index: *index },
+            ConnectorEnd::Bounds(curves) => ConnectorEnd::Bounds(
+                curves.iter()
+                    .map(|curve| self.curve(curve))
+                    .collect::<Result<_, String>>()?,
+            ),
+        })
+    }
+
+    fn layer(&mut self, layer: &Layer) -> Result<Layer, String> {
+        Ok(Layer {
+            name: layer.name.clone(),
+            content: match &layer.content {
+                LayerContent::Group(group) => LayerContent::Group(Group {
+                    layers: group.layers.iter()
+                        .map(|layer| self.layer(layer))
+                        .collect::<Result<_, String>>()?,
+                }),
+                LayerContent::Curve(curve) => LayerContent::Curve(self.curve(curve)?),
+                LayerContent::Compound(compound) => LayerContent::Compound(CompoundPath {
+                    subpaths: compound.subpaths.iter()
+                        .map(|subpath| self.curve(subpath))
+                        .collect::<Result<_, String>>()?,
+                }),
+                LayerContent::Connector(connector) => LayerContent::Connector(Connector {
+                    curve: self.curve(&connector.curve)?,
+                    start: self.connector_end(&connector.start)?,
+                    end: self.connector_end(&connector.end)?,
+                    routing: connector.routing,
+                }),
+                LayerContent::Rectangle(rectangle) => LayerContent::Rectangle(LiveRectangle {
+                    curve: self.curve(&rectangle.curve)?,
+                    rect: rectangle.rect,
+                    radii: rectangle.radii,
+                }),
+                LayerContent::Image { raster, rect } => LayerContent::Image { raster: self.raster(raster)?, rect: *rect },
+                LayerContent::Paint(paint) => LayerContent::Paint(paint.duplicate(self.rl, self.thread)?),
+            },
+            style: self.style(&layer.style)?,
+            export: layer.export.clone(),
+            is_template: layer.is_template,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_utc_date_time() {
+        assert_eq!(utc_date_time(0), [1970, 1, 1, 0, 0, 0]);
+        assert_eq!(utc_date_time(951_782_400), [2000, 2, 29, 0, 0, 0]);
+        assert_eq!(utc_date_time(1_700_000_000), [2023, 11, 14, 22, 13, 20]);
+    }
+
+    #[test]
+    fn test_artboard_rename() {
+        let rename = ArtboardRename { find: "old".to_owned(), replace: "new".to_owned(), pattern: "icon_{n}_{name}".to_owned(), start: 8, digits: 2 };
+        assert_eq!(rename.apply("old_old", 0), "icon_08_new_new");
+        assert_eq!(rename.apply("other", 3), "icon_11_other");
+
+        // an empty find replaces nothing, and an empty pattern keeps the name
+        let rename = ArtboardRename { pattern: String::new(), ..rename };
+        assert_eq!(rename.apply("old", 0), "new");
+        let rename = ArtboardRename { find: String::new(), pattern: "{n}".to_owned(), start: 98, digits: 1, ..rename };
+        assert_eq!(rename.apply("old", 3), "101", "numbers outgrow their padding");
+    }
+
+    #[test]
+    fn test_duplicate_names() {
+        assert!(duplicate_names(["a", "b", "c"]).is_empty());
+        assert_eq!(duplicate_names(["a", "b", "a", "a", "B"]), ["a", "B"], "each duplicate should be reported once");
+        assert_eq!(duplicate_names(["Icon", "icon"]), ["icon"], "names differing in case should clash");
+    }
+
+    #[test]
+    fn test_arrange_artboards() {
+        let mut document = Document::new("test".to_owned());
+        for (x, y, width) in [(0.0, 0.0, 100.0), (500.0, 50.0, 50.0), (130.0, 10.0, 200.0)] {
+            document.artboards.push(Artboard::new(String::new(), Rectangle::new(x, y, width, 80.0)));
+        }
+
+        document.distribute_artboards(&[0, 1, 2], false);
+        let xs = document.artboards.iter().map(|artboard| artboard.rect.x).collect::<Vec<_>>();
+        // the outermost artboards stay put, with 100 between each
+        assert_eq!(xs, [0.0, 500.0, 200.0]);
+
+        document.align_artboards(&[0, 1], ArtboardAlignment::Bottom);
+        assert_eq!(document.artboards[0].rect.y, 50.0);
+        assert_eq!(document.artboards[1].rect.y, 50.0);
+        assert_eq!(document.artboards[2].rect.y, 10.0);
+
+        document.resize_artboards(&[2, 7], 64.0, 32.0);
+        assert_eq!((document.artboards[2].rect.width, document.artboards[2].rect.height), (64.0, 32.0));
+        assert_eq!(document.artboards[0].rect.width, 100.0);
+    }
+
+    #[test]
+    fn test_arrange_artboards_grid() {
+        let mut document = Document::new("test".to_owned());
+        for (x, y, width, height) in [(10.0, 20.0, 100.0, 50.0), (-300.0, 400.0, 40.0, 80.0), (900.0, 0.0, 60.0, 60.0)] {
+            document.artboards.push(Artboard::new(String::new(), Rectangle::new(x, y, width, height)));
+        }
+        document.arrange_artboards(2, 10.0);
+        let corners = document.artboards.iter().map(|artboard| (artboard.rect.x, artboard.rect.y)).collect::<Vec<_>>();
+        assert_eq!(corners, [(10.0, 20.0), (120.0, 20.0), (10.0, 110.0)]);
+    }
+
+    #[test]
+    fn test_scale_document() {
+        let mut document = Document::new("test".to_owned());
+        document.artboards.push(Artboard::new(String::new(), Rectangle::new(100.0, 100.0, 200.0, 100.0)));
+        let curve = Arc::downgrade(document.create_curve(Curve::from(Rectangle::new(150.0, 120.0, 10.0, 10.0))));
+        let width = Arc::downgrade(document.create_width_profile(WidthProfile::Constant { inner: 1.0, outer: 2.0 }));
+        let layer = |content| Layer { name: String::new(), content, style: WeakStyle::new(), export: None, is_template: false };
+        // both layers share the curve, which should still only be scaled once
+        document.layers = vec![layer(LayerContent::Curve(curve.clone())), layer(LayerContent::Curve(curve))];
+        document.views.push(NamedView { name: String::new(), center: Vector2::new(200.0, 150.0), zoom: 1.0, rotation: 0.0 });
+
+        document.scale(2.0);
+        assert_eq!(document.artboards[0].rect, Rectangle::new(100.0, 100.0, 400.0, 200.0));
+        assert_eq!(document.layers[0].control_bounds(), Some(Rectangle::new(200.0, 140.0, 20.0, 20.0)));
+        assert!(matches!(*width.upgrade().unwrap().lock().borrow(), WidthProfile::Constant { inner: 2.0, outer: 4.0 }));
+        assert_eq!((document.views[0].center, document.views[0].zoom), (Vector2::new(300.0, 200.0), 0.5));
+
+        document.scale(0.0);
+        assert_eq!(document.artboards[0].rect.width, 400.0, "nonpositive factors should be ignored");
+    }
+
+    #[test]
+    fn test_repair_layers() {
+        let mut document = Document::new("test".to_owned());
+        let [kept, dropped, lone] = [0.0, 20.0, 40.0].map(|x| Arc::downgrade(document.create_curve(Curve::from(Rectangle::new(x, 0.0, 10.0, 10.0)))));
+        let layer = |content| Layer { name: String::new(), content, style: WeakStyle::new(), export: None, is_template: false };
+        document.layers = vec![
+            layer(LayerContent::Compound(CompoundPath { subpaths: vec![kept.clone(), dropped] })),
+            layer(LayerContent::Curve(lone.clone())),
+            layer(LayerContent::Group(Group { layers: vec![layer(LayerContent::Curve(lone))] })),
+            layer(LayerContent::Curve(kept)),
+        ];
+        document.curves.truncate(1);
+
+        // dropped curves are skipped instead of panicking
+        assert_eq!(document.broken_layer_count(), 3);
+        assert_eq!(document.layers[0].control_bounds(), Some(Rectangle::new(0.0, 0.0, 10.0, 10.0)));
+        assert_eq!(document.layers[1].control_bounds(), None);
+        document.layers[1].translate(Vector2::new(5.0, 5.0));
+
+        assert_eq!(document.repair_layers(), 3);
+        assert_eq!(document.broken_layer_count(), 0);
+        assert_eq!(document.layers.len(), 3);
+        assert!(matches!(&document.layers[0].content, LayerContent::Compound(compound) if compound.subpaths.len() == 1));
+        assert!(matches!(&document.layers[1].content, LayerContent::Group(group) if group.layers.is_empty()));
+    }
 }
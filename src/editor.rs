@@ -1,7 +1,7 @@
-use std::{cell::RefCell, sync::{Arc, Weak}};
+use std::{cell::RefCell, collections::HashMap, path::{Path, PathBuf}, sync::{Arc, Weak}};
 use parking_lot::ReentrantMutex;
 use raylib::prelude::*;
-use crate::{curve::WeakCurve, document::Document, style::{Style, WeakStyle, WeakWidthProfile}};
+use crate::{animation::{self, Animation, Playback}, annotation::{Annotation, AnnotationKind}, brush_tip::TipOptions, bucket, calligraphy::Nib, command::EditorAction, connector::{self, Connector, ConnectorEnd, ConnectorRouting}, curve::{Curve, CurvePoint, WeakCurve}, document::{Document, DocumentInfo, NamedView}, export::{self, ExportJob, ExportPreset, ImageFormat}, file_job::{FileJob, FileOutcome}, fill::{self, FillRule}, format, guides::{self, Guide}, journal::{CurveRecord, Journal, JournalEntry, StyleRecord}, preferences::{BrushPreferences, BucketPreferences, MagicWandPreferences, NavigationPreferences, NewObjectStyle}, layer::{self, CompoundPath, Group, Layer, LayerContent}, palette::{self, PaletteFormat}, query::{LayerFilter, LayerQuery}, raster::{self, TiledRaster}, recognize::{self, Shape}, repeat::{self, GridRepeatOptions}, revision::RevisionDiff, scatter::{self, ScatterOptions}, shape::{self, LiveRectangle}, simplify::SimplifyPreview, stabilizer::Stabilizer, style::{Pattern, Style, TextureTransform, WeakStyle, WeakWidthProfile, WidthProfile}, swatch::Swatch, units::PixelSnap, width_preset::WidthPresetLibrary};
 
 /// A collection selected items
 #[derive(Debug)]
@@ -23,9 +23,120 @@ pub enum Selection {
     ///
     /// Curves should be sorted in the order they appear in the document
     Paths(Vec<WeakCurve>),
+
+    /// One or more entire layers
+    ///
+    /// Each layer is identified by its path of indices from the top-level layers down
+    /// through nested groups; see [`Document::layer_at`]
+    Layers(Vec<Vec<usize>>),
+}
+
+impl Selection {
+    /// Gather the bounds, anchor count, and owning layers of the selection in `document`
+    ///
+    /// Walks every selected curve, so [`Editor`] caches the result as [`Editor::selection_info`]
+    pub fn measure(&self, document: &Document) -> SelectionInfo {
+        /// The index paths of the curve and compound path layers in `layers` drawing any of `curves`, in document order
+        fn visit(layers: &[Layer], prefix: &mut Vec<usize>, curves: &[&WeakCurve], out: &mut Vec<Vec<usize>>) {
+            for (index, layer) in layers.iter().enumerate() {
+                prefix.push(index);
+                match &layer.content {
+                    LayerContent::Group(group) => visit(&group.layers, prefix, curves, out),
+                    _ => if layer.curves().iter().any(|curve| curves.iter().any(|other| other.ptr_eq(curve))) {
+                        out.push(prefix.clone());
+                    },
+                }
+                prefix.pop();
+            }
+        }
+
+        match self {
+            Self::Layers(paths) => {
+                let mut layers = paths.clone();
+                layers.sort();
+                layers.dedup();
+                let selected = layers.iter().filter_map(|path| document.layer_at(path));
+                let bounds = selected.clone()
+                    .filter_map(Layer::control_bounds)
+                    .reduce(layer::union);
+                let anchor_count = selected
+                    .flat_map(Layer::curves)
+                    .filter_map(|curve| curve.upgrade())
+                    .map(|strong_curve| {
+                        let count = strong_curve.lock().borrow().points.len();
+                        count
+                    })
+                    .sum();
+                SelectionInfo { bounds, anchor_count, layers }
+            }
+
+            Self::Paths(curves) => {
+                let mut layers = Vec::new();
+                visit(&document.layers, &mut Vec::new(), &curves.iter().collect::<Vec<_>>(), &mut layers);
+                let (bounds, anchor_count) = curves.iter()
+                    .filter_map(WeakCurve::upgrade)
+                    .fold((None, 0usize), |(bounds, count), strong_curve| {
+                        let curve_lock = strong_curve.lock();
+                        let curve_borrow = curve_lock.borrow();
+                        let bounds = match (bounds, curve_borrow.control_bounds()) {
+                            (Some(a), Some(b)) => Some(layer::union(a, b)),
+                            (a, b) => a.or(b),
+                        };
+                        (bounds, count.saturating_add(curve_borrow.points.len()))
+                    });
+                SelectionInfo { bounds, anchor_count, layers }
+            }
+
+            Self::Points(curves) => {
+                let mut layers = Vec::new();
+                visit(&document.layers, &mut Vec::new(), &curves.iter().map(|(curve, _)| curve).collect::<Vec<_>>(), &mut layers);
+                let mut extent = None::<(na::Vector2<f32>, na::Vector2<f32>)>;
+                let mut anchor_count = 0usize;
+                for (curve, ranges) in curves {
+                    let Some(strong_curve) = curve.upgrade() else { continue };
+                    let curve_lock = strong_curve.lock();
+                    let curve_borrow = curve_lock.borrow();
+                    for range in ranges {
+                        for point in curve_borrow.points.get(range.start as usize..range.end as usize).unwrap_or_default() {
+                            anchor_count = anchor_count.saturating_add(1);
+                            for p in [point.p, point.p + point.c_in, point.p + point.c_out] {
+                                extent = Some(match extent {
+                                    Some((min, max)) => (min.inf(&p), max.sup(&p)),
+                                    None => (p, p),
+                                });
+                            }
+                        }
+                    }
+                }
+                let bounds = extent.map(|(min, max)| Rectangle::new(min.x, min.y, max.x - min.x, max.y - min.y));
+                SelectionInfo { bounds, anchor_count, layers }
+            }
+        }
+    }
+}
+
+/// Aggregate measurements of a [`Selection`], kept by the [`Editor`] so panels and tools don't remeasure it every frame
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SelectionInfo {
+    /// The worldspace rectangle containing every selected anchor and its handles
+    ///
+    /// [`None`] if nothing with artwork is selected
+    pub bounds: Option<Rectangle>,
+
+    /// How many anchors are selected, counting every anchor of selected paths and layers
+    pub anchor_count: usize,
+
+    /// The index paths of the layers the selection belongs to, sorted in document order; see [`Document::layer_at`]
+    ///
+    /// Selected groups are listed themselves, while selected paths and points list each layer drawing their curve
+    pub layers: Vec<Vec<usize>>,
 }
 
 /// Enumation of how user inputs should be interpreted
+///
+/// Switching tools goes through the lifecycle hooks [`Tool::on_activate`], [`Tool::on_deactivate`], [`Tool::commit`],
+/// and [`Tool::cancel`], which match on the tool rather than dispatching through a trait object,
+/// since the tools are a fixed set that is copied and compared like any other value
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 #[non_exhaustive]
 pub enum Tool {
@@ -37,6 +148,16 @@ pub enum Tool {
     #[default]
     PointSelect,
 
+    /// Tool for selecting whole layers
+    ///
+    /// Clicking selects the topmost layer within the current group scope, while double-clicking
+    /// or holding control selects the innermost layer, entering the groups containing it
+    ///
+    /// ### Selection
+    ///
+    /// The layers being selected
+    Select,
+
     /// Tool for painting or sculpting vector paths naturally with a stylus
     ///
     /// ### Selection
@@ -58,9 +179,306 @@ pub enum Tool {
     /// The layer receiving the pixels
     RasterBrush,
 
+    /// Tool for selecting the anchor points inside a freehand loop
+    ///
+    /// Holding control selects the whole paths with every anchor inside the loop instead
+    ///
+    /// ### Selection
+    ///
+    /// The points or paths inside the last loop drawn
+    Lasso,
+
+    /// Tool for selecting every path filled with a color close to the clicked path's fill, at any depth in groups
+    ///
+    /// ### Selection
+    ///
+    /// The layers being selected
+    MagicWand,
+
+    /// Tool for drawing rectangles by dragging from one corner to the opposite corner
+    ///
+    /// Rectangles stay rectangles, so their corners can be rounded with the select tool's corner widgets
+    ///
+    /// ### Selection
+    ///
+    /// The rectangle last drawn
+    Rectangle,
+
+    /// Tool for filling the region enclosed by overlapping paths under the cursor with a new path, for coloring line art
+    ///
+    /// Gaps between the paths narrower than the bucket's gap setting are treated as closed
+    ///
+    /// ### Selection
+    ///
+    /// The region last filled
+    Bucket,
+
     // ...
 }
 
+impl Tool {
+    /// Whether the tool is in the middle of something in `editor` that can be [committed][`Tool::commit`] or [canceled][`Tool::cancel`]
+    ///
+    /// The raster brush never is, since its dabs are painted as the mouse moves
+    pub fn is_busy(self, editor: &Editor) -> bool {
+        match self {
+            Self::Select => editor.move_drag.is_some() || editor.texture_drag.is_some() || editor.corner_drag.is_some(),
+            Self::VectorBrush => !editor.brush_stroke.is_empty(),
+            Self::Lasso => !editor.lasso.is_empty(),
+            Self::Rectangle => editor.rectangle_drag.is_some(),
+            Self::PointSelect | Self::VectorPen | Self::RasterBrush | Self::MagicWand | Self::Bucket => false,
+        }
+    }
+
+    /// Whether holding control changes what the tool does, so control doesn't [spring-load][`SpringTool::Select`] the select tool over it
+    pub const fn is_control_modifier(self) -> bool {
+        matches!(self, Self::Select | Self::Lasso)
+    }
+
+    /// Prepare `editor` for the tool becoming the current tool
+    pub fn on_activate(self, editor: &mut Editor) {
+        editor.smart_guides.clear();
+        if matches!(self, Self::VectorBrush | Self::RasterBrush) {
+            editor.stabilizer.reset();
+        }
+    }
+
+    /// Leave `editor` with nothing in progress for the tool, before another tool becomes the current tool
+    ///
+    /// Work in progress is [committed][`Tool::commit`], so switching tools in the middle of a stroke keeps what was drawn so far
+    pub fn on_deactivate(self, editor: &mut Editor, brushes: &BrushPreferences) {
+        self.commit(editor, brushes);
+    }
+
+    /// Finish the tool's work in progress in `editor` as if the mouse had been released
+    ///
+    /// Brush strokes and rectangles are added to the document, lasso loops select what they enclose,
+    /// and dragged layers are dropped. Does nothing if the tool has nothing in progress
+    pub fn commit(self, editor: &mut Editor, brushes: &BrushPreferences) {
+        match self {
+            Self::Select => {
+                editor.texture_drag = None;
+                editor.corner_drag = None;
+                // dropped layers have their anchors rounded to the pixel grid
+                if let Some(drag) = editor.move_drag.take()
+                    && drag.moved != Vector2::zero()
+                {
+                    editor.snap_selection_to_pixels();
+                }
+            }
+            Self::VectorBrush => if !editor.brush_stroke.is_empty() {
+                editor.finish_brush_stroke(brushes.vector_size, &brushes.calligraphy);
+            },
+            Self::RasterBrush => editor.end_paint(),
+            Self::Lasso => if !editor.lasso.is_empty() {
+                editor.finish_lasso(false);
+            },
+            Self::Rectangle => editor.finish_rectangle(),
+            Self::PointSelect | Self::VectorPen | Self::MagicWand | Self::Bucket => (),
+        }
+        editor.smart_guides.clear();
+    }
+
+    /// Discard the tool's work in progress in `editor`
+    ///
+    /// Dragged layers, texture fills, and corner radii return to how they were before the drag.
+    /// Raster brush dabs already painted are kept
+    pub fn cancel(self, editor: &mut Editor) {
+        match self {
+            Self::Select => {
+                editor.cancel_texture_drag();
+                editor.cancel_corner_drag();
+                if let Some(drag) = editor.move_drag.take() {
+                    editor.translate_selection(-drag.moved);
+                }
+            }
+            Self::VectorBrush => {
+                editor.brush_stroke.clear();
+                editor.recognized_shape = None;
+                editor.stabilizer.reset();
+            }
+            Self::RasterBrush => editor.end_paint(),
+            Self::Lasso => editor.lasso.clear(),
+            Self::Rectangle => editor.rectangle_drag = None,
+            Self::PointSelect | Self::VectorPen | Self::MagicWand | Self::Bucket => (),
+        }
+        editor.smart_guides.clear();
+    }
+
+    /// The tool `action` switches to, if it is one of the tool shortcuts
+    pub const fn from_action(action: EditorAction) -> Option<Self> {
+        match action {
+            EditorAction::PointSelect => Some(Self::PointSelect),
+            EditorAction::Select => Some(Self::Select),
+            EditorAction::VectorBrush => Some(Self::VectorBrush),
+            EditorAction::RasterBrush => Some(Self::RasterBrush),
+            EditorAction::Lasso => Some(Self::Lasso),
+            EditorAction::MagicWand => Some(Self::MagicWand),
+            EditorAction::Rectangle => Some(Self::Rectangle),
+            EditorAction::Bucket => Some(Self::Bucket),
+            _ => None,
+        }
+    }
+
+    /// Handle the left mouse button being pressed at the worldspace `position`, `time` seconds since the application started
+    ///
+    /// `select_at` selects the layer at a position for the select tool, and is given whether to select the innermost layer. \
+    /// The raster brush paints with the GPU, so is left to the caller
+    pub fn press(
+        self,
+        editor: &mut Editor,
+        position: Vector2,
+        time: f64,
+        modifiers: ToolModifiers,
+        settings: &ToolSettings,
+        select_at: impl FnOnce(&mut Editor, Vector2, bool),
+    ) {
+        match self {
+            Self::PointSelect | Self::VectorPen | Self::RasterBrush => (),
+            Self::Select => {
+                // pressing on a texture handle or corner widget drags it instead of selecting
+                if !editor.start_texture_drag(position, TextureHandle::RADIUS / editor.camera.zoom) && !editor.start_corner_drag(position) {
+                    let is_double_click = editor.click(position, time);
+                    select_at(editor, position, is_double_click || modifiers.is_control);
+                    // pressing on a layer selects it and starts dragging it
+                    if matches!(editor.selection(), Selection::Layers(paths) if !paths.is_empty()) {
+                        editor.move_drag = Some(MoveDrag { start: position, moved: Vector2::zero() });
+                    }
+                }
+            }
+            Self::VectorBrush => {
+                let brushes = settings.brushes;
+                editor.brush_stroke.clear();
+                editor.stabilizer.reset();
+                let position = editor.stabilizer.push(position, brushes.stabilizer, brushes.stabilizer_strength, editor.camera.zoom);
+                editor.extend_brush_stroke(position);
+            }
+            Self::MagicWand => editor.select_same_fill_at(position, settings.magic_wand.tolerance),
+            Self::Bucket => editor.bucket_fill_at(position, settings.bucket.gap),
+            Self::Lasso => {
+                editor.lasso.clear();
+                editor.extend_lasso(position);
+            }
+            Self::Rectangle => {
+                let position = editor.snap_point_to_pixels(position);
+                editor.rectangle_drag = Some((position, position));
+            }
+        }
+    }
+
+    /// Handle the mouse moving to the worldspace `position` with the left button held
+    pub fn drag(self, editor: &mut Editor, position: Vector2, modifiers: ToolModifiers, settings: &ToolSettings) {
+        match self {
+            Self::Select => {
+                if editor.texture_drag.is_some() {
+                    editor.drag_texture_handle(position);
+                } else if editor.corner_drag.is_some() {
+                    // holding alt rounds only the dragged corner
+                    editor.drag_corner_widget(position, modifiers.is_alt);
+                } else {
+                    editor.drag_selection(position, settings.guide_tolerance);
+                }
+            }
+            Self::Rectangle => {
+                let position = editor.snap_point_to_pixels(position);
+                if let Some((_, end)) = &mut editor.rectangle_drag {
+                    *end = position;
+                }
+            }
+            Self::VectorBrush => if !editor.brush_stroke.is_empty() {
+                let brushes = settings.brushes;
+                let position = editor.stabilizer.push(position, brushes.stabilizer, brushes.stabilizer_strength, editor.camera.zoom);
+                editor.extend_brush_stroke(position);
+            },
+            Self::Lasso => if !editor.lasso.is_empty() {
+                editor.extend_lasso(position);
+            },
+            Self::PointSelect | Self::VectorPen | Self::RasterBrush | Self::MagicWand | Self::Bucket => (),
+        }
+    }
+
+    /// Handle the left mouse button being held for `seconds` since the last drag or hold
+    ///
+    /// A vector brush stroke held still long enough is [recognized as a shape][`Editor::rest_brush_stroke`]
+    pub fn hold(self, editor: &mut Editor, seconds: f32, brushes: &BrushPreferences) {
+        if self == Self::VectorBrush && brushes.is_recognizing_shapes && !editor.brush_stroke.is_empty() {
+            editor.rest_brush_stroke(seconds);
+        }
+    }
+
+    /// Handle the left mouse button being let go
+    ///
+    /// Like [`Tool::commit`], except that a lasso loop drawn with control held selects whole paths
+    pub fn release(self, editor: &mut Editor, modifiers: ToolModifiers, brushes: &BrushPreferences) {
+        match self {
+            Self::Lasso if !editor.lasso.is_empty() => {
+                editor.finish_lasso(modifiers.is_control);
+                editor.smart_guides.clear();
+            }
+            _ => self.commit(editor, brushes),
+        }
+    }
+}
+
+/// The modifier keys held while a [`Tool`] handles the mouse
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ToolModifiers {
+    /// Either control key is held
+    pub is_control: bool,
+
+    /// Either alt key is held
+    pub is_alt: bool,
+}
+
+/// The preferences a [`Tool`] reads while handling the mouse
+#[derive(Debug, Clone, Copy)]
+pub struct ToolSettings<'a> {
+    /// The vector brush's stabilizer
+    pub brushes: &'a BrushPreferences,
+
+    /// The magic wand's color tolerance
+    pub magic_wand: MagicWandPreferences,
+
+    /// The bucket's gap size
+    pub bucket: BucketPreferences,
+
+    /// The worldspace distance within which dragged layers snap to smart guides, or [`None`] if smart guides are off
+    pub guide_tolerance: Option<f32>,
+}
+
+/// A tool used only while its key is held, after which the tool it was pressed over comes back with its work in progress intact
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpringTool {
+    /// Held with space: dragging with the left mouse button pans the view, and the current tool ignores the mouse
+    Pan,
+
+    /// Held with control: the select tool
+    Select,
+}
+
+impl SpringTool {
+    /// The tool that becomes current while held
+    ///
+    /// [`None`] if the current tool stays current, only ignoring input
+    pub const fn tool(self) -> Option<Tool> {
+        match self {
+            Self::Pan => None,
+            Self::Select => Some(Tool::Select),
+        }
+    }
+}
+
+/// One of the current style's two color wells in the toolbar
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorWell {
+    /// The style's fill pattern
+    #[default]
+    Fill,
+
+    /// The style's stroke pattern
+    Stroke,
+}
+
 /// A reuseable that may not be inside a document yet
 #[derive(Debug)]
 pub enum MaybeNew<T> {
@@ -84,12 +502,21 @@ pub struct Editor {
 
     /// The current selection
     ///
-    /// Takes on different meanings depending on `current_tool`
-    pub selection: Selection,
+    /// Takes on different meanings depending on `current_tool`.
+    /// Replaced with [`Editor::set_selection`] so that `selection_info` stays in sync
+    selection: Selection,
+
+    /// The cached measurements of `selection`
+    selection_info: SelectionInfo,
 
     /// The way user input should be used
     pub current_tool: Tool,
 
+    /// The [spring-loaded tools][`SpringTool`] being held, each with the tool that was current when it was pressed
+    ///
+    /// The last was pressed most recently
+    pub tool_stack: Vec<(SpringTool, Tool)>,
+
     /// The viewport camera
     pub camera: Camera2D,
 
@@ -99,15 +526,281 @@ pub struct Editor {
     /// new one that should be applied to the next styled item
     /// created by this editor
     pub current_style: MaybeNew<Style>,
+
+    /// Whether objects drawn by this editor get the document's default style or `current_style`
+    ///
+    /// Kept in step with [`Preferences::new_object_style`][`crate::preferences::Preferences::new_object_style`] by the engine
+    pub new_object_style: NewObjectStyle,
+
+    /// The most zoomed out and most zoomed in the camera can be
+    ///
+    /// Kept in step with [`Preferences::navigation`][`crate::preferences::Preferences::navigation`] by the engine
+    pub zoom_limits: (f32, f32),
+
+    /// The animation mode state
+    ///
+    /// [`None`] if the editor is not in animation mode
+    pub playback: Option<Playback>,
+
+    /// Edits made since the document was last saved
+    ///
+    /// [`None`] if the document has never been saved, since there is no snapshot to replay onto
+    pub journal: Option<Journal>,
+
+    /// The worldspace position the raster brush last painted at
+    ///
+    /// [`None`] if a brush stroke is not in progress
+    pub last_paint_position: Option<Vector2>,
+
+    /// The path of the group the select tool is scoped to
+    ///
+    /// Empty when selecting from the top-level layers
+    pub group_scope: Vec<usize>,
+
+    /// The time (in seconds) and worldspace position of the last select tool click, for detecting double-clicks
+    pub last_click: Option<(f64, Vector2)>,
+
+    /// The "Compare with saved" view
+    ///
+    /// [`None`] if the editor is not comparing
+    pub comparison: Option<RevisionDiff>,
+
+    /// The index of the artboard coordinates are measured from while they are [artboard-relative][`crate::preferences::CoordinatePreferences::is_artboard_relative`]
+    ///
+    /// Set by clicking on an artboard. Falls back to the first artboard if out of range
+    pub active_artboard: usize,
+
+    /// The index of the [named view][`crate::document::NamedView`] last saved or jumped to, which cycling continues from
+    ///
+    /// [`None`] if no view has been used since the document was opened
+    pub current_view: Option<usize>,
+
+    /// Whether the document's [annotations][`crate::annotation::Annotation`] are drawn over the artwork
+    pub is_showing_annotations: bool,
+
+    /// Whether vector brush strokes are added as markup annotations instead of curve layers
+    pub is_marking_up: bool,
+
+    /// Whether artwork is only drawn inside the artboards, showing what will be exported
+    ///
+    /// Artwork overflowing the artboards is drawn by default, so it can still be edited
+    pub is_trimming_to_artboards: bool,
+
+    /// The indices of the artboards selected in the [artboards panel][`crate::dialog::ArtboardPanel`], in the order they were selected
+    pub selected_artboards: Vec<usize>,
+
+    /// Whether the editor's tab is pinned; see [`Engine::set_editor_pinned`][`crate::engine::Engine::set_editor_pinned`]
+    pub is_pinned: bool,
+
+    /// Whether the document has changed since it was last saved
+    ///
+    /// Set with [`Editor::mark_edited`] for edits that aren't journaled on their own
+    pub is_dirty: bool,
+
+    /// Whether the document was [edited][`Editor::mark_edited`] since the journal last stored a snapshot of it
+    has_unjournaled_edits: bool,
+
+    /// The frame sequence, artboard, or asset export in progress
+    ///
+    /// [`None`] if the document is not being exported
+    pub export: Option<ExportJob>,
+
+    /// The save in progress
+    ///
+    /// [`None`] if the document is not being saved
+    pub save_job: Option<SaveJob>,
+
+    /// The path and compression of a save requested while another was in progress, started once that one finishes
+    ///
+    /// [`None`] if no save is waiting
+    pub queued_save: Option<(PathBuf, bool)>,
+
+    /// The [autosave][`format::autosave_path`] being written beside the document
+    ///
+    /// [`None`] if the document is not being autosaved
+    pub autosave_job: Option<FileJob>,
+
+    /// The screenspace positions of the two touches of the pinch in progress, as of the previous frame
+    ///
+    /// [`None`] if fewer than two fingers are touching
+    pub last_pinch: Option<[Vector2; 2]>,
+
+    /// The color well that "set color to none" applies to
+    pub active_well: ColorWell,
+
+    /// Whether the raster brush erases instead of painting
+    pub is_erasing: bool,
+
+    /// The smoothing state of the current brush stroke
+    pub stabilizer: Stabilizer,
+
+    /// The select tool drag in progress
+    ///
+    /// [`None`] if the selected layers are not being dragged
+    pub move_drag: Option<MoveDrag>,
+
+    /// The texture fill handle being dragged with the select tool, the worldspace offset from the mouse to the handle,
+    /// and the texture's placement before the drag
+    ///
+    /// [`None`] if no handle is being dragged
+    pub texture_drag: Option<(TextureHandle, Vector2, TextureTransform)>,
+
+    /// The corner radius widget of the selected rectangle being dragged with the select tool,
+    /// by index clockwise from the top-left corner, and the rectangle's radii before the drag
+    ///
+    /// [`None`] if no widget is being dragged
+    pub corner_drag: Option<(usize, [f32; 4])>,
+
+    /// The worldspace corner the rectangle tool's drag started from and the corner the mouse is at
+    ///
+    /// [`None`] if no rectangle is being drawn
+    pub rectangle_drag: Option<(Vector2, Vector2)>,
+
+    /// The smart guides to show for the current drag or brush stroke
+    pub smart_guides: Vec<Guide>,
+
+    /// The Simplify being previewed over the selection, which takes over input until it is applied or canceled
+    ///
+    /// [`None`] if not simplifying
+    pub simplify: Option<SimplifyPreview>,
+
+    /// The worldspace loop being drawn with the lasso tool, implicitly closed
+    ///
+    /// Empty if not lassoing
+    pub lasso: Vec<Vector2>,
+
+    /// The worldspace points of the vector brush stroke being drawn
+    ///
+    /// Empty if not drawing
+    pub brush_stroke: Vec<Vector2>,
+
+    /// Seconds the end of the vector brush stroke has stayed where it is
+    pub brush_rest: f32,
+
+    /// The shape the vector brush stroke was recognized as when it was held still, which it becomes when finished
+    ///
+    /// [`None`] if the stroke stays freehand
+    pub recognized_shape: Option<Shape>,
+}
+
+/// A document being written by [`Editor::start_save`]
+#[derive(Debug)]
+pub struct SaveJob {
+    /// The thread writing the document
+    pub job: FileJob,
+
+    /// The document info from before the save, restored if it fails
+    prev_info: DocumentInfo,
+
+    /// The [end][`Journal::end`] of the journal when the document was encoded; entries from then on aren't in the saved file
+    journal_end: usize,
+}
+
+/// A drag moving the selected layers with the select tool
+#[derive(Debug, Clone, Copy)]
+pub struct MoveDrag {
+    /// The worldspace position of the mouse when the drag started
+    pub start: Vector2,
+
+    /// The worldspace offset the layers have been moved by so far
+    pub moved: Vector2,
+}
+
+/// One of the select tool's handles for placing a [texture fill][`Pattern::Texture`] within the selected layer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextureHandle {
+    /// At the center of the texture, moving it
+    Move,
+
+    /// At the bottom-right corner of the texture, resizing it about its center
+    Scale,
+
+    /// Above the top edge of the texture, turning it about its center
+    Rotate,
+}
+
+impl TextureHandle {
+    /// Every handle, in the order they are drawn
+    pub const ALL: [Self; 3] = [Self::Move, Self::Scale, Self::Rotate];
+
+    /// Screenspace radius of the handles, within which they can be grabbed
+    pub const RADIUS: f32 = 5.0;
+
+    /// The texture coordinates of the handle; see [`TextureTransform::to_texture`]
+    pub const fn uv(self) -> Vector2 {
+        match self {
+            Self::Move => Vector2 { x: 0.5, y: 0.5 },
+            Self::Scale => Vector2 { x: 1.0, y: 1.0 },
+            Self::Rotate => Vector2 { x: 0.5, y: -0.25 },
+        }
+    }
+}
+
+/// The select tool's widgets for rounding the corners of a [`LiveRectangle`]
+pub struct CornerWidget;
+
+impl CornerWidget {
+    /// Screenspace radius of the widgets, within which they can be grabbed
+    pub const RADIUS: f32 = 4.0;
+
+    /// Least screenspace distance from each widget to its corner, so sharp corners aren't covered
+    pub const INSET: f32 = 12.0;
+}
+
+/// How the camera should move in response to two fingers moving across a touchscreen or trackpad
+#[derive(Debug, Clone, Copy)]
+pub struct PinchGesture {
+    /// Screenspace movement of the point between the fingers
+    pub pan: Vector2,
+
+    /// Ratio of the distance between the fingers to their distance on the previous frame
+    pub scale: f32,
+
+    /// Screenspace point between the fingers, which zooming should be centered on
+    pub center: Vector2,
+}
+
+impl PinchGesture {
+    /// The gesture of two fingers moving from the screenspace positions `previous` to `current`
+    pub fn between([last_a, last_b]: [Vector2; 2], [a, b]: [Vector2; 2]) -> Self {
+        let center = a.lerp(b, 0.5);
+        let last_distance = last_a.distance_to(last_b);
+        Self {
+            pan: center - last_a.lerp(last_b, 0.5),
+            // fingers landing on the same spot have no meaningful scale
+            scale: if last_distance > f32::EPSILON { a.distance_to(b) / last_distance } else { 1.0 },
+            center,
+        }
+    }
 }
 
 impl Editor {
+    /// The longest time (in seconds) between two clicks that counts as a double-click
+    pub const DOUBLE_CLICK_TIME: f64 = 0.4;
+
+    /// The farthest distance (in screenspace pixels) between two clicks that counts as a double-click
+    pub const DOUBLE_CLICK_DISTANCE: f32 = 4.0;
+
+    /// The screenspace distance the mouse must move before the lasso loop gets another point
+    pub const LASSO_SPACING: f32 = 3.0;
+
+    /// The screenspace distance a finished vector brush stroke may stray from the drawn points when its anchors are reduced
+    pub const BRUSH_TOLERANCE: f32 = 2.0;
+
+    /// The screenspace distance from an annotation's lines that still counts as clicking it
+    pub const ANNOTATION_HIT_TOLERANCE: f32 = 4.0;
+
+    /// The screenspace size of the cells the [bucket][`Tool::Bucket`] finds regions on
+    pub const BUCKET_RESOLUTION: f32 = 1.0;
+
     /// Construct a new editor with default values and no allocation
     pub const fn new(document: Document, current_style: MaybeNew<Style>) -> Self {
         Self {
             document,
             selection: Selection::Paths(Vec::new()),
+            selection_info: SelectionInfo::default(),
             current_tool: Tool::PointSelect,
+            tool_stack: Vec::new(),
             camera: Camera2D {
                 offset: Vector2::zero(),
                 target: Vector2::zero(),
@@ -115,9 +808,52 @@ impl Editor {
                 zoom: 1.0,
             },
             current_style,
+            new_object_style: NewObjectStyle::LastSelected,
+            zoom_limits: (NavigationPreferences::default_navigation_preferences().min_zoom, NavigationPreferences::default_navigation_preferences().max_zoom),
+            playback: None,
+            journal: None,
+            last_paint_position: None,
+            group_scope: Vec::new(),
+            last_click: None,
+            comparison: None,
+            active_artboard: 0,
+            current_view: None,
+            is_showing_annotations: true,
+            is_marking_up: false,
+            is_trimming_to_artboards: false,
+            selected_artboards: Vec::new(),
+            is_pinned: false,
+            is_dirty: false,
+            has_unjournaled_edits: false,
+            export: None,
+            save_job: None,
+            queued_save: None,
+            autosave_job: None,
+            last_pinch: None,
+            active_well: ColorWell::Fill,
+            is_erasing: false,
+            stabilizer: Stabilizer::new(),
+            move_drag: None,
+            texture_drag: None,
+            corner_drag: None,
+            rectangle_drag: None,
+            smart_guides: Vec::new(),
+            simplify: None,
+            lasso: Vec::new(),
+            brush_stroke: Vec::new(),
+            brush_rest: 0.0,
+            recognized_shape: None,
         }
     }
 
+    /// Construct a new editor whose current style is the [default style][`Style::default_style`]
+    ///
+    /// Adds the [default width profile][`WidthProfile::default_width_profile`] to the document for the style to reference
+    pub fn new_default(mut document: Document) -> Self {
+        let profile = Arc::downgrade(document.create_width_profile(WidthProfile::default_width_profile()));
+        Self::new(document, MaybeNew::New(Style::default_style(profile)))
+    }
+
     /// Push `current_style` to the document's local styles and get a weak reference to it
     pub fn upgrade_current_style(&mut self) -> &WeakStyle {
         if let MaybeNew::New(style) = std::mem::take(&mut self.current_style) {
@@ -127,4 +863,2111 @@ impl Editor {
         let MaybeNew::Existing(weak_style) = &self.current_style else { unreachable!("current_style should have either already been Existing or just been assigned Existing") };
         weak_style
     }
+
+    /// The style to give the next object drawn in this editor; see [`Editor::new_object_style`]
+    ///
+    /// The current style is pushed to the document if it is new.
+    /// If the document has no default style yet and one is wanted, a [default style][`Style::default_style`] is created for it
+    pub fn style_for_new_object(&mut self) -> WeakStyle {
+        match self.new_object_style {
+            NewObjectStyle::LastSelected => self.upgrade_current_style().clone(),
+            NewObjectStyle::DocumentDefault => {
+                if let Some(style) = &self.document.default_style
+                    && style.strong_count() > 0
+                {
+                    return style.clone();
+                }
+                let profile = Arc::downgrade(self.document.create_width_profile(WidthProfile::default_width_profile()));
+                let style = Arc::downgrade(self.document.create_style(Style::default_style(profile)));
+                self.document.default_style = Some(style.clone());
+                style
+            }
+        }
+    }
+
+    /// Make the current style the document's default style, pushing it to the document if it is new
+    pub fn set_default_style(&mut self) {
+        let style = self.upgrade_current_style().clone();
+        self.document.default_style = Some(style);
+        self.mark_edited();
+    }
+
+    /// The current style's fill and stroke patterns
+    ///
+    /// Both are transparent if the current style no longer exists
+    pub fn current_patterns(&self) -> (Pattern, Pattern) {
+        match &self.current_style {
+            MaybeNew::New(style) => (style.fill.clone(), style.stroke.pattern.clone()),
+            MaybeNew::Existing(style) => match style.upgrade() {
+                Some(strong) => {
+                    let style_lock = strong.lock();
+                    let style_borrow = style_lock.borrow();
+                    (style_borrow.fill.clone(), style_borrow.stroke.pattern.clone())
+                }
+                None => (Pattern::new(), Pattern::new()),
+            },
+        }
+    }
+
+    /// Apply `edit` to the current style, shared with every layer using it
+    ///
+    /// Does nothing if the current style no longer exists
+    pub fn edit_current_style(&mut self, edit: impl FnOnce(&mut Style)) {
+        match &mut self.current_style {
+            MaybeNew::New(style) => edit(style),
+            MaybeNew::Existing(style) => if let Some(strong) = style.upgrade() {
+                let style = style.clone();
+                let before = strong.lock().borrow().clone();
+                edit(&mut *strong.lock().borrow_mut());
+                self.record_style(&style, &before);
+            },
+        }
+    }
+
+    /// Switch to `tool`, [committing][`Tool::commit`] whatever the previous tool was in the middle of
+    ///
+    /// Any [spring-loaded tools][`SpringTool`] being held are let go of, committing the tools they suspended too.
+    /// `brushes` finishes a vector brush stroke in progress. Does nothing if `tool` is already the current tool
+    pub fn set_tool(&mut self, tool: Tool, brushes: &BrushPreferences) {
+        for (_, suspended) in std::mem::take(&mut self.tool_stack) {
+            if suspended != self.current_tool {
+                suspended.on_deactivate(self, brushes);
+            }
+        }
+        if tool == self.current_tool {
+            return;
+        }
+        self.current_tool.on_deactivate(self, brushes);
+        self.current_tool = tool;
+        tool.on_activate(self);
+    }
+
+    /// Start using `spring` until it is [released][`Editor::release_spring_tool`], suspending the current tool without committing its work in progress
+    ///
+    /// Does nothing if `spring` is already held
+    pub fn press_spring_tool(&mut self, spring: SpringTool) {
+        if self.tool_stack.iter().any(|&(held, _)| held == spring) {
+            return;
+        }
+        self.tool_stack.push((spring, self.current_tool));
+        if let Some(tool) = spring.tool() && tool != self.current_tool {
+            self.current_tool = tool;
+            tool.on_activate(self);
+        }
+    }
+
+    /// Stop using `spring`, returning to the tool it was pressed over
+    ///
+    /// Spring-loaded tools pressed after `spring` are released with it. Whatever they were in the middle of is
+    /// [committed][`Tool::commit`] with `brushes`, while the tool coming back picks up where it left off.
+    /// Does nothing if `spring` isn't held
+    pub fn release_spring_tool(&mut self, spring: SpringTool, brushes: &BrushPreferences) {
+        let Some(index) = self.tool_stack.iter().position(|&(held, _)| held == spring) else { return };
+        let (_, suspended) = self.tool_stack[index];
+        self.tool_stack.truncate(index);
+        if suspended != self.current_tool {
+            self.current_tool.commit(self, brushes);
+            self.current_tool = suspended;
+        }
+    }
+
+    /// Whether a [spring-loaded][`SpringTool::Pan`] pan is held, so the left mouse button pans instead of using the current tool
+    pub fn is_panning(&self) -> bool {
+        self.tool_stack.iter().any(|&(held, _)| held == SpringTool::Pan)
+    }
+
+    /// The current style's stroke width profile, shared with every style referencing it
+    ///
+    /// [`None`] if the stroke has no width profile or the current style no longer exists
+    pub fn current_stroke_width(&self) -> Option<WeakWidthProfile> {
+        match &self.current_style {
+            MaybeNew::New(style) => style.stroke.width.clone(),
+            MaybeNew::Existing(style) => style.upgrade()?.lock().borrow().stroke.width.clone(),
+        }
+    }
+
+    /// A copy of the current style's stroke width profile
+    ///
+    /// [`None`] if the stroke has no width profile or the current style no longer exists
+    pub fn current_width_profile(&self) -> Option<WidthProfile> {
+        let profile = self.current_stroke_width()?.upgrade()?;
+        let copy = profile.lock().borrow().clone();
+        Some(copy)
+    }
+
+    /// The number of segments in the longest path of the selected layers, for spreading width profiles over
+    ///
+    /// 1 if no paths are selected
+    pub fn selected_path_length(&self) -> usize {
+        let length = match &self.selection {
+            Selection::Layers(paths) => paths.iter()
+                .filter_map(|path| self.document.layer_at(path))
+                .flat_map(Layer::curves)
+                .filter_map(|curve| curve.upgrade())
+                .map(|curve| curve.lock().borrow().segment_count())
+                .max(),
+            _ => None,
+        };
+        length.unwrap_or(1).max(1)
+    }
+
+    /// Give the current style's stroke a copy of the preset at `index` in `presets`, as thick as the stroke already is
+    ///
+    /// The preset is spread along the longest selected path, or over a single segment if no paths are selected
+    pub fn apply_width_preset(&mut self, presets: &WidthPresetLibrary, index: usize) -> Result<(), String> {
+        let thickness = self.current_width_profile()
+            .map(|profile| profile.max_thickness())
+            .filter(|&thickness| thickness > 0.0)
+            .unwrap_or_else(|| WidthProfile::default_width_profile().max_thickness());
+        let length = self.selected_path_length();
+        let profile = presets.apply(index, thickness, length as f32, &mut self.document)?;
+        self.edit_current_style(|style| style.stroke.width = Some(profile));
+        Ok(())
+    }
+
+    /// Exchange the current style's fill and stroke patterns
+    pub fn swap_fill_stroke(&mut self) {
+        self.edit_current_style(|style| std::mem::swap(&mut style.fill, &mut style.stroke.pattern));
+    }
+
+    /// Restore the current style's fill and stroke patterns to the [default style's][`Style::default_style`] colors
+    pub fn reset_fill_stroke(&mut self) {
+        self.edit_current_style(|style| {
+            style.fill = Pattern::Solid(Style::DEFAULT_FILL);
+            style.stroke.pattern = Pattern::Solid(Style::DEFAULT_STROKE);
+        });
+    }
+
+    /// Make the current style's [active color well][`Editor::active_well`] transparent
+    pub fn clear_active_well(&mut self) {
+        let well = self.active_well;
+        self.edit_current_style(|style| match well {
+            ColorWell::Fill => style.fill = Pattern::new(),
+            ColorWell::Stroke => style.stroke.pattern = Pattern::new(),
+        });
+    }
+
+    /// The color the raster brush paints with; the current style's fill if it is solid, otherwise black
+    pub fn brush_color(&self) -> Color {
+        let fill = match &self.current_style {
+            MaybeNew::New(style) => style.fill.clone(),
+            MaybeNew::Existing(style) => match style.upgrade() {
+                Some(strong) => strong.lock().borrow().fill.clone(),
+                None => return Color::BLACK,
+            },
+        };
+        match fill {
+            Pattern::Solid(color) => color,
+            Pattern::Texture(..) => Color::BLACK,
+        }
+    }
+
+    /// Continue the current raster brush stroke to the worldspace `position` with a brush of worldspace `radius`,
+    /// or start one if there isn't one
+    ///
+    /// Paints onto the topmost layer if it is a paint layer, otherwise a new paint layer is added on top
+    ///
+    /// The brush is round, unless a `tip` is given to stamp along the stroke
+    pub fn paint(&mut self, rl: &mut RaylibHandle, thread: &RaylibThread, position: Vector2, radius: f32, tip: Option<(&Texture2D, TipOptions)>) -> Result<(), String> {
+        if !matches!(self.document.layers.last(), Some(Layer { content: LayerContent::Paint(_), .. })) {
+            let style = self.style_for_new_object();
+            self.document.layers.push(Layer {
+                name: "Paint".to_string(),
+                content: LayerContent::Paint(TiledRaster::new(Vector2::zero())),
+                style,
+                export: None,
+                is_template: false,
+            });
+        }
+        self.mark_edited();
+        let color = self.brush_color();
+        let Some(Layer { content: LayerContent::Paint(paint), .. }) = self.document.layers.last_mut() else {
+            unreachable!("topmost layer should have either already been a paint layer or just been pushed as one")
+        };
+        let Some((texture, options)) = tip else {
+            return match self.last_paint_position.replace(position) {
+                Some(start) => paint.paint_line(rl, thread, start, position, radius, color),
+                None => paint.paint_circle(rl, thread, position, radius, color),
+            };
+        };
+
+        // stamps are spaced from the last stamp rather than from where the pointer was last frame,
+        // so wide spacing isn't cut short by slow strokes
+        let (stamps, direction) = match self.last_paint_position {
+            Some(last) => (
+                raster::stamp_positions(last, position, options.spacing * radius * 2.0).collect::<Vec<_>>(),
+                (position.y - last.y).atan2(position.x - last.x).to_degrees(),
+            ),
+            None => (vec![position], 0.0),
+        };
+        let jitter = |rl: &RaylibHandle| rl.get_random_value::<i32>(-1000..1000) as f32 / 1000.0 * options.jitter;
+        for center in stamps {
+            let offset = Vector2::new(jitter(rl), jitter(rl)) * radius;
+            let angle = if options.is_following { direction } else { jitter(rl) * 180.0 };
+            paint.paint_stamp(rl, thread, center + offset, radius, angle, texture, color)?;
+            self.last_paint_position = Some(center);
+        }
+        Ok(())
+    }
+
+    /// Continue the current eraser stroke to the worldspace `position` with an eraser of worldspace `radius`,
+    /// or start one if there isn't one
+    ///
+    /// If the current style's fill is a texture, it is erased where it is stretched over the selected layers.
+    /// Otherwise the topmost layer is erased if it is a paint layer
+    pub fn erase(&mut self, rl: &mut RaylibHandle, thread: &RaylibThread, position: Vector2, radius: f32, hardness: f32) {
+        let start = self.last_paint_position.replace(position);
+        let centers = match start {
+            Some(start) => raster::dab_positions(start, position, radius).collect::<Vec<_>>(),
+            None => vec![position],
+        };
+        if let (Pattern::Texture(texture, transform), _) = self.current_patterns()
+            && let Some(bounds) = self.selection_bounds()
+            && let Some(strong) = texture.upgrade()
+        {
+            let texture_lock = strong.lock();
+            raster::erase_texture(rl, thread, &mut texture_lock.borrow_mut(), bounds, &transform, centers, radius, hardness);
+            self.mark_edited();
+        } else if let Some(Layer { content: LayerContent::Paint(paint), .. }) = self.document.layers.last_mut() {
+            paint.erase(rl, thread, centers, radius, hardness);
+            self.mark_edited();
+        }
+    }
+
+    /// Finish the current raster brush stroke, if there is one
+    pub fn end_paint(&mut self) {
+        self.last_paint_position = None;
+        self.stabilizer.reset();
+    }
+
+    /// Move the camera so the artwork moves by the screenspace `delta`
+    pub fn pan(&mut self, delta: Vector2) {
+        self.camera.target -= delta / self.camera.zoom;
+    }
+
+    /// Multiply the camera's zoom by `factor`, keeping the worldspace point under the screenspace `anchor` in place
+    ///
+    /// The zoom is clamped to [`Editor::zoom_limits`]
+    pub fn zoom_about(&mut self, anchor: Vector2, factor: f32) {
+        let world_anchor = self.camera.target + (anchor - self.camera.offset) / self.camera.zoom;
+        self.camera.offset = anchor;
+        self.camera.target = world_anchor;
+        let (min_zoom, max_zoom) = self.zoom_limits;
+        self.camera.zoom = (self.camera.zoom * factor).clamp(min_zoom, max_zoom);
+    }
+
+    /// Add `annotation` over the rest, showing annotations if they were hidden
+    pub fn add_annotation(&mut self, annotation: Annotation) {
+        self.document.annotations.push(annotation.clone());
+        self.is_showing_annotations = true;
+        self.record(JournalEntry::AddAnnotation(annotation));
+    }
+
+    /// Add an arrow from the most recently added sticky note to the worldspace `target`
+    ///
+    /// Does nothing if the document has no notes
+    pub fn add_arrow(&mut self, target: Vector2) {
+        let Some(position) = self.document.annotations.iter()
+            .rev()
+            .find(|annotation| matches!(annotation.kind, AnnotationKind::Note { .. }))
+            .map(|note| note.position)
+        else { return };
+        self.add_annotation(Annotation { position, kind: AnnotationKind::Arrow { target } });
+    }
+
+    /// The index of the topmost annotation under the screenspace `point`, as drawn at `font_size`
+    ///
+    /// [`None`] if annotations are hidden or none are under `point`
+    pub fn annotation_at(&self, point: Vector2, font_size: i32) -> Option<usize> {
+        if !self.is_showing_annotations {
+            return None;
+        }
+        self.document.annotations.iter()
+            .rposition(|annotation| annotation.is_hit(point, &self.camera, font_size, Self::ANNOTATION_HIT_TOLERANCE))
+    }
+
+    /// Remove the annotation at `index`
+    ///
+    /// Does nothing if there is no such annotation
+    pub fn remove_annotation(&mut self, index: usize) {
+        if index < self.document.annotations.len() {
+            self.document.annotations.remove(index);
+            self.record(JournalEntry::RemoveAnnotation(index));
+        }
+    }
+
+    /// The screenspace rectangles the artwork is drawn within, out of the `screen`
+    ///
+    /// Just the screen, unless [trimming to artboards][`Editor::is_trimming_to_artboards`], in which case each artboard
+    /// on screen gets its own. Scissor rectangles can't rotate, so a rotated artboard is clipped to the box around it
+    pub fn artwork_clips(&self, screen: Rectangle) -> Vec<Rectangle> {
+        if !self.is_trimming_to_artboards {
+            return vec![screen];
+        }
+        let camera = &self.camera;
+        let to_screen = |point: Vector2| camera.offset + ((point - camera.target) * camera.zoom).rotated(camera.rotation.to_radians());
+        self.document.artboards.iter()
+            .map(|artboard| {
+                let rect = artboard.rect;
+                let corners = [(0.0, 0.0), (1.0, 0.0), (0.0, 1.0), (1.0, 1.0)]
+                    .map(|(u, v)| to_screen(Vector2::new(rect.x + rect.width * u, rect.y + rect.height * v)));
+                let (min, max) = corners.iter().fold((corners[0], corners[0]), |(min, max), corner| {
+                    (Vector2::new(min.x.min(corner.x), min.y.min(corner.y)), Vector2::new(max.x.max(corner.x), max.y.max(corner.y)))
+                });
+                Rectangle::new(min.x, min.y, max.x - min.x, max.y - min.y)
+            })
+            .filter(|clip| clip.check_collision_recs(&screen))
+            .collect()
+    }
+
+    /// Save the camera as a new named view of the document
+    ///
+    /// `viewport_center` is the screenspace center of the viewport, whose worldspace position the view remembers
+    pub fn save_view(&mut self, viewport_center: Vector2) {
+        let number = self.document.views.len().saturating_add(1);
+        self.document.views.push(NamedView {
+            name: format!("View {number}"),
+            center: self.camera.target + (viewport_center - self.camera.offset) / self.camera.zoom,
+            zoom: self.camera.zoom,
+            rotation: self.camera.rotation,
+        });
+        self.current_view = Some(self.document.views.len().saturating_sub(1));
+        self.mark_edited();
+    }
+
+    /// Move the camera to the named view at `index`, centering it in the viewport
+    ///
+    /// Does nothing if there is no such view
+    pub fn go_to_view(&mut self, index: usize, viewport_center: Vector2) {
+        let Some(view) = self.document.views.get(index) else { return };
+        let (min_zoom, max_zoom) = self.zoom_limits;
+        self.camera = Camera2D {
+            offset: viewport_center,
+            target: view.center,
+            rotation: view.rotation,
+            zoom: view.zoom.clamp(min_zoom, max_zoom),
+        };
+        self.current_view = Some(index);
+    }
+
+    /// Jump to the named view after the current one, or before it if `is_backward`, wrapping around past either end
+    ///
+    /// Starts from the first (or the last, if `is_backward`) if no view is current
+    pub fn cycle_view(&mut self, is_backward: bool, viewport_center: Vector2) {
+        let Some(last) = self.document.views.len().checked_sub(1) else { return };
+        let index = match (self.current_view.filter(|&index| index <= last), is_backward) {
+            (None, false) => 0,
+            (None, true) => last,
+            (Some(index), false) => if index < last { index.saturating_add(1) } else { 0 },
+            (Some(index), true) => index.checked_sub(1).unwrap_or(last),
+        };
+        self.go_to_view(index, viewport_center);
+    }
+
+    /// Track the screenspace positions of the first two touch points, or [`None`] if fewer than two are touching
+    ///
+    /// Returns how the camera should move since the previous frame, [`None`] on the first frame of a pinch
+    pub fn track_pinch(&mut self, touches: Option<[Vector2; 2]>) -> Option<PinchGesture> {
+        let previous = std::mem::replace(&mut self.last_pinch, touches);
+        Some(PinchGesture::between(previous?, touches?))
+    }
+
+    /// Select the layer just above the topmost selected layer in the group scope, or just below the
+    /// bottommost if `is_backward`, wrapping around past either end
+    ///
+    /// Starts from the bottom (or the top, if `is_backward`) if nothing in the scope is selected.
+    /// Like clicking it, the layer's style becomes the current style
+    pub fn select_next_layer(&mut self, is_backward: bool) {
+        let Some(last) = self.document.layers_at(&self.group_scope)
+            .and_then(|layers| layers.len().checked_sub(1))
+        else { return };
+        let selected: Vec<usize> = match &self.selection {
+            Selection::Layers(paths) => paths.iter()
+                .filter_map(|path| path.split_last())
+                .filter(|(_, scope)| *scope == self.group_scope.as_slice())
+                .map(|(&index, _)| index)
+                .collect(),
+            _ => Vec::new(),
+        };
+        let index = if is_backward {
+            selected.iter().min().map_or(last, |&index| index.checked_sub(1).unwrap_or(last))
+        } else {
+            selected.iter().max().filter(|&&index| index < last).map_or(0, |&index| index.saturating_add(1))
+        };
+        let mut path = self.group_scope.clone();
+        path.push(index);
+        if let Some(layer) = self.document.layer_at(&path) {
+            self.current_style = MaybeNew::Existing(layer.style.clone());
+        }
+        self.set_selection(Selection::Layers(vec![path]));
+    }
+
+    /// Register a select tool click at the worldspace `position` at `time` seconds and get whether it completes a double-click
+    pub fn click(&mut self, position: Vector2, time: f64) -> bool {
+        let max_distance = Self::DOUBLE_CLICK_DISTANCE / self.camera.zoom;
+        let is_double_click = self.last_click
+            .is_some_and(|(last_time, last_position)| time - last_time <= Self::DOUBLE_CLICK_TIME && last_position.distance_to(position) <= max_distance);
+        // a third click starts a new double-click instead of completing another
+        self.last_click = if is_double_click { None } else { Some((time, position)) };
+        is_double_click
+    }
+
+    /// Select the topmost layer at the worldspace `position` within the current group scope
+    ///
+    /// If `is_deep`, groups are descended into until the innermost layer at `position`
+    /// is found, and the scope becomes the group directly containing it
+    ///
+    /// Clicking where nothing in the scope is leaves the scope and tries again from the top-level layers
+    ///
+    /// The selected layer's style becomes the current style, so that editing it affects every layer sharing it
+    pub fn select_layer_at(&mut self, position: Vector2, is_deep: bool) {
+        self.select_layer_by(|layers| layers.iter().rposition(|layer| layer.contains_point(position)), is_deep);
+    }
+
+    /// Select a layer like [`Editor::select_layer_at`], finding the index of the topmost layer at the position among a list of layers with `hit`
+    ///
+    /// Lets the layer be [picked on the GPU][`crate::picking::PickBuffer`] instead of tested geometrically
+    pub fn select_layer_by(&mut self, mut hit: impl FnMut(&[Layer]) -> Option<usize>, is_deep: bool) {
+        let index = match self.document.layers_at(&self.group_scope).and_then(&mut hit) {
+            Some(index) => Some(index),
+            None if !self.group_scope.is_empty() => {
+                self.group_scope.clear();
+                hit(&self.document.layers)
+            }
+            None => None,
+        };
+        let Some(index) = index else {
+            self.set_selection(Selection::Layers(Vec::new()));
+            return;
+        };
+
+        let mut path = self.group_scope.clone();
+        path.push(index);
+        if is_deep {
+            while let Some(Layer { content: LayerContent::Group(group), .. }) = self.document.layer_at(&path)
+                && let Some(index) = hit(&group.layers)
+            {
+                path.push(index);
+            }
+            if let Some((_, scope)) = path.split_last() {
+                self.group_scope = scope.to_vec();
+            }
+        }
+        if let Some(layer) = self.document.layer_at(&path) {
+            self.current_style = MaybeNew::Existing(layer.style.clone());
+        }
+        self.set_selection(Selection::Layers(vec![path]));
+    }
+
+    /// Extend the lasso loop to the worldspace `position`, skipping positions too close to the previous point
+    pub fn extend_lasso(&mut self, position: Vector2) {
+        let spacing = Self::LASSO_SPACING / self.camera.zoom;
+        if self.lasso.last().is_none_or(|last| last.distance_to(position) >= spacing) {
+            self.lasso.push(position);
+        }
+    }
+
+    /// Continue the vector brush stroke to the worldspace `position`, or start one if there isn't one
+    ///
+    /// Points are spaced like the [lasso][`Editor::extend_lasso`]. Moving on from a [recognized shape][`Editor::rest_brush_stroke`]
+    /// goes back to drawing freehand
+    pub fn extend_brush_stroke(&mut self, position: Vector2) {
+        let spacing = Self::LASSO_SPACING / self.camera.zoom;
+        if self.brush_stroke.last().is_none_or(|last| last.distance_to(position) >= spacing) {
+            self.brush_stroke.push(position);
+            self.brush_rest = 0.0;
+            self.recognized_shape = None;
+        }
+    }
+
+    /// Let the end of the vector brush stroke stay where it is for another `seconds`
+    ///
+    /// Once it has stayed for [`recognize::HOLD_DURATION`], the stroke is recognized as the shape it looks like, if any.
+    /// Markup is never recognized
+    pub fn rest_brush_stroke(&mut self, seconds: f32) {
+        let was_held = self.brush_rest >= recognize::HOLD_DURATION;
+        self.brush_rest += seconds;
+        if !was_held && self.brush_rest >= recognize::HOLD_DURATION && !self.is_marking_up {
+            self.recognized_shape = recognize::recognize(&self.brush_stroke);
+        }
+    }
+
+    /// Finish the vector brush stroke, adding it to the top of the document as a path stroked `size` wide
+    ///
+    /// The path gets a copy of the [style for new objects][`Editor::style_for_new_object`] with its own width profile: even, or following the
+    /// direction of the path if `nib` is enabled. A stroke too short to have a direction is dropped.
+    /// A stroke [recognized][`Editor::rest_brush_stroke`] as a shape is replaced by it
+    pub fn finish_brush_stroke(&mut self, size: f32, nib: &Nib) {
+        let points = std::mem::take(&mut self.brush_stroke);
+        let shape = self.recognized_shape.take();
+        self.brush_rest = 0.0;
+        self.stabilizer.reset();
+        if points.len() < 2 {
+            return;
+        }
+        if self.is_marking_up {
+            self.add_annotation(Annotation { position: points[0], kind: AnnotationKind::Markup { points } });
+            return;
+        }
+        let (name, curve) = if let Some(shape) = shape {
+            (shape.name(), shape.to_curve())
+        } else {
+            let drawn = Curve {
+                points: points.into_iter()
+                    .map(|point| CurvePoint { c_in: na::Vector2::zeros(), p: na::Vector2::new(point.x, point.y), c_out: na::Vector2::zeros() })
+                    .collect(),
+                is_closed: false,
+            };
+            ("Brush stroke", drawn.simplified(Self::BRUSH_TOLERANCE / self.camera.zoom))
+        };
+        let profile = if nib.is_enabled {
+            nib.width_profile(&curve, size)
+        } else {
+            WidthProfile::new_flat(size * 0.5)
+        };
+
+        let Some(current) = self.style_for_new_object().upgrade() else { return };
+        let mut style = current.lock().borrow().clone();
+        style.stroke.width = Some(Arc::downgrade(self.document.create_width_profile(profile)));
+        let style = Arc::downgrade(self.document.create_style(style));
+        let curve = Arc::downgrade(self.document.create_curve(curve));
+        self.document.layers.push(Layer {
+            name: name.to_string(),
+            content: LayerContent::Curve(curve),
+            style,
+            export: None,
+            is_template: false,
+        });
+        self.record_added_layers(self.document.layers.len().saturating_sub(1));
+    }
+
+    /// Add `curve` to the top of the document as a new layer named `name`, drawn with the [style for new objects][`Editor::style_for_new_object`]
+    pub fn add_curve_layer(&mut self, name: String, curve: Curve) {
+        self.add_path_layer(name, vec![curve]);
+    }
+
+    /// Add `curves` to the top of the document as a new layer named `name`, drawn with the [style for new objects][`Editor::style_for_new_object`]
+    ///
+    /// Several curves become one compound path. Does nothing if there are no curves
+    pub fn add_path_layer(&mut self, name: String, curves: Vec<Curve>) {
+        let mut curves = curves.into_iter()
+            .map(|curve| Arc::downgrade(self.document.create_curve(curve)))
+            .collect::<Vec<_>>();
+        let content = match curves.len() {
+            0 => return,
+            1 => LayerContent::Curve(curves.remove(0)),
+            _ => LayerContent::Compound(CompoundPath { subpaths: curves }),
+        };
+        let style = self.style_for_new_object();
+        self.document.layers.push(Layer {
+            name,
+            content,
+            style,
+            export: None,
+            is_template: false,
+        });
+        self.record_added_layers(self.document.layers.len().saturating_sub(1));
+    }
+
+    /// Finish the lasso loop, selecting the anchors inside it
+    ///
+    /// If `is_whole_paths`, the curves with every anchor inside are selected as paths instead.
+    /// A loop too small to enclose anything clears the selection
+    pub fn finish_lasso(&mut self, is_whole_paths: bool) {
+        let lasso = std::mem::take(&mut self.lasso);
+        let lasso = [lasso.into_iter().map(|point| na::Vector2::new(point.x, point.y)).collect::<Vec<_>>()];
+        let is_inside = |point| lasso[0].len() >= 3 && fill::contains(&lasso, point, FillRule::EvenOdd);
+
+        let mut curves = Vec::<WeakCurve>::new();
+        for curve in self.document.layers.iter().flat_map(Layer::curves) {
+            if !curves.iter().any(|other| other.ptr_eq(&curve)) {
+                curves.push(curve);
+            }
+        }
+
+        let selection = if is_whole_paths {
+            Selection::Paths(curves.into_iter()
+                .filter(|curve| curve.upgrade().is_some_and(|strong_curve| {
+                    let curve_lock = strong_curve.lock();
+                    let curve_borrow = curve_lock.borrow();
+                    !curve_borrow.points.is_empty() && curve_borrow.points.iter().all(|point| is_inside(point.p))
+                }))
+                .collect())
+        } else {
+            Selection::Points(curves.into_iter()
+                .filter_map(|curve| {
+                    let strong_curve = curve.upgrade()?;
+                    let curve_lock = strong_curve.lock();
+                    let mut ranges = Vec::<std::ops::Range<u32>>::new();
+                    for (index, point) in curve_lock.borrow().points.iter().enumerate() {
+                        if !is_inside(point.p) {
+                            continue;
+                        }
+                        let index = index as u32;
+                        match ranges.last_mut() {
+                            Some(range) if range.end == index => range.end = index.saturating_add(1),
+                            _ => ranges.push(index..index.saturating_add(1)),
+                        }
+                    }
+                    (!ranges.is_empty()).then_some((curve, ranges))
+                })
+                .collect())
+        };
+        self.set_selection(selection);
+    }
+
+    /// Select every layer whose style matches `filter`, including those nested in groups
+    ///
+    /// Groups themselves are never selected, only searched
+    pub fn select_matching(&mut self, filter: &LayerFilter) {
+        let paths = LayerQuery::new(&self.document, filter.clone()).paths();
+        self.set_selection(Selection::Layers(paths));
+    }
+
+    /// Select every curve, compound path, and rectangle whose solid fill has no channel farther than `tolerance` (0 to 255)
+    /// from the fill of the innermost filled path at the worldspace `position`
+    ///
+    /// Clicking where there is no filled path clears the selection
+    pub fn select_same_fill_at(&mut self, position: Vector2, tolerance: f32) {
+        fn find(layers: &[Layer], position: Vector2) -> Option<Color> {
+            layers.iter().rev().find_map(|layer| match &layer.content {
+                LayerContent::Group(group) => find(&group.layers, position),
+                LayerContent::Curve(_) | LayerContent::Compound(_) | LayerContent::Rectangle(_) => layer.fill_color()
+                    .filter(|color| color.a > 0 && layer.contains_point(position)),
+                LayerContent::Connector(_) | LayerContent::Image { .. } | LayerContent::Paint(_) => None,
+            })
+        }
+        let Some(target) = find(&self.document.layers, position) else {
+            self.set_selection(Selection::Layers(Vec::new()));
+            return;
+        };
+        let tolerance = tolerance.clamp(0.0, 255.0) as u8;
+        self.select_matching(&LayerFilter::new().fill_color(target).color_tolerance(tolerance));
+    }
+
+    /// Fill the region enclosed by paths around the worldspace `position` with a new path, bridging gaps between the paths
+    /// up to `gap` worldspace pixels wide
+    ///
+    /// The fill goes beneath the lowest top-level layer bordering the region, so the line art stays on top of it, and takes the fill
+    /// of the [style for new objects][`Editor::style_for_new_object`] without a stroke. A region with holes becomes a compound path.
+    /// Templates don't enclose anything. The fill becomes the selection. Does nothing if `position` is on a path or isn't enclosed
+    pub fn bucket_fill_at(&mut self, position: Vector2, gap: f32) {
+        let cell_size = Self::BUCKET_RESOLUTION / self.camera.zoom;
+        let templates = self.document.template_curves();
+        let mut paths = Vec::new();
+        let mut owners = Vec::new();
+        for (index, layer) in self.document.layers.iter().enumerate() {
+            for curve in layer.curves() {
+                if templates.contains(&(curve.as_ptr() as *const ())) {
+                    continue;
+                }
+                let Some(curve) = curve.upgrade() else { continue };
+                let mut polyline = Vec::new();
+                curve.lock().borrow().sample_into(&mut polyline, cell_size * 0.5);
+                paths.push(polyline);
+                owners.push(index);
+            }
+        }
+        let Some(region) = bucket::find_region(&paths, position, gap, cell_size) else { return };
+        let Some(&index) = owners.get(region.bottom) else { return };
+
+        let Some(current) = self.style_for_new_object().upgrade() else { return };
+        let fill = current.lock().borrow().fill.clone();
+        let style = Arc::downgrade(self.document.create_style(Style { fill, fill_rule: FillRule::EvenOdd, ..Style::new() }));
+        let mut subpaths = region.outlines.into_iter()
+            .map(|outline| Arc::downgrade(self.document.create_curve(outline)))
+            .collect::<Vec<_>>();
+        let content = if subpaths.len() == 1
+            && let Some(curve) = subpaths.pop()
+        {
+            LayerContent::Curve(curve)
+        } else {
+            LayerContent::Compound(CompoundPath { subpaths })
+        };
+        self.document.layers.insert(index, Layer {
+            name: "Bucket fill".to_owned(),
+            content,
+            style,
+            export: None,
+            is_template: false,
+        });
+        self.set_selection(Selection::Layers(vec![vec![index]]));
+        self.mark_edited();
+    }
+
+    /// Fork the current style into a new local style used only by the selected layers,
+    /// so that editing it no longer affects other layers sharing the original
+    ///
+    /// Does nothing unless the current style is an existing style and layers are selected
+    pub fn break_style_link(&mut self) {
+        let MaybeNew::Existing(style) = &self.current_style else { return };
+        let Selection::Layers(paths) = &self.selection else { return };
+        if paths.is_empty() {
+            return;
+        }
+        let Some(fork) = self.document.fork_style(style, paths) else { return };
+        self.mark_edited();
+        self.current_style = MaybeNew::Existing(fork);
+    }
+
+    /// Save `swatch` to the document, unless a swatch with the same color definition is already saved
+    pub fn add_swatch(&mut self, swatch: Swatch) {
+        if self.document.swatches.iter().any(|existing| existing.color == swatch.color) {
+            return;
+        }
+        self.mark_edited();
+        self.document.swatches.push(swatch);
+    }
+
+    /// Replace the swatch at `index` with `swatch`, such as to make a screen color print with process or spot inks
+    ///
+    /// Does nothing if there is no swatch at `index`
+    pub fn set_swatch(&mut self, index: usize, swatch: Swatch) {
+        if let Some(existing) = self.document.swatches.get_mut(index)
+            && *existing != swatch
+        {
+            *existing = swatch;
+            self.mark_edited();
+        }
+    }
+
+    /// Save each of `swatches` to the document like [`Editor::add_swatch`], skipping those already saved
+    ///
+    /// Returns the number of swatches added
+    pub fn import_swatches(&mut self, swatches: Vec<Swatch>) -> usize {
+        let before = self.document.swatches.len();
+        for swatch in swatches {
+            self.add_swatch(swatch);
+        }
+        self.document.swatches.len().saturating_sub(before)
+    }
+
+    /// Write the document's swatches to a palette file in `format` beside the document, named after it
+    ///
+    /// The name is numbered rather than replacing an existing file. Errors if the document hasn't been saved,
+    /// since there is nowhere beside it to write to. Returns the path written to
+    pub fn export_palette(&self, format: PaletteFormat) -> Result<PathBuf, String> {
+        let directory = self.document.file_path
+            .as_deref()
+            .and_then(Path::parent)
+            .ok_or("save the document before exporting its palette, so the palette has a folder to go in")?;
+        let path = export::unused_path(directory, &self.document.title, format.extension());
+        palette::write(&path, &self.document.title, &self.document.swatches, format)?;
+        Ok(path)
+    }
+
+    /// The current selection
+    ///
+    /// Takes on different meanings depending on `current_tool`
+    #[inline]
+    pub const fn selection(&self) -> &Selection {
+        &self.selection
+    }
+
+    /// The bounds, anchor count, and owning layers of the current selection, measured when it last changed
+    #[inline]
+    pub const fn selection_info(&self) -> &SelectionInfo {
+        &self.selection_info
+    }
+
+    /// Replace the selection and measure it
+    ///
+    /// Anything belonging to a [template][`Layer::is_template`] layer is left out, since templates are locked
+    pub fn set_selection(&mut self, mut selection: Selection) {
+        match &mut selection {
+            Selection::Layers(paths) => paths.retain(|path| !self.document.is_in_template(path)),
+            Selection::Paths(curves) => {
+                let locked = self.document.template_curves();
+                curves.retain(|curve| !locked.contains(&(curve.as_ptr() as *const ())));
+            }
+            Selection::Points(points) => {
+                let locked = self.document.template_curves();
+                points.retain(|(curve, _)| !locked.contains(&(curve.as_ptr() as *const ())));
+            }
+        }
+        self.selection_info = selection.measure(&self.document);
+        self.selection = selection;
+    }
+
+    /// Measure the selection again after its artwork was reshaped
+    pub fn refresh_selection_info(&mut self) {
+        self.selection_info = self.selection.measure(&self.document);
+    }
+
+    /// Get the worldspace rectangle containing every selected layer
+    ///
+    /// [`None`] if no layers with artwork are selected
+    pub fn selection_bounds(&self) -> Option<Rectangle> {
+        matches!(self.selection, Selection::Layers(_))
+            .then_some(self.selection_info.bounds)
+            .flatten()
+    }
+
+    /// Make the topmost artboard containing the worldspace `point` the [active artboard][`Editor::active_artboard`]
+    ///
+    /// Does nothing if no artboard contains the point
+    pub fn activate_artboard_at(&mut self, point: Vector2) {
+        if let Some(index) = self.document.artboards.iter().rposition(|artboard| artboard.rect.check_collision_point_rec(point)) {
+            self.active_artboard = index;
+        }
+    }
+
+    /// Get the worldspace position coordinates are measured from
+    ///
+    /// The top-left of the [active artboard][`Editor::active_artboard`] if `is_artboard_relative`
+    /// and the document has any artboards, otherwise the worldspace origin
+    pub fn coordinate_origin(&self, is_artboard_relative: bool) -> Vector2 {
+        let artboards = &self.document.artboards;
+        is_artboard_relative
+            .then(|| artboards.get(self.active_artboard).or(artboards.first()))
+            .flatten()
+            .map_or(Vector2::zero(), |artboard| Vector2::new(artboard.rect.x, artboard.rect.y))
+    }
+
+    /// Get the worldspace rectangles smart guides align to: every artboard, and every unselected layer in the group scope
+    ///
+    /// Painted layers are skipped unless `is_including_paint`, since a stroke in progress would otherwise align to itself
+    pub fn guide_targets(&self, is_including_paint: bool) -> Vec<Rectangle> {
+        let selected = match &self.selection {
+            Selection::Layers(paths) => paths.as_slice(),
+            _ => &[],
+        };
+        let layers = self.document.layers_at(&self.group_scope).unwrap_or_default();
+        let layer_bounds = layers.iter()
+            .enumerate()
+            .filter(|&(index, layer)| {
+                let is_selected = selected.iter().any(|path| path.split_last() == Some((&index, self.group_scope.as_slice())));
+                !is_selected && (is_including_paint || !matches!(layer.content, LayerContent::Paint(_)))
+            })
+            .filter_map(|(_, layer)| layer.control_bounds());
+        self.document.artboards.iter()
+            .map(|artboard| artboard.rect)
+            .chain(layer_bounds)
+            .collect()
+    }
+
+    /// Move every selected layer, path, or anchor by the worldspace `offset`
+    ///
+    /// Handles move along with their anchors
+    pub fn translate_selection(&mut self, offset: Vector2) {
+        if offset == Vector2::zero() {
+            return;
+        }
+        let offset_na = na::Vector2::new(offset.x, offset.y);
+        let entry = match &self.selection {
+            Selection::Layers(paths) => {
+                for path in paths {
+                    if let Some(layer) = self.document.layer_at_mut(path) {
+                        layer.translate(offset);
+                    }
+                }
+                Some(JournalEntry::Translate { layers: paths.clone(), offset })
+            }
+            Selection::Paths(curves) => {
+                for curve in curves {
+                    let Some(strong_curve) = curve.upgrade() else { continue };
+                    let curve_lock = strong_curve.lock();
+                    curve_lock.borrow_mut().translate(offset_na);
+                }
+                self.curve_records(curves)
+            }
+            Selection::Points(curves) => {
+                for (curve, ranges) in curves {
+                    let Some(strong_curve) = curve.upgrade() else { continue };
+                    let curve_lock = strong_curve.lock();
+                    let mut curve_borrow = curve_lock.borrow_mut();
+                    for range in ranges {
+                        for point in curve_borrow.points.get_mut(range.start as usize..range.end as usize).unwrap_or_default() {
+                            point.p += offset_na;
+                        }
+                    }
+                }
+                self.curve_records(curves.iter().map(|(curve, _)| curve))
+            }
+        };
+        if let Some(bounds) = &mut self.selection_info.bounds {
+            bounds.x += offset.x;
+            bounds.y += offset.y;
+        }
+        match entry {
+            Some(entry) => self.record(entry),
+            None => self.mark_edited(),
+        }
+    }
+
+    /// Stretch every selected layer by `factor` along each axis, about the center of their bounds
+    ///
+    /// If `is_scaling_strokes`, stroke widths grow and shrink with the artwork.
+    /// The selected layers' styles are forked first, so unselected layers sharing them keep their widths.
+    pub fn scale_selection(&mut self, factor: Vector2, is_scaling_strokes: bool) {
+        let Selection::Layers(paths) = &self.selection else { return };
+        let Some(bounds) = self.selection_info.bounds else { return };
+        if factor == Vector2::one() {
+            return;
+        }
+        let paths = paths.clone();
+        let center = Vector2::new(bounds.x + bounds.width * 0.5, bounds.y + bounds.height * 0.5);
+        for path in &paths {
+            if let Some(layer) = self.document.layer_at_mut(path) {
+                layer.scale(center, factor);
+            }
+        }
+        // a circle scaled to an ellipse keeps the same area of stroke
+        let stroke_factor = is_scaling_strokes.then(|| (factor.x * factor.y).abs().sqrt());
+        if let Some(stroke_factor) = stroke_factor {
+            self.scale_strokes(&paths, stroke_factor);
+        }
+        self.refresh_selection_info();
+        self.record(JournalEntry::Scale { layers: paths, center, factor, stroke_factor });
+    }
+
+    /// [Scale the whole document][`Document::scale`] by `factor`, rerouting connectors to match
+    pub fn scale_document(&mut self, factor: f32) {
+        self.document.scale(factor);
+        self.document.route_connectors();
+        self.refresh_selection_info();
+        self.record(JournalEntry::ScaleDocument(factor));
+    }
+
+    /// [Scale the strokes][`Document::scale_strokes`] of the layers at `paths` by `factor`, switching the current style to its copy
+    fn scale_strokes(&mut self, paths: &[Vec<usize>], factor: f32) {
+        let forks = self.document.scale_strokes(paths, factor);
+        if let MaybeNew::Existing(current) = &self.current_style
+            && let Some((_, fork)) = forks.iter().find(|(original, _)| original.ptr_eq(current))
+        {
+            self.current_style = MaybeNew::Existing(fork.clone());
+        }
+    }
+
+    /// The style, texture transform, and worldspace bounds of the selected layer, if exactly one layer is selected and it has a texture fill
+    fn selected_texture_fill(&self) -> Option<(WeakStyle, TextureTransform, Rectangle)> {
+        let Selection::Layers(paths) = &self.selection else { return None };
+        let [path] = paths.as_slice() else { return None };
+        let layer = self.document.layer_at(path)?;
+        let bounds = layer.control_bounds()?;
+        let style = layer.style.upgrade()?;
+        let style_lock = style.lock();
+        let transform = match &style_lock.borrow().fill {
+            Pattern::Texture(_, transform) => *transform,
+            Pattern::Solid(_) => return None,
+        };
+        Some((layer.style.clone(), transform, bounds))
+    }
+
+    /// The worldspace corners of the selected layer's texture fill and the positions of its [handles][`TextureHandle`]
+    ///
+    /// [`None`] unless exactly one layer is selected and it has a texture fill
+    pub fn texture_handles(&self) -> Option<([Vector2; 4], [(TextureHandle, Vector2); 3])> {
+        let (_, transform, bounds) = self.selected_texture_fill()?;
+        let corners = [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)]
+            .map(|(u, v)| transform.to_world(bounds, Vector2::new(u, v)));
+        let handles = TextureHandle::ALL.map(|handle| (handle, transform.to_world(bounds, handle.uv())));
+        Some((corners, handles))
+    }
+
+    /// Start dragging the texture fill handle within worldspace `radius` of the worldspace `position`
+    ///
+    /// Returns whether there was a handle to drag
+    pub fn start_texture_drag(&mut self, position: Vector2, radius: f32) -> bool {
+        let Some((_, transform, _)) = self.selected_texture_fill() else { return false };
+        let Some((_, handles)) = self.texture_handles() else { return false };
+        // later handles are drawn on top, so they are picked first
+        self.texture_drag = handles.into_iter()
+            .rev()
+            .find(|&(_, handle)| handle.distance_to(position) <= radius)
+            .map(|(handle, at)| (handle, at - position, transform));
+        self.texture_drag.is_some()
+    }
+
+    /// Move the texture fill handle being dragged to follow the mouse at the worldspace `position`
+    ///
+    /// The texture is placed by the selected layer's style, so every layer sharing the style follows
+    pub fn drag_texture_handle(&mut self, position: Vector2) {
+        let Some((handle, grab, _)) = self.texture_drag else { return };
+        let Some((weak_style, transform, bounds)) = self.selected_texture_fill() else { return };
+        let target = position + grab;
+        let center = transform.center(bounds);
+        let mut new_transform = transform;
+        match handle {
+            TextureHandle::Move => {
+                let offset = target - Vector2::new(bounds.x + bounds.width * 0.5, bounds.y + bounds.height * 0.5);
+                new_transform.offset = Vector2::new(
+                    if bounds.width > 0.0 { offset.x / bounds.width } else { 0.0 },
+                    if bounds.height > 0.0 { offset.y / bounds.height } else { 0.0 },
+                );
+            }
+            TextureHandle::Scale => {
+                let local = (target - center).rotated(-transform.rotation.to_radians());
+                let scale = |local: f32, size: f32, old: f32| {
+                    let scale = if size > 0.0 { local * 2.0 / size } else { old };
+                    if scale.abs() < TextureTransform::MIN_SCALE { TextureTransform::MIN_SCALE.copysign(old) } else { scale }
+                };
+                new_transform.scale = Vector2::new(
+                    scale(local.x, bounds.width, transform.scale.x),
+                    scale(local.y, bounds.height, transform.scale.y),
+                );
+            }
+            TextureHandle::Rotate => {
+                let from = transform.to_world(bounds, handle.uv()) - center;
+                let to = target - center;
+                let turn = (from.x * to.y - from.y * to.x).atan2(from.dot(to));
+                new_transform.rotation = (transform.rotation + turn.to_degrees()).rem_euclid(360.0);
+            }
+        }
+        self.set_texture_transform(&weak_style, new_transform);
+    }
+
+    /// Place the texture fill of `weak_style` with `transform`
+    fn set_texture_transform(&mut self, weak_style: &WeakStyle, transform: TextureTransform) {
+        if let Some(style) = weak_style.upgrade() {
+            let before = style.lock().borrow().clone();
+            if let Pattern::Texture(_, fill_transform) = &mut style.lock().borrow_mut().fill {
+                *fill_transform = transform;
+            }
+            self.record_style(weak_style, &before);
+        }
+    }
+
+    /// End the texture fill handle drag, putting the texture back where it was before the drag
+    fn cancel_texture_drag(&mut self) {
+        let Some((_, _, transform)) = self.texture_drag.take() else { return };
+        if let Some((weak_style, _, _)) = self.selected_texture_fill() {
+            self.set_texture_transform(&weak_style, transform);
+        }
+    }
+
+    /// The selected rectangle, if exactly one layer is selected and it is a rectangle
+    fn selected_rectangle(&self) -> Option<&LiveRectangle> {
+        let Selection::Layers(paths) = &self.selection else { return None };
+        let [path] = paths.as_slice() else { return None };
+        match &self.document.layer_at(path)?.content {
+            LayerContent::Rectangle(rectangle) => Some(rectangle),
+            _ => None,
+        }
+    }
+
+    /// The worldspace positions of the selected rectangle's [corner widgets][`CornerWidget`], clockwise from the top-left
+    ///
+    /// [`None`] unless exactly one layer is selected and it is a rectangle
+    pub fn corner_widgets(&self) -> Option<[Vector2; 4]> {
+        Some(self.selected_rectangle()?.widgets(CornerWidget::INSET / self.camera.zoom))
+    }
+
+    /// Start dragging the corner widget within [`CornerWidget::RADIUS`] of the worldspace `position`
+    ///
+    /// Returns whether there was a widget to drag
+    pub fn start_corner_drag(&mut self, position: Vector2) -> bool {
+        let radius = CornerWidget::RADIUS / self.camera.zoom;
+        self.corner_drag = self.selected_rectangle().and_then(|rectangle| {
+            let widgets = rectangle.widgets(CornerWidget::INSET / self.camera.zoom);
+            let index = widgets.iter().position(|widget| widget.distance_to(position) <= radius)?;
+            Some((index, rectangle.radii))
+        });
+        self.corner_drag.is_some()
+    }
+
+    /// End the corner widget drag, putting the selected rectangle's radii back to how they were before the drag
+    fn cancel_corner_drag(&mut self) {
+        let Some((_, radii)) = self.corner_drag.take() else { return };
+        let Selection::Layers(paths) = &self.selection else { return };
+        let [path] = paths.as_slice() else { return };
+        let Some(Layer { content: LayerContent::Rectangle(rectangle), .. }) = self.document.layer_at_mut(path) else { return };
+        rectangle.radii = radii;
+        rectangle.regenerate();
+        let layer = path.clone();
+        self.record(JournalEntry::SetRectangleRadii { layer, radii });
+    }
+
+    /// Round the corners of the selected rectangle so the widget being dragged follows the mouse at the worldspace `position`
+    ///
+    /// Every corner gets the same radius, unless `is_single_corner`, which rounds only the dragged corner
+    pub fn drag_corner_widget(&mut self, position: Vector2, is_single_corner: bool) {
+        let Some((corner, _)) = self.corner_drag else { return };
+        let Selection::Layers(paths) = &self.selection else { return };
+        let [path] = paths.as_slice() else { return };
+        let Some(Layer { content: LayerContent::Rectangle(rectangle), .. }) = self.document.layer_at_mut(path) else { return };
+        let radius = rectangle.radius_toward(corner, position);
+        if is_single_corner {
+            rectangle.radii[corner] = radius;
+        } else {
+            rectangle.radii = [radius; 4];
+        }
+        rectangle.regenerate();
+        let radii = rectangle.radii;
+        let layer = path.clone();
+        self.record(JournalEntry::SetRectangleRadii { layer, radii });
+    }
+
+    /// Add the rectangle drawn by the rectangle tool's drag as a new layer on top of the document, and select it
+    ///
+    /// The rectangle is drawn with the [style for new objects][`Editor::style_for_new_object`].
+    /// Does nothing if the drag has no width or height
+    pub fn finish_rectangle(&mut self) {
+        let Some((start, end)) = self.rectangle_drag.take() else { return };
+        let rect = Rectangle::new(start.x.min(end.x), start.y.min(end.y), (end.x - start.x).abs(), (end.y - start.y).abs());
+        if rect.width <= 0.0 || rect.height <= 0.0 {
+            return;
+        }
+        let style = self.style_for_new_object();
+        let radii = [0.0; 4];
+        let curve = Arc::downgrade(self.document.create_curve(shape::rounded_rectangle(rect, radii)));
+        self.document.layers.push(Layer {
+            name: "Rectangle".to_owned(),
+            content: LayerContent::Rectangle(LiveRectangle { curve, rect, radii }),
+            style,
+            export: None,
+            is_template: false,
+        });
+        let index = self.document.layers.len().saturating_sub(1);
+        self.set_selection(Selection::Layers(vec![vec![index]]));
+        self.record_added_layers(index);
+    }
+
+    /// Move the selected layers so they follow the mouse at the worldspace `position` during a [`MoveDrag`]
+    ///
+    /// If `tolerance` is [`Some`], the layers snap to smart guides within that worldspace distance
+    pub fn drag_selection(&mut self, position: Vector2, tolerance: Option<f32>) {
+        let Some(drag) = self.move_drag else { return };
+        let mut offset = position - drag.start;
+        self.smart_guides.clear();
+        if let Some(tolerance) = tolerance
+            && let Some(bounds) = self.selection_bounds()
+        {
+            // where the layers would be without snapping
+            let moving = Rectangle { x: bounds.x - drag.moved.x + offset.x, y: bounds.y - drag.moved.y + offset.y, ..bounds };
+            let snap = guides::snap_rect(moving, &self.guide_targets(true), tolerance);
+            offset += snap.offset;
+            self.smart_guides = snap.guides;
+        }
+        // moving by whole grid steps keeps layers that are on the pixel grid on it
+        let pixel_snap = self.document.info.pixel_snap;
+        offset = Vector2::new(pixel_snap.round(offset.x), pixel_snap.round(offset.y));
+        self.translate_selection(offset - drag.moved);
+        self.move_drag = Some(MoveDrag { moved: offset, ..drag });
+    }
+
+    /// Start previewing a Simplify of every curve in the selected layers
+    ///
+    /// Does nothing if no curves are selected
+    pub fn start_simplify(&mut self) {
+        let Selection::Layers(paths) = &self.selection else { return };
+        let curves = paths.iter()
+            .filter_map(|path| self.document.layer_at(path))
+            .flat_map(Layer::curves)
+            .collect::<Vec<_>>();
+        let preview = SimplifyPreview::new(curves);
+        if !preview.is_empty() {
+            self.simplify = Some(preview);
+        }
+    }
+
+    /// Finish the Simplify being previewed, replacing the curves if `is_applying`
+    pub fn end_simplify(&mut self, is_applying: bool) {
+        if let Some(preview) = self.simplify.take() && is_applying {
+            let curves = preview.apply();
+            self.refresh_selection_info();
+            match self.curve_records(&curves) {
+                Some(entry) => self.record(entry),
+                None => self.mark_edited(),
+            }
+        }
+    }
+
+    /// Combine the selected curves and compound paths into one compound path, in place of the bottommost of them
+    ///
+    /// Subpaths nested inside others are [turned into holes][`CompoundPath::orient_holes`].
+    /// The compound path takes the name and style of the bottommost layer
+    ///
+    /// Does nothing unless at least two curve, compound path, or rectangle layers directly inside the same group are selected
+    pub fn make_compound_path(&mut self) {
+        let Selection::Layers(paths) = &self.selection else { return };
+        let mut paths = paths.iter()
+            .filter(|path| matches!(self.document.layer_at(path), Some(Layer { content: LayerContent::Curve(_) | LayerContent::Compound(_) | LayerContent::Rectangle(_), .. })))
+            .cloned()
+            .collect::<Vec<_>>();
+        paths.sort();
+        paths.dedup();
+        let Some((&bottom, parent)) = paths.first().and_then(|path| path.split_last()) else { return };
+        let parent = parent.to_vec();
+        if paths.len() < 2 || !paths.iter().all(|path| path.split_last().is_some_and(|(_, group)| group == parent.as_slice())) {
+            return;
+        }
+        let Some(layers) = self.document.layers_at_mut(&parent) else { return };
+
+        // removed from the top down so the indices below stay put
+        let mut removed = paths.iter()
+            .rev()
+            .filter_map(|path| path.last())
+            .map(|&index| layers.remove(index))
+            .collect::<Vec<_>>();
+        removed.reverse();
+        let compound = CompoundPath { subpaths: removed.iter().flat_map(Layer::curves).collect() };
+        compound.orient_holes();
+        let Layer { name, style, export, is_template, .. } = removed.swap_remove(0);
+        layers.insert(bottom, Layer { name, content: LayerContent::Compound(compound), style, export, is_template });
+
+        let mut path = parent;
+        path.push(bottom);
+        self.set_selection(Selection::Layers(vec![path]));
+        self.mark_edited();
+    }
+
+    /// Split each selected compound path into separate curve layers with its name and style, in its place
+    ///
+    /// The separated curves become the selection. Does nothing unless a compound path is selected
+    pub fn release_compound_paths(&mut self) {
+        let Selection::Layers(paths) = &self.selection else { return };
+        let mut paths = paths.iter()
+            .filter(|path| matches!(self.document.layer_at(path), Some(Layer { content: LayerContent::Compound(_), .. })))
+            .cloned()
+            .collect::<Vec<_>>();
+        if paths.is_empty() {
+            return;
+        }
+        // released from the top down so the indices below stay put
+        paths.sort();
+        paths.dedup();
+        let mut released = Vec::new();
+        for path in paths.iter().rev() {
+            let Some((&index, parent)) = path.split_last() else { continue };
+            let Some(layers) = self.document.layers_at_mut(parent) else { continue };
+            // the released curves are separate objects, so none of them inherit the compound path's asset export
+            let Layer { name, content: LayerContent::Compound(compound), style, .. } = layers.remove(index) else {
+                unreachable!("selected layer should have been checked to be a compound path")
+            };
+            let count = compound.subpaths.len();
+            for (offset, subpath) in compound.subpaths.into_iter().enumerate() {
+                layers.insert(index.saturating_add(offset), Layer {
+                    name: name.clone(),
+                    content: LayerContent::Curve(subpath),
+                    style: style.clone(),
+                    export: None,
+                    is_template: false,
+                });
+            }
+            released.push((parent, index, count));
+        }
+
+        // releasing a compound path pushes up the layers above it in the same group
+        let selection = released.iter()
+            .flat_map(|&(parent, index, count)| {
+                let shift = released.iter()
+                    .filter(|&&(other_parent, other_index, _)| other_parent == parent && other_index < index)
+                    .fold(0usize, |shift, &(_, _, other_count)| shift.saturating_add(other_count.saturating_sub(1)));
+                (0..count).map(move |offset| {
+                    let mut path = parent.to_vec();
+                    path.push(index.saturating_add(shift).saturating_add(offset));
+                    path
+                })
+            })
+            .collect();
+        self.set_selection(Selection::Layers(selection));
+        self.mark_edited();
+    }
+
+    /// Turn the selected layers into [templates][`Layer::is_template`], which leaves them unselected since templates are locked
+    ///
+    /// Does nothing unless layers are selected
+    pub fn make_template(&mut self) {
+        let Selection::Layers(paths) = &self.selection else { return };
+        if paths.is_empty() {
+            return;
+        }
+        for path in paths.clone() {
+            if let Some(layer) = self.document.layer_at_mut(&path) {
+                layer.is_template = true;
+            }
+        }
+        self.set_selection(Selection::Layers(Vec::new()));
+        self.mark_edited();
+    }
+
+    /// Turn every [template][`Layer::is_template`] layer back into an ordinary layer and select them
+    ///
+    /// Templates can't be selected, so they are all released at once
+    pub fn release_templates(&mut self) {
+        let mut paths = Vec::new();
+        self.document.visit_layers(|path, layer| if layer.is_template {
+            paths.push(path.to_vec());
+        });
+        if paths.is_empty() {
+            return;
+        }
+        for path in &paths {
+            if let Some(layer) = self.document.layer_at_mut(path) {
+                layer.is_template = false;
+            }
+        }
+        // a released group is selected along with everything in it
+        paths.dedup_by(|path, kept| path.starts_with(kept));
+        self.set_selection(Selection::Layers(paths));
+        self.mark_edited();
+    }
+
+    /// [Repair][`Document::repair_layers`] the document's broken layers, clearing the selection if there were any
+    ///
+    /// Returns the number of layers repaired or removed
+    pub fn repair_layers(&mut self) -> usize {
+        let repaired = self.document.repair_layers();
+        if repaired > 0 {
+            // removed layers shift the paths of those above them
+            self.set_selection(Selection::Layers(Vec::new()));
+            self.record(JournalEntry::RepairLayers);
+        }
+        repaired
+    }
+
+    /// Add a connector from the first to the last selected anchor, path, or layer, on top of the document
+    ///
+    /// Anchors are attached to directly, while paths and layers are attached to by the edges of their bounds.
+    /// The connector is drawn with the [style for new objects][`Editor::style_for_new_object`] and becomes the selection.
+    /// Does nothing unless at least two anchors, paths, or layers with curves are selected
+    pub fn connect_selection(&mut self) {
+        let ends = match &self.selection {
+            Selection::Points(curves) => curves.iter()
+                .flat_map(|(curve, ranges)| ranges.iter()
+                    .cloned()
+                    .flatten()
+                    .map(move |index| ConnectorEnd::Anchor { curve: curve.clone(), index: index as usize }))
+                .collect::<Vec<_>>(),
+            Selection::Paths(curves) => curves.iter()
+                .map(|curve| ConnectorEnd::Bounds(vec![curve.clone()]))
+                .collect(),
+            Selection::Layers(paths) => paths.iter()
+                .filter_map(|path| self.document.layer_at(path))
+                .map(Layer::curves)
+                .filter(|curves| !curves.is_empty())
+                .map(ConnectorEnd::Bounds)
+                .collect(),
+        };
+        let [start, .., end] = ends.as_slice() else { return };
+        let (start, end) = (start.clone(), end.clone());
+
+        let style = self.style_for_new_object();
+        // the placeholder curve is replaced as soon as the connector is routed
+        let curve = Arc::downgrade(self.document.create_curve(connector::route(Vector2::zero(), Vector2::zero(), ConnectorRouting::Straight)));
+        self.document.layers.push(Layer {
+            name: "Connector".to_owned(),
+            content: LayerContent::Connector(Connector { curve, start, end, routing: ConnectorRouting::default() }),
+            style,
+            export: None,
+            is_template: false,
+        });
+        self.document.route_connectors();
+        let index = self.document.layers.len().saturating_sub(1);
+        self.set_selection(Selection::Layers(vec![vec![index]]));
+        self.record_added_layers(index);
+    }
+
+    /// Switch each selected connector between straight and elbow routing
+    ///
+    /// Does nothing unless a connector is selected
+    pub fn toggle_connector_routing(&mut self) {
+        let Selection::Layers(paths) = &self.selection else { return };
+        let mut is_changed = false;
+        for path in paths.clone() {
+            if let Some(Layer { content: LayerContent::Connector(connector), .. }) = self.document.layer_at_mut(&path) {
+                connector.routing = connector.routing.toggled();
+                is_changed = true;
+            }
+        }
+        if is_changed {
+            self.document.route_connectors();
+            self.mark_edited();
+        }
+    }
+
+    /// Place copies of the other selected layers along the curve of the topmost selected layer,
+    /// [spaced and turned][`scatter::placements`] by `options`, grouped just above the curve
+    ///
+    /// The copies are centered on the curve and share the originals' styles. The group becomes the selection.
+    /// Does nothing unless at least two layers are selected and the topmost of them is a curve
+    pub fn scatter_along_path(&mut self, options: &ScatterOptions) {
+        let Selection::Layers(paths) = &self.selection else { return };
+        let mut paths = paths.clone();
+        paths.sort();
+        paths.dedup();
+        let Some((target_path, objects)) = paths.split_last() else { return };
+        // a group holding the curve can't be scattered along it
+        let objects = objects.iter()
+            .filter(|path| !target_path.starts_with(path))
+            .cloned()
+            .collect::<Vec<_>>();
+        let Some(Layer { content: LayerContent::Curve(target), .. }) = self.document.layer_at(target_path) else { return };
+        let Some(target) = target.upgrade() else { return };
+        let Some(bounds) = objects.iter()
+            .filter_map(|path| self.document.layer_at(path)?.control_bounds())
+            .reduce(layer::union)
+        else { return };
+        let center = Vector2::new(bounds.x + bounds.width * 0.5, bounds.y + bounds.height * 0.5);
+        let target_lock = target.lock();
+        let placements = scatter::placements(&target_lock.borrow(), options);
+        drop(target_lock);
+        if placements.is_empty() {
+            return;
+        }
+
+        let group_style = Arc::downgrade(self.document.create_style(Style::new()));
+        let mut copies = Vec::with_capacity(placements.len());
+        for (position, angle) in placements {
+            let mut instance = self.document.instance_layers_at(&objects);
+            for layer in &mut instance {
+                layer.translate(position - center);
+                layer.rotate(position, angle);
+            }
+            copies.push(if instance.len() == 1 {
+                instance.remove(0)
+            } else {
+                Layer { name: "Copy".to_owned(), content: LayerContent::Group(Group { layers: instance }), style: group_style.clone(), export: None, is_template: false }
+            });
+        }
+
+        let Some((&target_index, parent)) = target_path.split_last() else { return };
+        let Some(layers) = self.document.layers_at_mut(parent) else { return };
+        let index = target_index.saturating_add(1);
+        layers.insert(index, Layer { name: "Scatter".to_owned(), content: LayerContent::Group(Group { layers: copies }), style: group_style, export: None, is_template: false });
+        let mut path = parent.to_vec();
+        path.push(index);
+        self.set_selection(Selection::Layers(vec![path]));
+        self.mark_edited();
+    }
+
+    /// The selected layers "Grid Repeat" would copy and their combined worldspace bounds
+    ///
+    /// Layers inside a selected group are left out, since they're copied along with it.
+    /// [`None`] if nothing with artwork is selected
+    pub fn grid_repeat_source(&self) -> Option<(Vec<Vec<usize>>, Rectangle)> {
+        let Selection::Layers(paths) = &self.selection else { return None };
+        let mut paths = paths.clone();
+        paths.sort();
+        // a group sorts just before its children
+        paths.dedup_by(|path, kept| path.starts_with(kept));
+        let bounds = paths.iter()
+            .filter_map(|path| self.document.layer_at(path)?.control_bounds())
+            .reduce(layer::union)?;
+        Some((paths, bounds))
+    }
+
+    /// Copy the selected layers into a grid [laid out][`repeat::placements`] by `options`,
+    /// grouped just above the topmost selected layer
+    ///
+    /// The original stays in the first cell and the rest are filled with copies sharing its styles.
+    /// The group becomes the selection. Does nothing unless the grid has more than one cell
+    pub fn grid_repeat(&mut self, options: &GridRepeatOptions) {
+        let Some((paths, bounds)) = self.grid_repeat_source() else { return };
+        let center = Vector2::new(bounds.x + bounds.width * 0.5, bounds.y + bounds.height * 0.5);
+        let placements = repeat::placements(Vector2::new(bounds.width, bounds.height), options);
+        if placements.len() < 2 {
+            return;
+        }
+
+        let group_style = Arc::downgrade(self.document.create_style(Style::new()));
+        let mut copies = Vec::with_capacity(placements.len().saturating_sub(1));
+        for placement in placements.iter().skip(1) {
+            let mut instance = self.document.instance_layers_at(&paths);
+            let copy_center = center + placement.offset;
+            for layer in &mut instance {
+                layer.translate(placement.offset);
+                layer.rotate(copy_center, placement.angle);
+                layer.scale(copy_center, Vector2::one() * placement.scale);
+            }
+            copies.push(if instance.len() == 1 {
+                instance.remove(0)
+            } else {
+                Layer { name: "Copy".to_owned(), content: LayerContent::Group(Group { layers: instance }), style: group_style.clone(), export: None, is_template: false }
+            });
+        }
+
+        let Some((&top_index, parent)) = paths.last().and_then(|path| path.split_last()) else { return };
+        let Some(layers) = self.document.layers_at_mut(parent) else { return };
+        let index = top_index.saturating_add(1);
+        layers.insert(index, Layer { name: "Grid Repeat".to_owned(), content: LayerContent::Group(Group { layers: copies }), style: group_style, export: None, is_template: false });
+        let mut path = parent.to_vec();
+        path.push(index);
+        self.set_selection(Selection::Layers(vec![path]));
+        self.mark_edited();
+    }
+
+    /// Round the anchors and bounds of every selected layer to the document's [pixel grid][`DocumentInfo::pixel_snap`]
+    ///
+    /// Does nothing if pixel snapping is off
+    pub fn snap_selection_to_pixels(&mut self) {
+        let Selection::Layers(paths) = &self.selection else { return };
+        let DocumentInfo { pixel_snap, is_aligning_strokes, .. } = self.document.info;
+        if pixel_snap == PixelSnap::Off || paths.is_empty() {
+            return;
+        }
+        let layers = paths.clone();
+        for path in &layers {
+            if let Some(layer) = self.document.layer_at_mut(path) {
+                layer.snap_to_pixels(pixel_snap, is_aligning_strokes);
+            }
+        }
+        self.refresh_selection_info();
+        self.record(JournalEntry::SnapToPixels { layers });
+    }
+
+    /// Round the worldspace `point` to the document's [pixel grid][`DocumentInfo::pixel_snap`]
+    pub fn snap_point_to_pixels(&self, point: Vector2) -> Vector2 {
+        let pixel_snap = self.document.info.pixel_snap;
+        Vector2::new(pixel_snap.round(point.x), pixel_snap.round(point.y))
+    }
+
+    /// Switch the document to the next [pixel snapping mode][`PixelSnap`] and journal the change
+    pub fn cycle_pixel_snap(&mut self) {
+        self.document.info.pixel_snap = self.document.info.pixel_snap.next();
+        self.record_info();
+    }
+
+    /// Switch the background of the topmost artboard under the worldspace `position` to the next kind
+    pub fn cycle_artboard_background_at(&mut self, position: Vector2) {
+        if let Some(artboard) = self.document.artboards.iter_mut().rev().find(|artboard| artboard.rect.check_collision_point_rec(position)) {
+            artboard.background = artboard.background.next();
+            self.record_artboards();
+        }
+    }
+
+    /// Run `action` if it only needs the editor, whether it was triggered by its shortcut, the command palette, or a script
+    ///
+    /// Returns false if `action` needs more than the editor (such as the window, the mouse, or the preferences),
+    /// leaving it to the caller
+    pub fn run_action(&mut self, action: EditorAction) -> bool {
+        match action {
+            EditorAction::ToggleEraser => self.is_erasing = !self.is_erasing,
+            EditorAction::CyclePixelSnap => self.cycle_pixel_snap(),
+            EditorAction::Simplify => self.start_simplify(),
+            EditorAction::MakeCompoundPath => self.make_compound_path(),
+            EditorAction::ReleaseCompoundPath => self.release_compound_paths(),
+            EditorAction::MakeTemplate => self.make_template(),
+            EditorAction::ReleaseTemplates => self.release_templates(),
+            EditorAction::Connect => self.connect_selection(),
+            EditorAction::ToggleConnectorRouting => self.toggle_connector_routing(),
+            EditorAction::SelectNextLayer => self.select_next_layer(false),
+            EditorAction::SelectPreviousLayer => self.select_next_layer(true),
+            EditorAction::SwapFillStroke => self.swap_fill_stroke(),
+            EditorAction::ResetFillStroke => self.reset_fill_stroke(),
+            EditorAction::ClearColor => self.clear_active_well(),
+            EditorAction::RepairLayers => {
+                let repaired = self.repair_layers();
+                eprintln!("repaired {repaired} broken layers");
+            }
+            EditorAction::ToggleTrimView => self.is_trimming_to_artboards = !self.is_trimming_to_artboards,
+            EditorAction::ToggleAnnotations => self.is_showing_annotations = !self.is_showing_annotations,
+            EditorAction::ToggleMarkup => self.is_marking_up = !self.is_marking_up,
+            EditorAction::ToggleAnimation => {
+                self.playback = match self.playback {
+                    Some(_) => None,
+                    // start on the artboard already in view
+                    None => {
+                        let mut playback = Playback::new();
+                        playback.frame = self.active_artboard.min(self.document.artboards.len().saturating_sub(1)) as u32;
+                        Some(playback)
+                    }
+                };
+            }
+            // frames only change in animation mode
+            EditorAction::PreviousFrame | EditorAction::NextFrame | EditorAction::PlayPause => {
+                let num_frames = self.document.artboards.len() as u32;
+                if let Some(playback) = &mut self.playback {
+                    let previous = playback.frame;
+                    match action {
+                        EditorAction::PreviousFrame => playback.step(-1, num_frames),
+                        EditorAction::NextFrame => playback.step(1, num_frames),
+                        _ => playback.is_playing = !playback.is_playing,
+                    }
+                    self.follow_playback(previous);
+                }
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    /// Advance [playback][`Editor::playback`] by `dt` seconds, if playing
+    pub fn tick_playback(&mut self, dt: f32) {
+        let num_frames = self.document.artboards.len() as u32;
+        if let Some(playback) = &mut self.playback {
+            let previous = playback.frame;
+            playback.tick(dt, self.document.animation.fps, num_frames);
+            self.follow_playback(previous);
+        }
+    }
+
+    /// Move the camera from the artboard of frame `previous` to the current frame's artboard, keeping it in the same place on screen,
+    /// and make the current frame the [active artboard][`Editor::active_artboard`]
+    ///
+    /// Does nothing if the frame hasn't changed
+    fn follow_playback(&mut self, previous: u32) {
+        let Some(playback) = &self.playback else { return };
+        if playback.frame == previous {
+            return;
+        }
+        let artboards = &self.document.artboards;
+        if let Some(from) = artboards.get(previous as usize)
+            && let Some(to) = artboards.get(playback.frame as usize)
+        {
+            self.camera.target += Vector2::new(to.rect.x - from.rect.x, to.rect.y - from.rect.y);
+        }
+        self.active_artboard = playback.frame as usize;
+    }
+
+    /// Leave the select tool's group scope, returning to the top-level layers
+    pub fn exit_group_scope(&mut self) {
+        self.group_scope.clear();
+    }
+
+    /// Start exporting the document with the settings of `preset`
+    ///
+    /// Does nothing if an export is already in progress
+    pub fn start_export(&mut self, preset: &ExportPreset) -> Result<(), String> {
+        if self.export.is_none() {
+            self.export = Some(ExportJob::start_preset(&self.document, preset)?);
+        }
+        Ok(())
+    }
+
+    /// Start exporting every artboard as a frame, and remember the settings for next time
+    ///
+    /// Frames are played at `fps`, and written into the folder of the last export settings (or the default folder),
+    /// either as one looping GIF or as one file of `format` each in a `<title>_frames` folder
+    ///
+    /// Does nothing if an export is already in progress
+    pub fn start_frame_export(&mut self, fps: f32, format: ImageFormat, is_gif: bool) -> Result<(), String> {
+        if self.export.is_none() {
+            let fps = fps.clamp(Animation::MIN_FPS, Animation::MAX_FPS);
+            let settings = &mut self.document.animation;
+            if settings.fps != fps || settings.format != format || settings.is_gif != is_gif {
+                settings.fps = fps;
+                settings.format = format;
+                settings.is_gif = is_gif;
+                self.mark_edited();
+            }
+            let directory = self.document.last_export.clone().unwrap_or_default().resolve_directory(&self.document);
+            self.export = Some(if is_gif {
+                ExportJob::start_gif(&self.document, directory)?
+            } else {
+                ExportJob::start(&self.document, directory.join(animation::frames_directory_name(&self.document)), format)?
+            });
+        }
+        Ok(())
+    }
+
+    /// Start exporting every layer marked as an asset, into the folder of the last export settings (or the default folder)
+    ///
+    /// Does nothing if an export is already in progress
+    pub fn start_asset_export(&mut self) -> Result<(), String> {
+        if self.export.is_none() {
+            let directory = self.document.last_export.clone().unwrap_or_default().resolve_directory(&self.document);
+            self.export = Some(ExportJob::start_assets(&self.document, directory)?);
+        }
+        Ok(())
+    }
+
+    /// Start saving the document to `path` on a background thread; [`Editor::poll_save`] finishes the save
+    ///
+    /// The document is encoded right away, so edits made while it is being written aren't saved until next time.
+    /// If a save is already in progress, this one is [queued][`Editor::queued_save`] and started (with those edits)
+    /// once it finishes. `is_compressed` compresses [packaged][`format::PACKAGE_EXTENSION`] documents
+    pub fn start_save(&mut self, path: &Path, is_compressed: bool) -> Result<(), String> {
+        if self.save_job.is_some() {
+            self.queued_save = Some((path.to_path_buf(), is_compressed));
+            return Ok(());
+        }
+        // the entry waiting to be merged is for an edit the file will have
+        if let Some(journal) = &mut self.journal
+            && let Err(e) = journal.flush()
+        {
+            eprintln!("failed to journal edit: {e}");
+        }
+        let prev_info = self.document.info.clone();
+        self.document.info.touch();
+        match format::encode(&self.document, path, is_compressed).and_then(|document| FileJob::save(document, path)) {
+            Ok(job) => {
+                let journal_end = self.journal.as_ref().map_or(0, Journal::end);
+                self.save_job = Some(SaveJob { job, prev_info, journal_end });
+                // edits made while saving mark the document dirty again
+                self.is_dirty = false;
+                Ok(())
+            }
+            Err(e) => {
+                self.document.info = prev_info;
+                Err(e)
+            }
+        }
+    }
+
+    /// Start writing the document to its [autosave path][`format::autosave_path`] on a background thread;
+    /// [`Editor::poll_autosave`] finishes the autosave
+    ///
+    /// Unlike [`Editor::start_save`], the document, its journal, and whether it has unsaved changes are left as they were.
+    /// Does nothing if the document has no file, has no unsaved changes, or is already being autosaved
+    pub fn start_autosave(&mut self, is_compressed: bool) -> Result<(), String> {
+        let Some(path) = &self.document.file_path else { return Ok(()) };
+        if !self.is_dirty || self.autosave_job.is_some() {
+            return Ok(());
+        }
+        let autosave_path = format::autosave_path(path);
+        let document = format::encode(&self.document, &autosave_path, is_compressed)?;
+        self.autosave_job = Some(FileJob::save(document, &autosave_path)?);
+        Ok(())
+    }
+
+    /// Check whether the autosave in progress has finished
+    ///
+    /// If the document was saved while it was being written, the autosave has nothing the document doesn't, so it is deleted.
+    /// Returns the autosave's result the first time it is called after it finishes, [`None`] otherwise
+    pub fn poll_autosave(&mut self) -> Option<Result<(), String>> {
+        let result = self.autosave_job.as_mut()?.poll()?;
+        let job = self.autosave_job.take()?;
+        if result.is_ok() && !self.is_dirty && self.save_job.is_none() {
+            return Some(format::remove(job.path()));
+        }
+        Some(result.map(|_| ()))
+    }
+
+    /// Check whether the save in progress has finished, and if it succeeded, drop the journal entries
+    /// the saved file contains, keeping the ones for edits made while it was being written
+    ///
+    /// Ends any comparison with the saved version, and starts the [queued save][`Editor::queued_save`], if any.
+    /// Returns the save's result the first time it is called after the save finishes, [`None`] otherwise
+    pub fn poll_save(&mut self) -> Option<Result<(), String>> {
+        let result = self.save_job.as_mut()?.job.poll()?;
+        Some(self.finish_save(result))
+    }
+
+    /// Block until the save in progress and any [queued save][`Editor::queued_save`] have been written,
+    /// finishing each like [`Editor::poll_save`]
+    ///
+    /// Returns the first error, if any. [`None`] if the document is not being saved
+    pub fn wait_for_save(&mut self) -> Option<Result<(), String>> {
+        self.save_job.as_ref()?;
+        let mut result = Ok(());
+        while let Some(save) = &mut self.save_job {
+            let outcome = save.job.wait();
+            let finished = self.finish_save(outcome);
+            if result.is_ok() {
+                result = finished;
+            }
+        }
+        Some(result)
+    }
+
+    /// Apply the `result` of the save in progress, then start the queued save
+    fn finish_save(&mut self, result: Result<FileOutcome, String>) -> Result<(), String> {
+        let Some(SaveJob { job, prev_info, journal_end }) = self.save_job.take() else { return result.map(|_| ()) };
+        let result = match result {
+            Ok(_) => {
+                let path = job.path().to_path_buf();
+                let journal_result = match &mut self.journal {
+                    // entries recorded while saving are for edits the file doesn't have yet
+                    Some(journal) if self.document.file_path.as_ref() == Some(&path) => journal.discard_through(journal_end),
+                    journal => {
+                        // saved somewhere new; the old file's journal no longer applies to this document
+                        let result = journal.as_mut().map_or(Ok(()), Journal::clear);
+                        *journal = Some(Journal::new(&path));
+                        result
+                    }
+                };
+                // the saved file is newer than its autosave; one still being written is deleted once it finishes
+                let autosave_result = if self.autosave_job.is_none() {
+                    format::remove(&format::autosave_path(&path))
+                } else {
+                    Ok(())
+                };
+                self.document.file_path = Some(path);
+                // the comparison would be against a stale copy
+                self.comparison = None;
+                journal_result.and(autosave_result)
+            }
+            Err(e) => {
+                self.document.info = prev_info;
+                self.is_dirty = true;
+                Err(e)
+            }
+        };
+        if let Some((path, is_compressed)) = self.queued_save.take() {
+            let queued = self.start_save(&path, is_compressed);
+            return result.and(queued);
+        }
+        result
+    }
+
+    /// Mark the document dirty after an edit that has no journal entry of its own
+    ///
+    /// The whole document is journaled by the next [`Editor::journal_edits`], so edits made in quick succession
+    /// share one snapshot
+    pub const fn mark_edited(&mut self) {
+        self.is_dirty = true;
+        self.has_unjournaled_edits = true;
+    }
+
+    /// Whether the journal has work waiting for the next [`Editor::journal_edits`]
+    pub fn is_journal_pending(&self) -> bool {
+        self.journal.as_ref().is_some_and(|journal| self.has_unjournaled_edits || journal.has_pending())
+    }
+
+    /// Write the journal entry waiting to be merged, and start journaling a snapshot of the document
+    /// if it was [edited][`Editor::mark_edited`] since the last one
+    ///
+    /// The snapshot is written on a background thread and finished by [`Editor::poll_journal`].
+    /// If one is already being written, the new one waits for a later call.
+    /// Journaling failures are reported but don't undo the edits
+    pub fn journal_edits(&mut self) {
+        let Some(journal) = &mut self.journal else {
+            self.has_unjournaled_edits = false;
+            return;
+        };
+        if let Err(e) = journal.flush() {
+            eprintln!("failed to journal edit: {e}");
+        }
+        if journal.is_snapshotting() || !std::mem::take(&mut self.has_unjournaled_edits) {
+            return;
+        }
+        if let Err(e) = journal.start_snapshot(&self.document) {
+            eprintln!("failed to journal edit: {e}");
+        }
+    }
+
+    /// Check whether the journal snapshot being written has finished
+    pub fn poll_journal(&mut self) {
+        if let Some(result) = self.journal.as_mut().and_then(Journal::poll_snapshot) {
+            self.finish_snapshot(result);
+        }
+    }
+
+    /// Block until the journal snapshot being written (if any) has finished
+    pub fn wait_for_journal(&mut self) {
+        if let Some(result) = self.journal.as_mut().and_then(Journal::wait_for_snapshot) {
+            self.finish_snapshot(result);
+        }
+    }
+
+    /// Journal every edit made so far and block until it is written, so exiting doesn't lose the most recent ones
+    pub fn finish_journal(&mut self) {
+        self.wait_for_journal();
+        self.journal_edits();
+        self.wait_for_journal();
+    }
+
+    /// Report a failed journal snapshot, so the next [`Editor::journal_edits`] tries again
+    fn finish_snapshot(&mut self, result: Result<(), String>) {
+        if let Err(e) = result {
+            eprintln!("failed to journal edit: {e}");
+            self.has_unjournaled_edits = true;
+        }
+    }
+
+    /// Journal `entry` for an edit that was just made, and mark the document dirty
+    ///
+    /// If the document was [edited][`Editor::mark_edited`] since the last snapshot, a snapshot is journaled instead,
+    /// since replaying the entry onto a document missing those edits could apply it to the wrong layers.
+    /// Journaling failures are reported but don't undo the edit
+    pub fn record(&mut self, entry: JournalEntry) {
+        self.is_dirty = true;
+        if self.has_unjournaled_edits {
+            self.wait_for_journal();
+            self.journal_edits();
+            return;
+        }
+        if let Some(journal) = &mut self.journal
+            && let Err(e) = journal.record(entry)
+        {
+            eprintln!("failed to journal edit: {e}");
+        }
+    }
+
+    /// Journal the layers from `start` to the top of the layer list as having just been added, and mark the document dirty
+    ///
+    /// Journaling failures are reported but don't undo the edit
+    pub fn record_added_layers(&mut self, start: usize) {
+        self.is_dirty = true;
+        // replaying the layers onto a snapshot missing earlier edits would lose them
+        if self.has_unjournaled_edits {
+            self.wait_for_journal();
+            self.journal_edits();
+            return;
+        }
+        if let Some(journal) = &mut self.journal
+            && let Some(layers) = self.document.layers.get(start..)
+            && let Err(e) = journal.record_add_layers(&self.document, layers)
+        {
+            eprintln!("failed to journal edit: {e}");
+        }
+    }
+
+    /// Journal the document info as having just been replaced, and mark the document dirty
+    pub fn record_info(&mut self) {
+        self.record(JournalEntry::SetInfo(self.document.info.clone()));
+    }
+
+    /// Journal the artboards as having just been edited, and mark the document dirty
+    pub fn record_artboards(&mut self) {
+        self.record(JournalEntry::SetArtboards(self.document.artboards.clone()));
+    }
+
+    /// Journal `style` as having just been edited from `before`, and mark the document dirty
+    ///
+    /// Edits that give the style a different texture or width profile, or to a style no layer uses,
+    /// have no entry of their own and are journaled like [`Editor::mark_edited`]
+    pub fn record_style(&mut self, style: &WeakStyle, before: &Style) {
+        let Some(strong_style) = style.upgrade() else { return };
+        let mut layers = Vec::new();
+        self.document.visit_layers(|path, layer| if layer.style.ptr_eq(style) {
+            layers.push(path.to_vec());
+        });
+        let entry = {
+            let style_lock = strong_style.lock();
+            let style_borrow = style_lock.borrow();
+            (!layers.is_empty() && StyleRecord::keeps_resources(before, &style_borrow))
+                .then(|| JournalEntry::SetStyle { layers, style: StyleRecord::new(&style_borrow) })
+        };
+        match entry {
+            Some(entry) => self.record(entry),
+            None => self.mark_edited(),
+        }
+    }
+
+    /// Journal the current style's stroke width profile as having just been edited in place, and mark the document dirty
+    ///
+    /// The profile may belong to a new style instead, which is journaled like [`Editor::mark_edited`]
+    pub fn record_width_profile(&mut self) {
+        if let MaybeNew::Existing(style) = &self.current_style
+            && let Some(strong) = style.upgrade()
+        {
+            let style = style.clone();
+            let current = strong.lock().borrow().clone();
+            self.record_style(&style, &current);
+        } else {
+            self.mark_edited();
+        }
+    }
+
+    /// A [`JournalEntry::SetCurves`] with the current shape of `curves`, each found by the first layer drawing it
+    ///
+    /// [`None`] if any of them isn't drawn by a layer
+    fn curve_records<'a>(&self, curves: impl IntoIterator<Item = &'a WeakCurve>) -> Option<JournalEntry> {
+        let mut users = HashMap::<*const (), (Vec<usize>, usize)>::new();
+        self.document.visit_layers(|path, layer| for (index, curve) in layer.curves().iter().enumerate() {
+            users.entry(curve.as_ptr() as *const ()).or_insert_with(|| (path.to_vec(), index));
+        });
+        let records = curves.into_iter()
+            .filter(|curve| curve.strong_count() > 0)
+            .map(|curve| {
+                let (layer, index) = users.get(&(curve.as_ptr() as *const ()))?.clone();
+                CurveRecord::new(layer, index, curve)
+            })
+            .collect::<Option<Vec<_>>>()?;
+        Some(JournalEntry::SetCurves(records))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pinch_gesture() {
+        let pinch = PinchGesture::between([Vector2::new(0.0, 0.0), Vector2::new(10.0, 0.0)], [Vector2::new(5.0, 5.0), Vector2::new(15.0, 5.0)]);
+        assert_eq!((pinch.pan, pinch.scale, pinch.center), (Vector2::new(5.0, 5.0), 1.0, Vector2::new(10.0, 5.0)), "moving together only pans");
+
+        let pinch = PinchGesture::between([Vector2::new(-10.0, 0.0), Vector2::new(10.0, 0.0)], [Vector2::new(0.0, -40.0), Vector2::new(0.0, 40.0)]);
+        assert_eq!((pinch.pan, pinch.scale, pinch.center), (Vector2::zero(), 4.0, Vector2::zero()), "spreading apart zooms, whatever the angle");
+
+        let pinch = PinchGesture::between([Vector2::new(3.0, 3.0); 2], [Vector2::new(0.0, 0.0), Vector2::new(6.0, 8.0)]);
+        assert_eq!(pinch.scale, 1.0, "fingers landing on the same spot shouldn't zoom");
+        assert_eq!(pinch.center, Vector2::new(3.0, 4.0));
+    }
+
+    #[test]
+    fn test_artwork_clips() {
+        let screen = Rectangle::new(0.0, 0.0, 800.0, 600.0);
+        let mut editor = Editor::new_default(Document::new("trim".to_owned()));
+        editor.document.artboards = vec![
+            crate::document::Artboard::new("wide".to_owned(), Rectangle::new(0.0, 0.0, 100.0, 50.0)),
+            crate::document::Artboard::new("far away".to_owned(), Rectangle::new(5000.0, 0.0, 10.0, 10.0)),
+        ];
+        editor.camera = Camera2D { offset: Vector2::new(400.0, 300.0), target: Vector2::zero(), rotation: 0.0, zoom: 2.0 };
+        assert_eq!(editor.artwork_clips(screen), [screen], "without the trim view, the artwork can go anywhere on screen");
+
+        editor.is_trimming_to_artboards = true;
+        assert_eq!(editor.artwork_clips(screen), [Rectangle::new(400.0, 300.0, 200.0, 100.0)], "artboards off screen shouldn't be clipped to");
+
+        let is_near = |clips: Vec<Rectangle>, expected: Rectangle| {
+            clips.len() == 1 && [clips[0].x - expected.x, clips[0].y - expected.y, clips[0].width - expected.width, clips[0].height - expected.height]
+                .iter().all(|difference| difference.abs() < 1e-3)
+        };
+        // a quarter turn clockwise swings the artboard's right edge down below the camera's target
+        editor.camera.rotation = 90.0;
+        let clips = editor.artwork_clips(screen);
+        assert!(is_near(clips.clone(), Rectangle::new(300.0, 300.0, 100.0, 200.0)), "{clips:?}");
+
+        editor.document.artboards[0].rect = Rectangle::new(-50.0, -50.0, 100.0, 100.0);
+        editor.camera.rotation = 45.0;
+        editor.camera.zoom = 1.0;
+        let diagonal = 100.0 * std::f32::consts::SQRT_2;
+        let clips = editor.artwork_clips(screen);
+        assert!(is_near(clips.clone(), Rectangle::new(400.0 - diagonal * 0.5, 300.0 - diagonal * 0.5, diagonal, diagonal)), "a rotated artboard should be clipped to the box around it, not {clips:?}");
+    }
+
+    #[test]
+    fn test_spring_tools() {
+        let brushes = BrushPreferences::default();
+        let mut editor = Editor::new_default(Document::new("springs".to_owned()));
+        editor.set_tool(Tool::VectorBrush, &brushes);
+        editor.brush_stroke = vec![Vector2::new(0.0, 0.0), Vector2::new(50.0, 10.0)];
+
+        editor.press_spring_tool(SpringTool::Select);
+        editor.press_spring_tool(SpringTool::Select);
+        editor.press_spring_tool(SpringTool::Pan);
+        assert_eq!(editor.current_tool, Tool::Select);
+        assert_eq!(editor.tool_stack, [(SpringTool::Select, Tool::VectorBrush), (SpringTool::Pan, Tool::Select)], "pressing a held spring again does nothing");
+        assert!(editor.is_panning());
+
+        editor.release_spring_tool(SpringTool::Select, &brushes);
+        assert_eq!(editor.current_tool, Tool::VectorBrush);
+        assert!(editor.tool_stack.is_empty(), "springs pressed later are released along with the first");
+        assert!(!editor.is_panning());
+        assert_eq!(editor.brush_stroke.len(), 2, "the suspended stroke should be picked back up");
+        assert!(editor.document.layers.is_empty());
+        editor.release_spring_tool(SpringTool::Pan, &brushes);
+        assert_eq!(editor.current_tool, Tool::VectorBrush);
+
+        editor.press_spring_tool(SpringTool::Select);
+        editor.set_tool(Tool::Lasso, &brushes);
+        assert_eq!(editor.current_tool, Tool::Lasso);
+        assert!(editor.tool_stack.is_empty());
+        assert!(editor.brush_stroke.is_empty(), "switching tools should commit the suspended stroke");
+        assert_eq!(editor.document.layers.len(), 1);
+    }
+
+    #[test]
+    fn test_finish_lasso() {
+        let mut editor = Editor::new_default(Document::new("lasso".to_owned()));
+        editor.add_curve_layer("both inside".to_owned(), crate::make_curve!((5,5) -> (25,5)));
+        editor.add_curve_layer("end in the notch".to_owned(), crate::make_curve!((5,20) -> (15,20)));
+        editor.add_curve_layer("start in the notch".to_owned(), crate::make_curve!((15,20) -> (25,20)));
+        editor.add_curve_layer("outside".to_owned(), crate::make_curve!((40,40) -> (50,50)));
+        let curves = editor.document.layers.iter().flat_map(Layer::curves).collect::<Vec<_>>();
+        // a U shape, with a notch cut out of the middle of its top
+        let u_shape = [(0.0, 0.0), (30.0, 0.0), (30.0, 30.0), (20.0, 30.0), (20.0, 10.0), (10.0, 10.0), (10.0, 30.0), (0.0, 30.0)]
+            .map(|(x, y)| Vector2::new(x, y));
+
+        editor.lasso = u_shape.to_vec();
+        editor.finish_lasso(false);
+        assert!(editor.lasso.is_empty());
+        let Selection::Points(points) = &editor.selection else { panic!("lasso should select points") };
+        let selected = points.iter()
+            .map(|(curve, ranges)| (curves.iter().position(|other| other.ptr_eq(curve)).unwrap(), ranges.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(selected, [(0, vec![0..2]), (1, vec![0..1]), (2, vec![1..2])]);
+
+        editor.lasso = u_shape.to_vec();
+        editor.finish_lasso(true);
+        let Selection::Paths(paths) = &editor.selection else { panic!("whole path lasso should select paths") };
+        assert!(paths.len() == 1 && paths[0].ptr_eq(&curves[0]), "only curves entirely inside should be selected");
+
+        editor.lasso = vec![Vector2::new(-100.0, -100.0), Vector2::new(100.0, 100.0)];
+        editor.finish_lasso(false);
+        assert!(matches!(&editor.selection, Selection::Points(points) if points.is_empty()), "a line encloses nothing");
+    }
 }
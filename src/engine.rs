@@ -1,10 +1,11 @@
-use std::{ffi::CString, str::FromStr};
+use std::{ffi::CString, path::{Path, PathBuf}, str::FromStr};
 use raylib::{ffi::MeasureText, prelude::*};
+use serde::{Deserialize, Serialize};
 
-use crate::Editor;
+use crate::{brush_tip::BrushTipLibrary, clipboard, command::EditorAction, dialog::{ArrangeArtboardsDialog, ArtboardPanel, AssetExportDialog, ArtboardPanelAction, ArtboardRenameDialog, CommandPalette, DocumentInfoDialog, ExportDialog, FrameExportDialog, GridRepeatDialog, LayerPanel, LayerPanelAction, NoteDialog, PaletteExportDialog, PreferencesDialog, RenameDialog, ScaleDialog, ScaleDocumentDialog, ScatterDialog, StylePanel, StylePanelAction, SwatchDialog, WidthPanel, WidthPanelAction}, document::{Artboard, Document}, editor::{MaybeNew, Selection, Tool, ToolModifiers, ToolSettings}, export, file_job::{FileJob, FileOutcome}, format, frame::FrameScheduler, guides, journal::{Journal, StyleRecord}, layer::{self, Layer}, library::Library, locale, preferences::{AccessibilityPreferences, BrushPreferences, DocumentPreset, Preferences, WindowPreferences}, style_library::StyleLibrary, swatch::Swatch, navigator::Navigator, palette::{self, PaletteFormat}, pdf, query::LayerFilter, raster, simplify, snapshot::DocumentSnapshot, svg, tool_options, toolbar::Toolbar, tooltip::Tooltip, ui::widgets::{ListRows, Ui}, width_preset::WidthPresetLibrary, Editor};
 
 /// Application-wide visual customization options
-#[derive(Debug, Clone, Copy, Default)]
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
 pub struct EngineTheme {
     /// The background color of the viewport
     pub color_background: Color,
@@ -22,6 +23,11 @@ pub struct EngineTheme {
     pub color_irreversible: Color,
     /// The vertical size of standard UI text
     pub font_size: i32,
+    /// The width of selection outlines, in screenspace pixels
+    ///
+    /// Derived from [`AccessibilityPreferences`] by [`Engine::set_preferences`] rather than saved with the theme
+    #[serde(skip)]
+    pub selection_thickness: f32,
 }
 
 impl EngineTheme {
@@ -36,8 +42,35 @@ impl EngineTheme {
             color_destructive: Color::CRIMSON,
             color_irreversible: Color::RED,
             font_size: 10,
+            selection_thickness: 1.0,
         }
     }
+
+    /// Style raygui's controls after the theme, so the panels and dialogs it draws use the same colors as the rest of the UI
+    ///
+    /// Pressed controls, like the selected toggles of a panel's list, are filled with the accent color
+    pub fn apply_gui_style(&self, rl: &mut RaylibHandle) {
+        let styles = [
+            (GuiControlProperty::BORDER_COLOR_NORMAL as i32, self.color_panel_edge),
+            (GuiControlProperty::BASE_COLOR_NORMAL as i32, self.color_panel),
+            (GuiControlProperty::TEXT_COLOR_NORMAL as i32, self.color_foreground),
+            (GuiControlProperty::BORDER_COLOR_FOCUSED as i32, self.color_accent),
+            (GuiControlProperty::BASE_COLOR_FOCUSED as i32, self.color_panel),
+            (GuiControlProperty::TEXT_COLOR_FOCUSED as i32, self.color_accent),
+            (GuiControlProperty::BORDER_COLOR_PRESSED as i32, self.color_accent),
+            (GuiControlProperty::BASE_COLOR_PRESSED as i32, self.color_accent),
+            (GuiControlProperty::TEXT_COLOR_PRESSED as i32, self.color_foreground),
+            (GuiControlProperty::BORDER_COLOR_DISABLED as i32, self.color_panel_edge),
+            (GuiControlProperty::BASE_COLOR_DISABLED as i32, self.color_panel),
+            (GuiControlProperty::TEXT_COLOR_DISABLED as i32, self.color_foreground.alpha(0.4)),
+            (GuiDefaultProperty::LINE_COLOR as i32, self.color_panel_edge),
+            (GuiDefaultProperty::BACKGROUND_COLOR as i32, self.color_panel),
+        ];
+        for (property, color) in styles {
+            rl.gui_set_style(GuiControl::DEFAULT, property, color.color_to_int());
+        }
+        rl.gui_set_style(GuiControl::DEFAULT, GuiDefaultProperty::TEXT_SIZE as i32, self.font_size);
+    }
 }
 
 #[derive(Debug)]
@@ -51,9 +84,189 @@ pub struct Engine {
     ///
     /// [`None`] if no editor is focused (for example: if there are no editors)
     focused_editor: Option<u32>,
+
+    /// Reusable artwork and assets shared by every editor
+    ///
+    /// [`None`] if the library folder hasn't been indexed
+    pub library: Option<Library>,
+
+    /// Whether the library panel is shown
+    pub is_library_open: bool,
+
+    /// The index of the library item being dragged onto the viewport
+    pub library_drag: Option<usize>,
+
+    /// How far the library panel is scrolled down, in pixels
+    pub library_scroll: f32,
+
+    /// Documents being opened or imported on background threads, each becoming a new editor once read
+    pub file_jobs: Vec<FileJob>,
+
+    /// Layers copied from any editor, along with private copies of the resources they reference
+    ///
+    /// Stored as a document so pasting works the same regardless of which editor the layers came from
+    clipboard: Option<Document>,
+
+    /// The path data written to the system clipboard along with [`Engine::clipboard`],
+    /// to tell whether something else has been copied since
+    clipboard_text: Option<String>,
+
+    /// Named styles that can be applied to layers in any editor
+    pub style_library: StyleLibrary,
+
+    /// The stamps the raster brush can paint with
+    pub brush_tips: BrushTipLibrary,
+
+    /// Stroke width profiles that can be applied from the style panel
+    pub width_presets: WidthPresetLibrary,
+
+    /// The open "Document Info" dialog, editing the focused editor's document
+    ///
+    /// [`None`] if the dialog is closed
+    pub document_info_dialog: Option<DocumentInfoDialog>,
+
+    /// Application-wide settings
+    ///
+    /// Set with [`Engine::set_preferences`] so they take effect
+    preferences: Preferences,
+
+    /// The open preferences window
+    ///
+    /// [`None`] if the window is closed
+    pub preferences_dialog: Option<PreferencesDialog>,
+
+    /// Seconds since documents were last autosaved
+    autosave_elapsed: f32,
+
+    /// Seconds since edits were last journaled
+    journal_elapsed: f32,
+
+    /// Whether the template menu of the "new document" split button is open
+    pub is_template_menu_open: bool,
+
+    /// The open "Rename Document" dialog
+    ///
+    /// [`None`] if the dialog is closed
+    pub rename_dialog: Option<RenameDialog>,
+
+    /// The open "Add Note" dialog
+    ///
+    /// [`None`] if the dialog is closed
+    pub note_dialog: Option<NoteDialog>,
+
+    /// The open "Export" dialog
+    ///
+    /// [`None`] if the dialog is closed
+    pub export_dialog: Option<ExportDialog>,
+
+    /// The open "Rename Artboards" dialog
+    ///
+    /// [`None`] if the dialog is closed
+    pub artboard_rename_dialog: Option<ArtboardRenameDialog>,
+
+    /// The open "Asset Export" dialog, for a layer of one of the editors
+    ///
+    /// [`None`] if the dialog is closed
+    pub asset_dialog: Option<AssetExportDialog>,
+
+    /// The open "Scatter Along Path" dialog, for the focused editor's selection
+    ///
+    /// [`None`] if the dialog is closed
+    pub scatter_dialog: Option<ScatterDialog>,
+
+    /// The open "Grid Repeat" dialog, for the focused editor's selection
+    ///
+    /// [`None`] if the dialog is closed
+    pub grid_repeat_dialog: Option<GridRepeatDialog>,
+
+    /// The open "Scale" dialog, for the focused editor's selection
+    ///
+    /// [`None`] if the dialog is closed
+    pub scale_dialog: Option<ScaleDialog>,
+
+    /// The open "Scale Document" dialog, for the focused editor's document
+    ///
+    /// [`None`] if the dialog is closed
+    pub scale_document_dialog: Option<ScaleDocumentDialog>,
+
+    /// The open "Export Frames" dialog, for the focused editor's document
+    ///
+    /// [`None`] if the dialog is closed
+    pub frame_export_dialog: Option<FrameExportDialog>,
+
+    /// The open "Swatch" dialog, for one of the focused editor's swatches
+    ///
+    /// [`None`] if the dialog is closed
+    pub swatch_dialog: Option<SwatchDialog>,
+
+    /// The open "Arrange Artboards" dialog, for the focused editor's document
+    ///
+    /// [`None`] if the dialog is closed
+    pub arrange_dialog: Option<ArrangeArtboardsDialog>,
+
+    /// The open "Export Palette" dialog, for the focused editor's swatches
+    ///
+    /// [`None`] if the dialog is closed
+    pub palette_export_dialog: Option<PaletteExportDialog>,
+
+    /// The open command palette
+    ///
+    /// [`None`] if the palette is closed
+    pub command_palette: Option<CommandPalette>,
+
+    /// Help text for the control under the mouse
+    pub tooltip: Tooltip,
+
+    /// Focus and input shared by the widgets of every panel
+    pub ui: Ui,
+
+    /// When the window can sleep until the next input
+    pub frames: FrameScheduler,
+
+    /// The tool buttons along the left edge of the viewport
+    pub toolbar: Toolbar,
+
+    /// The open navigator panel
+    ///
+    /// [`None`] if the panel is closed
+    pub navigator: Option<Navigator>,
+
+    /// The open layers panel
+    ///
+    /// [`None`] if the panel is closed
+    pub layer_panel: Option<LayerPanel>,
+
+    /// The screenspace position an Alt+drag brush resize started at, and the brush size at the time
+    ///
+    /// [`None`] if the brush is not being resized
+    pub brush_resize: Option<(Vector2, f32)>,
+
+    /// Whether the tool options have been edited since the preferences were last saved
+    pub is_tool_options_unsaved: bool,
+
+    /// The editor index and time (in seconds) of the last editor tab click, for detecting double-clicks
+    last_tab_click: Option<(u32, f64)>,
+
+    /// The open style panel, editing the focused editor's current style
+    ///
+    /// [`None`] if the panel is closed
+    pub style_panel: Option<StylePanel>,
+
+    /// The open width profile panel, editing the stroke width of the focused editor's current style
+    ///
+    /// [`None`] if the panel is closed
+    pub width_panel: Option<WidthPanel>,
+
+    /// The open artboards panel, editing the selected artboards of the focused editor's document
+    ///
+    /// [`None`] if the panel is closed
+    pub artboard_panel: Option<ArtboardPanel>,
 }
 
 impl Engine {
+    /// The name of the application, shown in the window title
+    pub const APP_NAME: &str = "Amity Vector Art";
+
     /// Horizontal padding from the edge to the content of an editor tab
     pub const TAB_PADDING_H: f32 = 5.0;
 
@@ -65,21 +278,319 @@ impl Engine {
     /// Tab names exceeding this should be clipped
     pub const TAB_MAX_WIDTH: f32 = 100.0;
 
+    /// Width of the arrow of the "new document" split button that opens the template menu
+    pub const TAB_MENU_BUTTON_WIDTH: f32 = 12.0;
+
+    /// Width of each item in the template menu
+    pub const TEMPLATE_MENU_WIDTH: f32 = 180.0;
+
+    /// Thickness of the rulers along the top and left edges of the viewport
+    pub const RULER_SIZE: f32 = 16.0;
+
+    /// Minimum screenspace distance between labeled ruler ticks
+    pub const RULER_MIN_TICK_SPACING: f32 = 60.0;
+
+    /// Width of the library panel
+    pub const LIBRARY_PANEL_WIDTH: f32 = 160.0;
+
+    /// Padding between the edge of the library panel and its items, and between items
+    pub const LIBRARY_PADDING: f32 = 4.0;
+
+    /// Height of each row of the library panel: an item and the padding above it
+    pub const LIBRARY_ROW_HEIGHT: f32 = Library::THUMBNAIL_SIZE as f32 + Engine::LIBRARY_PADDING;
+
     /// Construct an engine without allocations
     pub const fn new(theme: EngineTheme) -> Self {
         Self {
             editors: Vec::new(),
             theme,
             focused_editor: None,
+            library: None,
+            is_library_open: false,
+            library_drag: None,
+            library_scroll: 0.0,
+            file_jobs: Vec::new(),
+            clipboard: None,
+            clipboard_text: None,
+            style_library: StyleLibrary::new(),
+            brush_tips: BrushTipLibrary::new(),
+            width_presets: WidthPresetLibrary::new(),
+            document_info_dialog: None,
+            preferences: Preferences::default_preferences(),
+            preferences_dialog: None,
+            autosave_elapsed: 0.0,
+            journal_elapsed: 0.0,
+            is_template_menu_open: false,
+            rename_dialog: None,
+            note_dialog: None,
+            export_dialog: None,
+            artboard_rename_dialog: None,
+            asset_dialog: None,
+            scatter_dialog: None,
+            grid_repeat_dialog: None,
+            scale_dialog: None,
+            scale_document_dialog: None,
+            frame_export_dialog: None,
+            swatch_dialog: None,
+            arrange_dialog: None,
+            palette_export_dialog: None,
+            command_palette: None,
+            tooltip: Tooltip::new(),
+            ui: Ui::new(),
+            frames: FrameScheduler::new(),
+            toolbar: Toolbar::new(),
+            brush_resize: None,
+            is_tool_options_unsaved: false,
+            navigator: None,
+            layer_panel: None,
+            last_tab_click: None,
+            style_panel: None,
+            width_panel: None,
+            artboard_panel: None,
+        }
+    }
+
+    /// Application-wide settings
+    #[inline]
+    pub const fn preferences(&self) -> &Preferences {
+        &self.preferences
+    }
+
+    /// Replace the application-wide settings and apply them to the theme, UI, and open editors
+    pub fn set_preferences(&mut self, rl: &mut RaylibHandle, preferences: Preferences) {
+        let ui_scale = preferences.ui_scale.clamp(Preferences::MIN_UI_SCALE, Preferences::MAX_UI_SCALE);
+        self.theme = preferences.theme;
+        self.theme.font_size = (preferences.theme.font_size as f32 * ui_scale).round() as i32;
+        let accessibility = &preferences.accessibility;
+        if accessibility.is_color_blind_safe {
+            self.theme.color_accent = accessibility.accent;
+            self.theme.color_destructive = accessibility.destructive;
+            self.theme.color_irreversible = accessibility.destructive;
+            self.theme.selection_thickness = accessibility.selection_thickness
+                .clamp(AccessibilityPreferences::MIN_SELECTION_THICKNESS, AccessibilityPreferences::MAX_SELECTION_THICKNESS);
+        } else {
+            self.theme.selection_thickness = EngineTheme::default_theme().selection_thickness;
+        }
+        self.theme.apply_gui_style(rl);
+        let vsync = WindowState::default().set_vsync_hint(true);
+        if preferences.display.is_vsync {
+            rl.set_window_state(vsync);
+        } else {
+            rl.clear_window_state(vsync);
+        }
+        if let Err(e) = locale::set_language(preferences.language.as_deref().unwrap_or(locale::DEFAULT_LANGUAGE)) {
+            eprintln!("failed to load language: {e}");
+        }
+        for editor in &mut self.editors {
+            editor.new_object_style = preferences.new_object_style;
+            editor.zoom_limits = preferences.navigation.zoom_limits();
+        }
+        self.preferences = preferences;
+    }
+
+    /// Set the diameter of `tool`'s brush, without saving it; see [`BrushPreferences::set_size`]
+    pub fn set_brush_size(&mut self, tool: Tool, size: f32) {
+        self.preferences.brushes.set_size(tool, size);
+    }
+
+    /// Choose whether scaling also scales stroke widths, without saving it
+    pub const fn set_scaling_strokes(&mut self, is_scaling_strokes: bool) {
+        self.preferences.transform.is_scaling_strokes = is_scaling_strokes;
+    }
+
+    /// Set the raster eraser's hardness, without saving it; see [`BrushPreferences::set_eraser_hardness`]
+    pub fn set_eraser_hardness(&mut self, hardness: f32) {
+        self.preferences.brushes.set_eraser_hardness(hardness);
+    }
+
+    /// Turn smart guides on or off
+    pub fn toggle_smart_guides(&mut self) {
+        self.preferences.snapping.is_using_smart_guides = !self.preferences.snapping.is_using_smart_guides;
+    }
+
+    /// Switch between measuring coordinates from the worldspace origin and from each editor's active artboard
+    pub const fn toggle_artboard_coordinates(&mut self) {
+        self.preferences.coordinates.is_artboard_relative = !self.preferences.coordinates.is_artboard_relative;
+    }
+
+    /// Run `action` if it changes the preferences, without saving them
+    ///
+    /// Brush sizes apply to the focused editor's current tool, doing nothing if it has no brush. \
+    /// Returns false if `action` doesn't change the preferences
+    pub fn run_preference_action(&mut self, action: EditorAction) -> bool {
+        let hardness = self.preferences.brushes.eraser_hardness;
+        match action {
+            EditorAction::ToggleSmartGuides => self.toggle_smart_guides(),
+            EditorAction::ToggleArtboardCoordinates => self.toggle_artboard_coordinates(),
+            EditorAction::DecreaseEraserHardness => self.set_eraser_hardness(hardness - BrushPreferences::HARDNESS_STEP),
+            EditorAction::IncreaseEraserHardness => self.set_eraser_hardness(hardness + BrushPreferences::HARDNESS_STEP),
+            EditorAction::DecreaseBrushSize | EditorAction::IncreaseBrushSize => {
+                if let Some(tool) = self.focused_editor().map(|editor| editor.current_tool)
+                    && let Some(size) = self.preferences.brushes.size(tool)
+                {
+                    self.set_brush_size(tool, if action == EditorAction::IncreaseBrushSize {
+                        size * BrushPreferences::SIZE_STEP
+                    } else {
+                        size / BrushPreferences::SIZE_STEP
+                    });
+                }
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    /// Remember where the window is, without saving it; see [`WindowPreferences::capture`]
+    pub fn capture_window(&mut self, rl: &RaylibHandle) {
+        self.preferences.window.capture(rl);
+    }
+
+    /// Write the current preferences to the [config file][`Preferences::config_path`]
+    pub fn save_preferences(&self) -> Result<(), String> {
+        let path = Preferences::config_path().ok_or("config path could not be determined")?;
+        self.preferences.save(&path)
+    }
+
+    /// [Autosave][`Editor::start_autosave`] every document with unsaved changes that already has a file,
+    /// once every [autosave interval][`Preferences::autosave_minutes`]
+    ///
+    /// While a document is waiting to be autosaved, the window is [woken][`FrameScheduler::wake_after`] when the interval
+    /// runs out, so autosave doesn't wait for input. Does nothing if autosave is disabled
+    pub fn tick_autosave(&mut self, dt: f32) {
+        if self.preferences.autosave_minutes == 0 {
+            self.autosave_elapsed = 0.0;
+            return;
+        }
+        self.autosave_elapsed += dt;
+        let interval = self.preferences.autosave_minutes as f32 * 60.0;
+        if self.autosave_elapsed < interval {
+            if self.editors.iter().any(|editor| editor.is_dirty && editor.document.file_path.is_some()) {
+                self.frames.wake_after(interval - self.autosave_elapsed);
+            }
+            return;
+        }
+        self.autosave_elapsed = 0.0;
+        let is_compressed = self.preferences.is_compressing_packages;
+        for editor in &mut self.editors {
+            if let Err(e) = editor.start_autosave(is_compressed) {
+                eprintln!("failed to autosave {}: {e}", editor.document.title);
+            }
+        }
+    }
+
+    /// Advance the journaling timer by `dt` seconds, and [journal][`Editor::journal_edits`] the edits made to each document
+    /// since the last time it ran out
+    ///
+    /// Edits without entries of their own are journaled at most once an interval, since each journals a snapshot of the
+    /// whole document, and a drag's entries are merged until then. While either is waiting, the window is
+    /// [woken][`FrameScheduler::wake_after`] when the interval runs out. Snapshots being written are checked every call
+    pub fn tick_journal(&mut self, dt: f32) {
+        /// The seconds between journaling edits
+        const JOURNAL_INTERVAL: f32 = 1.0;
+
+        for editor in &mut self.editors {
+            editor.poll_journal();
+        }
+        self.journal_elapsed += dt;
+        if self.journal_elapsed < JOURNAL_INTERVAL {
+            if self.editors.iter().any(Editor::is_journal_pending) {
+                self.frames.wake_after(JOURNAL_INTERVAL - self.journal_elapsed);
+            }
+            return;
+        }
+        self.journal_elapsed = 0.0;
+        for editor in &mut self.editors {
+            editor.journal_edits();
         }
     }
 
     /// Push an editor and focuses it
-    pub fn create_editor(&mut self, editor: Editor) {
+    pub fn create_editor(&mut self, mut editor: Editor) {
+        editor.new_object_style = self.preferences.new_object_style;
+        editor.zoom_limits = self.preferences.navigation.zoom_limits();
         self.editors.push(editor);
         self.focused_editor = (self.editors.len() as u32).checked_sub(1);
     }
 
+    /// The text the OS window title should show: the focused document's title, marked with `*` if it has unsaved changes
+    pub fn window_title(&self) -> String {
+        match self.focused_editor() {
+            Some(editor) => format!("{}{} \u{2014} {}", editor.document.title, if editor.is_dirty { "*" } else { "" }, Engine::APP_NAME),
+            None => Engine::APP_NAME.to_owned(),
+        }
+    }
+
+    /// Register a click on the tab of the editor at `index` at `time` seconds and get whether it completes a double-click
+    pub fn click_tab(&mut self, index: u32, time: f64) -> bool {
+        let is_double_click = self.last_tab_click
+            .is_some_and(|(last_index, last_time)| last_index == index && time - last_time <= Editor::DOUBLE_CLICK_TIME);
+        self.last_tab_click = if is_double_click { None } else { Some((index, time)) };
+        is_double_click
+    }
+
+    /// Open the "Rename Document" dialog for the editor at `index`
+    ///
+    /// Does nothing if there is no editor at `index`
+    pub fn open_rename_dialog(&mut self, index: u32) {
+        if let Some(editor) = self.editor(index) {
+            self.rename_dialog = Some(RenameDialog::new(index, &editor.document.title));
+        }
+    }
+
+    /// Open the "Export" dialog for the editor at `index`
+    ///
+    /// Does nothing if there is no editor at `index`
+    pub fn open_export_dialog(&mut self, index: u32) {
+        if let Some(editor) = self.editor(index) {
+            self.export_dialog = Some(ExportDialog::new(index, &editor.document));
+        }
+    }
+
+    /// Open the "Rename Artboards" dialog for the editor at `index`
+    ///
+    /// Does nothing if there is no editor at `index`
+    pub fn open_artboard_rename_dialog(&mut self, index: u32) {
+        if let Some(editor) = self.editor(index) {
+            self.artboard_rename_dialog = Some(ArtboardRenameDialog::new(index, &editor.document));
+        }
+    }
+
+    /// Open the "Asset Export" dialog for the first selected layer of the editor at `index`
+    ///
+    /// Does nothing if there is no editor at `index` or it has no layers selected
+    pub fn open_asset_dialog(&mut self, index: u32) {
+        let dialog = self.editor(index).and_then(|editor| {
+            editor.selection_info().layers.first()
+                .map(|path| AssetExportDialog::new(index, &editor.document, path.clone()))
+        });
+        if dialog.is_some() {
+            self.asset_dialog = dialog;
+        }
+    }
+
+    /// Pin or unpin the editor at `index`
+    ///
+    /// Pinned tabs are always leftmost, so the editor is moved to the boundary between pinned and
+    /// unpinned editors. The focus index is updated so it refers to the same editor
+    pub fn set_editor_pinned(&mut self, index: u32, is_pinned: bool) {
+        let Some(editor) = self.editors.get(index as usize) else { return };
+        if editor.is_pinned == is_pinned {
+            return;
+        }
+        let mut editor = self.editors.remove(index as usize);
+        editor.is_pinned = is_pinned;
+        let boundary = self.editors.iter().take_while(|editor| editor.is_pinned).count() as u32;
+        self.editors.insert(boundary as usize, editor);
+        if let Some(focused_editor) = self.focused_editor {
+            self.focused_editor = Some(if focused_editor == index {
+                boundary
+            } else {
+                let shifted = if focused_editor > index { focused_editor - 1 } else { focused_editor };
+                if shifted >= boundary { shifted + 1 } else { shifted }
+            });
+        }
+    }
+
     #[inline]
     pub fn editors(&self) -> &[Editor] {
         &self.editors
@@ -114,6 +625,12 @@ impl Engine {
         self.focused_editor = None;
     }
 
+    #[inline]
+    pub const fn focused_editor_index(&self) -> Option<u32> {
+        self.focused_editor
+    }
+
+    #[inline]
     pub fn focused_editor_index_eq(&self, idx: u32) -> bool {
         self.focused_editor.is_some_and(|focused| focused == idx)
     }
@@ -183,6 +700,11 @@ impl Engine {
     /// Panics if index is out of bounds
     pub fn remove_editor(&mut self, index: u32) -> Editor {
         let editor = self.editors.remove(index as usize);
+        // the dialogs' indices would refer to a different editor
+        self.rename_dialog = None;
+        self.note_dialog = None;
+        self.export_dialog = None;
+        self.artboard_rename_dialog = None;
         let num_editors = self.editors.len() as u32;
         if let Some(focused_editor) = &self.focused_editor {
             if *focused_editor >= num_editors {
@@ -194,6 +716,268 @@ impl Engine {
         editor
     }
 
+    /// Open a file dropped onto the window at the screenspace `position`
+    ///
+    /// - Native documents (packaged or not), SVGs, PDFs, EPS, and Illustrator files are read on a background thread, then opened in a new editor by [`Engine::tick_file_jobs`]
+    /// - PNGs are placed as an image layer in the focused editor's document, centered on `position`
+    /// - Palette files add their colors to the focused editor's swatches
+    pub fn open_dropped_file(&mut self, rl: &mut RaylibHandle, thread: &RaylibThread, path: &Path, position: Vector2) -> Result<(), String> {
+        let extension = path.extension().and_then(|ext| ext.to_str()).unwrap_or_default();
+        if extension.eq_ignore_ascii_case(format::EXTENSION) || extension.eq_ignore_ascii_case(format::PACKAGE_EXTENSION) {
+            self.file_jobs.push(FileJob::open(path)?);
+        } else if extension.eq_ignore_ascii_case("svg") {
+            self.file_jobs.push(FileJob::import_svg(path)?);
+        } else if pdf::EXTENSIONS.iter().any(|ext| extension.eq_ignore_ascii_case(ext)) {
+            self.file_jobs.push(FileJob::import_pdf(path)?);
+        } else if extension.eq_ignore_ascii_case("png") && self.focused_editor().is_some_and(|editor| editor.current_tool == Tool::RasterBrush) {
+            // dropped onto the raster brush, an image becomes its tip
+            let name = self.brush_tips.add(rl, thread, path)?;
+            self.preferences.brushes.tip = Some(name);
+            self.is_tool_options_unsaved = true;
+        } else if extension.eq_ignore_ascii_case("png") {
+            let editor = self.focused_editor_mut().ok_or("no document to place the image in")?;
+            let image = Image::load_image(path.to_str().ok_or("image path should be valid UTF-8")?)?;
+            let name = path.file_stem().unwrap_or_default().to_string_lossy().into_owned();
+            let center = rl.get_screen_to_world2D(position, editor.camera);
+            let style = editor.style_for_new_object();
+            editor.document.place_image(rl, thread, name, &image, style, center)?;
+            editor.record_added_layers(editor.document.layers.len() - 1);
+        } else if PaletteFormat::from_extension(extension).is_some() {
+            let editor = self.focused_editor_mut().ok_or("no document to add the swatches to")?;
+            editor.import_swatches(palette::read(path)?);
+        } else {
+            return Err(format!("unsupported file type: {}", path.display()));
+        }
+        Ok(())
+    }
+
+    /// Open the documents that have finished being read, and finish the saves that have finished being written
+    ///
+    /// Failures are reported without stopping the other jobs
+    pub fn tick_file_jobs(&mut self, rl: &mut RaylibHandle, thread: &RaylibThread) {
+        let mut i = 0;
+        while let Some(job) = self.file_jobs.get_mut(i) {
+            let Some(result) = job.poll() else {
+                i = i.saturating_add(1);
+                continue;
+            };
+            let job = self.file_jobs.remove(i);
+            if let Err(e) = result.and_then(|outcome| self.open_file_outcome(rl, thread, job.path(), outcome)) {
+                eprintln!("failed to open {}: {e}", job.path().display());
+            }
+        }
+
+        for editor in &mut self.editors {
+            if let Some(Err(e)) = editor.poll_save() {
+                eprintln!("failed to save {}: {e}", editor.document.title);
+            }
+            if let Some(Err(e)) = editor.poll_autosave() {
+                eprintln!("failed to autosave {}: {e}", editor.document.title);
+            }
+        }
+    }
+
+    /// Open a document read from `path` by a [`FileJob`] in a new editor
+    fn open_file_outcome(&mut self, rl: &mut RaylibHandle, thread: &RaylibThread, path: &Path, outcome: FileOutcome) -> Result<(), String> {
+        match outcome {
+            FileOutcome::Opened(data) => {
+                let mut document = format::build(rl, thread, data)?;
+                // recover edits made after the last save, if the application exited without saving
+                let mut journal = Journal::new(path);
+                let is_recovered = journal.has_entries();
+                if is_recovered {
+                    journal.replay(rl, thread, &mut document)?;
+                }
+                let mut editor = Editor::new_default(document);
+                editor.journal = Some(journal);
+                // recovered edits still need to be saved
+                editor.is_dirty = is_recovered;
+                self.create_editor(editor);
+            }
+            FileOutcome::Imported(data) => {
+                let mut document = format::build(rl, thread, data)?;
+                // imported documents have no native file yet
+                document.file_path = None;
+                let mut editor = Editor::new_default(document);
+                editor.is_dirty = true;
+                self.create_editor(editor);
+            }
+            FileOutcome::Saved => (),
+        }
+        Ok(())
+    }
+
+    /// Block until every save in progress has been written, so exiting doesn't leave half-written files
+    ///
+    /// Edits that aren't journaled yet are journaled too, so they can still be recovered
+    pub fn finish_saves(&mut self) {
+        for editor in &mut self.editors {
+            if let Some(Err(e)) = editor.wait_for_save() {
+                eprintln!("failed to save {}: {e}", editor.document.title);
+            }
+            editor.finish_journal();
+        }
+    }
+
+    /// Whether any document is being opened, imported, or saved, or has a journal snapshot being written
+    pub fn has_file_jobs(&self) -> bool {
+        !self.file_jobs.is_empty() || self.editors.iter().any(|editor| {
+            editor.save_job.is_some() || editor.journal.as_ref().is_some_and(Journal::is_snapshotting)
+        })
+    }
+
+    /// Copy the topmost layer of the focused editor's document to the clipboard
+    ///
+    /// The outlines of its curves are also written to the system clipboard as SVG path data, for pasting into other applications
+    ///
+    /// Does nothing if there is no focused editor or the document has no layers
+    pub fn copy_layer(&mut self, rl: &mut RaylibHandle, thread: &RaylibThread) -> Result<(), String> {
+        if let Some(editor) = self.focused_editor()
+            && let Some(layer) = editor.document.layers.last()
+        {
+            let mut clipboard = Document::new("clipboard".to_owned());
+            clipboard.layers = clipboard.clone_layers_from(rl, thread, std::slice::from_ref(layer))?;
+            let path_data = layer.curves().iter()
+                .filter_map(|curve| curve.upgrade())
+                .map(|curve| curve.lock().borrow().to_svg_path_data())
+                .collect::<Vec<_>>()
+                .join(" ");
+            self.clipboard = Some(clipboard);
+            self.clipboard_text = None;
+            if !path_data.is_empty() {
+                rl.set_clipboard_text(&path_data).map_err(|e| e.to_string())?;
+                self.clipboard_text = Some(path_data);
+            }
+        }
+        Ok(())
+    }
+
+    /// The layers of the focused editor's selection, in document order, and the worldspace rectangle containing their artwork
+    ///
+    /// Selected points and paths stand for the layers drawing them, and layers inside selected groups are left to their group.
+    /// The rectangle contains the layers' [visual bounds][`Layer::visual_bounds`], since control bounds stop at the path
+    ///
+    /// [`None`] if there is no focused editor or nothing with artwork is selected
+    fn selected_artwork(&self) -> Option<(Vec<&Layer>, Rectangle)> {
+        let editor = self.focused_editor()?;
+        let paths = &editor.selection_info().layers;
+        let layers = paths.iter()
+            .filter(|path| !paths.iter().any(|other| other.len() < path.len() && path.starts_with(other)))
+            .filter_map(|path| editor.document.layer_at(path))
+            .collect::<Vec<_>>();
+        let bounds = layers.iter().filter_map(|layer| layer.visual_bounds()).reduce(layer::union)?;
+        Some((layers, bounds))
+    }
+
+    /// Render the focused editor's selection on a transparent background and place it on the system clipboard as a PNG image
+    pub fn copy_selection_png(&self, rl: &mut RaylibHandle, thread: &RaylibThread) -> Result<(), String> {
+        let (layers, bounds) = self.selected_artwork().ok_or("nothing with artwork is selected")?;
+        let image = export::render_layers(rl, thread, layers, bounds, Color::BLANK, 1.0)?;
+        clipboard::set_png(&raster::encode_png(&image)?)
+    }
+
+    /// Place the focused editor's selection on the system clipboard as SVG markup
+    ///
+    /// The markup is copied as text, which design tools and editors accept as SVG when pasted.
+    /// Its coordinates are measured from the editor's [coordinate origin][`Editor::coordinate_origin`],
+    /// and it carries the document's title and author as metadata
+    pub fn copy_selection_svg(&self, rl: &mut RaylibHandle) -> Result<(), String> {
+        let (layers, bounds) = self.selected_artwork().ok_or("nothing with artwork is selected")?;
+        let editor = self.focused_editor().ok_or("no document is open")?;
+        let origin = editor.coordinate_origin(self.preferences.coordinates.is_artboard_relative);
+        let text = export::png_text(&editor.document, "");
+        rl.set_clipboard_text(&svg::export(layers, bounds, origin, &text)).map_err(|e| e.to_string())
+    }
+
+    /// Paste the clipboard's layers above every layer of the focused editor's document
+    ///
+    /// The styles, width profiles, and rasters they reference are copied into the document,
+    /// so pasting into another tab doesn't leave references to resources it doesn't own.
+    ///
+    /// The app's own clipboard is preferred while the system clipboard still holds the path data copied with it.
+    /// If SVG path data was copied in another application since, it is pasted as a new path instead,
+    /// with several subpaths becoming one compound path
+    ///
+    /// Does nothing if the clipboard is empty or there is no focused editor
+    pub fn paste_layers(&mut self, rl: &mut RaylibHandle, thread: &RaylibThread) -> Result<(), String> {
+        // platforms may add line endings to copied text, so they don't count as a change
+        let is_own_text = |text: &str| self.clipboard_text.as_deref().is_some_and(|own| own.trim() == text.trim());
+        let pasted_curves = rl.get_clipboard_text()
+            .ok()
+            .filter(|text| !(self.clipboard.is_some() && is_own_text(text.as_str())))
+            .map(|text| svg::parse_path_data(&text))
+            .unwrap_or_default();
+        if !pasted_curves.is_empty()
+            && let Some(focused) = self.focused_editor
+            && let Some(editor) = self.editors.get_mut(focused as usize)
+        {
+            editor.add_path_layer("Pasted path".to_owned(), pasted_curves);
+        } else if let Some(clipboard) = &self.clipboard
+            && let Some(focused) = self.focused_editor
+            && let Some(editor) = self.editors.get_mut(focused as usize)
+        {
+            let layers = editor.document.clone_layers_from(rl, thread, &clipboard.layers)?;
+            let start = editor.document.layers.len();
+            editor.document.layers.extend(layers);
+            editor.record_added_layers(start);
+        }
+        Ok(())
+    }
+
+    /// Define a new graphic style from the style of the focused editor's topmost layer
+    ///
+    /// The topmost layer is linked to the new graphic style, so redefining it updates the layer too
+    ///
+    /// Does nothing if there is no focused editor or the document has no layers
+    pub fn define_graphic_style(&mut self, rl: &mut RaylibHandle, thread: &RaylibThread) -> Result<(), String> {
+        if let Some(focused) = self.focused_editor
+            && let Some(editor) = self.editors.get_mut(focused as usize)
+            && let Some(layer) = editor.document.layers.last()
+        {
+            let style = layer.style.upgrade().ok_or("style reference is dead")?;
+            let style = style.lock().borrow().clone();
+            let name = format!("Graphic Style {}", self.style_library.styles.len() + 1);
+            let index = self.style_library.define(rl, thread, name, &style)?;
+            let linked = self.style_library.link(rl, thread, index, &mut editor.document)?;
+            if let Some(layer) = editor.document.layers.last_mut() {
+                layer.style = linked;
+            }
+        }
+        Ok(())
+    }
+
+    /// Apply the graphic style at `index` to the focused editor's topmost layer
+    ///
+    /// Does nothing if there is no focused editor or the document has no layers
+    pub fn apply_graphic_style(&mut self, rl: &mut RaylibHandle, thread: &RaylibThread, index: usize) -> Result<(), String> {
+        if let Some(focused) = self.focused_editor
+            && let Some(editor) = self.editors.get_mut(focused as usize)
+            && !editor.document.layers.is_empty()
+        {
+            let linked = self.style_library.link(rl, thread, index, &mut editor.document)?;
+            if let Some(layer) = editor.document.layers.last_mut() {
+                layer.style = linked;
+            }
+        }
+        Ok(())
+    }
+
+    /// Redefine the graphic style at `index` from the style of the focused editor's topmost layer,
+    /// updating every linked layer in every editor
+    ///
+    /// Does nothing if there is no focused editor or the document has no layers
+    pub fn redefine_graphic_style(&mut self, rl: &mut RaylibHandle, thread: &RaylibThread, index: usize) -> Result<(), String> {
+        if let Some(editor) = self.focused_editor()
+            && let Some(layer) = editor.document.layers.last()
+        {
+            let style = layer.style.upgrade().ok_or("style reference is dead")?;
+            let style = style.lock().borrow().clone();
+            let documents = self.editors.iter_mut().map(|editor| &mut editor.document);
+            self.style_library.redefine(rl, thread, index, &style, documents)?;
+        }
+        Ok(())
+    }
+
     /// Iterate over tabs
     ///
     /// Order of tabs:
@@ -208,6 +992,498 @@ impl Engine {
     pub fn tab_well(&self, window_width: f32) -> Rectangle {
         Rectangle::new(0.0, 0.0, window_width, self.theme.font_size as f32 + Engine::TAB_PADDING_V * 2.0)
     }
+
+    /// Every preset offered by the template menu with the localization key of its name, starting with the user's default preset
+    pub fn templates(&self) -> impl Iterator<Item = (&'static str, DocumentPreset)> {
+        std::iter::once(("template.default", self.preferences.default_document))
+            .chain(DocumentPreset::TEMPLATES)
+    }
+
+    /// Get (calculate) the rectangle of the template at `index` in the template menu, which drops down from `new_tab_rect`
+    pub fn template_menu_item_rect(new_tab_rect: Rectangle, index: usize) -> Rectangle {
+        Rectangle::new(
+            new_tab_rect.x,
+            new_tab_rect.y + new_tab_rect.height * (index + 1) as f32,
+            Engine::TEMPLATE_MENU_WIDTH,
+            new_tab_rect.height,
+        )
+    }
+
+    /// Get (calculate) the tool options rectangle for the focused editor's current tool, beside the toolbar below the group scope breadcrumbs
+    pub fn tool_options_bounds(&self, window_width: f32) -> Rectangle {
+        let toolbar = self.toolbar_bounds(window_width);
+        let tool = self.focused_editor().map_or(Tool::default(), |editor| editor.current_tool);
+        tool_options::bounds(Vector2::new(
+            toolbar.x + toolbar.width + Engine::TAB_PADDING_H,
+            toolbar.y + self.theme.font_size as f32 + Engine::TAB_PADDING_V * 2.0,
+        ), tool)
+    }
+
+    /// Get (calculate) the screenspace rectangle of the Simplify preview panel, which takes the place of the tool options strip
+    pub fn simplify_panel_bounds(&self, window_width: f32) -> Rectangle {
+        let tool_options = self.tool_options_bounds(window_width);
+        simplify::bounds(Vector2::new(tool_options.x, tool_options.y))
+    }
+
+    /// Draw the options of the focused editor's current tool, applying edits to the preferences without saving them
+    ///
+    /// Does nothing if there is no focused editor, its tool has no options, or it is previewing a Simplify
+    pub fn draw_tool_options(&mut self, d: &mut impl RaylibDraw, window_width: f32) {
+        let bounds = self.tool_options_bounds(window_width);
+        let tool = self.focused_editor()
+            .filter(|editor| editor.simplify.is_none() && tool_options::has_options(editor.current_tool))
+            .map(|editor| editor.current_tool);
+        if let Some(tool) = tool
+            && tool_options::draw(d, &mut self.ui, bounds, tool, &mut self.preferences, &self.brush_tips)
+        {
+            self.is_tool_options_unsaved = true;
+        }
+    }
+
+    /// Get (calculate) toolbar rectangle, docked to the left edge beside the vertical ruler and below the horizontal one
+    pub fn toolbar_bounds(&self, window_width: f32) -> Rectangle {
+        let top = self.tab_well(window_width).height;
+        Toolbar::bounds(Vector2::new(Engine::RULER_SIZE, top + Engine::RULER_SIZE))
+    }
+
+    /// Get (calculate) the screenspace rectangle of the quick export button above the top-right corner of `artboard` in `editor`
+    pub fn quick_export_button(&self, editor: &Editor, artboard: &Artboard) -> Rectangle {
+        let size = self.theme.font_size as f32 + 4.0;
+        let camera = &editor.camera;
+        let corner = (Vector2::new(artboard.rect.x + artboard.rect.width, artboard.rect.y) - camera.target) * camera.zoom + camera.offset;
+        Rectangle::new(corner.x - size, corner.y - size - 2.0, size, size)
+    }
+
+    /// Get (calculate) the screenspace rectangle documents are shown in, below the tab well and inside the rulers
+    pub fn viewport(&self, window_width: f32, window_height: f32) -> Rectangle {
+        let top = self.tab_well(window_width).height + Engine::RULER_SIZE;
+        Rectangle::new(Engine::RULER_SIZE, top, window_width - Engine::RULER_SIZE, window_height - top)
+    }
+
+    /// Get (calculate) navigator panel rectangle, docked to the bottom-left corner of the viewport above the status text
+    pub fn navigator_bounds(&self, window_width: f32, window_height: f32) -> Rectangle {
+        let status_height = self.theme.font_size as f32 + Engine::TAB_PADDING_V * 2.0;
+        let viewport = self.viewport(window_width, window_height);
+        Navigator::bounds(viewport.x, viewport.y + viewport.height - status_height)
+    }
+
+    /// Refresh the navigator's thumbnail of the focused editor and move its camera while the view rectangle is dragged
+    ///
+    /// Does nothing if the navigator is closed or there is no focused editor
+    pub fn tick_navigator(&mut self, rl: &mut RaylibHandle, thread: &RaylibThread) -> Result<(), String> {
+        let (window_width, window_height) = (rl.get_screen_width() as f32, rl.get_screen_height() as f32);
+        let bounds = self.navigator_bounds(window_width, window_height);
+        let viewport = self.viewport(window_width, window_height);
+        if let Some(navigator) = &mut self.navigator
+            && let Some(focused) = self.focused_editor
+            && let Some(editor) = self.editors.get_mut(focused as usize)
+        {
+            let dt = rl.get_frame_time();
+            navigator.refresh(rl, thread, editor, focused, dt)?;
+            navigator.update(rl, bounds, editor, viewport);
+        }
+        Ok(())
+    }
+
+    /// Draw the navigator (if open) for the focused editor, zooming it if the zoom slider is moved
+    ///
+    /// Does nothing if the navigator is closed or there is no focused editor
+    pub fn draw_navigator(&mut self, d: &mut impl RaylibDraw, window_width: f32, window_height: f32) {
+        let bounds = self.navigator_bounds(window_width, window_height);
+        let viewport = self.viewport(window_width, window_height);
+        if let Some(navigator) = &self.navigator
+            && let Some(focused) = self.focused_editor
+            && let Some(editor) = self.editors.get_mut(focused as usize)
+        {
+            navigator.draw(d, bounds, &self.theme, editor, viewport);
+        }
+    }
+
+    /// Get (calculate) layers panel rectangle, docked to the left edge of the viewport above the navigator, or above the status text if it is closed
+    pub fn layer_panel_bounds(&self, window_width: f32, window_height: f32) -> Rectangle {
+        let viewport = self.viewport(window_width, window_height);
+        let bottom = if self.navigator.is_some() {
+            self.navigator_bounds(window_width, window_height).y
+        } else {
+            let status_height = self.theme.font_size as f32 + Engine::TAB_PADDING_V * 2.0;
+            viewport.y + viewport.height - status_height
+        };
+        LayerPanel::bounds(viewport.x, bottom)
+    }
+
+    /// Re-render the layers panel's out of date thumbnails of the focused editor, drawn from `snapshot` of its layers
+    ///
+    /// Does nothing if the panel is closed or there is no focused editor
+    pub fn refresh_layer_panel(&mut self, rl: &mut RaylibHandle, thread: &RaylibThread, snapshot: &DocumentSnapshot) -> Result<(), String> {
+        let bounds = self.layer_panel_bounds(rl.get_screen_width() as f32, rl.get_screen_height() as f32);
+        if let Some(panel) = &mut self.layer_panel
+            && let Some(focused) = self.focused_editor
+            && let Some(editor) = self.editors.get(focused as usize)
+        {
+            panel.refresh(rl, thread, bounds, editor, focused, snapshot)?;
+        }
+        Ok(())
+    }
+
+    /// Draw the layers panel (if open) for the focused editor, selecting or repairing layers as it asks
+    ///
+    /// Does nothing if the panel is closed or there is no focused editor
+    pub fn draw_layer_panel(&mut self, d: &mut impl RaylibDraw, window_width: f32, window_height: f32) {
+        let bounds = self.layer_panel_bounds(window_width, window_height);
+        let Some(panel) = &mut self.layer_panel else { return };
+        let Some(focused) = self.focused_editor else { return };
+        let Some(editor) = self.editors.get_mut(focused as usize) else { return };
+        match panel.draw(d, &mut self.ui, bounds, &self.theme, editor) {
+            Some(LayerPanelAction::Close) => self.layer_panel = None,
+            Some(LayerPanelAction::Select(path)) => editor.set_selection(Selection::Layers(vec![path])),
+            Some(LayerPanelAction::Repair) => {
+                let count = editor.repair_layers();
+                eprintln!("repaired {count} broken layers");
+            }
+            None => (),
+        }
+    }
+
+    /// Get (calculate) library panel rectangle, docked to the right edge below the tab well
+    pub fn library_panel(&self, window_width: f32, window_height: f32) -> Rectangle {
+        let top = self.tab_well(window_width).height;
+        Rectangle::new(window_width - Engine::LIBRARY_PANEL_WIDTH, top, Engine::LIBRARY_PANEL_WIDTH, window_height - top)
+    }
+
+    /// Get (calculate) the library items in view within the library panel, scrolled by [`Engine::library_scroll`]
+    pub fn library_rows(&self, panel: Rectangle) -> ListRows {
+        let count = self.library.as_ref().map_or(0, |library| library.items.len());
+        ListRows::new(panel, Engine::LIBRARY_ROW_HEIGHT, count, self.library_scroll)
+    }
+
+    /// Get (calculate) the rectangle of a library item within its `row` of the library panel
+    ///
+    /// The thumbnail is drawn at the left of the rectangle, with the name beside it
+    pub fn library_item_rect(row: Rectangle) -> Rectangle {
+        Rectangle::new(
+            row.x + Engine::LIBRARY_PADDING,
+            row.y + Engine::LIBRARY_PADDING,
+            row.width - Engine::LIBRARY_PADDING * 2.0,
+            Library::THUMBNAIL_SIZE as f32,
+        )
+    }
+
+    /// Get (calculate) style panel rectangle, docked to the right edge below the tab well, beside the library panel if it is open
+    pub fn style_panel_bounds(&self, window_width: f32) -> Rectangle {
+        let top = self.tab_well(window_width).height;
+        let right = if self.is_library_open { window_width - Engine::LIBRARY_PANEL_WIDTH } else { window_width };
+        StylePanel::bounds(right, top)
+    }
+
+    /// Draw the style panel (if open) over the focused editor, applying edits to its current style
+    ///
+    /// Does nothing if the panel is closed or there is no focused editor
+    pub fn draw_style_panel(&mut self, d: &mut impl RaylibDraw, window_width: f32) {
+        let bounds = self.style_panel_bounds(window_width);
+        if let Some(panel) = &mut self.style_panel
+            && let Some(focused) = self.focused_editor
+            && let Some(editor) = self.editors.get_mut(focused as usize)
+        {
+            let can_break_link = matches!(editor.selection(), Selection::Layers(paths) if !paths.is_empty())
+                && matches!(editor.current_style, MaybeNew::Existing(_));
+            // the style as it was before the panel edited it, if it did
+            let mut edited = None;
+            let action = match &mut editor.current_style {
+                MaybeNew::New(style) => panel.draw(d, bounds, style, None, &editor.document.swatches, &self.width_presets.presets, can_break_link),
+                MaybeNew::Existing(weak_style) => match weak_style.upgrade() {
+                    Some(style) => {
+                        let users = editor.document.style_users(weak_style);
+                        let style_lock = style.lock();
+                        let mut style_borrow = style_lock.borrow_mut();
+                        let before = style_borrow.clone();
+                        let before_record = StyleRecord::new(&style_borrow);
+                        let action = panel.draw(d, bounds, &mut style_borrow, Some((&*weak_style, users)), &editor.document.swatches, &self.width_presets.presets, can_break_link);
+                        if StyleRecord::new(&style_borrow) != before_record || !StyleRecord::keeps_resources(&before, &style_borrow) {
+                            edited = Some((weak_style.clone(), before));
+                        }
+                        action
+                    }
+                    None => None,
+                },
+            };
+            if let Some((style, before)) = edited {
+                editor.record_style(&style, &before);
+            }
+            match action {
+                Some(StylePanelAction::Close) => self.style_panel = None,
+                Some(StylePanelAction::BreakLink) => editor.break_style_link(),
+                Some(StylePanelAction::MakeDefault) => editor.set_default_style(),
+                Some(StylePanelAction::AddSwatch(color)) => editor.add_swatch(Swatch::from_rgb(color)),
+                Some(StylePanelAction::ApplyWidthPreset(index)) => if let Err(e) = editor.apply_width_preset(&self.width_presets, index) {
+                    eprintln!("failed to apply width preset: {e}");
+                },
+                Some(StylePanelAction::SaveWidthPreset) => if let Some(profile) = editor.current_width_profile() {
+                    let name = self.width_presets.next_name();
+                    if let Err(e) = self.width_presets.add(name, &profile) {
+                        eprintln!("failed to save width preset: {e}");
+                    }
+                },
+                None => (),
+            }
+        }
+    }
+
+    /// Get (calculate) width panel rectangle, docked to the right edge below the style panel, or below the tab well if the style panel is closed
+    pub fn width_panel_bounds(&self, window_width: f32) -> Rectangle {
+        let top = match self.style_panel {
+            Some(_) => self.style_panel_bounds(window_width).y + StylePanel::HEIGHT,
+            None => self.tab_well(window_width).height,
+        };
+        let right = if self.is_library_open { window_width - Engine::LIBRARY_PANEL_WIDTH } else { window_width };
+        WidthPanel::bounds(right, top)
+    }
+
+    /// Draw the width panel (if open) over the focused editor, applying edits to the width profile of its current style
+    ///
+    /// Does nothing if the panel is closed or there is no focused editor
+    pub fn draw_width_panel(&mut self, d: &mut impl RaylibDraw, window_width: f32) {
+        let bounds = self.width_panel_bounds(window_width);
+        if let Some(panel) = &mut self.width_panel
+            && let Some(focused) = self.focused_editor
+            && let Some(editor) = self.editors.get_mut(focused as usize)
+        {
+            let length = editor.selected_path_length() as f32;
+            let action = match editor.current_stroke_width().and_then(|weak| weak.upgrade()) {
+                Some(profile) => {
+                    let profile_lock = profile.lock();
+                    let mut profile_borrow = profile_lock.borrow_mut();
+                    panel.draw(d, &mut self.ui, bounds, Some(&mut profile_borrow), length)
+                }
+                None => panel.draw(d, &mut self.ui, bounds, None, length),
+            };
+            match action {
+                Some(WidthPanelAction::Close) => self.width_panel = None,
+                Some(WidthPanelAction::Edit) => editor.record_width_profile(),
+                None => (),
+            }
+        }
+    }
+
+    /// Get (calculate) artboards panel rectangle, docked to the right edge below the width panel, or whichever panels above it are open
+    pub fn artboard_panel_bounds(&self, window_width: f32) -> Rectangle {
+        let top = if self.width_panel.is_some() {
+            self.width_panel_bounds(window_width).y + WidthPanel::HEIGHT
+        } else if self.style_panel.is_some() {
+            self.style_panel_bounds(window_width).y + StylePanel::HEIGHT
+        } else {
+            self.tab_well(window_width).height
+        };
+        let right = if self.is_library_open { window_width - Engine::LIBRARY_PANEL_WIDTH } else { window_width };
+        ArtboardPanel::bounds(right, top)
+    }
+
+    /// Draw the artboards panel (if open) over the focused editor, applying edits to its selected artboards
+    ///
+    /// Does nothing if the panel is closed or there is no focused editor
+    pub fn draw_artboard_panel(&mut self, d: &mut impl RaylibDraw, window_width: f32) {
+        let bounds = self.artboard_panel_bounds(window_width);
+        let Some(panel) = &mut self.artboard_panel else { return };
+        let Some(focused) = self.focused_editor else { return };
+        let Some(editor) = self.editors.get_mut(focused as usize) else { return };
+        let action = panel.draw(d, &mut self.ui, bounds, &editor.document, &mut editor.selected_artboards);
+        let selected = editor.selected_artboards.as_slice();
+        match action {
+            Some(ArtboardPanelAction::Close) => self.artboard_panel = None,
+            Some(ArtboardPanelAction::Resize { width, height }) => {
+                editor.document.resize_artboards(selected, width, height);
+                editor.record_artboards();
+            }
+            Some(ArtboardPanelAction::Align(alignment)) => {
+                editor.document.align_artboards(selected, alignment);
+                editor.record_artboards();
+            }
+            Some(ArtboardPanelAction::Distribute { is_vertical }) => {
+                editor.document.distribute_artboards(selected, is_vertical);
+                editor.record_artboards();
+            }
+            Some(ArtboardPanelAction::Export) => {
+                let names = selected.iter()
+                    .filter_map(|&index| editor.document.artboards.get(index))
+                    .map(|artboard| artboard.name.clone())
+                    .collect::<Vec<_>>();
+                let mut dialog = ExportDialog::new(focused, &editor.document);
+                dialog.set_artboards(&names);
+                self.export_dialog = Some(dialog);
+            }
+            None => (),
+        }
+    }
+
+    /// Save the topmost layer of the focused editor's document to the library as a snippet
+    ///
+    /// Does nothing if there is no library, no focused editor, or the document has no layers
+    pub fn save_library_snippet(&mut self, rl: &mut RaylibHandle, thread: &RaylibThread) -> Result<(), String> {
+        if let Some(library) = &mut self.library
+            && let Some(focused) = self.focused_editor
+            && let Some(editor) = self.editors.get(focused as usize)
+            && let Some(layer) = editor.document.layers.last()
+        {
+            library.save_snippet(rl, thread, &editor.document, layer)?;
+        }
+        Ok(())
+    }
+
+    /// Place the library item at `index` into the focused editor's document, at the screenspace `position`
+    ///
+    /// Does nothing if there is no library or no focused editor
+    pub fn place_library_item(&mut self, rl: &mut RaylibHandle, thread: &RaylibThread, index: usize, position: Vector2) -> Result<(), String> {
+        if let Some(library) = &self.library
+            && let Some(focused) = self.focused_editor
+            && let Some(editor) = self.editors.get_mut(focused as usize)
+        {
+            let world_position = rl.get_screen_to_world2D(position, editor.camera);
+            let style = editor.style_for_new_object();
+            let start = editor.document.layers.len();
+            library.place(rl, thread, index, &mut editor.document, style, world_position)?;
+            editor.record_added_layers(start);
+        }
+        Ok(())
+    }
+
+    /// Run `steps` against the focused editor without a window, one simulated frame each, and get the resulting document
+    ///
+    /// Actions and tools that need a GPU context or a dialog, like exporting or the raster brush, are errors.
+    /// Preferences changed by the script aren't saved
+    #[cfg_attr(not(test), allow(dead_code, reason = "scripted input for integration tests, which the application doesn't run"))]
+    pub fn run_headless(&mut self, steps: impl IntoIterator<Item = HeadlessStep>) -> Result<&Document, String> {
+        /// The simulated time between steps, in seconds
+        const FRAME_TIME: f32 = 1.0 / 60.0;
+
+        let mut time = 0.0;
+        let mut pointer = None::<Vector2>;
+        for step in steps {
+            time += f64::from(FRAME_TIME);
+            if let HeadlessStep::Open(path) = &step {
+                let FileOutcome::Opened(data) = FileJob::open(path)?.wait()? else {
+                    unreachable!("opening a native document should only ever read it");
+                };
+                let document = format::build_headless(data)?;
+                self.create_editor(Editor::new_default(document));
+                continue;
+            }
+
+            let brushes = self.preferences.brushes.clone();
+            let guide_tolerance = self.preferences.snapping.is_using_smart_guides.then_some(guides::SNAP_DISTANCE);
+            let magic_wand = self.preferences.magic_wand;
+            let bucket = self.preferences.bucket;
+            let focused = self.focused_editor.ok_or("no document is open")?;
+            let editor = self.editors.get_mut(focused as usize).ok_or("no document is open")?;
+            let settings = ToolSettings {
+                brushes: &brushes,
+                magic_wand,
+                bucket,
+                guide_tolerance: guide_tolerance.map(|tolerance| tolerance / editor.camera.zoom),
+            };
+            // scripts have no keyboard to hold modifiers with
+            let modifiers = ToolModifiers::default();
+            match step {
+                HeadlessStep::Open(_) => unreachable!("handled above"),
+
+                HeadlessStep::Save(path) => {
+                    editor.start_save(&path, self.preferences.is_compressing_packages)?;
+                    editor.wait_for_save().unwrap_or(Ok(()))?;
+                }
+
+                HeadlessStep::Action(action) => {
+                    if let Some(tool) = Tool::from_action(action) {
+                        editor.set_tool(tool, &brushes);
+                        self.toolbar.show_tool(tool);
+                        continue;
+                    }
+                    if !editor.run_action(action) {
+                        if action == EditorAction::CycleArtboardBackground {
+                            let position = pointer.ok_or("the artboard background is cycled under the mouse, which hasn't been pressed yet")?;
+                            editor.cycle_artboard_background_at(position);
+                        } else if !self.run_preference_action(action) {
+                            return Err(format!("{action:?} can't run headless"));
+                        }
+                    }
+                }
+
+                HeadlessStep::Press(position) => {
+                    pointer = Some(position);
+                    editor.activate_artboard_at(position);
+                    let tool = editor.current_tool;
+                    if tool == Tool::RasterBrush {
+                        return Err("the raster brush can't paint without a GPU context".to_owned());
+                    }
+                    tool.press(editor, position, time, modifiers, &settings, |editor, position, is_deep| {
+                        editor.select_layer_at(position, is_deep);
+                    });
+                }
+
+                HeadlessStep::Drag(position) => {
+                    pointer = Some(position);
+                    editor.current_tool.drag(editor, position, modifiers, &settings);
+                }
+
+                HeadlessStep::Hold(seconds) => editor.current_tool.hold(editor, seconds, &brushes),
+                HeadlessStep::SelectMatching(filter) => editor.select_matching(&filter),
+                HeadlessStep::Release => editor.current_tool.release(editor, modifiers, &brushes),
+                HeadlessStep::Escape => editor.current_tool.cancel(editor),
+                HeadlessStep::Enter => editor.current_tool.commit(editor, &brushes),
+            }
+
+            if let Some(editor) = self.focused_editor_mut() {
+                editor.poll_journal();
+                editor.journal_edits();
+                editor.document.route_connectors();
+                if let Some(playback) = &mut editor.playback {
+                    playback.tick(FRAME_TIME, editor.document.animation.fps, editor.document.artboards.len() as u32);
+                }
+            }
+        }
+        self.focused_editor().map(|editor| &editor.document).ok_or_else(|| "no document is open".to_owned())
+    }
+}
+
+/// One input of a script run by [`Engine::run_headless`]
+///
+/// Positions are in worldspace, since there is no window for screenspace to be relative to
+#[cfg_attr(not(test), allow(dead_code, reason = "only constructed by tests"))]
+#[derive(Debug, Clone, PartialEq)]
+pub enum HeadlessStep {
+    /// Run an action as if it were chosen from the command palette
+    Action(EditorAction),
+
+    /// Press the left mouse button at a position
+    Press(Vector2),
+
+    /// Move the mouse to a position with the left button held
+    Drag(Vector2),
+
+    /// Keep the left mouse button held where it is for a number of seconds
+    Hold(f32),
+
+    /// Let go of the left mouse button where it is
+    Release,
+
+    /// Press escape, canceling whatever the current tool is in the middle of
+    Escape,
+
+    /// Press enter, committing whatever the current tool is in the middle of
+    Enter,
+
+    /// Select every layer whose style matches a filter, as a script's search would
+    SelectMatching(LayerFilter),
+
+    /// Save the focused document to a path in the native format, waiting for it to be written
+    Save(PathBuf),
+
+    /// Open a native document from a path in a new editor, focusing it
+    Open(PathBuf),
+}
+
+impl From<EditorAction> for HeadlessStep {
+    #[inline]
+    fn from(action: EditorAction) -> Self {
+        Self::Action(action)
+    }
 }
 
 pub enum EngineTabData<'a> {
@@ -220,10 +1496,17 @@ pub enum EngineTabData<'a> {
         editor: &'a Editor,
 
         /// The bounding rectangle for the close button
-        close_button_rect: Rectangle,
+        ///
+        /// [`None`] if the editor is pinned, since pinned tabs can't be closed
+        close_button_rect: Option<Rectangle>,
+    },
+    /// The "new document" split button
+    New {
+        /// The bounding rectangle for the arrow that opens the template menu
+        ///
+        /// The rest of the tab creates a document from the default preset
+        menu_button_rect: Rectangle,
     },
-    /// The "new document" tab
-    New,
     /// The "open document" tab
     Open,
 }
@@ -239,7 +1522,6 @@ pub struct EngineTab<'a> {
 enum EngineTabIterData {
     Editor {
         index: u32,
-        close_button_rect: Rectangle,
     },
     New,
     Open,
@@ -265,12 +1547,6 @@ impl<'a> EngineTabIter<'a> {
             ),
             data: EngineTabIterData::Editor {
                 index: 0,
-                close_button_rect: Rectangle::new(
-                    -Engine::TAB_PADDING_H - font_size as f32,
-                    Engine::TAB_PADDING_V,
-                    font_size as f32,
-                    font_size as f32,
-                ),
             }
         }
     }
@@ -281,39 +1557,61 @@ impl<'a> Iterator for EngineTabIter<'a> {
 
     fn next(&mut self) -> Option<Self::Item> {
         if let Some(editor) = self.iter.next() {
-            let EngineTabIterData::Editor { index, close_button_rect } = &mut self.data else { panic!("every tabs at the start should be an editor tab") };
-            let tab_name = editor.document.title.as_str();
-            let name_width = unsafe { MeasureText(CString::from_str(tab_name).unwrap().as_ptr(), self.font_size) } as f32;
-            let tab_width = name_width + Engine::TAB_PADDING_H * 4.0 + self.font_size as f32;
-            self.rect.width = tab_width.min(Engine::TAB_MAX_WIDTH);
-            close_button_rect.x += self.rect.width;
-            let (idx, rect, close_rec) = (*index, self.rect, *close_button_rect);
+            let EngineTabIterData::Editor { index } = &mut self.data else { panic!("every tabs at the start should be an editor tab") };
+            let button_size = self.font_size as f32;
+            self.rect.width = if editor.is_pinned {
+                // just wide enough for the first letter of the name
+                button_size + Engine::TAB_PADDING_H * 2.0
+            } else {
+                let tab_name = editor.document.title.as_str();
+                let name_width = unsafe { MeasureText(CString::from_str(tab_name).unwrap().as_ptr(), self.font_size) } as f32;
+                let tab_width = name_width + Engine::TAB_PADDING_H * 4.0 + button_size;
+                tab_width.min(Engine::TAB_MAX_WIDTH)
+            };
+            let close_button_rect = (!editor.is_pinned).then(|| Rectangle::new(
+                self.rect.x + self.rect.width - Engine::TAB_PADDING_H - button_size,
+                Engine::TAB_PADDING_V,
+                button_size,
+                button_size,
+            ));
+            let (idx, rect) = (*index, self.rect);
             *index += 1;
             self.rect.x += self.rect.width + 1.0;
-            close_button_rect.x += 1.0;
             Some(EngineTab {
                 rect,
                 data: EngineTabData::Editor {
                     index: idx,
                     editor,
-                    close_button_rect: close_rec,
+                    close_button_rect,
                 },
             })
         } else {
             match self.data {
-                EngineTabIterData::Editor { .. } | EngineTabIterData::New => {
-                    let data;
-                    (self.data, data) = match self.data {
-                        EngineTabIterData::Editor { .. } => (EngineTabIterData::New, EngineTabData::New),
-                        EngineTabIterData::New => (EngineTabIterData::Open, EngineTabData::Open),
-                        EngineTabIterData::Open => unreachable!(),
-                    };
+                EngineTabIterData::Editor { .. } => {
+                    self.data = EngineTabIterData::New;
+                    self.rect.width = self.rect.height + Engine::TAB_MENU_BUTTON_WIDTH;
+                    let rect = self.rect;
+                    self.rect.x += self.rect.width + 1.0;
+                    Some(EngineTab {
+                        rect,
+                        data: EngineTabData::New {
+                            menu_button_rect: Rectangle {
+                                x: rect.x + rect.width - Engine::TAB_MENU_BUTTON_WIDTH,
+                                width: Engine::TAB_MENU_BUTTON_WIDTH,
+                                ..rect
+                            },
+                        },
+                    })
+                }
+
+                EngineTabIterData::New => {
+                    self.data = EngineTabIterData::Open;
                     self.rect.width = self.rect.height;
                     let rect = self.rect;
                     self.rect.x += self.rect.width + 1.0;
                     Some(EngineTab {
                         rect,
-                        data,
+                        data: EngineTabData::Open,
                     })
                 }
 
@@ -324,3 +1622,295 @@ impl<'a> Iterator for EngineTabIter<'a> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_headless() {
+        let mut engine = Engine::new(EngineTheme::default());
+        let mut editor = Editor::new_default(Document::new("headless".to_owned()));
+        editor.document.artboards.push(Artboard::new("artboard 1".to_owned(), Rectangle::new(0.0, 0.0, 200.0, 200.0)));
+        engine.create_editor(editor);
+
+        // draw a stroke, then drag it 20 pixels right
+        let mut steps = vec![EditorAction::VectorBrush.into(), HeadlessStep::Press(Vector2::new(20.0, 100.0))];
+        steps.extend((1..=20).map(|i| HeadlessStep::Drag(Vector2::new(20.0 + i as f32 * 5.0, 100.0))));
+        steps.extend([
+            HeadlessStep::Release,
+            EditorAction::Select.into(),
+            HeadlessStep::Press(Vector2::new(70.0, 100.0)),
+            HeadlessStep::Drag(Vector2::new(90.0, 100.0)),
+            HeadlessStep::Release,
+        ]);
+        let document = engine.run_headless(steps).unwrap();
+        assert_eq!(document.layers.len(), 1);
+        let bounds = document.layers[0].control_bounds().unwrap();
+        assert!((bounds.x - 40.0).abs() < 2.0, "stroke should have moved, but starts at {}", bounds.x);
+
+        // the selection measurements follow the drag
+        let info = engine.focused_editor().unwrap().selection_info();
+        assert_eq!(info.layers, [[0]]);
+        let cached = info.bounds.unwrap();
+        assert!([cached.x - bounds.x, cached.y - bounds.y, cached.width - bounds.width, cached.height - bounds.height].iter().all(|d| d.abs() < 1e-3));
+        assert!(info.anchor_count >= 2);
+
+        // tabbing wraps around the only layer
+        let document = engine.run_headless([EditorAction::SelectNextLayer.into()]).unwrap();
+        assert_eq!(document.layers.len(), 1);
+        let editor = engine.focused_editor_mut().unwrap();
+        assert!(matches!(editor.selection(), Selection::Layers(paths) if paths == &[[0]]));
+        editor.translate_selection(Vector2::new(-0.5, 0.0));
+        editor.translate_selection(Vector2::new(0.5, 0.0));
+        assert!((editor.selection_info().bounds.unwrap().x - bounds.x).abs() < 1e-3);
+
+        // the stroke survives a round trip through the native format
+        let path = std::env::temp_dir().join(format!("headless_{}.json", std::process::id()));
+        let document = engine.run_headless([HeadlessStep::Save(path.clone()), HeadlessStep::Open(path.clone())]).unwrap();
+        assert_eq!(document.layers.len(), 1);
+        assert_eq!(document.layers[0].control_bounds(), Some(bounds));
+        assert_eq!(engine.editors().len(), 2);
+        let _ = std::fs::remove_file(&path);
+
+        // and through a compressed package
+        let path = std::env::temp_dir().join(format!("headless_{}.{}", std::process::id(), format::PACKAGE_EXTENSION));
+        let document = engine.run_headless([HeadlessStep::Save(path.clone()), HeadlessStep::Open(path.clone())]).unwrap();
+        assert_eq!(document.layers[0].control_bounds(), Some(bounds));
+        assert!(std::fs::read(&path).unwrap().starts_with(b"PK"));
+        let _ = std::fs::remove_file(&path);
+
+        assert!(engine.run_headless([EditorAction::Export.into()]).is_err());
+    }
+
+    #[test]
+    fn test_autosave() {
+        let path = std::env::temp_dir().join(format!("autosave_{}.json", std::process::id()));
+        let autosave_path = format::autosave_path(&path);
+        let mut editor = Editor::new_default(Document::new("autosave".to_owned()));
+        editor.start_save(&path, false).unwrap();
+        editor.wait_for_save().unwrap().unwrap();
+        let saved = std::fs::read(&path).unwrap();
+
+        // autosaving leaves the document file and its unsaved changes alone
+        editor.add_curve_layer("line".to_owned(), crate::make_curve!((0,0) -> (100,0)));
+        assert!(editor.is_dirty);
+        editor.start_autosave(false).unwrap();
+        editor.autosave_job.as_mut().unwrap().wait().unwrap();
+        editor.autosave_job = None;
+        assert!(autosave_path.exists());
+        assert_eq!(std::fs::read(&path).unwrap(), saved);
+        assert!(editor.is_dirty);
+        assert_eq!(editor.document.file_path.as_ref(), Some(&path));
+
+        // saving makes the autosave redundant
+        editor.start_save(&path, false).unwrap();
+        editor.wait_for_save().unwrap().unwrap();
+        assert!(!autosave_path.exists());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_save_while_saving() {
+        let path = std::env::temp_dir().join(format!("save_queue_{}.json", std::process::id()));
+        let other_path = std::env::temp_dir().join(format!("save_queue_other_{}.json", std::process::id()));
+        let mut editor = Editor::new_default(Document::new("queue".to_owned()));
+        editor.add_curve_layer("line".to_owned(), crate::make_curve!((0,0) -> (100,0)));
+        editor.document.file_path = Some(path.clone());
+        editor.journal = Some(Journal::new(&path));
+        editor.record_info();
+
+        // an edit made while saving stays journaled, and a second save waits for the first
+        editor.start_save(&path, false).unwrap();
+        editor.record_info();
+        editor.start_save(&other_path, false).unwrap();
+        assert_eq!(editor.queued_save, Some((other_path.clone(), false)));
+        let journal = Journal::new(&path);
+        editor.wait_for_save().unwrap().unwrap();
+        assert!(editor.save_job.is_none() && editor.queued_save.is_none());
+        assert!(path.exists() && other_path.exists());
+        assert_eq!(editor.document.file_path.as_ref(), Some(&other_path));
+        // the queued save went somewhere else, so the first file's journal was cleared along with the edit it held
+        assert!(!journal.has_entries());
+
+        // saving in place drops exactly the entries the file contains
+        editor.record_info();
+        editor.start_save(&other_path, false).unwrap();
+        editor.record_info();
+        editor.wait_for_save().unwrap().unwrap();
+        assert!(Journal::new(&other_path).has_entries());
+        editor.start_save(&other_path, false).unwrap();
+        editor.wait_for_save().unwrap().unwrap();
+        assert!(!Journal::new(&other_path).has_entries());
+
+        for path in [path, other_path] {
+            let _ = std::fs::remove_file(&path);
+        }
+    }
+
+    #[test]
+    fn test_round_rectangle_corners() {
+        let mut engine = Engine::new(EngineTheme::default());
+        engine.create_editor(Editor::new_default(Document::new("headless".to_owned())));
+
+        // draw a rectangle, then drag its top-left corner widget 10 pixels in
+        let steps = [
+            EditorAction::Rectangle.into(),
+            HeadlessStep::Press(Vector2::new(0.0, 0.0)),
+            HeadlessStep::Drag(Vector2::new(100.0, 60.0)),
+            HeadlessStep::Release,
+            EditorAction::Select.into(),
+            HeadlessStep::Press(Vector2::new(12.0, 12.0)),
+            HeadlessStep::Drag(Vector2::new(22.0, 22.0)),
+            HeadlessStep::Release,
+        ];
+        let document = engine.run_headless(steps).unwrap();
+        let layer::LayerContent::Rectangle(rectangle) = &document.layers[0].content else { panic!("rectangle tool should draw a rectangle") };
+        assert_eq!(rectangle.rect, Rectangle::new(0.0, 0.0, 100.0, 60.0));
+        assert_eq!(rectangle.radii, [22.0; 4]);
+        assert_eq!(document.layers[0].control_bounds(), Some(rectangle.rect));
+    }
+
+    #[test]
+    fn test_switch_tool_mid_stroke() {
+        let mut engine = Engine::new(EngineTheme::default());
+        engine.create_editor(Editor::new_default(Document::new("headless".to_owned())));
+
+        // switching tools before letting go keeps the stroke and the rectangle drawn so far
+        let steps = [
+            EditorAction::VectorBrush.into(),
+            HeadlessStep::Press(Vector2::new(0.0, 0.0)),
+            HeadlessStep::Drag(Vector2::new(50.0, 0.0)),
+            HeadlessStep::Drag(Vector2::new(100.0, 0.0)),
+            EditorAction::Rectangle.into(),
+            HeadlessStep::Press(Vector2::new(0.0, 20.0)),
+            HeadlessStep::Drag(Vector2::new(40.0, 50.0)),
+            EditorAction::Lasso.into(),
+            HeadlessStep::Release,
+        ];
+        let document = engine.run_headless(steps).unwrap();
+        assert_eq!(document.layers.len(), 2);
+        assert!(matches!(document.layers[0].content, layer::LayerContent::Curve(_)));
+        let layer::LayerContent::Rectangle(rectangle) = &document.layers[1].content else { panic!("rectangle should be committed") };
+        assert_eq!(rectangle.rect, Rectangle::new(0.0, 20.0, 40.0, 30.0));
+    }
+
+    #[test]
+    fn test_escape_and_enter() {
+        let mut engine = Engine::new(EngineTheme::default());
+        engine.create_editor(Editor::new_default(Document::new("headless".to_owned())));
+
+        let steps = [
+            // canceled strokes draw nothing, even once the mouse is let go
+            EditorAction::VectorBrush.into(),
+            HeadlessStep::Press(Vector2::new(0.0, 0.0)),
+            HeadlessStep::Drag(Vector2::new(100.0, 0.0)),
+            HeadlessStep::Escape,
+            HeadlessStep::Release,
+            // committed rectangles end where the mouse was when enter was pressed
+            EditorAction::Rectangle.into(),
+            HeadlessStep::Press(Vector2::new(0.0, 0.0)),
+            HeadlessStep::Drag(Vector2::new(50.0, 50.0)),
+            HeadlessStep::Enter,
+            HeadlessStep::Drag(Vector2::new(80.0, 80.0)),
+            HeadlessStep::Release,
+            // canceled moves put the layer back
+            EditorAction::Select.into(),
+            HeadlessStep::Press(Vector2::new(0.0, 25.0)),
+            HeadlessStep::Drag(Vector2::new(100.0, 25.0)),
+            HeadlessStep::Escape,
+            HeadlessStep::Release,
+        ];
+        let document = engine.run_headless(steps).unwrap();
+        assert_eq!(document.layers.len(), 1);
+        let layer::LayerContent::Rectangle(rectangle) = &document.layers[0].content else { panic!("rectangle should be committed") };
+        assert_eq!(rectangle.rect, Rectangle::new(0.0, 0.0, 50.0, 50.0));
+    }
+
+    #[test]
+    fn test_named_views() {
+        let mut engine = Engine::new(EngineTheme::default());
+        engine.create_editor(Editor::new_default(Document::new("views".to_owned())));
+        let center = Vector2::new(400.0, 300.0);
+        let editor = engine.focused_editor_mut().unwrap();
+        editor.camera = Camera2D { offset: center, target: Vector2::new(50.0, 80.0), rotation: 0.0, zoom: 2.0 };
+        editor.save_view(center);
+        editor.zoom_about(Vector2::new(100.0, 100.0), 4.0);
+        editor.save_view(center);
+        assert_eq!(editor.document.views[0].center, Vector2::new(50.0, 80.0));
+
+        // cycling wraps around, and puts the saved center back in the middle of the viewport
+        editor.cycle_view(false, center);
+        assert_eq!(editor.current_view, Some(0));
+        assert_eq!(editor.camera.zoom, 2.0);
+        assert_eq!(editor.camera.offset, center);
+        editor.cycle_view(true, center);
+        assert_eq!(editor.current_view, Some(1));
+
+        // views are saved with the document
+        let path = std::env::temp_dir().join(format!("views_{}.json", std::process::id()));
+        let document = engine.run_headless([HeadlessStep::Save(path.clone()), HeadlessStep::Open(path.clone())]).unwrap();
+        assert_eq!(document.views.iter().map(|view| view.name.as_str()).collect::<Vec<_>>(), ["View 1", "View 2"]);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_template_layers() {
+        let mut engine = Engine::new(EngineTheme::default());
+        let mut editor = Editor::new_default(Document::new("templates".to_owned()));
+        editor.add_curve_layer("sketch".to_owned(), crate::make_curve!((0,0) -> (100,0) -> (100,100) -> cycle));
+        editor.add_curve_layer("ink".to_owned(), crate::make_curve!((200,0) -> (300,0)));
+        editor.document.layers[0].export = Some(export::AssetExport::default());
+        engine.create_editor(editor);
+        let editor = engine.focused_editor_mut().unwrap();
+        editor.set_selection(Selection::Layers(vec![vec![0]]));
+        editor.make_template();
+
+        // templates are locked
+        editor.select_layer_at(Vector2::new(50.0, 0.0), false);
+        assert!(matches!(editor.selection(), Selection::Layers(paths) if paths.is_empty()));
+        let curves = editor.document.layers[0].curves();
+        editor.set_selection(Selection::Paths(curves));
+        assert!(matches!(editor.selection(), Selection::Paths(curves) if curves.is_empty()));
+        assert!(export::asset_layers(&editor.document.layers).is_empty(), "templates should never be exported");
+
+        // the flag is saved with the document
+        let path = std::env::temp_dir().join(format!("templates_{}.json", std::process::id()));
+        let document = engine.run_headless([HeadlessStep::Save(path.clone()), HeadlessStep::Open(path.clone())]).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert!(document.layers[0].is_template && !document.layers[1].is_template);
+
+        let editor = engine.focused_editor_mut().unwrap();
+        editor.document.layers[0].is_template = false;
+        editor.document.layers[1].is_template = true;
+        editor.release_templates();
+        assert!(matches!(editor.selection(), Selection::Layers(paths) if paths == &[[1]]));
+        assert!(!editor.document.layers[1].is_template);
+    }
+
+    #[test]
+    fn test_shape_recognition() {
+        let mut engine = Engine::new(EngineTheme::default());
+        engine.create_editor(Editor::new_default(Document::new("shapes".to_owned())));
+        engine.preferences.brushes.is_recognizing_shapes = true;
+
+        // a slightly crooked box, held at the end
+        let corners = [Vector2::new(0.0, 0.0), Vector2::new(100.0, 2.0), Vector2::new(100.0, 50.0), Vector2::new(0.0, 50.0), Vector2::new(0.0, 2.0)];
+        let mut steps = vec![EditorAction::VectorBrush.into(), HeadlessStep::Press(corners[0])];
+        for (&a, &b) in corners.iter().zip(&corners[1..]) {
+            steps.extend((1..=10).map(|i| HeadlessStep::Drag(a.lerp(b, i as f32 / 10.0))));
+        }
+        steps.extend([HeadlessStep::Hold(crate::recognize::HOLD_DURATION), HeadlessStep::Release]);
+        let document = engine.run_headless(steps).unwrap();
+        assert_eq!(document.layers[0].name, "Rectangle");
+        let bounds = document.layers[0].control_bounds().unwrap();
+        assert!(bounds.x.abs() < 1.0 && bounds.y.abs() < 1.0 && (bounds.width - 100.0).abs() < 1.0 && (bounds.height - 50.0).abs() < 1.0);
+
+        // moving on after the hold goes back to freehand
+        let mut steps = vec![HeadlessStep::Press(Vector2::new(0.0, 100.0)), HeadlessStep::Drag(Vector2::new(100.0, 100.0)), HeadlessStep::Hold(crate::recognize::HOLD_DURATION)];
+        steps.extend((1..=10).map(|i| HeadlessStep::Drag(Vector2::new(100.0 + i as f32 * 5.0, 100.0 + i as f32 * 5.0))));
+        steps.push(HeadlessStep::Release);
+        let document = engine.run_headless(steps).unwrap();
+        assert_eq!(document.layers[1].name, "Brush stroke");
+    }
+}
@@ -0,0 +1,842 @@
+use std::{path::{Path, PathBuf}, sync::{atomic::{AtomicBool, AtomicUsize, Ordering}, mpsc, Arc}, thread::JoinHandle};
+use raylib::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::{animation, document::{self, Artboard, Document}, gif::{self, GifFrame}, layer::{Layer, LayerContent, SampleQuality}, ora::{self, OraLayer}, pdf, raster::{self, SendImage}, snapshot::DocumentSnapshot, svg, swatch::Swatch};
+
+/// A file format artboards can be exported as
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ImageFormat {
+    #[default]
+    Png,
+    Qoi,
+    Bmp,
+    Tga,
+
+    /// Layered OpenRaster, with each top-level layer as its own raster layer
+    OpenRaster,
+
+    /// Scalable Vector Graphics, written by [`svg::export`]
+    Svg,
+
+    /// A one-page PDF, written by [`pdf::export`]
+    Pdf,
+}
+
+impl ImageFormat {
+    /// Every format, in the order they are listed in the UI
+    pub const ALL: [Self; 7] = [Self::Png, Self::Qoi, Self::Bmp, Self::Tga, Self::OpenRaster, Self::Svg, Self::Pdf];
+
+    /// The file extension of the format, which is also how raylib picks the encoder of flat images
+    pub const fn extension(self) -> &'static str {
+        match self {
+            Self::Png => "png",
+            Self::Qoi => "qoi",
+            Self::Bmp => "bmp",
+            Self::Tga => "tga",
+            Self::OpenRaster => ora::EXTENSION,
+            Self::Svg => "svg",
+            Self::Pdf => "pdf",
+        }
+    }
+
+    /// Whether the format stores artwork as paths rather than pixels, so it is written without rendering and doesn't depend on the export scale
+    pub const fn is_vector(self) -> bool {
+        matches!(self, Self::Svg | Self::Pdf)
+    }
+
+    /// The vector file of `layers` within the worldspace rectangle `bounds`, with the keyword/text pairs `text` as metadata
+    ///
+    /// PDFs print colors matching the document's process and spot `swatches` with their inks. [`None`] for raster formats
+    fn write_vector<'a>(self, layers: impl IntoIterator<Item = &'a Layer>, bounds: Rectangle, swatches: &[Swatch], text: &[(&str, String)]) -> Option<Vec<u8>> {
+        match self {
+            Self::Svg => Some(svg::export(layers, bounds, Vector2::new(bounds.x, bounds.y), text).into_bytes()),
+            Self::Pdf => Some(pdf::export(layers, bounds, swatches, text)),
+            Self::Png | Self::Qoi | Self::Bmp | Self::Tga | Self::OpenRaster => None,
+        }
+    }
+}
+
+/// `name` made safe to use as a single file name within an export folder
+///
+/// Path separators, drive prefixes, and other characters file systems reserve become `_`, and a name made of nothing but dots
+/// (like `..`) becomes `_`, so joining the result to a folder can never point outside of it
+pub fn clean_file_name(name: &str) -> String {
+    let cleaned = name.chars()
+        .map(|c| if matches!(c, '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|') || c.is_control() { '_' } else { c })
+        .collect::<String>();
+    let cleaned = cleaned.trim();
+    if cleaned.chars().all(|c| c == '.') { "_".to_owned() } else { cleaned.to_owned() }
+}
+
+/// A path in `directory` for a new file named after `name` with `extension`, which doesn't replace any existing file
+///
+/// `name` is [cleaned][`clean_file_name`] first, and numbered (`name 2`, `name 3`, and so on) if already taken
+pub fn unused_path(directory: &Path, name: &str, extension: &str) -> PathBuf {
+    let name = clean_file_name(name);
+    std::iter::once(format!("{name}.{extension}"))
+        .chain((2_u32..).map(|n| format!("{name} {n}.{extension}")))
+        .map(|file_name| directory.join(file_name))
+        .find(|path| !path.exists())
+        .expect("should find an unused name before running out of numbers")
+}
+
+/// A named export configuration, stored with the document so it can be repeated in one step
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExportPreset {
+    /// The display name of the preset
+    pub name: String,
+
+    /// The format each artboard is written as
+    pub format: ImageFormat,
+
+    /// Output pixels per worldspace pixel
+    ///
+    /// [Vector formats][`ImageFormat::is_vector`] ignore it
+    pub scale: f32,
+
+    /// The folder files are written to
+    ///
+    /// Relative paths are relative to the folder containing the document
+    pub directory: PathBuf,
+
+    /// The names of the artboards to export
+    ///
+    /// Empty to export every artboard
+    pub artboards: Vec<String>,
+
+    /// Whether exporting fails when artboards share a name, rather than letting their files overwrite each other
+    pub is_requiring_unique_names: bool,
+}
+
+impl Default for ExportPreset {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ExportPreset {
+    /// Smallest allowed [`ExportPreset::scale`]
+    pub const MIN_SCALE: f32 = 0.01;
+
+    /// Largest allowed [`ExportPreset::scale`]
+    pub const MAX_SCALE: f32 = 16.0;
+
+    /// Construct an unnamed preset exporting every artboard as PNG at 1x into an `export` folder beside the document
+    pub fn new() -> Self {
+        Self {
+            name: String::new(),
+            format: ImageFormat::Png,
+            scale: 1.0,
+            directory: PathBuf::from("export"),
+            artboards: Vec::new(),
+            is_requiring_unique_names: true,
+        }
+    }
+
+    /// The folder files are written to, with relative paths resolved against the folder containing `document`
+    pub fn resolve_directory(&self, document: &Document) -> PathBuf {
+        let base = document.file_path
+            .as_deref()
+            .and_then(Path::parent)
+            .unwrap_or(Path::new("."));
+        base.join(&self.directory)
+    }
+
+    /// The names of the artboards of `document` the preset exports, in document order
+    pub fn artboard_names(&self, document: &Document) -> Vec<String> {
+        document.artboards.iter()
+            .filter(|artboard| self.artboards.is_empty() || self.artboards.contains(&artboard.name))
+            .map(|artboard| artboard.name.clone())
+            .collect()
+    }
+
+    /// The [names of the artboards][`ExportPreset::artboard_names`] the preset exports, checked that there is something to export
+    ///
+    /// Fails if there are no artboards to export, or if any of them share a name while
+    /// [unique names are required][`ExportPreset::is_requiring_unique_names`]
+    pub fn validated_artboard_names(&self, document: &Document) -> Result<Vec<String>, String> {
+        let names = self.artboard_names(document);
+        if names.is_empty() {
+            return Err("no artboards to export".to_owned());
+        }
+        if self.is_requiring_unique_names {
+            let duplicates = document::duplicate_names(names.iter().map(String::as_str));
+            if !duplicates.is_empty() {
+                return Err(format!("artboard names must be unique, but these are repeated: {}", duplicates.join(", ")));
+            }
+        }
+        Ok(names)
+    }
+}
+
+/// How a layer marked as an asset is exported, cropped to its [visual bounds][`Layer::visual_bounds`] on a transparent background
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AssetExport {
+    /// The file name of the asset, without the scale suffix or extension
+    pub name: String,
+
+    /// The format the asset is written as
+    pub format: ImageFormat,
+
+    /// Output pixels per worldspace pixel; one file is written at each scale
+    pub scales: Vec<f32>,
+}
+
+impl Default for AssetExport {
+    #[inline]
+    fn default() -> Self {
+        Self::new(String::new())
+    }
+}
+
+impl AssetExport {
+    /// Construct an asset named `name`, exported as PNG at 1x
+    pub fn new(name: String) -> Self {
+        Self {
+            name,
+            format: ImageFormat::Png,
+            scales: vec![1.0],
+        }
+    }
+
+    /// The file name of the asset at `scale`: `<name>.<extension>` at 1x, and `<name>@<scale>x.<extension>` otherwise
+    ///
+    /// The name is [cleaned][`clean_file_name`] so the file always lands in the export folder
+    pub fn file_name(&self, scale: f32) -> String {
+        let name = clean_file_name(&self.name);
+        if scale == 1.0 {
+            format!("{name}.{}", self.format.extension())
+        } else {
+            format!("{name}@{scale}x.{}", self.format.extension())
+        }
+    }
+}
+
+/// Every layer of `layers` (and the groups among them) marked as an asset, in document order
+///
+/// [Templates][`Layer::is_template`] and everything inside them are never exported
+pub fn asset_layers(layers: &[Layer]) -> Vec<&Layer> {
+    let mut assets = Vec::new();
+    for layer in layers.iter().filter(|layer| !layer.is_template) {
+        if layer.export.is_some() {
+            assets.push(layer);
+        }
+        if let LayerContent::Group(group) = &layer.content {
+            assets.extend(asset_layers(&group.layers));
+        }
+    }
+    assets
+}
+
+/// Render `artboard` at `scale` output pixels per worldspace pixel to an upright image
+pub fn render_artboard(rl: &mut RaylibHandle, thread: &RaylibThread, document: &Document, artboard: &Artboard, scale: f32) -> Result<Image, String> {
+    render_layers(rl, thread, &document.layers, artboard.rect, artboard.background.export_color(document.paper_color), scale)
+}
+
+/// Render each top-level layer of `document` within `artboard` at `scale` output pixels per worldspace pixel
+/// to its own transparent image, along with the layer's name, listed bottom to top
+///
+/// Artboards with an opaque background get a solid "Background" layer beneath the rest.
+/// [Templates][`Layer::is_template`] are left out
+pub fn render_artboard_layers(rl: &mut RaylibHandle, thread: &RaylibThread, document: &Document, artboard: &Artboard, scale: f32) -> Result<Vec<(String, Image)>, String> {
+    let mut images = Vec::with_capacity(document.layers.len().saturating_add(1));
+    let background = artboard.background.export_color(document.paper_color);
+    if background.a > 0 {
+        let (width, height) = pixel_size(artboard.rect, scale);
+        images.push(("Background".to_owned(), Image::gen_image_color(width as i32, height as i32, background)));
+    }
+    for layer in document.layers.iter().filter(|layer| !layer.is_template) {
+        images.push((layer.name.clone(), render_layers(rl, thread, std::slice::from_ref(layer), artboard.rect, Color::BLANK, scale)?));
+    }
+    Ok(images)
+}
+
+/// The size in pixels of the worldspace rectangle `rect` rendered at `scale` output pixels per worldspace pixel
+fn pixel_size(rect: Rectangle, scale: f32) -> (u32, u32) {
+    let width = (rect.width * scale).round().max(1.0) as u32;
+    let height = (rect.height * scale).round().max(1.0) as u32;
+    (width, height)
+}
+
+/// Render `layers` within the worldspace rectangle `rect` at `scale` output pixels per worldspace pixel
+/// over `background` to an upright image
+pub fn render_layers<'a>(mut rl: &mut RaylibHandle, thread: &RaylibThread, layers: impl IntoIterator<Item = &'a Layer>, rect: Rectangle, background: Color, scale: f32) -> Result<Image, String> {
+    let (width, height) = pixel_size(rect, scale);
+    let layers = layers.into_iter().collect::<Vec<_>>();
+    let snapshot = DocumentSnapshot::of(layers.iter().copied());
+    let mut rtex = rl.load_render_texture(thread, width, height)?;
+    {
+        let mut d = rl.begin_texture_mode(thread, &mut rtex);
+        d.clear_background(background);
+        let mut d = d.begin_mode2D(Camera2D {
+            offset: Vector2::zero(),
+            target: Vector2::new(rect.x, rect.y),
+            rotation: 0.0,
+            zoom: scale,
+        });
+        for layer in layers {
+            layer.draw(&mut d, Color::WHITE, SampleQuality::High, &snapshot);
+        }
+    }
+    raster::image_from_render_texture(&rtex)
+}
+
+/// Encode `layers` (named and listed bottom to top) and their flattened `merged` image as an OpenRaster file at `dpi`
+fn encode_ora(layers: &[(String, SendImage)], merged: SendImage, dpi: f32) -> Result<Vec<u8>, String> {
+    let SendImage(mut merged) = merged;
+    let (width, height) = (merged.width().max(1), merged.height().max(1));
+    let layers = layers.iter()
+        .map(|(name, image)| Ok(OraLayer { name: name.clone(), png: raster::encode_png(&image.0)? }))
+        .collect::<Result<Vec<_>, String>>()?;
+    let merged_png = raster::encode_png(&merged)?;
+    // the thumbnail is only ever shrunk, never enlarged
+    let thumbnail_scale = (ora::THUMBNAIL_SIZE as f32 / width.max(height) as f32).min(1.0);
+    merged.resize(
+        ((width as f32 * thumbnail_scale).round() as i32).max(1),
+        ((height as f32 * thumbnail_scale).round() as i32).max(1),
+    );
+    let thumbnail_png = raster::encode_png(&merged)?;
+    Ok(ora::encode(width as u32, height as u32, dpi, &layers, &merged_png, &thumbnail_png))
+}
+
+/// Text identifying where an exported file of `artboard` came from, as PNG keyword/text pairs
+///
+/// SVG and PDF exports write the same pairs as their own metadata fields. Times are written as ISO 8601 UTC and tags are separated by commas.
+/// Fields of the document's [info][`crate::document::DocumentInfo`] that are blank are left out
+pub fn png_text(document: &Document, artboard: &str) -> Vec<(&'static str, String)> {
+    let iso_time = |seconds: Option<u64>| seconds.map_or_else(String::new, |seconds| {
+        let [year, month, day, hour, minute, second] = document::utc_date_time(seconds);
+        format!("{year:04}-{month:02}-{day:02}T{hour:02}:{minute:02}:{second:02}Z")
+    });
+    [
+        ("Title", document.title.clone()),
+        ("Author", document.info.author.clone()),
+        ("Description", document.info.description.clone()),
+        ("Keywords", document.info.tags.join(", ")),
+        ("Creation Time", iso_time(document.info.created)),
+        ("Modification Time", iso_time(document.info.modified)),
+        ("Software", concat!(env!("CARGO_PKG_NAME"), " ", env!("CARGO_PKG_VERSION")).to_owned()),
+        ("Artboard", artboard.to_owned()),
+    ]
+        .into_iter()
+        .filter(|(_, text)| !text.is_empty())
+        .collect()
+}
+
+/// The CRC-32 used by PNG chunks and zip archives
+pub fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in bytes {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { crc.wrapping_shr(1) ^ 0xEDB8_8320 } else { crc.wrapping_shr(1) };
+        }
+    }
+    !crc
+}
+
+/// Insert `text` as keyword/text chunks into the PNG file `png`, right after its header
+///
+/// Text that fits in Latin-1 is stored in `tEXt` chunks, and anything else in uncompressed UTF-8 `iTXt` chunks
+fn insert_png_text(png: &[u8], text: &[(&str, String)]) -> Result<Vec<u8>, String> {
+    const SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+    // the signature, then the IHDR chunk's length, type, 13 bytes of data, and CRC
+    const HEADER_END: usize = 33;
+    if !png.starts_with(SIGNATURE) || png.get(12..16) != Some(b"IHDR".as_slice()) || png.len() < HEADER_END {
+        return Err("not a PNG file".to_owned());
+    }
+    let mut out = Vec::with_capacity(png.len());
+    out.extend_from_slice(&png[..HEADER_END]);
+    for (keyword, text) in text {
+        let latin1 = text.chars().map(|c| u8::try_from(u32::from(c)).ok()).collect::<Option<Vec<_>>>();
+        let mut chunk = Vec::new();
+        match latin1 {
+            Some(latin1) => {
+                chunk.extend_from_slice(b"tEXt");
+                chunk.extend_from_slice(keyword.as_bytes());
+                chunk.push(0);
+                chunk.extend_from_slice(&latin1);
+            }
+            None => {
+                chunk.extend_from_slice(b"iTXt");
+                chunk.extend_from_slice(keyword.as_bytes());
+                // keyword terminator, no compression, and empty language and translated keyword
+                chunk.extend_from_slice(&[0, 0, 0, 0, 0]);
+                chunk.extend_from_slice(text.as_bytes());
+            }
+        }
+        // the length counts the data, not the chunk type before it
+        let length = u32::try_from(chunk.len().saturating_sub(4)).map_err(|_| "PNG text is too long")?;
+        out.extend_from_slice(&length.to_be_bytes());
+        out.extend_from_slice(&chunk);
+        out.extend_from_slice(&crc32(&chunk).to_be_bytes());
+    }
+    out.extend_from_slice(&png[HEADER_END..]);
+    Ok(out)
+}
+
+/// A file for the export thread to write
+enum ExportTask {
+    /// Encode an image in the format matching the extension of `path`
+    ///
+    /// `text` is embedded in the file afterwards; it must be empty unless the image is a PNG
+    Image { image: SendImage, path: PathBuf, text: Vec<(&'static str, String)> },
+
+    /// Encode named layers (listed bottom to top) and their flattened image as an OpenRaster file at `dpi`
+    Layered { layers: Vec<(String, SendImage)>, merged: SendImage, path: PathBuf, dpi: f32 },
+
+    /// Encode frames as a looping GIF, showing each for `delay` hundredths of a second
+    Gif { frames: Vec<GifFrame>, delay: u16, path: PathBuf },
+
+    /// Write a file as-is
+    File { contents: Vec<u8>, path: PathBuf },
+}
+
+/// What an [`ExportJob`] renders
+#[derive(Debug)]
+enum ExportOutput {
+    /// Every artboard as a numbered frame, followed by a manifest
+    Frames {
+        /// File names of the frames sent to the export thread so far
+        frames: Vec<String>,
+        format: ImageFormat,
+    },
+
+    /// Every artboard as a frame of one GIF, written once every frame has been rendered
+    Gif {
+        /// The frames rendered so far
+        frames: Vec<GifFrame>,
+        delay: u16,
+        file_name: String,
+    },
+
+    /// The named artboards, each written to `<name>.<extension>`
+    Artboards {
+        names: Vec<String>,
+        format: ImageFormat,
+        scale: f32,
+    },
+
+    /// The layers marked as assets, by asset name, paired with each scale they are written at
+    Assets {
+        files: Vec<(String, f32)>,
+    },
+}
+
+/// The task writing `artboard` of `document` to `path` as `format`, rendered at `scale` output pixels per worldspace pixel
+///
+/// [Vector formats][`ImageFormat::is_vector`] are written rather than rendered, with the artboard's top left corner as their origin.
+/// PNG, SVG, and PDF files carry [where they came from][`png_text`] as metadata
+fn artboard_task(rl: &mut RaylibHandle, thread: &RaylibThread, document: &Document, artboard: &Artboard, format: ImageFormat, scale: f32, path: PathBuf) -> Result<ExportTask, String> {
+    let text = png_text(document, &artboard.name);
+    if let Some(contents) = format.write_vector(&document.layers, artboard.rect, &document.swatches, &text) {
+        return Ok(ExportTask::File { contents, path });
+    }
+    let image = render_artboard(rl, thread, document, artboard, scale)?;
+    Ok(if format == ImageFormat::OpenRaster {
+        let layers = render_artboard_layers(rl, thread, document, artboard, scale)?
+            .into_iter()
+            .map(|(name, image)| (name, SendImage(image)))
+            .collect();
+        ExportTask::Layered { layers, merged: SendImage(image), path, dpi: document.info.dpi * scale }
+    } else {
+        let text = if format == ImageFormat::Png { text } else { Vec::new() };
+        ExportTask::Image { image: SendImage(image), path, text }
+    })
+}
+
+/// An export that renders one artboard per call to [`ExportJob::step`] and writes files on a background thread
+///
+/// Rendering needs the GPU context, so it stays on the main thread, but it is spread across
+/// frames of the main loop; encoding and writing the files, the slow part, never blocks it
+///
+/// Dropping the job cancels it. Files that were already written are left in place
+#[derive(Debug)]
+pub struct ExportJob {
+    /// The directory files are written to
+    directory: PathBuf,
+
+    /// What is being rendered
+    output: ExportOutput,
+
+    /// The index of the next artboard to render, among the artboards being exported
+    next: usize,
+
+    /// The number of files the job will write
+    total_files: usize,
+
+    /// [`None`] once every file has been sent
+    sender: Option<mpsc::Sender<ExportTask>>,
+
+    /// [`None`] once the thread has been joined
+    worker: Option<JoinHandle<Result<(), String>>>,
+
+    /// The number of files the export thread has written
+    files_written: Arc<AtomicUsize>,
+
+    /// Tells the export thread to stop before writing another file
+    is_cancelled: Arc<AtomicBool>,
+}
+
+impl ExportJob {
+    /// Start exporting every artboard of `document` to a numbered file of `format` in `directory`
+    ///
+    /// Files are named `<title>_0000.<extension>`, `<title>_0001.<extension>`, ... in frame order. \
+    /// A `<title>_manifest.json` listing the frames along with the document's [info][`crate::document::DocumentInfo`] is written beside them
+    pub fn start(document: &Document, directory: PathBuf, format: ImageFormat) -> Result<Self, String> {
+        let output = ExportOutput::Frames { frames: Vec::with_capacity(document.artboards.len()), format };
+        Self::spawn(directory, document.artboards.len().saturating_add(1), output)
+    }
+
+    /// Start exporting every artboard of `document` as a frame of a looping GIF in `directory`,
+    /// played at the document's [frame rate][`animation::Animation::fps`]
+    ///
+    /// The file is named `<title>.gif`
+    pub fn start_gif(document: &Document, directory: PathBuf) -> Result<Self, String> {
+        let output = ExportOutput::Gif {
+            frames: Vec::with_capacity(document.artboards.len()),
+            delay: gif::frame_delay(document.animation.fps.clamp(animation::Animation::MIN_FPS, animation::Animation::MAX_FPS)),
+            file_name: animation::gif_file_name(document),
+        };
+        Self::spawn(directory, 1, output)
+    }
+
+    /// Start exporting the artboards of `document` selected by `preset`, each to `<name>.<extension>` in the preset's folder
+    ///
+    /// Fails if the preset's [artboard names][`ExportPreset::validated_artboard_names`] don't pass validation
+    pub fn start_preset(document: &Document, preset: &ExportPreset) -> Result<Self, String> {
+        let names = preset.validated_artboard_names(document)?;
+        let total_files = names.len();
+        let output = ExportOutput::Artboards {
+            names,
+            format: preset.format,
+            scale: preset.scale.clamp(ExportPreset::MIN_SCALE, ExportPreset::MAX_SCALE),
+        };
+        Self::spawn(preset.resolve_directory(document), total_files, output)
+    }
+
+    /// Start exporting every layer of `document` marked as an asset, each at each of its scales, to [its file name][`AssetExport::file_name`] in `directory`
+    ///
+    /// Fails if any of the assets share a name, since their files would overwrite each other
+    pub fn start_assets(document: &Document, directory: PathBuf) -> Result<Self, String> {
+        let assets = asset_layers(&document.layers)
+            .into_iter()
+            .filter_map(|layer| layer.export.as_ref())
+            .collect::<Vec<_>>();
+        if assets.is_empty() {
+            return Err("no layers are marked as assets".to_owned());
+        }
+        let duplicates = document::duplicate_names(assets.iter().map(|asset| asset.name.as_str()));
+        if !duplicates.is_empty() {
+            return Err(format!("asset names must be unique, but these are repeated: {}", duplicates.join(", ")));
+        }
+        let files = assets.iter()
+            .flat_map(|asset| asset.scales.iter().map(|&scale| (asset.name.clone(), scale.clamp(ExportPreset::MIN_SCALE, ExportPreset::MAX_SCALE))))
+            .collect::<Vec<_>>();
+        let total_files = files.len();
+        Self::spawn(directory, total_files, ExportOutput::Assets { files })
+    }
+
+    /// Create `directory` and start the export thread
+    fn spawn(directory: PathBuf, total_files: usize, output: ExportOutput) -> Result<Self, String> {
+        std::fs::create_dir_all(&directory).map_err(|e| e.to_string())?;
+        let (sender, receiver) = mpsc::channel::<ExportTask>();
+        let files_written = Arc::new(AtomicUsize::new(0));
+        let is_cancelled = Arc::new(AtomicBool::new(false));
+        let worker = {
+            let files_written = files_written.clone();
+            let is_cancelled = is_cancelled.clone();
+            std::thread::Builder::new()
+                .name("export".to_owned())
+                .spawn(move || {
+                    for task in receiver {
+                        if is_cancelled.load(Ordering::Relaxed) {
+                            break;
+                        }
+                        match task {
+                            ExportTask::Image { image, path, text } => {
+                                image.0.export_image(path.to_str().ok_or("export path should be valid UTF-8")?);
+                                if !text.is_empty() {
+                                    let png = std::fs::read(&path).map_err(|e| e.to_string())?;
+                                    std::fs::write(&path, insert_png_text(&png, &text)?).map_err(|e| e.to_string())?;
+                                }
+                            }
+                            ExportTask::Layered { layers, merged, path, dpi } => {
+                                std::fs::write(path, encode_ora(&layers, merged, dpi)?).map_err(|e| e.to_string())?;
+                            }
+                            ExportTask::Gif { frames, delay, path } => {
+                                std::fs::write(path, gif::encode(&frames, delay)).map_err(|e| e.to_string())?;
+                            }
+                            ExportTask::File { contents, path } => {
+                                std::fs::write(path, contents).map_err(|e| e.to_string())?;
+                            }
+                        }
+                        files_written.fetch_add(1, Ordering::Relaxed);
+                    }
+                    Ok(())
+                })
+                .map_err(|e| e.to_string())?
+        };
+        Ok(Self {
+            directory,
+            output,
+            next: 0,
+            total_files,
+            sender: Some(sender),
+            worker: Some(worker),
+            files_written,
+            is_cancelled,
+        })
+    }
+
+    /// Render the next artboard and send it to the export thread
+    ///
+    /// Frame sequences send the manifest after the last frame, and GIFs are sent whole after their last frame is rendered.
+    /// Does nothing once everything has been sent
+    ///
+    /// Artboards are rendered as they are when this is called, so edits made during
+    /// the export show up in the artboards that haven't been rendered yet
+    pub fn step(&mut self, rl: &mut RaylibHandle, thread: &RaylibThread, document: &Document) -> Result<(), String> {
+        let Some(sender) = &self.sender else { return Ok(()) };
+        // a send error means the export thread stopped early; its error is reported by `poll`
+        match &mut self.output {
+            ExportOutput::Frames { frames, format } => {
+                if let Some(artboard) = document.artboards.get(self.next) {
+                    let file_name = animation::frame_file_name(document, self.next, *format);
+                    let task = artboard_task(rl, thread, document, artboard, *format, 1.0, self.directory.join(&file_name))?;
+                    frames.push(file_name);
+                    self.next = self.next.saturating_add(1);
+                    _ = sender.send(task);
+                } else {
+                    // artboards may have been removed since the export started
+                    self.total_files = frames.len().saturating_add(1);
+                    let contents = animation::manifest_json(document, frames)?.into_bytes();
+                    let path = self.directory.join(animation::manifest_file_name(document));
+                    _ = sender.send(ExportTask::File { contents, path });
+                    // closing the channel lets the export thread finish
+                    self.sender = None;
+                }
+            }
+
+            ExportOutput::Gif { frames, delay, file_name } => {
+                if let Some(artboard) = document.artboards.get(self.next) {
+                    let image = render_artboard(rl, thread, document, artboard, 1.0)?;
+                    frames.push(GifFrame::from_image(&image)?);
+                    self.next = self.next.saturating_add(1);
+                } else {
+                    let path = self.directory.join(&*file_name);
+                    _ = sender.send(ExportTask::Gif { frames: std::mem::take(frames), delay: *delay, path });
+                    // closing the channel lets the export thread finish
+                    self.sender = None;
+                }
+            }
+
+            ExportOutput::Artboards { names, format, scale } => {
+                if let Some(name) = names.get(self.next) {
+                    self.next = self.next.saturating_add(1);
+                    // artboards may have been renamed or removed since the export started
+                    if let Some(artboard) = document.artboards.iter().find(|artboard| artboard.name == *name) {
+                        let path = self.directory.join(format!("{}.{}", clean_file_name(name), format.extension()));
+                        _ = sender.send(artboard_task(rl, thread, document, artboard, *format, *scale, path)?);
+                    } else {
+                        self.total_files = self.total_files.saturating_sub(1);
+                    }
+                } else {
+                    // closing the channel lets the export thread finish
+                    self.sender = None;
+                }
+            }
+
+            ExportOutput::Assets { files } => {
+                if let Some((name, scale)) = files.get(self.next) {
+                    self.next = self.next.saturating_add(1);
+                    // layers may have been unmarked, emptied, or removed since the export started
+                    let asset = asset_layers(&document.layers)
+                        .into_iter()
+                        .find_map(|layer| Some((layer, layer.export.as_ref().filter(|asset| asset.name == *name)?, layer.visual_bounds()?)));
+                    if let Some((layer, asset, bounds)) = asset {
+                        let path = self.directory.join(asset.file_name(*scale));
+                        if let Some(contents) = asset.format.write_vector([layer], bounds, &document.swatches, &[]) {
+                            _ = sender.send(ExportTask::File { contents, path });
+                            return Ok(());
+                        }
+                        let image = render_layers(rl, thread, std::slice::from_ref(layer), bounds, Color::BLANK, *scale)?;
+                        let task = if asset.format == ImageFormat::OpenRaster {
+                            let layers = vec![(layer.name.clone(), SendImage(image.clone()))];
+                            ExportTask::Layered { layers, merged: SendImage(image), path, dpi: document.info.dpi * *scale }
+                        } else {
+                            ExportTask::Image { image: SendImage(image), path, text: Vec::new() }
+                        };
+                        _ = sender.send(task);
+                    } else {
+                        self.total_files = self.total_files.saturating_sub(1);
+                    }
+                } else {
+                    // closing the channel lets the export thread finish
+                    self.sender = None;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The fraction of files written so far, from 0 to 1
+    pub fn progress(&self) -> f32 {
+        self.files_written.load(Ordering::Relaxed) as f32 / self.total_files.max(1) as f32
+    }
+
+    /// Check whether the export thread has finished
+    ///
+    /// Returns the export's result the first time it is called after the thread finishes, [`None`] otherwise
+    pub fn poll(&mut self) -> Option<Result<(), String>> {
+        if !self.worker.as_ref()?.is_finished() {
+            return None;
+        }
+        let worker = self.worker.take()?;
+        Some(worker.join().unwrap_or_else(|_| Err("export thread panicked".to_owned())))
+    }
+}
+
+impl Drop for ExportJob {
+    fn drop(&mut self) {
+        self.is_cancelled.store(true, Ordering::Relaxed);
+        // the thread is detached; it stops after the file it is currently writing
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert_png_text() {
+        assert_eq!(crc32(b"IEND"), 0xAE42_6082);
+
+        let mut ihdr = b"IHDR".to_vec();
+        ihdr.extend_from_slice(&[0, 0, 0, 1, 0, 0, 0, 1, 8, 6, 0, 0, 0]);
+        let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+        png.extend_from_slice(&13u32.to_be_bytes());
+        png.extend_from_slice(&ihdr);
+        png.extend_from_slice(&crc32(&ihdr).to_be_bytes());
+        png.extend_from_slice(&[0, 0, 0, 0]);
+        png.extend_from_slice(b"IEND");
+        png.extend_from_slice(&crc32(b"IEND").to_be_bytes());
+
+        let out = insert_png_text(&png, &[("Title", "Logo".to_owned()), ("Author", "\u{5f20}".to_owned())]).unwrap();
+        let text = &out[33..];
+        assert_eq!(&text[..4], &10u32.to_be_bytes());
+        assert_eq!(&text[4..18], b"tEXtTitle\0Logo");
+        assert_eq!(&text[18..22], &crc32(b"tEXtTitle\0Logo").to_be_bytes());
+        assert_eq!(&text[26..30], b"iTXt");
+        assert!(out.ends_with(&png[33..]));
+        assert!(insert_png_text(b"not a png", &[]).is_err());
+    }
+
+    #[test]
+    fn test_png_text() {
+        let mut document = Document::new("Logo".to_owned());
+        document.info.tags = vec!["logo".to_owned(), "draft".to_owned()];
+        document.info.created = Some(1_700_000_000);
+        let text = png_text(&document, "Icon");
+        let value = |keyword: &str| text.iter().find(|(key, _)| *key == keyword).map(|(_, value)| value.as_str());
+        assert_eq!(value("Title"), Some("Logo"));
+        assert_eq!(value("Keywords"), Some("logo, draft"));
+        assert_eq!(value("Creation Time"), Some("2023-11-14T22:13:20Z"));
+        assert_eq!(value("Artboard"), Some("Icon"));
+        assert_eq!(value("Author"), None, "blank fields should be left out");
+        assert_eq!(value("Modification Time"), None, "unsaved documents have no modified time");
+    }
+
+    #[test]
+    fn test_validated_artboard_names() {
+        let mut document = Document::new("test".to_owned());
+        let mut preset = ExportPreset::new();
+        assert!(preset.validated_artboard_names(&document).is_err(), "there should be nothing to export");
+
+        for name in ["icon", "banner", "Icon"] {
+            document.artboards.push(Artboard::new(name.to_owned(), Rectangle::new(0.0, 0.0, 10.0, 10.0)));
+        }
+        let error = preset.validated_artboard_names(&document).unwrap_err();
+        assert!(error.contains("Icon"), "{error}");
+
+        // only the artboards being exported need unique names
+        preset.artboards = vec!["icon".to_owned(), "banner".to_owned()];
+        assert_eq!(preset.validated_artboard_names(&document).unwrap(), ["icon", "banner"]);
+
+        preset.artboards.clear();
+        preset.is_requiring_unique_names = false;
+        assert_eq!(preset.validated_artboard_names(&document).unwrap(), ["icon", "banner", "Icon"]);
+        preset.artboards = vec!["missing".to_owned()];
+        assert!(preset.validated_artboard_names(&document).is_err(), "validation can't be turned off for an empty export");
+    }
+
+    #[test]
+    fn test_write_vector() {
+        let document = Document::new("Logo".to_owned());
+        let bounds = Rectangle::new(10.0, 20.0, 30.0, 40.0);
+        let text = png_text(&document, "Icon");
+        let svg = String::from_utf8(ImageFormat::Svg.write_vector(&document.layers, bounds, &[], &text).unwrap()).unwrap();
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.contains("viewBox=\"0 0 30 40\""), "the artboard's corner should be the origin");
+        assert!(svg.contains("<dc:identifier>Icon</dc:identifier>"));
+        let pdf = ImageFormat::Pdf.write_vector(&document.layers, bounds, &[], &text).unwrap();
+        assert!(pdf.starts_with(b"%PDF"));
+        assert!(String::from_utf8_lossy(&pdf).contains("/Title (Logo)"));
+        for format in ImageFormat::ALL {
+            assert_eq!(format.write_vector(&document.layers, bounds, &[], &text).is_some(), format.is_vector(), "{format:?}");
+        }
+    }
+
+    #[test]
+    fn test_clean_file_name() {
+        assert_eq!(clean_file_name("Artboard 1"), "Artboard 1");
+        assert_eq!(clean_file_name("../../x"), ".._.._x");
+        assert_eq!(clean_file_name("/etc/passwd"), "_etc_passwd");
+        assert_eq!(clean_file_name("C:\\Windows\\x"), "C__Windows_x");
+        assert_eq!(clean_file_name(".."), "_");
+        assert_eq!(clean_file_name(" "), "_");
+        for name in ["../../x", "/etc/passwd", "C:\\x", "..", ""] {
+            let path = Path::new("out").join(clean_file_name(name));
+            assert_eq!(path.parent(), Some(Path::new("out")), "{name:?}");
+            assert!(path.components().all(|c| matches!(c, std::path::Component::Normal(_))), "{name:?}");
+        }
+    }
+
+    #[test]
+    fn test_unused_path() {
+        let directory = std::env::temp_dir().join(format!("unused_path_{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let first = unused_path(&directory, "a/b", "txt");
+        assert_eq!(first, directory.join("a_b.txt"));
+        std::fs::write(&first, "").unwrap();
+        assert_eq!(unused_path(&directory, "a/b", "txt"), directory.join("a_b 2.txt"));
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn test_asset_file_name() {
+        let mut asset = AssetExport::new("icon".to_owned());
+        assert_eq!(asset.file_name(1.0), "icon.png");
+        assert_eq!(asset.file_name(2.0), "icon@2x.png");
+        asset.format = ImageFormat::Qoi;
+        assert_eq!(asset.file_name(1.5), "icon@1.5x.qoi");
+        asset.name = "../icons/icon".to_owned();
+        assert_eq!(asset.file_name(2.0), ".._icons_icon@2x.qoi");
+    }
+
+    #[test]
+    #[ignore = "renders with the GPU against tests/golden"]
+    fn test_render_artboard_golden() {
+        let mut editor = crate::editor::Editor::new_default(Document::new("golden".to_owned()));
+        editor.add_curve_layer("wave".to_owned(), crate::make_curve!([-50,0](20,100)[50,0]->[-50,0](100,20)[50,0]->[-50,0](180,100)[50,0]));
+        editor.add_curve_layer("circle".to_owned(), crate::make_curve!((190,60) -> arc(0, 360, 30) -> cycle));
+        editor.document.artboards.push(Artboard::new("Artboard 1".to_owned(), Rectangle::new(0.0, 0.0, 200.0, 120.0)));
+
+        let image = crate::golden::render_artboard(&editor.document, &editor.document.artboards[0], 2.0).unwrap();
+        assert_eq!((image.width(), image.height()), (400, 240));
+        crate::golden::assert_matches("render_artboard", &image, crate::golden::Tolerance::default());
+    }
+}
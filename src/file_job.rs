@@ -0,0 +1,157 @@
+use std::{path::{Path, PathBuf}, sync::mpsc};
+use crate::{format::{self, DocumentData, EncodedDocument}, locale, pdf, svg};
+
+/// What a [`FileJob`] is doing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileJobKind {
+    /// Opening a native document
+    Open,
+    /// Importing an SVG as a new document
+    Import,
+    /// Saving a native document
+    Save,
+}
+
+/// What a [`FileJob`] produced
+pub enum FileOutcome {
+    /// A native document was read, and still has to be [built][`format::build`] on the main thread
+    Opened(DocumentData),
+    /// A file was imported into a new document, which still has to be [built][`format::build`] on the main thread
+    ///
+    /// The imported document is sent [detached][`format::detach`] so no handle to it is shared between threads
+    Imported(DocumentData),
+    /// A document was written
+    Saved,
+}
+
+/// A message from the file thread to the main loop
+enum FileMessage {
+    /// The fraction of the job done so far
+    Progress(f32),
+    /// The job finished
+    Done(Result<FileOutcome, String>),
+}
+
+/// A document being opened, imported, or saved on a background thread, so big files don't freeze the window
+///
+/// The thread reports its progress through a channel, which the main loop drains with [`FileJob::poll`].
+/// Anything needing the GPU context (uploading or downloading rasters) stays on the main thread
+///
+/// Dropping the job detaches the thread; a save in progress still finishes unless the application exits first
+#[derive(Debug)]
+pub struct FileJob {
+    kind: FileJobKind,
+    path: PathBuf,
+    receiver: mpsc::Receiver<FileMessage>,
+    progress: f32,
+}
+
+impl FileJob {
+    /// Start reading the native document at `path`
+    pub fn open(path: &Path) -> Result<Self, String> {
+        let owned_path = path.to_path_buf();
+        Self::spawn(FileJobKind::Open, path, move |progress| {
+            format::read(&owned_path, progress).map(FileOutcome::Opened)
+        })
+    }
+
+    /// Start importing the SVG at `path`
+    pub fn import_svg(path: &Path) -> Result<Self, String> {
+        let owned_path = path.to_path_buf();
+        Self::spawn(FileJobKind::Import, path, move |_| {
+            svg::import(&owned_path)
+                .and_then(|document| format::detach(&document, &owned_path))
+                .map(FileOutcome::Imported)
+        })
+    }
+
+    /// Start importing the PDF, EPS, or Illustrator file at `path`
+    pub fn import_pdf(path: &Path) -> Result<Self, String> {
+        let owned_path = path.to_path_buf();
+        Self::spawn(FileJobKind::Import, path, move |_| {
+            pdf::import(&owned_path)
+                .and_then(|document| format::detach(&document, &owned_path))
+                .map(FileOutcome::Imported)
+        })
+    }
+
+    /// Start writing an encoded document to the path it was encoded for, `path`
+    pub fn save(document: EncodedDocument, path: &Path) -> Result<Self, String> {
+        Self::spawn(FileJobKind::Save, path, move |progress| {
+            document.write(progress).map(|()| FileOutcome::Saved)
+        })
+    }
+
+    /// Start a thread running `work`, which reports its progress through the function it is given
+    fn spawn(kind: FileJobKind, path: &Path, work: impl FnOnce(&mut dyn FnMut(f32)) -> Result<FileOutcome, String> + Send + 'static) -> Result<Self, String> {
+        let (sender, receiver) = mpsc::channel();
+        std::thread::Builder::new()
+            .name("file".to_owned())
+            .spawn(move || {
+                // the main loop may have stopped listening; the work is finished either way
+                let result = work(&mut |fraction| _ = sender.send(FileMessage::Progress(fraction)));
+                _ = sender.send(FileMessage::Done(result));
+            })
+            .map_err(|e| e.to_string())?;
+        Ok(Self {
+            kind,
+            path: path.to_path_buf(),
+            receiver,
+            progress: 0.0,
+        })
+    }
+
+    /// The file being read or written
+    #[inline]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The fraction of the job done, as of the last [poll][`FileJob::poll`]
+    #[inline]
+    pub const fn progress(&self) -> f32 {
+        self.progress
+    }
+
+    /// Describe the job for its progress bar, like "Opening poster.ava"
+    pub fn label(&self) -> String {
+        let key = match self.kind {
+            FileJobKind::Open => "status.opening",
+            FileJobKind::Import => "status.importing",
+            FileJobKind::Save => "status.saving",
+        };
+        let name = self.path.file_name().unwrap_or_default().to_string_lossy();
+        locale::tr_format(key, &[&name])
+    }
+
+    /// Take the progress reported since the last call
+    ///
+    /// Returns the job's result the first time it is called after the thread finishes, [`None`] otherwise
+    pub fn poll(&mut self) -> Option<Result<FileOutcome, String>> {
+        loop {
+            match self.receiver.try_recv() {
+                Ok(FileMessage::Progress(fraction)) => self.progress = fraction,
+                Ok(FileMessage::Done(result)) => {
+                    self.progress = 1.0;
+                    return Some(result);
+                }
+                Err(mpsc::TryRecvError::Empty) => return None,
+                Err(mpsc::TryRecvError::Disconnected) => return Some(Err("file thread stopped without finishing".to_owned())),
+            }
+        }
+    }
+
+    /// Block until the thread finishes, returning the job's result
+    pub fn wait(&mut self) -> Result<FileOutcome, String> {
+        loop {
+            match self.receiver.recv() {
+                Ok(FileMessage::Progress(fraction)) => self.progress = fraction,
+                Ok(FileMessage::Done(result)) => {
+                    self.progress = 1.0;
+                    return result;
+                }
+                Err(mpsc::RecvError) => return Err("file thread stopped without finishing".to_owned()),
+            }
+        }
+    }
+}
@@ -0,0 +1,232 @@
+use raylib::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::{profiler, style::TextureWrap};
+
+/// Worldspace height below which a band between two vertices is too thin to fill
+const EPSILON: f32 = 1e-4;
+
+/// rlgl draw mode for batches of triangles
+const RL_TRIANGLES: i32 = 0x0004;
+
+/// Which points are inside a shape whose outlines overlap or cross themselves
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum FillRule {
+    /// Inside if the outlines wind around the point on balance, so overlapping outlines drawn the same
+    /// direction fill and outlines drawn in opposite directions cut holes
+    #[default]
+    NonZero,
+
+    /// Inside if the point is enclosed by an odd number of outlines, so overlaps are always holes
+    EvenOdd,
+}
+
+impl FillRule {
+    /// Whether a point that the outlines wind around `winding` times is inside
+    #[inline]
+    pub const fn is_inside(self, winding: i32) -> bool {
+        match self {
+            Self::NonZero => winding != 0,
+            Self::EvenOdd => winding % 2 != 0,
+        }
+    }
+}
+
+/// An edge of a polygon, from its top (smaller `y`) to its bottom
+#[derive(Debug, Clone, Copy)]
+struct Edge {
+    top: na::Vector2<f32>,
+    bottom: na::Vector2<f32>,
+    /// 1 if the polygon runs down along the edge, -1 if it runs up
+    direction: i32,
+}
+
+impl Edge {
+    /// The `x` where the edge crosses the horizontal line at `y`
+    fn x_at(&self, y: f32) -> f32 {
+        let t = (y - self.top.y) / (self.bottom.y - self.top.y);
+        self.top.x + (self.bottom.x - self.top.x) * t
+    }
+}
+
+/// Get every non-horizontal edge of `polygons`, each of which is implicitly closed
+fn edges(polygons: &[Vec<na::Vector2<f32>>]) -> Vec<Edge> {
+    polygons.iter()
+        .flat_map(|polygon| polygon.iter().zip(polygon.iter().cycle().skip(1)))
+        .filter(|(a, b)| a.y != b.y)
+        .map(|(&a, &b)| if a.y < b.y {
+            Edge { top: a, bottom: b, direction: 1 }
+        } else {
+            Edge { top: b, bottom: a, direction: -1 }
+        })
+        .collect()
+}
+
+/// How many times `polygons` wind around `point`, counting clockwise (on screen) as positive
+pub fn winding(polygons: &[Vec<na::Vector2<f32>>], point: na::Vector2<f32>) -> i32 {
+    edges(polygons).iter()
+        // half-open, so a ray through a vertex is only counted once
+        .filter(|edge| edge.top.y <= point.y && point.y < edge.bottom.y && edge.x_at(point.y) > point.x)
+        .fold(0, |winding, edge| winding.saturating_add(edge.direction))
+}
+
+/// Whether `point` is inside the shape outlined by `polygons`
+pub fn contains(polygons: &[Vec<na::Vector2<f32>>], point: na::Vector2<f32>, rule: FillRule) -> bool {
+    rule.is_inside(winding(polygons, point))
+}
+
+/// Twice the signed area of `polygon`; positive if it runs clockwise on screen
+pub fn signed_area(polygon: &[na::Vector2<f32>]) -> f32 {
+    polygon.iter()
+        .zip(polygon.iter().cycle().skip(1))
+        .map(|(a, b)| a.x * b.y - b.x * a.y)
+        .sum()
+}
+
+/// Split the inside of the shape outlined by `polygons` into triangles
+///
+/// The shape is cut into horizontal bands at every vertex and crossing, and the inside of each band is filled
+/// with trapezoids. Triangles are wound the way [`RaylibDraw::draw_triangle`] expects
+pub fn tessellate(polygons: &[Vec<na::Vector2<f32>>], rule: FillRule) -> Vec<[Vector2; 3]> {
+    let _span = profiler::span("tessellate");
+    let edges = edges(polygons);
+    let mut ys = edges.iter().flat_map(|edge| [edge.top.y, edge.bottom.y]).collect::<Vec<_>>();
+    ys.sort_by(f32::total_cmp);
+    ys.dedup();
+
+    let mut triangles = Vec::new();
+    for band in ys.windows(2) {
+        let (band_top, band_bottom) = (band[0], band[1]);
+        if band_bottom - band_top < EPSILON {
+            continue;
+        }
+        let active = edges.iter()
+            .filter(|edge| edge.top.y <= band_top && edge.bottom.y >= band_bottom)
+            .collect::<Vec<_>>();
+
+        // edges that cross within the band would twist the trapezoids between them
+        let mut cuts = vec![band_top, band_bottom];
+        for (i, a) in active.iter().enumerate() {
+            for b in &active[i.saturating_add(1)..] {
+                let top_gap = a.x_at(band_top) - b.x_at(band_top);
+                let bottom_gap = a.x_at(band_bottom) - b.x_at(band_bottom);
+                if top_gap * bottom_gap < 0.0 {
+                    let t = top_gap / (top_gap - bottom_gap);
+                    cuts.push(band_top + (band_bottom - band_top) * t);
+                }
+            }
+        }
+        cuts.sort_by(f32::total_cmp);
+        cuts.dedup();
+
+        for cut in cuts.windows(2) {
+            let (top, bottom) = (cut[0], cut[1]);
+            if bottom - top < EPSILON {
+                continue;
+            }
+            let middle = (top + bottom) * 0.5;
+            let mut crossing = active.clone();
+            crossing.sort_by(|a, b| a.x_at(middle).total_cmp(&b.x_at(middle)));
+
+            let mut winding = 0i32;
+            let mut left = None;
+            for edge in crossing {
+                let was_inside = rule.is_inside(winding);
+                winding = winding.saturating_add(edge.direction);
+                match (was_inside, rule.is_inside(winding)) {
+                    (false, true) => left = Some(edge),
+                    (true, false) => if let Some(left) = left.take() {
+                        let top_left = Vector2::new(left.x_at(top), top);
+                        let top_right = Vector2::new(edge.x_at(top), top);
+                        let bottom_left = Vector2::new(left.x_at(bottom), bottom);
+                        let bottom_right = Vector2::new(edge.x_at(bottom), bottom);
+                        triangles.push([top_left, bottom_left, bottom_right]);
+                        triangles.push([top_left, bottom_right, top_right]);
+                    },
+                    _ => (),
+                }
+            }
+        }
+    }
+    triangles
+}
+
+/// Fill the shape outlined by `polygons` with `color`
+pub fn draw(d: &mut impl RaylibDraw, polygons: &[Vec<na::Vector2<f32>>], rule: FillRule, color: Color) {
+    draw_triangles(d, &tessellate(polygons, rule), color);
+}
+
+/// Fill the shape [tessellated][`tessellate`] into `triangles` with `color`
+pub fn draw_triangles(d: &mut impl RaylibDraw, triangles: &[[Vector2; 3]], color: Color) {
+    for &[a, b, c] in triangles {
+        d.draw_triangle(a, b, c, color);
+    }
+}
+
+/// Fill the shape [tessellated][`tessellate`] into `triangles` with `texture`, sampled at the texture coordinates `uv`
+/// gives each worldspace point
+///
+/// `texture` is a render texture, stored upside-down. Coordinates outside `0.0..=1.0` are wrapped by the GPU following `wrap`
+pub fn draw_textured(_d: &mut impl RaylibDraw, triangles: &[[Vector2; 3]], texture: &impl AsRef<ffi::Texture2D>, wrap: TextureWrap, uv: impl Fn(Vector2) -> Vector2, tint: Color) {
+    let texture = *texture.as_ref();
+    let wrap = match wrap {
+        TextureWrap::Clamp => raylib::consts::TextureWrap::TEXTURE_WRAP_CLAMP,
+        TextureWrap::Repeat => raylib::consts::TextureWrap::TEXTURE_WRAP_REPEAT,
+        TextureWrap::Mirror => raylib::consts::TextureWrap::TEXTURE_WRAP_MIRROR_REPEAT,
+    };
+    // SAFETY: `_d` guarantees a drawing mode is active, and the batch is closed and untextured again before returning
+    unsafe {
+        ffi::SetTextureWrap(texture, wrap as i32);
+        ffi::rlSetTexture(texture.id);
+        ffi::rlBegin(RL_TRIANGLES);
+        ffi::rlColor4ub(tint.r, tint.g, tint.b, tint.a);
+        for &vertex in triangles.iter().flatten() {
+            let Vector2 { x: u, y: v } = uv(vertex);
+            ffi::rlTexCoord2f(u, 1.0 - v);
+            ffi::rlVertex2f(vertex.x, vertex.y);
+        }
+        ffi::rlEnd();
+        ffi::rlSetTexture(0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A square from `min` to `max`, clockwise on screen unless `is_reversed`
+    fn square(min: f32, max: f32, is_reversed: bool) -> Vec<na::Vector2<f32>> {
+        let mut square = vec![
+            na::Vector2::new(min, min),
+            na::Vector2::new(max, min),
+            na::Vector2::new(max, max),
+            na::Vector2::new(min, max),
+        ];
+        if is_reversed {
+            square.reverse();
+        }
+        square
+    }
+
+    /// The total area of `triangles`
+    fn area(triangles: &[[Vector2; 3]]) -> f32 {
+        triangles.iter()
+            .map(|[a, b, c]| ((b.x - a.x) * (c.y - a.y) - (c.x - a.x) * (b.y - a.y)).abs() * 0.5)
+            .sum()
+    }
+
+    #[test]
+    fn test_fill_rules() {
+        let same_direction = [square(0.0, 4.0, false), square(1.0, 3.0, false)];
+        let center = na::Vector2::new(2.0, 2.0);
+        assert_eq!(winding(&same_direction, center), 2);
+        assert!(contains(&same_direction, center, FillRule::NonZero));
+        assert!(!contains(&same_direction, center, FillRule::EvenOdd));
+        assert!((area(&tessellate(&same_direction, FillRule::NonZero)) - 16.0).abs() < 1e-3);
+        assert!((area(&tessellate(&same_direction, FillRule::EvenOdd)) - 12.0).abs() < 1e-3);
+
+        let opposite_direction = [square(0.0, 4.0, false), square(1.0, 3.0, true)];
+        assert!(!contains(&opposite_direction, center, FillRule::NonZero));
+        assert!((area(&tessellate(&opposite_direction, FillRule::NonZero)) - 12.0).abs() < 1e-3);
+        assert!(signed_area(&square(0.0, 4.0, false)) > 0.0);
+    }
+}
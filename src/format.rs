@@ -0,0 +1,993 @@
+use std::{collections::{BTreeSet, HashMap}, hash::{DefaultHasher, Hasher}, path::{Path, PathBuf}, sync::{Arc, Weak}};
+use raylib::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::{
+    animation::Animation,
+    annotation::Annotation,
+    chunks::{self, ChunkFile},
+    connector::{Connector, ConnectorEnd, ConnectorRouting},
+    curve::{Curve, CurvePoint, WeakCurve},
+    document::{Artboard, ArtboardBackground, Document, DocumentInfo, NamedView},
+    export::{AssetExport, ExportPreset},
+    fill::FillRule,
+    layer::{CompoundPath, Group, Layer, LayerContent, StrongRenderTexture2D, WeakRenderTexture2D},
+    raster::{self, SendImage, TiledRaster},
+    shape::LiveRectangle,
+    style::{Modifier, Pattern, Stroke, StrokeMarkers, StrokeTrim, Style, StyleItem, TextureTransform, WeakStyle, WeakWidthProfile, WidthProfile},
+    swatch::Swatch,
+    zip::{self, ZipArchive, ZipWriter},
+};
+
+/// The file extension of native documents
+pub const EXTENSION: &str = "ava";
+
+/// The file extension of packaged native documents: a zip archive holding the document along with its images,
+/// so it can be moved around as a single file
+pub const PACKAGE_EXTENSION: &str = "avz";
+
+/// The path within a package of the document itself
+const PACKAGE_DOCUMENT: &str = "document.json";
+
+/// The folder within a package holding the document's images
+const PACKAGE_ASSETS: &str = "assets";
+
+/// The format version written by [`encode`]
+///
+/// Files with a newer version than this are refused by [`load`], and older ones are upgraded by [`MIGRATIONS`]
+pub const VERSION: u32 = 1;
+
+/// Upgrades the raw JSON of a document file from one format version to the next
+type Migration = fn(&mut serde_json::Map<String, serde_json::Value>) -> Result<(), String>;
+
+/// The upgrade from each format version to the next, starting with the one from version 1 to 2
+///
+/// Bumping [`VERSION`] needs a migration added here, so files saved by earlier builds keep opening.
+/// Migrations work on the raw JSON, so old shapes of the format needn't be kept around as types
+const MIGRATIONS: [Migration; VERSION as usize - 1] = [];
+
+/// The key of the chunk holding every field of [`DocumentFile`] but the [chunked ones][`CHUNKED_FIELDS`]
+const HEADER_CHUNK: &str = "header";
+
+/// The fields of [`DocumentFile`] that grow with the artwork, saved in runs of [`CHUNK_LEN`] items
+/// so that a small edit only rewrites the runs it touches
+const CHUNKED_FIELDS: [&str; 2] = ["curves", "layers"];
+
+/// Number of items in each chunk of a [chunked field][`CHUNKED_FIELDS`]
+const CHUNK_LEN: usize = 256;
+
+#[derive(Serialize, Deserialize)]
+struct CurveFile {
+    /// `[c_in, p, c_out]` of each [`CurvePoint`]
+    points: Vec<[[f32; 2]; 3]>,
+    is_closed: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+enum PatternFile {
+    Solid(Color),
+    /// Index into [`DocumentFile::rasters`], stretched over the region like [`TextureTransform::IDENTITY`]
+    ///
+    /// Still written for untransformed textures, so files stay readable by builds without texture transforms
+    Texture(usize),
+    TransformedTexture {
+        /// Index into [`DocumentFile::rasters`]
+        raster: usize,
+        transform: TextureTransform,
+    },
+}
+
+#[derive(Serialize, Deserialize)]
+struct StrokeFile {
+    pattern: PatternFile,
+    /// Index into [`DocumentFile::width_profiles`]
+    width: Option<usize>,
+    #[serde(default)]
+    trim: StrokeTrim,
+    #[serde(default)]
+    markers: StrokeMarkers,
+}
+
+#[derive(Serialize, Deserialize)]
+enum ModifierFile {
+    Fill(PatternFile),
+    Stroke(StrokeFile),
+}
+
+#[derive(Serialize, Deserialize)]
+struct StyleItemFile {
+    name: Option<String>,
+    modifier: ModifierFile,
+}
+
+#[derive(Serialize, Deserialize)]
+struct StyleFile {
+    fill: PatternFile,
+    #[serde(default)]
+    fill_rule: FillRule,
+    stroke: StrokeFile,
+    items: Vec<StyleItemFile>,
+}
+
+#[derive(Serialize, Deserialize)]
+enum LayerContentFile {
+    Group(Vec<LayerFile>),
+    /// Index into [`DocumentFile::curves`]
+    Curve(usize),
+    /// Indices into [`DocumentFile::curves`]
+    Compound(Vec<usize>),
+    Connector {
+        /// Index into [`DocumentFile::curves`]
+        curve: usize,
+        start: ConnectorEndFile,
+        end: ConnectorEndFile,
+        routing: ConnectorRouting,
+    },
+    Rectangle {
+        /// Index into [`DocumentFile::curves`]
+        curve: usize,
+        rect: Rectangle,
+        radii: [f32; 4],
+    },
+    Image {
+        /// Index into [`DocumentFile::rasters`]
+        raster: usize,
+        rect: Rectangle,
+    },
+    Paint {
+        origin: [f32; 2],
+        /// Grid coordinates paired with indices into [`DocumentFile::tiles`]
+        tiles: Vec<([i32; 2], usize)>,
+    },
+}
+
+#[derive(Serialize, Deserialize)]
+enum ConnectorEndFile {
+    Free,
+    Anchor {
+        /// Index into [`DocumentFile::curves`]
+        curve: usize,
+        index: usize,
+    },
+    /// Indices into [`DocumentFile::curves`]
+    Bounds(Vec<usize>),
+}
+
+#[derive(Serialize, Deserialize)]
+struct LayerFile {
+    name: String,
+    content: LayerContentFile,
+    /// Index into [`DocumentFile::styles`]
+    style: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    export: Option<AssetExport>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    is_template: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ArtboardFile {
+    name: String,
+    rect: Rectangle,
+    #[serde(default)]
+    background: ArtboardBackground,
+}
+
+/// The on-disk representation of a [`Document`]
+///
+/// Shared resources are stored once and referenced by index. Saved [split into chunks][`to_chunks`],
+/// so that saving again only appends the parts that changed
+#[derive(Serialize, Deserialize)]
+struct DocumentFile {
+    version: u32,
+    title: String,
+    #[serde(default)]
+    info: DocumentInfo,
+    paper_color: Color,
+    /// PNG files relative to the directory containing the document
+    rasters: Vec<PathBuf>,
+    /// PNG files of paint layer tiles, relative to the directory containing the document
+    #[serde(default)]
+    tiles: Vec<PathBuf>,
+    width_profiles: Vec<WidthProfile>,
+    styles: Vec<StyleFile>,
+    curves: Vec<CurveFile>,
+    layers: Vec<LayerFile>,
+    artboards: Vec<ArtboardFile>,
+    animation: Animation,
+    /// Graphic style names paired with indices into [`DocumentFile::styles`]
+    #[serde(default)]
+    linked_styles: Vec<(String, usize)>,
+    /// Index into [`DocumentFile::styles`]
+    #[serde(default)]
+    default_style: Option<usize>,
+    #[serde(default)]
+    export_presets: Vec<ExportPreset>,
+    #[serde(default)]
+    last_export: Option<ExportPreset>,
+    #[serde(default)]
+    swatches: Vec<Swatch>,
+    /// Hash of the pixels of each of [`DocumentFile::rasters`] followed by [`DocumentFile::tiles`],
+    /// so saving again can skip the images that haven't changed
+    #[serde(default)]
+    image_hashes: Vec<u64>,
+    #[serde(default)]
+    views: Vec<NamedView>,
+    #[serde(default)]
+    annotations: Vec<Annotation>,
+}
+
+/// The fields of a saved [`DocumentFile`] naming its images, read back before saving over it
+#[derive(Deserialize)]
+struct SavedImages {
+    rasters: Vec<PathBuf>,
+    #[serde(default)]
+    tiles: Vec<PathBuf>,
+    #[serde(default)]
+    image_hashes: Vec<u64>,
+}
+
+/// Split `file` into the chunks it is saved as, starting with the [header][`HEADER_CHUNK`]
+fn to_chunks(file: &DocumentFile) -> Result<Vec<(String, serde_json::Value)>, String> {
+    let serde_json::Value::Object(mut header) = serde_json::to_value(file).map_err(|e| e.to_string())? else {
+        return Err("document should serialize to an object".to_owned());
+    };
+    let mut chunks = Vec::new();
+    for field in CHUNKED_FIELDS {
+        let Some(serde_json::Value::Array(items)) = header.remove(field) else { continue };
+        let mut items = items.into_iter().peekable();
+        let mut index = 0;
+        while items.peek().is_some() {
+            chunks.push((format!("{field}/{index}"), serde_json::Value::Array(items.by_ref().take(CHUNK_LEN).collect())));
+            index += 1;
+        }
+    }
+    chunks.insert(0, (HEADER_CHUNK.to_owned(), serde_json::Value::Object(header)));
+    Ok(chunks)
+}
+
+/// Reassemble the JSON of a file split by [`to_chunks`]
+fn from_chunks(chunks: Vec<(String, serde_json::Value)>) -> Result<serde_json::Value, String> {
+    let mut fields = CHUNKED_FIELDS.iter()
+        .map(|&field| (field.to_owned(), serde_json::Value::Array(Vec::new())))
+        .collect::<serde_json::Map<_, _>>();
+    for (key, data) in chunks {
+        match (key.split_once('/'), data) {
+            (None, serde_json::Value::Object(header)) if key == HEADER_CHUNK => fields.extend(header),
+            (Some((field, _)), serde_json::Value::Array(items)) if let Some(serde_json::Value::Array(all)) = fields.get_mut(field) => all.extend(items),
+            _ => return Err(format!("unexpected chunk \"{key}\"")),
+        }
+    }
+    Ok(serde_json::Value::Object(fields))
+}
+
+/// Upgrade `document`, the raw JSON of a document file, from the version it was saved with by running
+/// the `migrations` after it in order
+///
+/// `migrations[i]` upgrades version `i + 1`. Errors if the document is newer than the migrations reach
+fn migrate(document: &mut serde_json::Map<String, serde_json::Value>, migrations: &[Migration]) -> Result<(), String> {
+    let latest = migrations.len() + 1;
+    let version = document.get("version")
+        .and_then(serde_json::Value::as_u64)
+        .ok_or("document has no format version")?;
+    let version = usize::try_from(version).unwrap_or(usize::MAX);
+    if version > latest {
+        return Err(format!("document was saved by a newer version (format {version} > {latest})"));
+    }
+    for migration in &migrations[version.saturating_sub(1)..] {
+        migration(document)?;
+    }
+    document.insert("version".to_owned(), latest.into());
+    Ok(())
+}
+
+/// Collect the paths of the fields in `original` that are missing from `parsed`, like fields added by
+/// newer builds that were skipped while reading
+///
+/// `path` is the path of the values being compared. Array items share a path, so each field is only listed once
+fn unknown_fields(original: &serde_json::Value, parsed: &serde_json::Value, path: &mut String, unknown: &mut BTreeSet<String>) {
+    let len = path.len();
+    match (original, parsed) {
+        (serde_json::Value::Object(original), serde_json::Value::Object(parsed)) => {
+            for (key, value) in original {
+                if !path.is_empty() {
+                    path.push('.');
+                }
+                path.push_str(key);
+                match parsed.get(key) {
+                    Some(parsed_value) => unknown_fields(value, parsed_value, path, unknown),
+                    // fields left out when empty are read back as missing
+                    None if !value.is_null() => _ = unknown.insert(path.clone()),
+                    None => (),
+                }
+                path.truncate(len);
+            }
+        }
+        (serde_json::Value::Array(original), serde_json::Value::Array(parsed)) => {
+            path.push_str("[]");
+            for (value, parsed_value) in original.iter().zip(parsed) {
+                unknown_fields(value, parsed_value, path, unknown);
+            }
+            path.truncate(len);
+        }
+        _ => (),
+    }
+}
+
+/// Upgrade the raw JSON of the document file at `path` to the current version and read it
+///
+/// Fields this build doesn't know, likely written by a newer one, are ignored with a warning
+fn decode(mut json: serde_json::Value, path: &Path) -> Result<DocumentFile, String> {
+    let serde_json::Value::Object(document) = &mut json else {
+        return Err("document should be a JSON object".to_owned());
+    };
+    migrate(document, &MIGRATIONS)?;
+    let file = DocumentFile::deserialize(&json).map_err(|e| e.to_string())?;
+    let parsed = serde_json::to_value(&file).map_err(|e| e.to_string())?;
+    let mut unknown = BTreeSet::new();
+    unknown_fields(&json, &parsed, &mut String::new(), &mut unknown);
+    for field in unknown {
+        eprintln!("warning: ignoring unknown field `{field}` in {}", path.display());
+    }
+    Ok(file)
+}
+
+/// Hash of the size and pixels of `image`, to tell whether it has changed since it was saved
+fn image_hash(image: &Image) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    hasher.write_i32(image.width);
+    hasher.write_i32(image.height);
+    for color in image.get_image_data().iter() {
+        hasher.write(&[color.r, color.g, color.b, color.a]);
+    }
+    hasher.finish()
+}
+
+/// Flattens the reference graph of a document into index tables
+///
+/// Resources are deduplicated by identity, and only resources reachable from
+/// the encoded layers are included
+#[derive(Default)]
+struct Encoder {
+    raster_ids: HashMap<*const (), usize>,
+    rasters: Vec<StrongRenderTexture2D>,
+    width_profile_ids: HashMap<*const (), usize>,
+    width_profiles: Vec<WidthProfile>,
+    style_ids: HashMap<*const (), usize>,
+    styles: Vec<StyleFile>,
+    curve_ids: HashMap<*const (), usize>,
+    curves: Vec<CurveFile>,
+    /// Paint layer tiles are never shared, so they are downloaded as they are encountered
+    tiles: Vec<Image>,
+}
+
+impl Encoder {
+    fn raster(&mut self, raster: &WeakRenderTexture2D) -> Result<usize, String> {
+        let key = raster.as_ptr() as *const ();
+        if let Some(&id) = self.raster_ids.get(&key) {
+            return Ok(id);
+        }
+        let strong = raster.upgrade().ok_or("raster reference is dead")?;
+        let id = self.rasters.len();
+        self.rasters.push(strong);
+        self.raster_ids.insert(key, id);
+        Ok(id)
+    }
+
+    fn width_profile(&mut self, profile: &WeakWidthProfile) -> Result<usize, String> {
+        let key = profile.as_ptr() as *const ();
+        if let Some(&id) = self.width_profile_ids.get(&key) {
+            return Ok(id);
+        }
+        let strong = profile.upgrade().ok_or("width profile reference is dead")?;
+        let id = self.width_profiles.len();
+        self.width_profiles.push(strong.lock().borrow().clone());
+        self.width_profile_ids.insert(key, id);
+        Ok(id)
+    }
+
+    fn pattern(&mut self, pattern: &Pattern) -> Result<PatternFile, String> {
+        Ok(match pattern {
+            Pattern::Solid(color) => PatternFile::Solid(*color),
+            Pattern::Texture(raster, transform) if *transform == TextureTransform::IDENTITY => PatternFile::Texture(self.raster(raster)?),
+            Pattern::Texture(raster, transform) => PatternFile::TransformedTexture { raster: self.raster(raster)?, transform: *transform },
+        })
+    }
+
+    fn stroke(&mut self, stroke: &Stroke) -> Result<StrokeFile, String> {
+        Ok(StrokeFile {
+            pattern: self.pattern(&stroke.pattern)?,
+            width: stroke.width.as_ref().map(|width| self.width_profile(width)).transpose()?,
+            trim: stroke.trim,
+            markers: stroke.markers.clone(),
+        })
+    }
+
+    fn style(&mut self, style: &WeakStyle) -> Result<usize, String> {
+        let key = style.as_ptr() as *const ();
+        if let Some(&id) = self.style_ids.get(&key) {
+            return Ok(id);
+        }
+        let strong = style.upgrade().ok_or("style reference is dead")?;
+        let style_lock = strong.lock();
+        let style = style_lock.borrow();
+        let file = StyleFile {
+            fill: self.pattern(&style.fill)?,
+            fill_rule: style.fill_rule,
+            stroke: self.stroke(&style.stroke)?,
+            items: style.items.iter()
+                .map(|item| -> Result<_, String> { Ok(StyleItemFile {
+                    name: item.name.clone(),
+                    modifier: match &item.modifier {
+                        Modifier::Fill(pattern) => ModifierFile::Fill(self.pattern(pattern)?),
+                        Modifier::Stroke(stroke) => ModifierFile::Stroke(self.stroke(stroke)?),
+                    },
+                }) })
+                .collect::<Result<_, String>>()?,
+        };
+        let id = self.styles.len();
+        self.styles.push(file);
+        self.style_ids.insert(key, id);
+        Ok(id)
+    }
+
+    fn curve(&mut self, curve: &WeakCurve) -> Result<usize, String> {
+        let key = curve.as_ptr() as *const ();
+        if let Some(&id) = self.curve_ids.get(&key) {
+            return Ok(id);
+        }
+        let strong = curve.upgrade().ok_or("curve reference is dead")?;
+        let curve_lock = strong.lock();
+        let curve = curve_lock.borrow();
+        let file = CurveFile {
+            points: curve.points.iter()
+                .map(|pp| [pp.c_in.into(), pp.p.into(), pp.c_out.into()])
+                .collect(),
+            is_closed: curve.is_closed,
+        };
+        let id = self.curves.len();
+        self.curves.push(file);
+        self.curve_ids.insert(key, id);
+        Ok(id)
+    }
+
+    fn connector_end(&mut self, end: &ConnectorEnd) -> Result<ConnectorEndFile, String> {
+        Ok(match end {
+            ConnectorEnd::Free => ConnectorEndFile::Free,
+            ConnectorEnd::Anchor { curve, index } => ConnectorEndFile::Anchor { curve: self.curve(curve)?, index: *index },
+            ConnectorEnd::Bounds(curves) => ConnectorEndFile::Bounds(
+                curves.iter()
+                    .map(|curve| self.curve(curve))
+                    .collect::<Result<_, String>>()?
+            ),
+        })
+    }
+
+    fn layer(&mut self, layer: &Layer) -> Result<LayerFile, String> {
+        Ok(LayerFile {
+            name: layer.name.clone(),
+            content: match &layer.content {
+                LayerContent::Group(group) => LayerContentFile::Group(
+                    group.layers.iter()
+                        .map(|layer| self.layer(layer))
+                        .collect::<Result<_, String>>()?
+                ),
+                LayerContent::Curve(curve) => LayerContentFile::Curve(self.curve(curve)?),
+                LayerContent::Compound(compound) => LayerContentFile::Compound(
+                    compound.subpaths.iter()
+                        .map(|subpath| self.curve(subpath))
+                        .collect::<Result<_, String>>()?
+                ),
+                LayerContent::Connector(connector) => LayerContentFile::Connector {
+                    curve: self.curve(&connector.curve)?,
+                    start: self.connector_end(&connector.start)?,
+                    end: self.connector_end(&connector.end)?,
+                    routing: connector.routing,
+                },
+                LayerContent::Rectangle(rectangle) => LayerContentFile::Rectangle {
+                    curve: self.curve(&rectangle.curve)?,
+                    rect: rectangle.rect,
+                    radii: rectangle.radii,
+                },
+                LayerContent::Image { raster, rect } => LayerContentFile::Image { raster: self.raster(raster)?, rect: *rect },
+                LayerContent::Paint(paint) => LayerContentFile::Paint {
+                    origin: [paint.origin.x, paint.origin.y],
+                    tiles: paint.tiles.iter()
+                        .map(|(&(x, y), tile)| -> Result<_, String> {
+                            let id = self.tiles.len();
+                            self.tiles.push(raster::image_from_render_texture(tile)?);
+                            Ok(([x, y], id))
+                        })
+                        .collect::<Result<_, String>>()?,
+                },
+            },
+            style: self.style(&layer.style)?,
+            export: layer.export.clone(),
+            is_template: layer.is_template,
+        })
+    }
+}
+
+/// Whether the document at `path` is saved as a [package][`PACKAGE_EXTENSION`]
+pub fn is_package(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case(PACKAGE_EXTENSION))
+}
+
+/// The folder raster images of the document at `path` are written to
+fn assets_directory(path: &Path) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    path.with_file_name(format!("{stem}_assets"))
+}
+
+/// Where autosaves of the document at `path` are written: beside it, as `<name>.autosave.<extension>`
+///
+/// Autosaves never replace the document itself, so it stays as the user last saved it
+pub fn autosave_path(path: &Path) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let extension = path.extension().unwrap_or_default().to_string_lossy();
+    path.with_file_name(format!("{stem}.autosave.{extension}"))
+}
+
+/// Delete the document at `path` and its [assets folder][`assets_directory`], if they exist
+pub fn remove(path: &Path) -> Result<(), String> {
+    for result in [std::fs::remove_file(path), std::fs::remove_dir_all(assets_directory(path))] {
+        match result {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.to_string()),
+            _ => (),
+        }
+    }
+    Ok(())
+}
+
+/// A native document encoded on the main thread, ready to be [written][`EncodedDocument::write`] from any thread
+///
+/// Downloading rasters from the GPU needs its context, but serializing and writing the files, the slow part, doesn't
+pub struct EncodedDocument {
+    path: PathBuf,
+    file: DocumentFile,
+    /// Rasters and paint tiles, each with the path it is written to
+    images: Vec<(PathBuf, SendImage)>,
+    /// Whether the document is compressed within its [package][`PACKAGE_EXTENSION`]
+    is_compressed: bool,
+}
+
+impl EncodedDocument {
+    /// Write the document and its images, calling `progress` with the fraction done after each file
+    ///
+    /// Only the chunks of the document and the images that changed since it was last saved to
+    /// the same path are written; see [`ChunkFile`]
+    pub fn write(mut self, mut progress: impl FnMut(f32)) -> Result<(), String> {
+        if is_package(&self.path) {
+            return self.write_package(progress);
+        }
+        let total = self.images.len() as f32 + 1.0;
+        let chunk_file = ChunkFile::open(&self.path)?;
+        let saved_images = chunk_file.chunk::<SavedImages>(HEADER_CHUNK)
+            .map(|saved| saved.rasters.into_iter().chain(saved.tiles).zip(saved.image_hashes).collect::<HashMap<_, _>>())
+            .unwrap_or_default();
+        if let Some(directory) = self.images.first().and_then(|(path, _)| path.parent()) {
+            std::fs::create_dir_all(directory).map_err(|e| e.to_string())?;
+        }
+        let mut image_hashes = Vec::with_capacity(self.images.len());
+        let relative_paths = self.file.rasters.iter().chain(&self.file.tiles);
+        for (i, ((path, image), relative_path)) in self.images.iter().zip(relative_paths).enumerate() {
+            let hash = image_hash(&image.0);
+            if saved_images.get(relative_path) != Some(&hash) || !path.exists() {
+                image.0.export_image(path.to_str().ok_or("asset path should be valid UTF-8")?);
+            }
+            image_hashes.push(hash);
+            progress((i as f32 + 1.0) / total);
+        }
+        self.file.image_hashes = image_hashes;
+        chunk_file.write(&to_chunks(&self.file)?)?;
+        progress(1.0);
+        Ok(())
+    }
+
+    /// Write the document and its images into a single [package][`PACKAGE_EXTENSION`], replacing the file only once it is complete
+    fn write_package(mut self, mut progress: impl FnMut(f32)) -> Result<(), String> {
+        let total = self.images.len() as f32 + 1.0;
+        let mut zip = ZipWriter::default();
+        let mut names = Vec::with_capacity(self.images.len());
+        for (i, (path, image)) in self.images.iter().enumerate() {
+            let name = format!("{PACKAGE_ASSETS}/{}", path.file_name().unwrap_or_default().to_string_lossy());
+            zip.add(&name, &raster::encode_png(&image.0)?);
+            names.push(PathBuf::from(name));
+            progress((i as f32 + 1.0) / total);
+        }
+        self.file.tiles = names.split_off(self.file.rasters.len());
+        self.file.rasters = names;
+        let json = serde_json::to_vec(&self.file).map_err(|e| e.to_string())?;
+        if self.is_compressed {
+            zip.add_compressed(PACKAGE_DOCUMENT, &json);
+        } else {
+            zip.add(PACKAGE_DOCUMENT, &json);
+        }
+
+        let name = self.path.file_name().unwrap_or_default().to_string_lossy();
+        let temp_path = self.path.with_file_name(format!("{name}.tmp"));
+        std::fs::write(&temp_path, zip.finish()).map_err(|e| e.to_string())?;
+        std::fs::rename(&temp_path, &self.path).map_err(|e| e.to_string())?;
+        progress(1.0);
+        Ok(())
+    }
+}
+
+/// Encode `layers` along with every resource they reference, to be written to `path`
+///
+/// `default_style` is kept even if none of `layers` use it
+fn encode_layers(document: &Document, layers: &[Layer], artboards: &[Artboard], default_style: Option<&WeakStyle>, path: &Path, is_compressed: bool) -> Result<EncodedDocument, String> {
+    let mut encoder = Encoder::default();
+    let layers = layers.iter()
+        .map(|layer| encoder.layer(layer))
+        .collect::<Result<Vec<_>, String>>()?;
+    let default_style = default_style
+        .filter(|style| style.strong_count() > 0)
+        .map(|style| encoder.style(style))
+        .transpose()?;
+
+    let directory = assets_directory(path);
+    let mut images = Vec::with_capacity(encoder.rasters.len().saturating_add(encoder.tiles.len()));
+
+    let mut rasters = Vec::with_capacity(encoder.rasters.len());
+    for (i, raster) in encoder.rasters.iter().enumerate() {
+        let raster_lock = raster.lock();
+        let mut image = raster_lock.borrow().texture().load_image()?;
+        // render textures are stored upside-down
+        image.flip_vertical();
+        let file_name = PathBuf::from(format!("raster_{i}.png"));
+        images.push((directory.join(&file_name), SendImage(image)));
+        rasters.push(PathBuf::from(directory.file_name().unwrap_or_default()).join(file_name));
+    }
+
+    let mut tiles = Vec::with_capacity(encoder.tiles.len());
+    for (i, image) in std::mem::take(&mut encoder.tiles).into_iter().enumerate() {
+        let file_name = PathBuf::from(format!("tile_{i}.png"));
+        images.push((directory.join(&file_name), SendImage(image)));
+        tiles.push(PathBuf::from(directory.file_name().unwrap_or_default()).join(file_name));
+    }
+
+    let file = DocumentFile {
+        version: VERSION,
+        title: document.title.clone(),
+        info: document.info.clone(),
+        paper_color: document.paper_color,
+        rasters,
+        tiles,
+        width_profiles: encoder.width_profiles,
+        styles: encoder.styles,
+        curves: encoder.curves,
+        layers,
+        artboards: artboards.iter()
+            .map(|artboard| ArtboardFile { name: artboard.name.clone(), rect: artboard.rect, background: artboard.background })
+            .collect(),
+        animation: document.animation,
+        // links to styles that no saved layer uses are dropped along with the style
+        linked_styles: document.linked_styles.iter()
+            .filter_map(|(name, style)| Some((name.clone(), *encoder.style_ids.get(&(style.as_ptr() as *const ()))?)))
+            .collect(),
+        default_style,
+        export_presets: document.export_presets.clone(),
+        last_export: document.last_export.clone(),
+        swatches: document.swatches.clone(),
+        // filled in as the images are written
+        image_hashes: Vec::new(),
+        // views and annotations only belong to the whole document, not snippets of it
+        views: Vec::new(),
+        annotations: Vec::new(),
+    };
+    Ok(EncodedDocument { path: path.to_path_buf(), file, images, is_compressed })
+}
+
+/// Write `layers` along with every resource they reference to `path`
+fn save_layers(document: &Document, layers: &[Layer], artboards: &[Artboard], path: &Path) -> Result<(), String> {
+    encode_layers(document, layers, artboards, None, path, false)?.write(|_| ())
+}
+
+/// Encode the whole document to be written to `path` in the native format, for saving on a background thread
+///
+/// Rasters are written as PNG files in a `<name>_assets` folder next to the document, or inside it if it is
+/// a [package][`PACKAGE_EXTENSION`], in which case `is_compressed` compresses the rest of the document too.
+/// Doesn't update the document's modified time or file path, which is up to the caller
+pub fn encode(document: &Document, path: &Path, is_compressed: bool) -> Result<EncodedDocument, String> {
+    let mut encoded = encode_layers(document, &document.layers, &document.artboards, document.default_style.as_ref(), path, is_compressed)?;
+    encoded.file.views = document.views.clone();
+    encoded.file.annotations = document.annotations.clone();
+    Ok(encoded)
+}
+
+/// Write a single layer (usually a group) and the resources it references to `path`
+///
+/// The resulting document has one artboard framing the layer, named after it
+pub fn save_snippet(document: &Document, layer: &Layer, path: &Path) -> Result<(), String> {
+    let bounds = layer.control_bounds().unwrap_or(Rectangle::new(0.0, 0.0, 0.0, 0.0));
+    save_layers(document, std::slice::from_ref(layer), &[Artboard::new(layer.name.clone(), bounds)], path)
+}
+
+/// Write `layers` and the resources they reference to `path`, without any artboards
+///
+/// Used for pieces of a document that get merged into another with [`Document::append`]
+pub fn save_fragment(document: &Document, layers: &[Layer], path: &Path) -> Result<(), String> {
+    save_layers(document, layers, &[], path)
+}
+
+/// Encode a document that has no images, like one just imported, into data that can be sent to another thread
+/// and [built][`build`] there; `path` is where it came from
+///
+/// Errors if the document has rasters or paint tiles, since downloading them needs the GPU context
+pub fn detach(document: &Document, path: &Path) -> Result<DocumentData, String> {
+    if !document.rasters.is_empty() {
+        return Err("documents with rasters can't be sent between threads".to_owned());
+    }
+    let encoded = encode(document, path, false)?;
+    if !encoded.images.is_empty() {
+        return Err("documents with paint tiles can't be sent between threads".to_owned());
+    }
+    Ok(DocumentData { path: encoded.path, file: encoded.file, rasters: Vec::new(), tiles: Vec::new() })
+}
+
+/// A native document read from disk with its images decoded, ready to be [built][`build`] into a [`Document`]
+pub struct DocumentData {
+    path: PathBuf,
+    file: DocumentFile,
+    rasters: Vec<SendImage>,
+    tiles: Vec<SendImage>,
+}
+
+/// Read a document in the native format from `path` and decode its images, calling `progress` with the fraction done after each file
+///
+/// Needs no GPU context, so it can run on a background thread
+pub fn read(path: &Path, mut progress: impl FnMut(f32)) -> Result<DocumentData, String> {
+    let bytes = std::fs::read(path).map_err(|e| e.to_string())?;
+    let package = bytes.starts_with(zip::LOCAL_HEADER_SIGNATURE)
+        .then(|| ZipArchive::parse(&bytes))
+        .transpose()?;
+    let json = match &package {
+        Some(package) => serde_json::from_slice(&package.file(PACKAGE_DOCUMENT)?).map_err(|e| e.to_string())?,
+        None => {
+            let text = std::str::from_utf8(&bytes).map_err(|e| e.to_string())?;
+            match chunks::parse(text)? {
+                Some(chunks) => from_chunks(chunks)?,
+                // documents saved before saves were incremental are a single JSON object
+                None => serde_json::from_str(text).map_err(|e| e.to_string())?,
+            }
+        }
+    };
+    let file = decode(json, path)?;
+
+    let directory = path.parent().unwrap_or(Path::new("."));
+    let total = file.rasters.len().saturating_add(file.tiles.len()) as f32 + 1.0;
+    progress(1.0 / total);
+    let mut rasters = Vec::with_capacity(file.rasters.len().saturating_add(file.tiles.len()));
+    for (i, image_path) in file.rasters.iter().chain(&file.tiles).enumerate() {
+        let image = match &package {
+            Some(package) => Image::load_image_from_mem(".png", &package.file(&image_path.to_string_lossy())?)?,
+            None => Image::load_image(directory.join(image_path).to_str().ok_or("asset path should be valid UTF-8")?)?,
+        };
+        rasters.push(SendImage(image));
+        progress((i as f32 + 2.0) / total);
+    }
+    let tiles = rasters.split_off(file.rasters.len());
+    Ok(DocumentData { path: path.to_path_buf(), file, rasters, tiles })
+}
+
+/// Read a document in the native format from `path`
+///
+/// Rasters are uploaded to the GPU, which is why the raylib handle is needed
+pub fn load(rl: &mut RaylibHandle, thread: &RaylibThread, path: &Path) -> Result<Document, String> {
+    build(rl, thread, read(path, |_| ())?)
+}
+
+/// Build a document [read][`read`] from disk, uploading its rasters to the GPU
+pub fn build(rl: &mut RaylibHandle, thread: &RaylibThread, data: DocumentData) -> Result<Document, String> {
+    build_with(data, |image| raster::render_texture_from_image(rl, thread, image))
+}
+
+/// Build a document [read][`read`] from disk without a GPU context
+///
+/// Errors if the document has any rasters or paint tiles, since they can't be uploaded
+pub fn build_headless(data: DocumentData) -> Result<Document, String> {
+    build_with(data, |_| Err("rasters can't be loaded without a GPU context".to_owned()))
+}
+
+/// Build a document [read][`read`] from disk, turning its images into render textures with `upload`
+fn build_with(data: DocumentData, mut upload: impl FnMut(&Image) -> Result<RenderTexture2D, String>) -> Result<Document, String> {
+    fn pattern(file: PatternFile, rasters: &[WeakRenderTexture2D]) -> Result<Pattern, String> {
+        Ok(match file {
+            PatternFile::Solid(color) => Pattern::Solid(color),
+            PatternFile::Texture(id) => Pattern::Texture(rasters.get(id).ok_or("raster index out of bounds")?.clone(), TextureTransform::IDENTITY),
+            PatternFile::TransformedTexture { raster, transform } => Pattern::Texture(rasters.get(raster).ok_or("raster index out of bounds")?.clone(), transform),
+        })
+    }
+
+    fn stroke(file: StrokeFile, rasters: &[WeakRenderTexture2D], width_profiles: &[WeakWidthProfile]) -> Result<Stroke, String> {
+        Ok(Stroke {
+            pattern: pattern(file.pattern, rasters)?,
+            width: file.width
+                .map(|id| width_profiles.get(id).cloned().ok_or("width profile index out of bounds"))
+                .transpose()?,
+            trim: file.trim,
+            markers: file.markers,
+        })
+    }
+
+    fn connector_end(file: ConnectorEndFile, curves: &[WeakCurve]) -> Result<ConnectorEnd, String> {
+        Ok(match file {
+            ConnectorEndFile::Free => ConnectorEnd::Free,
+            ConnectorEndFile::Anchor { curve, index } => ConnectorEnd::Anchor {
+                curve: curves.get(curve).ok_or("curve index out of bounds")?.clone(),
+                index,
+            },
+            ConnectorEndFile::Bounds(ids) => ConnectorEnd::Bounds(
+                ids.into_iter()
+                    .map(|id| curves.get(id).cloned().ok_or("curve index out of bounds"))
+                    .collect::<Result<_, _>>()?
+            ),
+        })
+    }
+
+    fn layer(file: LayerFile, rasters: &[WeakRenderTexture2D], tiles: &mut [Option<RenderTexture2D>], styles: &[WeakStyle], curves: &[WeakCurve]) -> Result<Layer, String> {
+        Ok(Layer {
+            name: file.name,
+            content: match file.content {
+                LayerContentFile::Group(layers) => LayerContent::Group(Group {
+                    layers: layers.into_iter()
+                        .map(|file| layer(file, rasters, tiles, styles, curves))
+                        .collect::<Result<_, String>>()?,
+                }),
+                LayerContentFile::Curve(id) => LayerContent::Curve(curves.get(id).ok_or("curve index out of bounds")?.clone()),
+                LayerContentFile::Compound(ids) => LayerContent::Compound(CompoundPath {
+                    subpaths: ids.into_iter()
+                        .map(|id| curves.get(id).cloned().ok_or("curve index out of bounds"))
+                        .collect::<Result<_, _>>()?,
+                }),
+                LayerContentFile::Connector { curve, start, end, routing } => LayerContent::Connector(Connector {
+                    curve: curves.get(curve).ok_or("curve index out of bounds")?.clone(),
+                    start: connector_end(start, curves)?,
+                    end: connector_end(end, curves)?,
+                    routing,
+                }),
+                LayerContentFile::Rectangle { curve, rect, radii } => LayerContent::Rectangle(LiveRectangle {
+                    curve: curves.get(curve).ok_or("curve index out of bounds")?.clone(),
+                    rect,
+                    radii,
+                }),
+                LayerContentFile::Image { raster, rect } => LayerContent::Image {
+                    raster: rasters.get(raster).ok_or("raster index out of bounds")?.clone(),
+                    rect,
+                },
+                LayerContentFile::Paint { origin: [x, y], tiles: paint_tiles } => {
+                    let mut paint = TiledRaster::new(Vector2::new(x, y));
+                    for ([x, y], id) in paint_tiles {
+                        let tile = tiles.get_mut(id)
+                            .ok_or("tile index out of bounds")?
+                            .take()
+                            .ok_or("tile is used by multiple layers")?;
+                        paint.tiles.insert((x, y), tile);
+                    }
+                    LayerContent::Paint(paint)
+                }
+            },
+            style: styles.get(file.style).ok_or("style index out of bounds")?.clone(),
+            export: file.export,
+            is_template: file.is_template,
+        })
+    }
+
+    let DocumentData { path, file, rasters, tiles } = data;
+    let mut document = Document::new(file.title);
+    document.file_path = Some(path);
+    document.info = file.info;
+    document.paper_color = file.paper_color;
+    document.animation = file.animation;
+    document.export_presets = file.export_presets;
+    document.last_export = file.last_export;
+    document.swatches = file.swatches;
+    document.views = file.views;
+    document.annotations = file.annotations;
+
+    let rasters = rasters.into_iter()
+        .map(|image| -> Result<_, String> {
+            Ok(Arc::downgrade(document.create_raster(upload(&image.0)?)))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    let mut tiles = tiles.into_iter()
+        .map(|image| -> Result<_, String> {
+            Ok(Some(upload(&image.0)?))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    let width_profiles = file.width_profiles.into_iter()
+        .map(|profile| Arc::downgrade(document.create_width_profile(profile)))
+        .collect::<Vec<_>>();
+
+    let styles = file.styles.into_iter()
+        .map(|style| -> Result<_, String> {
+            let style = Style {
+                fill: pattern(style.fill, &rasters)?,
+                fill_rule: style.fill_rule,
+                stroke: stroke(style.stroke, &rasters, &width_profiles)?,
+                items: style.items.into_iter()
+                    .map(|item| -> Result<_, String> { Ok(StyleItem {
+                        name: item.name,
+                        modifier: match item.modifier {
+                            ModifierFile::Fill(file) => Modifier::Fill(pattern(file, &rasters)?),
+                            ModifierFile::Stroke(file) => Modifier::Stroke(stroke(file, &rasters, &width_profiles)?),
+                        },
+                    }) })
+                    .collect::<Result<_, String>>()?,
+            };
+            Ok(Arc::downgrade(document.create_style(style)))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    let curves = file.curves.into_iter()
+        .map(|curve| {
+            let curve = Curve {
+                points: curve.points.into_iter()
+                    .map(|[c_in, p, c_out]| CurvePoint { c_in: c_in.into(), p: p.into(), c_out: c_out.into() })
+                    .collect(),
+                is_closed: curve.is_closed,
+            };
+            Arc::downgrade(document.create_curve(curve))
+        })
+        .collect::<Vec<Weak<_>>>();
+
+    document.layers = file.layers.into_iter()
+        .map(|file| layer(file, &rasters, &mut tiles, &styles, &curves))
+        .collect::<Result<_, String>>()?;
+
+    document.linked_styles = file.linked_styles.into_iter()
+        .map(|(name, id)| -> Result<_, String> { Ok((name, styles.get(id).ok_or("style index out of bounds")?.clone())) })
+        .collect::<Result<_, String>>()?;
+
+    document.default_style = file.default_style
+        .map(|id| styles.get(id).cloned().ok_or("style index out of bounds"))
+        .transpose()?;
+
+    document.artboards = file.artboards.into_iter()
+        .map(|artboard| Artboard { background: artboard.background, ..Artboard::new(artboard.name, artboard.rect) })
+        .collect();
+
+    Ok(document)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migrate() {
+        let migrations: [Migration; 2] = [
+            |document| {
+                let title = document.remove("name").ok_or("version 1 documents have a name")?;
+                document.insert("title".to_owned(), title);
+                Ok(())
+            },
+            |document| {
+                document.insert("paper_color".to_owned(), serde_json::json!([255, 255, 255, 255]));
+                Ok(())
+            },
+        ];
+        let serde_json::Value::Object(mut document) = serde_json::json!({ "version": 1, "name": "old" }) else { unreachable!() };
+        migrate(&mut document, &migrations).unwrap();
+        assert_eq!(serde_json::Value::Object(document), serde_json::json!({ "version": 3, "title": "old", "paper_color": [255, 255, 255, 255] }));
+
+        // only the migrations after the saved version run
+        let serde_json::Value::Object(mut document) = serde_json::json!({ "version": 2, "title": "new" }) else { unreachable!() };
+        migrate(&mut document, &migrations).unwrap();
+        assert_eq!(document.get("title"), Some(&serde_json::json!("new")));
+
+        let serde_json::Value::Object(mut document) = serde_json::json!({ "version": 4 }) else { unreachable!() };
+        assert!(migrate(&mut document, &migrations).is_err(), "documents from newer builds should be refused");
+    }
+
+    #[test]
+    fn test_unknown_fields() {
+        let original = serde_json::json!({ "a": 1, "b": [{ "c": 2, "d": 3 }, { "c": 4, "d": 5 }], "e": null, "f": {} });
+        let parsed = serde_json::json!({ "a": 1, "b": [{ "c": 2 }, { "c": 4 }] });
+        let mut unknown = BTreeSet::new();
+        unknown_fields(&original, &parsed, &mut String::new(), &mut unknown);
+        assert_eq!(unknown.into_iter().collect::<Vec<_>>(), ["b[].d", "f"]);
+    }
+}
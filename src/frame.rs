@@ -0,0 +1,281 @@
+use std::{sync::{atomic::{AtomicU64, Ordering}, Arc}, time::Duration};
+use raylib::prelude::*;
+use crate::preferences::DisplayPreferences;
+
+/// OpenGL blend factors, for [`CanvasCache::draw`]
+const GL_ZERO: i32 = 0;
+const GL_ONE: i32 = 1;
+const GL_FUNC_ADD: i32 = 0x8006;
+
+unsafe extern "C" {
+    /// Wake the thread blocked in `glfwWaitEvents`, which is where raylib sleeps while [event waiting][`RaylibDraw::enable_event_waiting`]
+    ///
+    /// GLFW allows calling it from any thread. raylib doesn't expose it, but builds GLFW into itself on desktop
+    fn glfwPostEmptyEvent();
+}
+
+/// Decides when the window can sleep until the next input event instead of drawing frames nothing has changed in
+///
+/// Frames are skipped altogether while sleeping; while awake, the [`CanvasCache`] skips redrawing the artwork
+/// in frames that only change the interface over it. Input wakes the window, and drawing continues for
+/// [`FrameScheduler::SETTLE_TIME`] afterwards so that anything following input with a delay (tooltips, the
+/// navigator thumbnail) catches up before it sleeps again. Anything changing on its own, like animation playback
+/// or an export, [keeps it awake][`FrameScheduler::keep_awake`], and timers that run out while it sleeps, like
+/// autosave, [wake it][`FrameScheduler::wake_after`]
+///
+/// While awake, the frame rate follows the [display preferences][`DisplayPreferences`]; see [`FrameScheduler::apply_frame_rate`]
+#[derive(Debug)]
+pub struct FrameScheduler {
+    /// Seconds left to keep drawing after the window last woke up
+    awake_time: f32,
+
+    /// Whether something asked to keep drawing during this frame
+    is_busy: bool,
+
+    /// Whether something asked to keep drawing during the previous frame
+    was_busy: bool,
+
+    /// Seconds until the soonest timer asked to [wake the window][`FrameScheduler::wake_after`] during this frame
+    wake_time: Option<f32>,
+
+    /// Counts the frames, so a timer only wakes the window from the sleep it was started for
+    generation: Arc<AtomicU64>,
+
+    /// Whether the previous frame ended by waiting for input
+    is_waiting: bool,
+
+    /// The frame rate last given to raylib, [`None`] before the first frame
+    target_fps: Option<u32>,
+}
+
+impl FrameScheduler {
+    /// Seconds to keep drawing after the window wakes up
+    ///
+    /// Longer than [`Tooltip::DELAY`][`crate::tooltip::Tooltip::DELAY`] and
+    /// [`Navigator::REFRESH_INTERVAL`][`crate::navigator::Navigator::REFRESH_INTERVAL`], so both finish updating
+    pub const SETTLE_TIME: f32 = 1.0;
+
+    /// Construct a scheduler that draws the first frames without waiting
+    pub const fn new() -> Self {
+        Self {
+            awake_time: Self::SETTLE_TIME,
+            is_busy: false,
+            was_busy: false,
+            wake_time: None,
+            generation: Arc::new(AtomicU64::new(0)),
+            is_waiting: false,
+            target_fps: None,
+        }
+    }
+
+    /// Keep drawing without waiting for input through the end of this frame, for things that change on their own
+    ///
+    /// Call on every frame the thing is changing
+    #[inline]
+    pub fn keep_awake(&mut self) {
+        self.is_busy = true;
+    }
+
+    /// Make sure a frame is drawn within `seconds`, even if the window is sleeping, for timers that run out without input
+    ///
+    /// Call on every frame the timer is running
+    pub fn wake_after(&mut self, seconds: f32) {
+        let seconds = seconds.max(0.0);
+        self.wake_time = Some(self.wake_time.map_or(seconds, |wake_time| wake_time.min(seconds)));
+    }
+
+    /// Whether something [kept the window awake][`FrameScheduler::keep_awake`] during the previous frame
+    ///
+    /// Things that finish on their own, like opening a file, may change what's drawn in the frame after they stop asking
+    #[inline]
+    pub const fn was_busy(&self) -> bool {
+        self.was_busy
+    }
+
+    /// Whether input woke the window within the last [`FrameScheduler::SETTLE_TIME`]
+    #[inline]
+    pub fn is_interacting(&self) -> bool {
+        self.is_waiting || self.awake_time > 0.0
+    }
+
+    /// Cap the frame rate according to `display`, uncapping it while [interacting][`FrameScheduler::is_interacting`] if power saving
+    ///
+    /// Call once per frame, before drawing starts
+    pub fn apply_frame_rate(&mut self, rl: &mut RaylibHandle, display: &DisplayPreferences) {
+        let target_fps = if !display.is_power_saving {
+            display.target_fps.min(DisplayPreferences::MAX_FPS)
+        } else if self.is_interacting() {
+            0
+        } else {
+            display.idle_fps.clamp(DisplayPreferences::MIN_IDLE_FPS, DisplayPreferences::MAX_FPS)
+        };
+        if self.target_fps != Some(target_fps) {
+            // 0 uncaps raylib's frame rate
+            rl.set_target_fps(target_fps);
+            self.target_fps = Some(target_fps);
+        }
+    }
+
+    /// Decide whether drawing this frame ends by waiting for input, given the seconds `dt` the frame took
+    ///
+    /// Call once per frame, before the drawing handle is dropped
+    pub fn end_frame(&mut self, d: &impl RaylibDraw, dt: f32) {
+        if self.is_waiting {
+            // this frame was woken by input
+            self.awake_time = Self::SETTLE_TIME;
+        } else {
+            self.awake_time = (self.awake_time - dt).max(0.0);
+        }
+
+        let should_wait = !self.is_busy && self.awake_time <= 0.0;
+        if should_wait != self.is_waiting {
+            if should_wait {
+                d.enable_event_waiting();
+            } else {
+                d.disable_event_waiting();
+            }
+        }
+        // whatever ends the sleep, timers started before it are stale
+        let generation = self.generation.fetch_add(1, Ordering::Relaxed).wrapping_add(1);
+        if should_wait && let Some(seconds) = self.wake_time {
+            let current = Arc::clone(&self.generation);
+            std::thread::spawn(move || {
+                std::thread::sleep(Duration::from_secs_f32(seconds));
+                if current.load(Ordering::Relaxed) == generation {
+                    // SAFETY: the window and GLFW outlive the sleep this wakes, since the main loop is blocked in it
+                    unsafe { glfwPostEmptyEvent() };
+                }
+            });
+        }
+        self.is_waiting = should_wait;
+        self.was_busy = self.is_busy;
+        self.is_busy = false;
+        self.wake_time = None;
+    }
+}
+
+impl Default for FrameScheduler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Whether the user did anything since the previous frame: held or released a key or mouse button,
+/// moved or scrolled the mouse, touched the screen, dropped files, or resized the window
+pub fn has_input(rl: &RaylibHandle) -> bool {
+    /// raylib's `MAX_KEYBOARD_KEYS`, one past the largest key code
+    const KEY_CODES: i32 = 512;
+    /// One past the largest mouse button code, [`MouseButton::MOUSE_BUTTON_BACK`]
+    const MOUSE_BUTTONS: i32 = 7;
+
+    // SAFETY: only reads the input state raylib polled at the end of the previous frame; codes are within its arrays
+    let is_any_held = unsafe {
+        (0..KEY_CODES).any(|key| raylib::ffi::IsKeyDown(key) || raylib::ffi::IsKeyReleased(key))
+            || (0..MOUSE_BUTTONS).any(|button| raylib::ffi::IsMouseButtonDown(button) || raylib::ffi::IsMouseButtonReleased(button))
+    };
+    let wheel = rl.get_mouse_wheel_move_v();
+    is_any_held
+        || rl.get_mouse_delta() != Vector2::zero()
+        || wheel.x != 0.0
+        || wheel.y != 0.0
+        || rl.get_touch_point_count() > 0
+        || rl.is_file_dropped()
+        || rl.is_window_resized()
+}
+
+/// Where and how the canvas of a [`CanvasCache`] was drawn, besides the document itself
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CanvasView {
+    /// The index of the editor drawn, [`None`] if no editor is focused
+    pub editor: Option<u32>,
+
+    /// The number of open editors, which changes when a document finishes opening or one is closed
+    pub editor_count: usize,
+
+    /// The target, offset, rotation, and zoom of the editor's camera
+    pub camera: [f32; 6],
+
+    /// The size of the window, in pixels
+    pub size: (i32, i32),
+}
+
+impl CanvasView {
+    /// The view of the editor at `editor` through `camera`, in a window of `size`
+    pub fn new(editor: Option<u32>, editor_count: usize, camera: Option<Camera2D>, size: (i32, i32)) -> Self {
+        Self {
+            editor,
+            editor_count,
+            camera: camera.map_or([0.0; 6], |camera| [camera.target.x, camera.target.y, camera.offset.x, camera.offset.y, camera.rotation, camera.zoom]),
+            size,
+        }
+    }
+}
+
+/// The focused editor's canvas (the window background, artboard backgrounds, onion skin, and artwork) as last drawn,
+/// redrawn only when something may have changed it
+///
+/// Most frames drawn while the window is awake only change the interface over the canvas, like a tooltip appearing or
+/// the navigator catching up. Rather than tracking every edit, the canvas is invalidated by any [input][`has_input`],
+/// by anything having [kept the window awake][`FrameScheduler::was_busy`], and by its [view][`CanvasView`] changing.
+/// Invalidation lasts into the following frame, since input handled while drawing (like confirming a dialog)
+/// only shows on the canvas a frame later
+#[derive(Debug)]
+pub struct CanvasCache {
+    /// The canvas as last drawn, [`None`] before the first frame
+    texture: Option<RenderTexture2D>,
+
+    /// The view the canvas was last drawn from
+    view: Option<CanvasView>,
+
+    /// Whether the canvas was invalidated during the previous frame
+    was_invalidated: bool,
+}
+
+impl CanvasCache {
+    /// Construct a cache that draws the first canvas
+    pub const fn new() -> Self {
+        Self {
+            texture: None,
+            view: None,
+            was_invalidated: true,
+        }
+    }
+
+    /// The texture to redraw the canvas of `view` into, or [`None`] if the canvas drawn last is still current
+    ///
+    /// `is_invalidated` is whether anything happened this frame that may have changed the canvas.
+    /// The texture is remade whenever the window changes size
+    pub fn begin(&mut self, rl: &mut RaylibHandle, thread: &RaylibThread, view: CanvasView, is_invalidated: bool) -> Result<Option<&mut RenderTexture2D>, String> {
+        let is_stale = is_invalidated || self.was_invalidated || self.view != Some(view);
+        self.was_invalidated = is_invalidated;
+        self.view = Some(view);
+        // a minimized window has an empty view, but textures can't be
+        let (width, height) = (view.size.0.max(1), view.size.1.max(1));
+        if self.texture.as_ref().is_none_or(|texture| (texture.texture.width, texture.texture.height) != (width, height)) {
+            self.texture = Some(rl.load_render_texture(thread, width as u32, height as u32)?);
+        } else if !is_stale {
+            return Ok(None);
+        }
+        Ok(self.texture.as_mut())
+    }
+
+    /// Copy the canvas onto the window, replacing whatever was drawn there
+    pub fn draw(&self, d: &mut impl RaylibDraw) {
+        let Some(texture) = &self.texture else { return };
+        let (width, height) = (texture.texture.width as f32, texture.texture.height as f32);
+        // SAFETY: only changes the factors of the custom blend mode, which is reset by whoever uses it next
+        unsafe {
+            raylib::ffi::rlSetBlendFactors(GL_ONE, GL_ZERO, GL_FUNC_ADD);
+        }
+        // the canvas is opaque, but blending would fade it wherever translucent artwork lowered its alpha
+        let mut d = d.begin_blend_mode(BlendMode::BLEND_CUSTOM);
+        // render textures are stored upside down
+        d.draw_texture_rec(texture, Rectangle::new(0.0, 0.0, width, -height), Vector2::zero(), Color::WHITE);
+    }
+}
+
+impl Default for CanvasCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,283 @@
+use std::collections::HashMap;
+use raylib::prelude::*;
+
+/// The file extension of GIF files
+pub const EXTENSION: &str = "gif";
+
+/// Palette index of pixels less than half opaque; every frame's palette leaves it unused
+const TRANSPARENT: u8 = 255;
+
+/// Bits per palette index; every frame gets a full 256 color palette
+const MIN_CODE_SIZE: u8 = 8;
+
+/// Widest LZW code GIF allows, in bits
+const MAX_CODE_SIZE: u8 = 12;
+
+/// A frame of an animated GIF
+#[derive(Debug, Clone)]
+pub struct GifFrame {
+    pub width: u16,
+    pub height: u16,
+
+    /// Row-major, top to bottom
+    pub pixels: Vec<Color>,
+}
+
+impl GifFrame {
+    /// Construct a frame of `image`, failing if it is too large for GIF
+    pub fn from_image(image: &Image) -> Result<Self, String> {
+        Ok(Self {
+            width: u16::try_from(image.width()).map_err(|_| "image is too wide for GIF")?,
+            height: u16::try_from(image.height()).map_err(|_| "image is too tall for GIF")?,
+            pixels: image.get_image_data().to_vec(),
+        })
+    }
+}
+
+/// The delay between frames at `fps`, in the hundredths of a second GIF measures it in
+pub fn frame_delay(fps: f32) -> u16 {
+    (100.0 / fps.max(1.0)).round().max(1.0) as u16
+}
+
+/// Build a GIF that loops `frames` forever, showing each for `delay` hundredths of a second
+///
+/// Each frame is drawn at the top left of a canvas as large as the largest frame, replacing the frame before it.
+/// Frames of up to 255 colors keep their exact colors; frames with more are reduced to a fixed palette
+pub fn encode(frames: &[GifFrame], delay: u16) -> Vec<u8> {
+    let width = frames.iter().map(|frame| frame.width).max().unwrap_or(1);
+    let height = frames.iter().map(|frame| frame.height).max().unwrap_or(1);
+
+    let mut bytes = b"GIF89a".to_vec();
+    // logical screen: size, no global palette, background index, aspect ratio
+    bytes.extend(width.to_le_bytes());
+    bytes.extend(height.to_le_bytes());
+    bytes.extend([0, 0, 0]);
+    // NETSCAPE2.0 application extension, looping forever
+    bytes.extend([0x21, 0xFF, 0x0B]);
+    bytes.extend(b"NETSCAPE2.0");
+    bytes.extend([0x03, 0x01, 0x00, 0x00, 0x00]);
+
+    for frame in frames {
+        let (palette, indices) = quantize(&frame.pixels);
+        // graphic control extension: restore to background afterward, with a transparent index
+        bytes.extend([0x21, 0xF9, 0x04, (2 << 2) | 1]);
+        bytes.extend(delay.to_le_bytes());
+        bytes.extend([TRANSPARENT, 0x00]);
+        // image descriptor at the top left, with a 256 color local palette
+        bytes.push(0x2C);
+        bytes.extend([0, 0, 0, 0]);
+        bytes.extend(frame.width.to_le_bytes());
+        bytes.extend(frame.height.to_le_bytes());
+        bytes.push(0x80 | (MIN_CODE_SIZE - 1));
+        for i in 0..256 {
+            bytes.extend(palette.get(i).copied().unwrap_or([0; 3]));
+        }
+        bytes.push(MIN_CODE_SIZE);
+        for block in lzw_encode(&indices).chunks(255) {
+            bytes.push(block.len() as u8);
+            bytes.extend(block);
+        }
+        bytes.push(0x00);
+    }
+    bytes.push(0x3B);
+    bytes
+}
+
+/// The palette of `pixels` along with each pixel's index into it
+///
+/// Pixels less than half opaque get [`TRANSPARENT`]. If there are more than 255 other colors,
+/// they are reduced to a 6x7x6 color cube
+fn quantize(pixels: &[Color]) -> (Vec<[u8; 3]>, Vec<u8>) {
+    let mut palette = Vec::new();
+    let mut lookup = HashMap::<[u8; 3], u8>::new();
+    let mut indices = Vec::with_capacity(pixels.len());
+    for pixel in pixels {
+        if pixel.a < 128 {
+            indices.push(TRANSPARENT);
+            continue;
+        }
+        let rgb = [pixel.r, pixel.g, pixel.b];
+        if let Some(&index) = lookup.get(&rgb) {
+            indices.push(index);
+        } else if let Ok(index) = u8::try_from(palette.len()) && index != TRANSPARENT {
+            lookup.insert(rgb, index);
+            palette.push(rgb);
+            indices.push(index);
+        } else {
+            return cube_quantize(pixels);
+        }
+    }
+    (palette, indices)
+}
+
+/// The 252 color palette of a 6x7x6 color cube, along with the nearest color in it to each of `pixels`
+///
+/// Green gets the extra level since the eye is most sensitive to it
+fn cube_quantize(pixels: &[Color]) -> (Vec<[u8; 3]>, Vec<u8>) {
+    const LEVELS: [u8; 3] = [6, 7, 6];
+    let level = |value: u8, levels: u8| (u16::from(value) * u16::from(levels - 1) + 127) / 255;
+    let palette: Vec<[u8; 3]> = (0..LEVELS[0])
+        .flat_map(|r| (0..LEVELS[1]).flat_map(move |g| (0..LEVELS[2]).map(move |b| [r, g, b])))
+        .map(|rgb| std::array::from_fn(|i| (u16::from(rgb[i]) * 255 / u16::from(LEVELS[i] - 1)) as u8))
+        .collect();
+    let indices = pixels.iter()
+        .map(|pixel| if pixel.a < 128 {
+            TRANSPARENT
+        } else {
+            let (r, g, b) = (level(pixel.r, LEVELS[0]), level(pixel.g, LEVELS[1]), level(pixel.b, LEVELS[2]));
+            ((r * u16::from(LEVELS[1]) + g) * u16::from(LEVELS[2]) + b) as u8
+        })
+        .collect();
+    (palette, indices)
+}
+
+/// Packs codes into bytes, least significant bit first
+#[derive(Debug, Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    buffer: u32,
+    bits: u32,
+}
+
+impl BitWriter {
+    fn write(&mut self, code: u16, size: u8) {
+        self.buffer |= u32::from(code).wrapping_shl(self.bits);
+        self.bits = self.bits.saturating_add(u32::from(size));
+        while self.bits >= 8 {
+            self.bytes.push(self.buffer as u8);
+            self.buffer = self.buffer.wrapping_shr(8);
+            self.bits = self.bits.saturating_sub(8);
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.bits > 0 {
+            self.bytes.push(self.buffer as u8);
+        }
+        self.bytes
+    }
+}
+
+/// Compress palette `indices` with GIF's variable-width LZW, before splitting into sub-blocks
+///
+/// The code table is cleared whenever it fills up
+fn lzw_encode(indices: &[u8]) -> Vec<u8> {
+    let clear = 1u16.wrapping_shl(MIN_CODE_SIZE.into());
+    let end = clear.saturating_add(1);
+    let max_codes = 1u16.wrapping_shl(MAX_CODE_SIZE.into());
+    let mut writer = BitWriter::default();
+    let mut table = HashMap::<(u16, u8), u16>::new();
+    let mut code_size = MIN_CODE_SIZE.saturating_add(1);
+    let mut next_code = end.saturating_add(1);
+    writer.write(clear, code_size);
+
+    let mut indices = indices.iter().copied();
+    if let Some(first) = indices.next() {
+        let mut prefix = u16::from(first);
+        for index in indices {
+            if let Some(&code) = table.get(&(prefix, index)) {
+                prefix = code;
+                continue;
+            }
+            writer.write(prefix, code_size);
+            if next_code == max_codes {
+                writer.write(clear, code_size);
+                table.clear();
+                code_size = MIN_CODE_SIZE.saturating_add(1);
+                next_code = end.saturating_add(1);
+            } else {
+                table.insert((prefix, index), next_code);
+                // the decoder adds each code one code later, so it widens right as it reads past this one
+                if next_code == 1u16.wrapping_shl(code_size.into()) {
+                    code_size = code_size.saturating_add(1);
+                }
+                next_code = next_code.saturating_add(1);
+            }
+            prefix = u16::from(index);
+        }
+        writer.write(prefix, code_size);
+    }
+    writer.write(end, code_size);
+    writer.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Decompress LZW `data` the way a GIF decoder does
+    fn lzw_decode(data: &[u8]) -> Vec<u8> {
+        let clear = 1usize << MIN_CODE_SIZE;
+        let end = clear + 1;
+        let mut table = (0..=end).map(|i| vec![i as u8]).collect::<Vec<_>>();
+        let mut code_size = MIN_CODE_SIZE as usize + 1;
+        let mut previous: Option<Vec<u8>> = None;
+        let mut output = Vec::new();
+        let mut bit = 0;
+        while bit + code_size <= data.len() * 8 {
+            let code = (0..code_size).fold(0, |code, i| code | ((usize::from(data[(bit + i) / 8]) >> ((bit + i) % 8)) & 1) << i);
+            bit += code_size;
+            if code == clear {
+                table.truncate(end + 1);
+                code_size = MIN_CODE_SIZE as usize + 1;
+                previous = None;
+                continue;
+            }
+            if code == end {
+                break;
+            }
+            let entry = match (table.get(code), &previous) {
+                (Some(entry), _) => entry.clone(),
+                (None, Some(previous)) => [previous.as_slice(), &previous[..1]].concat(),
+                (None, None) => panic!("code {code} is not in the table"),
+            };
+            if let Some(mut previous) = previous {
+                previous.push(entry[0]);
+                table.push(previous);
+                if table.len() == 1 << code_size && code_size < MAX_CODE_SIZE as usize {
+                    code_size += 1;
+                }
+            }
+            output.extend_from_slice(&entry);
+            previous = Some(entry);
+        }
+        output
+    }
+
+    #[test]
+    fn test_lzw_round_trip() {
+        let repetitive = (0..20_000).map(|i| (i % 7) as u8).collect::<Vec<_>>();
+        // pseudo-random indices fill the code table, so it gets cleared several times
+        let noisy = (0u32..50_000).map(|i| (i.wrapping_mul(2_654_435_761) >> 24) as u8).collect::<Vec<_>>();
+        for indices in [vec![], vec![3], vec![5, 5, 5, 5, 5, 5, 5], repetitive, noisy] {
+            assert_eq!(lzw_decode(&lzw_encode(&indices)), indices);
+        }
+    }
+
+    #[test]
+    fn test_quantize() {
+        let red = Color::new(255, 0, 0, 255);
+        let blue = Color::new(0, 0, 255, 255);
+        let (palette, indices) = quantize(&[red, blue, Color::BLANK, red]);
+        assert_eq!(palette, [[255, 0, 0], [0, 0, 255]]);
+        assert_eq!(indices, [0, 1, TRANSPARENT, 0]);
+
+        let gradient = (0..=255).flat_map(|r| [Color::new(r, 0, 0, 255), Color::new(r, 255, 0, 255)]).collect::<Vec<_>>();
+        let (palette, indices) = quantize(&gradient);
+        assert_eq!(palette.len(), 6 * 7 * 6);
+        assert_eq!(palette[indices[0] as usize], [0, 0, 0]);
+        assert_eq!(palette[indices[511] as usize], [255, 255, 0]);
+        assert!(indices.iter().all(|&index| index != TRANSPARENT));
+    }
+
+    #[test]
+    fn test_encode() {
+        let frame = |width: u16, height: u16, color| GifFrame { width, height, pixels: vec![color; usize::from(width * height)] };
+        let gif = encode(&[frame(4, 2, Color::RED), frame(3, 5, Color::BLUE)], frame_delay(12.0));
+        assert!(gif.starts_with(b"GIF89a"));
+        assert_eq!(&gif[6..10], [4, 0, 5, 0]);
+        assert_eq!(gif.last(), Some(&0x3B));
+        assert_eq!(frame_delay(12.0), 8);
+        assert_eq!(frame_delay(60.0), 2);
+    }
+}
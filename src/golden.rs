@@ -0,0 +1,139 @@
+use std::{path::PathBuf, sync::{Mutex, MutexGuard}};
+use raylib::prelude::*;
+use crate::document::{Artboard, Document};
+
+/// Folder the reference images are kept in
+const GOLDEN_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/golden");
+
+/// Set this environment variable to overwrite the reference images with the current renderings
+const UPDATE_VAR: &str = "UPDATE_GOLDEN";
+
+/// Folder mismatched renderings are saved to for inspection, kept out of the source tree
+fn actual_dir() -> PathBuf {
+    std::env::temp_dir().join("vector-art-program-1-golden")
+}
+
+/// How far a rendering may stray from its reference and still match
+#[derive(Debug, Clone, Copy)]
+pub struct Tolerance {
+    /// The largest difference in any one channel for a pixel to still count as the same
+    pub channel: u8,
+
+    /// The fraction of pixels allowed to differ, to absorb antialiasing differences between GPU drivers
+    pub pixels: f32,
+}
+
+impl Default for Tolerance {
+    fn default() -> Self {
+        Self { channel: 8, pixels: 0.002 }
+    }
+}
+
+/// Ensure only one test can have a window open at a time; raylib only supports one per process
+fn rl_lock() -> MutexGuard<'static, ()> {
+    static RL_MUX: Mutex<()> = Mutex::new(());
+    RL_MUX.lock().unwrap_or_else(|x| { RL_MUX.clear_poison(); x.into_inner() })
+}
+
+/// Open a hidden window for the GPU context and run `f` with it
+///
+/// The window is closed again before returning
+pub fn with_window<T>(f: impl FnOnce(&mut RaylibHandle, &RaylibThread) -> T) -> T {
+    let _lock = rl_lock();
+    // raylib combines these with the builder's flags instead of replacing them
+    unsafe { raylib::ffi::SetConfigFlags(ConfigFlags::FLAG_WINDOW_HIDDEN as u32); }
+    let (mut rl, thread) = init()
+        .size(1, 1)
+        .title("golden")
+        .log_level(TraceLogLevel::LOG_WARNING)
+        .build();
+    f(&mut rl, &thread)
+}
+
+/// Render `width` by `height` pixels offscreen with `draw`, starting from a transparent background
+pub fn render(width: u32, height: u32, draw: impl FnOnce(&mut RaylibTextureMode<'_, RaylibHandle>)) -> Result<Image, String> {
+    with_window(|rl, thread| {
+        let mut rtex = rl.load_render_texture(thread, width, height)?;
+        {
+            let mut d = rl.begin_texture_mode(thread, &mut rtex);
+            d.clear_background(Color::BLANK);
+            draw(&mut d);
+        }
+        crate::raster::image_from_render_texture(&rtex)
+    })
+}
+
+/// Render `artboard` of `document` offscreen exactly as it would be exported
+pub fn render_artboard(document: &Document, artboard: &Artboard, scale: f32) -> Result<Image, String> {
+    with_window(|rl, thread| crate::export::render_artboard(rl, thread, document, artboard, scale))
+}
+
+/// The number of pixels that differ between `a` and `b` by more than `channel` in any channel
+///
+/// Errors if the images are not the same size
+fn count_differences(a: &Image, b: &Image, channel: u8) -> Result<usize, String> {
+    if (a.width(), a.height()) != (b.width(), b.height()) {
+        return Err(format!("size {}x{} does not match reference size {}x{}", a.width(), a.height(), b.width(), b.height()));
+    }
+    let (a, b) = (a.get_image_data(), b.get_image_data());
+    Ok(a.iter().zip(b.iter())
+        .filter(|(a, b)| {
+            [(a.r, b.r), (a.g, b.g), (a.b, b.b), (a.a, b.a)]
+                .into_iter()
+                .any(|(a, b)| a.abs_diff(b) > channel)
+        })
+        .count())
+}
+
+/// Compare `image` against the reference image `name` under `tests/golden`
+///
+/// Set `UPDATE_GOLDEN=1` to record `image` as the new reference instead; this is the only way anything is written under `tests/golden`.
+/// On a mismatch, `image` is saved to the system temp folder as `<name>.actual.png` for inspection.
+///
+/// Tests calling this need a GPU, so they are `#[ignore]`d and run with `cargo test -- --ignored`.
+///
+/// # Panics
+///
+/// If the reference is missing or `image` does not match it within `tolerance`
+#[track_caller]
+pub fn assert_matches(name: &str, image: &Image, tolerance: Tolerance) {
+    let dir = PathBuf::from(GOLDEN_DIR);
+    let expected_path = dir.join(format!("{name}.png"));
+    let actual_path = actual_dir().join(format!("{name}.actual.png"));
+
+    if std::env::var_os(UPDATE_VAR).is_some() {
+        std::fs::create_dir_all(&dir)
+            .unwrap_or_else(|e| panic!("could not create {}: {e}", dir.display()));
+        image.export_image(&expected_path.to_string_lossy());
+        let _ = std::fs::remove_file(&actual_path);
+        return;
+    }
+
+    let result = if expected_path.exists() {
+        Image::load_image(&expected_path.to_string_lossy())
+            .and_then(|expected| count_differences(image, &expected, tolerance.channel))
+            .and_then(|differences| {
+                let total = (image.width().max(0) as usize).saturating_mul(image.height().max(0) as usize);
+                let allowed = (total as f32 * tolerance.pixels) as usize;
+                if differences <= allowed {
+                    Ok(())
+                } else {
+                    Err(format!("{differences} of {total} pixels differ, only {allowed} allowed"))
+                }
+            })
+    } else {
+        Err(format!("no reference image; rerun with {UPDATE_VAR}=1 to record one"))
+    };
+
+    match result {
+        Ok(()) => {
+            let _ = std::fs::remove_file(&actual_path);
+        }
+        Err(e) => {
+            if std::fs::create_dir_all(actual_dir()).is_ok() {
+                image.export_image(&actual_path.to_string_lossy());
+            }
+            panic!("rendering does not match {}: {e}\nactual rendering saved to {}", expected_path.display(), actual_path.display());
+        }
+    }
+}
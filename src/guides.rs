@@ -0,0 +1,260 @@
+use raylib::prelude::*;
+
+/// The color smart guides are drawn in
+pub const GUIDE_COLOR: Color = Color::MAGENTA;
+
+/// Screenspace distance (in pixels) within which smart guides snap
+pub const SNAP_DISTANCE: f32 = 6.0;
+
+/// Worldspace distance within which edges count as aligned
+const EPSILON: f32 = 0.01;
+
+/// Length (in screenspace pixels) of the ticks at the ends of spacing guides
+const TICK_LENGTH: f32 = 4.0;
+
+/// A transient hint showing that the object being moved or drawn lines up with another object or artboard
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Guide {
+    /// Edges or centers share a worldspace `x`; the line spans from `top` to `bottom`
+    Vertical { x: f32, top: f32, bottom: f32 },
+
+    /// Edges or centers share a worldspace `y`; the line spans from `left` to `right`
+    Horizontal { y: f32, left: f32, right: f32 },
+
+    /// A gap between neighbors that equals another gap, from worldspace `start` to `end`
+    Spacing { start: Vector2, end: Vector2 },
+}
+
+impl Guide {
+    /// Draw the guide in screenspace, so that it stays one pixel thick at any zoom
+    pub fn draw(&self, d: &mut impl RaylibDraw, camera: &Camera2D) {
+        let to_screen = |point: Vector2| (point - camera.target) * camera.zoom + camera.offset;
+        match *self {
+            Self::Vertical { x, top, bottom } => {
+                d.draw_line_v(to_screen(Vector2::new(x, top)), to_screen(Vector2::new(x, bottom)), GUIDE_COLOR);
+            }
+
+            Self::Horizontal { y, left, right } => {
+                d.draw_line_v(to_screen(Vector2::new(left, y)), to_screen(Vector2::new(right, y)), GUIDE_COLOR);
+            }
+
+            Self::Spacing { start, end } => {
+                let (start, end) = (to_screen(start), to_screen(end));
+                d.draw_line_v(start, end, GUIDE_COLOR);
+                if let Some(tick) = tick(start, end) {
+                    d.draw_line_v(start - tick, start + tick, GUIDE_COLOR);
+                    d.draw_line_v(end - tick, end + tick, GUIDE_COLOR);
+                }
+            }
+        }
+    }
+}
+
+/// Half of the tick drawn across each end of the screenspace line from `start` to `end`
+///
+/// [`None`] if the line has no length, since it has no direction to cross
+fn tick(start: Vector2, end: Vector2) -> Option<Vector2> {
+    let length = start.distance_to(end);
+    (length > f32::EPSILON).then(|| {
+        let direction = (end - start) / length;
+        Vector2::new(-direction.y, direction.x) * TICK_LENGTH
+    })
+}
+
+/// How far to move something to line it up with its surroundings, and the guides showing why
+#[derive(Debug, Clone, Default)]
+pub struct Snap {
+    /// Worldspace offset to add to the position
+    pub offset: Vector2,
+
+    /// The alignments at the snapped position
+    pub guides: Vec<Guide>,
+}
+
+/// The horizontal extent of `rect`, as `(left, right)`
+fn span_x(rect: &Rectangle) -> (f32, f32) {
+    (rect.x, rect.x + rect.width)
+}
+
+/// The vertical extent of `rect`, as `(top, bottom)`
+fn span_y(rect: &Rectangle) -> (f32, f32) {
+    (rect.y, rect.y + rect.height)
+}
+
+/// The start, center, and end of a span
+fn stops((min, max): (f32, f32)) -> [f32; 3] {
+    [min, (min + max) * 0.5, max]
+}
+
+/// The smallest offset within `tolerance` along the axis measured by `span` that lines up an edge or center
+/// of `moving` with one of `targets`, or (if `is_spacing`) spaces it evenly among its neighbors
+///
+/// Neighbors are the targets overlapping `moving` along the axis measured by `cross_span`.
+/// Returns the offset and the gaps (as spans) that are equal, which is empty for an alignment
+fn snap_axis(
+    moving: Rectangle,
+    targets: &[Rectangle],
+    span: fn(&Rectangle) -> (f32, f32),
+    cross_span: fn(&Rectangle) -> (f32, f32),
+    tolerance: f32,
+    is_spacing: bool,
+) -> Option<(f32, Vec<(f32, f32)>)> {
+    let (min, max) = span(&moving);
+    let mut best: Option<(f32, Vec<(f32, f32)>)> = None;
+    let mut consider = |offset: f32, gaps: Vec<(f32, f32)>| {
+        if offset.abs() <= tolerance && best.as_ref().is_none_or(|(best_offset, _)| offset.abs() < best_offset.abs()) {
+            best = Some((offset, gaps));
+        }
+    };
+
+    for target in targets {
+        for target_stop in stops(span(target)) {
+            for stop in stops((min, max)) {
+                consider(target_stop - stop, Vec::new());
+            }
+        }
+    }
+
+    if is_spacing {
+        let (cross_min, cross_max) = cross_span(&moving);
+        let neighbors = targets.iter()
+            .filter(|target| {
+                let (target_min, target_max) = cross_span(target);
+                target_min < cross_max && target_max > cross_min
+            })
+            .map(span)
+            .collect::<Vec<_>>();
+        // the nearest neighbor ending before `limit`, and the nearest starting after it
+        let before = |limit: f32| neighbors.iter().copied()
+            .filter(|&(_, end)| end <= limit)
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+        let after = |limit: f32| neighbors.iter().copied()
+            .filter(|&(start, _)| start >= limit)
+            .min_by(|a, b| a.0.total_cmp(&b.0));
+
+        let center = (min + max) * 0.5;
+        let prev = before(center);
+        let next = after(center);
+        // centered between both neighbors
+        if let (Some(prev), Some(next)) = (prev, next) {
+            let offset = (prev.1 + next.0 - min - max) * 0.5;
+            if next.0 - (max + offset) >= 0.0 {
+                consider(offset, vec![(prev.1, min + offset), (max + offset, next.0)]);
+            }
+        }
+        // continuing the spacing of the neighbors before
+        if let Some(prev) = prev && let Some(prev_prev) = before(prev.0) {
+            let gap = prev.0 - prev_prev.1;
+            consider(prev.1 + gap - min, vec![(prev_prev.1, prev.0), (prev.1, prev.1 + gap)]);
+        }
+        // continuing the spacing of the neighbors after
+        if let Some(next) = next && let Some(next_next) = after(next.1) {
+            let gap = next_next.0 - next.1;
+            consider(next.0 - gap - max, vec![(next.1, next_next.0), (next.0 - gap, next.0)]);
+        }
+    }
+
+    best
+}
+
+/// Find the offset lining up `moving` with `targets` on each axis, and the guides at the snapped position
+fn snap(moving: Rectangle, targets: &[Rectangle], tolerance: f32, is_spacing: bool) -> Snap {
+    let snap_x = snap_axis(moving, targets, span_x, span_y, tolerance, is_spacing);
+    let snap_y = snap_axis(moving, targets, span_y, span_x, tolerance, is_spacing);
+    let offset = Vector2::new(
+        snap_x.as_ref().map_or(0.0, |&(offset, _)| offset),
+        snap_y.as_ref().map_or(0.0, |&(offset, _)| offset),
+    );
+    let snapped = Rectangle { x: moving.x + offset.x, y: moving.y + offset.y, ..moving };
+    let (left, right) = span_x(&snapped);
+    let (top, bottom) = span_y(&snapped);
+
+    let mut guides = Vec::new();
+    for target in targets {
+        let (target_left, target_right) = span_x(target);
+        let (target_top, target_bottom) = span_y(target);
+        for x in stops((left, right)) {
+            if stops((target_left, target_right)).iter().any(|&target_x| (target_x - x).abs() < EPSILON) {
+                guides.push(Guide::Vertical { x, top: top.min(target_top), bottom: bottom.max(target_bottom) });
+            }
+        }
+        for y in stops((top, bottom)) {
+            if stops((target_top, target_bottom)).iter().any(|&target_y| (target_y - y).abs() < EPSILON) {
+                guides.push(Guide::Horizontal { y, left: left.min(target_left), right: right.max(target_right) });
+            }
+        }
+    }
+    let (center_x, center_y) = ((left + right) * 0.5, (top + bottom) * 0.5);
+    for (start, end) in snap_x.into_iter().flat_map(|(_, gaps)| gaps) {
+        guides.push(Guide::Spacing { start: Vector2::new(start, center_y), end: Vector2::new(end, center_y) });
+    }
+    for (start, end) in snap_y.into_iter().flat_map(|(_, gaps)| gaps) {
+        guides.push(Guide::Spacing { start: Vector2::new(center_x, start), end: Vector2::new(center_x, end) });
+    }
+    // a point's edges and center are all the same line
+    guides.dedup();
+
+    Snap { offset, guides }
+}
+
+/// Snap the worldspace rectangle `moving` to align its edges or center with one of `targets`, or to space it
+/// evenly among its neighbors, on each axis independently
+///
+/// `tolerance` is the farthest worldspace distance it may move on each axis
+pub fn snap_rect(moving: Rectangle, targets: &[Rectangle], tolerance: f32) -> Snap {
+    snap(moving, targets, tolerance, true)
+}
+
+/// Snap the worldspace `point` to align with the edges or center of one of `targets`, on each axis independently
+///
+/// `tolerance` is the farthest worldspace distance it may move on each axis
+pub fn snap_point(point: Vector2, targets: &[Rectangle], tolerance: f32) -> Snap {
+    snap(Rectangle::new(point.x, point.y, 0.0, 0.0), targets, tolerance, false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tick() {
+        assert_eq!(tick(Vector2::new(0.0, 0.0), Vector2::new(10.0, 0.0)), Some(Vector2::new(0.0, TICK_LENGTH)));
+        assert_eq!(tick(Vector2::new(5.0, 5.0), Vector2::new(5.0, 5.0)), None, "a zero-length guide has no direction");
+    }
+
+    #[test]
+    fn test_snap_point() {
+        let targets = [Rectangle::new(0.0, 0.0, 100.0, 100.0)];
+        let snap = snap_point(Vector2::new(98.0, 51.0), &targets, 5.0);
+        assert_eq!(snap.offset, Vector2::new(2.0, -1.0));
+        assert_eq!(snap.guides, [
+            Guide::Vertical { x: 100.0, top: 0.0, bottom: 100.0 },
+            Guide::Horizontal { y: 50.0, left: 0.0, right: 100.0 },
+        ]);
+
+        let snap = snap_point(Vector2::new(60.0, 70.0), &targets, 5.0);
+        assert_eq!(snap.offset, Vector2::zero(), "nothing is within tolerance");
+        assert!(snap.guides.is_empty());
+    }
+
+    #[test]
+    fn test_snap_rect() {
+        // the nearest of several alignments wins
+        let targets = [Rectangle::new(0.0, 0.0, 10.0, 10.0)];
+        let snap = snap_rect(Rectangle::new(12.0, 3.0, 10.0, 10.0), &targets, 4.0);
+        assert_eq!(snap.offset, Vector2::new(-2.0, 2.0));
+        assert!(snap.guides.contains(&Guide::Vertical { x: 10.0, top: 0.0, bottom: 15.0 }));
+
+        // centered between two neighbors, with equal gaps on either side
+        let targets = [Rectangle::new(0.0, 0.0, 10.0, 10.0), Rectangle::new(30.0, 0.0, 10.0, 10.0)];
+        let snap = snap_rect(Rectangle::new(14.0, 0.0, 10.0, 10.0), &targets, 3.0);
+        assert_eq!(snap.offset, Vector2::new(1.0, 0.0));
+        assert!(snap.guides.contains(&Guide::Spacing { start: Vector2::new(10.0, 5.0), end: Vector2::new(15.0, 5.0) }));
+        assert!(snap.guides.contains(&Guide::Spacing { start: Vector2::new(25.0, 5.0), end: Vector2::new(30.0, 5.0) }));
+
+        // continuing the spacing of the neighbors before
+        let snap = snap_rect(Rectangle::new(59.0, 0.0, 10.0, 10.0), &targets, 3.0);
+        assert_eq!(snap.offset, Vector2::new(1.0, 0.0));
+        assert!(snap.guides.contains(&Guide::Spacing { start: Vector2::new(40.0, 5.0), end: Vector2::new(60.0, 5.0) }));
+    }
+}
@@ -0,0 +1,984 @@
+use std::{fs::{File, OpenOptions}, io::{BufRead, BufReader, Write}, path::{Path, PathBuf}, sync::Weak};
+use raylib::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::{
+    annotation::Annotation,
+    curve::{CurvePoint, WeakCurve},
+    document::{Artboard, Document, DocumentInfo},
+    export::AssetExport,
+    file_job::{FileJob, FileOutcome},
+    fill::FillRule,
+    format,
+    layer::{Layer, LayerContent, WeakRenderTexture2D},
+    style::{Modifier, Pattern, Stroke, StrokeMarkers, StrokeTrim, Style, StyleItem, TextureTransform, WeakStyle, WeakWidthProfile, WidthProfile},
+};
+
+/// A committed edit, recorded so it can be replayed onto the last saved snapshot of a document
+///
+/// Layers are found by their path, a list of indices from the top-level layers down through nested groups
+#[derive(Debug, Serialize, Deserialize)]
+pub enum JournalEntry {
+    /// Layers were added above every existing layer
+    ///
+    /// The layers and the resources they reference are stored as a [fragment][`format::save_fragment`],
+    /// relative to the journal's folder
+    AddLayers(PathBuf),
+
+    /// The document info was replaced
+    SetInfo(DocumentInfo),
+
+    /// The document was renamed
+    SetTitle(String),
+
+    /// The layers were moved by a worldspace offset, like [`Layer::translate`]
+    Translate { layers: Vec<Vec<usize>>, offset: Vector2 },
+
+    /// The layers were stretched about a worldspace center, like [`Layer::scale`]
+    ///
+    /// If `stroke_factor` is [`Some`], their [strokes were scaled][`Document::scale_strokes`] by it too
+    Scale { layers: Vec<Vec<usize>>, center: Vector2, factor: Vector2, stroke_factor: Option<f32> },
+
+    /// The whole document was [scaled][`Document::scale`]
+    ScaleDocument(f32),
+
+    /// The layers were [rounded to the pixel grid][`Layer::snap_to_pixels`] of the document info
+    SnapToPixels { layers: Vec<Vec<usize>> },
+
+    /// Curves were reshaped, such as by moving anchors
+    SetCurves(Vec<CurveRecord>),
+
+    /// The corner radii of a rectangle layer were replaced
+    SetRectangleRadii { layer: Vec<usize>, radii: [f32; 4] },
+
+    /// The style shared by the layers was edited
+    ///
+    /// Every layer using the style is listed, since layers [added][`JournalEntry::AddLayers`] together
+    /// are replayed with their own copy of it
+    SetStyle { layers: Vec<Vec<usize>>, style: StyleRecord },
+
+    /// The artboards were replaced, such as by renaming or arranging them
+    SetArtboards(Vec<Artboard>),
+
+    /// A layer was marked to be exported as an asset, or unmarked
+    SetLayerExport { layer: Vec<usize>, export: Option<AssetExport> },
+
+    /// The document's broken layers were [repaired][`Document::repair_layers`]
+    RepairLayers,
+
+    /// An annotation was added over the rest
+    AddAnnotation(Annotation),
+
+    /// The annotation at an index was removed
+    RemoveAnnotation(usize),
+
+    /// The document was edited in a way no other entry describes
+    ///
+    /// The whole document is stored as a [native file][`format::encode`] relative to the journal's folder,
+    /// and replaces the document when replayed
+    Snapshot(PathBuf),
+}
+
+impl JournalEntry {
+    /// Whether the entry describes part of an edit that may be followed by more of the same, such as a drag,
+    /// and should wait to be [merged][`JournalEntry::merge`] with them before being written
+    const fn is_mergeable(&self) -> bool {
+        matches!(self, Self::Translate { .. } | Self::SetCurves(_) | Self::SetRectangleRadii { .. } | Self::SetStyle { .. })
+    }
+
+    /// Fold `next` into `self` if replaying `self` would then have the same result as replaying both
+    ///
+    /// Returns whether `next` was merged
+    fn merge(&mut self, next: &Self) -> bool {
+        match (self, next) {
+            (Self::Translate { layers, offset }, Self::Translate { layers: next_layers, offset: next_offset }) if layers == next_layers => {
+                *offset += *next_offset;
+                true
+            }
+
+            (Self::SetCurves(curves), Self::SetCurves(next_curves))
+                if curves.len() == next_curves.len() && curves.iter().zip(next_curves).all(|(curve, next)| (&curve.layer, curve.index) == (&next.layer, next.index)) =>
+            {
+                curves.clone_from(next_curves);
+                true
+            }
+
+            (Self::SetRectangleRadii { layer, radii }, Self::SetRectangleRadii { layer: next_layer, radii: next_radii }) if layer == next_layer => {
+                *radii = *next_radii;
+                true
+            }
+
+            (Self::SetStyle { layers, style }, Self::SetStyle { layers: next_layers, style: next_style }) if layers == next_layers => {
+                style.clone_from(next_style);
+                true
+            }
+
+            _ => false,
+        }
+    }
+
+    /// Apply the edit to `document`, reading the documents stored with the entry with `load`
+    ///
+    /// Layers that no longer exist are skipped, like the edits they were recorded from would skip them
+    fn apply(self, document: &mut Document, directory: &Path, load: &mut impl FnMut(&Path) -> Result<Document, String>) -> Result<(), String> {
+        match self {
+            Self::AddLayers(file_name) => {
+                let fragment = load(&directory.join(file_name))?;
+                document.append(fragment, Vector2::zero());
+            }
+
+            Self::SetInfo(info) => document.info = info,
+
+            Self::SetTitle(title) => document.title = title,
+
+            Self::Translate { layers, offset } => for path in &layers {
+                if let Some(layer) = document.layer_at_mut(path) {
+                    layer.translate(offset);
+                }
+            },
+
+            Self::Scale { layers, center, factor, stroke_factor } => {
+                for path in &layers {
+                    if let Some(layer) = document.layer_at_mut(path) {
+                        layer.scale(center, factor);
+                    }
+                }
+                if let Some(stroke_factor) = stroke_factor {
+                    document.scale_strokes(&layers, stroke_factor);
+                }
+            }
+
+            Self::ScaleDocument(factor) => document.scale(factor),
+
+            Self::SnapToPixels { layers } => {
+                let DocumentInfo { pixel_snap, is_aligning_strokes, .. } = document.info;
+                for path in &layers {
+                    if let Some(layer) = document.layer_at_mut(path) {
+                        layer.snap_to_pixels(pixel_snap, is_aligning_strokes);
+                    }
+                }
+            }
+
+            Self::SetCurves(curves) => for record in curves {
+                if let Some(curve) = document.layer_at(&record.layer).and_then(|layer| layer.curves().get(record.index)?.upgrade()) {
+                    let curve_lock = curve.lock();
+                    let mut curve_borrow = curve_lock.borrow_mut();
+                    curve_borrow.points = record.points.iter()
+                        .map(|&[c_in, p, c_out]| CurvePoint { c_in: c_in.into(), p: p.into(), c_out: c_out.into() })
+                        .collect();
+                    curve_borrow.is_closed = record.is_closed;
+                }
+            },
+
+            Self::SetRectangleRadii { layer, radii } => {
+                if let Some(Layer { content: LayerContent::Rectangle(rectangle), .. }) = document.layer_at_mut(&layer) {
+                    rectangle.radii = radii;
+                    rectangle.regenerate();
+                }
+            }
+
+            Self::SetStyle { layers, style } => {
+                let mut styles = Vec::new();
+                for path in &layers {
+                    if let Some(layer) = document.layer_at(path)
+                        && !styles.iter().any(|applied: &WeakStyle| applied.ptr_eq(&layer.style))
+                    {
+                        styles.push(layer.style.clone());
+                    }
+                }
+                for strong_style in styles.iter().filter_map(Weak::upgrade) {
+                    style.apply(&mut strong_style.lock().borrow_mut())?;
+                }
+            }
+
+            Self::SetArtboards(artboards) => document.artboards = artboards,
+
+            Self::SetLayerExport { layer, export } => {
+                if let Some(layer) = document.layer_at_mut(&layer) {
+                    layer.export = export;
+                }
+            }
+
+            Self::RepairLayers => _ = document.repair_layers(),
+
+            Self::AddAnnotation(annotation) => document.annotations.push(annotation),
+
+            Self::RemoveAnnotation(index) => if index < document.annotations.len() {
+                document.annotations.remove(index);
+            },
+
+            Self::Snapshot(file_name) => {
+                let mut snapshot = load(&directory.join(file_name))?;
+                // the snapshot was read from the journal's folder, not from where the document is saved
+                snapshot.file_path = document.file_path.take();
+                *document = snapshot;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The shape of a curve, found by the layer drawing it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CurveRecord {
+    /// The path of a layer drawing the curve
+    pub layer: Vec<usize>,
+
+    /// The index of the curve in the layer's [curves][`Layer::curves`]
+    pub index: usize,
+
+    /// `[c_in, p, c_out]` of each [`CurvePoint`]
+    pub points: Vec<[[f32; 2]; 3]>,
+
+    pub is_closed: bool,
+}
+
+impl CurveRecord {
+    /// Record the current shape of `curve`, drawn by the layer at `layer` as its curve at `index`
+    ///
+    /// [`None`] if the curve no longer exists
+    pub fn new(layer: Vec<usize>, index: usize, curve: &WeakCurve) -> Option<Self> {
+        let curve = curve.upgrade()?;
+        let curve_lock = curve.lock();
+        let curve_borrow = curve_lock.borrow();
+        Some(Self {
+            layer,
+            index,
+            points: curve_borrow.points.iter().map(|point| [point.c_in.into(), point.p.into(), point.c_out.into()]).collect(),
+            is_closed: curve_borrow.is_closed,
+        })
+    }
+}
+
+/// A [`Pattern`] stored by value, except for the texture of a texture pattern, which is kept from the style it is applied to
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+enum PatternRecord {
+    Solid(Color),
+    Texture(TextureTransform),
+}
+
+impl PatternRecord {
+    fn new(pattern: &Pattern) -> Self {
+        match pattern {
+            Pattern::Solid(color) => Self::Solid(*color),
+            Pattern::Texture(_, transform) => Self::Texture(*transform),
+        }
+    }
+
+    /// Rebuild the pattern with the `texture` it kept
+    fn to_pattern(&self, texture: Option<WeakRenderTexture2D>) -> Result<Pattern, String> {
+        match (self, texture) {
+            (Self::Solid(color), _) => Ok(Pattern::Solid(*color)),
+            (Self::Texture(transform), Some(texture)) => Ok(Pattern::Texture(texture, *transform)),
+            (Self::Texture(_), None) => Err("journaled texture pattern has no texture to keep".to_owned()),
+        }
+    }
+}
+
+/// A [`Stroke`] stored by value, except that its texture and width profile are kept from the style it is applied to
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct StrokeRecord {
+    pattern: PatternRecord,
+    width: Option<WidthProfile>,
+    trim: StrokeTrim,
+    markers: StrokeMarkers,
+}
+
+impl StrokeRecord {
+    fn new(stroke: &Stroke) -> Self {
+        Self {
+            pattern: PatternRecord::new(&stroke.pattern),
+            width: stroke.width.as_ref().and_then(Weak::upgrade).map(|profile| profile.lock().borrow().clone()),
+            trim: stroke.trim,
+            markers: stroke.markers.clone(),
+        }
+    }
+
+    /// Rebuild the stroke with the `texture` and `width` profile it kept, overwriting the profile with the recorded one
+    ///
+    /// Width profiles may be shared between styles, so the others using it change too, as they did when it was recorded
+    fn to_stroke(&self, texture: Option<WeakRenderTexture2D>, width: Option<WeakWidthProfile>) -> Result<Stroke, String> {
+        let width = match (&self.width, width.as_ref().and_then(Weak::upgrade)) {
+            (None, _) => None,
+            (Some(profile), Some(kept)) => {
+                *kept.lock().borrow_mut() = profile.clone();
+                width
+            }
+            (Some(_), None) => return Err("journaled stroke has no width profile to keep".to_owned()),
+        };
+        Ok(Stroke {
+            pattern: self.pattern.to_pattern(texture)?,
+            width,
+            trim: self.trim,
+            markers: self.markers.clone(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+enum ModifierRecord {
+    Fill(PatternRecord),
+    Stroke(StrokeRecord),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct ItemRecord {
+    name: Option<String>,
+    modifier: ModifierRecord,
+}
+
+/// A [`Style`] stored by value, so edits to it can be journaled without storing the whole document
+///
+/// Textures and width profiles are resources of the document, so rather than being stored, each is kept from the
+/// same slot of the style the record is applied to. A slot is the fill, the stroke, or one of the items.
+/// Only edits that [keep the resources][`StyleRecord::keeps_resources`] of the style can be recorded this way
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StyleRecord {
+    fill: PatternRecord,
+    fill_rule: FillRule,
+    stroke: StrokeRecord,
+    items: Vec<ItemRecord>,
+}
+
+impl StyleRecord {
+    /// Record the current look of `style`
+    pub fn new(style: &Style) -> Self {
+        Self {
+            fill: PatternRecord::new(&style.fill),
+            fill_rule: style.fill_rule,
+            stroke: StrokeRecord::new(&style.stroke),
+            items: style.items.iter()
+                .map(|item| ItemRecord {
+                    name: item.name.clone(),
+                    modifier: match &item.modifier {
+                        Modifier::Fill(pattern) => ModifierRecord::Fill(PatternRecord::new(pattern)),
+                        Modifier::Stroke(stroke) => ModifierRecord::Stroke(StrokeRecord::new(stroke)),
+                    },
+                })
+                .collect(),
+        }
+    }
+
+    /// The pattern and width profile of each slot of `style`: its fill, its stroke, then each of its items
+    fn slots(style: &Style) -> Vec<(&Pattern, Option<&WeakWidthProfile>)> {
+        let mut slots = vec![(&style.fill, None), (&style.stroke.pattern, style.stroke.width.as_ref())];
+        slots.extend(style.items.iter().map(|item| match &item.modifier {
+            Modifier::Fill(pattern) => (pattern, None),
+            Modifier::Stroke(stroke) => (&stroke.pattern, stroke.width.as_ref()),
+        }));
+        slots
+    }
+
+    /// Whether every texture and width profile `after` uses is used by the same slot of `before`,
+    /// so a record of `after` applied to `before` gives back `after`
+    pub fn keeps_resources(before: &Style, after: &Style) -> bool {
+        let before = Self::slots(before);
+        Self::slots(after).into_iter().enumerate().all(|(index, (pattern, width))| {
+            let (kept_pattern, kept_width) = before.get(index).copied().unzip();
+            let is_texture_kept = match pattern {
+                Pattern::Solid(_) => true,
+                Pattern::Texture(texture, _) => matches!(kept_pattern, Some(Pattern::Texture(kept, _)) if kept.ptr_eq(texture)),
+            };
+            let is_width_kept = width.is_none_or(|width| kept_width.flatten().is_some_and(|kept| kept.ptr_eq(width)));
+            is_texture_kept && is_width_kept
+        })
+    }
+
+    /// Make `style` look like the recorded style, keeping the textures and width profiles in its slots
+    ///
+    /// Fails if the record needs a texture or width profile `style` doesn't have in that slot
+    pub fn apply(&self, style: &mut Style) -> Result<(), String> {
+        let kept = Self::slots(style).into_iter()
+            .map(|(pattern, width)| {
+                let texture = match pattern {
+                    Pattern::Texture(texture, _) => Some(texture.clone()),
+                    Pattern::Solid(_) => None,
+                };
+                (texture, width.cloned())
+            })
+            .collect::<Vec<_>>();
+        let mut kept = kept.into_iter();
+        let mut next_slot = || kept.next().unwrap_or((None, None));
+
+        let (texture, _) = next_slot();
+        let fill = self.fill.to_pattern(texture)?;
+        let (texture, width) = next_slot();
+        let stroke = self.stroke.to_stroke(texture, width)?;
+        let items = self.items.iter()
+            .map(|item| {
+                let (texture, width) = next_slot();
+                let modifier = match &item.modifier {
+                    ModifierRecord::Fill(pattern) => Modifier::Fill(pattern.to_pattern(texture)?),
+                    ModifierRecord::Stroke(stroke) => Modifier::Stroke(stroke.to_stroke(texture, width)?),
+                };
+                Ok(StyleItem { name: item.name.clone(), modifier })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        *style = Style { fill, fill_rule: self.fill_rule, stroke, items };
+        Ok(())
+    }
+}
+
+/// A [snapshot][`JournalEntry::Snapshot`] being written on a background thread
+#[derive(Debug)]
+struct SnapshotJob {
+    /// The number the snapshot's entry was given
+    number: usize,
+
+    /// The file name the snapshot is being written to, relative to the journal's folder
+    file_name: PathBuf,
+
+    job: FileJob,
+
+    /// The entries recorded while the snapshot is being written, which are only written once it is,
+    /// since they can't be replayed without the edits it stores
+    held: Vec<JournalEntry>,
+}
+
+/// An append-only log of edits made to a document since it was last saved
+///
+/// Each entry is written and flushed to disk as soon as it is recorded, so edits made after the
+/// last save (or autosave) can be recovered if the application exits without saving.
+/// Entries for the steps of a continuous edit, like a drag, are merged and written once it pauses
+#[derive(Debug)]
+pub struct Journal {
+    /// The journal file, one JSON entry per line
+    path: PathBuf,
+
+    /// The folder fragments of [`JournalEntry::AddLayers`] and [`JournalEntry::Snapshot`] are stored in
+    directory: PathBuf,
+
+    /// How many entries were [discarded][`Journal::discard_through`] from the start of the file,
+    /// so the entries left keep their numbers
+    first: usize,
+
+    /// How many entries are in the file
+    len: usize,
+
+    /// The number the next entry is given
+    end: usize,
+
+    /// The last entry recorded, if it may still be [merged][`JournalEntry::merge`] with the next
+    ///
+    /// It isn't numbered until it is [flushed][`Journal::flush`]
+    pending: Option<JournalEntry>,
+
+    /// The snapshot being written, if any
+    snapshot: Option<SnapshotJob>,
+}
+
+impl Journal {
+    /// Get the journal of the document saved at `document_path`
+    ///
+    /// Does not touch the filesystem; files are only created once an entry is recorded
+    pub fn new(document_path: &Path) -> Self {
+        let name = document_path.file_name().unwrap_or_default().to_string_lossy();
+        Self {
+            path: document_path.with_file_name(format!("{name}.journal")),
+            directory: document_path.with_file_name(format!("{name}.journal_assets")),
+            first: 0,
+            len: 0,
+            end: 0,
+            pending: None,
+            snapshot: None,
+        }
+    }
+
+    /// The number of entries recorded so far, counting discarded ones
+    ///
+    /// Pass it to [`Journal::discard_through`] once everything recorded up to now has been saved.
+    /// Doesn't count an entry still waiting to be merged; [flush][`Journal::flush`] first to include it
+    #[inline]
+    pub const fn end(&self) -> usize {
+        self.end
+    }
+
+    /// Whether the journal has entries on disk that haven't been saved into the document
+    pub fn has_entries(&self) -> bool {
+        std::fs::metadata(&self.path).is_ok_and(|metadata| metadata.len() > 0)
+    }
+
+    /// Whether an entry is waiting to be merged with the next, and needs [flushing][`Journal::flush`]
+    #[inline]
+    pub const fn has_pending(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// Whether a snapshot is being written on a background thread
+    #[inline]
+    pub const fn is_snapshotting(&self) -> bool {
+        self.snapshot.is_some()
+    }
+
+    /// Append an entry to the journal file and flush it to disk
+    fn append(&mut self, entry: &JournalEntry) -> Result<(), String> {
+        let mut line = serde_json::to_string(entry).map_err(|e| e.to_string())?;
+        line.push('\n');
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| e.to_string())?;
+        file.write_all(line.as_bytes()).map_err(|e| e.to_string())?;
+        file.sync_data().map_err(|e| e.to_string())?;
+        self.len = self.len.saturating_add(1);
+        Ok(())
+    }
+
+    /// Number `entry` and write it, or hold it until the snapshot being written is
+    fn write(&mut self, entry: JournalEntry) -> Result<(), String> {
+        self.end = self.end.saturating_add(1);
+        match &mut self.snapshot {
+            Some(snapshot) => {
+                snapshot.held.push(entry);
+                Ok(())
+            }
+            None => self.append(&entry),
+        }
+    }
+
+    /// Write the entry waiting to be merged, if any
+    pub fn flush(&mut self) -> Result<(), String> {
+        match self.pending.take() {
+            Some(entry) => self.write(entry),
+            None => Ok(()),
+        }
+    }
+
+    /// Record `entry`, merging it into the last entry if they are steps of the same edit
+    ///
+    /// Entries that may be followed by more steps wait to be written until the next entry or [flush][`Journal::flush`]
+    pub fn record(&mut self, entry: JournalEntry) -> Result<(), String> {
+        if let Some(pending) = &mut self.pending
+            && pending.merge(&entry)
+        {
+            return Ok(());
+        }
+        self.flush()?;
+        if entry.is_mergeable() {
+            self.pending = Some(entry);
+            Ok(())
+        } else {
+            self.write(entry)
+        }
+    }
+
+    /// A file name in the journal's folder for the fragment of the next entry, not used by any other fragment
+    ///
+    /// Fragments of entries left over from before a crash keep their names, so the entry number alone may be taken
+    fn fragment_name(&self) -> PathBuf {
+        (self.end..)
+            .map(|n| PathBuf::from(format!("entry_{n}.{}", format::EXTENSION)))
+            .find(|file_name| !self.directory.join(file_name).exists())
+            .expect("should find an unused name before running out of numbers")
+    }
+
+    /// Delete the fragment `file_name` and the folder of images it was written with
+    fn remove_fragment(&self, file_name: &Path) -> Result<(), String> {
+        let stem = file_name.file_stem().unwrap_or_default().to_string_lossy();
+        let results = [
+            std::fs::remove_file(self.directory.join(file_name)),
+            std::fs::remove_dir_all(self.directory.join(format!("{stem}_assets"))),
+        ];
+        for result in results {
+            match result {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.to_string()),
+                _ => (),
+            }
+        }
+        Ok(())
+    }
+
+    /// Delete the fragment stored with `entry`, if any
+    fn remove_fragment_of(&self, entry: &JournalEntry) -> Result<(), String> {
+        match entry {
+            JournalEntry::AddLayers(file_name) | JournalEntry::Snapshot(file_name) => self.remove_fragment(file_name),
+            _ => Ok(()),
+        }
+    }
+
+    /// Read the lines of every complete entry in the journal file
+    ///
+    /// A partially-written final entry (from a crash mid-write) is left out
+    fn lines(&self) -> Result<Vec<(String, JournalEntry)>, String> {
+        let file = match File::open(&self.path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.to_string()),
+        };
+        let mut lines = Vec::new();
+        for line in BufReader::new(file).lines() {
+            let line = line.map_err(|e| e.to_string())?;
+            let Ok(entry) = serde_json::from_str::<JournalEntry>(&line) else { break };
+            lines.push((line, entry));
+        }
+        Ok(lines)
+    }
+
+    /// Record that `layers` were added to the top of `document`
+    pub fn record_add_layers(&mut self, document: &Document, layers: &[Layer]) -> Result<(), String> {
+        self.flush()?;
+        std::fs::create_dir_all(&self.directory).map_err(|e| e.to_string())?;
+        let file_name = self.fragment_name();
+        format::save_fragment(document, layers, &self.directory.join(&file_name))?;
+        self.write(JournalEntry::AddLayers(file_name))
+    }
+
+    /// Start recording that `document` was edited in a way no other entry describes, by storing all of it
+    ///
+    /// The document is encoded right away and written on a background thread; [`Journal::poll_snapshot`] finishes it.
+    /// Every entry recorded before it is discarded once it is written, since the snapshot contains their edits.
+    /// A snapshot already being written is waited for first
+    pub fn start_snapshot(&mut self, document: &Document) -> Result<(), String> {
+        self.flush()?;
+        if let Some(result) = self.wait_for_snapshot() {
+            result?;
+        }
+        std::fs::create_dir_all(&self.directory).map_err(|e| e.to_string())?;
+        let file_name = self.fragment_name();
+        let path = self.directory.join(&file_name);
+        let job = format::encode(document, &path, false).and_then(|encoded| FileJob::save(encoded, &path))?;
+        self.snapshot = Some(SnapshotJob { number: self.end, file_name, job, held: Vec::new() });
+        self.end = self.end.saturating_add(1);
+        Ok(())
+    }
+
+    /// Check whether the snapshot being written has finished, and if it succeeded, write its entry and the ones held for it
+    ///
+    /// If it failed, the entries held for it are dropped too, as they can't be replayed without it.
+    /// Returns the snapshot's result the first time it is called after it finishes, [`None`] otherwise
+    pub fn poll_snapshot(&mut self) -> Option<Result<(), String>> {
+        let result = self.snapshot.as_mut()?.job.poll()?;
+        Some(self.finish_snapshot(result))
+    }
+
+    /// Block until the snapshot being written has finished, finishing it like [`Journal::poll_snapshot`]
+    ///
+    /// [`None`] if no snapshot is being written
+    pub fn wait_for_snapshot(&mut self) -> Option<Result<(), String>> {
+        let result = self.snapshot.as_mut()?.job.wait();
+        Some(self.finish_snapshot(result))
+    }
+
+    /// Apply the `result` of the snapshot being written
+    fn finish_snapshot(&mut self, result: Result<FileOutcome, String>) -> Result<(), String> {
+        let Some(SnapshotJob { number, file_name, held, .. }) = self.snapshot.take() else { return result.map(|_| ()) };
+        if let Err(e) = result {
+            self.remove_fragment(&file_name)?;
+            for entry in &held {
+                self.remove_fragment_of(entry)?;
+            }
+            return Err(e);
+        }
+        if number < self.first {
+            // a save that started after the snapshot was taken already has its edits
+            self.remove_fragment(&file_name)?;
+        } else {
+            self.append(&JournalEntry::Snapshot(file_name))?;
+            self.discard_through(number)?;
+        }
+        // the held entries were numbered right after the snapshot
+        let first_held = number.saturating_add(1);
+        for (entry_number, entry) in (first_held..).zip(held) {
+            if entry_number < self.first {
+                self.remove_fragment_of(&entry)?;
+            } else {
+                self.append(&entry)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Apply every recorded entry to `document`, in the order they were recorded
+    ///
+    /// `document` should be the snapshot the journal was recorded on top of.
+    /// A partially-written final entry (from a crash mid-write) is ignored
+    pub fn replay(&mut self, rl: &mut RaylibHandle, thread: &RaylibThread, document: &mut Document) -> Result<(), String> {
+        self.replay_with(document, |path| format::load(rl, thread, path))
+    }
+
+    /// [Replay][`Journal::replay`] the journal onto `document`, reading the documents stored with its entries with `load`
+    fn replay_with(&mut self, document: &mut Document, mut load: impl FnMut(&Path) -> Result<Document, String>) -> Result<(), String> {
+        let lines = self.lines()?;
+        let count = lines.len();
+        for (_, entry) in lines {
+            entry.apply(document, &self.directory, &mut load)?;
+        }
+        if count > 0 {
+            // connectors follow the artwork they are attached to
+            document.route_connectors();
+        }
+        self.first = 0;
+        self.len = count;
+        self.end = count;
+        Ok(())
+    }
+
+    /// Delete the entries numbered below `end` (as given by [`Journal::end`]), keeping the ones recorded since
+    ///
+    /// Used once a save that started after entry `end - 1` was recorded has finished, since the saved file contains
+    /// those edits, but not the ones made while it was being written
+    pub fn discard_through(&mut self, end: usize) -> Result<(), String> {
+        let count = end.saturating_sub(self.first).min(self.len);
+        if count == self.len {
+            if count > 0 {
+                self.remove_file()?;
+            }
+            // a snapshot being written may be numbered below `end` too, and is dropped once it finishes
+            self.first = self.first.max(end);
+            return Ok(());
+        }
+        if count == 0 {
+            return Ok(());
+        }
+        let lines = self.lines()?;
+        let (discarded, kept) = lines.split_at(count.min(lines.len()));
+        for (_, entry) in discarded {
+            self.remove_fragment_of(entry)?;
+        }
+        let mut contents = String::new();
+        for (line, _) in kept {
+            contents.push_str(line);
+            contents.push('\n');
+        }
+        // replace the journal only once the new one is complete, so a crash can't lose the kept entries
+        let name = self.path.file_name().unwrap_or_default().to_string_lossy();
+        let temp_path = self.path.with_file_name(format!("{name}.tmp"));
+        std::fs::write(&temp_path, contents).map_err(|e| e.to_string())?;
+        std::fs::rename(&temp_path, &self.path).map_err(|e| e.to_string())?;
+        self.first = self.first.saturating_add(count);
+        self.len = kept.len();
+        Ok(())
+    }
+
+    /// Delete every entry in the file along with its fragment, and the journal's folder if nothing else is in it
+    fn remove_file(&mut self) -> Result<(), String> {
+        for (_, entry) in self.lines()? {
+            self.remove_fragment_of(&entry)?;
+        }
+        self.len = 0;
+        match std::fs::remove_file(&self.path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.to_string()),
+            _ => (),
+        }
+        // the folder still holds the fragments of a snapshot being written, or left over from before a crash
+        _ = std::fs::remove_dir(&self.directory);
+        Ok(())
+    }
+
+    /// Delete the journal and its fragments, along with the entry waiting to be merged
+    ///
+    /// Should be called once the document has been saved, as the saved file contains every journaled edit.
+    /// A snapshot being written is waited for first
+    pub fn clear(&mut self) -> Result<(), String> {
+        self.pending = None;
+        // a failed snapshot has already cleaned up after itself, and a finished one is removed with the rest
+        _ = self.wait_for_snapshot();
+        self.len = 0;
+        self.first = self.end;
+        for result in [std::fs::remove_file(&self.path), std::fs::remove_dir_all(&self.directory)] {
+            match result {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.to_string()),
+                _ => (),
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use crate::editor::{Editor, MaybeNew, Selection};
+
+    /// The authors of the [`JournalEntry::SetInfo`] entries left in the journal
+    fn authors(journal: &Journal) -> Vec<String> {
+        journal.lines().unwrap().into_iter()
+            .filter_map(|(_, entry)| match entry {
+                JournalEntry::SetInfo(info) => Some(info.author),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// An editor for a new document saved at a temporary path named after `name`, along with a function
+    /// reading the saved document back with its journal replayed on top, like recovering from a crash
+    fn saved_editor(name: &str) -> (Editor, PathBuf, impl Fn() -> Document) {
+        let path = std::env::temp_dir().join(format!("journal_{name}_{}.{}", std::process::id(), format::EXTENSION));
+        let mut editor = Editor::new_default(Document::new(name.to_owned()));
+        editor.start_save(&path, false).unwrap();
+        editor.wait_for_save().unwrap().unwrap();
+        let saved_path = path.clone();
+        let recover = move || {
+            let mut document = format::build_headless(format::read(&saved_path, |_| ()).unwrap()).unwrap();
+            Journal::new(&saved_path).replay_with(&mut document, |path| format::build_headless(format::read(path, |_| ())?)).unwrap();
+            document
+        };
+        (editor, path, recover)
+    }
+
+    /// Delete the document saved by [`saved_editor`] along with its journal
+    fn remove_saved(mut editor: Editor, path: &Path) {
+        editor.journal.as_mut().unwrap().clear().unwrap();
+        std::fs::remove_file(path).unwrap();
+    }
+
+    fn names(document: &Document) -> Vec<String> {
+        document.layers.iter().map(|layer| layer.name.clone()).collect()
+    }
+
+    /// The points of the first curve of `layer`
+    fn points(layer: &Layer) -> Vec<CurvePoint> {
+        layer.curves()[0].upgrade().unwrap().lock().borrow().points.clone()
+    }
+
+    #[test]
+    fn test_discard_through() {
+        let path = std::env::temp_dir().join(format!("journal_discard_{}.{}", std::process::id(), format::EXTENSION));
+        let mut journal = Journal::new(&path);
+        let mut info = DocumentInfo::default();
+        for author in ["a", "b", "c"] {
+            info.author = author.to_owned();
+            journal.record(JournalEntry::SetInfo(info.clone())).unwrap();
+        }
+        assert_eq!(journal.end(), 3);
+
+        // a save started after the second entry keeps the third
+        journal.discard_through(2).unwrap();
+        assert_eq!(authors(&journal), ["c"]);
+        assert_eq!(journal.end(), 3);
+
+        info.author = "d".to_owned();
+        journal.record(JournalEntry::SetInfo(info.clone())).unwrap();
+        journal.discard_through(2).unwrap();
+        assert_eq!(authors(&journal), ["c", "d"]);
+
+        journal.discard_through(journal.end()).unwrap();
+        assert!(!journal.has_entries());
+        assert_eq!(journal.end(), 4);
+    }
+
+    #[test]
+    fn test_replay() {
+        let (mut editor, path, recover) = saved_editor("replay");
+
+        editor.add_curve_layer("a".to_owned(), crate::make_curve!((0,0) -> (100,0)));
+        editor.document.info.author = "author".to_owned();
+        editor.record_info();
+        let document = recover();
+        assert_eq!(names(&document), ["a"]);
+        assert_eq!(document.info.author, "author");
+
+        // an edit without its own entry is only journaled once the editor gets to it, as a snapshot replacing the entries before it
+        editor.document.layers[0].name = "renamed".to_owned();
+        editor.mark_edited();
+        assert_eq!(names(&recover()), ["a"]);
+        editor.journal_edits();
+        editor.wait_for_journal();
+        assert_eq!(Journal::new(&path).lines().unwrap().len(), 1);
+        let document = recover();
+        assert_eq!(names(&document), ["renamed"]);
+        assert_eq!(document.info.author, "author");
+        assert_eq!(document.file_path.as_ref(), Some(&path), "the snapshot should keep the document where it is saved");
+
+        // entries recorded after the snapshot replay on top of it
+        editor.add_curve_layer("b".to_owned(), crate::make_curve!((0,0) -> (0,100)));
+        assert_eq!(names(&recover()), ["renamed", "b"]);
+
+        // a pending edit is journaled along with the next one that has its own entry
+        editor.document.layers[1].name = "also renamed".to_owned();
+        editor.mark_edited();
+        editor.add_curve_layer("c".to_owned(), crate::make_curve!((0,0) -> (100,100)));
+        editor.wait_for_journal();
+        assert_eq!(names(&recover()), ["renamed", "also renamed", "c"]);
+
+        remove_saved(editor, &path);
+    }
+
+    #[test]
+    fn test_replay_held_for_snapshot() {
+        let (mut editor, path, recover) = saved_editor("held");
+        editor.add_curve_layer("a".to_owned(), crate::make_curve!((0,0) -> (100,0)));
+        editor.document.layers[0].name = "renamed".to_owned();
+        editor.mark_edited();
+        editor.journal_edits();
+
+        // entries recorded while the snapshot is being written wait for it, then replay on top of it
+        editor.document.info.author = "author".to_owned();
+        editor.record_info();
+        editor.wait_for_journal();
+        let document = recover();
+        assert_eq!(names(&document), ["renamed"]);
+        assert_eq!(document.info.author, "author");
+        assert_eq!(Journal::new(&path).lines().unwrap().len(), 2);
+
+        remove_saved(editor, &path);
+    }
+
+    #[test]
+    fn test_replay_translate() {
+        let (mut editor, path, recover) = saved_editor("translate");
+        editor.add_curve_layer("a".to_owned(), crate::make_curve!((0,0) -> (100,0)));
+        editor.set_selection(Selection::Layers(vec![vec![0]]));
+
+        // the steps of a drag are merged into one entry
+        for _ in 0..3 {
+            editor.translate_selection(Vector2::new(10.0, 5.0));
+        }
+        editor.journal_edits();
+        let lines = Journal::new(&path).lines().unwrap();
+        assert!(matches!(lines.last(), Some((_, JournalEntry::Translate { offset, .. })) if *offset == Vector2::new(30.0, 15.0)));
+        assert_eq!(points(&recover().layers[0]), points(&editor.document.layers[0]));
+
+        // anchors are journaled by where they ended up
+        let curve = editor.document.layers[0].curves()[0].clone();
+        editor.set_selection(Selection::Points(vec![(curve, vec![1..2])]));
+        editor.translate_selection(Vector2::new(0.0, 50.0));
+        editor.journal_edits();
+        assert_eq!(points(&recover().layers[0]), points(&editor.document.layers[0]));
+
+        remove_saved(editor, &path);
+    }
+
+    #[test]
+    fn test_replay_style() {
+        let (mut editor, path, recover) = saved_editor("style");
+        editor.add_curve_layer("a".to_owned(), crate::make_curve!((0,0) -> (100,0)));
+        editor.add_curve_layer("b".to_owned(), crate::make_curve!((0,0) -> (0,100)));
+        let MaybeNew::Existing(style) = &editor.current_style else { panic!("adding a layer should give it an existing style") };
+        let style = style.clone();
+        editor.edit_current_style(|style| style.fill = Pattern::Solid(Color::RED));
+        editor.journal_edits();
+        assert!(matches!(Journal::new(&path).lines().unwrap().last(), Some((_, JournalEntry::SetStyle { .. }))));
+
+        // both layers shared the style, so both are recovered with the edit, though each was journaled with its own copy
+        let document = recover();
+        for layer in &document.layers {
+            let layer_style = layer.style.upgrade().unwrap();
+            assert!(matches!(layer_style.lock().borrow().fill, Pattern::Solid(color) if color == Color::RED));
+        }
+        assert_eq!(document.layers.len(), 2);
+        assert!(editor.document.layers.iter().all(|layer| layer.style.ptr_eq(&style)));
+
+        remove_saved(editor, &path);
+    }
+
+    #[test]
+    fn test_style_record() {
+        let mut document = Document::new("record".to_owned());
+        let profile = Arc::downgrade(document.create_width_profile(WidthProfile::default_width_profile()));
+        let other = Arc::downgrade(document.create_width_profile(WidthProfile::default_width_profile()));
+        let before = Style::default_style(profile);
+
+        let mut after = before.clone();
+        after.fill = Pattern::Solid(Color::RED);
+        assert!(StyleRecord::keeps_resources(&before, &after));
+        let record = StyleRecord::new(&after);
+        let mut replayed = before.clone();
+        record.apply(&mut replayed).unwrap();
+        assert_eq!(StyleRecord::new(&replayed), record);
+
+        after.stroke.width = Some(other);
+        assert!(!StyleRecord::keeps_resources(&before, &after), "a different width profile can't be journaled by value");
+    }
+}
@@ -1,11 +1,42 @@
 use std::{cell::RefCell, sync::{Arc, Weak}};
 use parking_lot::ReentrantMutex;
 use raylib::prelude::*;
-use crate::{curve::WeakCurve, style::WeakStyle};
+use serde::{Deserialize, Serialize};
+use crate::{connector::Connector, curve::{distance_to_segment, Curve, WeakCurve}, export::AssetExport, fill::{self, FillRule}, lod::LodCache, profiler, raster::TiledRaster, shape::LiveRectangle, snapshot::DocumentSnapshot, style::{Marker, Pattern, StrokeTrim, WeakStyle}, units::PixelSnap};
 
 pub type StrongRenderTexture2D =  Arc<ReentrantMutex<RefCell<RenderTexture2D>>>;
 pub type WeakRenderTexture2D   = Weak<ReentrantMutex<RefCell<RenderTexture2D>>>;
 
+/// How many samples are taken along each curve segment when drawing
+///
+/// Strokes take a fixed number per segment, while fills take as many as [`SampleQuality::tolerance`] needs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum SampleQuality {
+    /// Fast, visibly faceted curves
+    Low,
+
+    /// Smooth at typical zoom levels
+    #[default]
+    Medium,
+
+    /// Smooth even when zoomed in closely
+    High,
+}
+
+impl SampleQuality {
+    /// Every quality, in the order they are listed in the UI
+    pub const ALL: [Self; 3] = [Self::Low, Self::Medium, Self::High];
+
+    /// Farthest a sampled fill outline may stray from the curve, in worldspace pixels
+    pub const fn tolerance(self) -> f32 {
+        match self {
+            Self::Low => 1.0,
+            Self::Medium => 0.1,
+            Self::High => 0.02,
+        }
+    }
+}
+
 /// A subset of layers that get rendered in a buffer together
 #[derive(Debug, Default)]
 pub struct Group {
@@ -13,11 +44,78 @@ pub struct Group {
     pub layers: Vec<Layer>,
 }
 
+/// Several curves filled and stroked together as one shape, such as the letter "O"
+///
+/// Where the subpaths overlap is filled or left as a hole depending on their directions
+#[derive(Debug, Default)]
+pub struct CompoundPath {
+    /// The outlines making up the shape
+    ///
+    /// Weakly references curves stored at the [`Document`][`crate::document::Document`] level
+    pub subpaths: Vec<WeakCurve>,
+}
+
+impl CompoundPath {
+    /// Number of points per segment sampled to find which subpaths are inside which
+    const ORIENT_RESOLUTION: u16 = 10;
+
+    /// Turn the subpaths so that those nested inside an odd number of the others run the opposite way
+    /// to the rest, making them holes
+    ///
+    /// Curves are shared by every layer referencing them, so their direction changes in those layers too
+    pub fn orient_holes(&self) {
+        let curves = self.subpaths.iter().filter_map(Weak::upgrade).collect::<Vec<_>>();
+        let polygons = curves.iter()
+            .map(|curve| {
+                let curve_lock = curve.lock();
+                let polyline = curve_lock.borrow().polyline::<{ Self::ORIENT_RESOLUTION }>();
+                polyline
+            })
+            .collect::<Vec<_>>();
+        for (i, (curve, polygon)) in curves.iter().zip(&polygons).enumerate() {
+            let Some(&start) = polygon.first() else { continue };
+            let depth = polygons.iter()
+                .enumerate()
+                .filter(|&(j, other)| j != i && fill::contains(std::slice::from_ref(other), start, FillRule::EvenOdd))
+                .count();
+            let is_clockwise = fill::signed_area(polygon) > 0.0;
+            if is_clockwise != (depth % 2 == 0) {
+                curve.lock().borrow_mut().reverse();
+            }
+        }
+    }
+}
+
 /// The actual content of a layer; either artwork or a collection of artwork
 #[derive(Debug)]
 pub enum LayerContent {
     Group(Group),
     Curve(WeakCurve),
+
+    /// Multiple curves forming one shape with holes
+    Compound(CompoundPath),
+
+    /// A curve whose ends stay attached to other artwork
+    Connector(Connector),
+
+    /// A rectangle with rounded corners, generated from its dimensions
+    Rectangle(LiveRectangle),
+
+    /// A raster placed in the document, such as an imported PNG
+    Image {
+        /// The pixels of the image
+        ///
+        /// Weakly references a raster stored at the [`Document`][`crate::document::Document`] level
+        raster: WeakRenderTexture2D,
+
+        /// The worldspace rectangle the raster is stretched over
+        rect: Rectangle,
+    },
+
+    /// Pixels painted directly into the layer with the raster brush
+    ///
+    /// Owned by the layer rather than the document, since paint is never shared
+    Paint(TiledRaster),
 }
 
 #[derive(Debug)]
@@ -32,4 +130,610 @@ pub struct Layer {
     ///
     /// Weakly refences a reuseably style stored at the [`Document`][`crate::document::Document`] level
     pub style: WeakStyle,
+
+    /// How the layer is written out by "Export All Assets", on its own and cropped to its artwork
+    ///
+    /// [`None`] if the layer isn't exported as an asset
+    pub export: Option<AssetExport>,
+
+    /// Whether the layer is a template, such as a tracing reference or layout skeleton
+    ///
+    /// Templates are drawn dimmed by [`Layer::draw_templates`] and skipped by [`Layer::draw`], so nothing exports them.
+    /// They can't be clicked or selected, and neither can anything inside them
+    pub is_template: bool,
+}
+
+impl Layer {
+    /// Worldspace distance from a curve's outline within which it is clicked, for thin or unstroked curves
+    pub const HIT_TOLERANCE: f32 = 3.0;
+
+    /// Number of points per segment sampled when hit testing curves
+    const HIT_RESOLUTION: u16 = 10;
+
+    /// Multiplied with the tint of [template][`Layer::is_template`] layers
+    pub const TEMPLATE_TINT: Color = Color::new(255, 255, 255, 96);
+
+    /// Draw the layer's artwork in worldspace, as it was when `snapshot` was taken
+    ///
+    /// `tint` is multiplied with every color drawn, so [`Color::WHITE`] draws the layer unmodified.
+    /// [Templates][`Layer::is_template`] are skipped, including those nested in groups
+    pub fn draw(&self, d: &mut impl RaylibDraw, tint: Color, quality: SampleQuality, snapshot: &DocumentSnapshot) {
+        if !self.is_template {
+            self.draw_artwork(d, tint, quality, snapshot);
+        }
+    }
+
+    /// Draw the [template][`Layer::is_template`] layers among this layer and those nested in it, dimmed by [`Layer::TEMPLATE_TINT`]
+    ///
+    /// Everything else is skipped, so this is drawn beneath [`Layer::draw`] in the editor
+    pub fn draw_templates(&self, d: &mut impl RaylibDraw, tint: Color, quality: SampleQuality, snapshot: &DocumentSnapshot) {
+        if self.is_template {
+            self.draw_artwork(d, Self::TEMPLATE_TINT.tint(tint), quality, snapshot);
+        }
+        if let LayerContent::Group(group) = &self.content {
+            for layer in &group.layers {
+                layer.draw_templates(d, tint, quality, snapshot);
+            }
+        }
+    }
+
+    /// Draw the layer's artwork whether or not it is a template
+    fn draw_artwork(&self, d: &mut impl RaylibDraw, tint: Color, quality: SampleQuality, snapshot: &DocumentSnapshot) {
+        /// Draw the portion of the curve within `trim`, sampled `RES` times per segment
+        ///
+        /// Returns the position and outward unit direction of the start and end of the drawn portion,
+        /// or [`None`] if nothing was drawn
+        fn draw_curve<const RES: u16>(d: &mut impl RaylibDraw, curve: &Curve, color: Color, trim: StrokeTrim) -> Option<[(na::Vector2<f32>, na::Vector2<f32>); 2]> {
+            const ROTATE_90DEG: na::Matrix2<f32> = na::Matrix2::new(
+                0.0, -1.0,
+                1.0,  0.0,
+            );
+            let num_segments = curve.segment_count().max(1) as f32;
+            let iter = curve
+                .pos_vel_iter::<RES>()
+                .filter(|&(i, t, _, _)| trim.contains((i as f32 + t) / num_segments))
+                .filter_map(|(_, _, p, v)| Some((p, v.try_normalize(f32::EPSILON)?)));
+
+            let mut ends = None;
+            for (p, tangent) in iter {
+                let outer = ROTATE_90DEG * tangent;
+                let inner = -outer;
+                d.draw_line_v(Vector2::from(p + inner), Vector2::from(p + outer), color);
+                ends = Some(match ends {
+                    None => [(p, -tangent), (p, tangent)],
+                    Some([start, _]) => [start, (p, tangent)],
+                });
+            }
+            ends
+        }
+
+        match &self.content {
+            // draw curve, compound path, connector, or rectangle
+            LayerContent::Curve(_) | LayerContent::Compound(_) | LayerContent::Connector(_) | LayerContent::Rectangle(_) => {
+                let curves = self.curves().iter()
+                    .filter_map(|curve| snapshot.curve(curve).map(Arc::as_ref))
+                    .collect::<Vec<_>>();
+                let (fill_pattern, fill_rule, stroke_pattern, trim, markers) = snapshot.style(&self.style)
+                    .map(|style| {
+                        let stroke = &style.stroke;
+                        let markers = [
+                            (stroke.markers.start.clone(), stroke.end_width(false)),
+                            (stroke.markers.end.clone(), stroke.end_width(true)),
+                        ];
+                        (Some(style.fill.clone()), style.fill_rule, Some(stroke.pattern.clone()), stroke.trim, markers)
+                    })
+                    .unwrap_or_else(|| (None, FillRule::NonZero, None, StrokeTrim::FULL, [(Marker::new(), 0.0), (Marker::new(), 0.0)]));
+                match fill_pattern {
+                    Some(Pattern::Solid(color)) if color.a > 0 => {
+                        fill::draw_triangles(d, &snapshot.fill(&self.curves(), fill_rule, quality), color.tint(tint));
+                    }
+                    Some(Pattern::Texture(raster, transform)) => if let Some(raster) = raster.upgrade()
+                        && let Some(bounds) = curves.iter().filter_map(|curve| curve.control_bounds()).reduce(union)
+                    {
+                        let raster_lock = raster.lock();
+                        let raster_borrow = raster_lock.borrow();
+                        let uv = |point| transform.to_texture(bounds, point);
+                        let triangles = snapshot.fill(&self.curves(), fill_rule, quality);
+                        fill::draw_textured(d, &triangles, raster_borrow.texture(), transform.wrap, uv, tint);
+                    },
+                    _ => (),
+                }
+                // textured strokes aren't rendered yet
+                let color = match stroke_pattern {
+                    Some(Pattern::Solid(color)) => color,
+                    _ => Color::RED,
+                }.tint(tint);
+                for &curve in &curves {
+                    let _span = profiler::span("stroke");
+                    let ends = match quality {
+                        SampleQuality::Low => draw_curve::<10>(d, curve, color, trim),
+                        SampleQuality::Medium => draw_curve::<40>(d, curve, color, trim),
+                        SampleQuality::High => draw_curve::<100>(d, curve, color, trim),
+                    };
+                    // markers are drawn over the stroke, so they follow trimming and every subpath gets its own
+                    for ((end, direction), (marker, width)) in ends.into_iter().flatten().zip(&markers) {
+                        let outline = marker.outline_at(end, direction, *width);
+                        if !outline.is_empty() {
+                            fill::draw(d, &[outline], FillRule::NonZero, color);
+                        }
+                    }
+                }
+            }
+
+            // draw group
+            LayerContent::Group(group) => {
+                for layer in &group.layers {
+                    layer.draw(d, tint, quality, snapshot);
+                }
+            }
+
+            // draw image
+            LayerContent::Image { raster, rect } => {
+                let Some(strong_raster) = raster.upgrade() else {
+                    draw_placeholder(d, *rect, Color::RED.tint(tint));
+                    return;
+                };
+                let raster_lock = strong_raster.lock();
+                let raster_borrow = raster_lock.borrow();
+                let texture = raster_borrow.texture();
+                // render textures are stored upside-down
+                let source = Rectangle::new(0.0, 0.0, texture.width as f32, -texture.height as f32);
+                d.draw_texture_pro(texture, source, *rect, Vector2::zero(), 0.0, tint);
+            }
+
+            // draw paint
+            LayerContent::Paint(paint) => paint.draw(d, tint),
+        }
+    }
+
+    /// Draw the layer like [`Layer::draw`], with outlines taken from `lod` at the level of detail suiting the camera's `zoom`
+    ///
+    /// Curves too small to see are skipped, and strokes are drawn as hairlines since they are thinner than a pixel at
+    /// these zooms. Falls back to [`Layer::draw`] when zoomed in, and for anything simplifying would change visibly,
+    /// such as trimmed strokes, markers, and textures
+    pub fn draw_simplified(&self, d: &mut impl RaylibDraw, tint: Color, quality: SampleQuality, snapshot: &DocumentSnapshot, lod: &mut LodCache, zoom: f32) {
+        if self.is_template {
+            return;
+        }
+        let Some(level) = LodCache::level(zoom) else {
+            self.draw(d, tint, quality, snapshot);
+            return;
+        };
+        match &self.content {
+            LayerContent::Curve(_) | LayerContent::Compound(_) | LayerContent::Connector(_) | LayerContent::Rectangle(_) => {
+                let style = snapshot.style(&self.style).map_or(Some((Color::BLANK, FillRule::NonZero, Color::RED)), |style| {
+                    let stroke = &style.stroke;
+                    let is_simple = stroke.trim.is_full() && stroke.markers.is_none()
+                        && !matches!(style.fill, Pattern::Texture(..));
+                    let solid = |pattern: &Pattern| match pattern {
+                        Pattern::Solid(color) => *color,
+                        _ => Color::RED,
+                    };
+                    is_simple.then(|| (solid(&style.fill), style.fill_rule, solid(&stroke.pattern)))
+                });
+                let Some((fill_color, fill_rule, stroke_color)) = style else {
+                    self.draw(d, tint, quality, snapshot);
+                    return;
+                };
+                let (closed, open): (Vec<_>, Vec<_>) = self.curves().iter()
+                    .filter_map(|curve| {
+                        let copy = snapshot.curve(curve)?;
+                        let outline = lod.outline(curve.as_ptr() as *const (), copy, level, zoom)?.to_vec();
+                        Some((outline, copy.is_closed))
+                    })
+                    .partition(|&(_, is_closed)| is_closed);
+                let closed = closed.into_iter().map(|(outline, _)| outline).collect::<Vec<_>>();
+                if fill_color.a > 0 {
+                    fill::draw(d, &closed, fill_rule, fill_color.tint(tint));
+                }
+                let stroke_color = stroke_color.tint(tint);
+                for outline in closed.iter().chain(open.iter().map(|(outline, _)| outline)) {
+                    for pair in outline.windows(2) {
+                        d.draw_line_v(Vector2::from(pair[0]), Vector2::from(pair[1]), stroke_color);
+                    }
+                }
+            }
+
+            LayerContent::Group(group) => {
+                for layer in &group.layers {
+                    layer.draw_simplified(d, tint, quality, snapshot, lod, zoom);
+                }
+            }
+
+            LayerContent::Image { .. } | LayerContent::Paint(_) => self.draw(d, tint, quality, snapshot),
+        }
+    }
+
+    /// Get a rectangle containing all of the layer's artwork
+    ///
+    /// See [`Curve::control_bounds`][`crate::curve::Curve::control_bounds`]
+    ///
+    /// [`None`] if the layer has no artwork
+    pub fn control_bounds(&self) -> Option<Rectangle> {
+        match &self.content {
+            LayerContent::Curve(curve) | LayerContent::Connector(Connector { curve, .. }) | LayerContent::Rectangle(LiveRectangle { curve, .. }) => {
+                let strong_curve = curve.upgrade()?;
+                let curve_lock = strong_curve.lock();
+                curve_lock.borrow().control_bounds()
+            }
+
+            LayerContent::Compound(compound) => {
+                compound.subpaths.iter()
+                    .filter_map(|subpath| {
+                        let strong_curve = subpath.upgrade()?;
+                        let curve_lock = strong_curve.lock();
+                        let bounds = curve_lock.borrow().control_bounds();
+                        bounds
+                    })
+                    .reduce(union)
+            }
+
+            LayerContent::Group(group) => {
+                group.layers.iter()
+                    .filter_map(Layer::control_bounds)
+                    .reduce(union)
+            }
+
+            LayerContent::Image { rect, .. } => Some(*rect),
+
+            LayerContent::Paint(paint) => paint.bounds(),
+        }
+    }
+
+    /// Get a rectangle containing all of the layer's artwork, grown by its widest stroke so the stroke isn't cropped
+    ///
+    /// [`None`] if the layer has no artwork
+    pub fn visual_bounds(&self) -> Option<Rectangle> {
+        /// The widest stroke of the layer or of any layer in it
+        fn stroke_width(layer: &Layer) -> f32 {
+            match &layer.content {
+                LayerContent::Group(group) => group.layers.iter().map(stroke_width).fold(0.0, f32::max),
+                _ => layer.style.upgrade().map_or(0.0, |style| {
+                    let style_lock = style.lock();
+                    let style_borrow = style_lock.borrow();
+                    style_borrow.stroke.end_width(false).max(style_borrow.stroke.end_width(true))
+                }),
+            }
+        }
+
+        let bounds = self.control_bounds()?;
+        let margin = stroke_width(self);
+        Some(Rectangle::new(bounds.x - margin, bounds.y - margin, bounds.width + margin * 2.0, bounds.height + margin * 2.0))
+    }
+
+    /// Move all of the layer's artwork by the worldspace `offset`
+    ///
+    /// Curves are shared by every layer referencing them, so those layers move too.
+    /// Attached ends of a connector return to their artwork the next time it is [routed][`Connector::route`]
+    pub fn translate(&mut self, offset: Vector2) {
+        match &mut self.content {
+            LayerContent::Curve(curve) | LayerContent::Connector(Connector { curve, .. }) => {
+                if let Some(strong_curve) = curve.upgrade() {
+                    let curve_lock = strong_curve.lock();
+                    curve_lock.borrow_mut().translate(na::Vector2::new(offset.x, offset.y));
+                }
+            }
+
+            LayerContent::Compound(compound) => {
+                for strong_curve in compound.subpaths.iter().filter_map(Weak::upgrade) {
+                    let curve_lock = strong_curve.lock();
+                    curve_lock.borrow_mut().translate(na::Vector2::new(offset.x, offset.y));
+                }
+            }
+
+            LayerContent::Rectangle(rectangle) => {
+                rectangle.rect.x += offset.x;
+                rectangle.rect.y += offset.y;
+                rectangle.regenerate();
+            }
+
+            LayerContent::Group(group) => {
+                for layer in &mut group.layers {
+                    layer.translate(offset);
+                }
+            }
+
+            LayerContent::Image { rect, .. } => {
+                rect.x += offset.x;
+                rect.y += offset.y;
+            }
+
+            LayerContent::Paint(paint) => paint.origin += offset,
+        }
+    }
+
+    /// Rotate the layer's artwork `angle` radians (clockwise on screen) about the worldspace `center`
+    ///
+    /// Images and paint can't be turned, so they are only moved around `center`, keeping their centers on the circle
+    /// Rectangles become plain curves, since they can no longer be rebuilt from a level rectangle
+    pub fn rotate(&mut self, center: Vector2, angle: f32) {
+        let (sin, cos) = angle.sin_cos();
+        let turn = |point: Vector2| {
+            let offset = point - center;
+            center + Vector2::new(offset.x * cos - offset.y * sin, offset.x * sin + offset.y * cos)
+        };
+        let rotate_curve = |curve: &WeakCurve| if let Some(strong_curve) = curve.upgrade() {
+            let curve_lock = strong_curve.lock();
+            curve_lock.borrow_mut().rotate(na::Vector2::new(center.x, center.y), angle);
+        };
+        match &mut self.content {
+            LayerContent::Curve(curve) | LayerContent::Connector(Connector { curve, .. }) => rotate_curve(curve),
+
+            // a turned rectangle's sides aren't level anymore, so it is left as a plain curve
+            LayerContent::Rectangle(rectangle) => {
+                rotate_curve(&rectangle.curve);
+                self.content = LayerContent::Curve(rectangle.curve.clone());
+            }
+
+            LayerContent::Compound(compound) => {
+                for subpath in &compound.subpaths {
+                    rotate_curve(subpath);
+                }
+            }
+
+            LayerContent::Group(group) => {
+                for layer in &mut group.layers {
+                    layer.rotate(center, angle);
+                }
+            }
+
+            LayerContent::Image { rect, .. } => {
+                let middle = Vector2::new(rect.x + rect.width * 0.5, rect.y + rect.height * 0.5);
+                let offset = turn(middle) - middle;
+                rect.x += offset.x;
+                rect.y += offset.y;
+            }
+
+            LayerContent::Paint(paint) => paint.origin = turn(paint.origin),
+        }
+    }
+
+    /// Stretch the layer away from `center` by `factor` along each axis
+    ///
+    /// Only geometry is scaled; stroke widths are left to the caller, since styles are shared between layers.
+    /// Paint can't be resampled, so paint layers only have their origin moved
+    pub fn scale(&mut self, center: Vector2, factor: Vector2) {
+        let stretch = |point: Vector2| Vector2::new(center.x + (point.x - center.x) * factor.x, center.y + (point.y - center.y) * factor.y);
+        let scale_curve = |curve: &WeakCurve| if let Some(strong_curve) = curve.upgrade() {
+            let curve_lock = strong_curve.lock();
+            curve_lock.borrow_mut().scale(na::Vector2::new(center.x, center.y), na::Vector2::new(factor.x, factor.y));
+        };
+        match &mut self.content {
+            LayerContent::Curve(curve) | LayerContent::Connector(Connector { curve, .. }) => scale_curve(curve),
+
+            // the corners stay circular, so the curve is rebuilt rather than stretched
+            LayerContent::Rectangle(rectangle) => {
+                let rect = rectangle.rect;
+                let a = stretch(Vector2::new(rect.x, rect.y));
+                let b = stretch(Vector2::new(rect.x + rect.width, rect.y + rect.height));
+                rectangle.rect = Rectangle::new(a.x.min(b.x), a.y.min(b.y), (b.x - a.x).abs(), (b.y - a.y).abs());
+                rectangle.regenerate();
+            }
+
+            LayerContent::Compound(compound) => {
+                for subpath in &compound.subpaths {
+                    scale_curve(subpath);
+                }
+            }
+
+            LayerContent::Group(group) => {
+                for layer in &mut group.layers {
+                    layer.scale(center, factor);
+                }
+            }
+
+            LayerContent::Image { rect, .. } => {
+                // mirroring swaps the corners
+                let a = stretch(Vector2::new(rect.x, rect.y));
+                let b = stretch(Vector2::new(rect.x + rect.width, rect.y + rect.height));
+                *rect = Rectangle::new(a.x.min(b.x), a.y.min(b.y), (b.x - a.x).abs(), (b.y - a.y).abs());
+            }
+
+            LayerContent::Paint(paint) => paint.origin = stretch(paint.origin),
+        }
+    }
+
+    /// The color of the layer's fill
+    ///
+    /// [`None`] if the fill is a texture or the style no longer exists
+    pub fn fill_color(&self) -> Option<Color> {
+        let style = self.style.upgrade()?;
+        let style_lock = style.lock();
+        let color = match style_lock.borrow().fill {
+            Pattern::Solid(color) => Some(color),
+            Pattern::Texture(..) => None,
+        };
+        color
+    }
+
+    /// Whether a curve or raster the layer's own content references has been dropped
+    ///
+    /// Broken layers draw what remains of them, or a placeholder in place of a missing image,
+    /// until [repaired][`crate::document::Document::repair_layers`]. Groups are never broken themselves
+    pub fn is_broken(&self) -> bool {
+        match &self.content {
+            LayerContent::Curve(curve) | LayerContent::Connector(Connector { curve, .. }) | LayerContent::Rectangle(LiveRectangle { curve, .. }) => curve.strong_count() == 0,
+            LayerContent::Compound(compound) => compound.subpaths.iter().any(|subpath| subpath.strong_count() == 0),
+            LayerContent::Image { raster, .. } => raster.strong_count() == 0,
+            LayerContent::Group(_) | LayerContent::Paint(_) => false,
+        }
+    }
+
+    /// Get every curve in the layer, including those in groups and compound paths
+    pub fn curves(&self) -> Vec<WeakCurve> {
+        match &self.content {
+            LayerContent::Curve(curve) | LayerContent::Connector(Connector { curve, .. }) | LayerContent::Rectangle(LiveRectangle { curve, .. }) => vec![curve.clone()],
+            LayerContent::Compound(compound) => compound.subpaths.clone(),
+            LayerContent::Group(group) => group.layers.iter().flat_map(Layer::curves).collect(),
+            LayerContent::Image { .. } | LayerContent::Paint(_) => Vec::new(),
+        }
+    }
+
+    /// Round the layer's anchors, image bounds, and paint origin to the pixel grid of `snap`
+    ///
+    /// If `is_aligning_strokes`, anchors of curves with a constant-width stroke are rounded so the outer edge
+    /// of the stroke lands on the grid instead, which keeps a 1px stroke from blurring across two pixels
+    ///
+    /// Curves are shared by every layer referencing them, so those layers are rounded too
+    pub fn snap_to_pixels(&mut self, snap: PixelSnap, is_aligning_strokes: bool) {
+        if snap == PixelSnap::Off {
+            return;
+        }
+        let stroke_edge = || if is_aligning_strokes && let Some(style) = self.style.upgrade() {
+            let style_lock = style.lock();
+            let outer = style_lock.borrow().stroke.outer_width();
+            outer.unwrap_or(0.0)
+        } else {
+            0.0
+        };
+        match &mut self.content {
+            LayerContent::Curve(_) | LayerContent::Compound(_) | LayerContent::Connector(_) => {
+                let edge = stroke_edge();
+                for strong_curve in self.curves().iter().filter_map(Weak::upgrade) {
+                    let curve_lock = strong_curve.lock();
+                    curve_lock.borrow_mut().round_anchors(|p| na::Vector2::new(snap.round(p.x + edge) - edge, snap.round(p.y + edge) - edge));
+                }
+            }
+
+            LayerContent::Group(group) => {
+                for layer in &mut group.layers {
+                    layer.snap_to_pixels(snap, is_aligning_strokes);
+                }
+            }
+
+            // the rectangle is rounded rather than its anchors, so the curve can still be rebuilt from it
+            LayerContent::Rectangle(rectangle) => {
+                let edge = stroke_edge();
+                let round = |value: f32| snap.round(value + edge) - edge;
+                let rect = rectangle.rect;
+                let (right, bottom) = (round(rect.x + rect.width), round(rect.y + rect.height));
+                rectangle.rect.x = round(rect.x);
+                rectangle.rect.y = round(rect.y);
+                rectangle.rect.width = right - rectangle.rect.x;
+                rectangle.rect.height = bottom - rectangle.rect.y;
+                rectangle.regenerate();
+            }
+
+            LayerContent::Image { rect, .. } => {
+                let (right, bottom) = (snap.round(rect.x + rect.width), snap.round(rect.y + rect.height));
+                rect.x = snap.round(rect.x);
+                rect.y = snap.round(rect.y);
+                rect.width = right - rect.x;
+                rect.height = bottom - rect.y;
+            }
+
+            LayerContent::Paint(paint) => paint.origin = Vector2::new(snap.round(paint.origin.x), snap.round(paint.origin.y)),
+        }
+    }
+
+    /// Whether the worldspace `point` is on the layer's artwork
+    ///
+    /// Curves and compound paths contain points inside their visible fill, following the style's [fill rule][`FillRule`],
+    /// or within [`Layer::HIT_TOLERANCE`] (or the stroke, if wider) of their outline.
+    /// Other layers are approximated with [`Layer::control_bounds`]; a group contains the point if any of its layers do.
+    /// [Templates][`Layer::is_template`] are locked, so they contain nothing
+    pub fn contains_point(&self, point: Vector2) -> bool {
+        if self.is_template {
+            return false;
+        }
+        match &self.content {
+            LayerContent::Group(group) => group.layers.iter().any(|layer| layer.contains_point(point)),
+
+            LayerContent::Curve(_) | LayerContent::Compound(_) | LayerContent::Connector(_) | LayerContent::Rectangle(_) => {
+                let (is_filled, fill_rule, tolerance) = self.hit_style();
+                let (polygons, closed) = polylines::<{ Self::HIT_RESOLUTION }>(&self.curves());
+                let point = na::Vector2::new(point.x, point.y);
+                (is_filled && fill::contains(&closed, point, fill_rule)) || polygons.iter()
+                    .flat_map(|polygon| polygon.windows(2))
+                    .any(|edge| distance_to_segment(point, edge[0], edge[1]) <= tolerance)
+            }
+
+            _ => self.control_bounds().is_some_and(|bounds| bounds.check_collision_point_rec(point)),
+        }
+    }
+
+    /// Draw the worldspace area [`Layer::contains_point`] is true within, filled with `color`
+    ///
+    /// Used to [pick][`crate::picking::PickBuffer`] layers on the GPU. Groups draw the areas of every layer inside them
+    pub fn draw_hit_area(&self, d: &mut impl RaylibDraw, color: Color) {
+        if self.is_template {
+            return;
+        }
+        match &self.content {
+            LayerContent::Group(group) => {
+                for layer in &group.layers {
+                    layer.draw_hit_area(d, color);
+                }
+            }
+
+            LayerContent::Curve(_) | LayerContent::Compound(_) | LayerContent::Connector(_) | LayerContent::Rectangle(_) => {
+                let (is_filled, fill_rule, tolerance) = self.hit_style();
+                let (polygons, closed) = polylines::<{ Self::HIT_RESOLUTION }>(&self.curves());
+                if is_filled {
+                    fill::draw(d, &closed, fill_rule, color);
+                }
+                // thick lines with round joins cover every point within the tolerance of the outline
+                for polygon in &polygons {
+                    for edge in polygon.windows(2) {
+                        d.draw_line_ex(Vector2::new(edge[0].x, edge[0].y), Vector2::new(edge[1].x, edge[1].y), tolerance * 2.0, color);
+                    }
+                    for point in polygon {
+                        d.draw_circle_v(Vector2::new(point.x, point.y), tolerance, color);
+                    }
+                }
+            }
+
+            _ => if let Some(bounds) = self.control_bounds() {
+                d.draw_rectangle_rec(bounds, color);
+            },
+        }
+    }
+
+    /// Whether the layer's fill can be clicked, the fill rule, and the worldspace distance from its outline within which it is clicked
+    fn hit_style(&self) -> (bool, FillRule, f32) {
+        self.style.upgrade()
+            .map(|style| {
+                let style_lock = style.lock();
+                let style_borrow = style_lock.borrow();
+                let is_filled = !matches!(style_borrow.fill, Pattern::Solid(color) if color.a == 0);
+                let tolerance = style_borrow.stroke.outer_width().unwrap_or(0.0).max(Self::HIT_TOLERANCE);
+                (is_filled, style_borrow.fill_rule, tolerance)
+            })
+            .unwrap_or((false, FillRule::NonZero, Self::HIT_TOLERANCE))
+    }
+}
+
+/// Get the outline of each curve, sampled `RES` times per segment, along with the outlines of only the closed curves
+///
+/// Open curves have no inside, so only the closed outlines are filled. Curves that have been dropped are skipped
+fn polylines<const RES: u16>(curves: &[WeakCurve]) -> (Vec<Vec<na::Vector2<f32>>>, Vec<Vec<na::Vector2<f32>>>) {
+    let mut polylines = Vec::new();
+    let mut closed = Vec::new();
+    for strong_curve in curves.iter().filter_map(Weak::upgrade) {
+        let curve_lock = strong_curve.lock();
+        let curve_borrow = curve_lock.borrow();
+        let polyline = curve_borrow.polyline::<RES>();
+        if curve_borrow.is_closed {
+            closed.push(polyline.clone());
+        }
+        polylines.push(polyline);
+    }
+    (polylines, closed)
+}
+
+/// Draw a crossed-out `rect` in place of artwork that has gone missing
+fn draw_placeholder(d: &mut impl RaylibDraw, rect: Rectangle, color: Color) {
+    let (left, top, right, bottom) = (rect.x, rect.y, rect.x + rect.width, rect.y + rect.height);
+    d.draw_rectangle_lines_ex(rect, 1.0, color);
+    d.draw_line_v(Vector2::new(left, top), Vector2::new(right, bottom), color);
+    d.draw_line_v(Vector2::new(right, top), Vector2::new(left, bottom), color);
+}
+
+/// The smallest rectangle containing both `a` and `b`
+pub fn union(a: Rectangle, b: Rectangle) -> Rectangle {
+    let x = a.x.min(b.x);
+    let y = a.y.min(b.y);
+    let right = (a.x + a.width).max(b.x + b.width);
+    let bottom = (a.y + a.height).max(b.y + b.height);
+    Rectangle::new(x, y, right - x, bottom - y)
 }
@@ -0,0 +1,200 @@
+use std::path::{Path, PathBuf};
+use raylib::prelude::*;
+use crate::{document::Document, export, format, layer::{Layer, SampleQuality}, preferences, snapshot::DocumentSnapshot, style::WeakStyle, svg};
+
+/// What kind of file a [`LibraryItem`] is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum LibraryItemKind {
+    /// A native document holding artwork saved with [`Library::save_snippet`]
+    Snippet,
+
+    /// A PNG image, placed as an image layer
+    Image,
+
+    /// An SVG file, imported as vector layers
+    Svg,
+}
+
+/// A reusable file in the [`Library`]
+#[derive(Debug)]
+pub struct LibraryItem {
+    /// The display name of the item (the file name without extension)
+    pub name: String,
+
+    /// Where the item is stored on the harddrive
+    pub path: PathBuf,
+
+    /// How the item gets placed into a document
+    pub kind: LibraryItemKind,
+
+    /// A [`Library::THUMBNAIL_SIZE`] square preview of the item
+    ///
+    /// Stored upside-down, like every render texture
+    pub thumbnail: RenderTexture2D,
+}
+
+/// An index of reusable artwork and assets in a user folder
+#[derive(Debug)]
+pub struct Library {
+    /// The folder being indexed
+    pub directory: PathBuf,
+
+    /// Every recognized file in `directory`, sorted by name
+    pub items: Vec<LibraryItem>,
+}
+
+impl Library {
+    /// The width and height of item thumbnails
+    pub const THUMBNAIL_SIZE: u32 = 64;
+
+    /// The folder used when the user hasn't chosen one
+    ///
+    /// [`None`] if the home folder can't be determined
+    pub fn default_directory() -> Option<PathBuf> {
+        Some(preferences::app_directory()?.join("library"))
+    }
+
+    /// Index every snippet and image in `directory`, creating the folder if it doesn't exist
+    ///
+    /// Files that fail to load are skipped
+    pub fn index(rl: &mut RaylibHandle, thread: &RaylibThread, directory: PathBuf) -> Result<Self, String> {
+        std::fs::create_dir_all(&directory).map_err(|e| e.to_string())?;
+        let mut library = Self {
+            directory,
+            items: Vec::new(),
+        };
+        let entries = std::fs::read_dir(&library.directory).map_err(|e| e.to_string())?;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if let Err(e) = library.add_item(rl, thread, path.clone()) {
+                eprintln!("failed to index library item {}: {e}", path.display());
+            }
+        }
+        Ok(library)
+    }
+
+    /// Add the file at `path` to the index, replacing any existing item with the same path
+    ///
+    /// Returns `Ok` without adding anything if the file isn't a recognized kind
+    fn add_item(&mut self, rl: &mut RaylibHandle, thread: &RaylibThread, path: PathBuf) -> Result<(), String> {
+        let kind = match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case(format::EXTENSION) => LibraryItemKind::Snippet,
+            Some(ext) if ext.eq_ignore_ascii_case("png") => LibraryItemKind::Image,
+            Some(ext) if ext.eq_ignore_ascii_case("svg") => LibraryItemKind::Svg,
+            _ => return Ok(()),
+        };
+        let thumbnail = match kind {
+            LibraryItemKind::Snippet | LibraryItemKind::Svg => {
+                let snippet = load_vector_item(rl, thread, kind, &path)?;
+                let bounds = snippet.artboards.first().map_or(Rectangle::new(0.0, 0.0, 1.0, 1.0), |artboard| artboard.rect);
+                let snapshot = DocumentSnapshot::of(&snippet.layers);
+                render_thumbnail(rl, thread, Self::THUMBNAIL_SIZE, bounds, |d| {
+                    for layer in &snippet.layers {
+                        layer.draw(d, Color::WHITE, SampleQuality::Medium, &snapshot);
+                    }
+                })?
+            }
+
+            LibraryItemKind::Image => {
+                let image = Image::load_image(path.to_str().ok_or("library path should be valid UTF-8")?)?;
+                let texture = rl.load_texture_from_image(thread, &image)?;
+                let bounds = Rectangle::new(0.0, 0.0, image.width as f32, image.height as f32);
+                render_thumbnail(rl, thread, Self::THUMBNAIL_SIZE, bounds, |d| {
+                    d.draw_texture(&texture, 0, 0, Color::WHITE);
+                })?
+            }
+        };
+        let name = path.file_stem().unwrap_or_default().to_string_lossy().into_owned();
+        self.items.retain(|item| item.path != path);
+        let index = self.items.partition_point(|item| item.name < name);
+        self.items.insert(index, LibraryItem { name, path, kind, thumbnail });
+        Ok(())
+    }
+
+    /// Save `layer` (usually a group) as a snippet named after the layer, and add it to the index
+    ///
+    /// The name is numbered rather than replacing a snippet already saved with it
+    pub fn save_snippet(&mut self, rl: &mut RaylibHandle, thread: &RaylibThread, document: &Document, layer: &Layer) -> Result<(), String> {
+        let path = export::unused_path(&self.directory, &layer.name, format::EXTENSION);
+        format::save_snippet(document, layer, &path)?;
+        self.add_item(rl, thread, path)
+    }
+
+    /// Place a copy of the item into `document`, centered on the worldspace `position`
+    ///
+    /// Snippets and SVGs are loaded fresh from disk, so every placement gets its own resources. \
+    /// Images are given `style`, since they don't bring one of their own
+    pub fn place(&self, rl: &mut RaylibHandle, thread: &RaylibThread, index: usize, document: &mut Document, style: WeakStyle, position: Vector2) -> Result<(), String> {
+        let item = self.items.get(index).ok_or("library item index out of bounds")?;
+        match item.kind {
+            LibraryItemKind::Snippet | LibraryItemKind::Svg => {
+                let snippet = load_vector_item(rl, thread, item.kind, &item.path)?;
+                let center = snippet.artboards.first().map_or(Vector2::zero(), |artboard| {
+                    Vector2::new(artboard.rect.x + artboard.rect.width * 0.5, artboard.rect.y + artboard.rect.height * 0.5)
+                });
+                document.append(snippet, position - center);
+            }
+
+            LibraryItemKind::Image => {
+                let image = Image::load_image(item.path.to_str().ok_or("library path should be valid UTF-8")?)?;
+                document.place_image(rl, thread, item.name.clone(), &image, style, position)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Load a snippet or SVG item as a document
+fn load_vector_item(rl: &mut RaylibHandle, thread: &RaylibThread, kind: LibraryItemKind, path: &Path) -> Result<Document, String> {
+    match kind {
+        LibraryItemKind::Svg => svg::import(path),
+        _ => format::load(rl, thread, path),
+    }
+}
+
+/// Render `bounds` of worldspace content into a square `size` pixels across, preserving aspect ratio
+pub fn render_thumbnail(mut rl: &mut RaylibHandle, thread: &RaylibThread, size: u32, bounds: Rectangle, draw: impl FnOnce(&mut RaylibMode2D<'_, RaylibTextureMode<'_, &mut RaylibHandle>>)) -> Result<RenderTexture2D, String> {
+    let mut rtex = rl.load_render_texture(thread, size, size)?;
+    {
+        let mut d = rl.begin_texture_mode(thread, &mut rtex);
+        d.clear_background(Color::BLANK);
+        let mut d = d.begin_mode2D(thumbnail_camera(size as f32, bounds));
+        draw(&mut d);
+    }
+    Ok(rtex)
+}
+
+/// The camera fitting `bounds` of worldspace content into the middle of a square `size` pixels across, preserving aspect ratio
+///
+/// Bounds smaller than a pixel are zoomed in as if they were a pixel across
+fn thumbnail_camera(size: f32, bounds: Rectangle) -> Camera2D {
+    Camera2D {
+        offset: Vector2::new(size * 0.5, size * 0.5),
+        target: Vector2::new(bounds.x + bounds.width * 0.5, bounds.y + bounds.height * 0.5),
+        rotation: 0.0,
+        zoom: (size / bounds.width.max(1.0)).min(size / bounds.height.max(1.0)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_thumbnail_camera() {
+        let to_thumbnail = |camera: Camera2D, point: Vector2| (point - camera.target) * camera.zoom + camera.offset;
+
+        let camera = thumbnail_camera(32.0, Rectangle::new(10.0, 20.0, 200.0, 50.0));
+        assert_eq!(to_thumbnail(camera, Vector2::new(10.0, 20.0)), Vector2::new(0.0, 12.0), "wide bounds should fill the width, centered vertically");
+        assert_eq!(to_thumbnail(camera, Vector2::new(210.0, 70.0)), Vector2::new(32.0, 20.0));
+
+        let camera = thumbnail_camera(32.0, Rectangle::new(-40.0, 0.0, 20.0, 80.0));
+        assert_eq!(to_thumbnail(camera, Vector2::new(-40.0, 0.0)), Vector2::new(12.0, 0.0), "tall bounds should fill the height, centered horizontally");
+        assert_eq!(to_thumbnail(camera, Vector2::new(-20.0, 80.0)), Vector2::new(20.0, 32.0));
+
+        let camera = thumbnail_camera(32.0, Rectangle::new(5.0, 5.0, 0.0, 0.0));
+        assert_eq!(camera.zoom, 32.0, "empty bounds shouldn't zoom in forever");
+        assert_eq!(to_thumbnail(camera, Vector2::new(5.0, 5.0)), Vector2::new(16.0, 16.0));
+    }
+}
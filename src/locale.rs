@@ -0,0 +1,472 @@
+use std::{collections::HashMap, ffi::CString, fmt::Display, path::PathBuf, sync::LazyLock};
+use parking_lot::RwLock;
+use crate::preferences::app_directory;
+
+/// The language used when the user hasn't chosen one, and the fallback for text missing from other languages
+pub const DEFAULT_LANGUAGE: &str = "en";
+
+/// Every key used by the UI, along with its English text
+///
+/// `{0}`, `{1}`, ... are replaced by the arguments of [`tr_format`]
+const ENGLISH: &[(&str, &str)] = &[
+    ("dialog.cancel", "Cancel"),
+    ("dialog.ok", "OK"),
+
+    ("document_info.title", "Document Info"),
+    ("document_info.author", "Author"),
+    ("document_info.description", "Description"),
+    ("document_info.tags", "Tags"),
+    ("document_info.units", "Units"),
+    ("document_info.dpi", "DPI"),
+    ("document_info.pixel_snap", "Pixel snap"),
+    ("document_info.pixel_snap.off", "Off"),
+    ("document_info.pixel_snap.whole", "Whole"),
+    ("document_info.pixel_snap.half", "Half"),
+    ("document_info.align_strokes", "Align strokes"),
+    ("document_info.created", "Created"),
+    ("document_info.modified", "Modified"),
+    ("document_info.never_saved", "never saved"),
+
+    ("rename.title", "Rename Document"),
+    ("rename.field", "Title"),
+
+    ("note.title", "Add Note"),
+    ("note.field", "Note"),
+
+    ("rename_artboards.title", "Rename Artboards"),
+    ("rename_artboards.find", "Find"),
+    ("rename_artboards.replace", "Replace with"),
+    ("rename_artboards.pattern", "Pattern ({name}, {n})"),
+    ("rename_artboards.numbering", "Start at"),
+    ("rename_artboards.digits", "Digits"),
+    ("rename_artboards.require_unique", "Require unique names"),
+    ("rename_artboards.preview", "{0} -> {1} ({2} renamed)"),
+    ("rename_artboards.duplicates", "Duplicate names: {0}"),
+    ("rename_artboards.no_artboards", "The document has no artboards"),
+
+    ("export.title", "Export"),
+    ("export.preset", "Preset"),
+    ("export.no_presets", "no saved presets"),
+    ("export.numbered_preset", "Preset {0}"),
+    ("export.save_preset", "Save"),
+    ("export.delete_preset", "Delete"),
+    ("export.name", "Name"),
+    ("export.format", "Format"),
+    ("export.scale", "Scale (%)"),
+    ("export.folder", "Folder"),
+    ("export.artboards", "Artboards (blank for all)"),
+    ("export.require_unique", "Require unique artboard names"),
+    ("export_frames.title", "Export Frames"),
+    ("export_frames.fps", "Frames per second"),
+    ("export_frames.gif", "Export as one looping GIF"),
+
+    ("palette.title", "Command Palette"),
+
+    ("preferences.title", "Preferences"),
+    ("preferences.page.general", "General"),
+    ("preferences.page.display", "Display"),
+    ("preferences.page.document", "Document"),
+    ("preferences.page.input", "Input"),
+    ("preferences.page.keymap", "Keymap"),
+    ("preferences.page.accessibility", "Accessibility"),
+    ("preferences.language", "Language"),
+    ("preferences.ui_scale", "UI scale"),
+    ("preferences.font_size", "Font size"),
+    ("preferences.autosave", "Autosave (minutes)"),
+    ("preferences.compress_packages", "Compress packages"),
+    ("preferences.compress_packages_description", "Smaller .avz files, slower saves"),
+    ("preferences.curve_quality", "Curve quality"),
+    ("preferences.quality.low", "Low"),
+    ("preferences.quality.medium", "Medium"),
+    ("preferences.quality.high", "High"),
+    ("preferences.checker_size", "Checker size (px)"),
+    ("preferences.checker_colors", "Checker colors"),
+    ("preferences.vsync", "Vsync"),
+    ("preferences.power_saving", "Power saving"),
+    ("preferences.power_saving_description", "Uncapped only while interacting"),
+    ("preferences.gpu_picking", "GPU picking"),
+    ("preferences.gpu_picking_description", "Faster clicking on complex artwork"),
+    ("preferences.simplify_zoomed_out", "Simplify when zoomed out"),
+    ("preferences.simplify_zoomed_out_description", "Smoother navigation of large documents"),
+    ("preferences.target_fps", "Frame rate cap (0: none)"),
+    ("preferences.idle_fps", "Idle frame rate"),
+    ("preferences.document_width", "Width (px)"),
+    ("preferences.document_height", "Height (px)"),
+    ("preferences.document_dpi", "DPI"),
+    ("preferences.document_units", "Units"),
+    ("preferences.new_object_style", "New objects use"),
+    ("preferences.new_object_style.default", "Default style"),
+    ("preferences.new_object_style.last", "Last selected"),
+    ("preferences.artboard_relative", "Coordinates"),
+    ("preferences.artboard_relative_description", "Relative to the active artboard"),
+    ("preferences.smart_guides", "Smart guides"),
+    ("preferences.zoom_speed", "Zoom speed"),
+    ("preferences.pan_speed", "Scroll speed"),
+    ("preferences.min_zoom", "Minimum zoom"),
+    ("preferences.max_zoom", "Maximum zoom"),
+    ("preferences.invert_scroll", "Invert scroll zoom"),
+    ("preferences.natural_trackpad", "Natural scrolling"),
+    ("preferences.press_a_key", "press a key..."),
+    ("preferences.color_blind_safe", "Color-blind safe"),
+    ("preferences.replace_accent_colors", "Replace accent colors"),
+    ("preferences.selection_outline", "Selection outline"),
+    ("preferences.selection_color", "Selection color"),
+    ("preferences.destructive_color", "Destructive color"),
+
+    ("scatter.title", "Scatter Along Path"),
+    ("scatter.count", "Copies"),
+    ("scatter.rotate", "Follow path"),
+    ("scatter.position_jitter", "Position jitter"),
+    ("scatter.angle_jitter", "Angle jitter"),
+    ("grid_repeat.title", "Grid Repeat"),
+    ("grid_repeat.columns", "Columns"),
+    ("grid_repeat.rows", "Rows"),
+    ("grid_repeat.horizontal_spacing", "Horizontal spacing"),
+    ("grid_repeat.vertical_spacing", "Vertical spacing"),
+    ("grid_repeat.rotation_step", "Rotation per copy"),
+    ("grid_repeat.scale_step", "Scale per copy"),
+    ("scale.title", "Scale"),
+    ("scale.uniform", "Uniform"),
+    ("scale.horizontal", "Horizontal"),
+    ("scale.vertical", "Vertical"),
+    ("scale.strokes", "Scale strokes and effects"),
+    ("scale_document.title", "Scale Document"),
+    ("scale_document.percent", "Scale"),
+    ("scale_document.width", "Artboard width"),
+    ("scale_document.height", "Artboard height"),
+    ("style.title", "Style"),
+    ("style.new", "New; applies to the next item"),
+    ("style.used_once", "Used by 1 layer"),
+    ("style.shared", "Shared by {0} layers"),
+    ("style.fill", "Fill"),
+    ("style.stroke", "Stroke"),
+    ("style.fill_rule", "Fill rule"),
+    ("style.fill_rule.non_zero", "Non-zero"),
+    ("style.fill_rule.even_odd", "Even-odd"),
+    ("style.trim_start", "Trim start"),
+    ("style.trim_end", "Trim end"),
+    ("style.trim_offset", "Trim offset"),
+    ("style.start_marker", "Start marker"),
+    ("style.end_marker", "End marker"),
+    ("style.marker.none", "None"),
+    ("style.marker.arrow", "Arrow"),
+    ("style.marker.open_arrow", "Open arrow"),
+    ("style.marker.dot", "Dot"),
+    ("style.marker.square", "Square"),
+    ("style.marker.bar", "Bar"),
+    ("style.marker.custom", "Custom"),
+    ("style.break_link", "Break link"),
+    ("style.edit_shared", "Edit all"),
+    ("style.fork", "Fork"),
+    ("style.make_default", "Make default"),
+    ("style.texture_wrap", "Tiling"),
+    ("style.texture_wrap.clamp", "Clamp"),
+    ("style.texture_wrap.repeat", "Repeat"),
+    ("style.texture_wrap.mirror", "Mirror"),
+    ("style.width_preset", "Width profile"),
+    ("style.save_width_preset", "Save"),
+    ("arrange.title", "Arrange Artboards"),
+    ("arrange.columns", "Columns"),
+    ("arrange.spacing", "Spacing"),
+    ("asset.title", "Asset Export"),
+    ("asset.exported", "Export as asset"),
+    ("asset.name", "File name"),
+    ("asset.scales", "Scales"),
+    ("asset.format", "Format"),
+    ("artboards.title", "Artboards"),
+    ("artboards.select_all", "Select all"),
+    ("artboards.select_none", "Select none"),
+    ("artboards.width", "Width"),
+    ("artboards.height", "Height"),
+    ("artboards.resize", "Make same size"),
+    ("artboards.align_left", "Left"),
+    ("artboards.align_center_x", "Center"),
+    ("artboards.align_right", "Right"),
+    ("artboards.align_top", "Top"),
+    ("artboards.align_center_y", "Middle"),
+    ("artboards.align_bottom", "Bottom"),
+    ("artboards.distribute_x", "Distribute across"),
+    ("artboards.distribute_y", "Distribute down"),
+    ("artboards.export", "Export selected..."),
+    ("layers.title", "Layers"),
+    ("width.title", "Width Profile"),
+    ("width.none", "No width profile on this stroke"),
+    ("width.inner", "Inner"),
+    ("width.outer", "Outer"),
+    ("width.make_variable", "Make variable"),
+    ("width.link_sides", "Link sides"),
+    ("width.add_point", "Add"),
+    ("width.remove_point", "Remove"),
+    ("width.position", "Position"),
+    ("palette_export.title", "Export Palette"),
+    ("palette.format", "Format"),
+    ("palette.format.gpl", "GIMP"),
+    ("palette.format.ase", "Adobe ASE"),
+    ("palette.format.hex", "Hex list"),
+    ("swatch.rgb", "Screen color"),
+    ("swatch.cmyk", "Process color"),
+    ("swatch.spot", "Spot color: {0}"),
+    ("swatch.inks", "C {0}%  M {1}%  Y {2}%  K {3}%"),
+    ("swatch_dialog.title", "Swatch"),
+    ("swatch_dialog.name", "Name"),
+    ("swatch_dialog.kind", "Type"),
+    ("swatch_dialog.kind.rgb", "Screen"),
+    ("swatch_dialog.kind.cmyk", "Process"),
+    ("swatch_dialog.kind.spot", "Spot"),
+    ("swatch_dialog.cyan", "Cyan (%)"),
+    ("swatch_dialog.magenta", "Magenta (%)"),
+    ("swatch_dialog.yellow", "Yellow (%)"),
+    ("swatch_dialog.black", "Black (%)"),
+    ("swatch_dialog.ink", "Ink name"),
+
+    ("action.point_select", "Point select tool"),
+    ("action.select", "Select tool"),
+    ("action.raster_brush", "Raster brush tool"),
+    ("action.vector_brush", "Vector brush tool"),
+    ("action.copy", "Copy"),
+    ("action.paste", "Paste"),
+    ("action.toggle_animation", "Toggle animation mode"),
+    ("action.previous_frame", "Previous frame"),
+    ("action.next_frame", "Next frame"),
+    ("action.play_pause", "Play/pause"),
+    ("action.export_frames", "Export frames"),
+    ("action.toggle_library", "Toggle library"),
+    ("action.save_snippet", "Save snippet"),
+    ("action.define_graphic_style", "Define graphic style"),
+    ("action.apply_graphic_style", "Apply graphic style {0}"),
+    ("action.redefine_graphic_style", "Redefine graphic style {0} from selection"),
+    ("action.document_info", "Document info"),
+    ("action.preferences", "Preferences"),
+    ("action.trim_rasters", "Trim rasters"),
+    ("action.toggle_style_panel", "Toggle style panel"),
+    ("action.compare_with_saved", "Compare with saved"),
+    ("action.cycle_artboard_background", "Cycle artboard background"),
+    ("action.rename_document", "Rename document"),
+    ("action.command_palette", "Command palette"),
+    ("action.decrease_brush_size", "Decrease brush size"),
+    ("action.increase_brush_size", "Increase brush size"),
+    ("action.swap_fill_stroke", "Swap fill and stroke"),
+    ("action.reset_fill_stroke", "Default fill and stroke"),
+    ("action.clear_color", "Set color to none"),
+    ("action.toggle_navigator", "Toggle navigator"),
+    ("action.toggle_layer_panel", "Toggle layers panel"),
+    ("action.export_last_settings", "Export with last settings"),
+    ("action.export", "Export..."),
+    ("action.rename_artboards", "Rename artboards"),
+    ("action.toggle_smart_guides", "Toggle smart guides"),
+    ("action.toggle_eraser", "Toggle eraser"),
+    ("action.decrease_eraser_hardness", "Decrease eraser hardness"),
+    ("action.increase_eraser_hardness", "Increase eraser hardness"),
+    ("action.cycle_pixel_snap", "Cycle pixel snap"),
+    ("action.simplify", "Simplify"),
+    ("action.make_compound_path", "Make compound path"),
+    ("action.release_compound_path", "Release compound path"),
+    ("action.lasso", "Lasso tool"),
+    ("action.magic_wand", "Magic wand tool"),
+    ("action.scatter_along_path", "Scatter along path"),
+    ("action.toggle_profiler", "Toggle profiler"),
+    ("action.save_trace", "Save profiler trace"),
+    ("action.select_next_layer", "Select next object"),
+    ("action.select_previous_layer", "Select previous object"),
+    ("action.scale_selection", "Scale selection"),
+    ("action.toggle_width_panel", "Toggle width profile panel"),
+    ("action.export_palette", "Export palette"),
+    ("action.copy_as_png", "Copy as PNG"),
+    ("action.copy_as_svg", "Copy as SVG"),
+    ("action.toggle_artboard_coordinates", "Toggle artboard-relative coordinates"),
+    ("action.toggle_artboard_panel", "Toggle artboards panel"),
+    ("action.arrange_artboards", "Arrange artboards in a grid"),
+    ("action.mark_as_asset", "Mark as asset..."),
+    ("action.export_all_assets", "Export All Assets"),
+    ("action.connect", "Connect selection"),
+    ("action.toggle_connector_routing", "Toggle straight/elbow connector"),
+    ("action.rectangle", "Rectangle tool"),
+    ("action.repair_layers", "Repair broken layers"),
+    ("action.save_view", "Save current view"),
+    ("action.next_view", "Go to next saved view"),
+    ("action.previous_view", "Go to previous saved view"),
+    ("action.toggle_annotations", "Show or hide annotations"),
+    ("action.add_note", "Add note"),
+    ("action.add_arrow", "Add arrow from last note"),
+    ("action.toggle_markup", "Toggle markup brush"),
+    ("action.remove_annotation", "Remove annotation"),
+    ("action.grid_repeat", "Grid repeat"),
+    ("action.scale_document", "Scale document"),
+    ("action.toggle_trim_view", "Toggle trim view"),
+    ("action.make_template", "Make template"),
+    ("action.release_templates", "Release templates"),
+    ("action.bucket", "Bucket tool"),
+
+    ("tool.vector_pen", "Vector pen tool"),
+    ("simplify.tolerance", "Tolerance"),
+    ("simplify.anchors", "{0} to {1} anchors"),
+    ("tool_options.stabilizer", "Stabilizer"),
+    ("tool_options.stabilizer.off", "Off"),
+    ("tool_options.stabilizer.average", "Average"),
+    ("tool_options.stabilizer.pull_string", "String"),
+    ("tool_options.tolerance", "Tolerance"),
+    ("tool_options.gap", "Gap"),
+    ("tool_options.calligraphy", "Calligraphy"),
+    ("tool_options.recognize_shapes", "Recognize shapes"),
+    ("tool_options.tip.round", "Round"),
+    ("tool_options.spacing", "Spacing"),
+    ("tool_options.jitter", "Jitter"),
+    ("tool_options.follow", "Follow"),
+    ("tool.fill_well", "Fill"),
+    ("tool.stroke_well", "Stroke"),
+
+    ("tooltip.quick_export", "Export this artboard"),
+
+    ("template.default", "Default"),
+    ("template.icon", "Icon (64 x 64 px)"),
+    ("template.screen", "Screen (1920 x 1080 px)"),
+    ("template.a4", "A4 (210 x 297 mm)"),
+    ("template.us_letter", "US Letter (8.5 x 11 in)"),
+    ("template.transparent", "Transparent (512 x 512 px)"),
+
+    ("tooltip.new_document", "New document"),
+    ("tooltip.templates", "New document from template"),
+    ("tooltip.open_document", "Open document"),
+
+    ("status.broken_layers", "{0} broken layers; run \"Repair broken layers\" to fix them"),
+    ("status.comparing", "comparing with saved: {0} added, {1} removed, {2} moved, {3} modified"),
+    ("status.frame", "frame {0}/{1} @ {2}fps"),
+    ("status.frame_playing", "frame {0}/{1} @ {2}fps (playing)"),
+    ("status.importing", "Importing {0}"),
+    ("status.opening", "Opening {0}"),
+    ("status.rasters", "{0} rasters ({1})"),
+    ("status.saving", "Saving {0}"),
+    ("status.selection", "{0} layers, {1} anchors selected at {2}, {3} ({4} x {5})"),
+];
+
+/// Localized UI text, by key
+///
+/// Loaded from `<language>.json` in the [locale directory][`locale_directory`], a JSON object mapping keys to text. \
+/// Keys missing from the file fall back to English
+#[derive(Debug, Clone)]
+pub struct Catalog {
+    language: String,
+    strings: HashMap<String, String>,
+}
+
+impl Catalog {
+    /// Construct the built-in English catalog
+    pub fn english() -> Self {
+        Self {
+            language: DEFAULT_LANGUAGE.to_owned(),
+            strings: ENGLISH.iter()
+                .map(|&(key, text)| (key.to_owned(), text.to_owned()))
+                .collect(),
+        }
+    }
+
+    /// Load the catalog of `language` from the [locale directory][`locale_directory`]
+    ///
+    /// English is built in and never needs a file
+    pub fn load(language: &str) -> Result<Self, String> {
+        let mut catalog = Self::english();
+        if language == DEFAULT_LANGUAGE {
+            return Ok(catalog);
+        }
+        let path = locale_directory()
+            .ok_or("locale directory could not be determined")?
+            .join(format!("{language}.json"));
+        let json = std::fs::read_to_string(&path).map_err(|e| format!("{}: {e}", path.display()))?;
+        let strings: HashMap<String, String> = serde_json::from_str(&json).map_err(|e| format!("{}: {e}", path.display()))?;
+        catalog.language = language.to_owned();
+        catalog.strings.extend(strings);
+        Ok(catalog)
+    }
+
+    /// The language of the catalog, like `en`
+    #[inline]
+    pub fn language(&self) -> &str {
+        &self.language
+    }
+
+    /// Get the text of `key`, or the key itself if the catalog has no text for it
+    pub fn get<'a>(&'a self, key: &'a str) -> &'a str {
+        self.strings.get(key).map_or(key, String::as_str)
+    }
+}
+
+/// The catalog used by [`tr`], replaced by [`set_language`]
+static CATALOG: LazyLock<RwLock<Catalog>> = LazyLock::new(|| RwLock::new(Catalog::english()));
+
+/// Where language files are stored
+///
+/// [`None`] if the home folder can't be determined
+pub fn locale_directory() -> Option<PathBuf> {
+    Some(app_directory()?.join("locales"))
+}
+
+/// Get every language that can be switched to: English, followed by every language file in the [locale directory][`locale_directory`]
+pub fn available_languages() -> Vec<String> {
+    let mut languages = vec![DEFAULT_LANGUAGE.to_owned()];
+    if let Some(entries) = locale_directory().and_then(|directory| std::fs::read_dir(directory).ok()) {
+        let mut installed = entries
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .filter_map(|path| Some(path.file_stem()?.to_str()?.to_owned()))
+            .filter(|language| language != DEFAULT_LANGUAGE)
+            .collect::<Vec<_>>();
+        installed.sort();
+        languages.extend(installed);
+    }
+    languages
+}
+
+/// Switch every UI string to `language`, taking effect the next time the UI is drawn
+///
+/// The current language is kept if `language` can't be loaded
+pub fn set_language(language: &str) -> Result<(), String> {
+    if CATALOG.read().language() == language {
+        return Ok(());
+    }
+    let catalog = Catalog::load(language)?;
+    *CATALOG.write() = catalog;
+    Ok(())
+}
+
+/// Get the localized text of `key`
+pub fn tr(key: &str) -> String {
+    CATALOG.read().get(key).to_owned()
+}
+
+/// Get the localized text of `key` as a C string, for raygui controls
+pub fn tr_c(key: &str) -> CString {
+    CString::new(tr(key)).unwrap_or_default()
+}
+
+/// Get the localized texts of `keys` joined with `;`, for raygui controls with multiple items like toggle groups
+pub fn tr_list(keys: &[&str]) -> CString {
+    let catalog = CATALOG.read();
+    let text = keys.iter()
+        .map(|key| catalog.get(key))
+        .collect::<Vec<_>>()
+        .join(";");
+    CString::new(text).unwrap_or_default()
+}
+
+/// Get the localized text of `key` with `{0}`, `{1}`, ... replaced by `args`
+pub fn tr_format(key: &str, args: &[&dyn Display]) -> String {
+    let mut text = tr(key);
+    for (i, arg) in args.iter().enumerate() {
+        text = text.replace(&format!("{{{i}}}"), &arg.to_string());
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_english_keys_are_unique() {
+        let mut keys = std::collections::HashSet::new();
+        for (key, _) in ENGLISH {
+            assert!(keys.insert(key), "{key} is listed twice");
+        }
+    }
+}
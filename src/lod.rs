@@ -0,0 +1,137 @@
+use std::{collections::HashMap, sync::Arc};
+use raylib::prelude::*;
+use crate::curve::{self, Curve};
+
+/// Worldspace tolerance of each level of detail, from the most detailed
+///
+/// Each level strays up to four times farther from the curve than the one before
+const LEVEL_TOLERANCES: [f32; 4] = [1.0, 4.0, 16.0, 64.0];
+
+/// Farthest a simplified outline may stray from the curve, in screenspace pixels
+const SCREEN_TOLERANCE: f32 = 0.5;
+
+/// Curves narrower and shorter than this many screenspace pixels are skipped
+const MIN_SCREEN_SIZE: f32 = 0.5;
+
+/// The simplified outlines of one curve
+#[derive(Debug)]
+struct CurveLod {
+    /// The [snapshot][`crate::snapshot::DocumentSnapshot`] copy the outlines were made from, to tell when the curve has been edited since
+    curve: Arc<Curve>,
+
+    /// The outline at each of the [levels][`LEVEL_TOLERANCES`]
+    levels: [Vec<na::Vector2<f32>>; LEVEL_TOLERANCES.len()],
+
+    /// Worldspace size of the rectangle containing the outline
+    size: na::Vector2<f32>,
+
+    /// Whether the outlines were drawn since the last [`LodCache::retain_used`]
+    is_used: bool,
+}
+
+impl CurveLod {
+    fn new(curve: &Arc<Curve>) -> Self {
+        let mut sampled = Vec::new();
+        curve.sample_into(&mut sampled, LEVEL_TOLERANCES[0]);
+        let levels = LEVEL_TOLERANCES.map(|tolerance| {
+            curve::simplify_polyline(&sampled, tolerance).into_iter()
+                .map(|i| sampled[i])
+                .collect()
+        });
+        let (min, max) = sampled.iter().fold(
+            (na::Vector2::repeat(f32::INFINITY), na::Vector2::repeat(f32::NEG_INFINITY)),
+            |(min, max), p| (min.inf(p), max.sup(p)),
+        );
+        Self {
+            curve: curve.clone(),
+            levels,
+            size: if sampled.is_empty() { na::Vector2::zeros() } else { max - min },
+            is_used: true,
+        }
+    }
+
+    /// Whether the outlines were made from `curve` as it is now
+    ///
+    /// Snapshots only copy a curve again once it changes, so an unchanged curve is the same copy
+    fn is_current(&self, curve: &Arc<Curve>) -> bool {
+        Arc::ptr_eq(&self.curve, curve)
+    }
+}
+
+/// Outlines of curves simplified ahead of time at several levels of detail, for drawing while zoomed out
+///
+/// Entries are keyed by the address of their document curve and remade whenever the curve is edited.
+/// Call [`LodCache::retain_used`] once per frame so deleted curves don't linger
+#[derive(Debug, Default)]
+pub struct LodCache {
+    curves: HashMap<*const (), CurveLod>,
+}
+
+impl LodCache {
+    /// Construct an empty cache
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The index of the simplest level that still looks smooth at the camera's `zoom`
+    ///
+    /// [`None`] if the curves should be drawn in full
+    pub fn level(zoom: f32) -> Option<usize> {
+        let tolerance = SCREEN_TOLERANCE / zoom;
+        LEVEL_TOLERANCES.iter().rposition(|&level| level <= tolerance)
+    }
+
+    /// The outline of `curve`, the snapshot copy of the document curve at `key`, simplified to the detail `level`
+    ///
+    /// [`None`] if the curve is smaller than [`MIN_SCREEN_SIZE`] at the camera's `zoom`
+    pub fn outline(&mut self, key: *const (), curve: &Arc<Curve>, level: usize, zoom: f32) -> Option<&[na::Vector2<f32>]> {
+        let lod = self.curves.entry(key)
+            .and_modify(|lod| if !lod.is_current(curve) {
+                *lod = CurveLod::new(curve);
+            })
+            .or_insert_with(|| CurveLod::new(curve));
+        lod.is_used = true;
+        if lod.size.x * zoom < MIN_SCREEN_SIZE && lod.size.y * zoom < MIN_SCREEN_SIZE {
+            return None;
+        }
+        lod.levels.get(level).map(Vec::as_slice)
+    }
+
+    /// Forget the curves that haven't been drawn since the last call
+    pub fn retain_used(&mut self) {
+        self.curves.retain(|_, lod| std::mem::take(&mut lod.is_used));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lod_cache() {
+        assert_eq!(LodCache::level(1.0), None);
+        assert_eq!(LodCache::level(0.5), Some(0));
+        assert_eq!(LodCache::level(0.1), Some(1));
+        assert_eq!(LodCache::level(0.001), Some(3));
+
+        let circle = Arc::new(crate::make_curve!((0,0) -> arc(0, 360, 500) -> cycle));
+        let key = Arc::as_ptr(&circle) as *const ();
+        let mut cache = LodCache::new();
+        let start = cache.outline(key, &circle, 0, 0.5).unwrap()[0];
+        let detailed = cache.outline(key, &circle, 0, 0.5).unwrap().len();
+        let simple = cache.outline(key, &circle, 3, 0.5).unwrap().len();
+        assert!(simple < detailed, "simplest level should have fewer points ({simple} vs {detailed})");
+        assert!(cache.outline(key, &circle, 0, 1e-4).is_none(), "sub-pixel curves should be skipped");
+
+        // a new copy of the edited curve remakes its outlines
+        let mut edited = (*circle).clone();
+        edited.translate(na::Vector2::new(1000.0, 0.0));
+        let moved = cache.outline(key, &Arc::new(edited), 0, 0.5).unwrap()[0];
+        assert!((moved - start - na::Vector2::new(1000.0, 0.0)).norm() < 1e-3);
+
+        cache.retain_used();
+        assert_eq!(cache.curves.len(), 1);
+        cache.retain_used();
+        assert!(cache.curves.is_empty(), "curves not drawn since the last frame should be forgotten");
+    }
+}
@@ -1,17 +1,64 @@
 #![feature(let_chains, if_let_guard, arbitrary_self_types)]
 #![warn(arithmetic_overflow, clippy::arithmetic_side_effects)]
 
-use std::sync::Arc;
-use document::{Artboard, Document};
-use editor::{Editor, MaybeNew, Tool};
+use std::{ffi::CString, rc::Rc, sync::Arc};
+use brush_tip::BrushTipLibrary;
+use command::EditorAction;
+use dialog::{ArrangeArtboardsDialog, ArtboardPanel, AssetExportDialog, ArtboardRenameDialog, CommandPalette, DialogResult, DocumentInfoDialog, ExportDialog, FrameExportDialog, GridRepeatDialog, LayerPanel, NoteDialog, PaletteExportDialog, PreferencesDialog, RenameDialog, ScaleDialog, ScaleDocumentDialog, ScatterDialog, StylePanel, SwatchDialog, WidthPanel};
+use document::{Artboard, ArtboardBackground, Document};
+use editor::{CornerWidget, Editor, Selection, SpringTool, TextureHandle, Tool, ToolModifiers, ToolSettings};
 use engine::{Engine, EngineTab, EngineTabData, EngineTheme};
+use export::ExportJob;
+use frame::{CanvasCache, CanvasView};
+use journal::JournalEntry;
 use layer::{Layer, LayerContent};
+use library::Library;
+use lod::LodCache;
+use navigator::Navigator;
+use picking::PickBuffer;
+use preferences::{NavigationPreferences, Preferences};
+use revision::{ChangeKind, RevisionDiff};
+use repeat::GridRepeatOptions;
+use scatter::ScatterOptions;
+use snapshot::DocumentSnapshot;
+use toolbar::ToolbarClick;
+use ui::widgets::WidgetId;
+use width_preset::WidthPresetLibrary;
 use raylib::prelude::{KeyboardKey::*, MouseButton::*, *};
-use style::{Style, WidthProfile};
+
+/// Frame-by-frame playback of [artboards][`crate::document::Artboard`]
+mod animation;
+
+/// Design review notes drawn over the artwork but never exported
+mod annotation;
+
+/// Grayscale stamps the raster brush paints with
+mod brush_tip;
+
+/// Filling regions enclosed by paths
+mod bucket;
+
+/// Angle-dependent stroke widths of the calligraphic vector brush
+mod calligraphy;
+
+/// Append-only files of named chunks, which native documents are saved in
+mod chunks;
+
+/// Images on the system clipboard, beyond the text raylib supports
+mod clipboard;
+
+/// Commands runnable by shortcut or from the command palette
+mod command;
+
+/// Lines that stay attached to the artwork at their ends
+mod connector;
 
 /// Vector path
 mod curve;
 
+/// Modal windows for editing settings
+mod dialog;
+
 /// Serializeable artwork
 mod document;
 
@@ -21,34 +68,166 @@ mod editor;
 /// Organizer for all open [editor][`crate::editor::Editor`]
 mod engine;
 
+/// Background export of artwork to image files
+mod export;
+
+/// Reading and writing documents on background threads
+mod file_job;
+
+/// Even-odd and non-zero filling of overlapping outlines
+mod fill;
+
+/// Native document file format
+mod format;
+
+/// Skipping frames, and redraws of the canvas, while nothing changes
+mod frame;
+
+/// Offscreen rendering compared against reference images
+#[cfg(test)]
+mod golden;
+
+/// Animated GIF encoding
+mod gif;
+
+/// Dynamic alignment hints while moving and drawing
+mod guides;
+
+/// Crash recovery log of document edits
+mod journal;
+
 /// [Document][`crate::document::Document`] element
 mod layer;
 
+/// User folder of reusable artwork and assets
+mod library;
+
+/// Translated UI text
+mod locale;
+
+/// Simplified outlines for drawing artwork while zoomed out
+mod lod;
+
+/// Zoomed-out overview of the focused document
+mod navigator;
+
+/// Layered OpenRaster image files for continuing artwork in raster paint applications
+mod ora;
+
+/// Reading and writing GIMP, Adobe Swatch Exchange, and hex list palette files
+mod palette;
+
+/// PDF, EPS, and PDF-compatible Illustrator artwork import, and PDF export
+mod pdf;
+
+/// Finding the layer at a point by drawing hit areas on the GPU
+mod picking;
+
+/// Persisted application settings
+mod preferences;
+
+/// Timing spans for diagnosing slow frames
+mod profiler;
+
+/// Searching a document's layers by style
+mod query;
+
+/// GPU memory management of document rasters
+mod raster;
+
+/// Recognizing brush strokes as simple shapes
+mod recognize;
+
+/// Copies of artwork arranged in a grid
+mod repeat;
+
+/// Comparison of a document with its saved version
+mod revision;
+
+/// Copies of artwork spread along a path
+mod scatter;
+
+/// Shapes drawn from their dimensions, like rounded rectangles
+mod shape;
+
+/// Previewing path simplification before committing it
+mod simplify;
+
+/// Copies of artwork taken for rendering without locking the document
+mod snapshot;
+
+/// Smoothing of shaky brush input
+mod stabilizer;
+
 /// Layer appearance modification
 mod style;
 
+/// Engine-wide named styles linked into documents
+mod style_library;
+
+/// Scalable Vector Graphics import and export
+mod svg;
+
+/// Named document colors with process or spot ink definitions for print
+mod swatch;
+
+/// Settings of the current tool, beside the toolbar
+mod tool_options;
+
+/// Tool buttons along the left edge of the viewport
+mod toolbar;
+
+/// Hover help text for UI controls
+mod tooltip;
+
+/// Building blocks of panels and dialogs
+mod ui;
+
+/// Units of measurement
+mod units;
+
+/// Built-in and user-saved stroke width profiles
+mod width_preset;
+
+/// Reading and writing zip archives, for OpenRaster files and packaged documents
+mod zip;
+
 #[allow(clippy::cognitive_complexity, reason = "you always overcomplicate everything when you listen to this about the main function, Amy.")]
 fn main() {
     let (mut rl, thread) = init()
-        .title("Amity Vector Art")
+        .title(Engine::APP_NAME)
         .size(1280, 720)
         .resizable()
         .build();
 
-    rl.set_target_fps(60);
-    rl.set_window_state(WindowState::set_window_maximized(rl.get_window_state(), true));
-
     // initialize engine
     let mut engine = Engine::new(EngineTheme::default_theme());
+    engine.set_preferences(&mut rl, Preferences::load_or_default());
+    engine.preferences().window.restore(&mut rl);
+
+    engine.brush_tips = BrushTipLibrary::load(&mut rl, &thread, BrushTipLibrary::default_directory())
+        .unwrap_or_else(|e| {
+            eprintln!("failed to load brush tips: {e}");
+            BrushTipLibrary::new()
+        });
+
+    engine.width_presets = WidthPresetLibrary::load(WidthPresetLibrary::default_path())
+        .unwrap_or_else(|e| {
+            eprintln!("failed to load width presets: {e}");
+            WidthPresetLibrary::load(None).unwrap_or_default()
+        });
+
+    engine.library = Library::default_directory()
+        .and_then(|directory| Library::index(&mut rl, &thread, directory)
+            .inspect_err(|e| eprintln!("failed to index library: {e}"))
+            .ok());
 
     // new/open file arent implemented yet, but I still want to make sure documents work right
     #[cfg(debug_assertions)]
     {
         engine.create_editor({
-            let mut document = Document::new("untitled".to_owned());
-            let profile = Arc::downgrade(document.create_width_profile(WidthProfile::default_width_profile()));
-            let mut editor = Editor::new(document, MaybeNew::New(Style::default_style(profile)));
-            let style = editor.upgrade_current_style().clone();
+            let mut editor = Editor::new_default(Document::new("untitled".to_owned()));
+            let style = editor.style_for_new_object();
             editor.document.artboards.push({
                 Artboard::new("artboard 1".to_owned(), Rectangle::new(0.0, 0.0, 512.0, 512.0))
             });
@@ -59,32 +238,126 @@ fn main() {
                 name: "new layer".to_owned(),
                 content,
                 style,
+                export: None,
+                is_template: false,
             });
             editor
         });
     }
 
+    let mut window_title = Engine::APP_NAME.to_owned();
+    let mut pick_buffer = PickBuffer::new();
+    let mut lod_cache = LodCache::new();
+    let mut canvas = CanvasCache::new();
+    let mut snapshot = DocumentSnapshot::new();
+    // an action clicked in the command palette, run at the start of the next frame
+    let mut clicked_palette_action = None;
     while !rl.window_should_close() {
+        engine.capture_window(&rl);
+        let keymap = engine.preferences().keymap;
+        let update_span = profiler::span("update");
+        let display = engine.preferences().display;
+        engine.frames.apply_frame_rate(&mut rl, &display);
+        engine.ui.begin_frame(&mut rl, &engine.theme);
+
+        // command palette
+        let mut palette_action = clicked_palette_action.take();
+        let was_palette_open = engine.command_palette.is_some();
+        if let Some(palette) = &mut engine.command_palette
+            && let Some(result) = palette.handle_keys(&rl)
+        {
+            if result == DialogResult::Confirm {
+                palette_action = palette.selected_action();
+            }
+            engine.command_palette = None;
+        }
+
+        // shortcuts are ignored while a dialog field (or the command palette) is capturing keyboard input
+        let is_typing = was_palette_open
+            || engine.ui.is_capturing_keys()
+            || engine.document_info_dialog.as_ref().is_some_and(DocumentInfoDialog::is_editing)
+            || engine.preferences_dialog.as_ref().is_some_and(PreferencesDialog::is_editing)
+            || engine.export_dialog.as_ref().is_some_and(ExportDialog::is_editing);
+        // an action runs when chosen from the command palette or when its shortcut is pressed while not typing
+        let is_triggered = |rl: &RaylibHandle, action: EditorAction| {
+            palette_action == Some(action) || (!is_typing && keymap.binding(action).is_pressed(rl))
+        };
+
+        if is_triggered(&rl, EditorAction::CommandPalette) {
+            engine.command_palette = Some(CommandPalette::new());
+        }
+
+        // window title
+        {
+            let title = engine.window_title();
+            if title != window_title {
+                rl.set_window_title(&thread, &title);
+                window_title = title;
+            }
+        }
+
+        engine.tick_autosave(rl.get_frame_time());
+        engine.tick_journal(rl.get_frame_time());
+        engine.tick_file_jobs(&mut rl, &thread);
+
+        // exports advance in the background, even for unfocused editors
+        for editor in engine.editors_mut() {
+            if let Some(job) = &mut editor.export {
+                let result = match job.step(&mut rl, &thread, &editor.document) {
+                    Ok(()) => job.poll(),
+                    Err(e) => Some(Err(e)),
+                };
+                if let Some(result) = result {
+                    if let Err(e) = result {
+                        eprintln!("failed to export: {e}");
+                    }
+                    editor.export = None;
+                }
+            }
+        }
+
         // editor tabs
         {
-            if rl.is_mouse_button_pressed(MOUSE_BUTTON_LEFT) {
-                let mouse_pos = rl.get_mouse_position();
+            let mouse_pos = rl.get_mouse_position();
+            if rl.is_mouse_button_pressed(MOUSE_BUTTON_LEFT) && engine.is_template_menu_open {
+                engine.is_template_menu_open = false;
+                let new_tab_rect = engine.tab_iter()
+                    .find_map(|tab| matches!(tab.data, EngineTabData::New { .. }).then_some(tab.rect));
+                let template = new_tab_rect.and_then(|new_tab_rect| {
+                    engine.templates()
+                        .enumerate()
+                        .find(|&(i, _)| Engine::template_menu_item_rect(new_tab_rect, i).check_collision_point_rec(mouse_pos))
+                        .map(|(_, (_, preset))| preset)
+                });
+                if let Some(preset) = template {
+                    engine.create_editor(Editor::new_default(preset.create_document("untitled".to_owned())));
+                }
+            } else if rl.is_mouse_button_pressed(MOUSE_BUTTON_RIGHT) {
+                if let Some(EngineTab { data: EngineTabData::Editor { index, editor, .. }, .. }) = engine.tab_iter().find(|tab| tab.rect.check_collision_point_rec(mouse_pos)) {
+                    let is_pinned = !editor.is_pinned;
+                    engine.set_editor_pinned(index, is_pinned);
+                }
+            } else if rl.is_mouse_button_pressed(MOUSE_BUTTON_LEFT) {
                 if let Some(EngineTab { data, .. }) = engine.tab_iter().find(|tab| tab.rect.check_collision_point_rec(mouse_pos)) {
                     match data {
                         EngineTabData::Editor { index, close_button_rect, .. } => {
-                            if close_button_rect.check_collision_point_rec(mouse_pos) {
+                            if close_button_rect.is_some_and(|rect| rect.check_collision_point_rec(mouse_pos)) {
                                 engine.remove_editor(index);
                             } else {
                                 engine.focus_editor(index);
+                                if engine.click_tab(index, rl.get_time()) {
+                                    engine.open_rename_dialog(index);
+                                }
                             }
                         }
 
-                        EngineTabData::New => {
-                            engine.create_editor({
-                                let mut document = Document::new("untitled".to_owned());
-                                let profile = Arc::downgrade(document.create_width_profile(WidthProfile::default_width_profile()));
-                                Editor::new(document, MaybeNew::New(Style::default_style(profile)))
-                            });
+                        EngineTabData::New { menu_button_rect } => {
+                            if menu_button_rect.check_collision_point_rec(mouse_pos) {
+                                engine.is_template_menu_open = true;
+                            } else {
+                                let document = engine.preferences().default_document.create_document("untitled".to_owned());
+                                engine.create_editor(Editor::new_default(document));
+                            }
                         }
 
                         EngineTabData::Open => {
@@ -95,158 +368,1446 @@ fn main() {
             }
         }
 
+        // dropped files
+        if rl.is_file_dropped() {
+            let dropped_files = rl.load_dropped_files();
+            let paths = dropped_files.paths().into_iter().map(std::path::PathBuf::from).collect::<Vec<_>>();
+            let mouse_pos = rl.get_mouse_position();
+            for path in paths {
+                if let Err(e) = engine.open_dropped_file(&mut rl, &thread, &path, mouse_pos) {
+                    eprintln!("failed to open {}: {e}", path.display());
+                }
+            }
+        }
+
+        // profiler
+        {
+            if is_triggered(&rl, EditorAction::ToggleProfiler) {
+                profiler::set_enabled(!profiler::is_enabled());
+            }
+
+            if is_triggered(&rl, EditorAction::SaveTrace) {
+                let seconds = std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map_or(0, |elapsed| elapsed.as_secs());
+                let result = preferences::app_directory()
+                    .ok_or_else(|| "app directory could not be determined".to_owned())
+                    .map(|directory| directory.join("traces"))
+                    .and_then(|directory| {
+                        std::fs::create_dir_all(&directory).map_err(|e| e.to_string())?;
+                        Ok(directory.join(format!("trace_{seconds}.json")))
+                    })
+                    .and_then(|path| profiler::write_trace(&path));
+                if let Err(e) = result {
+                    eprintln!("failed to save trace: {e}");
+                }
+            }
+        }
+
+        // library panel
+        {
+            if is_triggered(&rl, EditorAction::ToggleLibrary) {
+                engine.is_library_open = !engine.is_library_open;
+            }
+
+            if is_triggered(&rl, EditorAction::SaveSnippet) {
+                if let Err(e) = engine.save_library_snippet(&mut rl, &thread) {
+                    eprintln!("failed to save snippet: {e}");
+                }
+            }
+
+            if engine.is_library_open && engine.library.is_some() {
+                let panel = engine.library_panel(rl.get_screen_width() as f32, rl.get_screen_height() as f32);
+                let mouse_pos = rl.get_mouse_position();
+                if rl.is_mouse_button_pressed(MOUSE_BUTTON_LEFT) {
+                    let rows = engine.library_rows(panel);
+                    engine.library_drag = rows.row_at(mouse_pos)
+                        .filter(|&i| Engine::library_item_rect(rows.rect(i)).check_collision_point_rec(mouse_pos));
+                } else if rl.is_mouse_button_released(MOUSE_BUTTON_LEFT) && let Some(index) = engine.library_drag.take() {
+                    // dropping back onto the panel cancels the drag
+                    if !panel.check_collision_point_rec(mouse_pos) {
+                        if let Err(e) = engine.place_library_item(&mut rl, &thread, index, mouse_pos) {
+                            eprintln!("failed to place library item: {e}");
+                        }
+                    }
+                }
+            }
+        }
+
+        // clipboard
+        if is_triggered(&rl, EditorAction::Copy) {
+            if let Err(e) = engine.copy_layer(&mut rl, &thread) {
+                eprintln!("failed to copy: {e}");
+            }
+        } else if is_triggered(&rl, EditorAction::Paste) {
+            if let Err(e) = engine.paste_layers(&mut rl, &thread) {
+                eprintln!("failed to paste: {e}");
+            }
+        } else if is_triggered(&rl, EditorAction::CopyAsPng) {
+            if let Err(e) = engine.copy_selection_png(&mut rl, &thread) {
+                eprintln!("failed to copy as PNG: {e}");
+            }
+        } else if is_triggered(&rl, EditorAction::CopyAsSvg) {
+            if let Err(e) = engine.copy_selection_svg(&mut rl) {
+                eprintln!("failed to copy as SVG: {e}");
+            }
+        }
+
+        // graphic styles
+        if is_triggered(&rl, EditorAction::DefineGraphicStyle) {
+            if let Err(e) = engine.define_graphic_style(&mut rl, &thread) {
+                eprintln!("failed to define graphic style: {e}");
+            }
+        }
+        for action in EditorAction::ALL {
+            let result = match action {
+                EditorAction::ApplyGraphicStyle(index) if is_triggered(&rl, action) => engine.apply_graphic_style(&mut rl, &thread, index.into()),
+                EditorAction::RedefineGraphicStyle(index) if is_triggered(&rl, action) => engine.redefine_graphic_style(&mut rl, &thread, index.into()),
+                _ => continue,
+            };
+            if let Err(e) = result {
+                eprintln!("failed to update graphic style: {e}");
+            }
+        }
+
+        // document info
+        if is_triggered(&rl, EditorAction::DocumentInfo) {
+            engine.document_info_dialog = engine.focused_editor()
+                .map(|editor| DocumentInfoDialog::new(&editor.document.info));
+        }
+
+        // scatter along path
+        if is_triggered(&rl, EditorAction::ScatterAlongPath) && engine.focused_editor().is_some() {
+            // seeded by the time so each scatter jitters differently
+            let seed = (rl.get_time() * 1000.0) as u64;
+            engine.scatter_dialog = Some(ScatterDialog::new(&ScatterOptions::new(seed)));
+        }
+
+        // grid repeat
+        if is_triggered(&rl, EditorAction::GridRepeat)
+            && let Some(editor) = engine.focused_editor()
+            && editor.grid_repeat_source().is_some()
+        {
+            engine.grid_repeat_dialog = Some(GridRepeatDialog::new(&GridRepeatOptions::new(), &editor.document));
+        }
+
+        // scale document
+        if is_triggered(&rl, EditorAction::ScaleDocument) {
+            engine.scale_document_dialog = engine.focused_editor()
+                .map(|editor| ScaleDocumentDialog::new(&editor.document));
+        }
+
+        // export frames
+        if is_triggered(&rl, EditorAction::ExportFrames)
+            && let Some(editor) = engine.focused_editor()
+            && editor.playback.is_some()
+            && editor.export.is_none()
+        {
+            engine.frame_export_dialog = Some(FrameExportDialog::new(&editor.document.animation));
+        }
+
+        // scale selection
+        if is_triggered(&rl, EditorAction::ScaleSelection) && engine.focused_editor().is_some() {
+            engine.scale_dialog = Some(ScaleDialog::new(&engine.preferences().transform));
+        }
+
+        // arrange artboards
+        if is_triggered(&rl, EditorAction::ArrangeArtboards)
+            && let Some(editor) = engine.focused_editor()
+        {
+            engine.arrange_dialog = Some(ArrangeArtboardsDialog::new(&editor.document));
+        }
+
+        // export palette
+        if is_triggered(&rl, EditorAction::ExportPalette) && engine.focused_editor().is_some() {
+            engine.palette_export_dialog = Some(PaletteExportDialog::new());
+        }
+
+        // style panel
+        if is_triggered(&rl, EditorAction::ToggleStylePanel) {
+            engine.style_panel = match engine.style_panel {
+                Some(_) => None,
+                None => Some(StylePanel::new()),
+            };
+        }
+
+        // width panel
+        if is_triggered(&rl, EditorAction::ToggleWidthPanel) {
+            engine.width_panel = match engine.width_panel {
+                Some(_) => None,
+                None => Some(WidthPanel::new()),
+            };
+        }
+
+        // artboards panel
+        if is_triggered(&rl, EditorAction::ToggleArtboardPanel) {
+            engine.artboard_panel = match engine.artboard_panel {
+                Some(_) => None,
+                None => Some(ArtboardPanel::new()),
+            };
+        }
+
+        // rename document
+        if is_triggered(&rl, EditorAction::RenameDocument)
+            && let Some(index) = engine.focused_editor_index()
+        {
+            engine.open_rename_dialog(index);
+        }
+
+        // add note
+        if is_triggered(&rl, EditorAction::AddNote)
+            && let Some(index) = engine.focused_editor_index()
+            && let Some(editor) = engine.focused_editor()
+        {
+            let position = rl.get_screen_to_world2D(rl.get_mouse_position(), editor.camera);
+            engine.note_dialog = Some(NoteDialog::new(index, position));
+        }
+
+        // rename artboards
+        if is_triggered(&rl, EditorAction::RenameArtboards)
+            && let Some(index) = engine.focused_editor_index()
+        {
+            engine.open_artboard_rename_dialog(index);
+        }
+
+        // export
+        if let Some(index) = engine.focused_editor_index() {
+            if is_triggered(&rl, EditorAction::Export) {
+                engine.open_export_dialog(index);
+            } else if is_triggered(&rl, EditorAction::ExportLastSettings) {
+                // there are no last settings to reuse until the document has been exported from the dialog
+                let last_export = engine.editor(index).and_then(|editor| editor.document.last_export.clone());
+                if let Some(preset) = last_export {
+                    if let Some(editor) = engine.editor_mut(index)
+                        && let Err(e) = editor.start_export(&preset)
+                    {
+                        eprintln!("failed to export: {e}");
+                    }
+                } else {
+                    engine.open_export_dialog(index);
+                }
+            }
+        }
+
+        // asset exports
+        if let Some(index) = engine.focused_editor_index() {
+            if is_triggered(&rl, EditorAction::MarkAsAsset) {
+                engine.open_asset_dialog(index);
+            } else if is_triggered(&rl, EditorAction::ExportAllAssets)
+                && let Some(editor) = engine.editor_mut(index)
+                && let Err(e) = editor.start_asset_export()
+            {
+                eprintln!("failed to export assets: {e}");
+            }
+        }
+
+        // preferences
+        if is_triggered(&rl, EditorAction::Preferences) {
+            engine.preferences_dialog = Some(PreferencesDialog::new(engine.preferences()));
+        }
+        if let Some(dialog) = &mut engine.preferences_dialog {
+            dialog.capture_rebinding(&mut rl);
+        }
+
+        // navigator
+        if is_triggered(&rl, EditorAction::ToggleNavigator) {
+            engine.navigator = match engine.navigator {
+                Some(_) => None,
+                None => Some(Navigator::new()),
+            };
+        }
+        let is_over_navigator = engine.focused_editor().is_some() && engine.navigator.as_ref().is_some_and(|navigator| {
+            navigator.is_dragging() || engine.navigator_bounds(rl.get_screen_width() as f32, rl.get_screen_height() as f32)
+                .check_collision_point_rec(rl.get_mouse_position())
+        });
+        if let Err(e) = engine.tick_navigator(&mut rl, &thread) {
+            eprintln!("failed to render navigator: {e}");
+        }
+
+        // layers panel
+        if is_triggered(&rl, EditorAction::ToggleLayerPanel) {
+            engine.layer_panel = match engine.layer_panel {
+                Some(_) => None,
+                None => Some(LayerPanel::new()),
+            };
+        }
+        let is_over_layer_panel = engine.layer_panel.is_some() && engine.focused_editor().is_some()
+            && engine.layer_panel_bounds(rl.get_screen_width() as f32, rl.get_screen_height() as f32).check_collision_point_rec(rl.get_mouse_position());
+
+        // toolbar
+        let toolbar_bounds = engine.toolbar_bounds(rl.get_screen_width() as f32);
+        let is_over_quick_export = engine.focused_editor().is_some_and(|editor| {
+            editor.document.artboards.iter().any(|artboard| {
+                engine.quick_export_button(editor, artboard).check_collision_point_rec(rl.get_mouse_position())
+            })
+        });
+        let is_over_tool_options = engine.focused_editor().is_some_and(|editor| editor.simplify.is_none() && tool_options::has_options(editor.current_tool))
+            && engine.tool_options_bounds(rl.get_screen_width() as f32).check_collision_point_rec(rl.get_mouse_position());
+        let is_over_simplify = engine.focused_editor().is_some_and(|editor| editor.simplify.is_some())
+            && engine.simplify_panel_bounds(rl.get_screen_width() as f32).check_collision_point_rec(rl.get_mouse_position());
+        let is_over_width_panel = engine.width_panel.is_some() && engine.focused_editor().is_some()
+            && engine.width_panel_bounds(rl.get_screen_width() as f32).check_collision_point_rec(rl.get_mouse_position());
+        let is_over_artboard_panel = engine.artboard_panel.is_some() && engine.focused_editor().is_some()
+            && engine.artboard_panel_bounds(rl.get_screen_width() as f32).check_collision_point_rec(rl.get_mouse_position());
+        // clicks on panels over the viewport shouldn't reach the current tool
+        let is_over_style_panel = engine.style_panel.is_some() && engine.focused_editor().is_some()
+            && engine.style_panel_bounds(rl.get_screen_width() as f32).check_collision_point_rec(rl.get_mouse_position());
+        let is_over_library = engine.is_library_open && engine.library.is_some()
+            && engine.library_panel(rl.get_screen_width() as f32, rl.get_screen_height() as f32).check_collision_point_rec(rl.get_mouse_position());
+        let is_over_ui = engine.ui.is_mouse_over_popup() || is_over_navigator || is_over_layer_panel || is_over_quick_export || is_over_tool_options || is_over_simplify || is_over_width_panel || is_over_artboard_panel || is_over_style_panel || is_over_library || (engine.focused_editor().is_some()
+            && engine.toolbar.contains_point(toolbar_bounds, rl.get_mouse_position()));
+        let mut toolbar_tool = None;
+        let mut toolbar_well = None;
+        if engine.focused_editor().is_some() {
+            let is_right_click = rl.is_mouse_button_pressed(MOUSE_BUTTON_RIGHT);
+            if is_right_click || rl.is_mouse_button_pressed(MOUSE_BUTTON_LEFT) {
+                match engine.toolbar.click(toolbar_bounds, rl.get_mouse_position(), is_right_click) {
+                    ToolbarClick::Picked(tool) => toolbar_tool = Some(tool),
+                    ToolbarClick::Well(well) => toolbar_well = Some(well),
+                    ToolbarClick::Missed | ToolbarClick::Consumed => (),
+                }
+            }
+        }
+        let new_tool = EditorAction::ALL.into_iter()
+            .filter(|&action| is_triggered(&rl, action))
+            .find_map(Tool::from_action)
+            .or(toolbar_tool);
+        if let Some(tool) = new_tool {
+            engine.toolbar.show_tool(tool);
+        }
+
+        // brush size
+        if let Some(editor) = engine.focused_editor()
+            && let Some(size) = engine.preferences().brushes.size(editor.current_tool)
+        {
+            const RESIZE_ACTIONS: [EditorAction; 4] = [
+                EditorAction::DecreaseEraserHardness,
+                EditorAction::IncreaseEraserHardness,
+                EditorAction::DecreaseBrushSize,
+                EditorAction::IncreaseBrushSize,
+            ];
+
+            let (tool, zoom) = (editor.current_tool, editor.camera.zoom);
+            let mouse_pos = rl.get_mouse_position();
+            let mut is_resized = false;
+            if let Some(action) = RESIZE_ACTIONS.into_iter().find(|&action| is_triggered(&rl, action)) {
+                is_resized = engine.run_preference_action(action);
+            } else if !is_typing && !is_over_ui
+                && (rl.is_key_down(KEY_LEFT_ALT) || rl.is_key_down(KEY_RIGHT_ALT))
+                && rl.is_mouse_button_pressed(MOUSE_BUTTON_LEFT)
+            {
+                engine.brush_resize = Some((mouse_pos, size));
+            }
+            if let Some((anchor, start_size)) = engine.brush_resize {
+                if rl.is_mouse_button_down(MOUSE_BUTTON_LEFT) {
+                    // the edge of the brush follows the mouse horizontally
+                    engine.set_brush_size(tool, start_size + (mouse_pos.x - anchor.x) * 2.0 / zoom);
+                } else {
+                    engine.brush_resize = None;
+                    is_resized = true;
+                }
+            }
+            if is_resized && let Err(e) = engine.save_preferences() {
+                eprintln!("failed to save preferences: {e}");
+            }
+        } else {
+            engine.brush_resize = None;
+        }
+        let is_resizing_brush = engine.brush_resize.is_some();
+
+        // tool options are saved once a slider is let go, rather than every frame it moves
+        if engine.is_tool_options_unsaved && !rl.is_mouse_button_down(MOUSE_BUTTON_LEFT) {
+            engine.is_tool_options_unsaved = false;
+            if let Err(e) = engine.save_preferences() {
+                eprintln!("failed to save preferences: {e}");
+            }
+        }
+
+        // smart guides and artboard-relative coordinates
+        for action in [EditorAction::ToggleSmartGuides, EditorAction::ToggleArtboardCoordinates] {
+            if is_triggered(&rl, action)
+                && engine.run_preference_action(action)
+                && let Err(e) = engine.save_preferences()
+            {
+                eprintln!("failed to save preferences: {e}");
+            }
+        }
+        let is_using_smart_guides = engine.preferences().snapping.is_using_smart_guides;
+        let is_artboard_relative = engine.preferences().coordinates.is_artboard_relative;
+        let brushes = engine.preferences().brushes.clone();
+        // shared out of the library, since the editor borrows the engine
+        let brush_tip = brushes.tip.as_deref()
+            .and_then(|name| engine.brush_tips.get(name))
+            .map(|tip| Rc::clone(&tip.texture));
+        let magic_wand = engine.preferences().magic_wand;
+        let bucket = engine.preferences().bucket;
+        let navigation = engine.preferences().navigation;
+        let viewport = engine.viewport(rl.get_screen_width() as f32, rl.get_screen_height() as f32);
+        let viewport_center = Vector2::new(viewport.x + viewport.width * 0.5, viewport.y + viewport.height * 0.5);
+        let font_size = engine.theme.font_size;
+
         // tick editor
         if let Some(editor) = engine.focused_editor_mut() {
             // editor inputs
+            if let Some(tool) = new_tool {
+                editor.set_tool(tool, &brushes);
+            }
+
+            // spring-loaded tools: space pans and control selects until let go, then the previous tool comes back
+            if !is_typing && rl.is_key_pressed(KEY_SPACE) {
+                editor.press_spring_tool(SpringTool::Pan);
+            } else if rl.is_key_released(KEY_SPACE) {
+                editor.release_spring_tool(SpringTool::Pan, &brushes);
+            }
+            if !is_typing && (rl.is_key_pressed(KEY_LEFT_CONTROL) || rl.is_key_pressed(KEY_RIGHT_CONTROL))
+                && !editor.current_tool.is_control_modifier()
+                && !editor.current_tool.is_busy(editor)
             {
-                if rl.is_key_pressed(KEY_P) {
-                    editor.current_tool = Tool::PointSelect;
-                } else if rl.is_key_pressed(KEY_B) {
-                    editor.current_tool =
-                        if rl.is_key_down(KEY_LEFT_SHIFT) {
-                            Tool::VectorBrush
-                        } else {
-                            Tool::RasterBrush
-                        }
-                } else if rl.is_key_pressed(KEY_V) {
-                    editor.current_tool = Tool::PointSelect;
+                editor.press_spring_tool(SpringTool::Select);
+            } else if !rl.is_key_down(KEY_LEFT_CONTROL) && !rl.is_key_down(KEY_RIGHT_CONTROL) {
+                editor.release_spring_tool(SpringTool::Select, &brushes);
+            }
+
+            // actions that only need the editor
+            for action in EditorAction::ALL {
+                if is_triggered(&rl, action) {
+                    editor.run_action(action);
+                }
+            }
+
+            // keyboard nudging
+            if !is_typing && editor.simplify.is_none() {
+                // held arrows repeat, moving one document unit per step; shift for ten units and control for a tenth
+                let is_pressed = |key: KeyboardKey| rl.is_key_pressed(key) || unsafe { raylib::ffi::IsKeyPressedRepeat(key as i32) };
+                let step_key = |negative, positive| f32::from(i8::from(is_pressed(positive))) - f32::from(i8::from(is_pressed(negative)));
+                let direction = Vector2::new(step_key(KEY_LEFT, KEY_RIGHT), step_key(KEY_UP, KEY_DOWN));
+                if direction != Vector2::zero() {
+                    let info = &editor.document.info;
+                    let mut step = info.units.to_px(1.0, info.dpi);
+                    if rl.is_key_down(KEY_LEFT_SHIFT) || rl.is_key_down(KEY_RIGHT_SHIFT) {
+                        step *= 10.0;
+                    } else if rl.is_key_down(KEY_LEFT_CONTROL) || rl.is_key_down(KEY_RIGHT_CONTROL) {
+                        step *= 0.1;
+                    }
+                    editor.translate_selection(direction * step);
                 }
             }
 
+            // color wells
+            if let Some(well) = toolbar_well {
+                editor.active_well = well;
+            }
+
+            // raster memory
+            if is_triggered(&rl, EditorAction::TrimRasters) {
+                if let Err(e) = editor.document.trim_rasters(&mut rl, &thread) {
+                    eprintln!("failed to trim rasters: {e}");
+                }
+            }
+
+            // named views
+            if is_triggered(&rl, EditorAction::SaveView) {
+                editor.save_view(viewport_center);
+            }
+            if is_triggered(&rl, EditorAction::NextView) {
+                editor.cycle_view(false, viewport_center);
+            } else if is_triggered(&rl, EditorAction::PreviousView) {
+                editor.cycle_view(true, viewport_center);
+            }
+
+            // annotations
+            if is_triggered(&rl, EditorAction::AddArrow) {
+                editor.add_arrow(rl.get_screen_to_world2D(rl.get_mouse_position(), editor.camera));
+            }
+            if is_triggered(&rl, EditorAction::RemoveAnnotation)
+                && let Some(index) = editor.annotation_at(rl.get_mouse_position(), font_size)
+            {
+                editor.remove_annotation(index);
+            }
+
+            // artboard background
+            if is_triggered(&rl, EditorAction::CycleArtboardBackground) {
+                let mouse_world_pos = rl.get_screen_to_world2D(rl.get_mouse_position(), editor.camera);
+                editor.cycle_artboard_background_at(mouse_world_pos);
+            }
+
+            // coordinates are measured from the last artboard clicked on
+            if !is_typing && !is_over_ui && rl.is_mouse_button_pressed(MOUSE_BUTTON_LEFT) {
+                let mouse_world_pos = rl.get_screen_to_world2D(rl.get_mouse_position(), editor.camera);
+                editor.activate_artboard_at(mouse_world_pos);
+            }
+
+            // compare with saved
+            if is_triggered(&rl, EditorAction::CompareWithSaved) {
+                editor.comparison = match editor.comparison {
+                    Some(_) => None,
+                    None => RevisionDiff::load(&mut rl, &thread, &editor.document)
+                        .inspect_err(|e| eprintln!("failed to compare with saved: {e}"))
+                        .ok(),
+                };
+            }
+
+            // animation mode
+            {
+                // keeps playing while typing
+                editor.tick_playback(rl.get_frame_time());
+            }
+
             // zoom and pan
             {
+                // trackpads report pinches as ctrl+scroll in small fractional steps
+                const PINCH_ZOOM_SPEED: f32 = 0.1;
+
                 let mut pan = Vector2::zero();
+                // the screenspace point to zoom around, and the factor to zoom by
+                let mut zoom = None;
 
+                let mouse_position = rl.get_mouse_position();
                 let mut scroll = Vector2::from(rl.get_mouse_wheel_move_v());
                 if rl.is_key_down(KEY_LEFT_ALT) {
-                    const ZOOM_SPEED: f32 = 1.5;
-                    const MIN_ZOOM: f32 = 0.125;
-                    const MAX_ZOOM: f32 = 64.0;
-                    let zoom = if scroll.x.abs() < scroll.y.abs() { scroll.y } else { scroll.x };
-                    if zoom > 0.0 && editor.camera.zoom < MAX_ZOOM {
-                        editor.camera.zoom *= ZOOM_SPEED;
-                    } else if zoom < 0.0 && editor.camera.zoom > MIN_ZOOM {
-                        editor.camera.zoom /= ZOOM_SPEED;
+                    let zoom_speed = navigation.zoom_speed.clamp(NavigationPreferences::MIN_ZOOM_SPEED, NavigationPreferences::MAX_ZOOM_SPEED);
+                    let mut steps = if scroll.x.abs() < scroll.y.abs() { scroll.y } else { scroll.x };
+                    if navigation.is_scroll_inverted {
+                        steps = -steps;
+                    }
+                    if steps > 0.0 {
+                        zoom = Some((mouse_position, zoom_speed));
+                    } else if steps < 0.0 {
+                        zoom = Some((mouse_position, zoom_speed.recip()));
+                    }
+                } else if rl.is_key_down(KEY_LEFT_CONTROL) || rl.is_key_down(KEY_RIGHT_CONTROL) {
+                    if scroll.y != 0.0 {
+                        zoom = Some((mouse_position, (scroll.y * PINCH_ZOOM_SPEED).exp()));
                     }
                 } else {
                     if rl.is_key_down(KEY_LEFT_SHIFT) {
                         std::mem::swap(&mut scroll.x, &mut scroll.y);
                     }
-                    pan += scroll * 20.0;
+                    let pan_speed = navigation.pan_speed.clamp(NavigationPreferences::MIN_PAN_SPEED, NavigationPreferences::MAX_PAN_SPEED);
+                    // natural trackpads move the artwork the way the fingers do, the opposite of a scroll bar
+                    let direction = if navigation.is_natural_trackpad { -1.0 } else { 1.0 };
+                    pan += scroll * (pan_speed * direction);
                 }
-                if rl.is_mouse_button_down(MOUSE_BUTTON_MIDDLE) {
+                if rl.is_mouse_button_down(MOUSE_BUTTON_MIDDLE) || (editor.is_panning() && rl.is_mouse_button_down(MOUSE_BUTTON_LEFT)) {
                     let drag = rl.get_mouse_delta();
                     pan += drag;
                 }
 
-                editor.camera.target += (rl.get_mouse_delta() - pan) / editor.camera.zoom;
+                // two-finger touchscreen gestures: dragging pans and pinching zooms
+                let touches = (rl.get_touch_point_count() >= 2)
+                    .then(|| [rl.get_touch_position(0), rl.get_touch_position(1)]);
+                if let Some(pinch) = editor.track_pinch(touches) {
+                    pan += pinch.pan;
+                    zoom = Some((pinch.center, pinch.scale));
+                }
+
+                editor.camera.target += rl.get_mouse_delta() / editor.camera.zoom;
                 editor.camera.offset += rl.get_mouse_delta(); // equivalent to `rl.get_mouse_position()` when loading a file
+                editor.pan(pan);
+                if let Some((anchor, factor)) = zoom {
+                    editor.zoom_about(anchor, factor);
+                }
             }
 
             // tick current tool
-            match editor.current_tool {
-                Tool::PointSelect => {
-
+            let guide_tolerance = is_using_smart_guides.then(|| guides::SNAP_DISTANCE / editor.camera.zoom);
+            if editor.is_panning() {
+                // the left mouse button pans while space is held, leaving the current tool as it was
+            } else if editor.simplify.is_some() {
+                // the simplify preview takes over input until it is applied or canceled
+                if !is_typing && rl.is_key_pressed(KEY_ENTER) {
+                    editor.end_simplify(true);
+                } else if !is_typing && rl.is_key_pressed(KEY_ESCAPE) {
+                    editor.end_simplify(false);
+                }
+            } else {
+                // escape cancels and enter commits whatever the current tool is in the middle of
+                let current_tool = editor.current_tool;
+                let is_tool_busy = !is_typing && current_tool.is_busy(editor);
+                if is_tool_busy && rl.is_key_pressed(KEY_ESCAPE) {
+                    current_tool.cancel(editor);
+                } else if is_tool_busy && (rl.is_key_pressed(KEY_ENTER) || rl.is_key_pressed(KEY_KP_ENTER)) {
+                    current_tool.commit(editor, &brushes);
                 }
+                let position = rl.get_screen_to_world2D(rl.get_mouse_position(), editor.camera);
+                let modifiers = ToolModifiers {
+                    is_control: rl.is_key_down(KEY_LEFT_CONTROL) || rl.is_key_down(KEY_RIGHT_CONTROL),
+                    is_alt: rl.is_key_down(KEY_LEFT_ALT) || rl.is_key_down(KEY_RIGHT_ALT),
+                };
+                let settings = ToolSettings { brushes: &brushes, magic_wand, bucket, guide_tolerance };
+                // alt-dragging resizes the brush instead of painting with it
+                let is_pressed = !is_typing && !is_over_ui && rl.is_mouse_button_pressed(MOUSE_BUTTON_LEFT)
+                    && !(is_resizing_brush && matches!(current_tool, Tool::VectorBrush | Tool::RasterBrush));
+                if current_tool == Tool::RasterBrush {
+                    // dabs are painted every frame the button is held, rather than as the mouse moves
+                    if !is_typing && !is_over_ui && !is_resizing_brush && rl.is_mouse_button_down(MOUSE_BUTTON_LEFT) {
+                        let mut position = editor.stabilizer.push(position, brushes.stabilizer, brushes.stabilizer_strength, editor.camera.zoom);
+                        if let Some(tolerance) = guide_tolerance {
+                            let snap = guides::snap_point(position, &editor.guide_targets(false), tolerance);
+                            position += snap.offset;
+                            editor.smart_guides = snap.guides;
+                        }
+                        let position = editor.snap_point_to_pixels(position);
+                        if editor.is_erasing {
+                            editor.erase(&mut rl, &thread, position, brushes.raster_size * 0.5, brushes.eraser_hardness);
+                        } else if let Err(e) = editor.paint(&mut rl, &thread, position, brushes.raster_size * 0.5, brush_tip.as_deref().map(|texture| (texture, brushes.tip_options))) {
+                            eprintln!("failed to paint: {e}");
+                        }
+                    } else {
+                        editor.end_paint();
+                        editor.smart_guides.clear();
+                    }
+                } else if is_pressed {
+                    current_tool.press(editor, position, rl.get_time(), modifiers, &settings, |editor, position, is_deep| {
+                        if display.is_gpu_picking {
+                            editor.select_layer_by(|layers| {
+                                pick_buffer.pick(&mut rl, &thread, layers, position)
+                                    .inspect_err(|e| eprintln!("failed to pick layer: {e}"))
+                                    .unwrap_or_default()
+                            }, is_deep);
+                        } else {
+                            editor.select_layer_at(position, is_deep);
+                        }
+                    });
+                } else if rl.is_mouse_button_down(MOUSE_BUTTON_LEFT) {
+                    current_tool.drag(editor, position, modifiers, &settings);
+                    current_tool.hold(editor, rl.get_frame_time(), &brushes);
+                } else if current_tool.is_busy(editor) {
+                    current_tool.release(editor, modifiers, &brushes);
+                }
+                if current_tool == Tool::Select && !is_typing && !is_tool_busy && rl.is_key_pressed(KEY_ESCAPE) {
+                    editor.exit_group_scope();
+                }
+            }
+        }
 
-                Tool::VectorBrush => {
+        // connectors follow the artwork they are attached to, however it was moved
+        for editor in engine.editors_mut() {
+            editor.document.route_connectors();
+        }
 
-                }
+        // escape quits, except while the command palette, a text field, or the focused editor would take it instead
+        let is_escape_taken = engine.command_palette.is_some()
+            || engine.ui.is_capturing_keys()
+            || engine.preferences_dialog.as_ref().is_some_and(PreferencesDialog::is_editing)
+            || engine.focused_editor().is_some_and(|editor| {
+                editor.simplify.is_some()
+                    || editor.current_tool.is_busy(editor)
+                    || (editor.current_tool == Tool::Select && !editor.group_scope.is_empty())
+            });
+        rl.set_exit_key(if is_escape_taken { None } else { Some(KEY_ESCAPE) });
 
-                Tool::VectorPen => {
+        drop(update_span);
 
-                }
+        // everything below draws the artwork as it is now, without locking it again
+        if let Some(editor) = engine.focused_editor() {
+            snapshot.update(&editor.document.layers);
+        }
+        if let Err(e) = engine.refresh_layer_panel(&mut rl, &thread, &snapshot) {
+            eprintln!("failed to render layer thumbnails: {e}");
+        }
 
-                Tool::RasterBrush => {
+        // draw canvas
+        let canvas_view = CanvasView::new(
+            engine.focused_editor_index(),
+            engine.editors().len(),
+            engine.focused_editor().map(|editor| editor.camera),
+            (rl.get_screen_width(), rl.get_screen_height()),
+        );
+        let is_canvas_invalidated = frame::has_input(&rl) || engine.frames.was_busy();
+        match canvas.begin(&mut rl, &thread, canvas_view, is_canvas_invalidated) {
+            Ok(Some(texture)) => {
+                let mut d = rl.begin_texture_mode(&thread, texture);
+                d.clear_background(engine.theme.color_background);
+                let screen = Rectangle::new(0.0, 0.0, d.get_screen_width() as f32, d.get_screen_height() as f32);
+                if let Some(editor) = engine.focused_editor() {
+                    // draw artboard backgrounds
+                    for artboard in &editor.document.artboards {
+                        let is_paper = artboard.background == ArtboardBackground::Paper;
+                        // translucent paper shows the checkerboard through it
+                        let is_checkered = artboard.background == ArtboardBackground::Checkerboard
+                            || (is_paper && editor.document.paper_color.a < u8::MAX);
+                        if is_checkered {
+                            let checkerboard = &engine.preferences().checkerboard;
+                            let top_left = d.get_world_to_screen2D(Vector2::new(artboard.rect.x, artboard.rect.y), editor.camera);
+                            let size = Vector2::new(artboard.rect.width, artboard.rect.height) * editor.camera.zoom;
+                            draw_checkerboard(&mut d, screen, Rectangle::new(top_left.x, top_left.y, size.x, size.y), checkerboard.square_size, checkerboard.colors);
+                        }
+                        if is_paper {
+                            let mut d = d.begin_mode2D(editor.camera);
+                            d.draw_rectangle_rec(artboard.rect, editor.document.paper_color);
+                        }
+                    }
+
+                    // draw onion skin
+                    if let Some(playback) = &editor.playback
+                        && let Some(current) = editor.document.artboards.get(playback.frame as usize)
+                    {
+                        let num_frames = editor.document.artboards.len() as u32;
+                        let top_left = d.get_world_to_screen2D(Vector2::new(current.rect.x, current.rect.y), editor.camera);
+                        let bottom_right = d.get_world_to_screen2D(Vector2::new(current.rect.x + current.rect.width, current.rect.y + current.rect.height), editor.camera);
+                        let mut d = d.begin_scissor_mode(
+                            top_left.x as i32,
+                            top_left.y as i32,
+                            (bottom_right.x - top_left.x) as i32,
+                            (bottom_right.y - top_left.y) as i32,
+                        );
+                        for (frame, tint) in playback.onion_skin_frames(&editor.document.animation.onion_skin, num_frames) {
+                            let neighbor = &editor.document.artboards[frame as usize];
+                            // shift the camera so the neighboring artboard lines up with the current one
+                            let mut camera = editor.camera;
+                            camera.target.x += neighbor.rect.x - current.rect.x;
+                            camera.target.y += neighbor.rect.y - current.rect.y;
+                            let mut d = d.begin_mode2D(camera);
+                            for layer in &editor.document.layers {
+                                layer.draw(&mut d, tint, engine.preferences().sample_quality, &snapshot);
+                            }
+                        }
+                    }
 
+                    // draw artwork
+                    {
+                        let _span = profiler::span("artwork");
+                        // the trim view draws the artwork once within each artboard on screen
+                        for clip in editor.artwork_clips(screen) {
+                            let mut d = d.begin_scissor_mode(clip.x as i32, clip.y as i32, clip.width.ceil() as i32, clip.height.ceil() as i32);
+                            let mut d = d.begin_mode2D(editor.camera);
+                            // templates are references to draw over, so they go beneath everything else
+                            for layer in &editor.document.layers {
+                                layer.draw_templates(&mut d, Color::WHITE, engine.preferences().sample_quality, &snapshot);
+                            }
+                            for layer in &editor.document.layers {
+                                if display.is_simplifying_zoomed_out {
+                                    layer.draw_simplified(&mut d, Color::WHITE, engine.preferences().sample_quality, &snapshot, &mut lod_cache, editor.camera.zoom);
+                                } else {
+                                    layer.draw(&mut d, Color::WHITE, engine.preferences().sample_quality, &snapshot);
+                                }
+                            }
+                        }
+                        lod_cache.retain_used();
+                    }
                 }
             }
+            Ok(None) => {}
+            Err(e) => eprintln!("failed to draw canvas: {e}"),
         }
 
         // draw
         let mut d = rl.begin_drawing(&thread);
-        d.clear_background(engine.theme.color_background);
+        canvas.draw(&mut d);
 
         // draw focused editor
+        let mut quick_export = None;
+        let mut quick_export_tooltip = None;
         if let Some(editor) = engine.focused_editor() {
-            // draw viewport 2D
+            // draw grid repeat preview
+            if let Some(dialog) = &engine.grid_repeat_dialog
+                && let Some((paths, bounds)) = editor.grid_repeat_source()
             {
-                let mut d = d.begin_mode2D(editor.camera);
+                let center = Vector2::new(bounds.x + bounds.width * 0.5, bounds.y + bounds.height * 0.5);
+                // the first cell is the original, already drawn
+                for placement in repeat::placements(Vector2::new(bounds.width, bounds.height), &dialog.options()).iter().skip(1) {
+                    let anchor = d.get_world_to_screen2D(center + placement.offset, editor.camera);
+                    let mut d = d.begin_mode2D(repeat::preview_camera(&editor.camera, anchor, center, placement));
+                    for layer in paths.iter().filter_map(|path| editor.document.layer_at(path)) {
+                        layer.draw(&mut d, repeat::PREVIEW_TINT, engine.preferences().sample_quality, &snapshot);
+                    }
+                }
+            }
 
-                // draw artboard backgrounds
-                for artboard in &editor.document.artboards {
-                    d.draw_rectangle_rec(artboard.rect, editor.document.paper_color);
+            // draw comparison with saved
+            if let Some(comparison) = &editor.comparison {
+                let changes = comparison.changes(&editor.document);
+                {
+                    let mut d = d.begin_mode2D(editor.camera);
+                    comparison.draw(&mut d, &editor.document, &snapshot, &changes, engine.preferences().sample_quality);
                 }
+                let count = |f: fn(&ChangeKind) -> bool| changes.iter().filter(|change| f(&change.kind)).count();
+                let text = locale::tr_format("status.comparing", &[
+                    &count(|kind| matches!(kind, ChangeKind::Added)),
+                    &count(|kind| matches!(kind, ChangeKind::Removed)),
+                    &count(|kind| matches!(kind, ChangeKind::Moved(_))),
+                    &count(|kind| matches!(kind, ChangeKind::Modified)),
+                ]);
+                let line_height = engine.theme.font_size + Engine::TAB_PADDING_V as i32;
+                let y = d.get_screen_height() - engine.theme.font_size - Engine::TAB_PADDING_V as i32 - line_height;
+                d.draw_text(&text, (Engine::RULER_SIZE + Engine::TAB_PADDING_H) as i32, y, engine.theme.font_size, engine.theme.color_foreground);
+            }
 
-                // draw artwork
-                for layer in &editor.document.layers {
-                    match &layer.content {
-                        // draw curve
-                        LayerContent::Curve(curve) => {
-                            let strong_curve = curve.upgrade().expect("should not hold onto dead layer");
-                            let curve_lock = strong_curve.lock();
-                            let curve_borrow = curve_lock.borrow();
+            // draw annotations
+            if editor.is_showing_annotations {
+                for annotation in &editor.document.annotations {
+                    annotation.draw(&mut d, &editor.camera, engine.theme.font_size);
+                }
+            }
+
+            // draw tool visuals
+            match editor.current_tool {
+                Tool::PointSelect => {
+
+                }
 
-                            let iter = curve_borrow
-                                .pos_vel_iter::<40>()
-                                .flat_map(|(i, t, p, v)| {
-                                    const ROTATE_90DEG: na::Matrix2<f32> = na::Matrix2::new(
-                                        0.0, -1.0,
-                                        1.0,  0.0,
-                                    );
-                                    let _t_full = i as f32 + t;
-                                    let tangent = v.try_normalize(f32::EPSILON)?;
-                                    let outer = ROTATE_90DEG * tangent;
-                                    let inner = -outer;
-                                    Some((p + inner, p + outer))
-                                });
-
-                            for (inner, outer) in iter {
-                                d.draw_line_v(Vector2::from(inner), Vector2::from(outer), Color::RED);
+                Tool::Select | Tool::MagicWand | Tool::Bucket => {
+                    if let Selection::Layers(paths) = editor.selection() {
+                        for bounds in paths.iter().filter_map(|path| editor.document.layer_at(path)?.control_bounds()) {
+                            let top_left = d.get_world_to_screen2D(Vector2::new(bounds.x, bounds.y), editor.camera);
+                            let size = Vector2::new(bounds.width, bounds.height) * editor.camera.zoom;
+                            d.draw_rectangle_lines_ex(Rectangle::new(top_left.x, top_left.y, size.x, size.y), engine.theme.selection_thickness, engine.theme.color_accent);
+                        }
+                    }
+                    // the placement of the selected layer's texture fill, with handles to move, scale, and rotate it
+                    if editor.current_tool == Tool::Select
+                        && let Some((corners, handles)) = editor.texture_handles()
+                    {
+                        let corners = corners.map(|corner| d.get_world_to_screen2D(corner, editor.camera));
+                        for (&start, &end) in corners.iter().zip(corners.iter().cycle().skip(1)) {
+                            d.draw_line_ex(start, end, engine.theme.selection_thickness, engine.theme.color_foreground);
+                        }
+                        let top = (corners[0] + corners[1]) * 0.5;
+                        let radius = TextureHandle::RADIUS;
+                        for (handle, position) in handles {
+                            let position = d.get_world_to_screen2D(position, editor.camera);
+                            if handle == TextureHandle::Rotate {
+                                d.draw_line_ex(top, position, engine.theme.selection_thickness, engine.theme.color_foreground);
                             }
+                            d.draw_circle_v(position, radius, engine.theme.color_accent);
+                            d.draw_circle_lines(position.x as i32, position.y as i32, radius, engine.theme.color_foreground);
                         }
+                    }
+                    // the selected rectangle's corner radius widgets, drawn hollow so they read apart from the texture handles
+                    if editor.current_tool == Tool::Select
+                        && let Some(widgets) = editor.corner_widgets()
+                    {
+                        for widget in widgets {
+                            let position = d.get_world_to_screen2D(widget, editor.camera);
+                            d.draw_circle_v(position, CornerWidget::RADIUS, engine.theme.color_background);
+                            d.draw_circle_lines(position.x as i32, position.y as i32, CornerWidget::RADIUS, engine.theme.color_accent);
+                        }
+                    }
+                }
+
+                Tool::Rectangle => {
+                    // the rectangle being drawn
+                    if let Some((start, end)) = editor.rectangle_drag {
+                        let (start, end) = (d.get_world_to_screen2D(start, editor.camera), d.get_world_to_screen2D(end, editor.camera));
+                        let rect = Rectangle::new(start.x.min(end.x), start.y.min(end.y), (end.x - start.x).abs(), (end.y - start.y).abs());
+                        d.draw_rectangle_lines_ex(rect, engine.theme.selection_thickness, engine.theme.color_accent);
+                    }
+                }
+
+                Tool::VectorPen => {
+
+                }
+
+                Tool::Lasso => {
+                    let anchor_size = engine.theme.selection_thickness * 3.0;
+                    let mut draw_anchor = |point: na::Vector2<f32>| {
+                        let center = d.get_world_to_screen2D(Vector2::from(point), editor.camera);
+                        let corner = center - Vector2::new(anchor_size, anchor_size) * 0.5;
+                        d.draw_rectangle_v(corner, Vector2::new(anchor_size, anchor_size), engine.theme.color_accent);
+                    };
+                    match editor.selection() {
+                        Selection::Points(curves) => for (curve, ranges) in curves {
+                            let Some(strong_curve) = curve.upgrade() else { continue };
+                            let curve_lock = strong_curve.lock();
+                            let curve_borrow = curve_lock.borrow();
+                            for range in ranges {
+                                for point in curve_borrow.points.get(range.start as usize..range.end as usize).unwrap_or_default() {
+                                    draw_anchor(point.p);
+                                }
+                            }
+                        },
+                        Selection::Paths(curves) => for curve in curves {
+                            let Some(strong_curve) = curve.upgrade() else { continue };
+                            let curve_lock = strong_curve.lock();
+                            for point in &curve_lock.borrow().points {
+                                draw_anchor(point.p);
+                            }
+                        },
+                        _ => (),
+                    }
+
+                    // the loop being drawn, closed back to where it started
+                    let lasso = editor.lasso.iter()
+                        .map(|&point| d.get_world_to_screen2D(point, editor.camera))
+                        .collect::<Vec<_>>();
+                    for (&start, &end) in lasso.iter().zip(lasso.iter().cycle().skip(1)) {
+                        d.draw_line_ex(start, end, engine.theme.selection_thickness, engine.theme.color_accent);
+                    }
+                }
 
-                        // draw group
-                        LayerContent::Group(_group) => {
-                            todo!("group rendering not yet implemented")
+                Tool::VectorBrush | Tool::RasterBrush => {
+                    // the vector brush stroke being drawn, before it becomes a path, or the shape it was recognized as
+                    let stroke = if let Some(shape) = &editor.recognized_shape {
+                        let mut outline = Vec::new();
+                        shape.to_curve().sample_into(&mut outline, 0.5 / editor.camera.zoom);
+                        outline.into_iter()
+                            .map(|point| d.get_world_to_screen2D(Vector2::new(point.x, point.y), editor.camera))
+                            .collect::<Vec<_>>()
+                    } else {
+                        editor.brush_stroke.iter()
+                            .map(|&point| d.get_world_to_screen2D(point, editor.camera))
+                            .collect::<Vec<_>>()
+                    };
+                    for pair in stroke.windows(2) {
+                        d.draw_line_ex(pair[0], pair[1], engine.theme.selection_thickness, engine.theme.color_accent);
+                    }
+                    if let Some(size) = engine.preferences().brushes.size(editor.current_tool) {
+                        // while resizing, the preview stays where the drag started
+                        let center = engine.brush_resize.map_or_else(|| d.get_mouse_position(), |(anchor, _)| anchor);
+                        let radius = size * 0.5 * editor.camera.zoom;
+                        d.draw_circle_lines(center.x as i32, center.y as i32, radius, engine.theme.color_foreground);
+                        if editor.is_erasing && editor.current_tool == Tool::RasterBrush {
+                            // the inner circle marks where the eraser stops being fully hard
+                            let hardness = engine.preferences().brushes.eraser_hardness;
+                            d.draw_circle_lines(center.x as i32, center.y as i32, radius * hardness, engine.theme.color_destructive);
+                        }
+                        if engine.brush_resize.is_some() {
+                            let text = format!("{size:.1}px");
+                            d.draw_text(&text, center.x as i32, center.y as i32, engine.theme.font_size, engine.theme.color_foreground);
                         }
                     }
                 }
             }
 
-            // draw tool visuals
-            match editor.current_tool {
-                Tool::PointSelect => {
+            // draw smart guides
+            for guide in &editor.smart_guides {
+                guide.draw(&mut d, &editor.camera);
+            }
+
+            // draw simplify heatmap
+            if let Some(preview) = &editor.simplify {
+                preview.draw(&mut d, &editor.camera);
+            }
 
+            // draw artboard name and size
+            let info = &editor.document.info;
+            for (index, artboard) in editor.document.artboards.iter().enumerate() {
+                let corner = d.get_world_to_screen2D(Vector2::new(artboard.rect.x, artboard.rect.y), editor.camera);
+                // artboards selected in the panel are outlined, since the panel only lists their names
+                if engine.artboard_panel.is_some() && editor.selected_artboards.contains(&index) {
+                    let size = Vector2::new(artboard.rect.width, artboard.rect.height) * editor.camera.zoom;
+                    d.draw_rectangle_lines_ex(Rectangle::new(corner.x, corner.y, size.x, size.y), engine.theme.selection_thickness, engine.theme.color_accent);
                 }
+                let text = format!(
+                    "{} ({} x {})",
+                    artboard.name,
+                    info.units.format(artboard.rect.width, info.dpi),
+                    info.units.format(artboard.rect.height, info.dpi),
+                );
+                d.draw_text(&text, corner.x as i32, corner.y as i32 - engine.theme.font_size, engine.theme.font_size, engine.theme.color_foreground);
+                let button = engine.quick_export_button(editor, artboard);
+                if button.check_collision_point_rec(d.get_mouse_position()) {
+                    quick_export_tooltip = Some((button, locale::tr("tooltip.quick_export")));
+                }
+                if d.gui_button(button, Some(c"#7#")) {
+                    quick_export = Some(artboard.name.clone());
+                }
+            }
 
-                Tool::VectorBrush => {
+            // draw rulers
+            {
+                let (width, height) = (d.get_screen_width() as f32, d.get_screen_height() as f32);
+                let top = engine.tab_well(width).height;
+                let size = Engine::RULER_SIZE;
+                d.draw_rectangle_rec(Rectangle::new(0.0, top, width, size), engine.theme.color_panel);
+                d.draw_rectangle_rec(Rectangle::new(0.0, top, size, height - top), engine.theme.color_panel);
+
+                // screenspace length of one unit
+                let unit_length = info.units.to_px(1.0, info.dpi) * editor.camera.zoom;
+                let step = units::nice_step(Engine::RULER_MIN_TICK_SPACING / unit_length);
+                let step_length = step * unit_length;
+                let origin = d.get_world_to_screen2D(editor.coordinate_origin(is_artboard_relative), editor.camera);
 
+                let mut n = ((size - origin.x) / step_length).ceil();
+                while origin.x + n * step_length < width {
+                    let x = origin.x + n * step_length;
+                    d.draw_line_v(Vector2::new(x, top), Vector2::new(x, top + size), engine.theme.color_foreground);
+                    d.draw_text(&units::format_value(n * step), x as i32 + 2, top as i32 + 2, engine.theme.font_size, engine.theme.color_foreground);
+                    n += 1.0;
                 }
 
-                Tool::VectorPen => {
+                let mut n = ((top + size - origin.y) / step_length).ceil();
+                while origin.y + n * step_length < height {
+                    let y = origin.y + n * step_length;
+                    d.draw_line_v(Vector2::new(0.0, y), Vector2::new(size, y), engine.theme.color_foreground);
+                    d.draw_text(&units::format_value(n * step), 2, y as i32 + 2, engine.theme.font_size, engine.theme.color_foreground);
+                    n += 1.0;
+                }
 
+                d.draw_rectangle_rec(Rectangle::new(0.0, top, size, size), engine.theme.color_panel_edge);
+                d.draw_text(info.units.abbreviation(), 2, top as i32 + 2, engine.theme.font_size, engine.theme.color_foreground);
+            }
+
+            // draw group scope breadcrumbs
+            if !editor.group_scope.is_empty() {
+                let mut text = editor.document.title.clone();
+                for i in 1..=editor.group_scope.len() {
+                    if let Some(group) = editor.document.layer_at(&editor.group_scope[..i]) {
+                        text.push_str(" > ");
+                        text.push_str(&group.name);
+                    }
                 }
+                // beside the toolbar
+                let toolbar_bounds = engine.toolbar_bounds(d.get_screen_width() as f32);
+                let x = (toolbar_bounds.x + toolbar_bounds.width + Engine::TAB_PADDING_H) as i32;
+                let y = (toolbar_bounds.y + Engine::TAB_PADDING_V) as i32;
+                d.draw_text(&text, x, y, engine.theme.font_size, engine.theme.color_foreground);
+            }
+
+            // draw animation frame counter
+            if let Some(playback) = &editor.playback {
+                let text = locale::tr_format(
+                    if playback.is_playing { "status.frame_playing" } else { "status.frame" },
+                    &[&(playback.frame + 1), &editor.document.artboards.len(), &editor.document.animation.fps],
+                );
+                let y = d.get_screen_height() - engine.theme.font_size - Engine::TAB_PADDING_V as i32;
+                d.draw_text(&text, (Engine::RULER_SIZE + Engine::TAB_PADDING_H) as i32, y, engine.theme.font_size, engine.theme.color_foreground);
+            }
+
+            // draw selection summary
+            let selection = editor.selection_info();
+            if let Some(bounds) = selection.bounds {
+                let info = &editor.document.info;
+                let origin = editor.coordinate_origin(is_artboard_relative);
+                let text = locale::tr_format("status.selection", &[
+                    &selection.layers.len(),
+                    &selection.anchor_count,
+                    &info.units.format(bounds.x - origin.x, info.dpi),
+                    &info.units.format(bounds.y - origin.y, info.dpi),
+                    &info.units.format(bounds.width, info.dpi),
+                    &info.units.format(bounds.height, info.dpi),
+                ]);
+                let text_width = d.measure_text(&text, engine.theme.font_size);
+                let x = d.get_screen_width().saturating_sub(text_width) / 2;
+                let y = d.get_screen_height() - engine.theme.font_size - Engine::TAB_PADDING_V as i32;
+                d.draw_text(&text, x, y, engine.theme.font_size, engine.theme.color_foreground);
+            }
+        }
+
+        // draw raster memory usage
+        if let Some(editor) = engine.focused_editor()
+            && editor.document.raster_memory_usage() > 0
+        {
+            let text = locale::tr_format("status.rasters", &[
+                &editor.document.rasters.len(),
+                &format_bytes(editor.document.raster_memory_usage()),
+            ]);
+            let text_width = d.measure_text(&text, engine.theme.font_size);
+            let x = d.get_screen_width() - text_width - Engine::TAB_PADDING_H as i32;
+            let y = d.get_screen_height() - engine.theme.font_size - Engine::TAB_PADDING_V as i32;
+            d.draw_text(&text, x, y, engine.theme.font_size, engine.theme.color_foreground);
+        }
+
+        // flag broken layers, above the raster memory usage
+        let broken = engine.focused_editor().map_or(0, |editor| editor.document.broken_layer_count());
+        if broken > 0 {
+            let text = locale::tr_format("status.broken_layers", &[&broken]);
+            let text_width = d.measure_text(&text, engine.theme.font_size);
+            let line_height = engine.theme.font_size + Engine::TAB_PADDING_V as i32;
+            let x = d.get_screen_width() - text_width - Engine::TAB_PADDING_H as i32;
+            let y = d.get_screen_height() - engine.theme.font_size - Engine::TAB_PADDING_V as i32 - line_height;
+            d.draw_text(&text, x, y, engine.theme.font_size, engine.theme.color_destructive);
+        }
+
+        let ui_span = profiler::span("ui");
+
+        // draw export progress
+        let font_size = engine.theme.font_size;
+        if let Some(mut progress) = engine.focused_editor().and_then(|editor| editor.export.as_ref()).map(ExportJob::progress) {
+            const WIDTH: f32 = 240.0;
+            const CANCEL_WIDTH: f32 = 60.0;
+            let height = font_size as f32 + Engine::TAB_PADDING_V * 2.0;
+            let x = d.get_screen_width() as f32 - WIDTH - Engine::TAB_PADDING_H;
+            // above the raster memory usage
+            let y = d.get_screen_height() as f32 - (height + Engine::TAB_PADDING_V) * 2.0;
+            let percent = CString::new(format!("{:.0}%", progress * 100.0)).expect("percentage should not contain nul");
+            d.gui_progress_bar(
+                Rectangle::new(x, y, WIDTH - CANCEL_WIDTH - Engine::TAB_PADDING_H, height),
+                None,
+                Some(percent.as_c_str()),
+                &mut progress,
+                0.0,
+                1.0,
+            );
+            let cancel_button = Rectangle::new(x + WIDTH - CANCEL_WIDTH, y, CANCEL_WIDTH, height);
+            if engine.ui.button(&mut d, WidgetId::new("export.cancel"), cancel_button, &locale::tr("dialog.cancel"))
+                && let Some(editor) = engine.focused_editor_mut()
+            {
+                // dropping the job cancels it
+                editor.export = None;
+            }
+        }
 
-                Tool::RasterBrush => {
+        // draw file progress, above the export progress
+        {
+            const WIDTH: f32 = 160.0;
+            let height = font_size as f32 + Engine::TAB_PADDING_V * 2.0;
+            let save_jobs = engine.editors().iter().filter_map(|editor| editor.save_job.as_ref().map(|save| &save.job));
+            for (i, job) in engine.file_jobs.iter().chain(save_jobs).enumerate() {
+                let x = d.get_screen_width() as f32 - WIDTH - Engine::TAB_PADDING_H;
+                let y = d.get_screen_height() as f32 - (height + Engine::TAB_PADDING_V) * (i as f32 + 3.0);
+                // the label is drawn to the left of the bar
+                let label = CString::new(job.label()).unwrap_or_default();
+                let mut progress = job.progress();
+                d.gui_progress_bar(Rectangle::new(x, y, WIDTH, height), Some(label.as_c_str()), None, &mut progress, 0.0, 1.0);
+            }
+        }
+
+        // draw navigator
+        let (window_width, window_height) = (d.get_screen_width() as f32, d.get_screen_height() as f32);
+        engine.draw_navigator(&mut d, window_width, window_height);
+
+        // draw layers panel
+        engine.draw_layer_panel(&mut d, window_width, window_height);
+
+        // draw toolbar
+        let mut toolbar_tooltip = None;
+        if let Some(editor) = engine.focused_editor() {
+            let bounds = engine.toolbar_bounds(d.get_screen_width() as f32);
+            let mouse_pos = d.get_mouse_position();
+            toolbar_tooltip = engine.toolbar.draw(&mut d, bounds, &engine.theme, editor, &keymap, mouse_pos);
+        }
 
+        // draw library panel
+        if engine.is_library_open && let Some(library) = &engine.library {
+            let panel = engine.library_panel(d.get_screen_width() as f32, d.get_screen_height() as f32);
+            let mouse_pos = d.get_mouse_position();
+            let thumbnail_size = Library::THUMBNAIL_SIZE as f32;
+            // render textures are stored upside-down
+            let thumbnail_source = Rectangle::new(0.0, 0.0, thumbnail_size, -thumbnail_size);
+            d.draw_rectangle_rec(panel, engine.theme.color_panel);
+            let rows = engine.ui.list(&mut d, WidgetId::new("library"), panel, Engine::LIBRARY_ROW_HEIGHT, library.items.len(), &mut engine.library_scroll);
+            let hovered = rows.row_at(mouse_pos);
+            {
+                // only the items in view are drawn, however big the library is
+                let viewport = rows.viewport;
+                let mut d = d.begin_scissor_mode(viewport.x as i32, viewport.y as i32, viewport.width as i32, viewport.height as i32);
+                for (i, row) in rows.iter() {
+                    let Some(item) = library.items.get(i) else { continue };
+                    let rect = Engine::library_item_rect(row);
+                    if engine.library_drag == Some(i) {
+                        d.draw_rectangle_rec(rect, engine.theme.color_accent);
+                    } else if hovered == Some(i) && rect.check_collision_point_rec(mouse_pos) {
+                        d.draw_rectangle_rec(rect, engine.theme.color_panel_edge);
+                    }
+                    d.draw_texture_rec(&item.thumbnail, thumbnail_source, Vector2::new(rect.x, rect.y), Color::WHITE);
+                    d.draw_text(
+                        &item.name,
+                        (rect.x + thumbnail_size + Engine::LIBRARY_PADDING) as i32,
+                        (rect.y + (thumbnail_size - engine.theme.font_size as f32) * 0.5) as i32,
+                        engine.theme.font_size,
+                        engine.theme.color_foreground,
+                    );
                 }
             }
 
-            // draw artboard name
-            for artboard in &editor.document.artboards {
-                let corner = d.get_world_to_screen2D(Vector2::new(artboard.rect.x, artboard.rect.y), editor.camera);
-                d.draw_text(&artboard.name, corner.x as i32, corner.y as i32 - engine.theme.font_size, engine.theme.font_size, engine.theme.color_foreground);
+            // draw item being dragged
+            if let Some(item) = engine.library_drag.and_then(|i| library.items.get(i)) {
+                let position = mouse_pos - Vector2::new(thumbnail_size * 0.5, thumbnail_size * 0.5);
+                d.draw_texture_rec(&item.thumbnail, thumbnail_source, position, Color::WHITE.alpha(0.5));
+            }
+        }
+
+        let window_width = d.get_screen_width() as f32;
+
+        // draw tool options
+        engine.draw_tool_options(&mut d, window_width);
+
+        // draw simplify panel, in place of the tool options
+        let simplify_bounds = engine.simplify_panel_bounds(window_width);
+        let theme = engine.theme;
+        let simplify_result = engine.focused_editor_mut()
+            .and_then(|editor| editor.simplify.as_mut())
+            .and_then(|preview| preview.draw_panel(&mut d, simplify_bounds, &theme));
+        if let Some(result) = simplify_result
+            && let Some(editor) = engine.focused_editor_mut()
+        {
+            editor.end_simplify(result == DialogResult::Confirm);
+        }
+
+        // draw style panel
+        engine.draw_style_panel(&mut d, window_width);
+        engine.draw_width_panel(&mut d, window_width);
+        engine.draw_artboard_panel(&mut d, window_width);
+        let mut swatch_tooltip = None;
+        let mut swatch_dialog = None;
+        if engine.style_panel.is_some()
+            && let Some(editor) = engine.focused_editor()
+        {
+            let mouse_pos = d.get_mouse_position();
+            let swatches = &editor.document.swatches;
+            let hovered = swatches.iter()
+                .zip(StylePanel::swatch_rects(engine.style_panel_bounds(window_width), swatches.len()))
+                .enumerate()
+                .find(|(_, (_, rect))| rect.check_collision_point_rec(mouse_pos));
+            // right clicking a swatch edits how it prints
+            if let Some((index, (swatch, _))) = hovered
+                && d.is_mouse_button_pressed(MOUSE_BUTTON_RIGHT)
+            {
+                swatch_dialog = Some(SwatchDialog::new(index, swatch));
+            }
+            swatch_tooltip = hovered.map(|(_, (swatch, rect))| (rect, swatch.description()));
+        }
+        if swatch_dialog.is_some() {
+            engine.swatch_dialog = swatch_dialog;
+        }
+
+        // draw swatch dialog
+        let dialog_result = engine.swatch_dialog.as_mut().and_then(|dialog| {
+            let bounds = SwatchDialog::bounds(d.get_screen_width() as f32, d.get_screen_height() as f32);
+            dialog.draw(&mut d, &mut engine.ui, bounds)
+        });
+        if let Some(result) = dialog_result
+            && let Some(dialog) = engine.swatch_dialog.take()
+            && result == DialogResult::Confirm
+            && let Some(swatch) = dialog.swatch()
+            && let Some(editor) = engine.focused_editor_mut()
+        {
+            editor.set_swatch(dialog.index(), swatch);
+        }
+
+        // draw document info dialog
+        let dialog_result = engine.document_info_dialog.as_mut().and_then(|dialog| {
+            let bounds = DocumentInfoDialog::bounds(d.get_screen_width() as f32, d.get_screen_height() as f32);
+            dialog.draw(&mut d, bounds)
+        });
+        if let Some(result) = dialog_result
+            && let Some(dialog) = engine.document_info_dialog.take()
+            && result == DialogResult::Confirm
+            && let Some(editor) = engine.focused_editor_mut()
+        {
+            dialog.apply(&mut editor.document.info);
+            editor.record_info();
+        }
+
+        // draw scatter dialog
+        let dialog_result = engine.scatter_dialog.as_mut().and_then(|dialog| {
+            let bounds = ScatterDialog::bounds(d.get_screen_width() as f32, d.get_screen_height() as f32);
+            dialog.draw(&mut d, &mut engine.ui, bounds)
+        });
+        if let Some(result) = dialog_result
+            && let Some(dialog) = engine.scatter_dialog.take()
+            && result == DialogResult::Confirm
+            && let Some(editor) = engine.focused_editor_mut()
+        {
+            editor.scatter_along_path(&dialog.options());
+        }
+
+        // draw grid repeat dialog
+        let dialog_result = engine.grid_repeat_dialog.as_mut().and_then(|dialog| {
+            let bounds = GridRepeatDialog::bounds(d.get_screen_width() as f32, d.get_screen_height() as f32);
+            dialog.draw(&mut d, &mut engine.ui, bounds)
+        });
+        if let Some(result) = dialog_result
+            && let Some(dialog) = engine.grid_repeat_dialog.take()
+            && result == DialogResult::Confirm
+            && let Some(editor) = engine.focused_editor_mut()
+        {
+            editor.grid_repeat(&dialog.options());
+        }
+
+        // draw scale dialog
+        let dialog_result = engine.scale_dialog.as_mut().and_then(|dialog| {
+            let bounds = ScaleDialog::bounds(d.get_screen_width() as f32, d.get_screen_height() as f32);
+            dialog.draw(&mut d, &mut engine.ui, bounds)
+        });
+        if let Some(result) = dialog_result
+            && let Some(dialog) = engine.scale_dialog.take()
+            && result == DialogResult::Confirm
+        {
+            if let Some(editor) = engine.focused_editor_mut() {
+                editor.scale_selection(dialog.factor(), dialog.is_scaling_strokes());
+            }
+            if engine.preferences().transform.is_scaling_strokes != dialog.is_scaling_strokes() {
+                engine.set_scaling_strokes(dialog.is_scaling_strokes());
+                if let Err(e) = engine.save_preferences() {
+                    eprintln!("failed to save preferences: {e}");
+                }
+            }
+        }
+
+        // draw scale document dialog
+        let dialog_result = engine.scale_document_dialog.as_mut().and_then(|dialog| {
+            let bounds = ScaleDocumentDialog::bounds(d.get_screen_width() as f32, d.get_screen_height() as f32);
+            dialog.draw(&mut d, &mut engine.ui, bounds)
+        });
+        if let Some(result) = dialog_result
+            && let Some(dialog) = engine.scale_document_dialog.take()
+            && result == DialogResult::Confirm
+            && let Some(editor) = engine.focused_editor_mut()
+        {
+            editor.scale_document(dialog.factor());
+        }
+
+        // draw frame export dialog
+        let dialog_result = engine.frame_export_dialog.as_mut().and_then(|dialog| {
+            let bounds = FrameExportDialog::bounds(d.get_screen_width() as f32, d.get_screen_height() as f32);
+            dialog.draw(&mut d, &mut engine.ui, bounds)
+        });
+        if let Some(result) = dialog_result
+            && let Some(dialog) = engine.frame_export_dialog.take()
+            && result == DialogResult::Confirm
+            && let Some(editor) = engine.focused_editor_mut()
+            && let Err(e) = editor.start_frame_export(dialog.fps(), dialog.format(), dialog.is_gif())
+        {
+            eprintln!("failed to export frames: {e}");
+        }
+
+        // draw arrange artboards dialog
+        let dialog_result = engine.arrange_dialog.as_mut().and_then(|dialog| {
+            let bounds = ArrangeArtboardsDialog::bounds(d.get_screen_width() as f32, d.get_screen_height() as f32);
+            dialog.draw(&mut d, &mut engine.ui, bounds)
+        });
+        if let Some(result) = dialog_result
+            && let Some(dialog) = engine.arrange_dialog.take()
+            && result == DialogResult::Confirm
+            && let Some(editor) = engine.focused_editor_mut()
+        {
+            editor.document.arrange_artboards(dialog.columns(), dialog.spacing());
+            editor.refresh_selection_info();
+            editor.record_artboards();
+        }
+
+        // draw palette export dialog
+        let dialog_result = engine.palette_export_dialog.as_mut().and_then(|dialog| {
+            let bounds = PaletteExportDialog::bounds(d.get_screen_width() as f32, d.get_screen_height() as f32);
+            dialog.draw(&mut d, bounds)
+        });
+        if let Some(result) = dialog_result
+            && let Some(dialog) = engine.palette_export_dialog.take()
+            && result == DialogResult::Confirm
+            && let Some(editor) = engine.focused_editor()
+            && let Err(e) = editor.export_palette(dialog.format())
+        {
+            eprintln!("failed to export palette: {e}");
+        }
+
+        // quick export uses the last export settings (or the defaults) for a single artboard, without replacing them
+        if let Some(name) = quick_export
+            && let Some(editor) = engine.focused_editor_mut()
+        {
+            let mut preset = editor.document.last_export.clone().unwrap_or_default();
+            preset.artboards = vec![name];
+            if let Err(e) = editor.start_export(&preset) {
+                eprintln!("failed to export: {e}");
+            }
+        }
+
+        // draw rename dialog
+        let dialog_result = engine.rename_dialog.as_mut().and_then(|dialog| {
+            let bounds = RenameDialog::bounds(d.get_screen_width() as f32, d.get_screen_height() as f32);
+            dialog.draw(&mut d, &mut engine.ui, bounds)
+        });
+        if let Some(result) = dialog_result
+            && let Some(dialog) = engine.rename_dialog.take()
+            && result == DialogResult::Confirm
+            && let Some(title) = dialog.title()
+            && let Some(editor) = engine.editor_mut(dialog.editor_index())
+        {
+            editor.document.title = title.clone();
+            editor.record(JournalEntry::SetTitle(title));
+        }
+
+        // draw note dialog
+        let dialog_result = engine.note_dialog.as_mut().and_then(|dialog| {
+            let bounds = NoteDialog::bounds(d.get_screen_width() as f32, d.get_screen_height() as f32);
+            dialog.draw(&mut d, &mut engine.ui, bounds)
+        });
+        if let Some(result) = dialog_result
+            && let Some(dialog) = engine.note_dialog.take()
+            && result == DialogResult::Confirm
+            && let Some(note) = dialog.note()
+            && let Some(editor) = engine.editor_mut(dialog.editor_index())
+        {
+            editor.add_annotation(note);
+        }
+
+        // draw asset export dialog
+        let dialog_result = engine.asset_dialog.as_mut().and_then(|dialog| {
+            let bounds = AssetExportDialog::bounds(d.get_screen_width() as f32, d.get_screen_height() as f32);
+            dialog.draw(&mut d, &mut engine.ui, bounds)
+        });
+        if let Some(result) = dialog_result
+            && let Some(dialog) = engine.asset_dialog.take()
+            && result == DialogResult::Confirm
+            && let Some(editor) = engine.editor_mut(dialog.editor_index())
+            && let Some(layer) = editor.document.layer_at_mut(dialog.path())
+        {
+            let export = dialog.asset();
+            layer.export = export.clone();
+            editor.record(JournalEntry::SetLayerExport { layer: dialog.path().to_vec(), export });
+        }
+
+        // draw rename artboards dialog
+        let dialog_result = engine.artboard_rename_dialog.as_mut().and_then(|dialog| {
+            let bounds = ArtboardRenameDialog::bounds(d.get_screen_width() as f32, d.get_screen_height() as f32);
+            dialog.draw(&mut d, &mut engine.ui, bounds)
+        });
+        if let Some(result) = dialog_result
+            && let Some(dialog) = engine.artboard_rename_dialog.take()
+            && result == DialogResult::Confirm
+            && let Some(editor) = engine.editor_mut(dialog.editor_index())
+        {
+            dialog.apply(&mut editor.document);
+            editor.record_artboards();
+        }
+
+        // draw export dialog
+        let dialog_result = engine.export_dialog.as_mut().and_then(|dialog| {
+            let bounds = ExportDialog::bounds(d.get_screen_width() as f32, d.get_screen_height() as f32);
+            dialog.draw(&mut d, bounds)
+        });
+        if let Some(result) = dialog_result
+            && let Some(dialog) = engine.export_dialog.take()
+            && let Some(editor) = engine.editor_mut(dialog.editor_index())
+        {
+            // saved and deleted presets are discarded along with the dialog when cancelled
+            if result == DialogResult::Confirm {
+                dialog.apply(&mut editor.document);
+                editor.mark_edited();
+                if let Err(e) = editor.start_export(&dialog.preset()) {
+                    eprintln!("failed to export: {e}");
+                }
+            }
+        }
+
+        // draw command palette
+        let dialog_result = engine.command_palette.as_mut().and_then(|palette| {
+            let bounds = CommandPalette::bounds(d.get_screen_width() as f32, d.get_screen_height() as f32);
+            palette.draw(&mut d, bounds, &keymap)
+        });
+        if let Some(result) = dialog_result
+            && let Some(palette) = engine.command_palette.take()
+            && result == DialogResult::Confirm
+        {
+            clicked_palette_action = palette.selected_action();
+        }
+
+        // draw preferences dialog
+        let dialog_result = engine.preferences_dialog.as_mut().and_then(|dialog| {
+            let bounds = PreferencesDialog::bounds(d.get_screen_width() as f32, d.get_screen_height() as f32);
+            dialog.draw(&mut d, &mut engine.ui, bounds)
+        });
+        if let Some(result) = dialog_result
+            && let Some(dialog) = engine.preferences_dialog.take()
+            && result == DialogResult::Confirm
+        {
+            engine.set_preferences(&mut d, dialog.into_preferences());
+            if let Err(e) = engine.save_preferences() {
+                eprintln!("failed to save preferences: {e}");
             }
         }
 
         // draw editor tabs
         d.draw_rectangle_rec(engine.tab_well(d.get_render_width() as f32), engine.theme.color_panel_edge);
+        // the tabs borrow the engine, so their tooltip is offered after drawing them
+        let mut tab_tooltip = None;
         for tab in engine.tab_iter() {
             let is_hovered = tab.rect.check_collision_point_rec(d.get_mouse_position());
             match tab.data {
                 EngineTabData::Editor { index, editor, close_button_rect } => {
-                    let is_close_button_hovered = is_hovered && close_button_rect.check_collision_point_rec(d.get_mouse_position());
+                    let is_close_button_hovered = is_hovered && close_button_rect.is_some_and(|rect| rect.check_collision_point_rec(d.get_mouse_position()));
                     let is_focused = engine.focused_editor_index_eq(index);
 
                     let tab_color = if is_focused {
@@ -268,17 +1829,41 @@ fn main() {
                     };
 
                     d.draw_rectangle_rec(tab.rect, tab_color);
-                    d.draw_rectangle_rec(close_button_rect, close_color);
-                    d.draw_text(
-                        &editor.document.title,
-                        (tab.rect.x + Engine::TAB_PADDING_H) as i32,
-                        (tab.rect.y + Engine::TAB_PADDING_V) as i32,
-                        engine.theme.font_size,
-                        engine.theme.color_foreground,
-                    );
+                    if let Some(close_button_rect) = close_button_rect {
+                        d.draw_rectangle_rec(close_button_rect, close_color);
+                    }
+                    // pinned tabs only have room for the first letter
+                    let title = if editor.is_pinned {
+                        editor.document.title.chars().next().map(String::from).unwrap_or_default()
+                    } else {
+                        editor.document.title.clone()
+                    };
+                    let title_right = close_button_rect.map_or(tab.rect.x + tab.rect.width, |rect| rect.x) - Engine::TAB_PADDING_H;
+                    let title_width = title_right - (tab.rect.x + Engine::TAB_PADDING_H);
+                    let is_truncated = editor.is_pinned || d.measure_text(&title, engine.theme.font_size) as f32 > title_width;
+                    {
+                        let mut d = d.begin_scissor_mode(tab.rect.x as i32, tab.rect.y as i32, (title_right - tab.rect.x) as i32, tab.rect.height as i32);
+                        d.draw_text(
+                            &title,
+                            (tab.rect.x + Engine::TAB_PADDING_H) as i32,
+                            (tab.rect.y + Engine::TAB_PADDING_V) as i32,
+                            engine.theme.font_size,
+                            engine.theme.color_foreground,
+                        );
+                    }
+
+                    // the file path isn't shown anywhere else
+                    if is_hovered && !is_close_button_hovered && (is_truncated || editor.document.file_path.is_some()) {
+                        let mut text = editor.document.title.clone();
+                        if let Some(path) = &editor.document.file_path {
+                            text.push('\n');
+                            text.push_str(&path.display().to_string());
+                        }
+                        tab_tooltip = Some((tab.rect, text));
+                    }
                 }
 
-                EngineTabData::New | EngineTabData::Open => {
+                EngineTabData::New { .. } | EngineTabData::Open => {
                     let tab_color = if is_hovered {
                         engine.theme.color_accent
                     } else {
@@ -289,7 +1874,7 @@ fn main() {
                     d.draw_text(
                         match tab.data {
                             EngineTabData::Editor { .. } => unreachable!(),
-                            EngineTabData::New => "+",
+                            EngineTabData::New { .. } => "+",
                             EngineTabData::Open => "o",
                         },
                         (tab.rect.x + Engine::TAB_PADDING_H) as i32,
@@ -297,8 +1882,129 @@ fn main() {
                         engine.theme.font_size,
                         engine.theme.color_foreground,
                     );
+
+                    if is_hovered {
+                        let key = match tab.data {
+                            EngineTabData::New { menu_button_rect } if menu_button_rect.check_collision_point_rec(d.get_mouse_position()) => "tooltip.templates",
+                            EngineTabData::New { .. } => "tooltip.new_document",
+                            _ => "tooltip.open_document",
+                        };
+                        tab_tooltip = Some((tab.rect, locale::tr(key)));
+                    }
+
+                    // draw split button arrow
+                    if let EngineTabData::New { menu_button_rect } = tab.data {
+                        let center = Vector2::new(menu_button_rect.x + menu_button_rect.width * 0.5, menu_button_rect.y + menu_button_rect.height * 0.5);
+                        let half = menu_button_rect.width * 0.25;
+                        d.draw_line_v(Vector2::new(menu_button_rect.x, menu_button_rect.y), Vector2::new(menu_button_rect.x, menu_button_rect.y + menu_button_rect.height), engine.theme.color_panel_edge);
+                        d.draw_triangle(
+                            Vector2::new(center.x - half, center.y - half * 0.5),
+                            Vector2::new(center.x, center.y + half * 0.5),
+                            Vector2::new(center.x + half, center.y - half * 0.5),
+                            engine.theme.color_foreground,
+                        );
+                    }
                 }
             }
         }
+
+        // draw template menu
+        if engine.is_template_menu_open
+            && let Some(new_tab_rect) = engine.tab_iter().find_map(|tab| matches!(tab.data, EngineTabData::New { .. }).then_some(tab.rect))
+        {
+            let mouse_pos = d.get_mouse_position();
+            for (i, (name, _)) in engine.templates().enumerate() {
+                let rect = Engine::template_menu_item_rect(new_tab_rect, i);
+                let item_color = if rect.check_collision_point_rec(mouse_pos) {
+                    engine.theme.color_accent
+                } else {
+                    engine.theme.color_panel
+                };
+                d.draw_rectangle_rec(rect, item_color);
+                d.draw_text(
+                    &locale::tr(name),
+                    (rect.x + Engine::TAB_PADDING_H) as i32,
+                    (rect.y + Engine::TAB_PADDING_V) as i32,
+                    engine.theme.font_size,
+                    engine.theme.color_foreground,
+                );
+            }
+        }
+
+        // draw open dropdowns above the panels they belong to
+        engine.ui.end_frame(&mut d);
+        drop(ui_span);
+
+        // draw profiler overlay in the corner of the viewport
+        let viewport = engine.viewport(d.get_screen_width() as f32, d.get_screen_height() as f32);
+        profiler::draw_overlay(&mut d, &engine.theme, Vector2::new(viewport.x + Engine::TAB_PADDING_H, viewport.y + Engine::TAB_PADDING_V));
+
+        // draw tooltip above everything else
+        if let Some((rect, text)) = toolbar_tooltip {
+            engine.tooltip.offer(rect, text);
+        }
+        if let Some((rect, text)) = tab_tooltip {
+            engine.tooltip.offer(rect, text);
+        }
+        if let Some((rect, text)) = quick_export_tooltip {
+            engine.tooltip.offer(rect, text);
+        }
+        if let Some((rect, text)) = swatch_tooltip {
+            engine.tooltip.offer(rect, text);
+        }
+        let mouse_pos = d.get_mouse_position();
+        let time = d.get_time();
+        engine.tooltip.draw(&mut d, &engine.theme, mouse_pos, time);
+
+        // sleep until the next input once nothing is changing on its own
+        let is_animating = engine.has_file_jobs() || engine.editors().iter()
+            .any(|editor| editor.export.is_some() || editor.playback.as_ref().is_some_and(|playback| playback.is_playing));
+        if is_animating {
+            engine.frames.keep_awake();
+        }
+        let dt = d.get_frame_time();
+        engine.frames.end_frame(&d, dt);
+        profiler::end_frame();
+    }
+
+    engine.finish_saves();
+    // the window opens where it was left next time
+    if let Err(e) = engine.save_preferences() {
+        eprintln!("failed to save preferences: {e}");
+    }
+}
+
+/// Draw a checkerboard of `square_size` squares alternating between `colors` over the screenspace `rect`
+///
+/// Squares are aligned to the corner of `rect` and only drawn where they are visible within the screenspace `screen`
+fn draw_checkerboard(d: &mut impl RaylibDraw, screen: Rectangle, rect: Rectangle, square_size: f32, colors: [Color; 2]) {
+    let Some(visible) = rect.get_collision_rec(&screen) else { return };
+    if square_size <= 0.0 {
+        return;
+    }
+    d.draw_rectangle_rec(visible, colors[0]);
+    let first_col = ((visible.x - rect.x) / square_size).floor() as i32;
+    let first_row = ((visible.y - rect.y) / square_size).floor() as i32;
+    let last_col = ((visible.x + visible.width - rect.x) / square_size).ceil() as i32;
+    let last_row = ((visible.y + visible.height - rect.y) / square_size).ceil() as i32;
+    for row in first_row..last_row {
+        for col in (first_col..last_col).filter(|col| (col + row).rem_euclid(2) == 1) {
+            let square = Rectangle::new(rect.x + col as f32 * square_size, rect.y + row as f32 * square_size, square_size, square_size);
+            if let Some(square) = square.get_collision_rec(&visible) {
+                d.draw_rectangle_rec(square, colors[1]);
+            }
+        }
+    }
+}
+
+/// Format a number of bytes with a binary unit, like `1.5 MiB`
+fn format_bytes(bytes: usize) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut value = bytes as f32;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
     }
+    format!("{value:.1} {}", UNITS[unit])
 }
@@ -0,0 +1,255 @@
+use std::ffi::CString;
+use raylib::prelude::*;
+use crate::{editor::Editor, engine::EngineTheme, layer::SampleQuality, snapshot::DocumentSnapshot};
+
+/// A zoomed-out overview of the focused document, for orienting in large files
+///
+/// Shows a cached thumbnail of the whole [document extent][`crate::document::Document::extent`] with a
+/// rectangle marking the part visible in the viewport. Dragging in the thumbnail moves the camera, and
+/// the slider below it zooms about the center of the viewport
+#[derive(Debug)]
+pub struct Navigator {
+    /// The cached thumbnail and the worldspace rectangle it shows
+    ///
+    /// [`None`] before the first refresh, or if the document is empty
+    thumbnail: Option<(RenderTexture2D, Rectangle)>,
+
+    /// The index of the editor the thumbnail was rendered from
+    editor_index: Option<u32>,
+
+    /// Seconds since the thumbnail was last rendered
+    age: f32,
+
+    /// Screenspace offset from the center of the view rectangle to the mouse while it is being dragged
+    ///
+    /// [`None`] if the view rectangle is not being dragged
+    drag_offset: Option<Vector2>,
+}
+
+impl Default for Navigator {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Navigator {
+    /// Width of the panel
+    pub const WIDTH: f32 = 200.0;
+
+    /// Height of the thumbnail within the panel
+    pub const THUMBNAIL_HEIGHT: f32 = 150.0;
+
+    /// Height of the zoom slider below the thumbnail
+    pub const SLIDER_HEIGHT: f32 = 16.0;
+
+    /// Width of the zoom percentage to the right of the slider
+    const PERCENT_WIDTH: f32 = 40.0;
+
+    /// Padding between the edge of the panel and its contents, and between the thumbnail and slider
+    pub const PADDING: f32 = 4.0;
+
+    /// Height of the panel
+    pub const HEIGHT: f32 = Self::THUMBNAIL_HEIGHT + Self::SLIDER_HEIGHT + Self::PADDING * 3.0;
+
+    /// Seconds between re-rendering the thumbnail, so that it follows edits without rendering the whole document every frame
+    pub const REFRESH_INTERVAL: f32 = 0.5;
+
+    /// Fraction of the document extent left empty around the edges of the thumbnail
+    const MARGIN: f32 = 0.05;
+
+    /// Construct a navigator without a thumbnail
+    pub const fn new() -> Self {
+        Self {
+            thumbnail: None,
+            editor_index: None,
+            age: 0.0,
+            drag_offset: None,
+        }
+    }
+
+    /// The rectangle of the panel with its bottom-left corner at (`left`, `bottom`)
+    pub fn bounds(left: f32, bottom: f32) -> Rectangle {
+        Rectangle::new(left, bottom - Self::HEIGHT, Self::WIDTH, Self::HEIGHT)
+    }
+
+    /// The rectangle the thumbnail is drawn in
+    pub fn thumbnail_rect(bounds: Rectangle) -> Rectangle {
+        Rectangle::new(bounds.x + Self::PADDING, bounds.y + Self::PADDING, bounds.width - Self::PADDING * 2.0, Self::THUMBNAIL_HEIGHT)
+    }
+
+    /// The rectangle of the zoom slider
+    fn slider_rect(bounds: Rectangle) -> Rectangle {
+        let thumbnail = Self::thumbnail_rect(bounds);
+        Rectangle::new(thumbnail.x, thumbnail.y + thumbnail.height + Self::PADDING, thumbnail.width - Self::PERCENT_WIDTH, Self::SLIDER_HEIGHT)
+    }
+
+    /// Scale from worldspace to thumbnail pixels when `extent` is fit into `area`
+    fn scale(extent: Rectangle, area: Rectangle) -> f32 {
+        (area.width / extent.width.max(1.0)).min(area.height / extent.height.max(1.0))
+    }
+
+    /// Convert the worldspace `point` to a screenspace point on the thumbnail drawn in `area`
+    fn world_to_thumbnail(extent: Rectangle, area: Rectangle, point: Vector2) -> Vector2 {
+        let extent_center = Vector2::new(extent.x + extent.width * 0.5, extent.y + extent.height * 0.5);
+        let area_center = Vector2::new(area.x + area.width * 0.5, area.y + area.height * 0.5);
+        area_center + (point - extent_center) * Self::scale(extent, area)
+    }
+
+    /// Convert the screenspace `point` on the thumbnail drawn in `area` to a worldspace point
+    fn thumbnail_to_world(extent: Rectangle, area: Rectangle, point: Vector2) -> Vector2 {
+        let extent_center = Vector2::new(extent.x + extent.width * 0.5, extent.y + extent.height * 0.5);
+        let area_center = Vector2::new(area.x + area.width * 0.5, area.y + area.height * 0.5);
+        extent_center + (point - area_center) / Self::scale(extent, area)
+    }
+
+    /// The screenspace rectangle on the thumbnail drawn in `area` showing the part of the document `camera` shows in `viewport`
+    fn view_rect(extent: Rectangle, area: Rectangle, camera: &Camera2D, viewport: Rectangle) -> Rectangle {
+        let to_world = |point: Vector2| camera.target + (point - camera.offset) / camera.zoom;
+        let top_left = Self::world_to_thumbnail(extent, area, to_world(Vector2::new(viewport.x, viewport.y)));
+        let bottom_right = Self::world_to_thumbnail(extent, area, to_world(Vector2::new(viewport.x + viewport.width, viewport.y + viewport.height)));
+        Rectangle::new(top_left.x, top_left.y, bottom_right.x - top_left.x, bottom_right.y - top_left.y)
+    }
+
+    /// Re-render the thumbnail if it was rendered from a different editor or is older than [`Navigator::REFRESH_INTERVAL`]
+    ///
+    /// `dt` is the seconds since the previous call
+    pub fn refresh(&mut self, mut rl: &mut RaylibHandle, thread: &RaylibThread, editor: &Editor, editor_index: u32, dt: f32) -> Result<(), String> {
+        self.age += dt;
+        if self.editor_index == Some(editor_index) && self.age < Self::REFRESH_INTERVAL {
+            return Ok(());
+        }
+        self.editor_index = Some(editor_index);
+        self.age = 0.0;
+
+        let document = &editor.document;
+        let Some(extent) = document.extent() else {
+            self.thumbnail = None;
+            return Ok(());
+        };
+        let margin = extent.width.max(extent.height) * Self::MARGIN;
+        let extent = Rectangle::new(extent.x - margin, extent.y - margin, extent.width + margin * 2.0, extent.height + margin * 2.0);
+
+        let area = Self::thumbnail_rect(Self::bounds(0.0, Self::HEIGHT));
+        let mut rtex = match self.thumbnail.take() {
+            Some((rtex, _)) => rtex,
+            None => rl.load_render_texture(thread, area.width as u32, area.height as u32)?,
+        };
+        {
+            let mut d = rl.begin_texture_mode(thread, &mut rtex);
+            d.clear_background(Color::BLANK);
+            let mut d = d.begin_mode2D(Camera2D {
+                offset: Vector2::new(area.width * 0.5, area.height * 0.5),
+                target: Vector2::new(extent.x + extent.width * 0.5, extent.y + extent.height * 0.5),
+                rotation: 0.0,
+                zoom: Self::scale(extent, area),
+            });
+            for artboard in &document.artboards {
+                d.draw_rectangle_rec(artboard.rect, document.paper_color);
+            }
+            let snapshot = DocumentSnapshot::of(&document.layers);
+            for layer in &document.layers {
+                layer.draw(&mut d, Color::WHITE, SampleQuality::Low, &snapshot);
+            }
+        }
+        self.thumbnail = Some((rtex, extent));
+        Ok(())
+    }
+
+    /// Move `editor`'s camera while the view rectangle is dragged
+    ///
+    /// Pressing anywhere on the thumbnail starts a drag; pressing outside the view rectangle first
+    /// centers the view on the pressed point. `viewport` is the screenspace rectangle the document is shown in
+    pub fn update(&mut self, rl: &RaylibHandle, bounds: Rectangle, editor: &mut Editor, viewport: Rectangle) {
+        let Some(extent) = self.thumbnail.as_ref().map(|&(_, extent)| extent) else {
+            self.drag_offset = None;
+            return;
+        };
+        let area = Self::thumbnail_rect(bounds);
+        let mouse_pos = rl.get_mouse_position();
+        if rl.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_LEFT) && area.check_collision_point_rec(mouse_pos) {
+            let view = Self::view_rect(extent, area, &editor.camera, viewport);
+            let view_center = Vector2::new(view.x + view.width * 0.5, view.y + view.height * 0.5);
+            self.drag_offset = Some(if view.check_collision_point_rec(mouse_pos) { mouse_pos - view_center } else { Vector2::zero() });
+        } else if !rl.is_mouse_button_down(MouseButton::MOUSE_BUTTON_LEFT) {
+            self.drag_offset = None;
+        }
+
+        if let Some(drag_offset) = self.drag_offset {
+            let world_center = Self::thumbnail_to_world(extent, area, mouse_pos - drag_offset);
+            let viewport_center = Vector2::new(viewport.x + viewport.width * 0.5, viewport.y + viewport.height * 0.5);
+            editor.camera.target = world_center - (viewport_center - editor.camera.offset) / editor.camera.zoom;
+        }
+    }
+
+    /// Whether the view rectangle is being dragged
+    #[inline]
+    pub const fn is_dragging(&self) -> bool {
+        self.drag_offset.is_some()
+    }
+
+    /// Draw the panel, and zoom `editor` about the center of `viewport` if the zoom slider is moved
+    pub fn draw(&self, d: &mut impl RaylibDraw, bounds: Rectangle, theme: &EngineTheme, editor: &mut Editor, viewport: Rectangle) {
+        d.draw_rectangle_rec(bounds, theme.color_panel);
+        d.draw_rectangle_lines_ex(bounds, 1.0, theme.color_panel_edge);
+        let area = Self::thumbnail_rect(bounds);
+        d.draw_rectangle_rec(area, theme.color_background);
+        if let Some((rtex, extent)) = &self.thumbnail {
+            // render textures are stored upside-down
+            let source = Rectangle::new(0.0, 0.0, rtex.texture.width as f32, -(rtex.texture.height as f32));
+            d.draw_texture_rec(rtex, source, Vector2::new(area.x, area.y), Color::WHITE);
+            let view = Self::view_rect(*extent, area, &editor.camera, viewport);
+            let mut d = d.begin_scissor_mode(area.x as i32, area.y as i32, area.width as i32, area.height as i32);
+            d.draw_rectangle_lines_ex(view, 2.0, theme.color_accent);
+        }
+
+        // the slider is logarithmic so that each step zooms by the same factor
+        let mut zoom_log = editor.camera.zoom.log2();
+        let (min_zoom, max_zoom) = editor.zoom_limits;
+        let percent = CString::new(format!("{:.0}%", editor.camera.zoom * 100.0)).expect("percentage should not contain nul");
+        d.gui_slider(
+            Self::slider_rect(bounds),
+            None,
+            Some(percent.as_c_str()),
+            &mut zoom_log,
+            min_zoom.log2(),
+            max_zoom.log2(),
+        );
+        let zoom = zoom_log.exp2();
+        if (zoom - editor.camera.zoom).abs() > f32::EPSILON * editor.camera.zoom {
+            let viewport_center = Vector2::new(viewport.x + viewport.width * 0.5, viewport.y + viewport.height * 0.5);
+            editor.zoom_about(viewport_center, zoom / editor.camera.zoom);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_thumbnail_round_trip() {
+        let area = Rectangle::new(10.0, 20.0, 200.0, 100.0);
+        // wider and taller than the area, offset from the origin, and empty
+        for extent in [Rectangle::new(-500.0, 40.0, 1000.0, 100.0), Rectangle::new(30.0, -70.0, 50.0, 400.0), Rectangle::new(5.0, 5.0, 0.0, 0.0)] {
+            for point in [Vector2::new(extent.x, extent.y), Vector2::new(extent.x + extent.width, extent.y + extent.height), Vector2::new(-123.0, 456.0)] {
+                let round_trip = Navigator::thumbnail_to_world(extent, area, Navigator::world_to_thumbnail(extent, area, point));
+                assert!(round_trip.distance_to(point) < 1e-3, "{round_trip:?} should be {point:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_world_to_thumbnail() {
+        let area = Rectangle::new(10.0, 20.0, 200.0, 100.0);
+        // the extent is fit inside the area and centered, leaving space at the sides
+        let extent = Rectangle::new(0.0, 0.0, 400.0, 400.0);
+        assert_eq!(Navigator::world_to_thumbnail(extent, area, Vector2::new(0.0, 0.0)), Vector2::new(60.0, 20.0));
+        assert_eq!(Navigator::world_to_thumbnail(extent, area, Vector2::new(400.0, 400.0)), Vector2::new(160.0, 120.0));
+
+        // the view covers what the camera shows of the viewport
+        let camera = Camera2D { offset: Vector2::new(50.0, 50.0), target: Vector2::new(100.0, 100.0), rotation: 0.0, zoom: 0.5 };
+        let viewport = Rectangle::new(0.0, 0.0, 100.0, 100.0);
+        assert_eq!(Navigator::view_rect(extent, area, &camera, viewport), Rectangle::new(60.0, 20.0, 50.0, 50.0));
+    }
+}
@@ -0,0 +1,97 @@
+use crate::zip::ZipWriter;
+
+/// The file extension of OpenRaster files
+pub const EXTENSION: &str = "ora";
+
+/// The MIME type stored uncompressed at the start of every OpenRaster file, so it can be identified by its first bytes
+const MIMETYPE: &[u8] = b"image/openraster";
+
+/// Largest width or height of the thumbnail stored in OpenRaster files
+pub const THUMBNAIL_SIZE: i32 = 256;
+
+/// A layer of an OpenRaster file, rendered over the whole canvas
+#[derive(Debug, Clone, PartialEq)]
+pub struct OraLayer {
+    /// The display name of the layer
+    pub name: String,
+
+    /// The layer's pixels, encoded as PNG
+    pub png: Vec<u8>,
+}
+
+/// Build an OpenRaster file of `layers` (listed bottom to top) on a `width` by `height` pixel canvas at `dpi`
+///
+/// `merged` and `thumbnail` are PNGs of every layer flattened together, for applications that only read one image.
+/// Every layer is fully opaque and composited normally, since layers have no opacity or blend mode of their own
+pub fn encode(width: u32, height: u32, dpi: f32, layers: &[OraLayer], merged: &[u8], thumbnail: &[u8]) -> Vec<u8> {
+    let mut zip = ZipWriter::default();
+    zip.add("mimetype", MIMETYPE);
+    zip.add("stack.xml", stack_xml(width, height, dpi, layers).as_bytes());
+    for (i, layer) in layers.iter().enumerate() {
+        zip.add(&layer_path(i), &layer.png);
+    }
+    zip.add("mergedimage.png", merged);
+    zip.add("Thumbnails/thumbnail.png", thumbnail);
+    zip.finish()
+}
+
+/// The path within the file of the PNG of the `index`th layer from the bottom
+fn layer_path(index: usize) -> String {
+    format!("data/layer{index}.png")
+}
+
+/// The stack of layers, listed top to bottom as OpenRaster requires
+fn stack_xml(width: u32, height: u32, dpi: f32, layers: &[OraLayer]) -> String {
+    let mut xml = format!("<?xml version='1.0' encoding='UTF-8'?>\n<image version=\"0.0.5\" w=\"{width}\" h=\"{height}\" xres=\"{dpi}\" yres=\"{dpi}\">\n<stack>\n");
+    for (i, layer) in layers.iter().enumerate().rev() {
+        xml.push_str(&format!(
+            "<layer name=\"{}\" src=\"{}\" x=\"0\" y=\"0\" opacity=\"1.0\" visibility=\"visible\" composite-op=\"svg:src-over\"/>\n",
+            escape_attribute(&layer.name),
+            layer_path(i),
+        ));
+    }
+    xml.push_str("</stack>\n</image>\n");
+    xml
+}
+
+/// Escape `text` for use inside a double-quoted XML attribute, or as the text of an element
+pub fn escape_attribute(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode() {
+        let layers = [
+            OraLayer { name: "Background".to_owned(), png: vec![1, 2, 3] },
+            OraLayer { name: "Ink & \"color\"".to_owned(), png: vec![4, 5] },
+        ];
+        let ora = encode(64, 32, 300.0, &layers, &[6], &[7]);
+        // the mimetype comes first and uncompressed, right after its 30 byte local header
+        assert_eq!(&ora[..4], b"PK\x03\x04");
+        assert_eq!(&ora[30..38], b"mimetype");
+        assert_eq!(&ora[38..54], MIMETYPE);
+        // end of central directory, listing all 6 files
+        let end = &ora[ora.len() - 22..];
+        assert_eq!(&end[..4], b"PK\x05\x06");
+        assert_eq!(u16::from_le_bytes([end[10], end[11]]), 6);
+
+        let xml = stack_xml(64, 32, 300.0, &layers);
+        let top = xml.find("data/layer1.png").unwrap();
+        assert!(top < xml.find("data/layer0.png").unwrap(), "layers should be listed top to bottom");
+        assert!(xml.contains("name=\"Ink &amp; &quot;color&quot;\""));
+    }
+}
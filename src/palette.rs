@@ -0,0 +1,318 @@
+use std::path::Path;
+use raylib::prelude::*;
+use crate::swatch::{Cmyk, Swatch, SwatchColor};
+
+/// A palette file format swatches can be imported from and exported to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PaletteFormat {
+    /// GIMP palette: a text header followed by one `R G B name` line per color
+    #[default]
+    Gpl,
+
+    /// Adobe Swatch Exchange: a binary list of named RGB, CMYK, Lab, or gray colors, optionally spot colors
+    Ase,
+
+    /// One `RRGGBB` hex code per line, as shared by palette sites
+    Hex,
+}
+
+impl PaletteFormat {
+    /// Every format, in the order they are listed in the UI
+    pub const ALL: [Self; 3] = [Self::Gpl, Self::Ase, Self::Hex];
+
+    /// The file extension of the format
+    pub const fn extension(self) -> &'static str {
+        match self {
+            Self::Gpl => "gpl",
+            Self::Ase => "ase",
+            Self::Hex => "hex",
+        }
+    }
+
+    /// The format with the file extension `extension`, ignoring case
+    ///
+    /// [`None`] if the extension isn't a palette format
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|format| extension.eq_ignore_ascii_case(format.extension()))
+    }
+}
+
+/// Read the swatches of the palette file at `path`, in the format named by its extension
+pub fn read(path: &Path) -> Result<Vec<Swatch>, String> {
+    let extension = path.extension().and_then(|ext| ext.to_str()).unwrap_or_default();
+    let format = PaletteFormat::from_extension(extension).ok_or_else(|| format!("not a palette file: {}", path.display()))?;
+    match format {
+        PaletteFormat::Gpl => decode_gpl(&std::fs::read_to_string(path).map_err(|e| e.to_string())?),
+        PaletteFormat::Ase => decode_ase(&std::fs::read(path).map_err(|e| e.to_string())?),
+        PaletteFormat::Hex => decode_hex(&std::fs::read_to_string(path).map_err(|e| e.to_string())?),
+    }
+}
+
+/// Write `swatches` to `path` as a palette named `name` in `format`
+///
+/// Formats without print colors get the screen preview of process and spot colors
+pub fn write(path: &Path, name: &str, swatches: &[Swatch], format: PaletteFormat) -> Result<(), String> {
+    let bytes = match format {
+        PaletteFormat::Gpl => encode_gpl(name, swatches).into_bytes(),
+        PaletteFormat::Ase => encode_ase(swatches),
+        PaletteFormat::Hex => encode_hex(swatches).into_bytes(),
+    };
+    std::fs::write(path, bytes).map_err(|e| e.to_string())
+}
+
+/// Parse a GIMP palette
+///
+/// Colors without a name are named after their hex code
+pub fn decode_gpl(text: &str) -> Result<Vec<Swatch>, String> {
+    let mut lines = text.lines().enumerate();
+    if !lines.next().is_some_and(|(_, header)| header.trim() == "GIMP Palette") {
+        return Err("missing \"GIMP Palette\" header".to_owned());
+    }
+    let mut swatches = Vec::new();
+    for (i, line) in lines {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with("Name:") || line.starts_with("Columns:") {
+            continue;
+        }
+        let mut parts = line.split_whitespace();
+        let mut channel = || -> Result<u8, String> {
+            let part = parts.next().ok_or_else(|| format!("line {}: expected red, green, and blue", i.saturating_add(1)))?;
+            part.parse().map_err(|_| format!("line {}: {part:?} is not a channel from 0 to 255", i.saturating_add(1)))
+        };
+        let color = Color::new(channel()?, channel()?, channel()?, 255);
+        let name = parts.collect::<Vec<_>>().join(" ");
+        swatches.push(match name.as_str() {
+            // GIMP names unnamed colors "Untitled"
+            "" | "Untitled" => Swatch::from_rgb(color),
+            _ => Swatch { name, color: SwatchColor::Rgb(color) },
+        });
+    }
+    Ok(swatches)
+}
+
+/// Write a GIMP palette named `name`
+pub fn encode_gpl(name: &str, swatches: &[Swatch]) -> String {
+    let mut text = format!("GIMP Palette\nName: {name}\n#\n");
+    for swatch in swatches {
+        let Color { r, g, b, .. } = swatch.color.preview();
+        text.push_str(&format!("{r:>3} {g:>3} {b:>3}\t{}\n", swatch.name));
+    }
+    text
+}
+
+/// Parse a list of hex codes, one per line, each `RRGGBB` or `RRGGBBAA` with an optional leading `#`
+///
+/// Blank lines and lines starting with `;` are skipped
+pub fn decode_hex(text: &str) -> Result<Vec<Swatch>, String> {
+    text.lines()
+        .enumerate()
+        .map(|(i, line)| (i, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with(';'))
+        .map(|(i, line)| {
+            let code = line.strip_prefix('#').unwrap_or(line);
+            let value = u32::from_str_radix(code, 16).ok().filter(|_| code.len() == 6 || code.len() == 8);
+            let [r, g, b, a] = match (value.map(u32::to_be_bytes), code.len()) {
+                (Some([_, r, g, b]), 6) => [r, g, b, 255],
+                (Some(rgba), _) => rgba,
+                (None, _) => return Err(format!("line {}: {line:?} is not a hex color", i.saturating_add(1))),
+            };
+            Ok(Swatch::from_rgb(Color::new(r, g, b, a)))
+        })
+        .collect()
+}
+
+/// Write a list of hex codes, one `rrggbb` per line
+pub fn encode_hex(swatches: &[Swatch]) -> String {
+    swatches.iter()
+        .map(|swatch| {
+            let Color { r, g, b, .. } = swatch.color.preview();
+            format!("{r:02x}{g:02x}{b:02x}\n")
+        })
+        .collect()
+}
+
+/// ASE block containing one color
+const ASE_COLOR: u16 = 0x0001;
+
+/// ASE color type of spot colors, as opposed to global (0) and normal (2) colors
+const ASE_SPOT: u16 = 1;
+
+/// ASE color type written for colors that aren't spot colors
+const ASE_NORMAL: u16 = 2;
+
+/// Reads the big-endian values of an ASE file from front to back
+struct AseReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> AseReader<'a> {
+    /// The next `count` bytes
+    fn take(&mut self, count: usize) -> Result<&'a [u8], String> {
+        let (taken, rest) = self.bytes.split_at_checked(count).ok_or("swatch exchange file ends early")?;
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    /// The next `N` bytes
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], String> {
+        Ok(self.take(N)?.try_into().expect("should take exactly N bytes"))
+    }
+
+    fn u16(&mut self) -> Result<u16, String> {
+        Ok(u16::from_be_bytes(self.array()?))
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        Ok(u32::from_be_bytes(self.array()?))
+    }
+
+    fn f32(&mut self) -> Result<f32, String> {
+        Ok(f32::from_be_bytes(self.array()?))
+    }
+
+    /// A length-prefixed, null-terminated UTF-16 string
+    fn name(&mut self) -> Result<String, String> {
+        let length = usize::from(self.u16()?);
+        let units = (0..length).map(|_| self.u16()).collect::<Result<Vec<_>, _>>()?;
+        let units = units.strip_suffix(&[0]).unwrap_or(&units);
+        String::from_utf16(units).map_err(|e| e.to_string())
+    }
+}
+
+/// Parse an Adobe Swatch Exchange file
+///
+/// Groups are flattened, and Lab and gray colors become screen colors
+pub fn decode_ase(bytes: &[u8]) -> Result<Vec<Swatch>, String> {
+    let mut reader = AseReader { bytes };
+    if reader.take(4)? != b"ASEF" {
+        return Err("not a swatch exchange file".to_owned());
+    }
+    let _version = (reader.u16()?, reader.u16()?);
+    let block_count = reader.u32()?;
+    let mut swatches = Vec::new();
+    for _ in 0..block_count {
+        let kind = reader.u16()?;
+        let length = usize::try_from(reader.u32()?).map_err(|e| e.to_string())?;
+        let mut block = AseReader { bytes: reader.take(length)? };
+        // group starts and ends only organize the colors
+        if kind != ASE_COLOR {
+            continue;
+        }
+        let name = block.name()?;
+        let model = block.array::<4>()?;
+        let color = match &model {
+            b"RGB " => {
+                let [r, g, b] = [block.f32()?, block.f32()?, block.f32()?].map(unit_to_channel);
+                SwatchColor::Rgb(Color::new(r, g, b, 255))
+            }
+            b"CMYK" => SwatchColor::Cmyk(Cmyk::new(block.f32()?, block.f32()?, block.f32()?, block.f32()?)),
+            b"LAB " => SwatchColor::Rgb(lab_to_rgb(block.f32()? * 100.0, block.f32()?, block.f32()?)),
+            b"Gray" => {
+                let gray = unit_to_channel(block.f32()?);
+                SwatchColor::Rgb(Color::new(gray, gray, gray, 255))
+            }
+            _ => return Err(format!("unknown color model {:?}", String::from_utf8_lossy(&model))),
+        };
+        let color = match (block.u16()?, color) {
+            (ASE_SPOT, SwatchColor::Rgb(color)) => SwatchColor::Spot { ink: name.clone(), alternate: Cmyk::from_rgb(color) },
+            (ASE_SPOT, SwatchColor::Cmyk(cmyk)) => SwatchColor::Spot { ink: name.clone(), alternate: cmyk },
+            (_, color) => color,
+        };
+        swatches.push(Swatch { name, color });
+    }
+    Ok(swatches)
+}
+
+/// Write an Adobe Swatch Exchange file
+///
+/// Spot colors are named after their ink
+pub fn encode_ase(swatches: &[Swatch]) -> Vec<u8> {
+    let mut bytes = b"ASEF".to_vec();
+    bytes.extend(1u16.to_be_bytes());
+    bytes.extend(0u16.to_be_bytes());
+    bytes.extend(u32::try_from(swatches.len()).unwrap_or(u32::MAX).to_be_bytes());
+    for swatch in swatches {
+        let (name, model, values, kind): (&str, &[u8; 4], Vec<f32>, u16) = match &swatch.color {
+            SwatchColor::Rgb(color) => (swatch.name.as_str(), b"RGB ", [color.r, color.g, color.b].map(|channel| f32::from(channel) / 255.0).to_vec(), ASE_NORMAL),
+            SwatchColor::Cmyk(Cmyk { c, m, y, k }) => (swatch.name.as_str(), b"CMYK", vec![*c, *m, *y, *k], ASE_NORMAL),
+            SwatchColor::Spot { ink, alternate: Cmyk { c, m, y, k } } => (ink.as_str(), b"CMYK", vec![*c, *m, *y, *k], ASE_SPOT),
+        };
+        let mut block = Vec::new();
+        let units = name.encode_utf16().chain([0]).collect::<Vec<_>>();
+        block.extend(u16::try_from(units.len()).unwrap_or(u16::MAX).to_be_bytes());
+        block.extend(units.iter().flat_map(|unit| unit.to_be_bytes()));
+        block.extend(model);
+        block.extend(values.iter().flat_map(|value| value.to_be_bytes()));
+        block.extend(kind.to_be_bytes());
+
+        bytes.extend(ASE_COLOR.to_be_bytes());
+        bytes.extend(u32::try_from(block.len()).unwrap_or(u32::MAX).to_be_bytes());
+        bytes.extend(block);
+    }
+    bytes
+}
+
+/// Convert a fraction from 0 to 1 to a color channel from 0 to 255
+fn unit_to_channel(value: f32) -> u8 {
+    (value.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+/// Convert a CIE Lab color (relative to the D50 white point, like swatch exchange files) to an opaque screen color
+///
+/// `l` is from 0 to 100
+fn lab_to_rgb(l: f32, a: f32, b: f32) -> Color {
+    const EPSILON: f32 = 216.0 / 24389.0;
+    const KAPPA: f32 = 24389.0 / 27.0;
+    const WHITE: [f32; 3] = [0.96422, 1.0, 0.82521];
+    // XYZ (D50) to linear sRGB, with Bradford adaptation to D65
+    const XYZ_TO_RGB: [[f32; 3]; 3] = [
+        [ 3.1338561, -1.6168667, -0.4906146],
+        [-0.9787684,  1.9161415,  0.0334540],
+        [ 0.0719453, -0.2289914,  1.4052427],
+    ];
+
+    let fy = (l + 16.0) / 116.0;
+    let fx = fy + a / 500.0;
+    let fz = fy - b / 200.0;
+    let inverse = |f: f32| if f.powi(3) > EPSILON { f.powi(3) } else { (116.0 * f - 16.0) / KAPPA };
+    let y = if l > KAPPA * EPSILON { fy.powi(3) } else { l / KAPPA };
+    let xyz = [inverse(fx) * WHITE[0], y * WHITE[1], inverse(fz) * WHITE[2]];
+    let [r, g, b] = XYZ_TO_RGB.map(|row| {
+        let linear = row[0] * xyz[0] + row[1] * xyz[1] + row[2] * xyz[2];
+        let encoded = if linear <= 0.0031308 { linear * 12.92 } else { 1.055 * linear.powf(2.4_f32.recip()) - 0.055 };
+        unit_to_channel(encoded)
+    });
+    Color::new(r, g, b, 255)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gpl_and_hex() {
+        let gpl = "GIMP Palette\nName: Test\nColumns: 2\n# comment\n255   0   0\tRed\n  0 128 255\tUntitled\n";
+        let swatches = decode_gpl(gpl).unwrap();
+        assert_eq!(swatches.iter().map(|swatch| swatch.name.as_str()).collect::<Vec<_>>(), ["Red", "#0080FF"]);
+        assert_eq!(decode_gpl(&encode_gpl("Test", &swatches)).unwrap(), swatches);
+        assert!(decode_gpl("not a palette").is_err());
+
+        let swatches = decode_hex("; lospec\nff0000\n#00ff0080\n\n").unwrap();
+        assert_eq!(swatches.iter().map(|swatch| swatch.color.preview()).collect::<Vec<_>>(), [Color::new(255, 0, 0, 255), Color::new(0, 255, 0, 128)]);
+        assert_eq!(encode_hex(&swatches), "ff0000\n00ff00\n");
+        assert!(decode_hex("red").is_err());
+    }
+
+    #[test]
+    fn test_ase_round_trip() {
+        let swatches = vec![
+            Swatch::from_rgb(Color::new(255, 128, 0, 255)),
+            Swatch { name: "Paper".to_owned(), color: SwatchColor::Cmyk(Cmyk::new(0.0, 0.0, 0.1, 0.0)) },
+            Swatch { name: "PANTONE 186 C".to_owned(), color: SwatchColor::Spot { ink: "PANTONE 186 C".to_owned(), alternate: Cmyk::new(0.0, 1.0, 0.8, 0.05) } },
+        ];
+        assert_eq!(decode_ase(&encode_ase(&swatches)).unwrap(), swatches);
+        assert!(decode_ase(b"ASEF\0\x01").is_err());
+        // Lab white is screen white
+        assert_eq!(lab_to_rgb(100.0, 0.0, 0.0), Color::WHITE);
+    }
+}
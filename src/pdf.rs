@@ -0,0 +1,1098 @@
+use std::{collections::HashMap, path::Path};
+use raylib::prelude::*;
+use crate::{
+    curve::{Curve, CurvePoint},
+    document::{Artboard, Document},
+    fill::FillRule,
+    layer::{Layer, LayerContent},
+    style::Pattern,
+    svg::{self, Paint},
+    swatch::{Cmyk, Swatch, SwatchColor},
+    units::Unit,
+};
+
+/// File extensions of the formats [`import`] reads
+pub const EXTENSIONS: [&str; 3] = ["ai", "eps", "pdf"];
+
+/// US Letter in points, for files that don't say how big their page is
+const DEFAULT_PAGE: [f32; 4] = [0.0, 0.0, 612.0, 792.0];
+
+/// A transformation matrix `[a b c d e f]`, mapping `(x, y)` to `(ax + cy + e, bx + dy + f)` as in PDF
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Transform([f32; 6]);
+
+impl Transform {
+    fn apply(self, x: f32, y: f32) -> na::Vector2<f32> {
+        let [a, b, c, d, e, f] = self.0;
+        na::Vector2::new(a * x + c * y + e, b * x + d * y + f)
+    }
+
+    /// The transform applying `inner` first, then `self`
+    fn concat(self, inner: Self) -> Self {
+        let [a, b, c, d, e, f] = inner.0;
+        let [sa, sb, sc, sd, se, sf] = self.0;
+        Self([
+            sa * a + sc * b,
+            sb * a + sd * b,
+            sa * c + sc * d,
+            sb * c + sd * d,
+            sa * e + sc * f + se,
+            sb * e + sd * f + sf,
+        ])
+    }
+
+    /// How much the transform scales lengths, on average
+    fn scale(self) -> f32 {
+        let [a, b, c, d, _, _] = self.0;
+        (a * d - b * c).abs().sqrt()
+    }
+}
+
+/// The parts of the PDF graphics state that affect paths
+#[derive(Debug, Clone, Copy)]
+struct GraphicsState {
+    ctm: Transform,
+    fill: Color,
+    stroke: Color,
+    line_width: f32,
+}
+
+/// A path being built by path construction operators, in document space
+#[derive(Debug, Clone, Default)]
+struct PathBuilder {
+    curves: Vec<Curve>,
+    curve: Curve,
+}
+
+impl PathBuilder {
+    fn current(&self) -> Option<na::Vector2<f32>> {
+        self.curve.points.last().map(|point| point.p)
+    }
+
+    fn move_to(&mut self, p: na::Vector2<f32>) {
+        self.finish();
+        self.curve.points.push(CurvePoint { c_in: na::Vector2::zeros(), p, c_out: na::Vector2::zeros() });
+    }
+
+    /// Append a cubic segment from the current point to `p`; starts a new subpath at `p` if there is no current point
+    fn curve_to(&mut self, c1: na::Vector2<f32>, c2: na::Vector2<f32>, p: na::Vector2<f32>) {
+        if let Some(prev) = self.curve.points.last_mut() {
+            prev.c_out = c1 - prev.p;
+            self.curve.points.push(CurvePoint { c_in: c2 - p, p, c_out: na::Vector2::zeros() });
+        } else {
+            self.move_to(p);
+        }
+    }
+
+    fn line_to(&mut self, p: na::Vector2<f32>) {
+        let from = self.current().unwrap_or(p);
+        self.curve_to(from, p, p);
+    }
+
+    /// Close the current subpath; drawing continues from its start
+    fn close(&mut self) {
+        let Some(start) = self.curve.points.first().map(|point| point.p) else { return };
+        // a closing point on top of the start is redundant with `is_closed`
+        if self.curve.points.len() > 1
+            && let Some(last) = self.curve.points.last()
+            && (last.p - start).norm() < f32::EPSILON
+        {
+            let last = self.curve.points.pop().expect("should have at least two points");
+            self.curve.points[0].c_in = last.c_in;
+        }
+        self.curve.is_closed = true;
+        self.move_to(start);
+    }
+
+    /// Keep the current subpath if it has any segments
+    fn finish(&mut self) {
+        if self.curve.points.len() > 1 {
+            self.curves.push(std::mem::take(&mut self.curve));
+        } else {
+            self.curve.points.clear();
+        }
+        self.curve.is_closed = false;
+    }
+
+    /// Take every subpath, leaving the path empty
+    fn take(&mut self) -> Vec<Curve> {
+        self.finish();
+        std::mem::take(&mut self.curves)
+    }
+}
+
+/// A token of a PDF content stream or PostScript program
+#[derive(Debug, Clone, Copy)]
+enum Token<'a> {
+    Number(f32),
+    Operator(&'a [u8]),
+    /// Names, strings, arrays, dictionaries, and procedures, none of which the importer needs
+    Other,
+}
+
+/// Splits content streams into tokens, skipping comments
+struct Lexer<'a> {
+    src: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Lexer<'a> {
+    const fn new(src: &'a [u8]) -> Self {
+        Self { src, pos: 0 }
+    }
+
+    const fn is_whitespace(b: u8) -> bool {
+        matches!(b, b' ' | b'\t' | b'\r' | b'\n' | b'\x0c' | b'\0')
+    }
+
+    const fn is_delimiter(b: u8) -> bool {
+        matches!(b, b'(' | b')' | b'<' | b'>' | b'[' | b']' | b'{' | b'}' | b'/' | b'%')
+    }
+
+    fn peek(&self, offset: usize) -> Option<u8> {
+        self.src.get(self.pos.saturating_add(offset)).copied()
+    }
+
+    /// Take the run of characters up to the next whitespace or delimiter
+    fn word(&mut self) -> &'a [u8] {
+        let start = self.pos;
+        while let Some(b) = self.peek(0) && !Self::is_whitespace(b) && !Self::is_delimiter(b) {
+            self.pos += 1;
+        }
+        &self.src[start..self.pos]
+    }
+
+    /// Skip a literal string, whose parentheses may nest
+    fn skip_string(&mut self) {
+        let mut depth = 0u32;
+        while let Some(b) = self.peek(0) {
+            self.pos += 1;
+            match b {
+                b'\\' => self.pos += 1,
+                b'(' => depth += 1,
+                b')' => {
+                    depth = depth.saturating_sub(1);
+                    if depth == 0 {
+                        break;
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+
+    /// Skip the binary data of an inline image, which follows its `ID` operator up to `EI`
+    fn skip_inline_image(&mut self) {
+        let rest = &self.src[self.pos.min(self.src.len())..];
+        self.pos = rest.windows(4)
+            .position(|window| Self::is_whitespace(window[0]) && &window[1..3] == b"EI" && Self::is_whitespace(window[3]))
+            .map_or(self.src.len(), |i| self.pos + i + 3);
+    }
+}
+
+impl<'a> Iterator for Lexer<'a> {
+    type Item = Token<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let b = self.peek(0)?;
+            match b {
+                _ if Self::is_whitespace(b) => self.pos += 1,
+                b'%' => {
+                    while let Some(b) = self.peek(0) && b != b'\n' && b != b'\r' {
+                        self.pos += 1;
+                    }
+                }
+                b'(' => {
+                    self.skip_string();
+                    return Some(Token::Other);
+                }
+                b'<' if self.peek(1) != Some(b'<') => {
+                    // hex string
+                    while let Some(b) = self.peek(0) {
+                        self.pos += 1;
+                        if b == b'>' {
+                            break;
+                        }
+                    }
+                    return Some(Token::Other);
+                }
+                b'<' | b'>' => {
+                    self.pos += if self.peek(1) == Some(b) { 2 } else { 1 };
+                    return Some(Token::Other);
+                }
+                b'/' => {
+                    self.pos += 1;
+                    self.word();
+                    return Some(Token::Other);
+                }
+                _ if Self::is_delimiter(b) => {
+                    self.pos += 1;
+                    return Some(Token::Other);
+                }
+                b'0'..=b'9' | b'+' | b'-' | b'.' => {
+                    let word = self.word();
+                    let number = std::str::from_utf8(word).ok().and_then(|word| word.parse().ok());
+                    return Some(number.map_or(Token::Other, Token::Number));
+                }
+                _ => return Some(Token::Operator(self.word())),
+            }
+        }
+    }
+}
+
+/// An opaque color from gray, RGB, or CMYK components between 0 and 1, depending on how many there are
+fn color_from_components(components: &[f32]) -> Option<Color> {
+    let channel = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+    match *components {
+        [gray] => Some(Color::new(channel(gray), channel(gray), channel(gray), 255)),
+        [r, g, b] => Some(Color::new(channel(r), channel(g), channel(b), 255)),
+        [c, m, y, k] => Some(Cmyk::new(c, m, y, k).to_rgb()),
+        _ => None,
+    }
+}
+
+/// Runs content streams, turning painted paths into layers
+struct Interpreter<'a> {
+    document: &'a mut Document,
+    state: GraphicsState,
+    /// Saved by `q` and `gsave`, along with the path, which PostScript keeps in the graphics state
+    saved: Vec<(GraphicsState, PathBuilder)>,
+    path: PathBuilder,
+    /// The current point and the start of the current subpath, in user space, for relative PostScript operators
+    current: na::Vector2<f32>,
+    start: na::Vector2<f32>,
+    operands: Vec<f32>,
+    /// The layers of each open group, starting with the top level
+    groups: Vec<Vec<Layer>>,
+    /// The subpaths and paint of an Illustrator compound path (`*u` ... `*U`) being read, which become one layer
+    compound: Option<(Vec<Curve>, Option<Paint>)>,
+}
+
+impl<'a> Interpreter<'a> {
+    fn new(document: &'a mut Document, ctm: Transform) -> Self {
+        Self {
+            document,
+            state: GraphicsState { ctm, fill: Color::BLACK, stroke: Color::BLACK, line_width: 1.0 },
+            saved: Vec::new(),
+            path: PathBuilder::default(),
+            current: na::Vector2::zeros(),
+            start: na::Vector2::zeros(),
+            operands: Vec::new(),
+            groups: vec![Vec::new()],
+            compound: None,
+        }
+    }
+
+    /// The last `N` operands, if there are that many
+    fn operands<const N: usize>(&self) -> Option<[f32; N]> {
+        let start = self.operands.len().checked_sub(N)?;
+        self.operands[start..].try_into().ok()
+    }
+
+    fn move_to(&mut self, p: na::Vector2<f32>) {
+        self.current = p;
+        self.start = p;
+        self.path.move_to(self.state.ctm.apply(p.x, p.y));
+    }
+
+    fn line_to(&mut self, p: na::Vector2<f32>) {
+        self.current = p;
+        self.path.line_to(self.state.ctm.apply(p.x, p.y));
+    }
+
+    fn curve_to(&mut self, c1: na::Vector2<f32>, c2: na::Vector2<f32>, p: na::Vector2<f32>) {
+        self.current = p;
+        let ctm = self.state.ctm;
+        self.path.curve_to(ctm.apply(c1.x, c1.y), ctm.apply(c2.x, c2.y), ctm.apply(p.x, p.y));
+    }
+
+    fn close(&mut self) {
+        self.current = self.start;
+        self.path.close();
+    }
+
+    fn rect(&mut self, x: f32, y: f32, width: f32, height: f32) {
+        self.move_to(na::Vector2::new(x, y));
+        self.line_to(na::Vector2::new(x + width, y));
+        self.line_to(na::Vector2::new(x + width, y + height));
+        self.line_to(na::Vector2::new(x, y + height));
+        self.close();
+    }
+
+    /// Paint the current path, filled by `fill_rule` if there is one and stroked if `is_stroked`, then clear it
+    fn paint(&mut self, fill_rule: Option<FillRule>, is_stroked: bool) {
+        let curves = self.path.take();
+        let paint = Paint {
+            fill: fill_rule.map(|_| self.state.fill),
+            fill_rule: fill_rule.unwrap_or(FillRule::NonZero),
+            stroke: is_stroked.then_some(self.state.stroke),
+            stroke_width: self.state.line_width * self.state.ctm.scale(),
+        };
+        if let Some((subpaths, compound_paint)) = &mut self.compound {
+            subpaths.extend(curves);
+            *compound_paint = Some(paint);
+        } else {
+            self.push_layer(curves, &paint);
+        }
+    }
+
+    fn push_layer(&mut self, curves: Vec<Curve>, paint: &Paint) {
+        if let Some(layer) = svg::paint_layer(self.document, "path".to_owned(), curves, paint) {
+            self.groups.last_mut().expect("top level should never be popped").push(layer);
+        }
+    }
+
+    /// Close the innermost open group, dropping it if it is empty
+    fn end_group(&mut self) {
+        if self.groups.len() > 1 {
+            let layers = self.groups.pop().expect("should have a group to close");
+            if !layers.is_empty() {
+                let group = svg::group_layer(self.document, "group".to_owned(), layers);
+                self.groups.last_mut().expect("top level should never be popped").push(group);
+            }
+        }
+    }
+
+    fn run(&mut self, src: &[u8]) {
+        let mut lexer = Lexer::new(src);
+        while let Some(token) = lexer.next() {
+            let op = match token {
+                Token::Number(value) => {
+                    self.operands.push(value);
+                    continue;
+                }
+                Token::Other => continue,
+                Token::Operator(op) => op,
+            };
+            self.operator(op, &mut lexer);
+            self.operands.clear();
+        }
+    }
+
+    /// Set the fill and/or stroke color from the last `count` operands, or every operand if [`None`]
+    fn set_color(&mut self, count: Option<usize>, is_fill: bool, is_stroke: bool) {
+        let Some(start) = count.map_or(Some(0), |count| self.operands.len().checked_sub(count)) else { return };
+        if let Some(color) = color_from_components(&self.operands[start..]) {
+            if is_fill {
+                self.state.fill = color;
+            }
+            if is_stroke {
+                self.state.stroke = color;
+            }
+        }
+    }
+
+    fn operator(&mut self, op: &[u8], lexer: &mut Lexer<'_>) {
+        let v = na::Vector2::<f32>::new;
+        match op {
+            b"q" | b"gsave" => self.saved.push((self.state, self.path.clone())),
+            b"Q" | b"grestore" => if let Some((state, path)) = self.saved.pop() {
+                self.state = state;
+                self.path = path;
+            },
+            b"cm" | b"concat" => if let Some(m) = self.operands::<6>() {
+                self.state.ctm = self.state.ctm.concat(Transform(m));
+            },
+            b"w" | b"setlinewidth" => if let Some([width]) = self.operands() {
+                self.state.line_width = width;
+            },
+
+            // path construction; Illustrator's uppercase variants mark smooth points, which curves don't need to know
+            b"m" | b"moveto" => if let Some([x, y]) = self.operands() { self.move_to(v(x, y)) },
+            b"rmoveto" => if let Some([dx, dy]) = self.operands() { self.move_to(self.current + v(dx, dy)) },
+            b"l" | b"L" | b"lineto" => if let Some([x, y]) = self.operands() { self.line_to(v(x, y)) },
+            b"rlineto" => if let Some([dx, dy]) = self.operands() { self.line_to(self.current + v(dx, dy)) },
+            b"c" | b"C" | b"curveto" => if let Some([x1, y1, x2, y2, x3, y3]) = self.operands() {
+                self.curve_to(v(x1, y1), v(x2, y2), v(x3, y3));
+            },
+            b"rcurveto" => if let Some([x1, y1, x2, y2, x3, y3]) = self.operands() {
+                let from = self.current;
+                self.curve_to(from + v(x1, y1), from + v(x2, y2), from + v(x3, y3));
+            },
+            b"v" | b"V" => if let Some([x2, y2, x3, y3]) = self.operands() {
+                self.curve_to(self.current, v(x2, y2), v(x3, y3));
+            },
+            b"y" | b"Y" => if let Some([x1, y1, x3, y3]) = self.operands() {
+                self.curve_to(v(x1, y1), v(x3, y3), v(x3, y3));
+            },
+            b"h" | b"H" | b"closepath" => self.close(),
+            b"re" => if let Some([x, y, width, height]) = self.operands() { self.rect(x, y, width, height) },
+            b"rectfill" => if let Some([x, y, width, height]) = self.operands() {
+                self.rect(x, y, width, height);
+                self.paint(Some(FillRule::NonZero), false);
+            },
+
+            // path painting; filling implicitly closes every subpath, so they are closed explicitly
+            b"f" | b"F" | b"fill" => {
+                self.close();
+                self.paint(Some(FillRule::NonZero), false);
+            }
+            b"f*" | b"eofill" => {
+                self.close();
+                self.paint(Some(FillRule::EvenOdd), false);
+            }
+            b"S" | b"stroke" => self.paint(None, true),
+            b"s" => {
+                self.close();
+                self.paint(None, true);
+            }
+            b"B" => self.paint(Some(FillRule::NonZero), true),
+            b"B*" => self.paint(Some(FillRule::EvenOdd), true),
+            b"b" => {
+                self.close();
+                self.paint(Some(FillRule::NonZero), true);
+            }
+            b"b*" => {
+                self.close();
+                self.paint(Some(FillRule::EvenOdd), true);
+            }
+            // clipping paths are ignored, so the path they are set from is discarded
+            b"n" | b"N" | b"newpath" => _ = self.path.take(),
+
+            // color; PostScript operators set one color for both filling and stroking
+            b"g" => self.set_color(Some(1), true, false),
+            b"G" => self.set_color(Some(1), false, true),
+            b"setgray" => self.set_color(Some(1), true, true),
+            b"rg" | b"Xa" => self.set_color(Some(3), true, false),
+            b"RG" | b"XA" => self.set_color(Some(3), false, true),
+            b"setrgbcolor" => self.set_color(Some(3), true, true),
+            b"k" => self.set_color(Some(4), true, false),
+            b"K" => self.set_color(Some(4), false, true),
+            b"setcmykcolor" => self.set_color(Some(4), true, true),
+            // the number of components depends on the color space, which is assumed to be gray, RGB, or CMYK
+            b"sc" | b"scn" => self.set_color(None, true, false),
+            b"SC" | b"SCN" => self.set_color(None, false, true),
+
+            // groups: PDF marked content, which Illustrator wraps each layer in, and Illustrator's own groups
+            b"BMC" | b"BDC" | b"u" => self.groups.push(Vec::new()),
+            b"EMC" | b"U" => self.end_group(),
+            b"*u" => self.compound = Some((Vec::new(), None)),
+            b"*U" => if let Some((curves, Some(paint))) = self.compound.take() {
+                self.push_layer(curves, &paint);
+            },
+
+            b"ID" => lexer.skip_inline_image(),
+            _ => (),
+        }
+    }
+
+    /// Close any groups left open by malformed input and return the top-level layers
+    fn finish(mut self) -> Vec<Layer> {
+        while self.groups.len() > 1 {
+            self.end_group();
+        }
+        self.groups.pop().expect("top level should never be popped")
+    }
+}
+
+/// The position of the first occurrence of `needle` in `haystack`
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|window| window == needle)
+}
+
+/// The numbers in `text` until the first token that isn't one
+fn numbers(text: &str) -> impl Iterator<Item = f32> + '_ {
+    text.split(|c: char| c.is_whitespace() || c == '[' || c == ']')
+        .filter(|word| !word.is_empty())
+        .map_while(|word| word.parse().ok())
+}
+
+/// A rectangle given as `[left bottom right top]` at the start of `text`
+fn parse_box(text: &str) -> Option<[f32; 4]> {
+    let mut numbers = numbers(text);
+    let [a, b, c, d] = [numbers.next()?, numbers.next()?, numbers.next()?, numbers.next()?];
+    Some([a.min(c), b.min(d), a.max(c), b.max(d)])
+}
+
+/// Decompress zlib data, as stored by PDF's `FlateDecode` filter
+fn inflate(zlib: &[u8]) -> Option<Vec<u8>> {
+    // raylib inflates raw deflate data, without zlib's 2 byte header
+    let data = zlib.get(2..)?;
+    let mut size = 0;
+    // SAFETY: the input is only read, and the output buffer is copied before raylib frees it
+    unsafe {
+        let out = raylib::ffi::DecompressData(data.as_ptr(), i32::try_from(data.len()).ok()?, &mut size);
+        if out.is_null() {
+            return None;
+        }
+        let bytes = std::slice::from_raw_parts(out, size.max(0) as usize).to_vec();
+        raylib::ffi::MemFree(out.cast());
+        Some(bytes)
+    }
+}
+
+/// The objects of a PDF file, by object number
+///
+/// Objects in compressed object streams aren't found
+struct Objects<'a> {
+    bytes: &'a [u8],
+    /// The position just after `obj` of each object; later definitions replace earlier ones, as incremental updates do
+    offsets: HashMap<u32, usize>,
+}
+
+impl<'a> Objects<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        let mut offsets = HashMap::new();
+        let mut search = 0;
+        while let Some(found) = bytes.get(search..).and_then(|rest| find(rest, b"obj")) {
+            let at = search + found;
+            search = at + 3;
+            // `<number> <generation> obj`, which rules out `endobj`
+            let header = String::from_utf8_lossy(&bytes[at.saturating_sub(24)..at]);
+            if !header.ends_with(char::is_whitespace) {
+                continue;
+            }
+            let mut words = header.split_whitespace().rev();
+            if let (Some(generation), Some(number)) = (words.next(), words.next())
+                && generation.parse::<u32>().is_ok()
+                && let Ok(number) = number.parse::<u32>()
+            {
+                offsets.insert(number, search);
+            }
+        }
+        Self { bytes, offsets }
+    }
+
+    /// The text of the object's dictionary (or other value), and its stream data if it has a stream
+    fn get(&self, number: u32) -> Option<(String, Option<&'a [u8]>)> {
+        let body = &self.bytes[*self.offsets.get(&number)?..];
+        let end = find(body, b"endobj").unwrap_or(body.len());
+        let body = &body[..end];
+        let Some(stream_start) = find(body, b"stream") else {
+            return Some((String::from_utf8_lossy(body).into_owned(), None));
+        };
+        let dict = String::from_utf8_lossy(&body[..stream_start]).into_owned();
+        let mut data = &body[stream_start + b"stream".len()..];
+        data = data.strip_prefix(b"\r").unwrap_or(data);
+        data = data.strip_prefix(b"\n").unwrap_or(data);
+        let data_end = find(data, b"endstream").unwrap_or(data.len());
+        Some((dict, Some(&data[..data_end])))
+    }
+
+    /// The first page in file order, as the text of its dictionary
+    fn first_page(&self) -> Option<String> {
+        let mut offsets = self.offsets.iter().collect::<Vec<_>>();
+        offsets.sort_by_key(|&(_, &offset)| offset);
+        offsets.into_iter()
+            .filter_map(|(&number, _)| self.get(number))
+            .map(|(dict, _)| dict)
+            .find(|dict| {
+                let compact = dict.split_whitespace().collect::<String>();
+                compact.match_indices("/Type/Page").any(|(i, _)| {
+                    !compact[i + "/Type/Page".len()..].starts_with(|c: char| c.is_ascii_alphanumeric())
+                })
+            })
+    }
+
+    /// The decoded data of the stream object `number`
+    fn stream(&self, number: u32) -> Result<Vec<u8>, String> {
+        let (dict, data) = self.get(number).ok_or_else(|| format!("missing object {number}"))?;
+        let data = data.ok_or_else(|| format!("object {number} isn't a stream"))?;
+        if dict.contains("/FlateDecode") {
+            inflate(data).ok_or_else(|| format!("failed to decompress object {number}"))
+        } else if dict.contains("/Filter") {
+            Err(format!("object {number} uses an unsupported compression filter"))
+        } else {
+            Ok(data.to_vec())
+        }
+    }
+}
+
+/// The object numbers of the indirect references (`<number> <generation> R`) in `text`
+fn references(text: &str) -> Vec<u32> {
+    let words = text.split(|c: char| c.is_whitespace() || c == '[' || c == ']').filter(|word| !word.is_empty()).collect::<Vec<_>>();
+    words.windows(3)
+        .filter(|window| window[2] == "R" && window[1].parse::<u32>().is_ok())
+        .filter_map(|window| window[0].parse().ok())
+        .collect()
+}
+
+/// The page box and content of the first page of a PDF file, or Illustrator file saved with PDF content
+fn read_pdf(bytes: &[u8]) -> Result<([f32; 4], Vec<u8>), String> {
+    let objects = Objects::new(bytes);
+    let page = objects.first_page().ok_or("no page found; pages in compressed object streams aren't supported")?;
+    // the media box may be inherited from the page tree
+    let file_text = String::from_utf8_lossy(bytes);
+    let page_box = [page.as_str(), file_text.as_ref()].into_iter()
+        .find_map(|text| text.find("/MediaBox").and_then(|i| parse_box(&text[i + "/MediaBox".len()..])))
+        .unwrap_or(DEFAULT_PAGE);
+    let contents = page.find("/Contents").map(|i| &page[i + "/Contents".len()..]).ok_or("the page has no content")?;
+    let contents = contents.trim_start();
+    // one stream, an array of streams, or a reference to an array of streams
+    let mut streams = if contents.starts_with('[') {
+        references(&contents[..contents.find(']').unwrap_or(contents.len())])
+    } else {
+        references(contents).into_iter().take(1).collect()
+    };
+    if let [number] = streams[..]
+        && let Some((value, None)) = objects.get(number)
+        && value.trim_start().starts_with('[')
+    {
+        streams = references(&value);
+    }
+    let mut content = Vec::new();
+    for number in streams {
+        content.extend(objects.stream(number)?);
+        content.push(b'\n');
+    }
+    Ok((page_box, content))
+}
+
+/// The bounding box and artwork of an EPS file, or an Illustrator file saved without PDF content
+fn read_eps(bytes: &[u8]) -> Result<([f32; 4], Vec<u8>), String> {
+    // DOS EPS files wrap the PostScript in a binary header, beside a preview image
+    let ps = if bytes.starts_with(&[0xC5, 0xD0, 0xD3, 0xC6]) {
+        let field = |i: usize| bytes.get(i..i + 4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as usize);
+        let (offset, length) = field(4).zip(field(8)).ok_or("truncated EPS header")?;
+        bytes.get(offset..offset.saturating_add(length)).ok_or("truncated EPS file")?
+    } else {
+        bytes
+    };
+    if !ps.starts_with(b"%!") {
+        return Err("not a PDF, EPS, or Illustrator file".to_owned());
+    }
+    let text = String::from_utf8_lossy(ps);
+    // the bounding box may be deferred to the trailer with `(atend)`
+    let page_box = ["%%HiResBoundingBox:", "%%BoundingBox:"].into_iter()
+        .flat_map(|comment| text.match_indices(comment).map(move |(i, _)| i + comment.len()))
+        .find_map(|i| parse_box(&text[i..]))
+        .unwrap_or(DEFAULT_PAGE);
+    // skip the prolog and setup, which define procedures rather than draw
+    let start = [b"%%EndSetup".as_slice(), b"%%EndProlog"].into_iter()
+        .find_map(|comment| find(ps, comment))
+        .unwrap_or(0);
+    let end = find(&ps[start..], b"%%Trailer").map_or(ps.len(), |i| start + i);
+    Ok((page_box, ps[start..end].to_vec()))
+}
+
+/// Build a document titled `title` from a content stream drawn on `page_box` (`[left bottom right top]`, in points)
+fn read_artwork(title: String, page_box: [f32; 4], content: &[u8]) -> Document {
+    let mut document = Document::new(title);
+    // PDF and PostScript measure in points
+    document.info.units = Unit::Points;
+    document.info.dpi = 72.0;
+    let [left, bottom, right, top] = page_box;
+    if right > left && top > bottom {
+        document.artboards.push(Artboard::new(document.title.clone(), Rectangle::new(0.0, 0.0, right - left, top - bottom)));
+    }
+    // the page's y axis points up, and the document's points down
+    let mut interpreter = Interpreter::new(&mut document, Transform([1.0, 0.0, 0.0, -1.0, -left, top]));
+    interpreter.run(content);
+    let layers = interpreter.finish();
+    document.layers = layers;
+    document
+}
+
+/// Read the vector artwork of a PDF, EPS, or PDF-compatible Illustrator file into a new document
+///
+/// Only the first page is read. Paths are imported with solid gray, RGB, or CMYK fills and strokes, in groups
+/// following the file's layers; text, images, gradients, clipping, and embedded forms are ignored
+pub fn import(path: &Path) -> Result<Document, String> {
+    let bytes = std::fs::read(path).map_err(|e| e.to_string())?;
+    let title = path.file_stem().unwrap_or_default().to_string_lossy().into_owned();
+    let (page_box, content) = if bytes.starts_with(b"%PDF") { read_pdf(&bytes)? } else { read_eps(&bytes)? };
+    Ok(read_artwork(title, page_box, &content))
+}
+
+/// A content stream being written, along with the transparency and spot inks it paints with
+#[derive(Debug)]
+struct ContentWriter<'a> {
+    content: String,
+    /// Colors matching the screen preview of a process or spot swatch are painted with the swatch's inks
+    swatches: &'a [Swatch],
+    /// The alpha of the current graphics state
+    alpha: u8,
+    /// Every alpha set with `gs`, each of which needs a graphics state resource
+    alphas: Vec<u8>,
+    /// The name and alternate process inks of every spot ink painted with, each of which needs a separation color space resource
+    separations: Vec<(String, Cmyk)>,
+}
+
+impl<'a> ContentWriter<'a> {
+    /// Start a content stream mapping worldspace within `bounds` onto a page of the same size, whose y axis points up
+    fn new(bounds: Rectangle, swatches: &'a [Swatch]) -> Self {
+        Self {
+            content: format!("1 0 0 -1 {} {} cm\n", -bounds.x, bounds.y + bounds.height),
+            swatches,
+            alpha: 255,
+            alphas: Vec::new(),
+            separations: Vec::new(),
+        }
+    }
+
+    /// Set the fill or stroke color, along with the alpha of both
+    ///
+    /// Fills and strokes are painted separately, so sharing an alpha never mixes them up.
+    /// Colors that are the screen preview of a process swatch are set as CMYK, and those of a spot swatch as a full tint of its separation
+    fn set_color(&mut self, color: Color, is_stroke: bool) {
+        use std::fmt::Write;
+        let opaque = Color { a: 255, ..color };
+        match self.swatches.iter().find(|swatch| swatch.color.preview() == opaque).map(|swatch| &swatch.color) {
+            Some(SwatchColor::Cmyk(Cmyk { c, m, y, k })) => {
+                _ = writeln!(self.content, "{c} {m} {y} {k} {}", if is_stroke { "K" } else { "k" });
+            }
+            Some(SwatchColor::Spot { ink, alternate }) => {
+                let index = match self.separations.iter().position(|(name, _)| name == ink) {
+                    Some(index) => index,
+                    None => {
+                        self.separations.push((ink.clone(), *alternate));
+                        self.separations.len().saturating_sub(1)
+                    }
+                };
+                let (space, tint) = if is_stroke { ("CS", "SCN") } else { ("cs", "scn") };
+                _ = writeln!(self.content, "/Ink{index} {space} 1 {tint}");
+            }
+            Some(SwatchColor::Rgb(_)) | None => {
+                let [r, g, b] = [color.r, color.g, color.b].map(|channel| f32::from(channel) / 255.0);
+                _ = writeln!(self.content, "{r} {g} {b} {}", if is_stroke { "RG" } else { "rg" });
+            }
+        }
+        if color.a != self.alpha {
+            self.alpha = color.a;
+            if !self.alphas.contains(&color.a) {
+                self.alphas.push(color.a);
+            }
+            _ = writeln!(self.content, "/A{} gs", color.a);
+        }
+    }
+
+    /// Append the path construction operators of `curves`
+    fn path<'c>(&mut self, curves: impl IntoIterator<Item = &'c Curve>) {
+        use std::fmt::Write;
+        for curve in curves {
+            let Some(first) = curve.points.first() else { continue };
+            _ = writeln!(self.content, "{} {} m", first.p.x, first.p.y);
+            for [_, c1, c2, p] in curve.iter().spline().spline_windows() {
+                _ = writeln!(self.content, "{} {} {} {} {} {} c", c1.x, c1.y, c2.x, c2.y, p.x, p.y);
+            }
+            if curve.is_closed {
+                self.content.push_str("h\n");
+            }
+        }
+    }
+
+    /// Append the operators painting `layer`, with groups as marked content
+    ///
+    /// Paint follows [the SVG exporter][`svg::export`]: only closed curves are filled, and strokes are cut to their trim and ended with their markers.
+    /// [Templates][`Layer::is_template`], texture patterns, images, and painted rasters are left out
+    fn layer(&mut self, layer: &Layer) {
+        use std::fmt::Write;
+        if layer.is_template {
+            return;
+        }
+        match &layer.content {
+            LayerContent::Group(group) => {
+                self.content.push_str("/Group BMC\n");
+                for layer in &group.layers {
+                    self.layer(layer);
+                }
+                self.content.push_str("EMC\n");
+            }
+
+            LayerContent::Curve(_) | LayerContent::Compound(_) | LayerContent::Connector(_) | LayerContent::Rectangle(_) => {
+                let curves = layer.curves().iter()
+                    .filter_map(|curve| curve.upgrade())
+                    .map(|curve| curve.lock().borrow().clone())
+                    .filter(|curve| !curve.points.is_empty())
+                    .collect::<Vec<_>>();
+                let Some(style) = layer.style.upgrade() else { return };
+                let style_lock = style.lock();
+                let style = style_lock.borrow();
+
+                if let Pattern::Solid(color) = style.fill
+                    && color.a > 0
+                    && curves.iter().any(|curve| curve.is_closed)
+                {
+                    self.set_color(color, false);
+                    self.path(curves.iter().filter(|curve| curve.is_closed));
+                    self.content.push_str(if style.fill_rule == FillRule::EvenOdd { "f*\n" } else { "f\n" });
+                }
+
+                let Pattern::Solid(color) = style.stroke.pattern else { return };
+                if color.a == 0 {
+                    return;
+                }
+                let stroked = curves.iter()
+                    .flat_map(|curve| style.stroke.trim.portions(curve))
+                    .collect::<Vec<_>>();
+                // PDF strokes have one width, so variable-width strokes use their widest end
+                let width = style.stroke.end_width(false).max(style.stroke.end_width(true));
+                if width > 0.0 && !stroked.is_empty() {
+                    self.set_color(color, true);
+                    _ = writeln!(self.content, "{width} w");
+                    self.path(&stroked);
+                    self.content.push_str("S\n");
+                }
+                if !style.stroke.markers.is_none() {
+                    self.set_color(color, false);
+                    let markers = [(&style.stroke.markers.start, style.stroke.end_width(false)), (&style.stroke.markers.end, style.stroke.end_width(true))];
+                    for ends in stroked.iter().filter_map(Curve::ends) {
+                        for ((end, direction), (marker, width)) in ends.into_iter().zip(markers) {
+                            let outline = marker.outline_at(end, direction, width);
+                            let Some((first, rest)) = outline.split_first() else { continue };
+                            _ = writeln!(self.content, "{} {} m", first.x, first.y);
+                            for point in rest {
+                                _ = writeln!(self.content, "{} {} l", point.x, point.y);
+                            }
+                            self.content.push_str("h f\n");
+                        }
+                    }
+                }
+            }
+
+            // rasters aren't embedded
+            LayerContent::Image { .. } | LayerContent::Paint(_) => (),
+        }
+    }
+}
+
+/// `text` as a PDF name, without its leading `/`
+///
+/// Bytes outside of printable ASCII, along with delimiters and `#`, are written as `#` and two hex digits
+fn name(text: &str) -> String {
+    use std::fmt::Write;
+    let mut name = String::with_capacity(text.len());
+    for byte in text.bytes() {
+        if matches!(byte, b'!'..=b'~') && !b"()<>[]{}/%#".contains(&byte) {
+            name.push(char::from(byte));
+        } else {
+            _ = write!(name, "#{byte:02X}");
+        }
+    }
+    name
+}
+
+/// `text` as a PDF text string: a literal string if it is printable ASCII, and UTF-16 with a byte order mark otherwise
+fn text_string(text: &str) -> String {
+    use std::fmt::Write;
+    if text.chars().all(|c| c.is_ascii() && !c.is_ascii_control()) {
+        let mut string = String::with_capacity(text.len().saturating_add(2));
+        string.push('(');
+        for c in text.chars() {
+            if matches!(c, '(' | ')' | '\\') {
+                string.push('\\');
+            }
+            string.push(c);
+        }
+        string.push(')');
+        string
+    } else {
+        let mut string = "<FEFF".to_owned();
+        for unit in text.encode_utf16() {
+            _ = write!(string, "{unit:04X}");
+        }
+        string.push('>');
+        string
+    }
+}
+
+/// The Info dictionary keys each [keyword of exported metadata][`crate::export::png_text`] is written as
+///
+/// The artboard has no standard key, so it gets one of its own
+fn info_keys(keyword: &str) -> &'static [&'static str] {
+    match keyword {
+        "Title" => &["Title"],
+        "Author" => &["Author"],
+        "Description" => &["Subject"],
+        "Keywords" => &["Keywords"],
+        "Creation Time" => &["CreationDate"],
+        "Modification Time" => &["ModDate"],
+        "Software" => &["Creator", "Producer"],
+        "Artboard" => &["Artboard"],
+        _ => &[],
+    }
+}
+
+/// Write `layers` as a one-page PDF file whose page is the worldspace rectangle `bounds`, with a point for each worldspace pixel
+///
+/// Curves and compound paths keep their solid fills and strokes, and groups become marked content.
+/// Solid colors matching the screen preview of one of the process or spot `swatches` print with its inks, spot inks on separations of their own.
+/// The keyword/text pairs `text`, as [`png_text`][`crate::export::png_text`] makes them, are written in the Info dictionary
+pub fn export<'a>(layers: impl IntoIterator<Item = &'a Layer>, bounds: Rectangle, swatches: &[Swatch], text: &[(&str, String)]) -> Vec<u8> {
+    use std::fmt::Write;
+    let mut writer = ContentWriter::new(bounds, swatches);
+    for layer in layers {
+        writer.layer(layer);
+    }
+
+    let mut resources = String::new();
+    if !writer.separations.is_empty() {
+        resources.push_str(" /ColorSpace <<");
+        for (index, (ink, Cmyk { c, m, y, k })) in writer.separations.iter().enumerate() {
+            // tints mix linearly from no ink to the full alternate
+            _ = write!(resources, " /Ink{index} [/Separation /{} /DeviceCMYK << /FunctionType 2 /Domain [0 1] /C0 [0 0 0 0] /C1 [{c} {m} {y} {k}] /N 1 >>]", name(ink));
+        }
+        resources.push_str(" >>");
+    }
+    if !writer.alphas.is_empty() {
+        resources.push_str(" /ExtGState <<");
+        for alpha in &writer.alphas {
+            let fraction = f32::from(*alpha) / 255.0;
+            _ = write!(resources, " /A{alpha} << /ca {fraction} /CA {fraction} >>");
+        }
+        resources.push_str(" >>");
+    }
+    let mut info = String::from("<<");
+    for (keyword, value) in text {
+        for key in info_keys(keyword) {
+            if key.ends_with("Date") {
+                // PDF dates are `D:YYYYMMDDHHmmSSZ`, which is ISO 8601 without its separators
+                let digits = value.chars().filter(char::is_ascii_digit).collect::<String>();
+                _ = write!(info, " /{key} (D:{digits}Z)");
+            } else {
+                _ = write!(info, " /{key} {}", text_string(value));
+            }
+        }
+    }
+    info.push_str(" >>");
+
+    let objects = [
+        "<< /Type /Catalog /Pages 2 0 R >>".to_owned(),
+        "<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_owned(),
+        format!("<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] /Resources <<{resources} >> /Contents 4 0 R >>", bounds.width, bounds.height),
+        format!("<< /Length {} >>\nstream\n{}endstream", writer.content.len(), writer.content),
+        info,
+    ];
+    // the comment of bytes above 127 marks the file as binary, as the specification recommends
+    let mut pdf = b"%PDF-1.4\n%\xE2\xE3\xCF\xD3\n".to_vec();
+    let mut offsets = Vec::with_capacity(objects.len());
+    for (number, object) in (1_usize..).zip(&objects) {
+        offsets.push(pdf.len());
+        pdf.extend_from_slice(format!("{number} 0 obj\n{object}\nendobj\n").as_bytes());
+    }
+    let xref = pdf.len();
+    let size = objects.len().saturating_add(1);
+    let mut trailer = format!("xref\n0 {size}\n0000000000 65535 f \n");
+    for offset in offsets {
+        _ = writeln!(trailer, "{offset:010} 00000 n ");
+    }
+    _ = write!(trailer, "trailer\n<< /Size {size} /Root 1 0 R /Info 5 0 R >>\nstartxref\n{xref}\n%%EOF\n");
+    pdf.extend_from_slice(trailer.as_bytes());
+    pdf
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_pdf() {
+        let content = b"/OC /MC0 BDC q 1 0 0 1 10 0 cm 1 0 0 rg 0 0 100 50 re f Q EMC 0 0 1 RG 4 w 0 0 m 50 50 l S";
+        let mut pdf = b"%PDF-1.4\n1 0 obj << /Type /Pages /Kids [2 0 R] /MediaBox [0 0 200 100] >> endobj\n".to_vec();
+        pdf.extend_from_slice(b"2 0 obj << /Type /Page /Parent 1 0 R /Contents 3 0 R >> endobj\n");
+        pdf.extend_from_slice(format!("3 0 obj << /Length {} >> stream\n", content.len()).as_bytes());
+        pdf.extend_from_slice(content);
+        pdf.extend_from_slice(b"\nendstream endobj\n%%EOF\n");
+
+        let (page_box, content) = read_pdf(&pdf).unwrap();
+        assert_eq!(page_box, [0.0, 0.0, 200.0, 100.0]);
+        let document = read_artwork("test".to_owned(), page_box, &content);
+        assert_eq!(document.artboards[0].rect, Rectangle::new(0.0, 0.0, 200.0, 100.0));
+        assert_eq!(document.layers.len(), 2);
+
+        let LayerContent::Group(group) = &document.layers[0].content else { panic!("marked content should become a group") };
+        let LayerContent::Curve(rect) = &group.layers[0].content else { panic!("rectangle should be a single curve") };
+        let rect = rect.upgrade().unwrap();
+        let rect_lock = rect.lock();
+        let rect = rect_lock.borrow();
+        assert!(rect.is_closed);
+        // flipped so the y axis points down, and moved by the transform
+        assert_eq!(rect.points[0].p, na::Vector2::new(10.0, 100.0));
+        assert_eq!(rect.points[2].p, na::Vector2::new(110.0, 50.0));
+        let style = group.layers[0].style.upgrade().unwrap();
+        assert!(matches!(style.lock().borrow().fill, crate::style::Pattern::Solid(color) if color == Color::new(255, 0, 0, 255)));
+    }
+
+    #[test]
+    fn test_export() {
+        let mut document = Document::new("export".to_owned());
+        let fill = Paint { fill: Some(Color::new(255, 0, 0, 128)), fill_rule: FillRule::EvenOdd, stroke: None, stroke_width: 1.0 };
+        let square = svg::paint_layer(&mut document, "square".to_owned(), vec![crate::make_curve!((10,10) -> (30,10) -> (30,30) -> (10,30) -> cycle)], &fill).unwrap();
+        let stroke = Paint { fill: None, fill_rule: FillRule::NonZero, stroke: Some(Color::BLUE), stroke_width: 4.0 };
+        let line = svg::paint_layer(&mut document, "line".to_owned(), vec![crate::make_curve!((0,40) -> (100,40))], &stroke).unwrap();
+        let text = [
+            ("Title", "Logo (draft)".to_owned()),
+            ("Author", "\u{5f20}".to_owned()),
+            ("Creation Time", "2023-11-14T22:13:20Z".to_owned()),
+            ("Software", "vap".to_owned()),
+            ("Artboard", "Icon".to_owned()),
+        ];
+        let pdf = export([&square, &line], Rectangle::new(0.0, 0.0, 100.0, 50.0), &[], &text);
+
+        let file = String::from_utf8_lossy(&pdf);
+        assert!(file.contains("/Title (Logo \\(draft\\))"), "parentheses should be escaped");
+        assert!(file.contains("/Author <FEFF5F20>"), "non-ASCII text should be UTF-16");
+        assert!(file.contains("/CreationDate (D:20231114221320Z)"));
+        assert!(file.contains("/Creator (vap) /Producer (vap)"));
+        assert!(file.contains("/Artboard (Icon)"));
+        assert!(file.contains("/A128 << /ca 0.5019608 /CA 0.5019608 >>"), "translucent fills need a graphics state");
+        // every cross-reference entry should point at its object
+        let xref = file.rfind("startxref").and_then(|i| file[i + "startxref".len()..].split_whitespace().next()?.parse::<usize>().ok()).unwrap();
+        assert!(file[xref..].starts_with("xref"));
+        for (number, entry) in (1..).zip(file[xref..].lines().skip(3).take(5)) {
+            let offset = entry[..10].parse::<usize>().unwrap();
+            assert!(file[offset..].starts_with(&format!("{number} 0 obj")));
+        }
+
+        let (page_box, content) = read_pdf(&pdf).unwrap();
+        assert_eq!(page_box, [0.0, 0.0, 100.0, 50.0]);
+        let imported = read_artwork("export".to_owned(), page_box, &content);
+        assert_eq!(imported.layers.len(), 2);
+        let LayerContent::Curve(curve) = &imported.layers[0].content else { panic!("the square should be a single curve") };
+        let curve = curve.upgrade().unwrap();
+        let curve_lock = curve.lock();
+        let curve = curve_lock.borrow();
+        assert!(curve.is_closed);
+        assert_eq!(curve.points.iter().map(|point| point.p).collect::<Vec<_>>(), [
+            na::Vector2::new(10.0, 10.0), na::Vector2::new(30.0, 10.0), na::Vector2::new(30.0, 30.0), na::Vector2::new(10.0, 30.0),
+        ]);
+        let style = imported.layers[0].style.upgrade().unwrap();
+        let style_lock = style.lock();
+        let style = style_lock.borrow();
+        assert!(matches!(style.fill, Pattern::Solid(color) if (color.r, color.g, color.b) == (255, 0, 0)));
+        assert_eq!(style.fill_rule, FillRule::EvenOdd);
+        let line_style = imported.layers[1].style.upgrade().unwrap();
+        let line_style_lock = line_style.lock();
+        let line_style = line_style_lock.borrow();
+        assert!(matches!(line_style.fill, Pattern::Solid(color) if color.a == 0));
+        assert_eq!(line_style.stroke.end_width(true), 4.0);
+    }
+
+    #[test]
+    fn test_export_swatch_inks() {
+        let mut document = Document::new("inks".to_owned());
+        let process = Cmyk::new(0.0, 0.5, 1.0, 0.0);
+        let spot = Cmyk::new(1.0, 0.0, 0.0, 0.0);
+        let swatches = [
+            Swatch { name: "Orange".to_owned(), color: SwatchColor::Cmyk(process) },
+            Swatch { name: "Blue".to_owned(), color: SwatchColor::Spot { ink: "PANTONE 300 C".to_owned(), alternate: spot } },
+        ];
+        let paint = Paint { fill: Some(process.to_rgb()), fill_rule: FillRule::NonZero, stroke: Some(spot.to_rgb()), stroke_width: 2.0 };
+        let layer = svg::paint_layer(&mut document, "mark".to_owned(), vec![crate::make_curve!((0,0) -> (10,0) -> (10,10) -> cycle)], &paint).unwrap();
+        let pdf = export([&layer], Rectangle::new(0.0, 0.0, 10.0, 10.0), &swatches, &[]);
+        let file = String::from_utf8_lossy(&pdf);
+        assert!(file.contains("0 0.5 1 0 k\n"), "process colors should be CMYK");
+        assert!(file.contains("/Ink0 CS 1 SCN\n"), "spot colors should be a full tint of their separation");
+        assert!(file.contains("/ColorSpace << /Ink0 [/Separation /PANTONE#20300#20C /DeviceCMYK << /FunctionType 2 /Domain [0 1] /C0 [0 0 0 0] /C1 [1 0 0 0] /N 1 >>] >>"));
+
+        let plain = export([&layer], Rectangle::new(0.0, 0.0, 10.0, 10.0), &[], &[]);
+        let plain = String::from_utf8_lossy(&plain);
+        assert!(plain.contains(" rg\n") && !plain.contains("/ColorSpace"), "colors without a swatch should stay RGB");
+    }
+
+    #[test]
+    fn test_read_eps() {
+        let eps = b"%!PS-Adobe-3.0 EPSF-3.0\n%%BoundingBox: 0 0 100 100\n%%BeginProlog\n/m {moveto} def\n%%EndProlog\n\
+            *u\n0 0 0 1 k\n10 10 m 90 10 L 50 90 L f\n30 30 m 70 30 l 50 60 l f\n*U\n\
+            u\n0.5 setgray 10 10 moveto 90 90 lineto stroke\nU\n%%Trailer\n%%EOF\n";
+        let (page_box, content) = read_eps(eps).unwrap();
+        assert_eq!(page_box, [0.0, 0.0, 100.0, 100.0]);
+        let document = read_artwork("test".to_owned(), page_box, &content);
+        assert_eq!(document.layers.len(), 2);
+        let LayerContent::Compound(compound) = &document.layers[0].content else { panic!("compound path should be one layer") };
+        assert_eq!(compound.subpaths.len(), 2);
+        assert!(matches!(document.layers[1].content, LayerContent::Group(_)));
+        assert!(read_eps(b"not artwork").is_err());
+    }
+}
@@ -0,0 +1,97 @@
+use raylib::prelude::*;
+use crate::layer::Layer;
+
+/// A one-pixel offscreen buffer for finding the layer at a point on the GPU
+///
+/// Each layer's [hit area][`Layer::draw_hit_area`] is drawn in a color encoding its index, so the layer on top
+/// at the point is read back from a single pixel, however complex the outlines are. An alternative to testing
+/// each layer with [`Layer::contains_point`] in turn
+#[derive(Debug)]
+pub struct PickBuffer {
+    /// The pixel the hit areas are drawn to
+    ///
+    /// [`None`] until the first pick
+    target: Option<RenderTexture2D>,
+}
+
+impl PickBuffer {
+    /// Construct a pick buffer without allocating on the GPU
+    pub const fn new() -> Self {
+        Self { target: None }
+    }
+
+    /// The index of the topmost of `layers` whose hit area covers the worldspace `position`
+    ///
+    /// Errors if the buffer can't be created or read back
+    pub fn pick(&mut self, rl: &mut RaylibHandle, thread: &RaylibThread, layers: &[Layer], position: Vector2) -> Result<Option<usize>, String> {
+        let target = match self.target.take() {
+            Some(target) => target,
+            None => rl.load_render_texture(thread, 1, 1)?,
+        };
+        let target = self.target.insert(target);
+        // the center of the pixel is exactly at `position`
+        let camera = Camera2D {
+            offset: Vector2::new(0.5, 0.5),
+            target: position,
+            rotation: 0.0,
+            zoom: 1.0,
+        };
+        {
+            let mut d = rl.begin_texture_mode(thread, target);
+            d.clear_background(Color::BLANK);
+            let mut d = d.begin_mode2D(camera);
+            for (index, layer) in layers.iter().enumerate() {
+                layer.draw_hit_area(&mut d, id_color(index));
+            }
+        }
+        let image = target.texture().load_image()?;
+        Ok(image.get_image_data().first().copied().and_then(color_id))
+    }
+}
+
+/// The color the layer at `index` is drawn with
+///
+/// Transparent black is left for the background, and only the lowest 24 bits of the index fit
+const fn id_color(index: usize) -> Color {
+    let id = index + 1;
+    Color::new((id >> 16) as u8, (id >> 8) as u8, id as u8, 255)
+}
+
+/// The index of the layer drawn with `color`, or [`None`] for the background
+fn color_id(color: Color) -> Option<usize> {
+    if color.a == 0 {
+        return None;
+    }
+    let id = (usize::from(color.r) << 16) | (usize::from(color.g) << 8) | usize::from(color.b);
+    id.checked_sub(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{document::Document, editor::Editor, golden};
+    use super::*;
+
+    #[test]
+    fn test_id_color() {
+        for index in [0, 1, 255, 256, 70_000] {
+            assert_eq!(color_id(id_color(index)), Some(index));
+        }
+        assert_eq!(color_id(Color::BLANK), None);
+    }
+
+    #[test]
+    #[ignore = "opens a window"]
+    fn test_pick_matches_contains_point() {
+        let mut editor = Editor::new_default(Document::new("picking".to_owned()));
+        editor.add_curve_layer("circle".to_owned(), crate::make_curve!((60,40) -> arc(0, 360, 30) -> cycle));
+        editor.add_curve_layer("wave".to_owned(), crate::make_curve!([-50,0](20,100)[50,0]->[-50,0](100,20)[50,0]->[-50,0](180,100)[50,0]));
+        let layers = &editor.document.layers;
+        golden::with_window(|rl, thread| {
+            let mut buffer = PickBuffer::new();
+            for position in [Vector2::new(60.0, 40.0), Vector2::new(100.0, 21.0), Vector2::new(150.0, 10.0), Vector2::new(20.0, 99.0)] {
+                let expected = layers.iter().rposition(|layer| layer.contains_point(position));
+                assert_eq!(buffer.pick(rl, thread, layers, position).unwrap(), expected, "picking at {position:?}");
+            }
+        });
+    }
+}
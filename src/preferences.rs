@@ -0,0 +1,1218 @@
+use std::path::{Path, PathBuf};
+use raylib::prelude::{KeyboardKey::*, *};
+use serde::{Deserialize, Serialize};
+use crate::{brush_tip::TipOptions, calligraphy::Nib, command::EditorAction, document::{Artboard, Document}, editor::Tool, engine::EngineTheme, layer::SampleQuality, stabilizer::StabilizerMode, units::Unit};
+
+/// The folder application data (preferences, library, etc.) is stored in
+///
+/// [`None`] if the home folder can't be determined
+pub fn app_directory() -> Option<PathBuf> {
+    let home = std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE"))?;
+    Some(PathBuf::from(home).join("AmityVectorArt"))
+}
+
+/// (De)serializes a [`KeyboardKey`] as its raylib key code
+mod key_code {
+    use raylib::prelude::*;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(key: &KeyboardKey, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_i32(*key as i32)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<KeyboardKey, D::Error> {
+        let code = i32::deserialize(deserializer)?;
+        key_from_i32(code).ok_or_else(|| D::Error::custom(format!("unknown key code {code}")))
+    }
+}
+
+/// A keyboard shortcut: a key pressed while holding exactly a set of modifiers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyBinding {
+    /// The key that triggers the shortcut
+    #[serde(with = "key_code")]
+    pub key: KeyboardKey,
+
+    /// Whether either control key must be held
+    #[serde(default)]
+    pub ctrl: bool,
+
+    /// Whether either shift key must be held
+    #[serde(default)]
+    pub shift: bool,
+
+    /// Whether either alt key must be held
+    #[serde(default)]
+    pub alt: bool,
+}
+
+impl KeyBinding {
+    /// Construct a binding to `key` without any modifiers
+    pub const fn new(key: KeyboardKey) -> Self {
+        Self { key, ctrl: false, shift: false, alt: false }
+    }
+
+    /// The same binding, but also requiring control to be held
+    pub const fn ctrl(self) -> Self {
+        Self { ctrl: true, ..self }
+    }
+
+    /// The same binding, but also requiring shift to be held
+    pub const fn shift(self) -> Self {
+        Self { shift: true, ..self }
+    }
+
+    /// The same binding, but also requiring alt to be held
+    pub const fn alt(self) -> Self {
+        Self { alt: true, ..self }
+    }
+
+    /// Whether the key was pressed this frame while holding exactly the binding's modifiers
+    pub fn is_pressed(&self, rl: &RaylibHandle) -> bool {
+        let is_ctrl = rl.is_key_down(KEY_LEFT_CONTROL) || rl.is_key_down(KEY_RIGHT_CONTROL);
+        let is_shift = rl.is_key_down(KEY_LEFT_SHIFT) || rl.is_key_down(KEY_RIGHT_SHIFT);
+        let is_alt = rl.is_key_down(KEY_LEFT_ALT) || rl.is_key_down(KEY_RIGHT_ALT);
+        rl.is_key_pressed(self.key) && is_ctrl == self.ctrl && is_shift == self.shift && is_alt == self.alt
+    }
+
+    /// Get the display text of the binding, like `Ctrl+Shift+E`
+    pub fn label(&self) -> String {
+        let key = format!("{:?}", self.key);
+        let key = key.strip_prefix("KEY_").unwrap_or(&key);
+        let mut label = String::new();
+        for (is_held, modifier) in [(self.ctrl, "Ctrl+"), (self.shift, "Shift+"), (self.alt, "Alt+")] {
+            if is_held {
+                label.push_str(modifier);
+            }
+        }
+        label.push_str(key);
+        label
+    }
+}
+
+/// The keyboard shortcut of every rebindable action
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Keymap {
+    pub point_select: KeyBinding,
+    pub select: KeyBinding,
+    pub raster_brush: KeyBinding,
+    pub vector_brush: KeyBinding,
+    pub copy: KeyBinding,
+    pub paste: KeyBinding,
+    pub toggle_animation: KeyBinding,
+    pub previous_frame: KeyBinding,
+    pub next_frame: KeyBinding,
+    pub play_pause: KeyBinding,
+    pub export_frames: KeyBinding,
+    pub toggle_library: KeyBinding,
+    pub save_snippet: KeyBinding,
+    pub define_graphic_style: KeyBinding,
+    pub apply_graphic_style: [KeyBinding; EditorAction::GRAPHIC_STYLE_SHORTCUTS],
+    pub redefine_graphic_style: [KeyBinding; EditorAction::GRAPHIC_STYLE_SHORTCUTS],
+    pub document_info: KeyBinding,
+    pub preferences: KeyBinding,
+    pub trim_rasters: KeyBinding,
+    pub toggle_style_panel: KeyBinding,
+    pub compare_with_saved: KeyBinding,
+    pub cycle_artboard_background: KeyBinding,
+    pub rename_document: KeyBinding,
+    pub command_palette: KeyBinding,
+    pub decrease_brush_size: KeyBinding,
+    pub increase_brush_size: KeyBinding,
+    pub swap_fill_stroke: KeyBinding,
+    pub reset_fill_stroke: KeyBinding,
+    pub clear_color: KeyBinding,
+    pub toggle_navigator: KeyBinding,
+    pub toggle_layer_panel: KeyBinding,
+    pub export_last_settings: KeyBinding,
+    pub export: KeyBinding,
+    pub rename_artboards: KeyBinding,
+    pub toggle_smart_guides: KeyBinding,
+    pub toggle_eraser: KeyBinding,
+    pub decrease_eraser_hardness: KeyBinding,
+    pub increase_eraser_hardness: KeyBinding,
+    pub cycle_pixel_snap: KeyBinding,
+    pub simplify: KeyBinding,
+    pub make_compound_path: KeyBinding,
+    pub release_compound_path: KeyBinding,
+    pub lasso: KeyBinding,
+    pub magic_wand: KeyBinding,
+    pub scatter_along_path: KeyBinding,
+    pub toggle_profiler: KeyBinding,
+    pub save_trace: KeyBinding,
+    pub select_next_layer: KeyBinding,
+    pub select_previous_layer: KeyBinding,
+    pub scale_selection: KeyBinding,
+    pub toggle_width_panel: KeyBinding,
+    pub export_palette: KeyBinding,
+    pub copy_as_png: KeyBinding,
+    pub copy_as_svg: KeyBinding,
+    pub toggle_artboard_coordinates: KeyBinding,
+    pub toggle_artboard_panel: KeyBinding,
+    pub arrange_artboards: KeyBinding,
+    pub mark_as_asset: KeyBinding,
+    pub export_all_assets: KeyBinding,
+    pub connect: KeyBinding,
+    pub toggle_connector_routing: KeyBinding,
+    pub rectangle: KeyBinding,
+    pub repair_layers: KeyBinding,
+    pub save_view: KeyBinding,
+    pub next_view: KeyBinding,
+    pub previous_view: KeyBinding,
+    pub toggle_annotations: KeyBinding,
+    pub add_note: KeyBinding,
+    pub add_arrow: KeyBinding,
+    pub toggle_markup: KeyBinding,
+    pub remove_annotation: KeyBinding,
+    pub grid_repeat: KeyBinding,
+    pub scale_document: KeyBinding,
+    pub toggle_trim_view: KeyBinding,
+    pub make_template: KeyBinding,
+    pub release_templates: KeyBinding,
+    pub bucket: KeyBinding,
+}
+
+impl Default for Keymap {
+    #[inline]
+    fn default() -> Self {
+        Self::default_keymap()
+    }
+}
+
+impl Keymap {
+    /// The keymap used when the user hasn't customized it
+    pub const fn default_keymap() -> Self {
+        Self {
+            point_select: KeyBinding::new(KEY_P),
+            select: KeyBinding::new(KEY_V),
+            raster_brush: KeyBinding::new(KEY_B),
+            vector_brush: KeyBinding::new(KEY_B).shift(),
+            copy: KeyBinding::new(KEY_C).ctrl(),
+            paste: KeyBinding::new(KEY_V).ctrl(),
+            toggle_animation: KeyBinding::new(KEY_F5),
+            previous_frame: KeyBinding::new(KEY_COMMA),
+            next_frame: KeyBinding::new(KEY_PERIOD),
+            play_pause: KeyBinding::new(KEY_SLASH),
+            export_frames: KeyBinding::new(KEY_E).ctrl().shift(),
+            toggle_library: KeyBinding::new(KEY_F7),
+            save_snippet: KeyBinding::new(KEY_L).ctrl().shift(),
+            define_graphic_style: KeyBinding::new(KEY_G).ctrl().shift(),
+            apply_graphic_style: [
+                KeyBinding::new(KEY_ONE).alt(),
+                KeyBinding::new(KEY_TWO).alt(),
+                KeyBinding::new(KEY_THREE).alt(),
+                KeyBinding::new(KEY_FOUR).alt(),
+                KeyBinding::new(KEY_FIVE).alt(),
+                KeyBinding::new(KEY_SIX).alt(),
+                KeyBinding::new(KEY_SEVEN).alt(),
+                KeyBinding::new(KEY_EIGHT).alt(),
+                KeyBinding::new(KEY_NINE).alt(),
+            ],
+            redefine_graphic_style: [
+                KeyBinding::new(KEY_ONE).alt().shift(),
+                KeyBinding::new(KEY_TWO).alt().shift(),
+                KeyBinding::new(KEY_THREE).alt().shift(),
+                KeyBinding::new(KEY_FOUR).alt().shift(),
+                KeyBinding::new(KEY_FIVE).alt().shift(),
+                KeyBinding::new(KEY_SIX).alt().shift(),
+                KeyBinding::new(KEY_SEVEN).alt().shift(),
+                KeyBinding::new(KEY_EIGHT).alt().shift(),
+                KeyBinding::new(KEY_NINE).alt().shift(),
+            ],
+            document_info: KeyBinding::new(KEY_I).ctrl(),
+            preferences: KeyBinding::new(KEY_COMMA).ctrl(),
+            trim_rasters: KeyBinding::new(KEY_T).ctrl().shift(),
+            toggle_style_panel: KeyBinding::new(KEY_F6),
+            compare_with_saved: KeyBinding::new(KEY_D).ctrl().shift(),
+            cycle_artboard_background: KeyBinding::new(KEY_B).ctrl().shift(),
+            rename_document: KeyBinding::new(KEY_F2),
+            command_palette: KeyBinding::new(KEY_K).ctrl(),
+            decrease_brush_size: KeyBinding::new(KEY_LEFT_BRACKET),
+            increase_brush_size: KeyBinding::new(KEY_RIGHT_BRACKET),
+            swap_fill_stroke: KeyBinding::new(KEY_X),
+            reset_fill_stroke: KeyBinding::new(KEY_D),
+            clear_color: KeyBinding::new(KEY_BACKSLASH),
+            toggle_navigator: KeyBinding::new(KEY_F8),
+            toggle_layer_panel: KeyBinding::new(KEY_F10),
+            export_last_settings: KeyBinding::new(KEY_E).ctrl(),
+            export: KeyBinding::new(KEY_E).ctrl().alt(),
+            rename_artboards: KeyBinding::new(KEY_F2).shift(),
+            toggle_smart_guides: KeyBinding::new(KEY_U).ctrl(),
+            toggle_eraser: KeyBinding::new(KEY_E),
+            decrease_eraser_hardness: KeyBinding::new(KEY_LEFT_BRACKET).shift(),
+            increase_eraser_hardness: KeyBinding::new(KEY_RIGHT_BRACKET).shift(),
+            cycle_pixel_snap: KeyBinding::new(KEY_P).ctrl().alt(),
+            simplify: KeyBinding::new(KEY_S).ctrl().alt(),
+            make_compound_path: KeyBinding::new(KEY_EIGHT).ctrl(),
+            release_compound_path: KeyBinding::new(KEY_EIGHT).ctrl().alt().shift(),
+            lasso: KeyBinding::new(KEY_Q),
+            magic_wand: KeyBinding::new(KEY_Y),
+            scatter_along_path: KeyBinding::new(KEY_D).ctrl().alt(),
+            toggle_profiler: KeyBinding::new(KEY_F12),
+            save_trace: KeyBinding::new(KEY_F12).ctrl(),
+            select_next_layer: KeyBinding::new(KEY_TAB),
+            select_previous_layer: KeyBinding::new(KEY_TAB).shift(),
+            scale_selection: KeyBinding::new(KEY_S).ctrl().shift(),
+            toggle_width_panel: KeyBinding::new(KEY_F6).shift(),
+            export_palette: KeyBinding::new(KEY_E).ctrl().alt().shift(),
+            copy_as_png: KeyBinding::new(KEY_C).ctrl().shift(),
+            copy_as_svg: KeyBinding::new(KEY_C).ctrl().alt(),
+            toggle_artboard_coordinates: KeyBinding::new(KEY_R).ctrl().alt(),
+            toggle_artboard_panel: KeyBinding::new(KEY_F9),
+            arrange_artboards: KeyBinding::new(KEY_F9).shift(),
+            mark_as_asset: KeyBinding::new(KEY_A).ctrl().shift(),
+            export_all_assets: KeyBinding::new(KEY_A).ctrl().alt().shift(),
+            connect: KeyBinding::new(KEY_K).ctrl().shift(),
+            toggle_connector_routing: KeyBinding::new(KEY_K).ctrl().alt(),
+            rectangle: KeyBinding::new(KEY_M),
+            repair_layers: KeyBinding::new(KEY_R).ctrl().alt().shift(),
+            save_view: KeyBinding::new(KEY_V).ctrl().alt(),
+            next_view: KeyBinding::new(KEY_APOSTROPHE),
+            previous_view: KeyBinding::new(KEY_APOSTROPHE).shift(),
+            toggle_annotations: KeyBinding::new(KEY_N).ctrl().alt(),
+            add_note: KeyBinding::new(KEY_N).ctrl().shift(),
+            add_arrow: KeyBinding::new(KEY_N).alt().shift(),
+            toggle_markup: KeyBinding::new(KEY_N).alt(),
+            remove_annotation: KeyBinding::new(KEY_N).ctrl().alt().shift(),
+            grid_repeat: KeyBinding::new(KEY_G).ctrl().alt(),
+            scale_document: KeyBinding::new(KEY_S).ctrl().alt().shift(),
+            toggle_trim_view: KeyBinding::new(KEY_H).ctrl().shift(),
+            make_template: KeyBinding::new(KEY_T).ctrl().alt(),
+            release_templates: KeyBinding::new(KEY_T).ctrl().alt().shift(),
+            bucket: KeyBinding::new(KEY_K),
+        }
+    }
+
+    /// Get the binding of `action`
+    ///
+    /// Panics if `action` is a graphic style shortcut at or past [`EditorAction::GRAPHIC_STYLE_SHORTCUTS`]
+    pub const fn binding(&self, action: EditorAction) -> KeyBinding {
+        match action {
+            EditorAction::PointSelect => self.point_select,
+            EditorAction::Select => self.select,
+            EditorAction::RasterBrush => self.raster_brush,
+            EditorAction::VectorBrush => self.vector_brush,
+            EditorAction::Copy => self.copy,
+            EditorAction::Paste => self.paste,
+            EditorAction::ToggleAnimation => self.toggle_animation,
+            EditorAction::PreviousFrame => self.previous_frame,
+            EditorAction::NextFrame => self.next_frame,
+            EditorAction::PlayPause => self.play_pause,
+            EditorAction::ExportFrames => self.export_frames,
+            EditorAction::ToggleLibrary => self.toggle_library,
+            EditorAction::SaveSnippet => self.save_snippet,
+            EditorAction::DefineGraphicStyle => self.define_graphic_style,
+            EditorAction::ApplyGraphicStyle(index) => self.apply_graphic_style[index as usize],
+            EditorAction::RedefineGraphicStyle(index) => self.redefine_graphic_style[index as usize],
+            EditorAction::DocumentInfo => self.document_info,
+            EditorAction::Preferences => self.preferences,
+            EditorAction::TrimRasters => self.trim_rasters,
+            EditorAction::ToggleStylePanel => self.toggle_style_panel,
+            EditorAction::CompareWithSaved => self.compare_with_saved,
+            EditorAction::CycleArtboardBackground => self.cycle_artboard_background,
+            EditorAction::RenameDocument => self.rename_document,
+            EditorAction::CommandPalette => self.command_palette,
+            EditorAction::DecreaseBrushSize => self.decrease_brush_size,
+            EditorAction::IncreaseBrushSize => self.increase_brush_size,
+            EditorAction::SwapFillStroke => self.swap_fill_stroke,
+            EditorAction::ResetFillStroke => self.reset_fill_stroke,
+            EditorAction::ClearColor => self.clear_color,
+            EditorAction::ToggleNavigator => self.toggle_navigator,
+            EditorAction::ToggleLayerPanel => self.toggle_layer_panel,
+            EditorAction::ExportLastSettings => self.export_last_settings,
+            EditorAction::Export => self.export,
+            EditorAction::RenameArtboards => self.rename_artboards,
+            EditorAction::ToggleSmartGuides => self.toggle_smart_guides,
+            EditorAction::ToggleEraser => self.toggle_eraser,
+            EditorAction::DecreaseEraserHardness => self.decrease_eraser_hardness,
+            EditorAction::IncreaseEraserHardness => self.increase_eraser_hardness,
+            EditorAction::CyclePixelSnap => self.cycle_pixel_snap,
+            EditorAction::Simplify => self.simplify,
+            EditorAction::MakeCompoundPath => self.make_compound_path,
+            EditorAction::ReleaseCompoundPath => self.release_compound_path,
+            EditorAction::Lasso => self.lasso,
+            EditorAction::MagicWand => self.magic_wand,
+            EditorAction::ScatterAlongPath => self.scatter_along_path,
+            EditorAction::ToggleProfiler => self.toggle_profiler,
+            EditorAction::SaveTrace => self.save_trace,
+            EditorAction::SelectNextLayer => self.select_next_layer,
+            EditorAction::SelectPreviousLayer => self.select_previous_layer,
+            EditorAction::ScaleSelection => self.scale_selection,
+            EditorAction::ToggleWidthPanel => self.toggle_width_panel,
+            EditorAction::ExportPalette => self.export_palette,
+            EditorAction::CopyAsPng => self.copy_as_png,
+            EditorAction::CopyAsSvg => self.copy_as_svg,
+            EditorAction::ToggleArtboardCoordinates => self.toggle_artboard_coordinates,
+            EditorAction::ToggleArtboardPanel => self.toggle_artboard_panel,
+            EditorAction::ArrangeArtboards => self.arrange_artboards,
+            EditorAction::MarkAsAsset => self.mark_as_asset,
+            EditorAction::ExportAllAssets => self.export_all_assets,
+            EditorAction::Connect => self.connect,
+            EditorAction::ToggleConnectorRouting => self.toggle_connector_routing,
+            EditorAction::Rectangle => self.rectangle,
+            EditorAction::RepairLayers => self.repair_layers,
+            EditorAction::SaveView => self.save_view,
+            EditorAction::NextView => self.next_view,
+            EditorAction::PreviousView => self.previous_view,
+            EditorAction::ToggleAnnotations => self.toggle_annotations,
+            EditorAction::AddNote => self.add_note,
+            EditorAction::AddArrow => self.add_arrow,
+            EditorAction::ToggleMarkup => self.toggle_markup,
+            EditorAction::RemoveAnnotation => self.remove_annotation,
+            EditorAction::GridRepeat => self.grid_repeat,
+            EditorAction::ScaleDocument => self.scale_document,
+            EditorAction::ToggleTrimView => self.toggle_trim_view,
+            EditorAction::MakeTemplate => self.make_template,
+            EditorAction::ReleaseTemplates => self.release_templates,
+            EditorAction::Bucket => self.bucket,
+        }
+    }
+
+    /// Get the binding of `action` to change it
+    ///
+    /// Panics if `action` is a graphic style shortcut at or past [`EditorAction::GRAPHIC_STYLE_SHORTCUTS`]
+    pub const fn binding_mut(&mut self, action: EditorAction) -> &mut KeyBinding {
+        match action {
+            EditorAction::PointSelect => &mut self.point_select,
+            EditorAction::Select => &mut self.select,
+            EditorAction::RasterBrush => &mut self.raster_brush,
+            EditorAction::VectorBrush => &mut self.vector_brush,
+            EditorAction::Copy => &mut self.copy,
+            EditorAction::Paste => &mut self.paste,
+            EditorAction::ToggleAnimation => &mut self.toggle_animation,
+            EditorAction::PreviousFrame => &mut self.previous_frame,
+            EditorAction::NextFrame => &mut self.next_frame,
+            EditorAction::PlayPause => &mut self.play_pause,
+            EditorAction::ExportFrames => &mut self.export_frames,
+            EditorAction::ToggleLibrary => &mut self.toggle_library,
+            EditorAction::SaveSnippet => &mut self.save_snippet,
+            EditorAction::DefineGraphicStyle => &mut self.define_graphic_style,
+            EditorAction::ApplyGraphicStyle(index) => &mut self.apply_graphic_style[index as usize],
+            EditorAction::RedefineGraphicStyle(index) => &mut self.redefine_graphic_style[index as usize],
+            EditorAction::DocumentInfo => &mut self.document_info,
+            EditorAction::Preferences => &mut self.preferences,
+            EditorAction::TrimRasters => &mut self.trim_rasters,
+            EditorAction::ToggleStylePanel => &mut self.toggle_style_panel,
+            EditorAction::CompareWithSaved => &mut self.compare_with_saved,
+            EditorAction::CycleArtboardBackground => &mut self.cycle_artboard_background,
+            EditorAction::RenameDocument => &mut self.rename_document,
+            EditorAction::CommandPalette => &mut self.command_palette,
+            EditorAction::DecreaseBrushSize => &mut self.decrease_brush_size,
+            EditorAction::IncreaseBrushSize => &mut self.increase_brush_size,
+            EditorAction::SwapFillStroke => &mut self.swap_fill_stroke,
+            EditorAction::ResetFillStroke => &mut self.reset_fill_stroke,
+            EditorAction::ClearColor => &mut self.clear_color,
+            EditorAction::ToggleNavigator => &mut self.toggle_navigator,
+            EditorAction::ToggleLayerPanel => &mut self.toggle_layer_panel,
+            EditorAction::ExportLastSettings => &mut self.export_last_settings,
+            EditorAction::Export => &mut self.export,
+            EditorAction::RenameArtboards => &mut self.rename_artboards,
+            EditorAction::ToggleSmartGuides => &mut self.toggle_smart_guides,
+            EditorAction::ToggleEraser => &mut self.toggle_eraser,
+            EditorAction::DecreaseEraserHardness => &mut self.decrease_eraser_hardness,
+            EditorAction::IncreaseEraserHardness => &mut self.increase_eraser_hardness,
+            EditorAction::CyclePixelSnap => &mut self.cycle_pixel_snap,
+            EditorAction::Simplify => &mut self.simplify,
+            EditorAction::MakeCompoundPath => &mut self.make_compound_path,
+            EditorAction::ReleaseCompoundPath => &mut self.release_compound_path,
+            EditorAction::Lasso => &mut self.lasso,
+            EditorAction::MagicWand => &mut self.magic_wand,
+            EditorAction::ScatterAlongPath => &mut self.scatter_along_path,
+            EditorAction::ToggleProfiler => &mut self.toggle_profiler,
+            EditorAction::SaveTrace => &mut self.save_trace,
+            EditorAction::SelectNextLayer => &mut self.select_next_layer,
+            EditorAction::SelectPreviousLayer => &mut self.select_previous_layer,
+            EditorAction::ScaleSelection => &mut self.scale_selection,
+            EditorAction::ToggleWidthPanel => &mut self.toggle_width_panel,
+            EditorAction::ExportPalette => &mut self.export_palette,
+            EditorAction::CopyAsPng => &mut self.copy_as_png,
+            EditorAction::CopyAsSvg => &mut self.copy_as_svg,
+            EditorAction::ToggleArtboardCoordinates => &mut self.toggle_artboard_coordinates,
+            EditorAction::ToggleArtboardPanel => &mut self.toggle_artboard_panel,
+            EditorAction::ArrangeArtboards => &mut self.arrange_artboards,
+            EditorAction::MarkAsAsset => &mut self.mark_as_asset,
+            EditorAction::ExportAllAssets => &mut self.export_all_assets,
+            EditorAction::Connect => &mut self.connect,
+            EditorAction::ToggleConnectorRouting => &mut self.toggle_connector_routing,
+            EditorAction::Rectangle => &mut self.rectangle,
+            EditorAction::RepairLayers => &mut self.repair_layers,
+            EditorAction::SaveView => &mut self.save_view,
+            EditorAction::NextView => &mut self.next_view,
+            EditorAction::PreviousView => &mut self.previous_view,
+            EditorAction::ToggleAnnotations => &mut self.toggle_annotations,
+            EditorAction::AddNote => &mut self.add_note,
+            EditorAction::AddArrow => &mut self.add_arrow,
+            EditorAction::ToggleMarkup => &mut self.toggle_markup,
+            EditorAction::RemoveAnnotation => &mut self.remove_annotation,
+            EditorAction::GridRepeat => &mut self.grid_repeat,
+            EditorAction::ScaleDocument => &mut self.scale_document,
+            EditorAction::ToggleTrimView => &mut self.toggle_trim_view,
+            EditorAction::MakeTemplate => &mut self.make_template,
+            EditorAction::ReleaseTemplates => &mut self.release_templates,
+            EditorAction::Bucket => &mut self.bucket,
+        }
+    }
+}
+
+/// The settings new documents are created with
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DocumentPreset {
+    /// Width of the first artboard, in pixels
+    pub width: f32,
+
+    /// Height of the first artboard, in pixels
+    pub height: f32,
+
+    /// The unit measurements are displayed in
+    pub units: Unit,
+
+    /// How many pixels make up an inch
+    pub dpi: f32,
+
+    /// Color of the background in the artboard
+    pub paper_color: Color,
+}
+
+impl Default for DocumentPreset {
+    #[inline]
+    fn default() -> Self {
+        Self::default_preset()
+    }
+}
+
+impl DocumentPreset {
+    /// The preset used when the user hasn't customized it
+    pub const fn default_preset() -> Self {
+        Self {
+            width: 512.0,
+            height: 512.0,
+            units: Unit::Pixels,
+            dpi: 96.0,
+            paper_color: Color::GRAY,
+        }
+    }
+
+    /// Built-in presets offered by the "new document" template menu, after the user's default preset
+    ///
+    /// Each preset is paired with the [localization][`crate::locale`] key of its name
+    pub const TEMPLATES: [(&'static str, Self); 5] = [
+        ("template.icon", Self { width: 64.0, height: 64.0, units: Unit::Pixels, dpi: 96.0, paper_color: Color::BLANK }),
+        ("template.screen", Self { width: 1920.0, height: 1080.0, units: Unit::Pixels, dpi: 96.0, paper_color: Color::WHITE }),
+        ("template.a4", Self { width: 2480.0, height: 3508.0, units: Unit::Millimeters, dpi: 300.0, paper_color: Color::WHITE }),
+        ("template.us_letter", Self { width: 2550.0, height: 3300.0, units: Unit::Inches, dpi: 300.0, paper_color: Color::WHITE }),
+        ("template.transparent", Self { width: 512.0, height: 512.0, units: Unit::Pixels, dpi: 96.0, paper_color: Color::BLANK }),
+    ];
+
+    /// Construct a new document with one artboard, using the preset's settings
+    pub fn create_document(&self, title: String) -> Document {
+        let mut document = Document::new(title);
+        document.info.units = self.units;
+        document.info.dpi = self.dpi;
+        document.paper_color = self.paper_color;
+        document.artboards.push(Artboard::new("artboard 1".to_owned(), Rectangle::new(0.0, 0.0, self.width, self.height)));
+        document
+    }
+}
+
+/// How points snap while drawing and transforming
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SnapPreferences {
+    /// Whether objects being dragged or drawn snap to alignments with other objects and artboards
+    pub is_using_smart_guides: bool,
+}
+
+impl Default for SnapPreferences {
+    #[inline]
+    fn default() -> Self {
+        Self::default_snap_preferences()
+    }
+}
+
+impl SnapPreferences {
+    /// The snapping settings used when the user hasn't customized them
+    pub const fn default_snap_preferences() -> Self {
+        Self {
+            is_using_smart_guides: true,
+        }
+    }
+}
+
+/// How the scroll wheel, trackpad, and zoom limits move the view
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NavigationPreferences {
+    /// Factor each step of the scroll wheel zooms by while alt is held
+    pub zoom_speed: f32,
+
+    /// Screenspace pixels each step of the scroll wheel pans by
+    pub pan_speed: f32,
+
+    /// Most zoomed out the camera can be
+    pub min_zoom: f32,
+
+    /// Most zoomed in the camera can be
+    pub max_zoom: f32,
+
+    /// Whether scrolling up with alt held zooms out instead of in
+    pub is_scroll_inverted: bool,
+
+    /// Whether scrolling pans the artwork along with the fingers on a trackpad, instead of moving the view the way a scroll bar would
+    pub is_natural_trackpad: bool,
+}
+
+impl Default for NavigationPreferences {
+    #[inline]
+    fn default() -> Self {
+        Self::default_navigation_preferences()
+    }
+}
+
+impl NavigationPreferences {
+    /// Smallest zoom speed that can be chosen
+    pub const MIN_ZOOM_SPEED: f32 = 1.05;
+
+    /// Largest zoom speed that can be chosen
+    pub const MAX_ZOOM_SPEED: f32 = 4.0;
+
+    /// Smallest pan speed that can be chosen
+    pub const MIN_PAN_SPEED: f32 = 1.0;
+
+    /// Largest pan speed that can be chosen
+    pub const MAX_PAN_SPEED: f32 = 100.0;
+
+    /// Smallest zoom limit that can be chosen; [`NavigationPreferences::min_zoom`] is at most 100%
+    pub const SMALLEST_MIN_ZOOM: f32 = 0.01;
+
+    /// Largest zoom limit that can be chosen; [`NavigationPreferences::max_zoom`] is at least 100%
+    pub const LARGEST_MAX_ZOOM: f32 = 256.0;
+
+    /// The navigation settings used when the user hasn't customized them
+    pub const fn default_navigation_preferences() -> Self {
+        Self {
+            zoom_speed: 1.5,
+            pan_speed: 20.0,
+            min_zoom: 0.125,
+            max_zoom: 64.0,
+            is_scroll_inverted: false,
+            is_natural_trackpad: false,
+        }
+    }
+
+    /// The most zoomed out and most zoomed in the camera can be, kept within their ranges however the preferences file was edited
+    pub fn zoom_limits(&self) -> (f32, f32) {
+        (
+            self.min_zoom.clamp(Self::SMALLEST_MIN_ZOOM, 1.0),
+            self.max_zoom.clamp(1.0, Self::LARGEST_MAX_ZOOM),
+        )
+    }
+}
+
+/// Where the window was and how big it was when the application last closed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WindowPreferences {
+    /// Name of the monitor the window was on
+    ///
+    /// [`None`] if it couldn't be determined
+    pub monitor: Option<String>,
+
+    /// Position of the window's top-left corner relative to its monitor's, in screen coordinates
+    ///
+    /// [`None`] to center the window
+    pub position: Option<(i32, i32)>,
+
+    /// Width of the window when not maximized, in screen coordinates
+    pub width: i32,
+
+    /// Height of the window when not maximized, in screen coordinates
+    pub height: i32,
+
+    /// Whether the window was maximized
+    pub is_maximized: bool,
+}
+
+impl Default for WindowPreferences {
+    #[inline]
+    fn default() -> Self {
+        Self::default_window_preferences()
+    }
+}
+
+impl WindowPreferences {
+    /// Smallest width or height the window is restored with, so a bad preferences file can't make it vanish
+    pub const MIN_SIZE: i32 = 320;
+
+    /// The window placement used the first time the application opens
+    pub const fn default_window_preferences() -> Self {
+        Self {
+            monitor: None,
+            position: None,
+            width: 1280,
+            height: 720,
+            is_maximized: true,
+        }
+    }
+
+    /// Remember where the window is now
+    ///
+    /// The size and position are only taken while the window is neither maximized nor minimized,
+    /// so they are what un-maximizing the restored window returns to
+    pub fn capture(&mut self, rl: &RaylibHandle) {
+        if rl.is_window_minimized() {
+            return;
+        }
+        let monitor = get_current_monitor();
+        self.monitor = monitor_name(monitor);
+        self.is_maximized = unsafe { raylib::ffi::IsWindowMaximized() };
+        if !self.is_maximized {
+            let position = rl.get_window_position() - get_monitor_position(monitor);
+            self.position = Some((position.x as i32, position.y as i32));
+            self.width = rl.get_screen_width();
+            self.height = rl.get_screen_height();
+        }
+    }
+
+    /// Place the window where it was [captured][`WindowPreferences::capture`], on the same monitor if it's still connected
+    ///
+    /// If that monitor was disconnected, the window is centered on the current monitor instead.
+    /// Either way, the window is kept within the monitor it is placed on
+    pub fn restore(&self, rl: &mut RaylibHandle) {
+        let remembered = self.monitor.as_deref()
+            .and_then(|name| (0..get_monitor_count()).find(|&monitor| monitor_name(monitor).as_deref() == Some(name)));
+        let monitor = remembered.unwrap_or_else(get_current_monitor);
+        if (0..get_monitor_count()).contains(&monitor) {
+            let origin = get_monitor_position(monitor);
+            let (monitor_width, monitor_height) = (get_monitor_width(monitor), get_monitor_height(monitor));
+            let width = self.width.max(Self::MIN_SIZE).min(monitor_width.max(Self::MIN_SIZE));
+            let height = self.height.max(Self::MIN_SIZE).min(monitor_height.max(Self::MIN_SIZE));
+            let (x, y) = match (remembered, self.position) {
+                (Some(_), Some((x, y))) => (x.clamp(0, (monitor_width - width).max(0)), y.clamp(0, (monitor_height - height).max(0))),
+                _ => ((monitor_width - width) / 2, (monitor_height - height) / 2),
+            };
+            rl.set_window_size(width, height);
+            rl.set_window_position(origin.x as i32 + x, origin.y as i32 + y);
+        }
+        if self.is_maximized {
+            rl.set_window_state(WindowState::set_window_maximized(rl.get_window_state(), true));
+        }
+    }
+}
+
+/// The name of `monitor`, for recognizing it after monitors are connected or disconnected
+///
+/// [`None`] if the name isn't available
+fn monitor_name(monitor: i32) -> Option<String> {
+    // raylib owns the name, so it is copied rather than taken
+    let name = unsafe { raylib::ffi::GetMonitorName(monitor) };
+    (!name.is_null()).then(|| unsafe { std::ffi::CStr::from_ptr(name) }.to_string_lossy().into_owned())
+}
+
+/// How transparency is shown behind artboards
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CheckerboardPreferences {
+    /// Width and height of each square, in screenspace pixels
+    pub square_size: f32,
+
+    /// The colors the squares alternate between, starting from the top-left corner
+    pub colors: [Color; 2],
+}
+
+impl Default for CheckerboardPreferences {
+    #[inline]
+    fn default() -> Self {
+        Self::default_checkerboard_preferences()
+    }
+}
+
+impl CheckerboardPreferences {
+    /// The checkerboard used when the user hasn't customized it
+    pub const fn default_checkerboard_preferences() -> Self {
+        Self {
+            square_size: 8.0,
+            colors: [Color::WHITE, Color::LIGHTGRAY],
+        }
+    }
+}
+
+/// Brush sizes, remembered separately for each brush tool
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BrushPreferences {
+    /// Diameter of the vector brush, in worldspace pixels
+    pub vector_size: f32,
+
+    /// Diameter of the raster brush, in worldspace pixels
+    pub raster_size: f32,
+
+    /// Fraction of the raster eraser's radius that erases completely, from 0 (soft) to 1 (hard)
+    pub eraser_hardness: f32,
+
+    /// How brush input is smoothed
+    pub stabilizer: StabilizerMode,
+
+    /// How much brush input is smoothed, from 0 to 1
+    pub stabilizer_strength: f32,
+
+    /// The nib of the vector brush in calligraphy mode
+    pub calligraphy: Nib,
+
+    /// Whether holding the vector brush still at the end of a stroke replaces the stroke with the [shape][`crate::recognize::Shape`] it looks like
+    pub is_recognizing_shapes: bool,
+
+    /// The name of the [tip][`crate::brush_tip::BrushTip`] the raster brush stamps
+    ///
+    /// [`None`] for a plain round brush
+    pub tip: Option<String>,
+
+    /// How the raster brush's tip is stamped along strokes
+    pub tip_options: TipOptions,
+}
+
+impl Default for BrushPreferences {
+    #[inline]
+    fn default() -> Self {
+        Self::default_brush_preferences()
+    }
+}
+
+impl BrushPreferences {
+    /// Smallest allowed brush diameter
+    pub const MIN_SIZE: f32 = 1.0;
+
+    /// Largest allowed brush diameter
+    pub const MAX_SIZE: f32 = 1000.0;
+
+    /// Factor the brush size keys shrink or grow the brush by
+    pub const SIZE_STEP: f32 = 1.25;
+
+    /// Amount the eraser hardness keys change the hardness by
+    pub const HARDNESS_STEP: f32 = 0.25;
+
+    /// The brush sizes used when the user hasn't customized them
+    pub const fn default_brush_preferences() -> Self {
+        Self {
+            vector_size: 4.0,
+            raster_size: 16.0,
+            eraser_hardness: 1.0,
+            stabilizer: StabilizerMode::Off,
+            stabilizer_strength: 0.5,
+            calligraphy: Nib::new(),
+            is_recognizing_shapes: false,
+            tip: None,
+            tip_options: TipOptions::new(),
+        }
+    }
+
+    /// The diameter of `tool`'s brush
+    ///
+    /// [`None`] if `tool` isn't a brush
+    pub const fn size(&self, tool: Tool) -> Option<f32> {
+        match tool {
+            Tool::VectorBrush => Some(self.vector_size),
+            Tool::RasterBrush => Some(self.raster_size),
+            Tool::PointSelect | Tool::Select | Tool::VectorPen | Tool::Lasso | Tool::MagicWand | Tool::Rectangle | Tool::Bucket => None,
+        }
+    }
+
+    /// Set the diameter of `tool`'s brush, clamped to the allowed range
+    ///
+    /// Does nothing if `tool` isn't a brush
+    pub fn set_size(&mut self, tool: Tool, size: f32) {
+        let size = size.clamp(Self::MIN_SIZE, Self::MAX_SIZE);
+        match tool {
+            Tool::VectorBrush => self.vector_size = size,
+            Tool::RasterBrush => self.raster_size = size,
+            Tool::PointSelect | Tool::Select | Tool::VectorPen | Tool::Lasso | Tool::MagicWand | Tool::Rectangle | Tool::Bucket => {}
+        }
+    }
+
+    /// Set the eraser hardness, clamped between 0 and 1
+    pub fn set_eraser_hardness(&mut self, hardness: f32) {
+        self.eraser_hardness = hardness.clamp(0.0, 1.0);
+    }
+}
+
+/// Settings of the magic wand tool
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MagicWandPreferences {
+    /// How far (0 to 255) each channel of a fill color may be from the clicked fill's to be selected with it
+    pub tolerance: f32,
+}
+
+impl Default for MagicWandPreferences {
+    #[inline]
+    fn default() -> Self {
+        Self::default_magic_wand_preferences()
+    }
+}
+
+impl MagicWandPreferences {
+    /// Largest allowed tolerance, at which every fill color matches
+    pub const MAX_TOLERANCE: f32 = 255.0;
+
+    /// The magic wand settings used when the user hasn't customized them
+    pub const fn default_magic_wand_preferences() -> Self {
+        Self {
+            tolerance: 32.0,
+        }
+    }
+}
+
+/// Settings of the bucket tool
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BucketPreferences {
+    /// Widest gap between paths, in worldspace pixels, that the bucket treats as closed
+    pub gap: f32,
+}
+
+impl Default for BucketPreferences {
+    #[inline]
+    fn default() -> Self {
+        Self::default_bucket_preferences()
+    }
+}
+
+impl BucketPreferences {
+    /// Largest allowed gap
+    pub const MAX_GAP: f32 = 100.0;
+
+    /// The bucket settings used when the user hasn't customized them
+    pub const fn default_bucket_preferences() -> Self {
+        Self {
+            gap: 2.0,
+        }
+    }
+}
+
+/// Settings of transforming the selection
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TransformPreferences {
+    /// Whether scaling artwork also scales its stroke widths, or keeps them as they are
+    pub is_scaling_strokes: bool,
+}
+
+impl Default for TransformPreferences {
+    #[inline]
+    fn default() -> Self {
+        Self::default_transform_preferences()
+    }
+}
+
+impl TransformPreferences {
+    /// The transform settings used when the user hasn't customized them
+    pub const fn default_transform_preferences() -> Self {
+        Self {
+            is_scaling_strokes: true,
+        }
+    }
+}
+
+/// Settings of the coordinates shown in the rulers and status bar
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CoordinatePreferences {
+    /// Whether coordinates are measured from the top-left of the editor's [active artboard][`crate::editor::Editor::active_artboard`]
+    /// rather than from the worldspace origin
+    pub is_artboard_relative: bool,
+}
+
+impl Default for CoordinatePreferences {
+    #[inline]
+    fn default() -> Self {
+        Self::default_coordinate_preferences()
+    }
+}
+
+impl CoordinatePreferences {
+    /// The coordinate settings used when the user hasn't customized them
+    pub const fn default_coordinate_preferences() -> Self {
+        Self {
+            is_artboard_relative: false,
+        }
+    }
+}
+
+/// Settings for users who have difficulty telling the default theme's colors apart
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AccessibilityPreferences {
+    /// Whether the colors below replace the theme's accent and destructive colors
+    pub is_color_blind_safe: bool,
+
+    /// Replaces [`EngineTheme::color_accent`], used for selections
+    pub accent: Color,
+
+    /// Replaces [`EngineTheme::color_destructive`] and [`EngineTheme::color_irreversible`]
+    pub destructive: Color,
+
+    /// Width of selection outlines while color-blind safe colors are used, in screenspace pixels
+    pub selection_thickness: f32,
+}
+
+impl Default for AccessibilityPreferences {
+    #[inline]
+    fn default() -> Self {
+        Self::default_accessibility_preferences()
+    }
+}
+
+impl AccessibilityPreferences {
+    /// Thinnest allowed [`AccessibilityPreferences::selection_thickness`]
+    pub const MIN_SELECTION_THICKNESS: f32 = 1.0;
+
+    /// Thickest allowed [`AccessibilityPreferences::selection_thickness`]
+    pub const MAX_SELECTION_THICKNESS: f32 = 6.0;
+
+    /// The accessibility settings used when the user hasn't customized them
+    ///
+    /// The colors are the blue and orange of the Okabe-Ito palette, which stay distinct under the common forms of color blindness
+    pub const fn default_accessibility_preferences() -> Self {
+        Self {
+            is_color_blind_safe: false,
+            accent: Color::new(0, 114, 178, 255),
+            destructive: Color::new(230, 159, 0, 255),
+            selection_thickness: 3.0,
+        }
+    }
+}
+
+/// How often the window is redrawn
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DisplayPreferences {
+    /// Whether frames wait for the monitor to finish showing the previous one, which prevents tearing
+    pub is_vsync: bool,
+
+    /// Most frames drawn per second
+    ///
+    /// 0 leaves the frame rate uncapped. Ignored while [`DisplayPreferences::is_power_saving`]
+    pub target_fps: u32,
+
+    /// Whether frames are uncapped while the user is interacting and capped at
+    /// [`DisplayPreferences::idle_fps`] while only something like playback or an export is changing
+    ///
+    /// Either way, drawing stops entirely once nothing is changing
+    pub is_power_saving: bool,
+
+    /// Most frames drawn per second while power saving and not interacting
+    pub idle_fps: u32,
+
+    /// Whether clicking finds layers by drawing them to an offscreen buffer on the GPU,
+    /// rather than testing their outlines one by one
+    pub is_gpu_picking: bool,
+
+    /// Whether artwork is drawn with simplified outlines while zoomed out, keeping large documents fluid to navigate
+    pub is_simplifying_zoomed_out: bool,
+}
+
+impl Default for DisplayPreferences {
+    #[inline]
+    fn default() -> Self {
+        Self::default_display_preferences()
+    }
+}
+
+impl DisplayPreferences {
+    /// Largest frame rate that can be chosen
+    pub const MAX_FPS: u32 = 480;
+
+    /// Smallest frame rate that can be chosen while idle, so playback doesn't stall
+    pub const MIN_IDLE_FPS: u32 = 1;
+
+    /// The display settings used when the user hasn't customized them
+    pub const fn default_display_preferences() -> Self {
+        Self {
+            is_vsync: false,
+            target_fps: 60,
+            is_power_saving: false,
+            idle_fps: 20,
+            is_gpu_picking: false,
+            is_simplifying_zoomed_out: true,
+        }
+    }
+}
+
+/// Which style newly drawn objects are given
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum NewObjectStyle {
+    /// The [document's default style][`Document::default_style`], regardless of what was selected
+    DocumentDefault,
+
+    /// The style of the most recently selected object, so drawing carries on in the same style
+    #[default]
+    LastSelected,
+}
+
+impl NewObjectStyle {
+    /// Every option, in the order they are listed in the UI
+    pub const ALL: [Self; 2] = [Self::DocumentDefault, Self::LastSelected];
+}
+
+/// Application-wide settings, persisted between sessions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Preferences {
+    /// The visual theme of the application, before [`Preferences::ui_scale`] is applied
+    pub theme: EngineTheme,
+
+    /// Multiplier applied to the size of UI text
+    pub ui_scale: f32,
+
+    /// Keyboard shortcuts
+    pub keymap: Keymap,
+
+    /// Minutes between automatically saving documents that already have a file
+    ///
+    /// 0 disables autosave
+    pub autosave_minutes: u32,
+
+    /// Whether documents saved as a single [package][`crate::format::PACKAGE_EXTENSION`] are compressed,
+    /// making them smaller but slower to save
+    pub is_compressing_packages: bool,
+
+    /// The settings new documents are created with
+    pub default_document: DocumentPreset,
+
+    /// How finely curves are sampled in the viewport
+    pub sample_quality: SampleQuality,
+
+    /// Whether new objects are drawn with the document's default style or the last selected one
+    pub new_object_style: NewObjectStyle,
+
+    /// How dragged and drawn artwork snaps
+    pub snapping: SnapPreferences,
+
+    /// Scroll and zoom speeds
+    pub navigation: NavigationPreferences,
+
+    /// Transparency preview settings
+    pub checkerboard: CheckerboardPreferences,
+
+    /// Color-blind safe colors and thicker selections
+    pub accessibility: AccessibilityPreferences,
+
+    /// Brush sizes
+    pub brushes: BrushPreferences,
+
+    /// Magic wand tolerance
+    pub magic_wand: MagicWandPreferences,
+
+    /// Bucket gap size
+    pub bucket: BucketPreferences,
+
+    /// Whether scaling affects stroke widths
+    pub transform: TransformPreferences,
+
+    /// Where coordinates are measured from
+    pub coordinates: CoordinatePreferences,
+
+    /// Frame rate and vsync
+    pub display: DisplayPreferences,
+
+    /// Where the window was left, to open it there again
+    pub window: WindowPreferences,
+
+    /// The language of UI text, matching a file in the [locale directory][`crate::locale::locale_directory`]
+    ///
+    /// [`None`] for the built-in English
+    pub language: Option<String>,
+}
+
+impl Default for Preferences {
+    #[inline]
+    fn default() -> Self {
+        Self::default_preferences()
+    }
+}
+
+impl Preferences {
+    /// Smallest allowed [`Preferences::ui_scale`]
+    pub const MIN_UI_SCALE: f32 = 0.5;
+
+    /// Largest allowed [`Preferences::ui_scale`]
+    pub const MAX_UI_SCALE: f32 = 3.0;
+
+    /// The preferences used when the user hasn't customized them
+    pub const fn default_preferences() -> Self {
+        Self {
+            theme: EngineTheme::default_theme(),
+            ui_scale: 1.0,
+            keymap: Keymap::default_keymap(),
+            autosave_minutes: 0,
+            is_compressing_packages: true,
+            default_document: DocumentPreset::default_preset(),
+            sample_quality: SampleQuality::Medium,
+            new_object_style: NewObjectStyle::LastSelected,
+            snapping: SnapPreferences::default_snap_preferences(),
+            navigation: NavigationPreferences::default_navigation_preferences(),
+            checkerboard: CheckerboardPreferences::default_checkerboard_preferences(),
+            accessibility: AccessibilityPreferences::default_accessibility_preferences(),
+            brushes: BrushPreferences::default_brush_preferences(),
+            magic_wand: MagicWandPreferences::default_magic_wand_preferences(),
+            bucket: BucketPreferences::default_bucket_preferences(),
+            transform: TransformPreferences::default_transform_preferences(),
+            coordinates: CoordinatePreferences::default_coordinate_preferences(),
+            display: DisplayPreferences::default_display_preferences(),
+            window: WindowPreferences::default_window_preferences(),
+            language: None,
+        }
+    }
+
+    /// Where preferences are stored
+    ///
+    /// [`None`] if the home folder can't be determined
+    pub fn config_path() -> Option<PathBuf> {
+        Some(app_directory()?.join("preferences.json"))
+    }
+
+    /// Read preferences from `path`
+    ///
+    /// Settings missing from the file are given their default values
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let json = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        serde_json::from_str(&json).map_err(|e| e.to_string())
+    }
+
+    /// Write preferences to `path`, creating its folder if it doesn't exist
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        if let Some(directory) = path.parent() {
+            std::fs::create_dir_all(directory).map_err(|e| e.to_string())?;
+        }
+        let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::write(path, json).map_err(|e| e.to_string())
+    }
+
+    /// Read preferences from the [config file][`Preferences::config_path`]
+    ///
+    /// Defaults are used if the file doesn't exist or can't be read
+    pub fn load_or_default() -> Self {
+        match Self::config_path() {
+            Some(path) if path.exists() => Self::load(&path)
+                .inspect_err(|e| eprintln!("failed to load preferences: {e}"))
+                .unwrap_or_default(),
+            _ => Self::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_bindings_are_unique() {
+        let keymap = Keymap::default_keymap();
+        for (i, a) in EditorAction::ALL.into_iter().enumerate() {
+            for b in EditorAction::ALL.into_iter().skip(i.saturating_add(1)) {
+                assert_ne!(keymap.binding(a), keymap.binding(b), "{a:?} and {b:?} share a shortcut");
+            }
+        }
+    }
+}
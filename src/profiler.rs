@@ -0,0 +1,225 @@
+use std::{collections::VecDeque, path::Path, sync::{atomic::{AtomicBool, AtomicU64, Ordering}, LazyLock}, time::{Duration, Instant}};
+use parking_lot::Mutex;
+use raylib::prelude::*;
+use serde::Serialize;
+use crate::engine::EngineTheme;
+
+/// Whether spans are being recorded
+///
+/// Checked before anything else, so spans cost a single atomic load while profiling is off
+static IS_ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// The recorded spans, shared by every thread
+static PROFILER: LazyLock<Mutex<Profiler>> = LazyLock::new(|| Mutex::new(Profiler::new()));
+
+/// Source of the numbers identifying each thread in traces
+static NEXT_THREAD: AtomicU64 = AtomicU64::new(0);
+
+thread_local! {
+    /// The number identifying the current thread in traces
+    static THREAD: u64 = NEXT_THREAD.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Number of frames kept for [writing a trace][`write_trace`], about ten seconds at 60fps
+const TRACE_FRAMES: usize = 600;
+
+/// A finished span
+#[derive(Debug, Clone, Copy)]
+struct Record {
+    name: &'static str,
+    thread: u64,
+    /// Time since [`Profiler::epoch`]
+    start: Duration,
+    duration: Duration,
+}
+
+/// The time spent in one kind of span over a frame
+#[derive(Debug, Clone, Copy)]
+struct Total {
+    name: &'static str,
+    duration: Duration,
+    count: usize,
+}
+
+#[derive(Debug)]
+struct Profiler {
+    /// When profiling was started, which trace timestamps count from
+    epoch: Instant,
+
+    /// Spans finished during the current frame
+    current: Vec<Record>,
+
+    /// When the current frame started
+    frame_start: Instant,
+
+    /// The spans of the most recent frames, oldest first
+    frames: VecDeque<Vec<Record>>,
+
+    /// How long the last frame took, and the time each kind of span took during it, longest first
+    summary: (Duration, Vec<Total>),
+}
+
+impl Profiler {
+    fn new() -> Self {
+        let now = Instant::now();
+        Self {
+            epoch: now,
+            current: Vec::new(),
+            frame_start: now,
+            frames: VecDeque::new(),
+            summary: (Duration::ZERO, Vec::new()),
+        }
+    }
+}
+
+/// Times the scope it lives in, recording it as a span once dropped; see [`span`]
+#[must_use = "the span ends as soon as it is dropped"]
+pub struct Span {
+    name: &'static str,
+    /// [`None`] if profiling was off when the span started
+    start: Option<Instant>,
+}
+
+impl Drop for Span {
+    fn drop(&mut self) {
+        let Some(start) = self.start else { return };
+        let duration = start.elapsed();
+        let thread = THREAD.with(|&thread| thread);
+        let mut profiler = PROFILER.lock();
+        let record = Record {
+            name: self.name,
+            thread,
+            start: start.saturating_duration_since(profiler.epoch),
+            duration,
+        };
+        profiler.current.push(record);
+    }
+}
+
+/// Start timing a span named `name`, ending when the returned guard is dropped
+///
+/// Spans may nest and may be started on any thread. While profiling is off, does nothing
+///
+/// Spans around drawing measure the time spent queueing draw calls, not the time the GPU spends on them
+#[inline]
+pub fn span(name: &'static str) -> Span {
+    Span {
+        name,
+        start: IS_ENABLED.load(Ordering::Relaxed).then(Instant::now),
+    }
+}
+
+/// Whether spans are being recorded
+#[inline]
+pub fn is_enabled() -> bool {
+    IS_ENABLED.load(Ordering::Relaxed)
+}
+
+/// Start or stop recording spans, forgetting everything recorded so far when starting
+pub fn set_enabled(is_enabled: bool) {
+    if is_enabled && !self::is_enabled() {
+        *PROFILER.lock() = Profiler::new();
+    }
+    IS_ENABLED.store(is_enabled, Ordering::Relaxed);
+}
+
+/// Close the current frame, summarizing it for the [overlay][`draw_overlay`] and keeping it for [traces][`write_trace`]
+///
+/// Call once per frame. Does nothing while profiling is off
+pub fn end_frame() {
+    if !is_enabled() {
+        return;
+    }
+    let mut profiler = PROFILER.lock();
+    let now = Instant::now();
+    let frame_time = now.saturating_duration_since(profiler.frame_start);
+    profiler.frame_start = now;
+
+    let records = std::mem::take(&mut profiler.current);
+    let mut totals = Vec::<Total>::new();
+    for record in &records {
+        match totals.iter_mut().find(|total| total.name == record.name) {
+            Some(total) => {
+                total.duration = total.duration.saturating_add(record.duration);
+                total.count = total.count.saturating_add(1);
+            }
+            None => totals.push(Total { name: record.name, duration: record.duration, count: 1 }),
+        }
+    }
+    totals.sort_by(|a, b| b.duration.cmp(&a.duration));
+    profiler.summary = (frame_time, totals);
+
+    if profiler.frames.len() >= TRACE_FRAMES {
+        profiler.frames.pop_front();
+    }
+    profiler.frames.push_back(records);
+}
+
+/// Draw the last frame's time and the time spent in each kind of span, longest first, from `position` down
+///
+/// Does nothing while profiling is off
+pub fn draw_overlay(d: &mut impl RaylibDraw, theme: &EngineTheme, position: Vector2) {
+    if !is_enabled() {
+        return;
+    }
+    const PADDING: f32 = 4.0;
+    const WIDTH: f32 = 220.0;
+
+    let profiler = PROFILER.lock();
+    let (frame_time, totals) = &profiler.summary;
+    let lines = std::iter::once(format!("frame {:.2}ms", frame_time.as_secs_f64() * 1000.0))
+        .chain(totals.iter().map(|total| format!("{} {:.2}ms x{}", total.name, total.duration.as_secs_f64() * 1000.0, total.count)))
+        .collect::<Vec<_>>();
+    let line_height = theme.font_size as f32 + PADDING * 0.5;
+    let bounds = Rectangle::new(position.x, position.y, WIDTH, line_height * lines.len() as f32 + PADDING * 1.5);
+    d.draw_rectangle_rec(bounds, theme.color_panel.alpha(0.85));
+    for (i, line) in lines.iter().enumerate() {
+        let y = bounds.y + PADDING + line_height * i as f32;
+        d.draw_text(line, (bounds.x + PADDING) as i32, y as i32, theme.font_size, theme.color_foreground);
+    }
+}
+
+/// A span in Chrome's Trace Event Format, which `about:tracing` and Perfetto open
+#[derive(Serialize)]
+struct TraceEvent {
+    name: &'static str,
+    /// Always `"X"`, a complete event with a duration
+    ph: &'static str,
+    /// Start, in microseconds
+    ts: f64,
+    /// Duration, in microseconds
+    dur: f64,
+    pid: u32,
+    tid: u64,
+}
+
+#[derive(Serialize)]
+struct Trace {
+    #[serde(rename = "traceEvents")]
+    trace_events: Vec<TraceEvent>,
+}
+
+/// Write the spans of the last [`TRACE_FRAMES`] frames to `path` as a trace viewable in Chrome or Perfetto
+pub fn write_trace(path: &Path) -> Result<(), String> {
+    let trace = {
+        let profiler = PROFILER.lock();
+        if profiler.frames.is_empty() {
+            return Err("nothing has been profiled".to_owned());
+        }
+        Trace {
+            trace_events: profiler.frames.iter()
+                .flatten()
+                .map(|record| TraceEvent {
+                    name: record.name,
+                    ph: "X",
+                    ts: record.start.as_secs_f64() * 1e6,
+                    dur: record.duration.as_secs_f64() * 1e6,
+                    pid: std::process::id(),
+                    tid: record.thread,
+                })
+                .collect(),
+        }
+    };
+    let json = serde_json::to_string(&trace).map_err(|e| e.to_string())?;
+    std::fs::write(path, json).map_err(|e| e.to_string())
+}
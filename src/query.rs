@@ -0,0 +1,228 @@
+use raylib::prelude::*;
+use crate::{document::Document, layer::{Layer, LayerContent}, style::{Pattern, Style}};
+
+/// What a layer's style must look like to match a [`LayerQuery`]
+///
+/// Owned apart from any document, so scripts can describe a search before there is one to run it on.
+/// An empty filter matches every vector layer
+#[derive(Debug, Clone, PartialEq)]
+pub struct LayerFilter {
+    /// The solid fill color to match
+    ///
+    /// Only curves, compound paths, and rectangles are filled, so nothing else matches
+    pub fill_color: Option<Color>,
+
+    /// The solid stroke color to match
+    pub stroke_color: Option<Color>,
+
+    /// How far (0 to 255) each channel of a color may be from `fill_color` or `stroke_color` and still match
+    pub color_tolerance: u8,
+
+    /// The stroke must be wider than this
+    ///
+    /// A stroke's width is the thickest point of its width profile, as in [`WidthProfile::max_thickness`][`crate::style::WidthProfile::max_thickness`]
+    pub stroke_wider_than: Option<f32>,
+
+    /// The stroke must be narrower than this
+    pub stroke_narrower_than: Option<f32>,
+}
+
+impl Default for LayerFilter {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LayerFilter {
+    /// Construct a filter matching every vector layer
+    pub const fn new() -> Self {
+        Self {
+            fill_color: None,
+            stroke_color: None,
+            color_tolerance: 0,
+            stroke_wider_than: None,
+            stroke_narrower_than: None,
+        }
+    }
+
+    /// Match layers filled with `color`
+    pub const fn fill_color(mut self, color: Color) -> Self {
+        self.fill_color = Some(color);
+        self
+    }
+
+    /// Match layers stroked with `color`
+    #[cfg_attr(not(test), allow(dead_code, reason = "part of the query API for scripts, which the application doesn't run"))]
+    pub const fn stroke_color(mut self, color: Color) -> Self {
+        self.stroke_color = Some(color);
+        self
+    }
+
+    /// Let colors differ by up to `tolerance` per channel
+    pub const fn color_tolerance(mut self, tolerance: u8) -> Self {
+        self.color_tolerance = tolerance;
+        self
+    }
+
+    /// Match layers whose stroke is wider than `width`
+    #[cfg_attr(not(test), allow(dead_code, reason = "part of the query API for scripts, which the application doesn't run"))]
+    pub const fn stroke_wider_than(mut self, width: f32) -> Self {
+        self.stroke_wider_than = Some(width);
+        self
+    }
+
+    /// Match layers whose stroke is narrower than `width`
+    #[cfg_attr(not(test), allow(dead_code, reason = "part of the query API for scripts, which the application doesn't run"))]
+    pub const fn stroke_narrower_than(mut self, width: f32) -> Self {
+        self.stroke_narrower_than = Some(width);
+        self
+    }
+
+    /// Whether `layer` matches the filter
+    ///
+    /// Groups, images, and paint never match, and neither do layers whose style was dropped
+    pub fn matches(&self, layer: &Layer) -> bool {
+        let is_filled = match &layer.content {
+            LayerContent::Curve(_) | LayerContent::Compound(_) | LayerContent::Rectangle(_) => true,
+            LayerContent::Connector(_) => false,
+            LayerContent::Group(_) | LayerContent::Image { .. } | LayerContent::Paint(_) => return false,
+        };
+        if self.fill_color.is_some() && !is_filled {
+            return false;
+        }
+        let Some(style) = layer.style.upgrade() else { return false };
+        let style_lock = style.lock();
+        let is_match = self.matches_style(&style_lock.borrow());
+        is_match
+    }
+
+    fn matches_style(&self, style: &Style) -> bool {
+        let is_near = |pattern: &Pattern, target: Color| match pattern {
+            Pattern::Solid(color) => [(color.r, target.r), (color.g, target.g), (color.b, target.b), (color.a, target.a)]
+                .into_iter()
+                .all(|(a, b)| a.abs_diff(b) <= self.color_tolerance),
+            Pattern::Texture(..) => false,
+        };
+        if self.fill_color.is_some_and(|color| !is_near(&style.fill, color))
+            || self.stroke_color.is_some_and(|color| !is_near(&style.stroke.pattern, color))
+        {
+            return false;
+        }
+        if self.stroke_wider_than.is_none() && self.stroke_narrower_than.is_none() {
+            return true;
+        }
+        let width = style.stroke.width.as_ref()
+            .and_then(|width| width.upgrade())
+            .map_or(0.0, |profile| profile.lock().borrow().max_thickness());
+        self.stroke_wider_than.is_none_or(|min| width > min)
+            && self.stroke_narrower_than.is_none_or(|max| width < max)
+    }
+}
+
+/// A search of a document's layers (including those nested in groups) by style, built with [`Document::query`]
+///
+/// ```ignore
+/// let paths = document.query().fill_color(Color::RED).stroke_wider_than(4.0).paths();
+/// ```
+#[derive(Debug, Clone)]
+pub struct LayerQuery<'a> {
+    document: &'a Document,
+    filter: LayerFilter,
+}
+
+#[cfg_attr(not(test), allow(dead_code, reason = "part of the query API for scripts, which the application doesn't run"))]
+impl<'a> LayerQuery<'a> {
+    /// Construct a query of `document` for the layers matching `filter`
+    pub const fn new(document: &'a Document, filter: LayerFilter) -> Self {
+        Self { document, filter }
+    }
+
+    /// See [`LayerFilter::fill_color`]
+    pub const fn fill_color(mut self, color: Color) -> Self {
+        self.filter = self.filter.fill_color(color);
+        self
+    }
+
+    /// See [`LayerFilter::stroke_color`]
+    pub const fn stroke_color(mut self, color: Color) -> Self {
+        self.filter = self.filter.stroke_color(color);
+        self
+    }
+
+    /// See [`LayerFilter::color_tolerance`]
+    pub const fn color_tolerance(mut self, tolerance: u8) -> Self {
+        self.filter = self.filter.color_tolerance(tolerance);
+        self
+    }
+
+    /// See [`LayerFilter::stroke_wider_than`]
+    pub const fn stroke_wider_than(mut self, width: f32) -> Self {
+        self.filter = self.filter.stroke_wider_than(width);
+        self
+    }
+
+    /// See [`LayerFilter::stroke_narrower_than`]
+    pub const fn stroke_narrower_than(mut self, width: f32) -> Self {
+        self.filter = self.filter.stroke_narrower_than(width);
+        self
+    }
+
+    /// The paths of the matching layers in document order; see [`Document::layer_at`]
+    pub fn paths(&self) -> Vec<Vec<usize>> {
+        let mut paths = Vec::new();
+        self.document.visit_layers(|path, layer| {
+            if self.filter.matches(layer) {
+                paths.push(path.to_vec());
+            }
+        });
+        paths
+    }
+
+    /// The matching layers in document order
+    pub fn layers(&self) -> Vec<&'a Layer> {
+        let mut layers = Vec::new();
+        self.document.visit_layers(|_, layer| {
+            if self.filter.matches(layer) {
+                layers.push(layer);
+            }
+        });
+        layers
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use crate::{editor::Editor, style::{Stroke, WidthProfile}};
+    use super::*;
+
+    #[test]
+    fn test_layer_query() {
+        let mut editor = Editor::new_default(Document::new("query".to_owned()));
+        editor.add_curve_layer("thin".to_owned(), crate::make_curve!((0,0) -> (100,0)));
+        editor.add_curve_layer("thick".to_owned(), crate::make_curve!((0,10) -> (100,10)));
+        editor.add_curve_layer("red".to_owned(), crate::make_curve!((60,40) -> arc(0, 360, 30) -> cycle));
+        let document = &mut editor.document;
+        let mut style_of = |fill: Color, thickness: f32| {
+            let width = Arc::downgrade(document.create_width_profile(WidthProfile::Constant { inner: thickness, outer: thickness }));
+            Arc::downgrade(document.create_style(Style {
+                fill: Pattern::Solid(fill),
+                stroke: Stroke { pattern: Pattern::Solid(Color::BLACK), width: Some(width), ..Stroke::new() },
+                ..Style::new()
+            }))
+        };
+        let styles = [style_of(Color::BLUE, 1.0), style_of(Color::BLUE, 8.0), style_of(Color::new(250, 0, 0, 255), 8.0)];
+        for (layer, style) in document.layers.iter_mut().zip(styles) {
+            layer.style = style;
+        }
+
+        assert_eq!(document.query().paths(), [[0], [1], [2]]);
+        assert_eq!(document.query().stroke_wider_than(4.0).paths(), [[1], [2]]);
+        assert_eq!(document.query().fill_color(Color::BLUE).stroke_wider_than(4.0).paths(), [[1]]);
+        let red = Color::new(255, 0, 0, 255);
+        assert!(document.query().fill_color(red).paths().is_empty(), "colors should match exactly by default");
+        assert_eq!(document.query().fill_color(red).color_tolerance(8).paths(), [[2]]);
+        assert_eq!(document.query().stroke_color(Color::BLACK).stroke_narrower_than(4.0).layers()[0].name, "thin");
+    }
+}
@@ -0,0 +1,489 @@
+use std::{cell::RefCell, collections::HashMap, sync::Arc};
+use parking_lot::ReentrantMutex;
+use raylib::prelude::*;
+use crate::{layer::StrongRenderTexture2D, style::TextureTransform};
+
+/// OpenGL blend factors, for [`erase_circle`]
+const GL_ZERO: i32 = 0;
+const GL_ONE: i32 = 1;
+const GL_ONE_MINUS_SRC_ALPHA: i32 = 0x0303;
+const GL_FUNC_ADD: i32 = 0x8006;
+
+/// An [`Image`] that can be handed to a background thread, like the export or file thread
+pub struct SendImage(pub Image);
+
+// SAFETY: an image's pixels are a plain heap allocation that isn't tied to the GPU context,
+// so encoding and unloading it on another thread is sound while only one thread owns it
+unsafe impl Send for SendImage {}
+
+/// Positions of brush dabs spaced along the line from `start` (exclusive) to `end` (inclusive), so fast strokes don't leave gaps
+pub fn dab_positions(start: Vector2, end: Vector2, radius: f32) -> impl Iterator<Item = Vector2> {
+    let spacing = (radius * 0.25).max(1.0);
+    let steps = (start.distance_to(end) / spacing).ceil().max(1.0) as u32;
+    (1..=steps).map(move |i| start.lerp(end, i as f32 / steps as f32))
+}
+
+/// Positions `spacing` apart along the line from `start` (exclusive) toward `end`, stopping before passing `end`
+///
+/// Unlike [`dab_positions`], the spacing is exact, so the last position usually falls short of `end`
+pub fn stamp_positions(start: Vector2, end: Vector2, spacing: f32) -> impl Iterator<Item = Vector2> {
+    let spacing = spacing.max(1.0);
+    let distance = start.distance_to(end);
+    let steps = (distance / spacing).floor() as u32;
+    let direction = if distance > 0.0 { (end - start) / distance } else { Vector2::zero() };
+    (1..=steps).map(move |i| start + direction * (spacing * i as f32))
+}
+
+/// Erase a circle of `radius` at `center` from the texture being drawn to, leaving its colors untouched
+///
+/// `hardness` is the fraction of the radius that is erased completely; beyond it, less is erased towards the edge
+pub fn erase_circle(d: &mut impl RaylibDraw, center: Vector2, radius: f32, hardness: f32) {
+    // only alpha is reduced, by the alpha drawn; color channels are kept so partially erased pixels don't darken
+    // SAFETY: only changes the factors of the custom blend mode, which nothing else uses
+    unsafe {
+        raylib::ffi::rlSetBlendFactorsSeparate(GL_ZERO, GL_ONE, GL_ZERO, GL_ONE_MINUS_SRC_ALPHA, GL_FUNC_ADD, GL_FUNC_ADD);
+    }
+    let mut d = d.begin_blend_mode(BlendMode::BLEND_CUSTOM_SEPARATE);
+    if hardness < 1.0 {
+        d.draw_circle_gradient(center.x as i32, center.y as i32, radius, Color::WHITE, Color::BLANK);
+    }
+    d.draw_circle_v(center, radius * hardness, Color::WHITE);
+}
+
+/// Erase circles at the worldspace `centers` from `rtex`, which is placed within the worldspace `rect` by `transform`
+///
+/// Used to erase from [texture patterns][`crate::style::Pattern::Texture`]. Circles over a wrapped copy
+/// of the texture erase the texture itself, so every copy shows the erasure
+pub fn erase_texture(mut rl: &mut RaylibHandle, thread: &RaylibThread, rtex: &mut RenderTexture2D, rect: Rectangle, transform: &TextureTransform, centers: impl IntoIterator<Item = Vector2>, radius: f32, hardness: f32) {
+    let size = Vector2::new(rtex.texture.width as f32, rtex.texture.height as f32);
+    let scale = Vector2::new(
+        size.x / (rect.width * transform.scale.x.abs()).max(1.0),
+        size.y / (rect.height * transform.scale.y.abs()).max(1.0),
+    );
+    let mut d = rl.begin_texture_mode(thread, rtex);
+    for center in centers {
+        let uv = transform.to_texture(rect, center);
+        let local = Vector2::new(transform.wrap.apply(uv.x) * size.x, transform.wrap.apply(uv.y) * size.y);
+        erase_circle(&mut d, local, radius * scale.x.max(scale.y), hardness);
+    }
+}
+
+/// Copy an image into a new render texture
+///
+/// The render texture is stored upside-down, like every render texture
+pub fn render_texture_from_image(mut rl: &mut RaylibHandle, thread: &RaylibThread, image: &Image) -> Result<RenderTexture2D, String> {
+    let texture = rl.load_texture_from_image(thread, image)?;
+    let mut rtex = rl.load_render_texture(thread, image.width as u32, image.height as u32)?;
+    {
+        let mut d = rl.begin_texture_mode(thread, &mut rtex);
+        d.clear_background(Color::BLANK);
+        d.draw_texture(&texture, 0, 0, Color::WHITE);
+    }
+    Ok(rtex)
+}
+
+/// Download a render texture from the GPU as an upright image
+pub fn image_from_render_texture(rtex: &RenderTexture2D) -> Result<Image, String> {
+    let mut image = rtex.texture().load_image()?;
+    // render textures are stored upside-down
+    image.flip_vertical();
+    Ok(image)
+}
+
+/// Encode an image as a PNG file in memory
+///
+/// Doesn't need the GPU context, so it can run on the export thread
+pub fn encode_png(image: &Image) -> Result<Vec<u8>, String> {
+    let mut size = 0;
+    // SAFETY: the image is only read, and the encoded buffer is copied before raylib frees it
+    unsafe {
+        let data = raylib::ffi::ExportImageToMemory(**image, c".png".as_ptr(), &mut size);
+        if data.is_null() {
+            return Err("failed to encode PNG".to_owned());
+        }
+        let png = std::slice::from_raw_parts(data, size.max(0) as usize).to_vec();
+        raylib::ffi::MemFree(data.cast());
+        Ok(png)
+    }
+}
+
+/// Approximate GPU memory used by a render texture, in bytes
+///
+/// Counts an 8-bit RGBA color buffer and a 32-bit depth buffer
+pub fn render_texture_size(rtex: &RenderTexture2D) -> usize {
+    let texture = rtex.texture();
+    texture.width.max(0) as usize * texture.height.max(0) as usize * 8
+}
+
+/// The rasters of a [`Document`][`crate::document::Document`], along with tools for keeping their GPU memory in check
+///
+/// Multiple styles and layers can reference the same raster
+///
+/// A raster should be removed when the weak count is 0; the
+/// document doesn't need a raster if nothing references it
+#[derive(Debug, Default)]
+pub struct RasterStore {
+    rasters: Vec<StrongRenderTexture2D>,
+}
+
+impl RasterStore {
+    /// Construct an empty store without any allocations
+    pub const fn new() -> Self {
+        Self {
+            rasters: Vec::new(),
+        }
+    }
+
+    /// Push a new raster to the store and get a reference to it
+    pub fn push(&mut self, rtex: RenderTexture2D) -> &StrongRenderTexture2D {
+        self.rasters.push(Arc::new(ReentrantMutex::new(RefCell::new(rtex))));
+        #[cfg(debug_assertions)] {
+            self.rasters.last().expect("should have at least one element after push")
+        } #[cfg(not(debug_assertions))] {
+            unsafe { self.rasters.last().unwrap_unchecked() }
+        }
+    }
+
+    /// Move every raster of `other` into this store
+    pub fn append(&mut self, other: RasterStore) {
+        self.rasters.extend(other.rasters);
+    }
+
+    /// Iterate over the rasters
+    #[inline]
+    pub fn iter(&self) -> std::slice::Iter<'_, StrongRenderTexture2D> {
+        self.rasters.iter()
+    }
+
+    /// The number of rasters in the store
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.rasters.len()
+    }
+
+    /// Whether the store has no rasters
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.rasters.is_empty()
+    }
+
+    /// Approximate GPU memory used by every raster, in bytes
+    ///
+    /// See [`render_texture_size`]
+    pub fn memory_usage(&self) -> usize {
+        self.rasters.iter()
+            .map(|raster| render_texture_size(&raster.lock().borrow()))
+            .sum()
+    }
+
+    /// Unload every raster that nothing references anymore
+    ///
+    /// Returns the number of bytes freed
+    pub fn remove_unused(&mut self) -> usize {
+        let mut freed = 0;
+        self.rasters.retain(|raster| {
+            let is_used = Arc::weak_count(raster) > 0;
+            if !is_used {
+                freed += render_texture_size(&raster.lock().borrow());
+            }
+            is_used
+        });
+        freed
+    }
+
+    /// Shrink `raster` to the smallest rectangle containing all of its non-transparent pixels,
+    /// along with up to `margin` transparent pixels on each side
+    ///
+    /// The raster is replaced in place, so existing references see the cropped version
+    ///
+    /// Returns the cropped region in the raster's original pixel coordinates (top-down), or [`None`] if
+    /// nothing was cropped because the raster is already as small as it can be or entirely transparent
+    pub fn crop_to_content(rl: &mut RaylibHandle, thread: &RaylibThread, raster: &StrongRenderTexture2D, margin: f32) -> Result<Option<Rectangle>, String> {
+        let raster_lock = raster.lock();
+        let mut image = image_from_render_texture(&raster_lock.borrow())?;
+        let content = image.get_image_alpha_border(0.0);
+        if content.width < 1.0 || content.height < 1.0 {
+            return Ok(None);
+        }
+        let (left, top) = ((content.x - margin).max(0.0), (content.y - margin).max(0.0));
+        let right = (content.x + content.width + margin).min(image.width as f32);
+        let bottom = (content.y + content.height + margin).min(image.height as f32);
+        let border = Rectangle::new(left, top, right - left, bottom - top);
+        if border.width as i32 == image.width && border.height as i32 == image.height {
+            return Ok(None);
+        }
+        image.crop(border);
+        let cropped = render_texture_from_image(rl, thread, &image)?;
+        *raster_lock.borrow_mut() = cropped;
+        Ok(Some(border))
+    }
+
+    /// Make a copy of `raster` scaled down to fit within a `max_size` square, preserving aspect ratio
+    ///
+    /// Scaled on the CPU, so every pixel contributes instead of the few a small draw would sample.
+    /// Rasters that already fit are copied at their original size
+    pub fn downsample(rl: &mut RaylibHandle, thread: &RaylibThread, raster: &RenderTexture2D, max_size: u32) -> Result<RenderTexture2D, String> {
+        let mut image = image_from_render_texture(raster)?;
+        let scale = (max_size as f32 / image.width.max(image.height).max(1) as f32).min(1.0);
+        if scale < 1.0 {
+            let width = ((image.width as f32 * scale).round() as i32).max(1);
+            let height = ((image.height as f32 * scale).round() as i32).max(1);
+            image.resize(width, height);
+        }
+        render_texture_from_image(rl, thread, &image)
+    }
+}
+
+impl<'a> IntoIterator for &'a RasterStore {
+    type Item = &'a StrongRenderTexture2D;
+    type IntoIter = std::slice::Iter<'a, StrongRenderTexture2D>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Copy a render texture into a new render texture of the same size
+pub fn copy_render_texture(mut rl: &mut RaylibHandle, thread: &RaylibThread, rtex: &RenderTexture2D) -> Result<RenderTexture2D, String> {
+    let texture = rtex.texture();
+    let mut copy = rl.load_render_texture(thread, texture.width as u32, texture.height as u32)?;
+    {
+        let mut d = rl.begin_texture_mode(thread, &mut copy);
+        d.clear_background(Color::BLANK);
+        // drawing flipped keeps the copy upside-down like the original
+        let source = Rectangle::new(0.0, 0.0, texture.width as f32, -texture.height as f32);
+        d.draw_texture_rec(texture, source, Vector2::zero(), Color::WHITE);
+    }
+    Ok(copy)
+}
+
+/// Painted pixels stored as a sparse grid of [`TiledRaster::TILE_SIZE`] square tiles
+///
+/// Tiles are only allocated where something has been painted, so a painting can span a huge
+/// area without allocating one huge texture up front
+#[derive(Debug, Default)]
+pub struct TiledRaster {
+    /// The worldspace position of the corner of tile `(0, 0)`
+    pub origin: Vector2,
+
+    /// The allocated tiles, by grid coordinate
+    ///
+    /// Stored upside-down, like every render texture
+    pub tiles: HashMap<(i32, i32), RenderTexture2D>,
+
+    /// Counts the times the raster has been painted on or erased, to tell when something rendered from it is out of date
+    revision: u64,
+}
+
+impl TiledRaster {
+    /// The width and height of every tile, in pixels
+    pub const TILE_SIZE: u32 = 256;
+
+    /// Construct an empty raster without any allocations
+    pub fn new(origin: Vector2) -> Self {
+        Self {
+            origin,
+            tiles: HashMap::new(),
+            revision: 0,
+        }
+    }
+
+    /// The number of times the raster has been painted on or erased, which changes with every edit
+    pub const fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// The worldspace rectangle covered by the tile at `coord`
+    pub fn tile_rect(&self, (x, y): (i32, i32)) -> Rectangle {
+        let size = Self::TILE_SIZE as f32;
+        Rectangle::new(self.origin.x + x as f32 * size, self.origin.y + y as f32 * size, size, size)
+    }
+
+    /// Iterate over the grid coordinates of every tile (allocated or not) overlapping the worldspace `rect`
+    ///
+    /// Tiles only touching the edge of `rect` don't overlap it, but an empty `rect` still overlaps the tile it is in
+    pub fn tiles_overlapping(&self, rect: Rectangle) -> impl Iterator<Item = (i32, i32)> {
+        let size = Self::TILE_SIZE as f32;
+        let x_min = ((rect.x - self.origin.x) / size).floor() as i32;
+        let y_min = ((rect.y - self.origin.y) / size).floor() as i32;
+        let x_max = (((rect.x + rect.width - self.origin.x) / size).ceil() as i32).saturating_sub(1).max(x_min);
+        let y_max = (((rect.y + rect.height - self.origin.y) / size).ceil() as i32).saturating_sub(1).max(y_min);
+        (y_min..=y_max).flat_map(move |y| (x_min..=x_max).map(move |x| (x, y)))
+    }
+
+    /// Get the tile at `coord`, allocating a transparent one if there isn't one
+    fn tile_or_allocate(&mut self, mut rl: &mut RaylibHandle, thread: &RaylibThread, coord: (i32, i32)) -> Result<&mut RenderTexture2D, String> {
+        Ok(match self.tiles.entry(coord) {
+            std::collections::hash_map::Entry::Occupied(entry) => entry.into_mut(),
+            std::collections::hash_map::Entry::Vacant(entry) => {
+                let mut tile = rl.load_render_texture(thread, Self::TILE_SIZE, Self::TILE_SIZE)?;
+                rl.begin_texture_mode(thread, &mut tile).clear_background(Color::BLANK);
+                entry.insert(tile)
+            }
+        })
+    }
+
+    /// Paint a filled circle at the worldspace `center`, allocating tiles as needed
+    pub fn paint_circle(&mut self, mut rl: &mut RaylibHandle, thread: &RaylibThread, center: Vector2, radius: f32, color: Color) -> Result<(), String> {
+        let bounds = Rectangle::new(center.x - radius, center.y - radius, radius * 2.0, radius * 2.0);
+        self.revision = self.revision.wrapping_add(1);
+        for coord in self.tiles_overlapping(bounds).collect::<Vec<_>>() {
+            let rect = self.tile_rect(coord);
+            let tile = self.tile_or_allocate(rl, thread, coord)?;
+            let mut d = rl.begin_texture_mode(thread, tile);
+            d.draw_circle_v(Vector2::new(center.x - rect.x, center.y - rect.y), radius, color);
+        }
+        Ok(())
+    }
+
+    /// Paint `tip` tinted with `color` over the square of `radius` around the worldspace `center`, turned clockwise
+    /// by `angle` degrees, allocating tiles as needed
+    pub fn paint_stamp(&mut self, mut rl: &mut RaylibHandle, thread: &RaylibThread, center: Vector2, radius: f32, angle: f32, tip: &Texture2D, color: Color) -> Result<(), String> {
+        // the corners of the turned square reach this far at most
+        let extent = radius * std::f32::consts::SQRT_2;
+        let bounds = Rectangle::new(center.x - extent, center.y - extent, extent * 2.0, extent * 2.0);
+        let source = Rectangle::new(0.0, 0.0, tip.width as f32, tip.height as f32);
+        self.revision = self.revision.wrapping_add(1);
+        for coord in self.tiles_overlapping(bounds).collect::<Vec<_>>() {
+            let rect = self.tile_rect(coord);
+            let tile = self.tile_or_allocate(rl, thread, coord)?;
+            let mut d = rl.begin_texture_mode(thread, tile);
+            let dest = Rectangle::new(center.x - rect.x, center.y - rect.y, radius * 2.0, radius * 2.0);
+            d.draw_texture_pro(tip, source, dest, Vector2::new(radius, radius), angle, color);
+        }
+        Ok(())
+    }
+
+    /// Paint circles spaced along the line from `start` to `end`, so fast strokes don't leave gaps
+    pub fn paint_line(&mut self, rl: &mut RaylibHandle, thread: &RaylibThread, start: Vector2, end: Vector2, radius: f32, color: Color) -> Result<(), String> {
+        for center in dab_positions(start, end, radius) {
+            self.paint_circle(rl, thread, center, radius, color)?;
+        }
+        Ok(())
+    }
+
+    /// Erase circles at the worldspace `centers` from the tiles they overlap
+    ///
+    /// Unallocated tiles are already transparent, so they are never allocated
+    pub fn erase(&mut self, mut rl: &mut RaylibHandle, thread: &RaylibThread, centers: impl IntoIterator<Item = Vector2>, radius: f32, hardness: f32) {
+        self.revision = self.revision.wrapping_add(1);
+        for center in centers {
+            let bounds = Rectangle::new(center.x - radius, center.y - radius, radius * 2.0, radius * 2.0);
+            for coord in self.tiles_overlapping(bounds).collect::<Vec<_>>() {
+                let rect = self.tile_rect(coord);
+                if let Some(tile) = self.tiles.get_mut(&coord) {
+                    let mut d = rl.begin_texture_mode(thread, tile);
+                    erase_circle(&mut d, Vector2::new(center.x - rect.x, center.y - rect.y), radius, hardness);
+                }
+            }
+        }
+    }
+
+    /// Draw every allocated tile in worldspace
+    pub fn draw(&self, d: &mut impl RaylibDraw, tint: Color) {
+        let size = Self::TILE_SIZE as f32;
+        // render textures are stored upside-down
+        let source = Rectangle::new(0.0, 0.0, size, -size);
+        for (&coord, tile) in &self.tiles {
+            let rect = self.tile_rect(coord);
+            d.draw_texture_rec(tile.texture(), source, Vector2::new(rect.x, rect.y), tint);
+        }
+    }
+
+    /// Get a rectangle containing every allocated tile
+    ///
+    /// [`None`] if no tiles are allocated
+    pub fn bounds(&self) -> Option<Rectangle> {
+        self.tiles.keys()
+            .map(|&coord| self.tile_rect(coord))
+            .reduce(|a, b| {
+                let x = a.x.min(b.x);
+                let y = a.y.min(b.y);
+                let right = (a.x + a.width).max(b.x + b.width);
+                let bottom = (a.y + a.height).max(b.y + b.height);
+                Rectangle::new(x, y, right - x, bottom - y)
+            })
+    }
+
+    /// Approximate GPU memory used by every allocated tile, in bytes
+    ///
+    /// See [`render_texture_size`]
+    pub fn memory_usage(&self) -> usize {
+        self.tiles.values().map(render_texture_size).sum()
+    }
+
+    /// Make a copy of the raster with its own tiles
+    pub fn duplicate(&self, rl: &mut RaylibHandle, thread: &RaylibThread) -> Result<Self, String> {
+        Ok(Self {
+            origin: self.origin,
+            tiles: self.tiles.iter()
+                .map(|(&coord, tile)| Ok((coord, copy_render_texture(rl, thread, tile)?)))
+                .collect::<Result<_, String>>()?,
+            revision: self.revision,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dab_positions() {
+        // dabs are at most a quarter of the radius apart, spread evenly, and always reach the end
+        let dabs = dab_positions(Vector2::new(0.0, 0.0), Vector2::new(10.0, 0.0), 16.0).collect::<Vec<_>>();
+        assert_eq!(dabs.len(), 3);
+        assert!(dabs.windows(2).all(|pair| pair[0].distance_to(pair[1]) <= 4.0));
+        assert_eq!(dabs.last(), Some(&Vector2::new(10.0, 0.0)));
+
+        assert_eq!(dab_positions(Vector2::new(0.0, 0.0), Vector2::new(0.0, 3.0), 1.0).collect::<Vec<_>>(), [
+            Vector2::new(0.0, 1.0),
+            Vector2::new(0.0, 2.0),
+            Vector2::new(0.0, 3.0),
+        ], "spacing is at least a pixel");
+
+        // strokes shorter than the spacing, even without any length, still get a dab at the end
+        assert_eq!(dab_positions(Vector2::new(0.0, 0.0), Vector2::new(1.0, 0.0), 16.0).collect::<Vec<_>>(), [Vector2::new(1.0, 0.0)]);
+        assert_eq!(dab_positions(Vector2::new(5.0, 5.0), Vector2::new(5.0, 5.0), 16.0).collect::<Vec<_>>(), [Vector2::new(5.0, 5.0)]);
+    }
+
+    #[test]
+    fn test_stamp_positions() {
+        let stamps = stamp_positions(Vector2::new(0.0, 0.0), Vector2::new(10.0, 0.0), 3.0).collect::<Vec<_>>();
+        assert_eq!(stamps, [Vector2::new(3.0, 0.0), Vector2::new(6.0, 0.0), Vector2::new(9.0, 0.0)]);
+
+        // the spacing holds in any direction, and a move landing exactly on a stamp includes it
+        let stamps = stamp_positions(Vector2::new(1.0, 0.0), Vector2::new(1.0, -8.0), 4.0).collect::<Vec<_>>();
+        assert_eq!(stamps, [Vector2::new(1.0, -4.0), Vector2::new(1.0, -8.0)]);
+
+        assert_eq!(stamp_positions(Vector2::new(0.0, 0.0), Vector2::new(2.0, 0.0), 3.0).count(), 0, "moves shorter than the spacing stamp nothing");
+        assert_eq!(stamp_positions(Vector2::new(5.0, 5.0), Vector2::new(5.0, 5.0), 3.0).count(), 0);
+        assert_eq!(stamp_positions(Vector2::new(0.0, 0.0), Vector2::new(2.0, 0.0), 0.0).count(), 2, "spacing is at least a pixel");
+    }
+
+    #[test]
+    fn test_tile_rect() {
+        let raster = TiledRaster::new(Vector2::new(10.0, -20.0));
+        assert_eq!(raster.tile_rect((0, 0)), Rectangle::new(10.0, -20.0, 256.0, 256.0));
+        assert_eq!(raster.tile_rect((-1, 2)), Rectangle::new(-246.0, 492.0, 256.0, 256.0));
+    }
+
+    #[test]
+    fn test_tiles_overlapping() {
+        let raster = TiledRaster::new(Vector2::new(10.0, -20.0));
+        let tiles = |rect| raster.tiles_overlapping(rect).collect::<Vec<_>>();
+        assert_eq!(tiles(Rectangle::new(10.0, -20.0, 256.0, 256.0)), [(0, 0)], "tiles touching the edges don't overlap");
+        assert_eq!(tiles(Rectangle::new(9.0, -20.0, 2.0, 1.0)), [(-1, 0), (0, 0)]);
+        assert_eq!(tiles(Rectangle::new(266.0, 236.0, 0.0, 0.0)), [(1, 1)], "an empty rectangle overlaps the tile it is in");
+
+        // tiles at negative coordinates round toward negative infinity
+        let raster = TiledRaster::new(Vector2::zero());
+        let rect = Rectangle::new(-300.0, -10.0, 50.0, 20.0);
+        let tiles = raster.tiles_overlapping(rect).collect::<Vec<_>>();
+        assert_eq!(tiles, [(-2, -1), (-1, -1), (-2, 0), (-1, 0)]);
+        for coord in tiles {
+            assert!(raster.tile_rect(coord).check_collision_recs(&rect));
+        }
+    }
+}
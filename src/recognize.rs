@@ -0,0 +1,322 @@
+use std::f32::consts::{FRAC_PI_2, TAU};
+use raylib::prelude::*;
+use crate::curve::{self, Curve, CurveBuilder};
+
+/// Seconds the end of a vector brush stroke has to rest in place before the stroke is recognized as a shape
+pub const HOLD_DURATION: f32 = 0.5;
+
+/// Largest average distance between a stroke and the shape fitted to it, as a fraction of the stroke's size,
+/// for the stroke to be recognized as that shape
+const MAX_ERROR: f32 = 0.04;
+
+/// Largest gap between the ends of a stroke, as a fraction of its size, for the stroke to be closed
+const CLOSING_GAP: f32 = 0.2;
+
+/// How far a corner has to stand out from the sides around it, as a fraction of the stroke's size
+const CORNER_TOLERANCE: f32 = 0.08;
+
+/// Smallest ratio of an ellipse's radii for it to be a circle
+const CIRCLE_RATIO: f32 = 0.85;
+
+/// Lines and rectangles turned less than this many radians from level are leveled
+const LEVEL_ANGLE: f32 = 0.09;
+
+/// A primitive a brush stroke looks like
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Shape {
+    /// A straight line between two worldspace points
+    Line {
+        start: Vector2,
+        end: Vector2,
+    },
+
+    /// A circle around a worldspace center
+    Circle {
+        center: Vector2,
+        radius: f32,
+    },
+
+    /// An ellipse around a worldspace center with `radii`, turned `rotation` radians clockwise on screen
+    Ellipse {
+        center: Vector2,
+        radii: Vector2,
+        rotation: f32,
+    },
+
+    /// A rectangle, possibly turned, with its worldspace corners in order around it
+    Rectangle {
+        corners: [Vector2; 4],
+    },
+
+    /// A triangle with its worldspace corners in order around it
+    Triangle {
+        corners: [Vector2; 3],
+    },
+}
+
+impl Shape {
+    /// The name a layer drawn as the shape gets
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Line { .. } => "Line",
+            Self::Circle { .. } => "Circle",
+            Self::Ellipse { .. } => "Ellipse",
+            Self::Rectangle { .. } => "Rectangle",
+            Self::Triangle { .. } => "Triangle",
+        }
+    }
+
+    /// A curve tracing the shape
+    ///
+    /// Lines are open; every other shape is closed
+    pub fn to_curve(&self) -> Curve {
+        match *self {
+            Self::Line { start, end } => CurveBuilder::new()
+                .corner(to_na(start))
+                .corner(to_na(end))
+                .build(),
+            Self::Circle { center, radius } => ellipse(center, Vector2::new(radius, radius), 0.0),
+            Self::Ellipse { center, radii, rotation } => ellipse(center, radii, rotation),
+            Self::Rectangle { corners } => polygon(&corners),
+            Self::Triangle { corners } => polygon(&corners),
+        }
+    }
+
+    /// The worldspace distance from `point` to the nearest part of the shape's outline
+    ///
+    /// Approximate for ellipses, measured toward the center rather than straight to the outline
+    fn distance_to(&self, point: Vector2) -> f32 {
+        match *self {
+            Self::Line { start, end } => curve::distance_to_segment(to_na(point), to_na(start), to_na(end)),
+            Self::Circle { center, radius } => (point.distance_to(center) - radius).abs(),
+            Self::Ellipse { center, radii, rotation } => {
+                let local = na::Rotation2::new(-rotation) * to_na(point - center);
+                let angle = (local.y / radii.y).atan2(local.x / radii.x);
+                (local - na::Vector2::new(radii.x * angle.cos(), radii.y * angle.sin())).norm()
+            }
+            Self::Rectangle { corners } => distance_to_polygon(point, &corners),
+            Self::Triangle { corners } => distance_to_polygon(point, &corners),
+        }
+    }
+}
+
+/// Find the shape the worldspace `points` of a brush stroke look like, fitted to them
+///
+/// Open strokes can only be lines, and closed strokes (ones that end near where they started) can be any other shape.
+/// [`None`] if the stroke doesn't look like any of them
+pub fn recognize(points: &[Vector2]) -> Option<Shape> {
+    let (&first, &last) = (points.first()?, points.last()?);
+    let (min, max) = points.iter().fold((first, first), |(min, max), point| {
+        (Vector2::new(min.x.min(point.x), min.y.min(point.y)), Vector2::new(max.x.max(point.x), max.y.max(point.y)))
+    });
+    let size = min.distance_to(max);
+    if points.len() < 2 || size <= f32::EPSILON {
+        return None;
+    }
+
+    let candidates = if first.distance_to(last) <= size * CLOSING_GAP {
+        vec![fit_ellipse(points), fit_polygon(points, size)]
+    } else {
+        vec![Some(fit_line(first, last))]
+    };
+    candidates.into_iter()
+        .flatten()
+        .map(|shape| (points.iter().map(|&point| shape.distance_to(point)).sum::<f32>() / points.len() as f32, shape))
+        .filter(|&(error, _)| error <= size * MAX_ERROR)
+        .min_by(|a, b| a.0.total_cmp(&b.0))
+        .map(|(_, shape)| shape)
+}
+
+/// The line from `start` to `end`, leveled about its middle if it is nearly level
+fn fit_line(start: Vector2, end: Vector2) -> Shape {
+    let span = end - start;
+    let angle = span.y.atan2(span.x);
+    let leveled = level(angle);
+    if leveled == angle {
+        return Shape::Line { start, end };
+    }
+    let middle = (start + end) * 0.5;
+    let half = Vector2::new(leveled.cos(), leveled.sin()) * (span.length() * 0.5);
+    Shape::Line { start: middle - half, end: middle + half }
+}
+
+/// The ellipse spanning `points` along the axes they spread out the most and least along, or a circle if those are about the same
+fn fit_ellipse(points: &[Vector2]) -> Option<Shape> {
+    let count = points.len() as f32;
+    let centroid = points.iter().fold(Vector2::zero(), |sum, &point| sum + point) / count;
+    let (xx, xy, yy) = points.iter().fold((0.0, 0.0, 0.0), |(xx, xy, yy), &point| {
+        let d = point - centroid;
+        (xx + d.x * d.x, xy + d.x * d.y, yy + d.y * d.y)
+    });
+    let rotation = level(0.5 * (2.0 * xy).atan2(xx - yy));
+    let (center, radii) = span_along(points, rotation);
+    if radii.x.min(radii.y) <= f32::EPSILON {
+        return None;
+    }
+    Some(if radii.x.min(radii.y) >= radii.x.max(radii.y) * CIRCLE_RATIO {
+        Shape::Circle { center, radius: (radii.x + radii.y) * 0.5 }
+    } else {
+        Shape::Ellipse { center, radii, rotation }
+    })
+}
+
+/// The triangle or rectangle through the corners of the closed stroke `points`, `size` across
+///
+/// [`None`] if the stroke doesn't have three or four corners
+fn fit_polygon(points: &[Vector2], size: f32) -> Option<Shape> {
+    let tolerance = size * CORNER_TOLERANCE;
+    let polyline = points.iter().copied().map(to_na).collect::<Vec<_>>();
+    // split the loop at the point farthest from where it starts, so both halves have ends to simplify between
+    let far = polyline.iter()
+        .enumerate()
+        .max_by(|a, b| (a.1 - polyline[0]).norm().total_cmp(&(b.1 - polyline[0]).norm()))
+        .map(|(i, _)| i)?;
+    let (head, tail) = (&polyline[..=far], &polyline[far..]);
+    let tail = tail.iter().copied().chain(std::iter::once(polyline[0])).collect::<Vec<_>>();
+    let mut corners = curve::simplify_polyline(head, tolerance).into_iter().map(|i| head[i])
+        .chain(curve::simplify_polyline(&tail, tolerance).into_iter().skip(1).map(|i| tail[i]))
+        .collect::<Vec<_>>();
+    // the start of the loop comes back around at the end
+    corners.pop();
+
+    // the stroke may have started partway along a side, and its ends may not quite meet
+    while corners.len() > 2
+        && let Some(straightest) = (0..corners.len())
+            .map(|i| {
+                let prev = corners[i.checked_sub(1).unwrap_or(corners.len().saturating_sub(1))];
+                let next = corners.get(i.saturating_add(1)).unwrap_or(&corners[0]);
+                (i, curve::distance_to_segment(corners[i], prev, *next))
+            })
+            .filter(|&(_, distance)| distance < tolerance)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+    {
+        corners.remove(straightest.0);
+    }
+
+    match *corners.as_slice() {
+        [a, b, c] => Some(Shape::Triangle { corners: [from_na(a), from_na(b), from_na(c)] }),
+        [a, b, c, d] => {
+            // average the directions of the sides, folded into a quarter turn
+            let (sin, cos) = [(a, b), (b, c), (c, d), (d, a)].iter()
+                .map(|(from, to)| 4.0 * (to.y - from.y).atan2(to.x - from.x))
+                .fold((0.0, 0.0), |(sin, cos), angle| (sin + angle.sin(), cos + angle.cos()));
+            let rotation = level(sin.atan2(cos) * 0.25);
+            let corners = [a, b, c, d].map(from_na);
+            let (center, radii) = span_along(&corners, rotation);
+            let turn = |x: f32, y: f32| center + Vector2::new(x, y).rotated(rotation);
+            Some(Shape::Rectangle {
+                corners: [
+                    turn(-radii.x, -radii.y),
+                    turn( radii.x, -radii.y),
+                    turn( radii.x,  radii.y),
+                    turn(-radii.x,  radii.y),
+                ],
+            })
+        }
+        _ => None,
+    }
+}
+
+/// The center and half-extents of `points` along the axes turned `rotation` radians from level
+fn span_along(points: &[Vector2], rotation: f32) -> (Vector2, Vector2) {
+    let (mut min, mut max) = (Vector2::new(f32::INFINITY, f32::INFINITY), Vector2::new(f32::NEG_INFINITY, f32::NEG_INFINITY));
+    for &point in points {
+        let local = point.rotated(-rotation);
+        min = Vector2::new(min.x.min(local.x), min.y.min(local.y));
+        max = Vector2::new(max.x.max(local.x), max.y.max(local.y));
+    }
+    ((min + max).scale_by(0.5).rotated(rotation), (max - min).scale_by(0.5))
+}
+
+/// `angle` snapped to the nearest quarter turn if it is within [`LEVEL_ANGLE`] of it
+fn level(angle: f32) -> f32 {
+    let nearest = (angle / FRAC_PI_2).round() * FRAC_PI_2;
+    if (angle - nearest).abs() < LEVEL_ANGLE { nearest } else { angle }
+}
+
+/// The worldspace distance from `point` to the nearest side of the closed polygon `corners`
+fn distance_to_polygon(point: Vector2, corners: &[Vector2]) -> f32 {
+    corners.iter()
+        .zip(corners.iter().cycle().skip(1))
+        .map(|(&a, &b)| curve::distance_to_segment(to_na(point), to_na(a), to_na(b)))
+        .fold(f32::INFINITY, f32::min)
+}
+
+/// A closed curve around an ellipse
+fn ellipse(center: Vector2, radii: Vector2, rotation: f32) -> Curve {
+    let start = center + Vector2::new(radii.x, 0.0).rotated(rotation);
+    CurveBuilder::new()
+        .corner(to_na(start))
+        .arc(0.0, TAU, to_na(radii), rotation)
+        .cycle()
+}
+
+/// A closed curve with sharp corners at `corners`
+fn polygon(corners: &[Vector2]) -> Curve {
+    corners.iter()
+        .fold(CurveBuilder::new(), |builder, &corner| builder.corner(to_na(corner)))
+        .cycle()
+}
+
+/// Convert a raylib vector to a nalgebra vector
+#[inline]
+fn to_na(v: Vector2) -> na::Vector2<f32> {
+    na::Vector2::new(v.x, v.y)
+}
+
+/// Convert a nalgebra vector to a raylib vector
+#[inline]
+fn from_na(v: na::Vector2<f32>) -> Vector2 {
+    Vector2::new(v.x, v.y)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Points every 2 pixels along the sides of the closed polygon `corners`, starting partway along the first side
+    fn trace(corners: &[Vector2]) -> Vec<Vector2> {
+        let mut points = Vec::new();
+        for (&a, &b) in corners.iter().zip(corners.iter().cycle().skip(1)) {
+            let steps = (a.distance_to(b) / 2.0).ceil() as usize;
+            points.extend((0..steps).map(|i| a.lerp(b, i as f32 / steps as f32)));
+        }
+        points.rotate_left(3);
+        points
+    }
+
+    #[test]
+    fn test_recognize_line() {
+        let points = (0..=50).map(|i| Vector2::new(i as f32 * 4.0, i as f32 * 0.1 + if i % 2 == 0 { 1.0 } else { -1.0 })).collect::<Vec<_>>();
+        let Some(Shape::Line { start, end }) = recognize(&points) else { panic!("a wobbly stroke should be a line") };
+        assert!((start.y - end.y).abs() < 1e-3, "a nearly level line should be leveled");
+        assert!(start.distance_to(Vector2::new(0.0, 3.5)) < 2.0 && end.distance_to(Vector2::new(200.0, 3.5)) < 2.0);
+    }
+
+    #[test]
+    fn test_recognize_closed_shapes() {
+        let circle = (0..90).map(|i| Vector2::new(50.0, 0.0).rotated(i as f32 * TAU / 90.0) + Vector2::new(100.0, 100.0)).collect::<Vec<_>>();
+        let Some(Shape::Circle { center, radius }) = recognize(&circle) else { panic!("a round loop should be a circle") };
+        assert!(center.distance_to(Vector2::new(100.0, 100.0)) < 1.0 && (radius - 50.0).abs() < 1.0);
+
+        let ellipse = (0..90).map(|i| {
+            let angle = i as f32 * TAU / 90.0;
+            Vector2::new(80.0 * angle.cos(), 30.0 * angle.sin())
+        }).collect::<Vec<_>>();
+        assert!(matches!(recognize(&ellipse), Some(Shape::Ellipse { .. })), "an oval loop should be an ellipse");
+
+        let rectangle = trace(&[Vector2::new(0.0, 0.0), Vector2::new(120.0, 1.0), Vector2::new(120.0, 60.0), Vector2::new(0.0, 60.0)]);
+        let Some(Shape::Rectangle { corners }) = recognize(&rectangle) else { panic!("a four-cornered loop should be a rectangle") };
+        assert!((corners[0].y - corners[1].y).abs() < 1e-3, "a nearly level rectangle should be leveled");
+
+        let triangle = trace(&[Vector2::new(0.0, 100.0), Vector2::new(50.0, 0.0), Vector2::new(100.0, 100.0)]);
+        assert!(matches!(recognize(&triangle), Some(Shape::Triangle { .. })), "a three-cornered loop should be a triangle");
+    }
+
+    #[test]
+    fn test_recognize_nothing() {
+        let squiggle = (0..=60).map(|i| Vector2::new(i as f32 * 3.0, (i as f32 * 0.3).sin() * 40.0)).collect::<Vec<_>>();
+        assert_eq!(recognize(&squiggle), None, "an open curve shouldn't be a line");
+        assert_eq!(recognize(&[Vector2::new(5.0, 5.0)]), None);
+    }
+}
@@ -0,0 +1,140 @@
+use raylib::prelude::*;
+
+/// Tint of the copies previewed while the "Grid Repeat" dialog is open
+pub const PREVIEW_TINT: Color = Color::new(255, 255, 255, 128);
+
+/// How copies are arranged by "Grid Repeat"
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GridRepeatOptions {
+    /// Number of copies across each row
+    pub columns: usize,
+
+    /// Number of rows of copies
+    pub rows: usize,
+
+    /// Worldspace pixels between the bounds of neighboring copies, horizontally and vertically
+    pub spacing: Vector2,
+
+    /// Degrees each copy turns beyond the one before it, clockwise on screen
+    pub rotation_step: f32,
+
+    /// How much larger each copy is than the one before it, as a fraction of the original's size
+    ///
+    /// Negative to shrink
+    pub scale_step: f32,
+}
+
+impl Default for GridRepeatOptions {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GridRepeatOptions {
+    /// Smallest number of rows or columns
+    pub const MIN_COUNT: usize = 1;
+
+    /// Largest number of rows or columns
+    pub const MAX_COUNT: usize = 100;
+
+    /// Largest rotation step the dialog allows, in either direction
+    pub const MAX_ROTATION_STEP: f32 = 180.0;
+
+    /// Largest scale step the dialog allows, in either direction
+    pub const MAX_SCALE_STEP: f32 = 1.0;
+
+    /// Smallest scale a copy is shrunk to, so shrinking steps never flip or collapse a copy
+    const MIN_SCALE: f32 = 0.01;
+
+    /// A 3x3 grid of untransformed copies
+    pub const fn new() -> Self {
+        Self {
+            columns: 3,
+            rows: 3,
+            spacing: Vector2::new(20.0, 20.0),
+            rotation_step: 0.0,
+            scale_step: 0.0,
+        }
+    }
+}
+
+/// Where one copy in a grid goes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GridPlacement {
+    /// Worldspace distance from the original
+    pub offset: Vector2,
+
+    /// How far the copy turns about its center, in radians clockwise on screen
+    pub angle: f32,
+
+    /// How much the copy is scaled about its center
+    pub scale: f32,
+}
+
+/// Where each cell of the grid goes for artwork of the given worldspace `size`, row by row
+///
+/// The first cell is where the original already is, untransformed. Rotation and scale build up from
+/// one cell to the next in that order, so the last copy is turned and scaled the most
+pub fn placements(size: Vector2, options: &GridRepeatOptions) -> Vec<GridPlacement> {
+    let stride = size + options.spacing;
+    (0..options.rows)
+        .flat_map(|row| (0..options.columns).map(move |column| (row, column)))
+        .enumerate()
+        .map(|(step, (row, column))| GridPlacement {
+            offset: Vector2::new(column as f32 * stride.x, row as f32 * stride.y),
+            angle: (step as f32 * options.rotation_step).to_radians(),
+            scale: (1.0 + step as f32 * options.scale_step).max(GridRepeatOptions::MIN_SCALE),
+        })
+        .collect()
+}
+
+/// The camera that draws artwork centered on `center` where `placement` would put a copy of it, as seen through `camera`
+///
+/// `anchor` is the screenspace position of `center` moved by the placement's offset, as seen through `camera`
+///
+/// Strokes are drawn scaled along with the copy, though [`Editor::grid_repeat`][`crate::editor::Editor::grid_repeat`] leaves their widths alone
+pub fn preview_camera(camera: &Camera2D, anchor: Vector2, center: Vector2, placement: &GridPlacement) -> Camera2D {
+    Camera2D {
+        offset: anchor,
+        target: center,
+        rotation: camera.rotation + placement.angle.to_degrees(),
+        zoom: camera.zoom * placement.scale,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{document::Document, editor::{Editor, Selection}, layer::LayerContent};
+    use super::*;
+
+    #[test]
+    fn test_grid_placements() {
+        let options = GridRepeatOptions { columns: 3, rows: 2, spacing: Vector2::new(10.0, 5.0), rotation_step: 90.0, scale_step: -0.5 };
+        let placements = placements(Vector2::new(100.0, 50.0), &options);
+        assert_eq!(placements.len(), 6);
+        assert_eq!(placements[0], GridPlacement { offset: Vector2::zero(), angle: 0.0, scale: 1.0 });
+        assert_eq!(placements[2].offset, Vector2::new(220.0, 0.0));
+        assert_eq!(placements[3].offset, Vector2::new(0.0, 55.0), "cells should fill each row before the next");
+        assert!((placements[1].angle - std::f32::consts::FRAC_PI_2).abs() < 1e-5);
+        assert_eq!(placements[1].scale, 0.5);
+        assert_eq!(placements[5].scale, GridRepeatOptions::MIN_SCALE, "copies should never be scaled to nothing");
+    }
+
+    #[test]
+    fn test_grid_repeat() {
+        let mut editor = Editor::new_default(Document::new("repeat".to_owned()));
+        editor.add_curve_layer("line".to_owned(), crate::make_curve!((0,0) -> (100,0)));
+        editor.add_curve_layer("other".to_owned(), crate::make_curve!((0,50) -> (10,50)));
+        editor.set_selection(Selection::Layers(vec![vec![0]]));
+        editor.grid_repeat(&GridRepeatOptions { columns: 2, rows: 2, spacing: Vector2::new(10.0, 10.0), ..GridRepeatOptions::new() });
+
+        assert_eq!(editor.document.layers.len(), 3);
+        assert_eq!(editor.document.layers[0].name, "line", "the original should stay where it was");
+        let LayerContent::Group(group) = &editor.document.layers[1].content else { panic!("copies should be grouped") };
+        assert_eq!(group.layers.len(), 3, "the original fills the first cell");
+        let bounds = group.layers[0].control_bounds().unwrap();
+        assert!((bounds.x - 110.0).abs() < 1e-3 && bounds.y.abs() < 1e-3);
+        assert!(matches!(editor.selection(), Selection::Layers(paths) if paths == &[vec![1]]));
+    }
+}
@@ -0,0 +1,284 @@
+use std::collections::HashMap;
+use raylib::prelude::*;
+use crate::{curve::{Curve, WeakCurve}, document::Document, format, layer::{Layer, LayerContent, SampleQuality}, snapshot::DocumentSnapshot};
+
+/// How a layer differs between the saved and current versions of a document
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ChangeKind {
+    /// The layer only exists in the current version
+    Added,
+
+    /// The layer only exists in the saved version
+    Removed,
+
+    /// The layer's artwork was translated by the offset without otherwise changing
+    Moved(Vector2),
+
+    /// The layer's artwork was reshaped or replaced
+    Modified,
+}
+
+/// A layer that differs between the saved and current versions of a document
+#[derive(Debug, Clone)]
+pub struct LayerChange {
+    /// The path of the layer in the saved version; see [`Document::layer_at`]
+    ///
+    /// [`None`] if the layer was [added][`ChangeKind::Added`]
+    pub saved: Option<Vec<usize>>,
+
+    /// The path of the layer in the current version; see [`Document::layer_at`]
+    ///
+    /// [`None`] if the layer was [removed][`ChangeKind::Removed`]
+    pub current: Option<Vec<usize>>,
+
+    /// How the layer changed
+    pub kind: ChangeKind,
+}
+
+/// The "Compare with saved" view, overlaying the differences between a document and its file on disk
+///
+/// Layers are matched by their position in the layer tree and their name, so reordered or
+/// renamed layers show up as removed from their old place and added at their new one
+#[derive(Debug)]
+pub struct RevisionDiff {
+    /// The document as it was last saved
+    pub saved: Document,
+
+    /// The saved document's artwork, taken once since it never changes
+    saved_snapshot: DocumentSnapshot,
+}
+
+impl RevisionDiff {
+    /// Tint of layers only in the current version
+    pub const ADDED_TINT: Color = Color::LIME;
+
+    /// Tint of layers only in the saved version
+    pub const REMOVED_TINT: Color = Color::RED;
+
+    /// Tint of layers that were moved
+    pub const MOVED_TINT: Color = Color::SKYBLUE;
+
+    /// Tint of layers that were reshaped
+    pub const MODIFIED_TINT: Color = Color::ORANGE;
+
+    /// Opacity of the saved version of moved and modified layers, drawn behind the current version
+    pub const GHOST_ALPHA: f32 = 0.35;
+
+    /// How far apart (in worldspace pixels) points can be while still being considered the same
+    const EPSILON: f32 = 0.001;
+
+    /// Load the saved version of `current` from its file
+    pub fn load(rl: &mut RaylibHandle, thread: &RaylibThread, current: &Document) -> Result<Self, String> {
+        let path = current.file_path.as_deref().ok_or("document has never been saved")?;
+        let saved = format::load(rl, thread, path)?;
+        let saved_snapshot = DocumentSnapshot::of(&saved.layers);
+        Ok(Self { saved, saved_snapshot })
+    }
+
+    /// Get every layer that differs between the saved version and `current`
+    ///
+    /// Only leaf layers are compared; groups are descended into rather than reported themselves
+    pub fn changes(&self, current: &Document) -> Vec<LayerChange> {
+        let saved_leaves = leaves(&self.saved.layers);
+        let current_leaves = leaves(&current.layers);
+        let mut unmatched = saved_leaves.iter()
+            .map(|(path, layer)| (path.as_slice(), *layer))
+            .collect::<HashMap<_, _>>();
+
+        let mut changes = Vec::new();
+        for (path, layer) in &current_leaves {
+            let saved_layer = unmatched.get(path.as_slice())
+                .copied()
+                .filter(|saved_layer| saved_layer.name == layer.name);
+            let Some(saved_layer) = saved_layer else {
+                changes.push(LayerChange { saved: None, current: Some(path.clone()), kind: ChangeKind::Added });
+                continue;
+            };
+            unmatched.remove(path.as_slice());
+            if let Some(kind) = content_change(&saved_layer.content, &layer.content) {
+                changes.push(LayerChange { saved: Some(path.clone()), current: Some(path.clone()), kind });
+            }
+        }
+        // keep removals in document order
+        for (path, _) in &saved_leaves {
+            if unmatched.contains_key(path.as_slice()) {
+                changes.push(LayerChange { saved: Some(path.clone()), current: None, kind: ChangeKind::Removed });
+            }
+        }
+        changes
+    }
+
+    /// Draw `changes` (from [`RevisionDiff::changes`]) over the current document's artwork, in worldspace
+    ///
+    /// `snapshot` is of the current document's layers
+    pub fn draw(&self, d: &mut impl RaylibDraw, current: &Document, snapshot: &DocumentSnapshot, changes: &[LayerChange], quality: SampleQuality) {
+        for change in changes {
+            let saved_layer = change.saved.as_deref().and_then(|path| self.saved.layer_at(path));
+            let current_layer = change.current.as_deref().and_then(|path| current.layer_at(path));
+            let tint = match change.kind {
+                ChangeKind::Added => Self::ADDED_TINT,
+                ChangeKind::Removed => Self::REMOVED_TINT,
+                ChangeKind::Moved(_) => Self::MOVED_TINT,
+                ChangeKind::Modified => Self::MODIFIED_TINT,
+            };
+            match (saved_layer, current_layer) {
+                (Some(saved_layer), Some(current_layer)) => {
+                    saved_layer.draw(d, tint.alpha(Self::GHOST_ALPHA), quality, &self.saved_snapshot);
+                    current_layer.draw(d, tint, quality, snapshot);
+                }
+                (Some(layer), None) => layer.draw(d, tint, quality, &self.saved_snapshot),
+                (None, Some(layer)) => layer.draw(d, tint, quality, snapshot),
+                (None, None) => (),
+            }
+        }
+    }
+}
+
+/// Collect every non-group layer along with its path
+fn leaves(layers: &[Layer]) -> Vec<(Vec<usize>, &Layer)> {
+    fn visit<'a>(layers: &'a [Layer], prefix: &mut Vec<usize>, out: &mut Vec<(Vec<usize>, &'a Layer)>) {
+        for (i, layer) in layers.iter().enumerate() {
+            prefix.push(i);
+            match &layer.content {
+                LayerContent::Group(group) => visit(&group.layers, prefix, out),
+                _ => out.push((prefix.clone(), layer)),
+            }
+            prefix.pop();
+        }
+    }
+    let mut out = Vec::new();
+    visit(layers, &mut Vec::new(), &mut out);
+    out
+}
+
+/// Classify the translation between two positions, [`None`] if they are the same
+fn offset_change(old: Vector2, new: Vector2) -> Option<ChangeKind> {
+    let offset = new - old;
+    (offset.length() > RevisionDiff::EPSILON).then_some(ChangeKind::Moved(offset))
+}
+
+/// How the artwork of a curve changed, [`None`] if it didn't
+fn curve_change(old: &Curve, new: &Curve) -> Option<ChangeKind> {
+    if old.is_closed != new.is_closed || old.points.len() != new.points.len() {
+        return Some(ChangeKind::Modified);
+    }
+    let (first_old, first_new) = old.points.first().zip(new.points.first())?;
+    let offset = first_new.p - first_old.p;
+    let is_translated = old.points.iter()
+        .zip(&new.points)
+        .all(|(a, b)| {
+            (b.p - a.p - offset).norm() <= RevisionDiff::EPSILON &&
+            (b.c_in - a.c_in).norm() <= RevisionDiff::EPSILON &&
+            (b.c_out - a.c_out).norm() <= RevisionDiff::EPSILON
+        });
+    if is_translated {
+        offset_change(Vector2::zero(), Vector2::from(offset))
+    } else {
+        Some(ChangeKind::Modified)
+    }
+}
+
+/// How the artwork of a shared curve changed, [`None`] if it didn't
+fn weak_curve_change(old: &WeakCurve, new: &WeakCurve) -> Option<ChangeKind> {
+    let (Some(old), Some(new)) = (old.upgrade(), new.upgrade()) else { return Some(ChangeKind::Modified) };
+    let (old_lock, new_lock) = (old.lock(), new.lock());
+    curve_change(&old_lock.borrow(), &new_lock.borrow())
+}
+
+/// How the artwork of a layer changed, [`None`] if it didn't
+///
+/// Pixels are not compared; images are compared by their rectangle and paint by which tiles are allocated
+fn content_change(old: &LayerContent, new: &LayerContent) -> Option<ChangeKind> {
+    match (old, new) {
+        (LayerContent::Curve(old), LayerContent::Curve(new)) => weak_curve_change(old, new),
+
+        (LayerContent::Connector(old), LayerContent::Connector(new)) => weak_curve_change(&old.curve, &new.curve),
+
+        (LayerContent::Rectangle(old), LayerContent::Rectangle(new)) => weak_curve_change(&old.curve, &new.curve),
+
+        (LayerContent::Compound(old), LayerContent::Compound(new)) => {
+            if old.subpaths.len() != new.subpaths.len() {
+                return Some(ChangeKind::Modified);
+            }
+            let changes = old.subpaths.iter()
+                .zip(&new.subpaths)
+                .map(|(old, new)| weak_curve_change(old, new))
+                .collect::<Vec<_>>();
+            // only moved if every subpath moved together
+            match changes.first() {
+                Some(&first) if changes.iter().all(|&change| change == first) => first,
+                Some(_) => Some(ChangeKind::Modified),
+                None => None,
+            }
+        }
+
+        (LayerContent::Image { rect: old, .. }, LayerContent::Image { rect: new, .. }) => {
+            if old.width != new.width || old.height != new.height {
+                return Some(ChangeKind::Modified);
+            }
+            offset_change(Vector2::new(old.x, old.y), Vector2::new(new.x, new.y))
+        }
+
+        (LayerContent::Paint(old), LayerContent::Paint(new)) => {
+            if old.tiles.len() != new.tiles.len() || !old.tiles.keys().all(|coord| new.tiles.contains_key(coord)) {
+                return Some(ChangeKind::Modified);
+            }
+            offset_change(old.origin, new.origin)
+        }
+
+        _ => Some(ChangeKind::Modified),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use crate::{layer::Group, style::WeakStyle};
+    use super::*;
+
+    /// A document with a curve layer of `rect` named after each entry of `layers`, in a group if `is_grouped`
+    fn document(layers: &[(&str, Rectangle, bool)]) -> Document {
+        let mut document = Document::new("test".to_owned());
+        let mut top = Vec::new();
+        let mut grouped = Vec::new();
+        for &(name, rect, is_grouped) in layers {
+            let curve = Arc::downgrade(document.create_curve(Curve::from(rect)));
+            let layer = Layer { name: name.to_owned(), content: LayerContent::Curve(curve), style: WeakStyle::new(), export: None, is_template: false };
+            if is_grouped {
+                grouped.push(layer);
+            } else {
+                top.push(layer);
+            }
+        }
+        top.push(Layer { name: "group".to_owned(), content: LayerContent::Group(Group { layers: grouped }), style: WeakStyle::new(), export: None, is_template: false });
+        document.layers = top;
+        document
+    }
+
+    #[test]
+    fn test_changes() {
+        let square = Rectangle::new(0.0, 0.0, 10.0, 10.0);
+        let saved = document(&[("same", square, false), ("moved", square, false), ("reshaped", square, true), ("removed", square, true)]);
+        let diff = RevisionDiff { saved_snapshot: DocumentSnapshot::of(&saved.layers), saved };
+        let current = document(&[
+            ("same", square, false),
+            ("moved", Rectangle { x: 5.0, y: -2.0, ..square }, false),
+            ("reshaped", Rectangle { width: 20.0, ..square }, true),
+            ("added", square, true),
+        ]);
+
+        let changes = diff.changes(&current)
+            .into_iter()
+            .map(|change| (change.saved, change.current, change.kind))
+            .collect::<Vec<_>>();
+        assert_eq!(changes, [
+            (Some(vec![1]), Some(vec![1]), ChangeKind::Moved(Vector2::new(5.0, -2.0))),
+            (Some(vec![2, 0]), Some(vec![2, 0]), ChangeKind::Modified),
+            // layers are matched by name as well as position
+            (None, Some(vec![2, 1]), ChangeKind::Added),
+            (Some(vec![2, 1]), None, ChangeKind::Removed),
+        ]);
+
+        assert!(diff.changes(&diff.saved).is_empty(), "a document shouldn't differ from itself");
+    }
+}
@@ -0,0 +1,94 @@
+use raylib::prelude::*;
+use crate::curve::Curve;
+
+/// Points per segment the target curve is measured at
+const SPACING_RESOLUTION: u16 = 40;
+
+/// How copies are spread along a path by "Scatter Along Path"
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScatterOptions {
+    /// Number of copies placed
+    pub count: usize,
+
+    /// Whether each copy is turned to follow the direction of the path
+    pub is_rotating: bool,
+
+    /// Farthest (in worldspace pixels) a copy may be moved off its even spacing along each axis
+    pub position_jitter: f32,
+
+    /// Farthest (in degrees) a copy may be turned off its angle, in either direction
+    pub angle_jitter: f32,
+
+    /// Picks the jitter, so the same options always scatter the same way
+    pub seed: u64,
+}
+
+impl Default for ScatterOptions {
+    #[inline]
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+impl ScatterOptions {
+    /// Smallest number of copies
+    pub const MIN_COUNT: usize = 1;
+
+    /// Largest number of copies
+    pub const MAX_COUNT: usize = 1000;
+
+    /// Largest position jitter the dialog allows
+    pub const MAX_POSITION_JITTER: f32 = 100.0;
+
+    /// Largest angle jitter the dialog allows
+    pub const MAX_ANGLE_JITTER: f32 = 180.0;
+
+    /// Ten unjittered copies following the path, jittered by `seed` once jitter is added
+    pub const fn new(seed: u64) -> Self {
+        Self {
+            count: 10,
+            is_rotating: true,
+            position_jitter: 0.0,
+            angle_jitter: 0.0,
+            seed,
+        }
+    }
+}
+
+/// Tiny xorshift generator, so jitter is repeatable without pulling in a dependency
+#[derive(Debug, Clone, Copy)]
+struct Jitter(u64);
+
+impl Jitter {
+    /// Start generating from `seed`
+    const fn new(seed: u64) -> Self {
+        // xorshift gets stuck at zero
+        Self(if seed == 0 { 0x9E37_79B9_7F4A_7C15 } else { seed })
+    }
+
+    /// The next value, from -1 to 1
+    fn next(&mut self) -> f32 {
+        self.0 ^= self.0.wrapping_shl(13);
+        self.0 ^= self.0.wrapping_shr(7);
+        self.0 ^= self.0.wrapping_shl(17);
+        // the top 24 bits, scaled to 0..2
+        self.0.wrapping_shr(40) as f32 / 8_388_608.0 - 1.0
+    }
+}
+
+/// Where each copy scattered along `curve` goes: the worldspace position of its center and
+/// how far it turns (in radians, clockwise on screen)
+///
+/// Empty if the curve has no length
+pub fn placements(curve: &Curve, options: &ScatterOptions) -> Vec<(Vector2, f32)> {
+    let mut jitter = Jitter::new(options.seed);
+    curve.evenly_spaced::<SPACING_RESOLUTION>(options.count)
+        .into_iter()
+        .map(|(position, direction)| {
+            let angle = if options.is_rotating { direction.y.atan2(direction.x) } else { 0.0 };
+            let offset = Vector2::new(jitter.next(), jitter.next()) * options.position_jitter;
+            let turn = (jitter.next() * options.angle_jitter).to_radians();
+            (Vector2::new(position.x, position.y) + offset, angle + turn)
+        })
+        .collect()
+}
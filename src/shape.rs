@@ -0,0 +1,138 @@
+use raylib::prelude::*;
+use crate::curve::{Curve, CurvePoint, WeakCurve};
+
+/// Handle length of a quarter-circle bezier arc, as a fraction of its radius
+const KAPPA: f32 = 0.552_284_8;
+
+/// The direction the outline travels along each side meeting at each corner, clockwise from the top-left corner
+///
+/// Pairs the direction arriving at the corner with the direction leaving it
+const CORNER_DIRECTIONS: [(Vector2, Vector2); 4] = [
+    (Vector2 { x:  0.0, y: -1.0 }, Vector2 { x:  1.0, y:  0.0 }),
+    (Vector2 { x:  1.0, y:  0.0 }, Vector2 { x:  0.0, y:  1.0 }),
+    (Vector2 { x:  0.0, y:  1.0 }, Vector2 { x: -1.0, y:  0.0 }),
+    (Vector2 { x: -1.0, y:  0.0 }, Vector2 { x:  0.0, y: -1.0 }),
+];
+
+/// A rectangle whose corners can be rounded, drawn with a curve generated from its dimensions
+///
+/// Editing the anchors of the curve directly is undone the next time the curve is [regenerated][`LiveRectangle::regenerate`]
+#[derive(Debug)]
+pub struct LiveRectangle {
+    /// The outline generated from the rectangle
+    ///
+    /// Weakly references a curve stored at the [`Document`][`crate::document::Document`] level
+    pub curve: WeakCurve,
+
+    /// The worldspace rectangle before rounding
+    pub rect: Rectangle,
+
+    /// The radius of each corner, clockwise from the top-left, in worldspace pixels
+    ///
+    /// Radii larger than [`LiveRectangle::max_radius`] are drawn as that
+    pub radii: [f32; 4],
+}
+
+impl LiveRectangle {
+    /// The largest radius a corner can be rounded by, where the arcs of neighboring corners meet
+    pub fn max_radius(&self) -> f32 {
+        self.rect.width.min(self.rect.height).max(0.0) * 0.5
+    }
+
+    /// Rewrite the curve to match the rectangle and its radii
+    pub fn regenerate(&self) {
+        let Some(strong_curve) = self.curve.upgrade() else { return };
+        let curve_lock = strong_curve.lock();
+        *curve_lock.borrow_mut() = rounded_rectangle(self.rect, self.radii);
+    }
+
+    /// The worldspace position of the corner at `index`, clockwise from the top-left,
+    /// along with the diagonal pointing into the rectangle from it
+    pub fn corner(&self, index: usize) -> (Vector2, Vector2) {
+        let Rectangle { x, y, width, height } = self.rect;
+        let corner = [
+            Vector2::new(x, y),
+            Vector2::new(x + width, y),
+            Vector2::new(x + width, y + height),
+            Vector2::new(x, y + height),
+        ][index % 4];
+        let (arriving, leaving) = CORNER_DIRECTIONS[index % 4];
+        (corner, leaving - arriving)
+    }
+
+    /// The worldspace positions of the corner radius widgets, clockwise from the top-left
+    ///
+    /// Each widget sits on its corner's diagonal as far in as the radius, but at least `min_inset`, so it
+    /// doesn't cover the corner while the corner is sharp
+    pub fn widgets(&self, min_inset: f32) -> [Vector2; 4] {
+        let max_radius = self.max_radius();
+        std::array::from_fn(|index| {
+            let (corner, inward) = self.corner(index);
+            corner + inward * self.radii[index].min(max_radius).max(min_inset)
+        })
+    }
+
+    /// The radius that puts the widget of the corner at `index` nearest to the worldspace `point`
+    pub fn radius_toward(&self, index: usize, point: Vector2) -> f32 {
+        let (corner, inward) = self.corner(index);
+        let offset = point - corner;
+        // the diagonal is two units long, so the projection is halved
+        ((offset.x * inward.x + offset.y * inward.y) * 0.5).clamp(0.0, self.max_radius())
+    }
+}
+
+/// A closed curve tracing `rect` clockwise from its top-left corner, with each corner rounded by the matching radius
+///
+/// Corners with no radius are single anchors, so an unrounded rectangle matches [`Curve::from`] a [`Rectangle`]
+pub fn rounded_rectangle(rect: Rectangle, radii: [f32; 4]) -> Curve {
+    let shape = LiveRectangle { curve: WeakCurve::new(), rect, radii };
+    let max_radius = shape.max_radius();
+    let point = |c_in: Vector2, p: Vector2, c_out: Vector2| CurvePoint {
+        c_in: na::Vector2::new(c_in.x, c_in.y),
+        p: na::Vector2::new(p.x, p.y),
+        c_out: na::Vector2::new(c_out.x, c_out.y),
+    };
+    let mut points = Vec::with_capacity(8);
+    for (index, (arriving, leaving)) in CORNER_DIRECTIONS.into_iter().enumerate() {
+        let (corner, _) = shape.corner(index);
+        let radius = radii[index].clamp(0.0, max_radius);
+        if radius > 0.0 {
+            points.push(point(Vector2::zero(), corner - arriving * radius, arriving * (radius * KAPPA)));
+            points.push(point(leaving * -(radius * KAPPA), corner + leaving * radius, Vector2::zero()));
+        } else {
+            points.push(point(Vector2::zero(), corner, Vector2::zero()));
+        }
+    }
+    Curve { points, is_closed: true }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rounded_rectangle() {
+        let rect = Rectangle::new(10.0, 20.0, 100.0, 40.0);
+        let sharp = rounded_rectangle(rect, [0.0; 4]);
+        assert_eq!(sharp.points, Curve::from(rect).points);
+
+        // too large a radius is limited to half the shorter side
+        let rounded = rounded_rectangle(rect, [5.0, 0.0, 50.0, 0.0]);
+        let anchors = rounded.points.iter().map(|point| point.p).collect::<Vec<_>>();
+        assert_eq!(anchors, [
+            na::Vector2::new(10.0, 25.0),
+            na::Vector2::new(15.0, 20.0),
+            na::Vector2::new(110.0, 20.0),
+            na::Vector2::new(110.0, 40.0),
+            na::Vector2::new(90.0, 60.0),
+            na::Vector2::new(10.0, 60.0),
+        ]);
+        assert!((rounded.points[0].c_out.y + 5.0 * KAPPA).abs() < 1e-5);
+
+        let shape = LiveRectangle { curve: WeakCurve::new(), rect, radii: [5.0, 0.0, 50.0, 0.0] };
+        assert_eq!(shape.widgets(8.0)[0], Vector2::new(18.0, 28.0));
+        assert_eq!(shape.widgets(8.0)[2], Vector2::new(90.0, 40.0));
+        assert_eq!(shape.radius_toward(1, Vector2::new(100.0, 30.0)), 10.0);
+        assert_eq!(shape.radius_toward(1, Vector2::new(200.0, 0.0)), 0.0);
+    }
+}
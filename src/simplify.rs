@@ -0,0 +1,200 @@
+use std::ffi::CString;
+use raylib::prelude::*;
+use crate::{curve::{Curve, WeakCurve}, dialog::DialogResult, engine::EngineTheme, locale::{tr_c, tr_format}};
+
+/// Width of the panel
+pub const WIDTH: f32 = 440.0;
+
+/// Height of the panel
+pub const HEIGHT: f32 = 20.0;
+
+/// Padding between the edge of the panel and its controls, and between controls
+const PADDING: f32 = 2.0;
+
+/// Width of the label before the tolerance slider
+const LABEL_WIDTH: f32 = 64.0;
+
+/// Width of the tolerance to the right of the slider
+const VALUE_WIDTH: f32 = 40.0;
+
+/// Width of the anchor counts
+const COUNT_WIDTH: f32 = 96.0;
+
+/// Width of each of the OK and Cancel buttons
+const BUTTON_WIDTH: f32 = 48.0;
+
+/// Screenspace thickness of the heatmap
+const HEATMAP_THICKNESS: f32 = 3.0;
+
+/// Screenspace radius of the dots marking the simplified anchors
+const ANCHOR_RADIUS: f32 = 3.0;
+
+/// Points per segment the heatmap is measured at
+const HEATMAP_RESOLUTION: u16 = 10;
+
+/// The rectangle of the panel with its top-left corner at `corner`
+pub fn bounds(corner: Vector2) -> Rectangle {
+    Rectangle::new(corner.x, corner.y, WIDTH, HEIGHT)
+}
+
+/// The color of the heatmap where the simplified curve is `deviation` away from the original
+///
+/// Green within half of `tolerance`, shading through yellow to red at twice `tolerance` or more
+pub fn heat_color(deviation: f32, tolerance: f32) -> Color {
+    let heat = if tolerance > f32::EPSILON { (deviation / tolerance * 0.5).clamp(0.0, 1.0) } else { 1.0 };
+    // below a quarter heat is all the same green
+    let hue = 120.0 * (1.0 - ((heat - 0.25) / 0.75).max(0.0));
+    Color::color_from_hsv(hue, 1.0, 1.0)
+}
+
+/// One of the curves being simplified
+#[derive(Debug)]
+struct SimplifiedCurve {
+    /// The curve that gets replaced
+    original: WeakCurve,
+
+    /// The replacement at the current tolerance
+    simplified: Curve,
+
+    /// Points along `simplified` with their distance from the original
+    deviation: Vec<(na::Vector2<f32>, f32)>,
+}
+
+/// A Simplify in progress over the selected curves, previewed with a heatmap of how far it strays from them
+///
+/// Nothing changes until the preview is [applied][`SimplifyPreview::apply`]
+#[derive(Debug)]
+pub struct SimplifyPreview {
+    /// How far (in worldspace pixels) the points skipped over may be from the lines between the anchors kept
+    pub tolerance: f32,
+
+    /// The tolerance `curves` were simplified at
+    simplified_tolerance: f32,
+
+    curves: Vec<SimplifiedCurve>,
+}
+
+impl SimplifyPreview {
+    /// The tolerance a new preview starts at
+    pub const DEFAULT_TOLERANCE: f32 = 2.0;
+
+    /// The smallest tolerance the slider allows
+    pub const MIN_TOLERANCE: f32 = 0.1;
+
+    /// The largest tolerance the slider allows
+    pub const MAX_TOLERANCE: f32 = 50.0;
+
+    /// Start previewing the simplification of `curves`
+    ///
+    /// Curves appearing more than once are only simplified once
+    pub fn new(curves: Vec<WeakCurve>) -> Self {
+        let mut preview = Self {
+            tolerance: Self::DEFAULT_TOLERANCE,
+            simplified_tolerance: Self::DEFAULT_TOLERANCE,
+            curves: Vec::with_capacity(curves.len()),
+        };
+        for original in curves {
+            if !preview.curves.iter().any(|curve| curve.original.ptr_eq(&original)) {
+                preview.curves.push(SimplifiedCurve { original, simplified: Curve::new(), deviation: Vec::new() });
+            }
+        }
+        preview.resimplify();
+        preview
+    }
+
+    /// Simplify every curve again at the current tolerance
+    fn resimplify(&mut self) {
+        self.simplified_tolerance = self.tolerance;
+        for curve in &mut self.curves {
+            let Some(strong_curve) = curve.original.upgrade() else { continue };
+            let curve_lock = strong_curve.lock();
+            let original = curve_lock.borrow();
+            curve.simplified = original.simplified(self.tolerance);
+            curve.deviation = curve.simplified.deviation_from::<HEATMAP_RESOLUTION>(&original);
+        }
+    }
+
+    /// Whether there are no curves to simplify
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.curves.is_empty()
+    }
+
+    /// The total number of anchors before and after simplifying
+    pub fn anchor_counts(&self) -> (usize, usize) {
+        self.curves.iter()
+            .fold((0, 0), |(before, after), curve| {
+                let original = curve.original.upgrade().map_or(0, |strong_curve| strong_curve.lock().borrow().points.len());
+                (before.saturating_add(original), after.saturating_add(curve.simplified.points.len()))
+            })
+    }
+
+    /// Draw the simplified curves colored by how far they stray from the originals, in screenspace
+    pub fn draw(&self, d: &mut impl RaylibDraw, camera: &Camera2D) {
+        let to_screen = |point: na::Vector2<f32>| (Vector2::from(point) - camera.target) * camera.zoom + camera.offset;
+        for curve in &self.curves {
+            for pair in curve.deviation.windows(2) {
+                let ((start, start_deviation), (end, end_deviation)) = (pair[0], pair[1]);
+                let color = heat_color(start_deviation.max(end_deviation), self.tolerance);
+                d.draw_line_ex(to_screen(start), to_screen(end), HEATMAP_THICKNESS, color);
+            }
+            for point in &curve.simplified.points {
+                let center = to_screen(point.p);
+                d.draw_circle_v(center, ANCHOR_RADIUS, Color::WHITE);
+                d.draw_circle_lines(center.x as i32, center.y as i32, ANCHOR_RADIUS, Color::BLACK);
+            }
+        }
+    }
+
+    /// Draw the tolerance slider, anchor counts, and OK and Cancel buttons within `bounds`
+    ///
+    /// The curves are simplified again whenever the tolerance changes.
+    /// Returns [`Some`] once either button is clicked
+    pub fn draw_panel(&mut self, d: &mut impl RaylibDraw, bounds: Rectangle, theme: &EngineTheme) -> Option<DialogResult> {
+        d.draw_rectangle_rec(bounds, theme.color_panel);
+        d.draw_rectangle_lines_ex(bounds, 1.0, theme.color_panel_edge);
+        let row = Rectangle::new(bounds.x + PADDING, bounds.y + PADDING, bounds.width - PADDING * 2.0, bounds.height - PADDING * 2.0);
+
+        let label = Rectangle { width: LABEL_WIDTH, ..row };
+        d.gui_label(label, Some(tr_c("simplify.tolerance").as_c_str()));
+
+        let cancel = Rectangle { x: row.x + row.width - BUTTON_WIDTH, width: BUTTON_WIDTH, ..row };
+        let ok = Rectangle { x: cancel.x - PADDING - BUTTON_WIDTH, ..cancel };
+        let count = Rectangle { x: ok.x - PADDING - COUNT_WIDTH, width: COUNT_WIDTH, ..row };
+        let slider_x = label.x + label.width;
+        let slider = Rectangle { x: slider_x, width: count.x - PADDING - VALUE_WIDTH - slider_x, ..row };
+        let value = CString::new(format!("{:.1}px", self.tolerance)).expect("tolerance should not contain nul");
+        d.gui_slider(slider, None, Some(value.as_c_str()), &mut self.tolerance, Self::MIN_TOLERANCE, Self::MAX_TOLERANCE);
+        if self.tolerance != self.simplified_tolerance {
+            self.resimplify();
+        }
+
+        let (before, after) = self.anchor_counts();
+        let text = CString::new(tr_format("simplify.anchors", &[&before, &after])).unwrap_or_default();
+        d.gui_label(count, Some(text.as_c_str()));
+
+        if d.gui_button(ok, Some(tr_c("dialog.ok").as_c_str())) {
+            return Some(DialogResult::Confirm);
+        }
+        if d.gui_button(cancel, Some(tr_c("dialog.cancel").as_c_str())) {
+            return Some(DialogResult::Cancel);
+        }
+        None
+    }
+
+    /// Replace every original curve with its simplified version
+    ///
+    /// Curves are shared by every layer referencing them, so those layers are simplified too.
+    /// Returns the curves that were replaced
+    pub fn apply(self) -> Vec<WeakCurve> {
+        self.curves.into_iter()
+            .map(|curve| {
+                if let Some(strong_curve) = curve.original.upgrade() {
+                    let curve_lock = strong_curve.lock();
+                    *curve_lock.borrow_mut() = curve.simplified;
+                }
+                curve.original
+            })
+            .collect()
+    }
+}
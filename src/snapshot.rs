@@ -0,0 +1,190 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc, sync::Arc};
+use raylib::prelude::*;
+use crate::{curve::{Curve, WeakCurve}, fill::{self, FillRule}, layer::{Layer, LayerContent, SampleQuality}, style::{Style, WeakStyle}};
+
+/// The fill of some curves, tessellated from their copies in a [`DocumentSnapshot`]
+#[derive(Debug)]
+struct CachedFill {
+    /// The copies the fill was tessellated from, to tell when any of the curves has been edited since
+    curves: Vec<Arc<Curve>>,
+
+    /// The fill rule the curves were tessellated with
+    rule: FillRule,
+
+    /// The quality the curves were sampled at
+    quality: SampleQuality,
+
+    /// The triangles covering the inside of the curves
+    triangles: Rc<[[Vector2; 3]]>,
+}
+
+/// Immutable copies of the curves and styles referenced by some layers, as they were when the snapshot was taken
+///
+/// Rendering reads from a snapshot instead of locking each curve and style while drawing, so edits never
+/// contend with the render pass. Curves that haven't changed since the last [update][`DocumentSnapshot::update`]
+/// keep sharing their copy, so whether a curve was edited between frames can be told with [`Arc::ptr_eq`].
+/// Styles are small enough to copy every time
+///
+/// Entries are keyed by the address of the document's curve or style, like [`LodCache`][`crate::lod::LodCache`]
+#[derive(Debug, Default)]
+pub struct DocumentSnapshot {
+    curves: HashMap<*const (), Arc<Curve>>,
+    styles: HashMap<*const (), Arc<Style>>,
+
+    /// Tessellated [fills][`DocumentSnapshot::fill`], keyed by the addresses of the curves filled together
+    ///
+    /// Filled while drawing, which only has shared access to the snapshot
+    fills: RefCell<HashMap<Vec<*const ()>, CachedFill>>,
+}
+
+impl DocumentSnapshot {
+    /// Construct an empty snapshot
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Take a snapshot of everything `layers` reference, for drawing them once
+    pub fn of<'a>(layers: impl IntoIterator<Item = &'a Layer>) -> Self {
+        let mut snapshot = Self::new();
+        snapshot.update(layers);
+        snapshot
+    }
+
+    /// Retake the snapshot from `layers`, keeping the copies of curves that haven't changed
+    ///
+    /// Curves and styles no longer referenced by `layers` are dropped
+    pub fn update<'a>(&mut self, layers: impl IntoIterator<Item = &'a Layer>) {
+        fn visit(layer: &Layer, old_curves: &mut HashMap<*const (), Arc<Curve>>, snapshot: &mut DocumentSnapshot) {
+            if let Some(style) = layer.style.upgrade() {
+                let style_lock = style.lock();
+                let copy = Arc::new(style_lock.borrow().clone());
+                snapshot.styles.insert(Arc::as_ptr(&style) as *const (), copy);
+            }
+            match &layer.content {
+                LayerContent::Group(group) => {
+                    for layer in &group.layers {
+                        visit(layer, old_curves, snapshot);
+                    }
+                }
+                _ => for curve in layer.curves() {
+                    let key = curve.as_ptr() as *const ();
+                    if snapshot.curves.contains_key(&key) {
+                        continue;
+                    }
+                    let Some(strong_curve) = curve.upgrade() else { continue };
+                    let curve_lock = strong_curve.lock();
+                    let curve_borrow = curve_lock.borrow();
+                    let copy = match old_curves.remove(&key) {
+                        Some(copy) if copy.is_closed == curve_borrow.is_closed && copy.points == curve_borrow.points => copy,
+                        _ => Arc::new(curve_borrow.clone()),
+                    };
+                    snapshot.curves.insert(key, copy);
+                },
+            }
+        }
+
+        let mut old_curves = std::mem::take(&mut self.curves);
+        self.styles.clear();
+        for layer in layers {
+            visit(layer, &mut old_curves, self);
+        }
+        let curves = &self.curves;
+        self.fills.get_mut().retain(|keys, _| keys.iter().all(|key| curves.contains_key(key)));
+    }
+
+    /// The copy of `curve`
+    ///
+    /// [`None`] if it wasn't referenced by the layers the snapshot was taken of
+    pub fn curve(&self, curve: &WeakCurve) -> Option<&Arc<Curve>> {
+        self.curves.get(&(curve.as_ptr() as *const ()))
+    }
+
+    /// The copy of `style`
+    ///
+    /// [`None`] if it wasn't referenced by the layers the snapshot was taken of
+    pub fn style(&self, style: &WeakStyle) -> Option<&Arc<Style>> {
+        self.styles.get(&(style.as_ptr() as *const ()))
+    }
+
+    /// The triangles filling the closed curves among `curves` following `rule`, sampled within the tolerance of `quality`
+    ///
+    /// Open curves have no inside, so they are left out. The fill is tessellated once and reused until one of the curves
+    /// is edited or the rule or quality changes
+    pub fn fill(&self, curves: &[WeakCurve], rule: FillRule, quality: SampleQuality) -> Rc<[[Vector2; 3]]> {
+        let keys = curves.iter().map(|curve| curve.as_ptr() as *const ()).collect::<Vec<_>>();
+        let copies = curves.iter().filter_map(|curve| self.curve(curve)).cloned().collect::<Vec<_>>();
+        let mut fills = self.fills.borrow_mut();
+        if let Some(fill) = fills.get(&keys)
+            && fill.rule == rule
+            && fill.quality == quality
+            && fill.curves.len() == copies.len()
+            && fill.curves.iter().zip(&copies).all(|(a, b)| Arc::ptr_eq(a, b))
+        {
+            return Rc::clone(&fill.triangles);
+        }
+        let polygons = copies.iter()
+            .filter(|curve| curve.is_closed)
+            .map(|curve| {
+                let mut polygon = Vec::new();
+                curve.sample_into(&mut polygon, quality.tolerance());
+                polygon
+            })
+            .collect::<Vec<_>>();
+        let triangles = Rc::<[_]>::from(fill::tessellate(&polygons, rule));
+        fills.insert(keys, CachedFill { curves: copies, rule, quality, triangles: Rc::clone(&triangles) });
+        triangles
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{document::Document, editor::Editor};
+    use super::*;
+
+    #[test]
+    fn test_snapshot_copy_on_write() {
+        let mut editor = Editor::new_default(Document::new("snapshot".to_owned()));
+        editor.add_curve_layer("circle".to_owned(), crate::make_curve!((60,40) -> arc(0, 360, 30) -> cycle));
+        editor.add_curve_layer("line".to_owned(), crate::make_curve!((0,0) -> (100,0)));
+        let [circle, line] = [0, 1].map(|i| editor.document.layers[i].curves()[0].clone());
+
+        let mut snapshot = DocumentSnapshot::of(&editor.document.layers);
+        let first = [&circle, &line].map(|curve| snapshot.curve(curve).unwrap().clone());
+        assert!(snapshot.style(&editor.document.layers[0].style).is_some());
+
+        // edits don't reach a snapshot already taken
+        circle.upgrade().unwrap().lock().borrow_mut().translate(na::Vector2::new(10.0, 0.0));
+        assert_eq!(snapshot.curve(&circle).unwrap().points, first[0].points);
+
+        snapshot.update(&editor.document.layers);
+        assert!(!Arc::ptr_eq(snapshot.curve(&circle).unwrap(), &first[0]), "edited curve should be copied again");
+        assert!(Arc::ptr_eq(snapshot.curve(&line).unwrap(), &first[1]), "unchanged curve should keep its copy");
+
+        editor.document.layers.pop();
+        snapshot.update(&editor.document.layers);
+        assert!(snapshot.curve(&line).is_none(), "curves of removed layers should be dropped");
+    }
+
+    #[test]
+    fn test_fill_cache() {
+        let mut editor = Editor::new_default(Document::new("fill".to_owned()));
+        editor.add_curve_layer("square".to_owned(), crate::make_curve!((0,0) -> (10,0) -> (10,10) -> (0,10) -> cycle));
+        editor.add_curve_layer("open".to_owned(), crate::make_curve!((0,0) -> (10,0) -> (10,10)));
+        let square = editor.document.layers[0].curves();
+        let open = editor.document.layers[1].curves();
+
+        let mut snapshot = DocumentSnapshot::of(&editor.document.layers);
+        let first = snapshot.fill(&square, FillRule::NonZero, SampleQuality::Medium);
+        assert!(!first.is_empty());
+        assert!(snapshot.fill(&open, FillRule::NonZero, SampleQuality::Medium).is_empty(), "open curves shouldn't be filled");
+
+        snapshot.update(&editor.document.layers);
+        assert!(Rc::ptr_eq(&snapshot.fill(&square, FillRule::NonZero, SampleQuality::Medium), &first), "unchanged curves should keep their fill");
+        assert!(!Rc::ptr_eq(&snapshot.fill(&square, FillRule::EvenOdd, SampleQuality::Medium), &first), "a new rule should tessellate again");
+
+        square[0].upgrade().unwrap().lock().borrow_mut().translate(na::Vector2::new(10.0, 0.0));
+        snapshot.update(&editor.document.layers);
+        let moved = snapshot.fill(&square, FillRule::NonZero, SampleQuality::Medium);
+        assert!(moved.iter().flatten().all(|vertex| vertex.x >= 10.0 - 1e-3), "edited curves should be tessellated again");
+    }
+}
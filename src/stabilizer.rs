@@ -0,0 +1,123 @@
+use std::collections::VecDeque;
+use raylib::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// How brush input is smoothed before it is painted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum StabilizerMode {
+    /// Samples are used as-is
+    #[default]
+    Off,
+
+    /// Each sample is replaced by the average of the most recent samples
+    Average,
+
+    /// The brush trails behind the pointer on a string, only moving once the string is pulled taut
+    PullString,
+}
+
+impl StabilizerMode {
+    /// Every mode, in the order they are listed in the UI
+    pub const ALL: [Self; 3] = [Self::Off, Self::Average, Self::PullString];
+}
+
+/// The smoothing state of one brush stroke
+#[derive(Debug, Clone, Default)]
+pub struct Stabilizer {
+    /// The most recent samples, oldest first, for [`StabilizerMode::Average`]
+    samples: VecDeque<Vector2>,
+
+    /// Where the brush is, for [`StabilizerMode::PullString`]
+    ///
+    /// [`None`] before the first sample of the stroke
+    position: Option<Vector2>,
+}
+
+impl Stabilizer {
+    /// The number of samples averaged at full strength
+    pub const MAX_WINDOW: f32 = 32.0;
+
+    /// The length of the string at full strength, in screenspace pixels
+    pub const MAX_STRING_LENGTH: f32 = 80.0;
+
+    /// Construct a stabilizer for a new stroke
+    pub const fn new() -> Self {
+        Self {
+            samples: VecDeque::new(),
+            position: None,
+        }
+    }
+
+    /// Forget the current stroke, so the next sample starts a new one
+    pub fn reset(&mut self) {
+        self.samples.clear();
+        self.position = None;
+    }
+
+    /// Add the worldspace pointer `sample` to the stroke and get where the brush should be
+    ///
+    /// `strength` is from 0 (no smoothing) to 1 (the most smoothing), and `zoom` converts the string length to worldspace
+    pub fn push(&mut self, sample: Vector2, mode: StabilizerMode, strength: f32, zoom: f32) -> Vector2 {
+        let strength = strength.clamp(0.0, 1.0);
+        match mode {
+            StabilizerMode::Off => sample,
+
+            StabilizerMode::Average => {
+                let window = (1.0 + strength * (Self::MAX_WINDOW - 1.0)).round() as usize;
+                self.samples.push_back(sample);
+                while self.samples.len() > window {
+                    self.samples.pop_front();
+                }
+                let sum = self.samples.iter().fold(Vector2::zero(), |sum, &sample| sum + sample);
+                sum / self.samples.len() as f32
+            }
+
+            StabilizerMode::PullString => {
+                let length = strength * Self::MAX_STRING_LENGTH / zoom;
+                let position = match self.position {
+                    Some(position) if position.distance_to(sample) > length => {
+                        sample + (position - sample).normalized() * length
+                    }
+                    Some(position) => position,
+                    None => sample,
+                };
+                self.position = Some(position);
+                position
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_push_average() {
+        let mut stabilizer = Stabilizer::new();
+        assert_eq!(stabilizer.push(Vector2::new(7.0, 7.0), StabilizerMode::Off, 1.0, 1.0), Vector2::new(7.0, 7.0));
+
+        // a window of three samples
+        let strength = 2.0 / (Stabilizer::MAX_WINDOW - 1.0);
+        let averages = [0.0, 3.0, 6.0, 9.0]
+            .map(|x| stabilizer.push(Vector2::new(x, 0.0), StabilizerMode::Average, strength, 1.0).x);
+        assert_eq!(averages, [0.0, 1.5, 3.0, 6.0]);
+
+        // no strength averages nothing
+        assert_eq!(stabilizer.push(Vector2::new(20.0, 0.0), StabilizerMode::Average, 0.0, 1.0), Vector2::new(20.0, 0.0));
+    }
+
+    #[test]
+    fn test_push_pull_string() {
+        let mut stabilizer = Stabilizer::new();
+        // a string 20 worldspace pixels long at 2x zoom
+        let mut push = |x| stabilizer.push(Vector2::new(x, 0.0), StabilizerMode::PullString, 0.5, 2.0).x;
+        assert_eq!(push(0.0), 0.0, "the stroke starts at the first sample");
+        assert_eq!(push(10.0), 0.0, "the brush stays put while the string is slack");
+        assert_eq!(push(50.0), 30.0, "a taut string drags the brush behind the pointer");
+        assert_eq!(push(35.0), 30.0);
+
+        stabilizer.reset();
+        assert_eq!(stabilizer.push(Vector2::new(-4.0, 2.0), StabilizerMode::PullString, 0.5, 2.0), Vector2::new(-4.0, 2.0));
+    }
+}
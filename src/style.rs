@@ -2,6 +2,8 @@ use std::{cell::RefCell, sync::{Arc, Weak}};
 
 use parking_lot::ReentrantMutex;
 use raylib::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::{curve::Curve, fill::FillRule, layer::WeakRenderTexture2D};
 
 /// A color or texture that can be applied to a stroke or fill
 #[derive(Debug, Clone)]
@@ -13,7 +15,9 @@ pub enum Pattern {
     ///
     /// The texture can be painted to with [`RasterBrush`][`crate::editor::Tool::RasterBrush`],
     /// modifying all linked instances simultaneously
-    Texture(WeakRenderTexture2D),
+    ///
+    /// The transform places the texture within the bounds of each region it fills
+    Texture(WeakRenderTexture2D, TextureTransform),
 }
 
 impl Default for Pattern {
@@ -30,7 +34,128 @@ impl Pattern {
     }
 }
 
-#[derive(Debug, Clone, Copy, Default)]
+/// How a [texture pattern][`Pattern::Texture`] continues past the edges of its texture
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum TextureWrap {
+    /// The edge pixels of the texture stretch outward
+    Clamp,
+
+    /// The texture tiles
+    #[default]
+    Repeat,
+
+    /// The texture tiles, flipping every other tile so neighboring edges match
+    Mirror,
+}
+
+impl TextureWrap {
+    /// Every wrap mode, in the order they are listed in the UI
+    pub const ALL: [Self; 3] = [Self::Clamp, Self::Repeat, Self::Mirror];
+
+    /// Wrap the texture coordinate `x`, measured in texture widths, into `0.0..=1.0`
+    pub fn apply(self, x: f32) -> f32 {
+        match self {
+            Self::Clamp => x.clamp(0.0, 1.0),
+            Self::Repeat => x.rem_euclid(1.0),
+            Self::Mirror => {
+                let x = x.rem_euclid(2.0);
+                if x > 1.0 { 2.0 - x } else { x }
+            }
+        }
+    }
+}
+
+/// Where a [texture pattern][`Pattern::Texture`] sits within the bounds of a region it fills
+///
+/// Measured relative to the bounds, so the texture moves and stretches with the region.
+/// The identity transform stretches the texture exactly over the bounds
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TextureTransform {
+    /// The offset of the texture's center from the center of the bounds, as a fraction of the bounds' size
+    pub offset: Vector2,
+
+    /// The size of the texture as a fraction of the bounds' size
+    ///
+    /// Negative to flip the texture
+    pub scale: Vector2,
+
+    /// The clockwise rotation of the texture about its center, in degrees
+    pub rotation: f32,
+
+    /// How the texture continues past its edges
+    pub wrap: TextureWrap,
+}
+
+impl Default for TextureTransform {
+    #[inline]
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl TextureTransform {
+    /// Stretches the texture exactly over the bounds
+    pub const IDENTITY: Self = Self {
+        offset: Vector2 { x: 0.0, y: 0.0 },
+        scale: Vector2 { x: 1.0, y: 1.0 },
+        rotation: 0.0,
+        wrap: TextureWrap::Repeat,
+    };
+
+    /// Smallest magnitude of [`TextureTransform::scale`], so the texture can't collapse
+    pub const MIN_SCALE: f32 = 0.01;
+
+    /// The worldspace center of the texture within the worldspace `bounds`
+    pub fn center(&self, bounds: Rectangle) -> Vector2 {
+        Vector2::new(
+            bounds.x + bounds.width * (0.5 + self.offset.x),
+            bounds.y + bounds.height * (0.5 + self.offset.y),
+        )
+    }
+
+    /// Convert the worldspace `point` to texture coordinates, where the texture spans `0.0..=1.0` on each axis
+    ///
+    /// Not wrapped; see [`TextureWrap::apply`]
+    pub fn to_texture(&self, bounds: Rectangle, point: Vector2) -> Vector2 {
+        let local = (point - self.center(bounds)).rotated(-self.rotation.to_radians());
+        let size = Vector2::new(bounds.width * self.scale.x, bounds.height * self.scale.y);
+        Vector2::new(
+            if size.x != 0.0 { local.x / size.x + 0.5 } else { 0.5 },
+            if size.y != 0.0 { local.y / size.y + 0.5 } else { 0.5 },
+        )
+    }
+
+    /// Convert the texture coordinates `uv` to a worldspace point; the inverse of [`TextureTransform::to_texture`]
+    pub fn to_world(&self, bounds: Rectangle, uv: Vector2) -> Vector2 {
+        let local = Vector2::new((uv.x - 0.5) * bounds.width * self.scale.x, (uv.y - 0.5) * bounds.height * self.scale.y);
+        self.center(bounds) + local.rotated(self.rotation.to_radians())
+    }
+
+    /// Whether [`TextureTransform::cropped`] keeps a cropped texture exactly where it was in every region
+    ///
+    /// Tiling textures would repeat at the cropped size, and turns other than half turns are stretched by
+    /// the region's aspect ratio, so only clamped textures turned by a multiple of 180 degrees can be cropped
+    pub fn is_croppable(&self) -> bool {
+        self.wrap == TextureWrap::Clamp && self.rotation.rem_euclid(180.0) == 0.0
+    }
+
+    /// The transform placing the `crop` of the texture, in texture coordinates, where it was before cropping
+    ///
+    /// Only exact if the transform [is croppable][`TextureTransform::is_croppable`]
+    pub fn cropped(&self, crop: Rectangle) -> Self {
+        let center = Vector2::new(
+            (crop.x + crop.width * 0.5 - 0.5) * self.scale.x,
+            (crop.y + crop.height * 0.5 - 0.5) * self.scale.y,
+        );
+        Self {
+            offset: self.offset + center.rotated(self.rotation.to_radians()),
+            scale: Vector2::new(self.scale.x * crop.width, self.scale.y * crop.height),
+            ..*self
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
 pub struct WidthProfileVertex {
     /// The entry intensity of the thickness
     pub speed_in: f32,
@@ -62,7 +187,7 @@ impl WidthProfileVertex {
     }
 }
 
-#[derive(Debug, Clone, Copy, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
 pub struct WidthProfileControl {
     /// The t-value along the curve to which this control relates
     pub t: f32,
@@ -95,7 +220,7 @@ impl WidthProfileControl {
 }
 
 /// A curve representing the thickness of a stroke along a path
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum WidthProfile {
     Constant { inner: f32, outer: f32 },
     Variable(Vec<WidthProfileControl>),
@@ -128,11 +253,272 @@ impl WidthProfile {
     pub const fn new_variable() -> Self {
         Self::Variable(Vec::new())
     }
+
+    /// The inner and outer thickness at `t`, easing between neighboring controls with their speeds
+    ///
+    /// Controls are expected in ascending `t`; before the first and after the last, their thickness is held
+    pub fn thickness_at(&self, t: f32) -> (f32, f32) {
+        match self {
+            Self::Constant { inner, outer } => (*inner, *outer),
+            Self::Variable(controls) => {
+                let next = controls.partition_point(|control| control.t <= t);
+                match (next.checked_sub(1).and_then(|prev| controls.get(prev)), controls.get(next)) {
+                    (Some(a), Some(b)) if b.t > a.t => {
+                        let span = b.t - a.t;
+                        let u = (t - a.t) / span;
+                        (ease(&a.inner, &b.inner, u, span), ease(&a.outer, &b.outer, u, span))
+                    }
+                    (Some(control), _) | (None, Some(control)) => (control.inner.thick, control.outer.thick),
+                    (None, None) => (0.0, 0.0),
+                }
+            }
+        }
+    }
+
+    /// The thickness of the thickest side at the thickest point
+    ///
+    /// 0 for an empty variable-width profile
+    pub fn max_thickness(&self) -> f32 {
+        match self {
+            Self::Constant { inner, outer } => inner.max(*outer),
+            Self::Variable(controls) => controls.iter()
+                .map(|control| control.inner.thick.max(control.outer.thick))
+                .fold(0.0, f32::max),
+        }
+    }
+
+    /// Multiply every thickness by `factor`, keeping where along the path each control is
+    pub fn scale(&mut self, factor: f32) {
+        match self {
+            Self::Constant { inner, outer } => {
+                *inner *= factor;
+                *outer *= factor;
+            }
+            Self::Variable(controls) => for control in controls {
+                control.inner.thick *= factor;
+                control.outer.thick *= factor;
+            },
+        }
+    }
+}
+
+/// The thickness a fraction `u` of the way from `a` to `b`, `span` apart in t, leaving `a` at its exit speed and arriving at `b` at its entry speed
+fn ease(a: &WidthProfileVertex, b: &WidthProfileVertex, u: f32, span: f32) -> f32 {
+    // cubic hermite spline, with speeds as the slope of thickness over t
+    let (u2, u3) = (u * u, u * u * u);
+    let thick = (2.0 * u3 - 3.0 * u2 + 1.0) * a.thick
+        + (u3 - 2.0 * u2 + u) * a.speed_out * span
+        + (3.0 * u2 - 2.0 * u3) * b.thick
+        + (u3 - u2) * b.speed_in * span;
+    thick.max(0.0)
 }
 
 pub type StrongWidthProfile =  Arc<ReentrantMutex<RefCell<WidthProfile>>>;
 pub type WeakWidthProfile   = Weak<ReentrantMutex<RefCell<WidthProfile>>>;
 
+/// The portion of a path a [`Stroke`] is drawn along, for trim-path effects
+///
+/// Positions are fractions of the path's `t` range, where 0 is the first point and 1 is the last
+/// (or the first point again, for closed paths)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StrokeTrim {
+    /// Where the stroke starts
+    pub start: f32,
+
+    /// Where the stroke ends
+    ///
+    /// The stroke is hidden entirely if this is not after `start`
+    pub end: f32,
+
+    /// How far both ends are shifted along the path, wrapping around
+    ///
+    /// Animating this moves the stroked portion along the path without changing its length
+    pub offset: f32,
+}
+
+impl Default for StrokeTrim {
+    #[inline]
+    fn default() -> Self {
+        Self::FULL
+    }
+}
+
+impl StrokeTrim {
+    /// Stroke the entire path
+    pub const FULL: Self = Self {
+        start: 0.0,
+        end: 1.0,
+        offset: 0.0,
+    };
+
+    /// Whether the stroke covers the entire path
+    pub fn is_full(&self) -> bool {
+        self.end - self.start >= 1.0
+    }
+
+    /// Whether the stroke is drawn at the fraction `t` along the path
+    pub fn contains(&self, t: f32) -> bool {
+        if self.is_full() {
+            return true;
+        }
+        if self.end <= self.start {
+            return false;
+        }
+        // measure from the shifted start, wrapping around the path
+        let from_start = (t - self.start - self.offset).rem_euclid(1.0);
+        from_start <= self.end - self.start
+    }
+
+    /// The parts of `curve` the stroke covers, from the start of the stroke to its end
+    ///
+    /// The stroke continues around a closed curve past its first anchor, but is cut in two where it would wrap
+    /// around the ends of an open one
+    pub fn portions(&self, curve: &Curve) -> Vec<Curve> {
+        if self.is_full() {
+            return vec![curve.clone()];
+        }
+        if self.end <= self.start {
+            return Vec::new();
+        }
+        let start = (self.start + self.offset).rem_euclid(1.0);
+        let end = start + (self.end - self.start);
+        if end <= 1.0 || curve.is_closed {
+            vec![curve.portion(start, end)]
+        } else {
+            vec![curve.portion(start, 1.0), curve.portion(0.0, end - 1.0)]
+        }
+    }
+}
+
+/// The shape of a [`Marker`]
+///
+/// Shapes are outlined in marker space, where one unit is the width of the stroke and `+x` points
+/// along the path, away from the stroke
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub enum MarkerShape {
+    /// No marker
+    #[default]
+    None,
+
+    /// A filled triangle pointing away from the stroke
+    Arrow,
+
+    /// A chevron pointing away from the stroke
+    OpenArrow,
+
+    /// A circle centered on the end of the stroke
+    Dot,
+
+    /// A square centered on the end of the stroke
+    Square,
+
+    /// A line across the end of the stroke
+    Bar,
+
+    /// A closed polygon in marker space, filled with the non-zero rule
+    Custom(Vec<[f32; 2]>),
+}
+
+impl MarkerShape {
+    /// Every shape that can be chosen without defining a custom outline, in the order they are listed
+    pub const BUILT_IN: [Self; 6] = [Self::None, Self::Arrow, Self::OpenArrow, Self::Dot, Self::Square, Self::Bar];
+
+    /// Number of vertices in the outline of [`MarkerShape::Dot`]
+    const DOT_VERTICES: u16 = 16;
+
+    /// The closed outline of the shape in marker space
+    ///
+    /// Empty for [`MarkerShape::None`]
+    pub fn outline(&self) -> Vec<na::Vector2<f32>> {
+        let points: &[[f32; 2]] = match self {
+            Self::None => &[],
+            Self::Arrow => &[[2.0, 0.0], [-1.0, 1.5], [-1.0, -1.5]],
+            Self::OpenArrow => &[[2.0, 0.0], [-1.0, 1.5], [-1.0, 1.0], [1.0, 0.0], [-1.0, -1.0], [-1.0, -1.5]],
+            Self::Dot => {
+                return (0..Self::DOT_VERTICES)
+                    .map(|i| {
+                        let angle = f32::from(i) / f32::from(Self::DOT_VERTICES) * std::f32::consts::TAU;
+                        na::Vector2::new(angle.cos(), angle.sin())
+                    })
+                    .collect();
+            }
+            Self::Square => &[[1.0, 1.0], [-1.0, 1.0], [-1.0, -1.0], [1.0, -1.0]],
+            Self::Bar => &[[0.25, 1.5], [-0.25, 1.5], [-0.25, -1.5], [0.25, -1.5]],
+            Self::Custom(points) => points.as_slice(),
+        };
+        points.iter().map(|&[x, y]| na::Vector2::new(x, y)).collect()
+    }
+}
+
+/// A decoration drawn at one end of a [`Stroke`], like an arrowhead
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Marker {
+    /// The shape drawn
+    pub shape: MarkerShape,
+
+    /// How large the shape is drawn, relative to the width of the stroke
+    pub scale: f32,
+}
+
+impl Default for Marker {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Marker {
+    /// Smallest scale the style panel allows
+    pub const MIN_SCALE: f32 = 0.5;
+
+    /// Largest scale the style panel allows
+    pub const MAX_SCALE: f32 = 5.0;
+
+    /// Construct an absent marker
+    pub const fn new() -> Self {
+        Self {
+            shape: MarkerShape::None,
+            scale: 1.0,
+        }
+    }
+
+    /// The worldspace outline of the marker at the end of a stroke `width` wide, positioned at `end`
+    /// and pointing in the unit `direction`
+    pub fn outline_at(&self, end: na::Vector2<f32>, direction: na::Vector2<f32>, width: f32) -> Vec<na::Vector2<f32>> {
+        let size = self.scale * width;
+        let side = na::Vector2::new(-direction.y, direction.x);
+        self.shape.outline().into_iter()
+            .map(|point| end + (direction * point.x + side * point.y) * size)
+            .collect()
+    }
+}
+
+/// The [markers][`Marker`] drawn at the ends of a [`Stroke`]
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StrokeMarkers {
+    /// Drawn at the start of the stroked portion of the path, pointing backward
+    pub start: Marker,
+
+    /// Drawn at the end of the stroked portion of the path, pointing forward
+    pub end: Marker,
+}
+
+impl StrokeMarkers {
+    /// No markers at either end
+    pub const NONE: Self = Self {
+        start: Marker::new(),
+        end: Marker::new(),
+    };
+
+    /// Whether neither end has a marker
+    pub fn is_none(&self) -> bool {
+        self.start.shape == MarkerShape::None && self.end.shape == MarkerShape::None
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Stroke {
     /// The color pattern applied to the stroke
@@ -140,6 +526,12 @@ pub struct Stroke {
 
     /// The thickness curve of the stroke
     pub width: Option<WeakWidthProfile>,
+
+    /// The portion of the path that is stroked
+    pub trim: StrokeTrim,
+
+    /// Decorations drawn at the ends of the stroke
+    pub markers: StrokeMarkers,
 }
 
 impl Default for Stroke {
@@ -154,8 +546,44 @@ impl Stroke {
         Self {
             pattern: Pattern::new(),
             width: None,
+            trim: StrokeTrim::FULL,
+            markers: StrokeMarkers::NONE,
         }
     }
+
+    /// How far the stroke extends outside the path
+    ///
+    /// [`None`] if the stroke has no width profile or its width varies along the path
+    pub fn outer_width(&self) -> Option<f32> {
+        let profile = self.width.as_ref()?.upgrade()?;
+        let profile_lock = profile.lock();
+        let outer = match *profile_lock.borrow() {
+            WidthProfile::Constant { outer, .. } => Some(outer),
+            WidthProfile::Variable(_) => None,
+        };
+        outer
+    }
+
+    /// How wide the stroke is at the start of the path, or at its end if `is_end`
+    ///
+    /// 0 if the stroke has no width profile
+    pub fn end_width(&self, is_end: bool) -> f32 {
+        let Some(profile) = self.width.as_ref().and_then(Weak::upgrade) else { return 0.0 };
+        let profile_lock = profile.lock();
+        let width = match &*profile_lock.borrow() {
+            WidthProfile::Constant { inner, outer } => inner + outer,
+            WidthProfile::Variable(controls) => {
+                let mut by_t = controls.iter();
+                let control = if is_end {
+                    by_t.max_by(|a, b| a.t.total_cmp(&b.t))
+                } else {
+                    by_t.min_by(|a, b| a.t.total_cmp(&b.t))
+                };
+                control.map_or(0.0, |control| control.inner.thick + control.outer.thick)
+            }
+        };
+        width
+    }
 }
 
 /// A style modifier
@@ -225,6 +653,9 @@ pub struct Style {
     /// A transparent fill tells the renderer to skip filling the path
     pub fill: Pattern,
 
+    /// Which parts of overlapping subpaths are filled
+    pub fill_rule: FillRule,
+
     /// Every path must have at least one (possibly transparent, zero-width) stroke
     ///
     /// A transparent fill or zero-width thickness tells the renderer to skip outlining the path
@@ -243,13 +674,40 @@ impl Default for Style {
 }
 
 impl Style {
+    /// The fill color of the [default style][`Style::default_style`]
+    pub const DEFAULT_FILL: Color = Color::SLATEBLUE;
+
+    /// The stroke color of the [default style][`Style::default_style`]
+    pub const DEFAULT_STROKE: Color = Color::BLACK;
+
+    /// Iterate over the fill, stroke, and modifier patterns, in that order
+    pub fn patterns(&self) -> impl Iterator<Item = &Pattern> {
+        [&self.fill, &self.stroke.pattern].into_iter()
+            .chain(self.items.iter().map(|item| match &item.modifier {
+                Modifier::Fill(pattern) => pattern,
+                Modifier::Stroke(stroke) => &stroke.pattern,
+            }))
+    }
+
+    /// Iterate over the fill, stroke, and modifier patterns to change them, in that order
+    pub fn patterns_mut(&mut self) -> impl Iterator<Item = &mut Pattern> {
+        [&mut self.fill, &mut self.stroke.pattern].into_iter()
+            .chain(self.items.iter_mut().map(|item| match &mut item.modifier {
+                Modifier::Fill(pattern) => pattern,
+                Modifier::Stroke(stroke) => &mut stroke.pattern,
+            }))
+    }
+
     /// The style used when the user hasn't customized it
     pub const fn default_style(width: WeakWidthProfile) -> Self {
         Self {
-            fill: Pattern::Solid(Color::SLATEBLUE),
+            fill: Pattern::Solid(Self::DEFAULT_FILL),
+            fill_rule: FillRule::NonZero,
             stroke: Stroke {
-                pattern: Pattern::Solid(Color::BLACK),
+                pattern: Pattern::Solid(Self::DEFAULT_STROKE),
                 width: Some(width),
+                trim: StrokeTrim::FULL,
+                markers: StrokeMarkers::NONE,
             },
             items: Vec::new(),
         }
@@ -259,6 +717,7 @@ impl Style {
     pub const fn new() -> Self {
         Self {
             fill: Pattern::new(),
+            fill_rule: FillRule::NonZero,
             stroke: Stroke::new(),
             items: Vec::new(),
         }
@@ -267,3 +726,150 @@ impl Style {
 
 pub type StrongStyle =  Arc<ReentrantMutex<RefCell<Style>>>;
 pub type WeakStyle   = Weak<ReentrantMutex<RefCell<Style>>>;
+
+#[cfg(test)]
+mod tests {
+    use crate::curve::CurvePoint;
+    use super::*;
+
+    /// A strong width profile to weakly reference from a stroke
+    fn profile(profile: WidthProfile) -> StrongWidthProfile {
+        Arc::new(ReentrantMutex::new(RefCell::new(profile)))
+    }
+
+    #[test]
+    fn test_marker_outline_at() {
+        // pointing up the screen from (10, 10), on a stroke 3 wide
+        let marker = Marker { shape: MarkerShape::Arrow, scale: 2.0 };
+        let outline = marker.outline_at(na::Vector2::new(10.0, 10.0), na::Vector2::new(0.0, -1.0), 3.0);
+        let expected = [na::Vector2::new(10.0, -2.0), na::Vector2::new(19.0, 16.0), na::Vector2::new(1.0, 16.0)];
+        assert_eq!(outline.len(), expected.len());
+        for (point, expected) in outline.iter().zip(expected) {
+            assert!((point - expected).norm() < 1e-4, "{point} should be {expected}");
+        }
+
+        assert!(Marker::new().outline_at(na::Vector2::zeros(), na::Vector2::x(), 3.0).is_empty());
+    }
+
+    #[test]
+    fn test_end_width() {
+        let constant = profile(WidthProfile::Constant { inner: 1.0, outer: 2.0 });
+        let mut stroke = Stroke { width: Some(Arc::downgrade(&constant)), ..Stroke::new() };
+        assert_eq!((stroke.end_width(false), stroke.end_width(true)), (3.0, 3.0));
+
+        // the ends are the first and last controls along the path, whatever order they are stored in
+        let control = |t, inner, outer| WidthProfileControl { t, inner: WidthProfileVertex::flat(inner), outer: WidthProfileVertex::flat(outer) };
+        let variable = profile(WidthProfile::Variable(vec![control(1.0, 4.0, 0.0), control(0.0, 1.0, 1.0), control(0.5, 10.0, 10.0)]));
+        stroke.width = Some(Arc::downgrade(&variable));
+        assert_eq!((stroke.end_width(false), stroke.end_width(true)), (2.0, 4.0));
+
+        drop(variable);
+        assert_eq!(stroke.end_width(true), 0.0, "a dropped profile should have no width");
+        assert_eq!(Stroke::new().end_width(false), 0.0);
+    }
+
+    #[test]
+    fn test_stroke_trim_contains() {
+        let trim = |start, end, offset| StrokeTrim { start, end, offset };
+        assert!(StrokeTrim::FULL.is_full());
+        assert!(StrokeTrim::FULL.contains(0.0) && StrokeTrim::FULL.contains(1.0));
+
+        let empty = trim(0.0, 0.0, 0.0);
+        assert!(!empty.is_full());
+        assert!(!empty.contains(0.0) && !empty.contains(0.5), "a trim ending where it starts hides the stroke");
+
+        let reversed = trim(0.75, 0.25, 0.0);
+        assert!((0..=4).all(|i| !reversed.contains(i as f32 * 0.25)), "a trim ending before it starts hides the stroke");
+
+        let middle = trim(0.25, 0.75, 0.0);
+        assert!(middle.contains(0.25) && middle.contains(0.5) && middle.contains(0.75));
+        assert!(!middle.contains(0.1) && !middle.contains(0.9));
+
+        // shifted by half, the stroke wraps around from 0.75 to 0.25
+        let shifted = trim(0.25, 0.75, 0.5);
+        assert!(shifted.contains(0.9) && shifted.contains(0.1));
+        assert!(!shifted.contains(0.5));
+    }
+
+    #[test]
+    fn test_stroke_trim_portions() {
+        let trim = |start, end, offset| StrokeTrim { start, end, offset };
+        let point = |x| CurvePoint { c_in: na::Vector2::zeros(), p: na::Vector2::new(x, 0.0), c_out: na::Vector2::zeros() };
+        let anchors = |curve: &Curve| curve.points.iter().map(|point| point.p.x).collect::<Vec<_>>();
+        let open = Curve { points: vec![point(0.0), point(10.0), point(20.0)], is_closed: false };
+
+        let full = StrokeTrim::FULL.portions(&open);
+        assert_eq!(full.len(), 1);
+        assert_eq!(full[0].points, open.points);
+        assert!(trim(0.0, 0.0, 0.0).portions(&open).is_empty());
+        assert!(trim(0.75, 0.25, 0.0).portions(&open).is_empty());
+
+        let tail = trim(0.5, 1.0, 0.0).portions(&open);
+        assert_eq!(tail.iter().map(anchors).collect::<Vec<_>>(), [vec![10.0, 20.0]]);
+
+        // wrapping past the end of an open curve cuts the stroke in two
+        let cut = trim(0.0, 0.5, 0.75).portions(&open);
+        assert_eq!(cut.iter().map(anchors).collect::<Vec<_>>(), [vec![15.0, 20.0], vec![0.0, 5.0]]);
+
+        // while a closed curve's stroke continues past its first anchor
+        let closed = Curve { is_closed: true, ..open };
+        let around = trim(0.0, 0.5, 0.75).portions(&closed);
+        assert_eq!(around.len(), 1);
+        assert!(anchors(&around[0]).contains(&0.0));
+    }
+
+    #[test]
+    fn test_texture_wrap() {
+        let wrap = |mode: TextureWrap| [-0.25, 0.5, 1.25, 2.25].map(|x| mode.apply(x));
+        assert_eq!(wrap(TextureWrap::Clamp), [0.0, 0.5, 1.0, 1.0]);
+        assert_eq!(wrap(TextureWrap::Repeat), [0.75, 0.5, 0.25, 0.25]);
+        assert_eq!(wrap(TextureWrap::Mirror), [0.25, 0.5, 0.75, 0.25]);
+    }
+
+    #[test]
+    fn test_texture_transform() {
+        let bounds = Rectangle::new(0.0, 0.0, 100.0, 50.0);
+        let identity = TextureTransform::IDENTITY;
+        assert_eq!(identity.to_texture(bounds, Vector2::new(0.0, 0.0)), Vector2::new(0.0, 0.0));
+        assert_eq!(identity.to_texture(bounds, Vector2::new(100.0, 50.0)), Vector2::new(1.0, 1.0), "the identity should stretch over the bounds");
+
+        let transform = TextureTransform { offset: Vector2::new(0.25, 0.0), scale: Vector2::new(0.5, -2.0), rotation: 90.0, wrap: TextureWrap::Mirror };
+        assert_eq!(transform.center(bounds), Vector2::new(75.0, 25.0));
+        let right = transform.to_world(bounds, Vector2::new(1.0, 0.5));
+        assert!(right.distance_to(Vector2::new(75.0, 50.0)) < 1e-4, "rotating a quarter turn clockwise should turn the right edge downward, not {right:?}");
+        for point in [Vector2::new(0.0, 0.0), Vector2::new(75.0, 25.0), Vector2::new(-30.0, 140.0), Vector2::new(12.5, -7.0)] {
+            let round_trip = transform.to_world(bounds, transform.to_texture(bounds, point));
+            assert!(round_trip.distance_to(point) < 1e-3, "{point:?} came back as {round_trip:?}");
+        }
+        for uv in [Vector2::new(0.0, 0.0), Vector2::new(0.5, 0.5), Vector2::new(1.0, 0.25), Vector2::new(-2.0, 3.5)] {
+            let round_trip = transform.to_texture(bounds, transform.to_world(bounds, uv));
+            assert!(round_trip.distance_to(uv) < 1e-4, "{uv:?} came back as {round_trip:?}");
+        }
+
+        let flat = Rectangle::new(10.0, 10.0, 0.0, 20.0);
+        assert_eq!(identity.to_texture(flat, Vector2::new(50.0, 20.0)), Vector2::new(0.5, 0.5), "bounds with no width shouldn't divide by zero");
+    }
+
+    #[test]
+    fn test_texture_transform_cropped() {
+        let crop = Rectangle::new(0.25, 0.5, 0.5, 0.25);
+        for rotation in [0.0, 180.0, -180.0] {
+            let transform = TextureTransform { offset: Vector2::new(0.1, -0.2), scale: Vector2::new(0.5, -2.0), rotation, wrap: TextureWrap::Clamp };
+            assert!(transform.is_croppable());
+            let cropped = transform.cropped(crop);
+            // wide and tall regions stretch the texture differently, so the crop has to hold in both
+            for bounds in [Rectangle::new(0.0, 0.0, 100.0, 50.0), Rectangle::new(-20.0, 40.0, 10.0, 80.0)] {
+                for uv in [Vector2::new(0.25, 0.5), Vector2::new(0.75, 0.75), Vector2::new(0.4, 0.6)] {
+                    let cropped_uv = Vector2::new((uv.x - crop.x) / crop.width, (uv.y - crop.y) / crop.height);
+                    let (before, after) = (transform.to_world(bounds, uv), cropped.to_world(bounds, cropped_uv));
+                    assert!(before.distance_to(after) < 1e-3, "turned {rotation}, {uv:?} moved from {before:?} to {after:?}");
+                }
+            }
+        }
+
+        let tiled = TextureTransform { wrap: TextureWrap::Repeat, ..TextureTransform::IDENTITY };
+        assert!(!tiled.is_croppable(), "cropping a tiled texture would change how far apart its tiles are");
+        let turned = TextureTransform { wrap: TextureWrap::Clamp, rotation: 90.0, ..TextureTransform::IDENTITY };
+        assert!(!turned.is_croppable(), "a quarter turn depends on the region's aspect ratio");
+    }
+}
@@ -0,0 +1,92 @@
+use std::sync::Arc;
+use raylib::prelude::*;
+use crate::{document::Document, style::{Style, WeakStyle}};
+
+/// A named style that can be applied to layers in any document
+#[derive(Debug)]
+pub struct GraphicStyle {
+    /// The display name of the style, also used to find its linked copies in documents
+    pub name: String,
+
+    /// The appearance itself
+    ///
+    /// References resources owned by the [`StyleLibrary`]
+    pub style: Style,
+}
+
+/// Graphic styles shared by every editor
+#[derive(Debug)]
+pub struct StyleLibrary {
+    /// Owns the width profiles and rasters the graphic styles reference
+    resources: Document,
+
+    /// The graphic styles, in the order they were defined
+    pub styles: Vec<GraphicStyle>,
+}
+
+impl Default for StyleLibrary {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StyleLibrary {
+    /// Construct an empty style library without any allocations
+    pub const fn new() -> Self {
+        Self {
+            resources: Document::new(String::new()),
+            styles: Vec::new(),
+        }
+    }
+
+    /// Add a copy of `style` (which may reference resources in any document) as a new graphic style
+    ///
+    /// Returns the index of the new graphic style
+    pub fn define(&mut self, rl: &mut RaylibHandle, thread: &RaylibThread, name: String, style: &Style) -> Result<usize, String> {
+        let style = self.resources.clone_style_from(rl, thread, style)?;
+        let index = self.styles.len();
+        self.styles.push(GraphicStyle { name, style });
+        Ok(index)
+    }
+
+    /// Replace the graphic style at `index` with a copy of `style`, updating its linked copy in every one of `documents`
+    pub fn redefine<'a>(
+        &mut self,
+        rl: &mut RaylibHandle,
+        thread: &RaylibThread,
+        index: usize,
+        style: &Style,
+        documents: impl IntoIterator<Item = &'a mut Document>,
+    ) -> Result<(), String> {
+        let style = self.resources.clone_style_from(rl, thread, style)?;
+        let graphic_style = self.styles.get_mut(index).ok_or("graphic style index out of bounds")?;
+        graphic_style.style = style;
+        let graphic_style = &self.styles[index];
+        for document in documents {
+            let linked = document.linked_styles.iter()
+                .filter(|(name, _)| name == &graphic_style.name)
+                .filter_map(|(_, style)| style.upgrade())
+                .collect::<Vec<_>>();
+            for strong in linked {
+                let copy = document.clone_style_from(rl, thread, &graphic_style.style)?;
+                *strong.lock().borrow_mut() = copy;
+            }
+        }
+        Ok(())
+    }
+
+    /// Get the document's linked copy of the graphic style at `index`, copying it into the document if it hasn't been yet
+    pub fn link(&self, rl: &mut RaylibHandle, thread: &RaylibThread, index: usize, document: &mut Document) -> Result<WeakStyle, String> {
+        let graphic_style = self.styles.get(index).ok_or("graphic style index out of bounds")?;
+        if let Some((_, style)) = document.linked_styles.iter()
+            .find(|(name, style)| name == &graphic_style.name && style.strong_count() > 0)
+        {
+            return Ok(style.clone());
+        }
+        let copy = document.clone_style_from(rl, thread, &graphic_style.style)?;
+        let style = Arc::downgrade(document.create_style(copy));
+        document.linked_styles.push((graphic_style.name.clone(), style.clone()));
+        Ok(style)
+    }
+}
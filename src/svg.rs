@@ -0,0 +1,826 @@
+use std::{path::Path, sync::Arc};
+use raylib::prelude::*;
+use crate::{
+    curve::{Curve, CurveBuilder, CurvePoint},
+    document::{Artboard, Document},
+    fill::FillRule,
+    layer::{CompoundPath, Group, Layer, LayerContent},
+    ora,
+    style::{Pattern, Stroke, StrokeMarkers, StrokeTrim, Style, WidthProfile},
+};
+
+/// Presentation attributes that are inherited from parent elements
+#[derive(Debug, Clone, Copy)]
+pub struct Paint {
+    pub fill: Option<Color>,
+    pub fill_rule: FillRule,
+    pub stroke: Option<Color>,
+    pub stroke_width: f32,
+}
+
+impl Paint {
+    /// The SVG initial values: black non-zero fill, no stroke, 1 unit stroke width
+    const fn initial() -> Self {
+        Self {
+            fill: Some(Color::BLACK),
+            fill_rule: FillRule::NonZero,
+            stroke: None,
+            stroke_width: 1.0,
+        }
+    }
+
+    /// Apply a single presentation attribute, ignoring unrecognized ones
+    fn apply(&mut self, name: &str, value: &str) {
+        match name {
+            "fill" => if let Some(color) = parse_paint(value) { self.fill = color },
+            "fill-rule" => match value {
+                "nonzero" => self.fill_rule = FillRule::NonZero,
+                "evenodd" => self.fill_rule = FillRule::EvenOdd,
+                _ => (),
+            },
+            "stroke" => if let Some(color) = parse_paint(value) { self.stroke = color },
+            "stroke-width" => if let Some(width) = parse_length(value) { self.stroke_width = width },
+            _ => (),
+        }
+    }
+
+    /// Apply every presentation attribute of an element, including those in its `style` attribute
+    fn inherit(mut self, attributes: &[(&str, &str)]) -> Self {
+        for &(name, value) in attributes {
+            if name == "style" {
+                for declaration in value.split(';') {
+                    if let Some((name, value)) = declaration.split_once(':') {
+                        self.apply(name.trim(), value.trim());
+                    }
+                }
+            } else {
+                self.apply(name, value);
+            }
+        }
+        self
+    }
+}
+
+/// Parse a `fill`/`stroke` value
+///
+/// `Some(None)` for `none`, [`None`] if unrecognized
+fn parse_paint(value: &str) -> Option<Option<Color>> {
+    let value = value.trim();
+    if value == "none" {
+        return Some(None);
+    }
+    if let Some(hex) = value.strip_prefix('#') {
+        let expanded;
+        let hex = if hex.len() == 3 {
+            expanded = hex.chars().flat_map(|c| [c, c]).collect::<String>();
+            expanded.as_str()
+        } else {
+            hex
+        };
+        if hex.len() != 6 {
+            return None;
+        }
+        let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+        return Some(Some(Color::new(channel(0)?, channel(2)?, channel(4)?, 255)));
+    }
+    let color = match value {
+        "black" => Color::BLACK,
+        "white" => Color::WHITE,
+        "red" => Color::RED,
+        "green" => Color::GREEN,
+        "blue" => Color::BLUE,
+        "yellow" => Color::YELLOW,
+        "orange" => Color::ORANGE,
+        "purple" => Color::PURPLE,
+        "gray" | "grey" => Color::GRAY,
+        _ => return None,
+    };
+    Some(Some(color))
+}
+
+/// Parse a length, ignoring `px` units
+fn parse_length(value: &str) -> Option<f32> {
+    value.trim().trim_end_matches("px").parse().ok()
+}
+
+/// Split path data or point lists into commands and numbers
+struct Tokens<'a> {
+    src: &'a str,
+}
+
+impl<'a> Tokens<'a> {
+    fn new(src: &'a str) -> Self {
+        Self { src }
+    }
+
+    fn skip_separators(&mut self) {
+        self.src = self.src.trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+    }
+
+    /// Take the next command letter, if the next token is one
+    fn command(&mut self) -> Option<char> {
+        self.skip_separators();
+        let c = self.src.chars().next().filter(|c| c.is_ascii_alphabetic() && *c != 'e' && *c != 'E')?;
+        self.src = &self.src[c.len_utf8()..];
+        Some(c)
+    }
+
+    /// Take the next number, if the next token is one
+    fn number(&mut self) -> Option<f32> {
+        self.skip_separators();
+        let bytes = self.src.as_bytes();
+        let mut end = 0;
+        let mut has_dot = false;
+        let mut has_exponent = false;
+        while let Some(&b) = bytes.get(end) {
+            match b {
+                b'+' | b'-' if end == 0 || matches!(bytes[end - 1], b'e' | b'E') => (),
+                b'0'..=b'9' => (),
+                b'.' if !has_dot && !has_exponent => has_dot = true,
+                b'e' | b'E' if !has_exponent && end > 0 => has_exponent = true,
+                _ => break,
+            }
+            end += 1;
+        }
+        let value = self.src[..end].parse().ok()?;
+        self.src = &self.src[end..];
+        Some(value)
+    }
+
+    fn point(&mut self) -> Option<na::Vector2<f32>> {
+        Some(na::Vector2::new(self.number()?, self.number()?))
+    }
+}
+
+/// The angle the elliptical arc of an SVG `A` command from `from` to `to` starts at and the angle it sweeps, in radians,
+/// along with its radii, which are enlarged if they are too small to reach `to`
+///
+/// The angles are as [`CurveBuilder::arc`] takes them. [`None`] if the arc is degenerate and should be a straight line;
+/// see [the SVG implementation notes](https://www.w3.org/TR/SVG11/implnote.html#ArcImplementationNotes)
+fn arc_angles(from: na::Vector2<f32>, to: na::Vector2<f32>, radii: na::Vector2<f32>, rotation: f32, is_large_arc: bool, is_sweep: bool) -> Option<(f32, f32, na::Vector2<f32>)> {
+    let mut radii = radii.abs();
+    if radii.x <= f32::EPSILON || radii.y <= f32::EPSILON || (to - from).norm() <= f32::EPSILON {
+        return None;
+    }
+    // the midpoint between the ends, in the ellipse's unrotated frame
+    let mid = na::Rotation2::new(-rotation) * ((from - to) * 0.5);
+    let scale = (mid.x / radii.x).powi(2) + (mid.y / radii.y).powi(2);
+    if scale > 1.0 {
+        radii *= scale.sqrt();
+    }
+    let (rx2, ry2) = (radii.x * radii.x, radii.y * radii.y);
+    let (mx2, my2) = (mid.x * mid.x, mid.y * mid.y);
+    let sign = if is_large_arc == is_sweep { -1.0 } else { 1.0 };
+    let coefficient = sign * ((rx2 * ry2 - rx2 * my2 - ry2 * mx2) / (rx2 * my2 + ry2 * mx2)).max(0.0).sqrt();
+    let center = na::Vector2::new(coefficient * radii.x * mid.y / radii.y, -coefficient * radii.y * mid.x / radii.x);
+    let start = ((mid.y - center.y) / radii.y).atan2((mid.x - center.x) / radii.x);
+    let end = ((-mid.y - center.y) / radii.y).atan2((-mid.x - center.x) / radii.x);
+    let mut sweep = (end - start).rem_euclid(std::f32::consts::TAU);
+    if !is_sweep && sweep > 0.0 {
+        sweep -= std::f32::consts::TAU;
+    }
+    Some((start, sweep, radii))
+}
+
+/// Parse SVG path data (the `d` attribute) into one curve per subpath
+pub fn parse_path_data(d: &str) -> Vec<Curve> {
+    let mut curves = Vec::new();
+    let mut curve = Curve::new();
+    let mut tokens = Tokens::new(d);
+    let mut current = na::Vector2::zeros();
+    let mut start = na::Vector2::zeros();
+    // the second control point of the previous segment, for smooth curve reflection
+    let mut last_control: Option<na::Vector2<f32>> = None;
+    let mut command = 'M';
+
+    /// Append a cubic segment from the last point to `p`, with absolute control points `c1` and `c2`
+    fn push_segment(curve: &mut Curve, c1: na::Vector2<f32>, c2: na::Vector2<f32>, p: na::Vector2<f32>) {
+        if let Some(prev) = curve.points.last_mut() {
+            prev.c_out = c1 - prev.p;
+        }
+        curve.points.push(CurvePoint { c_in: c2 - p, p, c_out: na::Vector2::zeros() });
+    }
+
+    fn finish(curves: &mut Vec<Curve>, curve: &mut Curve) {
+        if curve.points.len() > 1 {
+            curves.push(std::mem::take(curve));
+        } else {
+            curve.points.clear();
+        }
+        curve.is_closed = false;
+    }
+
+    loop {
+        if let Some(c) = tokens.command() {
+            command = c;
+        } else if tokens.src.trim().is_empty() {
+            break;
+        }
+        let is_relative = command.is_ascii_lowercase();
+        let origin = if is_relative { current } else { na::Vector2::zeros() };
+
+        match command.to_ascii_uppercase() {
+            'M' => {
+                let Some(p) = tokens.point() else { break };
+                finish(&mut curves, &mut curve);
+                current = origin + p;
+                start = current;
+                curve.points.push(CurvePoint { c_in: na::Vector2::zeros(), p: current, c_out: na::Vector2::zeros() });
+                // subsequent pairs are implicit lineto commands
+                command = if is_relative { 'l' } else { 'L' };
+                last_control = None;
+            }
+
+            'L' => {
+                let Some(p) = tokens.point() else { break };
+                let p = origin + p;
+                push_segment(&mut curve, current, p, p);
+                current = p;
+                last_control = None;
+            }
+
+            'H' => {
+                let Some(x) = tokens.number() else { break };
+                let p = na::Vector2::new(origin.x + x, current.y);
+                push_segment(&mut curve, current, p, p);
+                current = p;
+                last_control = None;
+            }
+
+            'V' => {
+                let Some(y) = tokens.number() else { break };
+                let p = na::Vector2::new(current.x, origin.y + y);
+                push_segment(&mut curve, current, p, p);
+                current = p;
+                last_control = None;
+            }
+
+            'C' | 'S' => {
+                let c1 = if command.to_ascii_uppercase() == 'C' {
+                    let Some(c1) = tokens.point() else { break };
+                    origin + c1
+                } else {
+                    last_control.map_or(current, |c| current * 2.0 - c)
+                };
+                let (Some(c2), Some(p)) = (tokens.point(), tokens.point()) else { break };
+                let (c2, p) = (origin + c2, origin + p);
+                push_segment(&mut curve, c1, c2, p);
+                current = p;
+                last_control = Some(c2);
+            }
+
+            'Q' | 'T' => {
+                let q = if command.to_ascii_uppercase() == 'Q' {
+                    let Some(q) = tokens.point() else { break };
+                    origin + q
+                } else {
+                    last_control.map_or(current, |c| current * 2.0 - c)
+                };
+                let Some(p) = tokens.point() else { break };
+                let p = origin + p;
+                // elevate the quadratic to a cubic
+                let c1 = current + (q - current) * (2.0 / 3.0);
+                let c2 = p + (q - p) * (2.0 / 3.0);
+                push_segment(&mut curve, c1, c2, p);
+                current = p;
+                last_control = Some(q);
+            }
+
+            'A' => {
+                let (Some(rx), Some(ry), Some(rotation), Some(large_arc), Some(sweep), Some(p)) =
+                    (tokens.number(), tokens.number(), tokens.number(), tokens.number(), tokens.number(), tokens.point())
+                else { break };
+                let p = origin + p;
+                let rotation = rotation.to_radians();
+                match arc_angles(current, p, na::Vector2::new(rx, ry), rotation, large_arc != 0.0, sweep != 0.0) {
+                    Some((start, sweep, radii)) => {
+                        let arc = CurveBuilder::new()
+                            .corner(current)
+                            .arc(start, start + sweep, radii, rotation)
+                            .build();
+                        let mut points = arc.points.into_iter();
+                        if let (Some(prev), Some(first)) = (curve.points.last_mut(), points.next()) {
+                            prev.c_out = first.c_out;
+                        }
+                        curve.points.extend(points);
+                        // land exactly on the endpoint, so a closepath can tell it returned to the start
+                        if let Some(last) = curve.points.last_mut() {
+                            last.p = p;
+                        }
+                    }
+                    None => push_segment(&mut curve, current, p, p),
+                }
+                current = p;
+                last_control = None;
+            }
+
+            'Z' => {
+                // a closing point on top of the start is redundant with `is_closed`
+                if curve.points.len() > 1
+                    && let Some(last) = curve.points.last()
+                    && (last.p - start).norm() < f32::EPSILON
+                {
+                    let last = curve.points.pop().expect("should have at least two points");
+                    curve.points[0].c_in = last.c_in;
+                }
+                curve.is_closed = true;
+                finish(&mut curves, &mut curve);
+                // drawing can continue from the start point without a moveto
+                current = start;
+                curve.points.push(CurvePoint { c_in: na::Vector2::zeros(), p: current, c_out: na::Vector2::zeros() });
+                last_control = None;
+                // closepath takes no arguments, so it can't repeat implicitly
+                command = '\0';
+            }
+
+            _ => break,
+        }
+    }
+    finish(&mut curves, &mut curve);
+    curves
+}
+
+/// Parse the points attribute of `<polyline>` and `<polygon>`
+fn parse_points(points: &str, is_closed: bool) -> Curve {
+    let mut tokens = Tokens::new(points);
+    let mut builder = CurveBuilder::new();
+    while let Some(p) = tokens.point() {
+        builder = builder.corner(p);
+    }
+    if is_closed { builder.cycle() } else { builder.build() }
+}
+
+/// Construct a closed curve approximating an ellipse with four cubic segments
+fn ellipse(cx: f32, cy: f32, rx: f32, ry: f32) -> Curve {
+    // distance of the control points from the anchors for a circular arc of 90 degrees
+    const KAPPA: f32 = 0.552_284_8;
+    let (kx, ky) = (rx * KAPPA, ry * KAPPA);
+    CurveBuilder::new()
+        .smooth(na::Vector2::new(cx, cy - ry), na::Vector2::new( kx, 0.0))
+        .smooth(na::Vector2::new(cx + rx, cy), na::Vector2::new(0.0,  ky))
+        .smooth(na::Vector2::new(cx, cy + ry), na::Vector2::new(-kx, 0.0))
+        .smooth(na::Vector2::new(cx - rx, cy), na::Vector2::new(0.0, -ky))
+        .cycle()
+}
+
+/// A tag in the source text
+struct Tag<'a> {
+    name: &'a str,
+    attributes: Vec<(&'a str, &'a str)>,
+    is_closing: bool,
+    is_self_closing: bool,
+}
+
+impl<'a> Tag<'a> {
+    fn attribute(&self, name: &str) -> Option<&'a str> {
+        self.attributes.iter().find(|(n, _)| *n == name).map(|(_, v)| *v)
+    }
+
+    fn number(&self, name: &str) -> f32 {
+        self.attribute(name).and_then(parse_length).unwrap_or(0.0)
+    }
+}
+
+/// Iterate over the element tags of an XML document, skipping comments, declarations, and text
+fn tags(src: &str) -> impl Iterator<Item = Tag<'_>> {
+    src.split('<').skip(1).filter_map(|chunk| {
+        let (inner, _text) = chunk.split_once('>')?;
+        if inner.starts_with('?') || inner.starts_with('!') {
+            return None;
+        }
+        let is_closing = inner.starts_with('/');
+        let is_self_closing = inner.ends_with('/');
+        let inner = inner.trim_start_matches('/').trim_end_matches('/');
+        let name_end = inner.find(char::is_whitespace).unwrap_or(inner.len());
+        let (name, mut rest) = inner.split_at(name_end);
+        let mut attributes = Vec::new();
+        while let Some((key, after)) = rest.split_once('=') {
+            let after = after.trim_start();
+            let quote = after.chars().next()?;
+            let (value, after) = after[quote.len_utf8()..].split_once(quote)?;
+            attributes.push((key.trim(), value));
+            rest = after;
+        }
+        Some(Tag { name, attributes, is_closing, is_self_closing })
+    })
+}
+
+/// A layer of `curves` painted with `paint`, with the curves and a new style added to `document`
+///
+/// Several curves become one compound path, which may have holes. [`None`] if there are no curves
+pub fn paint_layer(document: &mut Document, name: String, curves: Vec<Curve>, paint: &Paint) -> Option<Layer> {
+    let stroke_width = paint.stroke.map(|_| {
+        Arc::downgrade(document.create_width_profile(WidthProfile::new_flat(paint.stroke_width * 0.5)))
+    });
+    let mut curves = curves.into_iter()
+        .map(|curve| Arc::downgrade(document.create_curve(curve)))
+        .collect::<Vec<_>>();
+    let content = match curves.len() {
+        0 => return None,
+        1 => LayerContent::Curve(curves.remove(0)),
+        _ => LayerContent::Compound(CompoundPath { subpaths: curves }),
+    };
+    let style = Arc::downgrade(document.create_style(Style {
+        fill: paint.fill.map_or(Pattern::new(), Pattern::Solid),
+        fill_rule: paint.fill_rule,
+        stroke: Stroke {
+            pattern: paint.stroke.map_or(Pattern::new(), Pattern::Solid),
+            width: stroke_width,
+            trim: StrokeTrim::FULL,
+            markers: StrokeMarkers::NONE,
+        },
+        items: Vec::new(),
+    }));
+    Some(Layer { name, content, style, export: None, is_template: false })
+}
+
+/// A group layer of `layers`, with a new unstyled style added to `document`
+pub fn group_layer(document: &mut Document, name: String, layers: Vec<Layer>) -> Layer {
+    let style = Arc::downgrade(document.create_style(Style::new()));
+    Layer {
+        name,
+        content: LayerContent::Group(Group { layers }),
+        style,
+        export: None,
+        is_template: false,
+    }
+}
+
+/// Read an SVG file into a new document
+///
+/// Supports `<path>`, `<rect>`, `<circle>`, `<ellipse>`, `<line>`, `<polyline>`, `<polygon>`, and `<g>`
+/// with solid fills, fill rules, and strokes. Transforms, gradients, and text are ignored.
+pub fn import(path: &Path) -> Result<Document, String> {
+    let src = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let title = path.file_stem().unwrap_or_default().to_string_lossy().into_owned();
+    let mut document = Document::new(title);
+
+    // each open `<g>` has its own layer list and inherited paint
+    let mut stack: Vec<(String, Vec<Layer>, Paint)> = vec![(String::new(), Vec::new(), Paint::initial())];
+
+    for tag in tags(&src) {
+        let paint = stack.last().expect("root should never be popped").2;
+        if tag.is_closing {
+            if tag.name == "g" && stack.len() > 1 {
+                let (name, layers, _) = stack.pop().expect("should have a group to close");
+                let group = group_layer(&mut document, name, layers);
+                stack.last_mut().expect("root should never be popped").1.push(group);
+            }
+            continue;
+        }
+
+        let curves = match tag.name {
+            "svg" => {
+                let rect = match tag.attribute("viewBox").map(|view_box| {
+                    let mut tokens = Tokens::new(view_box);
+                    (tokens.number(), tokens.number(), tokens.number(), tokens.number())
+                }) {
+                    Some((Some(x), Some(y), Some(width), Some(height))) => Rectangle::new(x, y, width, height),
+                    _ => Rectangle::new(0.0, 0.0, tag.number("width"), tag.number("height")),
+                };
+                if rect.width > 0.0 && rect.height > 0.0 {
+                    document.artboards.push(Artboard::new(document.title.clone(), rect));
+                }
+                continue;
+            }
+
+            "g" => {
+                if !tag.is_self_closing {
+                    let name = tag.attribute("id").unwrap_or("group").to_owned();
+                    stack.push((name, Vec::new(), paint.inherit(&tag.attributes)));
+                }
+                continue;
+            }
+
+            "path" => parse_path_data(tag.attribute("d").unwrap_or_default()),
+            "rect" => vec![Curve::from(Rectangle::new(tag.number("x"), tag.number("y"), tag.number("width"), tag.number("height")))],
+            "circle" => vec![ellipse(tag.number("cx"), tag.number("cy"), tag.number("r"), tag.number("r"))],
+            "ellipse" => vec![ellipse(tag.number("cx"), tag.number("cy"), tag.number("rx"), tag.number("ry"))],
+            "line" => vec![parse_points(&format!("{} {} {} {}", tag.number("x1"), tag.number("y1"), tag.number("x2"), tag.number("y2")), false)],
+            "polyline" => vec![parse_points(tag.attribute("points").unwrap_or_default(), false)],
+            "polygon" => vec![parse_points(tag.attribute("points").unwrap_or_default(), true)],
+            _ => continue,
+        };
+
+        let paint = paint.inherit(&tag.attributes);
+        let name = tag.attribute("id").unwrap_or(tag.name).to_owned();
+        // a path with several subpaths is one shape, which may have holes
+        if let Some(layer) = paint_layer(&mut document, name, curves, &paint) {
+            stack.last_mut().expect("root should never be popped").1.push(layer);
+        }
+    }
+
+    // close any groups left open by malformed input
+    while stack.len() > 1 {
+        let (name, layers, _) = stack.pop().expect("should have a group to close");
+        let group = group_layer(&mut document, name, layers);
+        stack.last_mut().expect("root should never be popped").1.push(group);
+    }
+    document.layers = stack.pop().expect("root should never be popped").1;
+    Ok(document)
+}
+
+/// An SVG attribute value for `pattern`, along with its opacity if it isn't opaque
+///
+/// Textures have no SVG equivalent, so they are left unpainted
+fn paint_attribute(pattern: &Pattern) -> (String, Option<f32>) {
+    match pattern {
+        Pattern::Solid(color) if color.a > 0 => (
+            format!("#{:02x}{:02x}{:02x}", color.r, color.g, color.b),
+            (color.a < 255).then(|| f32::from(color.a) / 255.0),
+        ),
+        _ => ("none".to_owned(), None),
+    }
+}
+
+/// Append the SVG element of `layer` to `out`, indented by `depth`
+///
+/// [Templates][`Layer::is_template`] are skipped
+fn write_layer(out: &mut String, layer: &Layer, depth: usize) {
+    use std::fmt::Write;
+    if layer.is_template {
+        return;
+    }
+    let indent = "  ".repeat(depth);
+    match &layer.content {
+        LayerContent::Group(group) => {
+            _ = writeln!(out, "{indent}<g>");
+            for layer in &group.layers {
+                write_layer(out, layer, depth + 1);
+            }
+            _ = writeln!(out, "{indent}</g>");
+        }
+
+        LayerContent::Curve(_) | LayerContent::Compound(_) | LayerContent::Connector(_) | LayerContent::Rectangle(_) => {
+            let curves = layer.curves().iter()
+                .filter_map(|curve| curve.upgrade())
+                .map(|curve| curve.lock().borrow().clone())
+                .filter(|curve| !curve.points.is_empty())
+                .collect::<Vec<_>>();
+            if curves.is_empty() {
+                return;
+            }
+            let Some(style) = layer.style.upgrade() else { return };
+            let style_lock = style.lock();
+            let style = style_lock.borrow();
+            let path_data = |curves: &[Curve]| curves.iter()
+                .filter(|curve| !curve.points.is_empty())
+                .map(Curve::to_svg_path_data)
+                .collect::<Vec<_>>()
+                .join(" ");
+
+            let (fill, fill_opacity) = paint_attribute(&style.fill);
+            let mut fill_attributes = format!(" fill=\"{fill}\"");
+            if let Some(opacity) = fill_opacity {
+                _ = write!(fill_attributes, " fill-opacity=\"{opacity}\"");
+            }
+            if style.fill_rule == FillRule::EvenOdd {
+                fill_attributes.push_str(" fill-rule=\"evenodd\"");
+            }
+            // SVG strokes have one width, so variable-width strokes use their widest end
+            let width = style.stroke.end_width(false).max(style.stroke.end_width(true));
+            let (stroke, stroke_opacity) = paint_attribute(&style.stroke.pattern);
+            let mut stroke_attributes = String::new();
+            if stroke != "none" && width > 0.0 {
+                _ = write!(stroke_attributes, " stroke=\"{stroke}\" stroke-width=\"{width}\"");
+                if let Some(opacity) = stroke_opacity {
+                    _ = write!(stroke_attributes, " stroke-opacity=\"{opacity}\"");
+                }
+            }
+
+            // SVG fills open subpaths and strokes whole paths, so open curves and trimmed strokes get a path of their own
+            if style.stroke.trim.is_full() && curves.iter().all(|curve| curve.is_closed) {
+                _ = writeln!(out, "{indent}<path d=\"{}\"{fill_attributes}{stroke_attributes}/>", path_data(&curves));
+                write_markers(out, &indent, &curves, &style.stroke);
+                return;
+            }
+            let closed = curves.iter().filter(|curve| curve.is_closed).cloned().collect::<Vec<_>>();
+            if fill != "none" && !closed.is_empty() {
+                _ = writeln!(out, "{indent}<path d=\"{}\"{fill_attributes}/>", path_data(&closed));
+            }
+            let stroked = curves.iter()
+                .flat_map(|curve| style.stroke.trim.portions(curve))
+                .collect::<Vec<_>>();
+            let stroke_data = path_data(&stroked);
+            if !stroke_attributes.is_empty() && !stroke_data.is_empty() {
+                _ = writeln!(out, "{indent}<path d=\"{stroke_data}\" fill=\"none\"{stroke_attributes}/>");
+            }
+            write_markers(out, &indent, &stroked, &style.stroke);
+        }
+
+        // rasters aren't embedded
+        LayerContent::Image { .. } | LayerContent::Paint(_) => (),
+    }
+}
+
+/// Append the [markers][`crate::style::Marker`] of `stroke` at the ends of each of the `stroked` curves to `out`, indented by `indent`
+///
+/// Markers are written as filled outlines in the stroke's color, like they are drawn, so they keep the width of each end of the stroke
+fn write_markers(out: &mut String, indent: &str, stroked: &[Curve], stroke: &Stroke) {
+    use std::fmt::Write;
+    let (color, opacity) = paint_attribute(&stroke.pattern);
+    if color == "none" || stroke.markers.is_none() {
+        return;
+    }
+    let markers = [(&stroke.markers.start, stroke.end_width(false)), (&stroke.markers.end, stroke.end_width(true))];
+    for ends in stroked.iter().filter_map(Curve::ends) {
+        for ((end, direction), (marker, width)) in ends.into_iter().zip(markers) {
+            let outline = marker.outline_at(end, direction, width);
+            let Some((first, rest)) = outline.split_first() else { continue };
+            _ = write!(out, "{indent}<path d=\"M{} {}", first.x, first.y);
+            for point in rest {
+                _ = write!(out, " L{} {}", point.x, point.y);
+            }
+            _ = write!(out, " Z\" fill=\"{color}\"");
+            if let Some(opacity) = opacity {
+                _ = write!(out, " fill-opacity=\"{opacity}\"");
+            }
+            out.push_str("/>\n");
+        }
+    }
+}
+
+/// The Dublin Core or XMP element each [keyword of exported metadata][`crate::export::png_text`] is written as
+///
+/// The artboard an image was exported from has no standard element, so it is written as the image's identifier
+fn metadata_element(keyword: &str) -> Option<&'static str> {
+    match keyword {
+        "Title" => Some("dc:title"),
+        "Author" => Some("dc:creator"),
+        "Description" => Some("dc:description"),
+        "Keywords" => Some("dc:subject"),
+        "Creation Time" => Some("xmp:CreateDate"),
+        "Modification Time" => Some("xmp:ModifyDate"),
+        "Software" => Some("xmp:CreatorTool"),
+        "Artboard" => Some("dc:identifier"),
+        _ => None,
+    }
+}
+
+/// Append a `<metadata>` element describing the file with the keyword/text pairs `text` to `out`
+///
+/// Does nothing if there is no text
+fn write_metadata(out: &mut String, text: &[(&str, String)]) {
+    use std::fmt::Write;
+    if text.is_empty() {
+        return;
+    }
+    out.push_str("  <metadata>\n");
+    out.push_str("    <rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\" xmlns:dc=\"http://purl.org/dc/elements/1.1/\" xmlns:xmp=\"http://ns.adobe.com/xap/1.0/\">\n");
+    out.push_str("      <rdf:Description rdf:about=\"\">\n");
+    for (keyword, value) in text {
+        if let Some(element) = metadata_element(keyword) {
+            _ = writeln!(out, "        <{element}>{}</{element}>", ora::escape_attribute(value));
+        }
+    }
+    out.push_str("      </rdf:Description>\n");
+    out.push_str("    </rdf:RDF>\n");
+    out.push_str("  </metadata>\n");
+}
+
+/// Write `layers` as a standalone SVG file whose view box is the worldspace rectangle `bounds`
+///
+/// Coordinates in the file are measured from the worldspace point `origin`.
+/// Curves and compound paths keep their solid fills and strokes, with only closed curves filled and strokes cut to their trim
+/// and ended with their markers; groups become `<g>` elements. Texture patterns, images, and painted rasters are left out
+///
+/// The keyword/text pairs `text`, as [`png_text`][`crate::export::png_text`] makes them, are written in a `<metadata>` element
+pub fn export<'a>(layers: impl IntoIterator<Item = &'a Layer>, bounds: Rectangle, origin: Vector2, text: &[(&str, String)]) -> String {
+    let mut out = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"{} {} {} {}\" width=\"{}\" height=\"{}\">\n",
+        bounds.x - origin.x, bounds.y - origin.y, bounds.width, bounds.height, bounds.width, bounds.height,
+    );
+    write_metadata(&mut out, text);
+    let is_offset = origin != Vector2::zero();
+    if is_offset {
+        out.push_str(&format!("  <g transform=\"translate({} {})\">\n", -origin.x, -origin.y));
+    }
+    for layer in layers {
+        write_layer(&mut out, layer, if is_offset { 2 } else { 1 });
+    }
+    if is_offset {
+        out.push_str("  </g>\n");
+    }
+    out.push_str("</svg>\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::style::{Marker, MarkerShape};
+
+    /// A layer of `curve` in the default style, changed by `edit`
+    fn styled_layer(document: &mut Document, curve: Curve, edit: impl FnOnce(&mut Style)) -> Layer {
+        let profile = Arc::downgrade(document.create_width_profile(WidthProfile::default_width_profile()));
+        let mut style = Style::default_style(profile);
+        edit(&mut style);
+        let style = Arc::downgrade(document.create_style(style));
+        let curve = Arc::downgrade(document.create_curve(curve));
+        Layer { name: String::new(), content: LayerContent::Curve(curve), style, export: None, is_template: false }
+    }
+
+    /// The anchors of each subpath in the path data `d`
+    fn anchors(d: &str) -> Vec<Vec<na::Vector2<f32>>> {
+        parse_path_data(d).iter()
+            .map(|curve| curve.points.iter().map(|point| point.p).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_path_data_round_trip() {
+        let open = crate::make_curve!([0,1](2,3)[4,5]->[6,7](8,9)[10,11]->[12,13](14,15)[16,17]);
+        let closed = crate::make_curve!([0,1](2,3)[4,5]->[6,7](8,9)[10,11]->[12,13](14,15)[16,17]->cycle);
+        for curve in [open, closed] {
+            let parsed = parse_path_data(&curve.to_svg_path_data());
+            assert_eq!(parsed.len(), 1);
+            assert_eq!(parsed[0].is_closed, curve.is_closed);
+            // the ends of an open curve have no handles in path data
+            let expected = if curve.is_closed { curve.points.clone() } else {
+                let mut points = curve.points.clone();
+                if let Some(first) = points.first_mut() { first.c_in = na::Vector2::zeros(); }
+                if let Some(last) = points.last_mut() { last.c_out = na::Vector2::zeros(); }
+                points
+            };
+            assert_eq!(parsed[0].points, expected);
+        }
+
+        assert!(parse_path_data("").is_empty());
+        assert_eq!(parse_path_data("M0 0 L1 1 M2 2 L3 3").len(), 2);
+    }
+
+    #[test]
+    fn test_export_trim_and_open_curves() {
+        let mut document = Document::new("export".to_owned());
+        let bounds = Rectangle::new(0.0, 0.0, 10.0, 10.0);
+
+        let square = styled_layer(&mut document, crate::make_curve!((0,0) -> (10,0) -> (10,10) -> (0,10) -> cycle), |_| ());
+        let svg = export([&square], bounds, Vector2::zero(), &[]);
+        let paths = tags(&svg).filter(|tag| tag.name == "path").collect::<Vec<_>>();
+        assert_eq!(paths.len(), 1, "a closed curve with its whole stroke should be one path");
+        assert!(paths[0].attribute("fill").is_some_and(|fill| fill != "none"));
+        assert!(paths[0].attribute("stroke").is_some());
+
+        let trimmed = styled_layer(&mut document, crate::make_curve!((0,0) -> (10,0) -> (10,10) -> (0,10) -> cycle), |style| {
+            style.stroke.trim = StrokeTrim { start: 0.0, end: 0.5, offset: 0.0 };
+        });
+        let svg = export([&trimmed], bounds, Vector2::zero(), &[]);
+        let paths = tags(&svg).filter(|tag| tag.name == "path").collect::<Vec<_>>();
+        assert_eq!(paths.len(), 2, "the fill and the trimmed stroke should be separate paths");
+        assert_eq!(paths[0].attribute("stroke"), None);
+        assert_eq!(paths[1].attribute("fill"), Some("none"));
+        assert_eq!(anchors(paths[1].attribute("d").unwrap()), [vec![na::Vector2::new(0.0, 0.0), na::Vector2::new(10.0, 0.0), na::Vector2::new(10.0, 10.0)]]);
+
+        let open = styled_layer(&mut document, crate::make_curve!((0,0) -> (10,0) -> (10,10)), |_| ());
+        let svg = export([&open], bounds, Vector2::zero(), &[]);
+        let paths = tags(&svg).filter(|tag| tag.name == "path").collect::<Vec<_>>();
+        assert_eq!(paths.len(), 1, "an open curve should only be stroked");
+        assert_eq!(paths[0].attribute("fill"), Some("none"));
+        assert!(paths[0].attribute("stroke").is_some());
+
+        let arrow = styled_layer(&mut document, crate::make_curve!((0,0) -> (10,0)), |style| {
+            style.stroke.markers.end = Marker { shape: MarkerShape::Arrow, scale: 1.0 };
+        });
+        let svg = export([&arrow], bounds, Vector2::zero(), &[]);
+        let paths = tags(&svg).filter(|tag| tag.name == "path").collect::<Vec<_>>();
+        assert_eq!(paths.len(), 2, "the marker should follow the stroke");
+        assert_eq!(paths[1].attribute("fill"), paths[0].attribute("stroke"));
+        // the default stroke is 10 wide, so the arrow reaches 20 past the end of the line
+        let tip = &anchors(paths[1].attribute("d").unwrap())[0][0];
+        assert!((tip - na::Vector2::new(30.0, 0.0)).norm() < 1e-3, "{tip} should be ahead of the line");
+
+        let hidden = styled_layer(&mut document, crate::make_curve!((0,0) -> (10,0)), |style| {
+            style.stroke.trim = StrokeTrim { start: 0.5, end: 0.5, offset: 0.0 };
+        });
+        assert!(!export([&hidden], bounds, Vector2::zero(), &[]).contains("<path"));
+    }
+
+    #[test]
+    fn test_export_metadata() {
+        let text = [
+            ("Title", "Logo & mark".to_owned()),
+            ("Author", "Amity".to_owned()),
+            ("Keywords", "logo, draft".to_owned()),
+            ("Creation Time", "2023-11-14T22:13:20Z".to_owned()),
+            ("Artboard", "Icon".to_owned()),
+        ];
+        let svg = export([], Rectangle::new(0.0, 0.0, 10.0, 10.0), Vector2::zero(), &text);
+        assert!(svg.contains("<metadata>"));
+        assert!(svg.contains("<dc:title>Logo &amp; mark</dc:title>"));
+        assert!(svg.contains("<dc:creator>Amity</dc:creator>"));
+        assert!(svg.contains("<dc:subject>logo, draft</dc:subject>"));
+        assert!(svg.contains("<xmp:CreateDate>2023-11-14T22:13:20Z</xmp:CreateDate>"));
+        assert!(svg.contains("<dc:identifier>Icon</dc:identifier>"));
+        assert!(!export([], Rectangle::new(0.0, 0.0, 10.0, 10.0), Vector2::zero(), &[]).contains("<metadata>"));
+    }
+
+    #[test]
+    fn test_tags_multibyte_attribute() {
+        let tags = tags("<svg><path id=\u{201c}wave\u{201c} d=\"M0 0L10 10\"/></svg>").collect::<Vec<_>>();
+        assert_eq!(tags.len(), 3);
+        assert_eq!(tags[1].name, "path");
+        assert_eq!(tags[1].attribute("id"), Some("wave"));
+        assert_eq!(tags[1].attribute("d"), Some("M0 0L10 10"));
+        assert!(tags[1].is_self_closing);
+    }
+}
@@ -0,0 +1,130 @@
+use raylib::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::locale::{tr, tr_format};
+
+/// A process color as fractions (0 to 1) of cyan, magenta, yellow, and black ink
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct Cmyk {
+    pub c: f32,
+    pub m: f32,
+    pub y: f32,
+    pub k: f32,
+}
+
+impl Cmyk {
+    /// Construct a process color, clamping each ink between 0 and 1
+    pub const fn new(c: f32, m: f32, y: f32, k: f32) -> Self {
+        Self {
+            c: c.clamp(0.0, 1.0),
+            m: m.clamp(0.0, 1.0),
+            y: y.clamp(0.0, 1.0),
+            k: k.clamp(0.0, 1.0),
+        }
+    }
+
+    /// The inks that would print `color`, using as much black as possible
+    ///
+    /// A naive conversion without an ICC profile; good enough for a starting point, not for proofing
+    pub fn from_rgb(color: Color) -> Self {
+        let (r, g, b) = (f32::from(color.r) / 255.0, f32::from(color.g) / 255.0, f32::from(color.b) / 255.0);
+        let k = 1.0 - r.max(g).max(b);
+        if k >= 1.0 {
+            return Self::new(0.0, 0.0, 0.0, 1.0);
+        }
+        let white = 1.0 - k;
+        Self::new((white - r) / white, (white - g) / white, (white - b) / white, k)
+    }
+
+    /// An opaque screen approximation of the inks; the inverse of [`Cmyk::from_rgb`]
+    pub fn to_rgb(self) -> Color {
+        let channel = |ink: f32| ((1.0 - ink) * (1.0 - self.k) * 255.0).round() as u8;
+        Color::new(channel(self.c), channel(self.m), channel(self.y), 255)
+    }
+}
+
+/// How a swatch's color is defined for print
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum SwatchColor {
+    /// A screen color, converted to process inks when printed
+    Rgb(Color),
+
+    /// A mix of process inks
+    Cmyk(Cmyk),
+
+    /// A premixed ink printed on its own separation plate
+    Spot {
+        /// The name of the ink, which a print shop matches to their stock (like `PANTONE 186 C`)
+        ink: String,
+
+        /// The process mix standing in for the ink on screen and on printers without the plate
+        alternate: Cmyk,
+    },
+}
+
+impl SwatchColor {
+    /// The color the swatch is previewed and rendered with on screen
+    pub fn preview(&self) -> Color {
+        match self {
+            Self::Rgb(color) => *color,
+            Self::Cmyk(cmyk) | Self::Spot { alternate: cmyk, .. } => cmyk.to_rgb(),
+        }
+    }
+
+    /// The process inks the swatch prints with, where spot colors fall back to their alternate
+    pub fn cmyk(&self) -> Cmyk {
+        match self {
+            Self::Rgb(color) => Cmyk::from_rgb(*color),
+            Self::Cmyk(cmyk) | Self::Spot { alternate: cmyk, .. } => *cmyk,
+        }
+    }
+}
+
+/// A named color saved with a document for reuse
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Swatch {
+    /// The display name of the swatch
+    pub name: String,
+
+    /// The color the swatch stands for
+    pub color: SwatchColor,
+}
+
+impl Swatch {
+    /// Construct a screen color swatch named after its hex code, like `#FF8000`
+    pub fn from_rgb(color: Color) -> Self {
+        Self {
+            name: format!("#{:02X}{:02X}{:02X}", color.r, color.g, color.b),
+            color: SwatchColor::Rgb(color),
+        }
+    }
+
+    /// The name, kind, and printed inks of the swatch, one per line
+    pub fn description(&self) -> String {
+        let kind = match &self.color {
+            SwatchColor::Rgb(_) => tr("swatch.rgb"),
+            SwatchColor::Cmyk(_) => tr("swatch.cmyk"),
+            SwatchColor::Spot { ink, .. } => tr_format("swatch.spot", &[ink]),
+        };
+        let Cmyk { c, m, y, k } = self.color.cmyk();
+        let [c, m, y, k] = [c, m, y, k].map(|ink| (ink * 100.0).round());
+        format!("{}\n{kind}\n{}", self.name, tr_format("swatch.inks", &[&c, &m, &y, &k]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cmyk_round_trip() {
+        for color in [Color::BLACK, Color::WHITE, Color::new(255, 128, 0, 255), Color::new(12, 200, 99, 255)] {
+            let round_trip = Cmyk::from_rgb(color).to_rgb();
+            for (a, b) in [(color.r, round_trip.r), (color.g, round_trip.g), (color.b, round_trip.b)] {
+                assert!(a.abs_diff(b) <= 1, "{color:?} became {round_trip:?}");
+            }
+        }
+        assert_eq!(Cmyk::from_rgb(Color::BLACK), Cmyk::new(0.0, 0.0, 0.0, 1.0));
+        let spot = SwatchColor::Spot { ink: "Orange".to_owned(), alternate: Cmyk::new(0.0, 0.5, 1.0, 0.0) };
+        assert_eq!(spot.preview(), Color::new(255, 128, 0, 255));
+    }
+}
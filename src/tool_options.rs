@@ -0,0 +1,189 @@
+use raylib::prelude::*;
+use crate::{brush_tip::{BrushTipLibrary, TipOptions}, calligraphy::Nib, editor::Tool, locale::{tr, tr_c, tr_list}, preferences::{BrushPreferences, BucketPreferences, MagicWandPreferences, Preferences}, stabilizer::StabilizerMode, ui::{number_field::NumberSpec, widgets::{Ui, WidgetId}}};
+
+/// Width of the strip
+pub const WIDTH: f32 = 380.0;
+
+/// Height of each row of the strip
+pub const HEIGHT: f32 = 20.0;
+
+/// Padding between the edge of the strip and its controls, and between controls
+const PADDING: f32 = 2.0;
+
+/// Width of the label before each group of controls
+const LABEL_WIDTH: f32 = 64.0;
+
+/// Width of each stabilizer mode button
+const MODE_WIDTH: f32 = 64.0;
+
+/// Width of the calligraphy mode toggle
+const TOGGLE_WIDTH: f32 = 80.0;
+
+/// Width of the shape recognition toggle
+const RECOGNIZE_WIDTH: f32 = 120.0;
+
+/// Width of the brush tip list
+const TIP_WIDTH: f32 = 64.0;
+
+/// Width of the label before each stamping slider
+const SHORT_LABEL_WIDTH: f32 = 40.0;
+
+/// Width of the stamp rotation toggle
+const FOLLOW_WIDTH: f32 = 48.0;
+
+/// The rectangle of the strip for `tool` with its top-left corner at `corner`
+pub fn bounds(corner: Vector2, tool: Tool) -> Rectangle {
+    Rectangle::new(corner.x, corner.y, WIDTH, HEIGHT * rows(tool) as f32)
+}
+
+/// The number of rows of options `tool` has
+const fn rows(tool: Tool) -> usize {
+    match tool {
+        Tool::VectorBrush => 3,
+        Tool::RasterBrush => 2,
+        _ => 1,
+    }
+}
+
+/// Whether `tool` has any options to show
+pub const fn has_options(tool: Tool) -> bool {
+    matches!(tool, Tool::VectorBrush | Tool::RasterBrush | Tool::MagicWand | Tool::Bucket)
+}
+
+/// Draw the options of `tool` within `bounds`, editing its settings in `preferences`
+///
+/// The raster brush's tip is chosen from `tips`. Returns whether any option changed
+pub fn draw(d: &mut impl RaylibDraw, ui: &mut Ui, bounds: Rectangle, tool: Tool, preferences: &mut Preferences, tips: &BrushTipLibrary) -> bool {
+    let brushes = &mut preferences.brushes;
+    let theme = ui.theme();
+    d.draw_rectangle_rec(bounds, theme.color_panel);
+    d.draw_rectangle_lines_ex(bounds, 1.0, theme.color_panel_edge);
+    let row = Rectangle::new(bounds.x + PADDING, bounds.y + PADDING, bounds.width - PADDING * 2.0, HEIGHT - PADDING * 2.0);
+    match tool {
+        Tool::MagicWand => draw_magic_wand(d, ui, row, &mut preferences.magic_wand),
+        Tool::Bucket => draw_bucket(d, ui, row, &mut preferences.bucket),
+        Tool::VectorBrush => {
+            let is_stabilizer_changed = draw_stabilizer(d, ui, row, brushes);
+            let is_nib_changed = draw_nib(d, ui, Rectangle { y: row.y + HEIGHT, ..row }, brushes);
+            let is_recognition_changed = draw_recognition(d, ui, Rectangle { y: row.y + HEIGHT * 2.0, ..row }, brushes);
+            is_stabilizer_changed || is_nib_changed || is_recognition_changed
+        }
+        Tool::RasterBrush => {
+            let is_stabilizer_changed = draw_stabilizer(d, ui, row, brushes);
+            let is_tip_changed = draw_tip(d, ui, Rectangle { y: row.y + HEIGHT, ..row }, brushes, tips);
+            is_stabilizer_changed || is_tip_changed
+        }
+        _ => draw_stabilizer(d, ui, row, brushes),
+    }
+}
+
+/// Draw the stabilizer mode and strength of the brush tools within `row`
+fn draw_stabilizer(d: &mut impl RaylibDraw, ui: &mut Ui, row: Rectangle, brushes: &mut BrushPreferences) -> bool {
+    let label = Rectangle { width: LABEL_WIDTH, ..row };
+    d.gui_label(label, Some(tr_c("tool_options.stabilizer").as_c_str()));
+
+    let prev_mode = brushes.stabilizer;
+    let mut mode = StabilizerMode::ALL.iter().position(|&mode| mode == prev_mode).unwrap_or(0) as i32;
+    let modes = Rectangle { x: label.x + label.width, width: MODE_WIDTH - 1.0, ..row };
+    d.gui_toggle_group(modes, Some(tr_list(&[
+        "tool_options.stabilizer.off",
+        "tool_options.stabilizer.average",
+        "tool_options.stabilizer.pull_string",
+    ]).as_c_str()), &mut mode);
+    brushes.stabilizer = StabilizerMode::ALL.get(mode as usize).copied().unwrap_or_default();
+
+    let slider_x = modes.x + MODE_WIDTH * StabilizerMode::ALL.len() as f32 + PADDING;
+    let slider = Rectangle { x: slider_x, width: row.x + row.width - slider_x, ..row };
+    let percent = format!("{:.0}%", brushes.stabilizer_strength * 100.0);
+    let is_strength_changed = ui.slider(d, WidgetId::new("tool_options.stabilizer_strength"), slider, &percent, &mut brushes.stabilizer_strength, 0.0..=1.0);
+
+    brushes.stabilizer != prev_mode || is_strength_changed
+}
+
+/// Draw the calligraphy mode and nib of the vector brush within `row`
+fn draw_nib(d: &mut impl RaylibDraw, ui: &mut Ui, row: Rectangle, brushes: &mut BrushPreferences) -> bool {
+    let prev_nib = brushes.calligraphy;
+    let nib = &mut brushes.calligraphy;
+    let toggle = Rectangle { width: TOGGLE_WIDTH, ..row };
+    ui.checkbox(d, WidgetId::new("tool_options.calligraphy"), toggle, &tr("tool_options.calligraphy"), &mut nib.is_enabled);
+
+    // the nib only shapes strokes in calligraphy mode, so it is only offered then
+    if nib.is_enabled {
+        let sliders_x = toggle.x + toggle.width + PADDING;
+        let slider_width = (row.x + row.width - sliders_x - PADDING) * 0.5;
+        let angle_slider = Rectangle { x: sliders_x, width: slider_width, ..row };
+        let angle = format!("{:.0}deg", nib.angle);
+        ui.slider(d, WidgetId::new("tool_options.nib_angle"), angle_slider, &angle, &mut nib.angle, 0.0..=Nib::MAX_ANGLE);
+        let roundness_slider = Rectangle { x: angle_slider.x + slider_width + PADDING, width: slider_width, ..row };
+        let percent = format!("{:.0}%", nib.roundness * 100.0);
+        ui.slider(d, WidgetId::new("tool_options.nib_roundness"), roundness_slider, &percent, &mut nib.roundness, 0.0..=1.0);
+    }
+
+    *nib != prev_nib
+}
+
+/// Draw whether the vector brush recognizes shapes within `row`
+fn draw_recognition(d: &mut impl RaylibDraw, ui: &mut Ui, row: Rectangle, brushes: &mut BrushPreferences) -> bool {
+    let toggle = Rectangle { width: RECOGNIZE_WIDTH, ..row };
+    ui.checkbox(d, WidgetId::new("tool_options.recognize_shapes"), toggle, &tr("tool_options.recognize_shapes"), &mut brushes.is_recognizing_shapes)
+}
+
+/// Draw the tip of the raster brush and how it is stamped within `row`
+fn draw_tip(d: &mut impl RaylibDraw, ui: &mut Ui, row: Rectangle, brushes: &mut BrushPreferences, tips: &BrushTipLibrary) -> bool {
+    let prev_tip = brushes.tip.clone();
+    let prev_options = brushes.tip_options;
+
+    // the round brush comes first, then every tip
+    let round = tr("tool_options.tip.round");
+    let names = std::iter::once(round.as_str())
+        .chain(tips.tips.iter().map(|tip| tip.name.as_str()))
+        .collect::<Vec<_>>();
+    let mut active = brushes.tip.as_ref()
+        .and_then(|name| tips.tips.iter().position(|tip| tip.name == *name))
+        .map_or(0, |i| i.saturating_add(1));
+    let list = Rectangle { width: TIP_WIDTH, ..row };
+    ui.dropdown(d, WidgetId::new("tool_options.tip"), list, &names, &mut active);
+    brushes.tip = active.checked_sub(1)
+        .and_then(|i| tips.tips.get(i))
+        .map(|tip| tip.name.clone());
+
+    // stamping only applies to tips, so it is only offered for them
+    if brushes.tip.is_some() {
+        let options = &mut brushes.tip_options;
+        let slider_width = (row.width - TIP_WIDTH - FOLLOW_WIDTH - PADDING * 3.0) * 0.5 - SHORT_LABEL_WIDTH;
+        let mut x = list.x + list.width + PADDING;
+        for (label_text, value, min, max) in [
+            ("tool_options.spacing", &mut options.spacing, TipOptions::MIN_SPACING, TipOptions::MAX_SPACING),
+            ("tool_options.jitter", &mut options.jitter, 0.0, 1.0),
+        ] {
+            d.gui_label(Rectangle { x, width: SHORT_LABEL_WIDTH, ..row }, Some(tr_c(label_text).as_c_str()));
+            let slider = Rectangle { x: x + SHORT_LABEL_WIDTH, width: slider_width, ..row };
+            let percent = format!("{:.0}%", *value * 100.0);
+            ui.slider(d, WidgetId::new(label_text), slider, &percent, value, min..=max);
+            x = slider.x + slider.width + PADDING;
+        }
+        ui.checkbox(d, WidgetId::new("tool_options.follow"), Rectangle { x, width: FOLLOW_WIDTH, ..row }, &tr("tool_options.follow"), &mut options.is_following);
+    }
+
+    brushes.tip != prev_tip || brushes.tip_options != prev_options
+}
+
+/// Draw the color tolerance of the magic wand within `row`
+fn draw_magic_wand(d: &mut impl RaylibDraw, ui: &mut Ui, row: Rectangle, magic_wand: &mut MagicWandPreferences) -> bool {
+    let label = Rectangle { width: LABEL_WIDTH, ..row };
+    d.gui_label(label, Some(tr_c("tool_options.tolerance").as_c_str()));
+
+    let field = Rectangle { x: label.x + label.width, width: row.width - label.width, ..row };
+    let spec = NumberSpec::integer(0.0, MagicWandPreferences::MAX_TOLERANCE);
+    ui.number_field(d, WidgetId::new("tool_options.tolerance"), field, &mut magic_wand.tolerance, &spec)
+}
+
+/// Draw the gap size of the bucket within `row`
+fn draw_bucket(d: &mut impl RaylibDraw, ui: &mut Ui, row: Rectangle, bucket: &mut BucketPreferences) -> bool {
+    let label = Rectangle { width: LABEL_WIDTH, ..row };
+    d.gui_label(label, Some(tr_c("tool_options.gap").as_c_str()));
+
+    let field = Rectangle { x: label.x + label.width, width: row.width - label.width, ..row };
+    let spec = NumberSpec::integer(0.0, BucketPreferences::MAX_GAP);
+    ui.number_field(d, WidgetId::new("tool_options.gap"), field, &mut bucket.gap, &spec)
+}
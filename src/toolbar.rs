@@ -0,0 +1,405 @@
+use raylib::prelude::*;
+use crate::{command::EditorAction, editor::{ColorWell, Editor, Tool}, engine::EngineTheme, locale, preferences::Keymap, style::Pattern};
+
+/// The toolbar's buttons, top to bottom
+///
+/// Tools in the same group share a button showing whichever of them was used last, and the
+/// rest are picked from a flyout
+pub const TOOL_GROUPS: [&[Tool]; 3] = [
+    &[Tool::Select, Tool::PointSelect, Tool::Lasso, Tool::MagicWand],
+    &[Tool::VectorPen, Tool::Rectangle],
+    &[Tool::VectorBrush, Tool::RasterBrush, Tool::Bucket],
+];
+
+/// The [localization][`crate::locale`] key of `tool`'s name
+pub const fn tool_name_key(tool: Tool) -> &'static str {
+    match tool {
+        Tool::PointSelect => "action.point_select",
+        Tool::Select => "action.select",
+        Tool::VectorBrush => "action.vector_brush",
+        Tool::VectorPen => "tool.vector_pen",
+        Tool::RasterBrush => "action.raster_brush",
+        Tool::Lasso => "action.lasso",
+        Tool::MagicWand => "action.magic_wand",
+        Tool::Rectangle => "action.rectangle",
+        Tool::Bucket => "action.bucket",
+    }
+}
+
+/// The action that switches to `tool`, [`None`] if it has no shortcut
+pub const fn tool_action(tool: Tool) -> Option<EditorAction> {
+    match tool {
+        Tool::PointSelect => Some(EditorAction::PointSelect),
+        Tool::Select => Some(EditorAction::Select),
+        Tool::VectorBrush => Some(EditorAction::VectorBrush),
+        Tool::VectorPen => None,
+        Tool::RasterBrush => Some(EditorAction::RasterBrush),
+        Tool::Lasso => Some(EditorAction::Lasso),
+        Tool::MagicWand => Some(EditorAction::MagicWand),
+        Tool::Rectangle => Some(EditorAction::Rectangle),
+        Tool::Bucket => Some(EditorAction::Bucket),
+    }
+}
+
+/// The tool's name followed by its shortcut, like `Select tool (V)`
+pub fn tool_tooltip(tool: Tool, keymap: &Keymap) -> String {
+    let name = locale::tr(tool_name_key(tool));
+    match tool_action(tool) {
+        Some(action) => format!("{name} ({})", keymap.binding(action).label()),
+        None => name,
+    }
+}
+
+/// What happened to a click given to the [`Toolbar`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ToolbarClick {
+    /// The click wasn't on the toolbar or its flyout
+    Missed,
+
+    /// The click was on the toolbar but didn't pick a tool
+    Consumed,
+
+    /// The click picked a tool
+    Picked(Tool),
+
+    /// The click was on one of the color wells
+    Well(ColorWell),
+}
+
+/// The vertical strip of tool buttons along the left edge of the viewport, with the current style's color wells at the bottom
+#[derive(Debug)]
+pub struct Toolbar {
+    /// The tool shown on each group's button, by index into [`TOOL_GROUPS`]
+    shown: [Tool; TOOL_GROUPS.len()],
+
+    /// The index of the group whose flyout is open
+    flyout: Option<usize>,
+}
+
+impl Default for Toolbar {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Toolbar {
+    /// Width and height of each button
+    pub const BUTTON_SIZE: f32 = 28.0;
+
+    /// Space around and between buttons
+    pub const PADDING: f32 = 3.0;
+
+    /// Width and height of each color well
+    ///
+    /// The fill and stroke wells overlap diagonally within the space of one button
+    pub const WELL_SIZE: f32 = Self::BUTTON_SIZE * 0.75;
+
+    /// Construct a toolbar showing the first tool of each group, with no flyout open
+    pub const fn new() -> Self {
+        Self {
+            shown: [TOOL_GROUPS[0][0], TOOL_GROUPS[1][0], TOOL_GROUPS[2][0]],
+            flyout: None,
+        }
+    }
+
+    /// The rectangle of the toolbar with its top-left corner at `corner`
+    pub fn bounds(corner: Vector2) -> Rectangle {
+        // one extra button's worth of space for the color wells
+        let height = Self::PADDING + (Self::BUTTON_SIZE + Self::PADDING) * (TOOL_GROUPS.len() as f32 + 1.0);
+        Rectangle::new(corner.x, corner.y, Self::BUTTON_SIZE + Self::PADDING * 2.0, height)
+    }
+
+    /// The rectangle of the button of the group at `index`
+    pub fn button_rect(bounds: Rectangle, index: usize) -> Rectangle {
+        Rectangle::new(
+            bounds.x + Self::PADDING,
+            bounds.y + Self::PADDING + (Self::BUTTON_SIZE + Self::PADDING) * index as f32,
+            Self::BUTTON_SIZE,
+            Self::BUTTON_SIZE,
+        )
+    }
+
+    /// The rectangle of `well`, below the buttons
+    pub fn well_rect(bounds: Rectangle, well: ColorWell) -> Rectangle {
+        let corner = Self::button_rect(bounds, TOOL_GROUPS.len());
+        let offset = match well {
+            ColorWell::Fill => 0.0,
+            ColorWell::Stroke => Self::BUTTON_SIZE - Self::WELL_SIZE,
+        };
+        Rectangle::new(corner.x + offset, corner.y + offset, Self::WELL_SIZE, Self::WELL_SIZE)
+    }
+
+    /// The rectangle of the flyout item at `index`, which opens to the right of `button`
+    pub fn flyout_item_rect(button: Rectangle, index: usize) -> Rectangle {
+        Rectangle {
+            x: button.x + (Self::BUTTON_SIZE + Self::PADDING) * (index as f32 + 1.0) + Self::PADDING,
+            ..button
+        }
+    }
+
+    /// Whether `point` is over the toolbar or its open flyout
+    pub fn contains_point(&self, bounds: Rectangle, point: Vector2) -> bool {
+        bounds.check_collision_point_rec(point) || self.flyout.is_some_and(|group| {
+            let button = Self::button_rect(bounds, group);
+            (0..TOOL_GROUPS[group].len()).any(|i| Self::flyout_item_rect(button, i).check_collision_point_rec(point))
+        })
+    }
+
+    /// Remember `tool` as the one its group's button shows, like after switching to it by shortcut
+    pub fn show_tool(&mut self, tool: Tool) {
+        if let Some(group) = TOOL_GROUPS.iter().position(|tools| tools.contains(&tool)) {
+            self.shown[group] = tool;
+        }
+    }
+
+    /// Handle a click at `point`
+    ///
+    /// Left clicking a button picks the tool it shows. Right clicking a button with more than one
+    /// tool opens its flyout, and left clicking a flyout item picks that tool. Any click closes an open flyout
+    pub fn click(&mut self, bounds: Rectangle, point: Vector2, is_right_click: bool) -> ToolbarClick {
+        if let Some(group) = self.flyout.take() {
+            let button = Self::button_rect(bounds, group);
+            if let Some(&tool) = TOOL_GROUPS[group].iter()
+                .enumerate()
+                .find_map(|(i, tool)| Self::flyout_item_rect(button, i).check_collision_point_rec(point).then_some(tool))
+            {
+                self.shown[group] = tool;
+                return ToolbarClick::Picked(tool);
+            }
+        }
+        if !bounds.check_collision_point_rec(point) {
+            return ToolbarClick::Missed;
+        }
+        // the fill well is drawn over the stroke well, so it is hit first
+        if let Some(well) = [ColorWell::Fill, ColorWell::Stroke].into_iter().find(|&well| Self::well_rect(bounds, well).check_collision_point_rec(point)) {
+            return ToolbarClick::Well(well);
+        }
+        let Some(group) = (0..TOOL_GROUPS.len()).find(|&i| Self::button_rect(bounds, i).check_collision_point_rec(point)) else {
+            return ToolbarClick::Consumed;
+        };
+        if is_right_click {
+            if TOOL_GROUPS[group].len() > 1 {
+                self.flyout = Some(group);
+            }
+            ToolbarClick::Consumed
+        } else {
+            ToolbarClick::Picked(self.shown[group])
+        }
+    }
+
+    /// Draw the toolbar and its open flyout, highlighting `editor`'s current tool and showing its current style in the color wells
+    ///
+    /// Returns the tooltip of the hovered button along with its rectangle, if any
+    pub fn draw(&self, d: &mut impl RaylibDraw, bounds: Rectangle, theme: &EngineTheme, editor: &Editor, keymap: &Keymap, mouse_pos: Vector2) -> Option<(Rectangle, String)> {
+        let current_tool = editor.current_tool;
+        let mut tooltip = None;
+        d.draw_rectangle_rec(bounds, theme.color_panel);
+        d.draw_rectangle_lines_ex(bounds, 1.0, theme.color_panel_edge);
+        for (group, tools) in TOOL_GROUPS.iter().enumerate() {
+            let button = Self::button_rect(bounds, group);
+            let tool = self.shown[group];
+            let is_hovered = button.check_collision_point_rec(mouse_pos);
+            draw_button(d, button, theme, tools.contains(&current_tool), is_hovered);
+            draw_tool_icon(d, button, tool, theme.color_foreground);
+            // a corner mark shows that there are more tools in the flyout
+            if tools.len() > 1 {
+                let (right, bottom) = (button.x + button.width - 2.0, button.y + button.height - 2.0);
+                d.draw_triangle(Vector2::new(right, bottom - 5.0), Vector2::new(right - 5.0, bottom), Vector2::new(right, bottom), theme.color_foreground);
+            }
+            if is_hovered {
+                tooltip = Some((button, tool_tooltip(tool, keymap)));
+            }
+        }
+
+        // draw the stroke well under the fill well, matching the order clicks hit them in
+        let (fill, stroke) = editor.current_patterns();
+        for (well, pattern) in [(ColorWell::Stroke, &stroke), (ColorWell::Fill, &fill)] {
+            let rect = Self::well_rect(bounds, well);
+            draw_swatch(d, rect, pattern, theme);
+            if well == ColorWell::Stroke {
+                // hollow, like an outline
+                let inset = rect.width / 3.0;
+                let hole = Rectangle::new(rect.x + inset, rect.y + inset, rect.width - inset * 2.0, rect.height - inset * 2.0);
+                d.draw_rectangle_rec(hole, theme.color_panel);
+                d.draw_rectangle_lines_ex(hole, 1.0, theme.color_foreground);
+            }
+            let (thickness, color) = if well == editor.active_well { (2.0, theme.color_accent) } else { (1.0, theme.color_foreground) };
+            d.draw_rectangle_lines_ex(rect, thickness, color);
+        }
+        if let Some(well) = [ColorWell::Fill, ColorWell::Stroke].into_iter().find(|&well| Self::well_rect(bounds, well).check_collision_point_rec(mouse_pos)) {
+            let name = locale::tr(match well {
+                ColorWell::Fill => "tool.fill_well",
+                ColorWell::Stroke => "tool.stroke_well",
+            });
+            let text = [EditorAction::SwapFillStroke, EditorAction::ResetFillStroke, EditorAction::ClearColor].into_iter()
+                .fold(name, |text, action| format!("{text}\n{} ({})", action.name(), keymap.binding(action).label()));
+            tooltip = Some((Self::well_rect(bounds, well), text));
+        }
+
+        if let Some(group) = self.flyout {
+            let button = Self::button_rect(bounds, group);
+            for (i, &tool) in TOOL_GROUPS[group].iter().enumerate() {
+                let item = Self::flyout_item_rect(button, i);
+                let is_hovered = item.check_collision_point_rec(mouse_pos);
+                d.draw_rectangle_rec(Rectangle::new(item.x - Self::PADDING, item.y - Self::PADDING, item.width + Self::PADDING * 2.0, item.height + Self::PADDING * 2.0), theme.color_panel);
+                draw_button(d, item, theme, tool == current_tool, is_hovered);
+                draw_tool_icon(d, item, tool, theme.color_foreground);
+                if is_hovered {
+                    tooltip = Some((item, tool_tooltip(tool, keymap)));
+                }
+            }
+        }
+        tooltip
+    }
+}
+
+/// Draw the background of a toolbar button
+fn draw_button(d: &mut impl RaylibDraw, rect: Rectangle, theme: &EngineTheme, is_active: bool, is_hovered: bool) {
+    if is_active {
+        d.draw_rectangle_rec(rect, theme.color_accent);
+    } else if is_hovered {
+        d.draw_rectangle_rec(rect, theme.color_panel_edge);
+    }
+}
+
+/// Draw `pattern` filling `rect`, with a slash for transparent and a checkerboard for textures, in the colors of `theme`
+fn draw_swatch(d: &mut impl RaylibDraw, rect: Rectangle, pattern: &Pattern, theme: &EngineTheme) {
+    match pattern {
+        Pattern::Solid(color) if color.a == 0 => {
+            d.draw_rectangle_rec(rect, theme.color_background);
+            d.draw_line_ex(
+                Vector2::new(rect.x, rect.y + rect.height),
+                Vector2::new(rect.x + rect.width, rect.y),
+                2.0,
+                theme.color_destructive,
+            );
+        }
+
+        Pattern::Solid(color) => d.draw_rectangle_rec(rect, *color),
+
+        Pattern::Texture(..) => {
+            let (half_width, half_height) = (rect.width * 0.5, rect.height * 0.5);
+            d.draw_rectangle_rec(rect, theme.color_foreground);
+            d.draw_rectangle_rec(Rectangle::new(rect.x, rect.y, half_width, half_height), theme.color_panel_edge);
+            d.draw_rectangle_rec(Rectangle::new(rect.x + half_width, rect.y + half_height, half_width, half_height), theme.color_panel_edge);
+        }
+    }
+}
+
+/// Draw a simple icon representing `tool` within `rect`
+fn draw_tool_icon(d: &mut impl RaylibDraw, rect: Rectangle, tool: Tool, color: Color) {
+    // icons are drawn on a 10x10 grid inset from the edges of the button
+    let inset = rect.width * 0.2;
+    let unit = (rect.width - inset * 2.0) / 10.0;
+    let p = |x: f32, y: f32| Vector2::new(rect.x + inset + x * unit, rect.y + inset + y * unit);
+    match tool {
+        Tool::Select => {
+            // solid arrow cursor
+            d.draw_triangle(p(1.0, 0.0), p(1.0, 9.0), p(7.0, 6.0), color);
+            d.draw_line_ex(p(4.0, 7.0), p(6.0, 10.0), unit * 1.5, color);
+        }
+
+        Tool::PointSelect => {
+            // hollow arrow cursor beside an anchor point
+            d.draw_triangle_lines(p(0.0, 0.0), p(0.0, 8.0), p(6.0, 5.0), color);
+            d.draw_rectangle_v(p(7.0, 7.0), Vector2::new(unit * 3.0, unit * 3.0), color);
+        }
+
+        Tool::VectorPen => {
+            // pen nib
+            d.draw_triangle(p(5.0, 0.0), p(1.0, 6.0), p(5.0, 10.0), color);
+            d.draw_triangle(p(5.0, 0.0), p(5.0, 10.0), p(9.0, 6.0), color);
+            d.draw_line_ex(p(5.0, 5.0), p(5.0, 10.0), unit * 0.5, Color::BLACK.alpha(0.5));
+        }
+
+        Tool::VectorBrush => {
+            // smooth stroke
+            d.draw_line_bezier(p(0.0, 8.0), p(10.0, 2.0), unit * 1.5, color);
+        }
+
+        Tool::RasterBrush => {
+            // round brush tip on a handle
+            d.draw_line_ex(p(10.0, 0.0), p(4.0, 6.0), unit * 1.5, color);
+            d.draw_circle_v(p(3.0, 7.0), unit * 3.0, color);
+        }
+
+        Tool::Lasso => {
+            // loop trailing off into a tail
+            d.draw_ellipse_lines(p(5.0, 4.0).x as i32, p(5.0, 4.0).y as i32, unit * 5.0, unit * 3.5, color);
+            d.draw_line_bezier(p(3.0, 7.0), p(1.0, 10.0), unit, color);
+        }
+
+        Tool::MagicWand => {
+            // wand with a sparkle at its tip
+            d.draw_line_ex(p(0.0, 10.0), p(6.0, 4.0), unit * 1.5, color);
+            d.draw_line_ex(p(8.0, 0.0), p(8.0, 4.0), unit * 0.75, color);
+            d.draw_line_ex(p(6.0, 2.0), p(10.0, 2.0), unit * 0.75, color);
+        }
+
+        Tool::Rectangle => {
+            // outlined rounded rectangle
+            let corner = p(0.0, 2.0);
+            d.draw_rectangle_rounded_lines(Rectangle::new(corner.x, corner.y, unit * 10.0, unit * 6.0), 0.4, 4, unit, color);
+        }
+
+        Tool::Bucket => {
+            // pail tapering to its base, under an arched handle
+            d.draw_triangle(p(1.0, 3.0), p(2.5, 9.0), p(7.5, 9.0), color);
+            d.draw_triangle(p(1.0, 3.0), p(7.5, 9.0), p(9.0, 3.0), color);
+            d.draw_line_ex(p(1.0, 3.0), p(5.0, 0.0), unit * 0.75, color);
+            d.draw_line_ex(p(5.0, 0.0), p(9.0, 3.0), unit * 0.75, color);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_click() {
+        let mut toolbar = Toolbar::new();
+        let bounds = Toolbar::bounds(Vector2::zero());
+        let center = |rect: Rectangle| Vector2::new(rect.x + rect.width * 0.5, rect.y + rect.height * 0.5);
+        let button = |index| center(Toolbar::button_rect(bounds, index));
+
+        assert_eq!(toolbar.click(bounds, button(0), false), ToolbarClick::Picked(Tool::Select));
+        assert_eq!(toolbar.click(bounds, Vector2::new(bounds.x + bounds.width + 1.0, button(0).y), false), ToolbarClick::Missed);
+        // the padding between buttons is still part of the toolbar
+        let gap = Vector2::new(button(0).x, Toolbar::button_rect(bounds, 1).y - Toolbar::PADDING * 0.5);
+        assert_eq!(toolbar.click(bounds, gap, false), ToolbarClick::Consumed);
+
+        // where the wells overlap, the fill well is on top
+        let fill = Toolbar::well_rect(bounds, ColorWell::Fill);
+        let stroke = Toolbar::well_rect(bounds, ColorWell::Stroke);
+        assert_eq!(toolbar.click(bounds, Vector2::new(stroke.x + 1.0, stroke.y + 1.0), false), ToolbarClick::Well(ColorWell::Fill));
+        assert_eq!(toolbar.click(bounds, Vector2::new(fill.x + 1.0, fill.y + 1.0), false), ToolbarClick::Well(ColorWell::Fill));
+        assert_eq!(toolbar.click(bounds, Vector2::new(stroke.x + stroke.width - 1.0, stroke.y + stroke.height - 1.0), false), ToolbarClick::Well(ColorWell::Stroke));
+    }
+
+    #[test]
+    fn test_click_flyout() {
+        let mut toolbar = Toolbar::new();
+        let bounds = Toolbar::bounds(Vector2::zero());
+        let button = Toolbar::button_rect(bounds, 1);
+        let item = Toolbar::flyout_item_rect(button, 1);
+        let item_center = Vector2::new(item.x + item.width * 0.5, item.y + item.height * 0.5);
+        let button_center = Vector2::new(button.x + button.width * 0.5, button.y + button.height * 0.5);
+
+        // the flyout is only over the toolbar while it is open
+        assert!(!toolbar.contains_point(bounds, item_center));
+        assert_eq!(toolbar.click(bounds, button_center, true), ToolbarClick::Consumed);
+        assert!(toolbar.contains_point(bounds, item_center));
+
+        // picking from the flyout closes it and shows the tool on the group's button
+        assert_eq!(toolbar.click(bounds, item_center, false), ToolbarClick::Picked(TOOL_GROUPS[1][1]));
+        assert!(!toolbar.contains_point(bounds, item_center));
+        assert_eq!(toolbar.click(bounds, button_center, false), ToolbarClick::Picked(TOOL_GROUPS[1][1]));
+
+        // clicking anywhere else closes it without picking anything
+        toolbar.click(bounds, button_center, true);
+        assert_eq!(toolbar.click(bounds, Vector2::new(item_center.x, item_center.y + 500.0), false), ToolbarClick::Missed);
+        assert_eq!(toolbar.click(bounds, item_center, false), ToolbarClick::Missed);
+    }
+}
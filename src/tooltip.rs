@@ -0,0 +1,89 @@
+use raylib::prelude::*;
+use crate::{engine::EngineTheme, ui::widgets};
+
+/// Hover help text, shown once the mouse has rested on a control for [`Tooltip::DELAY`] seconds
+///
+/// Controls [offer][`Tooltip::offer`] their text on every frame they are hovered, and the tooltip
+/// is [drawn][`Tooltip::draw`] after everything else so that it appears above all other UI
+#[derive(Debug, Default)]
+pub struct Tooltip {
+    /// The control offered this frame, by its screenspace rectangle, and its text
+    offered: Option<(Rectangle, String)>,
+
+    /// The control hovered since the time (in seconds) in the third element
+    hovered: Option<(Rectangle, String, f64)>,
+}
+
+impl Tooltip {
+    /// Seconds the mouse has to rest on a control before its tooltip is shown
+    pub const DELAY: f64 = 0.5;
+
+    /// Space between the tooltip's text and its edges
+    const PADDING: f32 = 4.0;
+
+    /// Distance from the mouse to the tooltip's corner
+    const MOUSE_OFFSET: f32 = 16.0;
+
+    /// Construct a tooltip that isn't showing anything
+    pub const fn new() -> Self {
+        Self {
+            offered: None,
+            hovered: None,
+        }
+    }
+
+    /// Offer `text` as the tooltip of the hovered control at the screenspace `rect`
+    ///
+    /// Only call this while the mouse is over the control. Later offers on the same frame replace
+    /// earlier ones, so offering in drawing order lets controls drawn on top win
+    pub fn offer(&mut self, rect: Rectangle, text: String) {
+        self.offered = Some((rect, text));
+    }
+
+    /// Draw the tooltip if the same control has been offered for long enough, then forget this frame's offer
+    ///
+    /// `time` is the current time in seconds. Each line of the text is drawn on its own row
+    pub fn draw(&mut self, d: &mut impl RaylibDraw, theme: &EngineTheme, mouse_pos: Vector2, time: f64) {
+        let Some((rect, text)) = self.offered.take() else {
+            self.hovered = None;
+            return;
+        };
+        let start = match &self.hovered {
+            Some((hovered_rect, hovered_text, start)) if *hovered_rect == rect && *hovered_text == text => *start,
+            _ => time,
+        };
+        if time - start >= Self::DELAY {
+            let line_height = theme.font_size as f32 + Self::PADDING * 0.5;
+            let lines = text.lines().collect::<Vec<_>>();
+            let text_width = lines.iter()
+                .map(|line| widgets::measure_text(line, theme.font_size))
+                .max()
+                .unwrap_or(0) as f32;
+            let width = text_width + Self::PADDING * 2.0;
+            let height = line_height * lines.len() as f32 + Self::PADDING * 1.5;
+            // keep the tooltip on screen, flipping it to the other side of the mouse if needed
+            let (screen_width, screen_height) = (d.get_screen_width() as f32, d.get_screen_height() as f32);
+            let mut x = mouse_pos.x + Self::MOUSE_OFFSET;
+            let mut y = mouse_pos.y + Self::MOUSE_OFFSET;
+            if x + width > screen_width {
+                x = (mouse_pos.x - width).max(0.0);
+            }
+            if y + height > screen_height {
+                y = (mouse_pos.y - height).max(0.0);
+            }
+            let bounds = Rectangle::new(x, y, width, height);
+            d.draw_rectangle_rec(bounds, theme.color_panel);
+            d.draw_rectangle_lines_ex(bounds, 1.0, theme.color_panel_edge);
+            for (i, line) in lines.iter().enumerate() {
+                d.draw_text(
+                    line,
+                    (x + Self::PADDING) as i32,
+                    (y + Self::PADDING + line_height * i as f32) as i32,
+                    theme.font_size,
+                    theme.color_foreground,
+                );
+            }
+        }
+        self.hovered = Some((rect, text, start));
+    }
+}
@@ -0,0 +1,8 @@
+/// Limits, steps, and formats of scrubbable number fields
+pub mod number_field;
+
+/// Editable single-line text, drawn by [`widgets::Ui::text_field`]
+pub mod text_field;
+
+/// Themed controls sharing focus and keyboard navigation
+pub mod widgets;
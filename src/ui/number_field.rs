@@ -0,0 +1,106 @@
+use crate::units::{self, Unit};
+
+/// How a [number field][`super::widgets::Ui::number_field`] limits, steps, shows, and parses its value
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NumberSpec {
+    /// The smallest value
+    pub min: f32,
+
+    /// The largest value
+    pub max: f32,
+
+    /// How much each arrow key press changes the value, and how much each [`NumberSpec::SCRUB_PIXELS`] dragged does
+    pub step: f32,
+
+    /// Whether the value is rounded to whole numbers
+    pub is_integer: bool,
+
+    /// The unit the value is shown and typed in, and the pixels per inch converting it,
+    /// for lengths stored in pixels (document units)
+    ///
+    /// [`None`] for plain numbers
+    pub unit: Option<(Unit, f32)>,
+}
+
+impl NumberSpec {
+    /// Pixels dragged per [step][`NumberSpec::step`] while scrubbing
+    pub const SCRUB_PIXELS: f32 = 4.0;
+
+    /// How many times larger steps are while Shift is held
+    pub const COARSE: f32 = 10.0;
+
+    /// How many times smaller steps are while Ctrl is held
+    pub const FINE: f32 = 10.0;
+
+    /// A plain number between `min` and `max`, changing by `step`
+    pub const fn new(min: f32, max: f32, step: f32) -> Self {
+        Self {
+            min,
+            max,
+            step,
+            is_integer: false,
+            unit: None,
+        }
+    }
+
+    /// A whole number between `min` and `max`, changing by 1
+    pub const fn integer(min: f32, max: f32) -> Self {
+        Self {
+            is_integer: true,
+            ..Self::new(min, max, 1.0)
+        }
+    }
+
+    /// The same spec for a length in pixels, shown and typed in `unit` at `dpi` pixels per inch
+    pub const fn with_unit(self, unit: Unit, dpi: f32) -> Self {
+        Self {
+            unit: Some((unit, dpi)),
+            ..self
+        }
+    }
+
+    /// Clamp `value` into range, rounding it if the spec is for whole numbers
+    pub fn constrain(&self, value: f32) -> f32 {
+        let value = if self.is_integer { value.round() } else { value };
+        value.clamp(self.min, self.max.max(self.min))
+    }
+
+    /// Format `value` for display, like `12.7 mm` or `3`
+    pub fn format(&self, value: f32) -> String {
+        match self.unit {
+            Some((unit, dpi)) => unit.format(value, dpi),
+            None if self.is_integer => format!("{value:.0}"),
+            None => units::format_value(value),
+        }
+    }
+
+    /// Parse typed text into a value in range, accepting any unit if the spec has one
+    ///
+    /// [`None`] if the text isn't a number (with an optional known unit)
+    pub fn parse(&self, text: &str) -> Option<f32> {
+        let value = match self.unit {
+            Some((unit, dpi)) => unit.parse(text, dpi)?,
+            None => text.trim().parse::<f32>().ok()?,
+        };
+        value.is_finite().then(|| self.constrain(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_number_spec() {
+        let count = NumberSpec::integer(1.0, 100.0);
+        assert_eq!(count.parse(" 12.4 "), Some(12.0));
+        assert_eq!(count.parse("500"), Some(100.0));
+        assert_eq!(count.parse("twelve"), None);
+        assert_eq!(count.format(7.0), "7");
+
+        let width = NumberSpec::new(1.0, 16384.0, 1.0).with_unit(Unit::Millimeters, 96.0);
+        assert_eq!(width.parse("1in"), Some(96.0));
+        assert_eq!(width.format(96.0), "25.4 mm");
+        assert_eq!(width.parse("inf"), None);
+    }
+}
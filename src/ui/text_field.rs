@@ -0,0 +1,235 @@
+use std::ops::Range;
+
+/// A way of moving the cursor of a [`TextField`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Motion {
+    /// One character back
+    Left,
+    /// One character forward
+    Right,
+    /// To the start of the current or previous word
+    WordLeft,
+    /// To the end of the current or next word
+    WordRight,
+    /// To the start of the text
+    Home,
+    /// To the end of the text
+    End,
+}
+
+/// Single-line editable text with a cursor and a selection, independent of how it is drawn
+///
+/// Positions are byte offsets into the text, always on character boundaries
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TextField {
+    text: String,
+
+    /// Where typing happens, and the moving end of the selection
+    cursor: usize,
+
+    /// The fixed end of the selection; equal to `cursor` when nothing is selected
+    anchor: usize,
+
+    /// The first character drawn, so the cursor stays in view in a field too narrow for the text
+    pub(super) scroll: usize,
+}
+
+impl TextField {
+    /// Construct a field holding `text`, with all of it selected so typing replaces it
+    pub fn new(text: &str) -> Self {
+        let text = single_line(text);
+        Self {
+            cursor: text.len(),
+            anchor: 0,
+            scroll: 0,
+            text,
+        }
+    }
+
+    /// The text of the field
+    #[inline]
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Where typing happens
+    #[inline]
+    pub const fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    /// The selected part of the text, which may be empty
+    pub fn selection(&self) -> Range<usize> {
+        self.cursor.min(self.anchor)..self.cursor.max(self.anchor)
+    }
+
+    /// The selected text
+    pub fn selected_text(&self) -> &str {
+        &self.text[self.selection()]
+    }
+
+    /// Select the whole text
+    pub fn select_all(&mut self) {
+        self.anchor = 0;
+        self.cursor = self.text.len();
+    }
+
+    /// Move the cursor to `index` (rounded down to a character boundary), extending the selection if `is_selecting`
+    pub fn set_cursor(&mut self, index: usize, is_selecting: bool) {
+        let mut index = index.min(self.text.len());
+        while !self.text.is_char_boundary(index) {
+            index = index.saturating_sub(1);
+        }
+        self.cursor = index;
+        if !is_selecting {
+            self.anchor = index;
+        }
+    }
+
+    /// Move the cursor by `motion`, extending the selection if `is_selecting`
+    ///
+    /// Without `is_selecting`, moving left or right collapses a selection to its start or end instead of moving
+    pub fn move_cursor(&mut self, motion: Motion, is_selecting: bool) {
+        let selection = self.selection();
+        let index = match motion {
+            Motion::Left if !is_selecting && !selection.is_empty() => selection.start,
+            Motion::Right if !is_selecting && !selection.is_empty() => selection.end,
+            Motion::Left => self.prev_char(self.cursor),
+            Motion::Right => self.next_char(self.cursor),
+            Motion::WordLeft => self.prev_word(self.cursor),
+            Motion::WordRight => self.next_word(self.cursor),
+            Motion::Home => 0,
+            Motion::End => self.text.len(),
+        };
+        self.set_cursor(index, is_selecting);
+    }
+
+    /// Replace the selection with `text`, leaving the cursor after it
+    ///
+    /// Line breaks and other control characters are dropped, since the field is a single line
+    pub fn insert(&mut self, text: &str) {
+        let text = single_line(text);
+        let selection = self.selection();
+        self.text.replace_range(selection.clone(), &text);
+        self.set_cursor(selection.start.saturating_add(text.len()), false);
+    }
+
+    /// Delete the selection, or the character (or word, if `is_word`) before the cursor if nothing is selected
+    ///
+    /// Returns whether anything was deleted
+    pub fn backspace(&mut self, is_word: bool) -> bool {
+        if self.selection().is_empty() {
+            self.anchor = if is_word { self.prev_word(self.cursor) } else { self.prev_char(self.cursor) };
+        }
+        self.delete_selection()
+    }
+
+    /// Delete the selection, or the character (or word, if `is_word`) after the cursor if nothing is selected
+    ///
+    /// Returns whether anything was deleted
+    pub fn delete(&mut self, is_word: bool) -> bool {
+        if self.selection().is_empty() {
+            self.anchor = if is_word { self.next_word(self.cursor) } else { self.next_char(self.cursor) };
+        }
+        self.delete_selection()
+    }
+
+    /// Remove the selected text and return it
+    ///
+    /// [`None`] if nothing is selected
+    pub fn cut(&mut self) -> Option<String> {
+        let text = self.selected_text().to_owned();
+        self.delete_selection().then_some(text)
+    }
+
+    /// Remove the selected text, returning whether there was any
+    fn delete_selection(&mut self) -> bool {
+        let selection = self.selection();
+        self.text.replace_range(selection.clone(), "");
+        self.set_cursor(selection.start, false);
+        !selection.is_empty()
+    }
+
+    /// The start of the character before `index`
+    fn prev_char(&self, index: usize) -> usize {
+        self.text[..index].char_indices().next_back().map_or(0, |(i, _)| i)
+    }
+
+    /// The end of the character after `index`
+    fn next_char(&self, index: usize) -> usize {
+        self.text[index..].chars().next().map_or(index, |ch| index.saturating_add(ch.len_utf8()))
+    }
+
+    /// The start of the word before `index`, skipping any whitespace in between
+    fn prev_word(&self, index: usize) -> usize {
+        let before = self.text[..index].trim_end();
+        before.char_indices()
+            .rev()
+            .find(|(_, ch)| ch.is_whitespace())
+            .map_or(0, |(i, ch)| i.saturating_add(ch.len_utf8()))
+    }
+
+    /// The end of the word after `index`, skipping any whitespace in between
+    fn next_word(&self, index: usize) -> usize {
+        let after = &self.text[index..];
+        let skipped = after.len().saturating_sub(after.trim_start().len());
+        let word_start = index.saturating_add(skipped);
+        self.text[word_start..].char_indices()
+            .find(|(_, ch)| ch.is_whitespace())
+            .map_or(self.text.len(), |(i, _)| word_start.saturating_add(i))
+    }
+}
+
+/// `text` without control characters like line breaks
+fn single_line(text: &str) -> String {
+    text.chars().filter(|ch| !ch.is_control()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert_replaces_selection() {
+        let mut field = TextField::new("artboard 1");
+        field.insert("cover\n");
+        assert_eq!(field.text(), "cover");
+        assert_eq!(field.cursor(), 5);
+
+        field.move_cursor(Motion::WordLeft, true);
+        assert_eq!(field.selected_text(), "cover");
+        assert_eq!(field.cut().as_deref(), Some("cover"));
+        assert!(field.text().is_empty());
+    }
+
+    #[test]
+    fn test_multibyte_editing() {
+        let mut field = TextField::new("caf\u{e9} na\u{ef}ve");
+        field.move_cursor(Motion::End, false);
+        assert!(field.backspace(false));
+        assert_eq!(field.text(), "caf\u{e9} na\u{ef}v");
+        field.move_cursor(Motion::Left, false);
+        field.move_cursor(Motion::Left, false);
+        assert!(field.backspace(false));
+        assert_eq!(field.text(), "caf\u{e9} n\u{ef}v");
+        // positions inside a character round down to its start
+        field.set_cursor(4, false);
+        assert_eq!(field.cursor(), 3);
+    }
+
+    #[test]
+    fn test_word_motion() {
+        let mut field = TextField::new("one  two three");
+        field.set_cursor(0, false);
+        field.move_cursor(Motion::WordRight, false);
+        assert_eq!(field.cursor(), 3);
+        field.move_cursor(Motion::WordRight, false);
+        assert_eq!(field.cursor(), 8);
+        field.move_cursor(Motion::End, false);
+        assert!(field.backspace(true));
+        assert_eq!(field.text(), "one  two ");
+        field.set_cursor(3, false);
+        assert!(field.delete(true));
+        assert_eq!(field.text(), "one ");
+    }
+}
@@ -0,0 +1,863 @@
+use std::{ffi::CString, hash::{DefaultHasher, Hash, Hasher}, ops::{Range, RangeInclusive}};
+use raylib::{ffi::{IsKeyPressedRepeat, MeasureText}, prelude::*};
+use crate::engine::EngineTheme;
+use super::{number_field::NumberSpec, text_field::{Motion, TextField}};
+
+/// Space between the edge of a widget and its text
+const PADDING: f32 = 4.0;
+
+/// Width of the bar along the right edge of a scrollable area
+pub const SCROLLBAR_WIDTH: f32 = 8.0;
+
+/// Distance scrolled by each notch of the mouse wheel or each arrow key press
+const SCROLL_STEP: f32 = 32.0;
+
+/// Fraction of a slider's range each arrow key press moves it by
+const SLIDER_STEP: f32 = 0.05;
+
+/// Pixels the mouse has to move while pressed on a number field before it scrubs instead of clicking
+const SCRUB_THRESHOLD: f32 = 3.0;
+
+/// Identifies a widget from one frame to the next, so it can keep focus and track drags
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct WidgetId(u64);
+
+impl WidgetId {
+    /// The id of the widget named `name`, which should be unique among the widgets drawn each frame
+    pub fn new(name: &str) -> Self {
+        let mut hasher = DefaultHasher::new();
+        name.hash(&mut hasher);
+        Self(hasher.finish())
+    }
+
+    /// The id of the `index`th of several widgets sharing this id, like the rows of a list
+    pub fn child(self, index: usize) -> Self {
+        let mut hasher = DefaultHasher::new();
+        (self.0, index).hash(&mut hasher);
+        Self(hasher.finish())
+    }
+}
+
+/// The mouse and keyboard input of one frame, captured before anything is drawn
+#[derive(Debug, Default)]
+struct FrameInput {
+    mouse_pos: Vector2,
+    is_mouse_pressed: bool,
+    is_mouse_down: bool,
+    is_mouse_released: bool,
+    wheel: f32,
+
+    /// Characters typed this frame, in order, if a widget had focus
+    typed: Vec<char>,
+
+    /// Whether Enter or Space was pressed, activating the focused widget
+    is_activated: bool,
+
+    /// Whether Enter was pressed, confirming a text field
+    is_enter: bool,
+
+    /// Whether Escape was pressed, dropping focus and cancelling a typed number
+    is_escaped: bool,
+
+    is_shift: bool,
+    is_ctrl: bool,
+
+    /// How the cursor of a text field moves, from the arrow keys (by words with Ctrl), Home, or End
+    motion: Option<Motion>,
+    is_backspace: bool,
+    is_delete: bool,
+    is_select_all: bool,
+    is_copy: bool,
+    is_cut: bool,
+
+    /// The clipboard text, if Ctrl+V was pressed while a widget had focus
+    paste: Option<String>,
+
+    /// Arrow keys pressed this frame, -1 for left/up and 1 for right/down
+    step: Vector2,
+
+    /// Page Up (-1) or Page Down (1)
+    page: f32,
+}
+
+/// How a widget was interacted with this frame
+#[derive(Debug, Clone, Copy)]
+struct Interaction {
+    is_hovered: bool,
+    is_focused: bool,
+
+    /// Whether the mouse was pressed on the widget and hasn't been released yet
+    is_held: bool,
+
+    /// Whether the mouse was pressed and released on the widget
+    is_clicked: bool,
+}
+
+/// The open list of a [dropdown][`Ui::dropdown`], drawn above every other widget by [`Ui::end_frame`]
+#[derive(Debug)]
+struct Popup {
+    owner: WidgetId,
+    header: Rectangle,
+    list: Rectangle,
+    items: Vec<String>,
+    selected: usize,
+}
+
+/// State shared by every widget: the input of the current frame, which widget has keyboard focus, and which is being dragged
+///
+/// Call [`Ui::begin_frame`] before handling input, draw widgets in the order Tab should visit them,
+/// then call [`Ui::end_frame`] after the last widget so popups appear above the rest of the UI
+#[derive(Debug, Default)]
+pub struct Ui {
+    input: FrameInput,
+
+    /// The theme widgets are drawn with, updated every frame
+    theme: EngineTheme,
+
+    /// The widget receiving keyboard input
+    focused: Option<WidgetId>,
+
+    /// Whether a widget had focus when the frame began, so its keys shouldn't also trigger shortcuts
+    is_capturing_keys: bool,
+
+    /// Focusable widgets in the order they were drawn this frame
+    order: Vec<WidgetId>,
+
+    /// The widget the mouse was pressed on, until it is released
+    held: Option<WidgetId>,
+
+    /// The dropdown whose list is open
+    open_dropdown: Option<WidgetId>,
+
+    /// The open list drawn this frame
+    popup: Option<Popup>,
+
+    /// The open list drawn last frame, which blocks the mouse from the widgets beneath it
+    prev_popup: Option<Popup>,
+
+    /// Text copied from a text field, written to the system clipboard at the start of the next frame
+    clipboard: Option<String>,
+
+    /// The number field being typed into, and its text
+    number_edit: Option<(WidgetId, TextField)>,
+
+    /// The number field the mouse was last pressed on
+    scrub: Option<Scrub>,
+}
+
+/// The state of a drag on a [number field][`Ui::number_field`]
+#[derive(Debug, Clone, Copy)]
+struct Scrub {
+    id: WidgetId,
+
+    /// The mouse position last frame
+    last_x: f32,
+
+    /// How far the mouse has moved since it was pressed, to tell scrubbing from clicking
+    distance: f32,
+
+    /// The unrounded value, so slow drags still add up on whole-number fields
+    value: f32,
+}
+
+impl Ui {
+    /// Construct a UI without focus
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Capture this frame's input and `theme`, and move focus with Tab (or Shift+Tab) through the widgets drawn last frame
+    ///
+    /// Tab only moves focus that a widget already has (from being clicked), so it doesn't take the key from shortcuts.
+    /// Pressing the mouse drops focus unless a widget claims it while being drawn, and Escape drops it outright
+    pub fn begin_frame(&mut self, rl: &mut RaylibHandle, theme: &EngineTheme) {
+        self.theme = *theme;
+        if self.input.is_mouse_released {
+            self.held = None;
+        }
+
+        if let Some(text) = self.clipboard.take()
+            && let Err(e) = rl.set_clipboard_text(&text)
+        {
+            eprintln!("failed to copy text: {e}");
+        }
+
+        let mut typed = std::mem::take(&mut self.input.typed);
+        typed.clear();
+        // raygui text boxes read the same queue, so it is only drained while a widget has focus
+        if self.focused.is_some() {
+            while let Some(ch) = rl.get_char_pressed() {
+                typed.push(ch);
+            }
+        }
+        // held keys repeat, for moving through and deleting text
+        let is_pressed = |key: KeyboardKey| rl.is_key_pressed(key) || unsafe { IsKeyPressedRepeat(key as i32) };
+        let step_key = |negative, positive| f32::from(i8::from(is_pressed(positive))) - f32::from(i8::from(is_pressed(negative)));
+        let is_ctrl = rl.is_key_down(KEY_LEFT_CONTROL) || rl.is_key_down(KEY_RIGHT_CONTROL);
+        let motion = if is_pressed(KEY_LEFT) {
+            Some(if is_ctrl { Motion::WordLeft } else { Motion::Left })
+        } else if is_pressed(KEY_RIGHT) {
+            Some(if is_ctrl { Motion::WordRight } else { Motion::Right })
+        } else if rl.is_key_pressed(KEY_HOME) {
+            Some(Motion::Home)
+        } else if rl.is_key_pressed(KEY_END) {
+            Some(Motion::End)
+        } else {
+            None
+        };
+        let is_enter = rl.is_key_pressed(KEY_ENTER) || rl.is_key_pressed(KEY_KP_ENTER);
+        self.input = FrameInput {
+            mouse_pos: rl.get_mouse_position(),
+            is_mouse_pressed: rl.is_mouse_button_pressed(MOUSE_BUTTON_LEFT),
+            is_mouse_down: rl.is_mouse_button_down(MOUSE_BUTTON_LEFT),
+            is_mouse_released: rl.is_mouse_button_released(MOUSE_BUTTON_LEFT),
+            wheel: rl.get_mouse_wheel_move(),
+            typed,
+            is_activated: is_enter || rl.is_key_pressed(KEY_SPACE),
+            is_enter,
+            is_escaped: rl.is_key_pressed(KEY_ESCAPE),
+            is_shift: rl.is_key_down(KEY_LEFT_SHIFT) || rl.is_key_down(KEY_RIGHT_SHIFT),
+            is_ctrl,
+            motion,
+            is_backspace: is_pressed(KEY_BACKSPACE),
+            is_delete: is_pressed(KEY_DELETE),
+            is_select_all: is_ctrl && rl.is_key_pressed(KEY_A),
+            is_copy: is_ctrl && rl.is_key_pressed(KEY_C),
+            is_cut: is_ctrl && rl.is_key_pressed(KEY_X),
+            paste: (self.focused.is_some() && is_ctrl && rl.is_key_pressed(KEY_V))
+                .then(|| rl.get_clipboard_text().ok())
+                .flatten(),
+            step: Vector2::new(step_key(KEY_LEFT, KEY_RIGHT), step_key(KEY_UP, KEY_DOWN)),
+            page: step_key(KEY_PAGE_UP, KEY_PAGE_DOWN),
+        };
+
+        // focus can't stay on a widget that is no longer drawn
+        let mut order = std::mem::take(&mut self.order);
+        if self.focused.is_some_and(|id| !order.contains(&id)) {
+            self.focused = None;
+        }
+        self.is_capturing_keys = self.focused.is_some();
+
+        if self.input.is_escaped {
+            self.focused = None;
+            self.open_dropdown = None;
+        } else if rl.is_key_pressed(KEY_TAB)
+            && let Some(focused) = self.focused
+        {
+            // Tab only moves focus once a widget has it, so it stays free for shortcuts otherwise
+            self.focused = next_focus(&order, focused, self.input.is_shift);
+            self.open_dropdown = None;
+        }
+
+        self.prev_popup = self.popup.take();
+        if self.input.is_mouse_pressed {
+            let is_on_popup = self.prev_popup.as_ref()
+                .is_some_and(|popup| popup.header.check_collision_point_rec(self.input.mouse_pos) || popup.list.check_collision_point_rec(self.input.mouse_pos));
+            if !is_on_popup {
+                self.open_dropdown = None;
+            }
+            self.focused = None;
+        }
+
+        order.clear();
+        self.order = order;
+    }
+
+    /// Draw the open dropdown list, if any, above everything drawn since [`Ui::begin_frame`]
+    pub fn end_frame(&self, d: &mut impl RaylibDraw) {
+        let theme = &self.theme;
+        let Some(popup) = &self.popup else { return };
+        d.draw_rectangle_rec(popup.list, theme.color_panel);
+        for (i, item) in popup.items.iter().enumerate() {
+            let row = Rectangle { y: popup.list.y + popup.header.height * i as f32, height: popup.header.height, ..popup.list };
+            if row.check_collision_point_rec(self.input.mouse_pos) {
+                d.draw_rectangle_rec(row, theme.color_accent.alpha(0.5));
+            } else if i == popup.selected {
+                d.draw_rectangle_rec(row, theme.color_panel_edge);
+            }
+            draw_label(d, theme, row, item, false);
+        }
+        d.draw_rectangle_lines_ex(popup.list, 1.0, theme.color_panel_edge);
+    }
+
+    /// Whether the mouse is over the dropdown list left open last frame, which covers whatever is beneath it
+    pub fn is_mouse_over_popup(&self) -> bool {
+        self.prev_popup.as_ref().is_some_and(|popup| popup.list.check_collision_point_rec(self.input.mouse_pos))
+    }
+
+    /// The theme captured by [`Ui::begin_frame`]
+    #[inline]
+    pub const fn theme(&self) -> &EngineTheme {
+        &self.theme
+    }
+
+    /// Whether a widget has keyboard focus, so keyboard shortcuts should be ignored
+    pub const fn is_capturing_keys(&self) -> bool {
+        self.is_capturing_keys
+    }
+
+    /// Give keyboard focus to the widget `id`, or take it from every widget with [`None`]
+    pub fn set_focus(&mut self, id: Option<WidgetId>) {
+        self.focused = id;
+    }
+
+    /// Whether the widget `id` has keyboard focus
+    pub fn is_focused(&self, id: WidgetId) -> bool {
+        self.focused == Some(id)
+    }
+
+    /// Whether the mouse is over `bounds` and not over an open dropdown list belonging to another widget
+    fn is_hovered(&self, id: WidgetId, bounds: Rectangle) -> bool {
+        let is_blocked = self.prev_popup.as_ref()
+            .is_some_and(|popup| popup.owner != id && popup.list.check_collision_point_rec(self.input.mouse_pos));
+        !is_blocked && bounds.check_collision_point_rec(self.input.mouse_pos)
+    }
+
+    /// Register the widget `id` at `bounds` for Tab navigation, focusing and holding it if the mouse was pressed on it
+    fn interact(&mut self, id: WidgetId, bounds: Rectangle) -> Interaction {
+        self.order.push(id);
+        if self.is_hovered(id, bounds) && self.input.is_mouse_pressed {
+            self.focused = Some(id);
+        }
+        self.hold(id, bounds)
+    }
+
+    /// Like [`Ui::interact`], for parts of widgets that can't take focus on their own
+    fn hold(&mut self, id: WidgetId, bounds: Rectangle) -> Interaction {
+        let is_hovered = self.is_hovered(id, bounds);
+        if is_hovered && self.input.is_mouse_pressed {
+            self.held = Some(id);
+        }
+        let is_held = self.held == Some(id);
+        Interaction {
+            is_hovered,
+            is_focused: self.focused == Some(id),
+            is_held: is_held && self.input.is_mouse_down,
+            is_clicked: is_held && is_hovered && self.input.is_mouse_released,
+        }
+    }
+
+    /// Draw an outline around `bounds` if `interaction` is focused
+    fn draw_focus(d: &mut impl RaylibDraw, theme: &EngineTheme, bounds: Rectangle, interaction: Interaction) {
+        if interaction.is_focused {
+            d.draw_rectangle_lines_ex(bounds, theme.selection_thickness, theme.color_accent);
+        }
+    }
+
+    /// The fill of a clickable widget: the accent while held, a faded accent while hovered, and the panel edge color otherwise
+    fn fill_color(theme: &EngineTheme, interaction: Interaction) -> Color {
+        if interaction.is_held {
+            theme.color_accent
+        } else if interaction.is_hovered {
+            theme.color_accent.alpha(0.5)
+        } else {
+            theme.color_panel_edge
+        }
+    }
+
+    /// Draw a button labeled `text`
+    ///
+    /// Returns whether it was clicked, or activated with Enter or Space while focused
+    pub fn button(&mut self, d: &mut impl RaylibDraw, id: WidgetId, bounds: Rectangle, text: &str) -> bool {
+        let theme = self.theme;
+        let interaction = self.interact(id, bounds);
+        d.draw_rectangle_rec(bounds, Self::fill_color(&theme, interaction));
+        draw_label(d, &theme, bounds, text, true);
+        Self::draw_focus(d, &theme, bounds, interaction);
+        interaction.is_clicked || (interaction.is_focused && self.input.is_activated)
+    }
+
+    /// Draw a box, checked if `value` is true, followed by `text`, toggling `value` when clicked or activated
+    ///
+    /// Returns whether `value` changed
+    pub fn checkbox(&mut self, d: &mut impl RaylibDraw, id: WidgetId, bounds: Rectangle, text: &str, value: &mut bool) -> bool {
+        let theme = self.theme;
+        let interaction = self.interact(id, bounds);
+        let is_toggled = interaction.is_clicked || (interaction.is_focused && self.input.is_activated);
+        if is_toggled {
+            *value = !*value;
+        }
+        let check_box = Rectangle { width: bounds.height, ..bounds };
+        d.draw_rectangle_rec(check_box, Self::fill_color(&theme, interaction));
+        if *value {
+            let inset = bounds.height * 0.25;
+            d.draw_rectangle_rec(Rectangle::new(check_box.x + inset, check_box.y + inset, check_box.width - inset * 2.0, check_box.height - inset * 2.0), theme.color_foreground);
+        }
+        draw_label(d, &theme, Rectangle { x: bounds.x + bounds.height, width: bounds.width - bounds.height, ..bounds }, text, false);
+        Self::draw_focus(d, &theme, check_box, interaction);
+        is_toggled
+    }
+
+    /// Draw a horizontal slider setting `value` within `range`, with `text` (usually the value) drawn over it
+    ///
+    /// Dragged with the mouse, or stepped with the arrow keys while focused. Returns whether `value` changed
+    pub fn slider(&mut self, d: &mut impl RaylibDraw, id: WidgetId, bounds: Rectangle, text: &str, value: &mut f32, range: RangeInclusive<f32>) -> bool {
+        let (min, max) = range.into_inner();
+        let theme = self.theme;
+        let interaction = self.interact(id, bounds);
+        let prev_value = *value;
+        if interaction.is_held && bounds.width > 0.0 {
+            let amount = ((self.input.mouse_pos.x - bounds.x) / bounds.width).clamp(0.0, 1.0);
+            *value = min + (max - min) * amount;
+        } else if interaction.is_focused {
+            // right and up both increase the value
+            let step = self.input.step.x - self.input.step.y;
+            *value = (*value + (max - min) * SLIDER_STEP * step).clamp(min.min(max), max.max(min));
+        }
+
+        d.draw_rectangle_rec(bounds, theme.color_panel_edge);
+        let amount = if max == min { 0.0 } else { ((*value - min) / (max - min)).clamp(0.0, 1.0) };
+        let fill = if interaction.is_held || interaction.is_hovered { theme.color_accent } else { theme.color_accent.alpha(0.75) };
+        d.draw_rectangle_rec(Rectangle { width: bounds.width * amount, ..bounds }, fill);
+        draw_label(d, &theme, bounds, text, true);
+        Self::draw_focus(d, &theme, bounds, interaction);
+        *value != prev_value
+    }
+
+    /// Draw a single-line field editing `field`
+    ///
+    /// While focused, the field takes typed text, moves its cursor with the arrow keys (by words with Ctrl),
+    /// Home, and End, selects with Shift or by dragging, deletes with Backspace and Delete (by words with Ctrl),
+    /// and copies, cuts, and pastes with the system clipboard. Text composed with an input method arrives once
+    /// it is committed, since raylib doesn't report compositions in progress. Enter drops focus.
+    /// Returns whether the text changed
+    pub fn text_field(&mut self, d: &mut impl RaylibDraw, id: WidgetId, bounds: Rectangle, field: &mut TextField) -> bool {
+        let interaction = self.interact(id, bounds);
+        self.edit_text(d, bounds, interaction, field)
+    }
+
+    /// Handle the input of the text field at `bounds` with `interaction` and draw it; see [`Ui::text_field`]
+    fn edit_text(&mut self, d: &mut impl RaylibDraw, bounds: Rectangle, interaction: Interaction, field: &mut TextField) -> bool {
+        let theme = self.theme;
+        let font_size = theme.font_size;
+        let text_x = bounds.x + PADDING;
+        let width = bounds.width - PADDING * 2.0;
+
+        if interaction.is_held {
+            // the press places the cursor (or extends the selection, with Shift) and dragging selects from there
+            let is_selecting = !self.input.is_mouse_pressed || self.input.is_shift;
+            field.set_cursor(index_at(field, self.input.mouse_pos.x - text_x, font_size), is_selecting);
+        }
+
+        let mut is_changed = false;
+        if interaction.is_focused {
+            let input = &self.input;
+            if let Some(motion) = input.motion {
+                field.move_cursor(motion, input.is_shift);
+            }
+            if input.is_select_all {
+                field.select_all();
+            }
+            if (input.is_copy || input.is_cut) && !field.selection().is_empty() {
+                self.clipboard = Some(field.selected_text().to_owned());
+            }
+            if input.is_cut {
+                is_changed |= field.cut().is_some();
+            }
+            if let Some(text) = &input.paste {
+                field.insert(text);
+                is_changed = true;
+            }
+            let typed = input.typed.iter().filter(|ch| !ch.is_control()).collect::<String>();
+            if !typed.is_empty() {
+                field.insert(&typed);
+                is_changed = true;
+            }
+            if input.is_backspace {
+                is_changed |= field.backspace(input.is_ctrl);
+            }
+            if input.is_delete {
+                is_changed |= field.delete(input.is_ctrl);
+            }
+            if input.is_enter {
+                self.focused = None;
+            }
+        }
+
+        // keep the cursor in view, scrolling by whole characters
+        let text = field.text();
+        let mut scroll = field.scroll.min(field.cursor());
+        while !text.is_char_boundary(scroll) {
+            scroll = scroll.saturating_sub(1);
+        }
+        while scroll < field.cursor() && measure_text(&text[scroll..field.cursor()], font_size) as f32 > width {
+            scroll = text[scroll..].chars().next().map_or(text.len(), |ch| scroll.saturating_add(ch.len_utf8()));
+        }
+        let visible = fit_start(&text[scroll..], font_size, width);
+        let visible_end = scroll.saturating_add(visible.len());
+        let offset_of = |index: usize| measure_text(&text[scroll..index.clamp(scroll, visible_end)], font_size) as f32;
+
+        d.draw_rectangle_rec(bounds, theme.color_background);
+        d.draw_rectangle_lines_ex(bounds, 1.0, theme.color_panel_edge);
+        let y = bounds.y + (bounds.height - font_size as f32) * 0.5;
+        if interaction.is_focused {
+            let selection = field.selection();
+            let (start, end) = (offset_of(selection.start), offset_of(selection.end));
+            if end > start {
+                d.draw_rectangle_rec(Rectangle::new(text_x + start, y, end - start, font_size as f32), theme.color_accent.alpha(0.5));
+            }
+        }
+        d.draw_text(visible, text_x as i32, y as i32, font_size, theme.color_foreground);
+        if interaction.is_focused {
+            let caret = text_x + offset_of(field.cursor()) + 1.0;
+            d.draw_line_v(Vector2::new(caret, y), Vector2::new(caret, y + font_size as f32), theme.color_foreground);
+        }
+        Self::draw_focus(d, &theme, bounds, interaction);
+        field.scroll = scroll;
+        is_changed
+    }
+
+    /// Draw a field showing `value` as described by `spec`
+    ///
+    /// Dragging left or right scrubs the value by [`NumberSpec::step`] every [`NumberSpec::SCRUB_PIXELS`],
+    /// [coarser][`NumberSpec::COARSE`] with Shift and [finer][`NumberSpec::FINE`] with Ctrl, as do the arrow keys while focused.
+    /// Clicking without dragging, or pressing Enter or Space while focused, turns the field into a text field
+    /// for typing an exact value (with any unit, if `spec` has one). Typed values apply when Enter is pressed
+    /// or focus moves away, and Escape discards them. Returns whether `value` changed
+    pub fn number_field(&mut self, d: &mut impl RaylibDraw, id: WidgetId, bounds: Rectangle, value: &mut f32, spec: &NumberSpec) -> bool {
+        let theme = self.theme;
+        let prev_value = *value;
+        let interaction = self.interact(id, bounds);
+        let speed = if self.input.is_shift {
+            NumberSpec::COARSE
+        } else if self.input.is_ctrl {
+            NumberSpec::FINE.recip()
+        } else {
+            1.0
+        };
+
+        let was_typing = self.number_edit.as_ref().is_some_and(|(edit_id, _)| *edit_id == id);
+        if let Some((_, mut field)) = self.number_edit.take_if(|(edit_id, _)| *edit_id == id) {
+            if interaction.is_focused {
+                self.edit_text(d, bounds, interaction, &mut field);
+            }
+            if self.focused == Some(id) {
+                self.number_edit = Some((id, field));
+                return false;
+            }
+            if !self.input.is_escaped && let Some(typed) = spec.parse(field.text()) {
+                *value = typed;
+            }
+        } else if interaction.is_held {
+            let mouse_x = self.input.mouse_pos.x;
+            if self.input.is_mouse_pressed {
+                self.scrub = Some(Scrub { id, last_x: mouse_x, distance: 0.0, value: *value });
+            } else if let Some(scrub) = &mut self.scrub && scrub.id == id {
+                let delta = mouse_x - scrub.last_x;
+                scrub.last_x = mouse_x;
+                scrub.distance += delta.abs();
+                if scrub.distance > SCRUB_THRESHOLD {
+                    scrub.value = (scrub.value + delta / NumberSpec::SCRUB_PIXELS * spec.step * speed).clamp(spec.min, spec.max.max(spec.min));
+                    *value = spec.constrain(scrub.value);
+                }
+            }
+        } else if interaction.is_focused {
+            // right and up both increase the value
+            let step = self.input.step.x - self.input.step.y;
+            if step != 0.0 {
+                *value = spec.constrain(*value + step * spec.step * speed);
+            }
+        }
+
+        let is_click = interaction.is_clicked && self.scrub.is_some_and(|scrub| scrub.id == id && scrub.distance <= SCRUB_THRESHOLD);
+        // the Enter that applies a typed value shouldn't start typing again
+        if !was_typing && (is_click || (interaction.is_focused && self.input.is_activated)) {
+            self.number_edit = Some((id, TextField::new(&spec.format(*value))));
+        }
+
+        d.draw_rectangle_rec(bounds, Self::fill_color(&theme, interaction));
+        draw_label(d, &theme, bounds, &spec.format(*value), true);
+        Self::draw_focus(d, &theme, bounds, interaction);
+        *value != prev_value
+    }
+
+    /// Draw a button showing the `selected` item, which opens a list of `items` to choose from
+    ///
+    /// While focused, Up and Down choose the previous and next items without opening the list.
+    /// Returns whether `selected` changed
+    pub fn dropdown(&mut self, d: &mut impl RaylibDraw, id: WidgetId, bounds: Rectangle, items: &[&str], selected: &mut usize) -> bool {
+        let theme = self.theme;
+        let prev_selected = *selected;
+        let list = Rectangle { y: bounds.y + bounds.height, height: bounds.height * items.len() as f32, ..bounds };
+        let is_open = self.open_dropdown == Some(id);
+
+        // the list is checked before the header, since it covers widgets drawn after the dropdown
+        if is_open && self.input.is_mouse_pressed && self.is_hovered(id, list) {
+            let row = ((self.input.mouse_pos.y - list.y) / bounds.height).floor() as usize;
+            *selected = row.min(items.len().saturating_sub(1));
+            self.open_dropdown = None;
+            self.focused = Some(id);
+        }
+        let interaction = self.interact(id, bounds);
+        if interaction.is_focused && self.input.step.y != 0.0 && !items.is_empty() {
+            *selected = if self.input.step.y < 0.0 {
+                selected.saturating_sub(1)
+            } else {
+                selected.saturating_add(1).min(items.len().saturating_sub(1))
+            };
+        }
+        if (interaction.is_hovered && self.input.is_mouse_pressed) || (interaction.is_focused && self.input.is_activated) {
+            self.open_dropdown = if is_open { None } else { Some(id) };
+        }
+
+        d.draw_rectangle_rec(bounds, Self::fill_color(&theme, interaction));
+        let arrow_size = bounds.height * 0.25;
+        let text_bounds = Rectangle { width: bounds.width - arrow_size * 2.0 - PADDING, ..bounds };
+        draw_label(d, &theme, text_bounds, items.get(*selected).copied().unwrap_or_default(), false);
+        let center = Vector2::new(bounds.x + bounds.width - PADDING - arrow_size, bounds.y + bounds.height * 0.5);
+        d.draw_triangle(
+            Vector2::new(center.x - arrow_size, center.y - arrow_size * 0.5),
+            Vector2::new(center.x, center.y + arrow_size * 0.5),
+            Vector2::new(center.x + arrow_size, center.y - arrow_size * 0.5),
+            theme.color_foreground,
+        );
+        Self::draw_focus(d, &theme, bounds, interaction);
+
+        if self.open_dropdown == Some(id) {
+            self.popup = Some(Popup {
+                owner: id,
+                header: bounds,
+                list,
+                items: items.iter().map(|&item| item.to_owned()).collect(),
+                selected: *selected,
+            });
+        }
+        *selected != prev_selected
+    }
+
+    /// Scroll a column of content `content_height` tall within `bounds`, keeping `offset` (the distance scrolled from the top) in range
+    ///
+    /// Scrolls with the mouse wheel while hovered, by dragging the scrollbar, and with the arrow and page keys while focused.
+    /// Returns the part of `bounds` left for the content beside the scrollbar; draw the content shifted up by `offset`,
+    /// clipped to that rectangle
+    pub fn scroll_area(&mut self, d: &mut impl RaylibDraw, id: WidgetId, bounds: Rectangle, content_height: f32, offset: &mut f32) -> Rectangle {
+        let theme = self.theme;
+        let max_offset = (content_height - bounds.height).max(0.0);
+        if max_offset <= 0.0 {
+            *offset = 0.0;
+            return scroll_viewport(bounds, content_height);
+        }
+
+        let track = Rectangle { x: bounds.x + bounds.width - SCROLLBAR_WIDTH, width: SCROLLBAR_WIDTH, ..bounds };
+        let thumb_height = (bounds.height * bounds.height / content_height).max(SCROLLBAR_WIDTH);
+        let interaction = self.interact(id, bounds);
+        // the scrollbar is held separately, so dragging the content doesn't scroll
+        let scrollbar = self.hold(id.child(0), track);
+        let is_dragging = scrollbar.is_held;
+        if is_dragging {
+            // center the thumb on the mouse
+            let amount = (self.input.mouse_pos.y - track.y - thumb_height * 0.5) / (track.height - thumb_height);
+            *offset = max_offset * amount;
+        } else {
+            if interaction.is_hovered {
+                *offset -= self.input.wheel * SCROLL_STEP;
+            }
+            if interaction.is_focused {
+                *offset += self.input.step.y * SCROLL_STEP + self.input.page * bounds.height;
+            }
+        }
+        *offset = offset.clamp(0.0, max_offset);
+
+        let thumb = Rectangle {
+            y: track.y + (track.height - thumb_height) * (*offset / max_offset),
+            height: thumb_height,
+            ..track
+        };
+        d.draw_rectangle_rec(track, theme.color_panel_edge);
+        d.draw_rectangle_rec(thumb, if is_dragging { theme.color_accent } else { theme.color_foreground.alpha(0.5) });
+        Self::draw_focus(d, &theme, bounds, interaction);
+        scroll_viewport(bounds, content_height)
+    }
+
+    /// Scroll a list of `count` rows, each `row_height` tall, within `bounds` like a [`Ui::scroll_area`]
+    ///
+    /// Returns only the rows in view, so drawing a long list costs no more than drawing a short one.
+    /// Rows at the edges are only partly in view; draw them clipped to [`ListRows::viewport`]
+    pub fn list(&mut self, d: &mut impl RaylibDraw, id: WidgetId, bounds: Rectangle, row_height: f32, count: usize, offset: &mut f32) -> ListRows {
+        self.scroll_area(d, id, bounds, row_height * count as f32, offset);
+        ListRows::new(bounds, row_height, count, *offset)
+    }
+
+    /// Draw a round handle `radius` in size at `position` that follows the mouse while dragged, staying within `area`
+    ///
+    /// Returns whether `position` changed
+    pub fn drag_handle(&mut self, d: &mut impl RaylibDraw, id: WidgetId, position: &mut Vector2, radius: f32, area: Rectangle) -> bool {
+        let theme = self.theme;
+        let bounds = Rectangle::new(position.x - radius, position.y - radius, radius * 2.0, radius * 2.0);
+        let interaction = self.hold(id, bounds);
+        let prev_position = *position;
+        if interaction.is_held {
+            let mouse_pos = self.input.mouse_pos;
+            *position = Vector2::new(mouse_pos.x.clamp(area.x, area.x + area.width), mouse_pos.y.clamp(area.y, area.y + area.height));
+        }
+        d.draw_circle_v(*position, radius, Self::fill_color(&theme, interaction));
+        d.draw_circle_lines(position.x as i32, position.y as i32, radius, theme.color_foreground);
+        *position != prev_position
+    }
+
+    /// Whether the mouse was pressed on the widget `id` and hasn't been released yet
+    pub fn is_held(&self, id: WidgetId) -> bool {
+        self.held == Some(id)
+    }
+
+    /// Whether the mouse is over `bounds`, for ignoring clicks on parts of rows clipped out of a [list][`Ui::list`]
+    pub fn is_mouse_within(&self, bounds: Rectangle) -> bool {
+        bounds.check_collision_point_rec(self.input.mouse_pos)
+    }
+}
+
+/// The rows of a [list][`Ui::list`] that are in view
+#[derive(Debug, Clone, PartialEq)]
+pub struct ListRows {
+    /// The part of the list's bounds beside the scrollbar, which rows should be clipped to
+    pub viewport: Rectangle,
+
+    /// Indices of the rows at least partly inside the viewport
+    pub visible: Range<usize>,
+
+    row_height: f32,
+    offset: f32,
+}
+
+impl ListRows {
+    /// The rows in view of a list of `count` rows, each `row_height` tall, within `bounds` and scrolled down by `offset`
+    ///
+    /// Matches what [`Ui::list`] returns for the same arguments, for hit testing rows outside the draw phase
+    pub fn new(bounds: Rectangle, row_height: f32, count: usize, offset: f32) -> Self {
+        let viewport = scroll_viewport(bounds, row_height * count as f32);
+        Self {
+            viewport,
+            visible: visible_rows(offset, row_height, viewport.height, count),
+            row_height,
+            offset,
+        }
+    }
+
+    /// The rectangle of the row at `index`, which may be outside the viewport
+    pub fn rect(&self, index: usize) -> Rectangle {
+        Rectangle {
+            y: self.viewport.y + self.row_height * index as f32 - self.offset,
+            height: self.row_height,
+            ..self.viewport
+        }
+    }
+
+    /// The index and rectangle of each row in view, from the top
+    pub fn iter(&self) -> impl Iterator<Item = (usize, Rectangle)> + '_ {
+        self.visible.clone().map(|i| (i, self.rect(i)))
+    }
+
+    /// The row in view under `point`, if any
+    pub fn row_at(&self, point: Vector2) -> Option<usize> {
+        if !self.viewport.check_collision_point_rec(point) {
+            return None;
+        }
+        self.iter().find(|(_, rect)| rect.check_collision_point_rec(point)).map(|(i, _)| i)
+    }
+}
+
+/// The part of a [scroll area][`Ui::scroll_area`] at `bounds` left for content `content_height` tall,
+/// which is narrower if the content overflows and needs a scrollbar
+pub fn scroll_viewport(bounds: Rectangle, content_height: f32) -> Rectangle {
+    if content_height > bounds.height {
+        Rectangle { width: bounds.width - SCROLLBAR_WIDTH, ..bounds }
+    } else {
+        bounds
+    }
+}
+
+/// The widget after `focused` in the Tab `order` (or before it, if `is_backward`), wrapping around at either end
+///
+/// [`None`] if `focused` isn't in the order
+fn next_focus(order: &[WidgetId], focused: WidgetId, is_backward: bool) -> Option<WidgetId> {
+    let i = order.iter().position(|&id| id == focused)?;
+    let last = order.len().saturating_sub(1);
+    let next = if is_backward {
+        i.checked_sub(1).unwrap_or(last)
+    } else if i >= last {
+        0
+    } else {
+        i.saturating_add(1)
+    };
+    order.get(next).copied()
+}
+
+/// Indices of the rows, of `count` rows each `row_height` tall scrolled down by `offset`,
+/// that are at least partly within `view_height` of the top
+pub fn visible_rows(offset: f32, row_height: f32, view_height: f32, count: usize) -> Range<usize> {
+    if row_height <= 0.0 {
+        return 0..0;
+    }
+    let start = (offset / row_height).floor().max(0.0) as usize;
+    let end = ((offset + view_height) / row_height).ceil().max(0.0) as usize;
+    start.min(count)..end.min(count)
+}
+
+/// Draw `text` vertically centered in `bounds`, either centered horizontally or from the left with padding
+///
+/// Text wider than `bounds` is cut short rather than clipped, so labels can be drawn within scissor modes
+fn draw_label(d: &mut impl RaylibDraw, theme: &EngineTheme, bounds: Rectangle, text: &str, is_centered: bool) {
+    let text = fit_start(text, theme.font_size, bounds.width - PADDING * 2.0);
+    let x = if is_centered {
+        bounds.x + ((bounds.width - measure_text(text, theme.font_size) as f32) * 0.5).max(PADDING)
+    } else {
+        bounds.x + PADDING
+    };
+    let y = bounds.y + (bounds.height - theme.font_size as f32) * 0.5;
+    d.draw_text(text, x as i32, y as i32, theme.font_size, theme.color_foreground);
+}
+
+/// The width of `text` in the default font at `font_size`, in pixels
+///
+/// Unlike [`RaylibHandle::measure_text`], usable while only a drawing handle is available
+pub fn measure_text(text: &str, font_size: i32) -> i32 {
+    let Ok(text) = CString::new(text) else { return 0 };
+    unsafe { MeasureText(text.as_ptr(), font_size) }
+}
+
+/// The longest start of `text` no wider than `width` at `font_size`
+fn fit_start<'a>(text: &'a str, font_size: i32, width: f32) -> &'a str {
+    let mut end = text.len();
+    while end > 0 && measure_text(&text[..end], font_size) as f32 > width {
+        end = text[..end].char_indices().next_back().map_or(0, |(i, _)| i);
+    }
+    &text[..end]
+}
+
+/// The position in `field` nearest to `x` pixels right of the start of its visible text
+fn index_at(field: &TextField, x: f32, font_size: i32) -> usize {
+    let visible = &field.text()[field.scroll..];
+    visible.char_indices()
+        .map(|(i, _)| i)
+        .chain(std::iter::once(visible.len()))
+        .min_by(|&a, &b| {
+            let distance = |i: usize| (measure_text(&visible[..i], font_size) as f32 - x).abs();
+            distance(a).total_cmp(&distance(b))
+        })
+        .map_or(field.text().len(), |i| field.scroll.saturating_add(i))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_focus() {
+        let order = [WidgetId::new("a"), WidgetId::new("b"), WidgetId::new("c")];
+        assert_eq!(next_focus(&order, order[0], false), Some(order[1]));
+        assert_eq!(next_focus(&order, order[2], false), Some(order[0]));
+        assert_eq!(next_focus(&order, order[0], true), Some(order[2]));
+        assert_eq!(next_focus(&order, order[1], true), Some(order[0]));
+        assert_eq!(next_focus(&order, WidgetId::new("d"), false), None);
+    }
+
+    #[test]
+    fn test_visible_rows() {
+        assert_eq!(visible_rows(0.0, 20.0, 100.0, 1000), 0..5);
+        // partly scrolled rows at either edge are still drawn
+        assert_eq!(visible_rows(30.0, 20.0, 100.0, 1000), 1..7);
+        assert_eq!(visible_rows(19_950.0, 20.0, 100.0, 1000), 997..1000);
+        assert_eq!(visible_rows(0.0, 20.0, 100.0, 3), 0..3);
+        assert_eq!(visible_rows(0.0, 0.0, 100.0, 3), 0..0);
+    }
+}
@@ -0,0 +1,183 @@
+use serde::{Deserialize, Serialize};
+
+/// A unit of length that document measurements can be displayed in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Unit {
+    /// Pixels
+    #[default]
+    Pixels,
+
+    /// Millimeters
+    Millimeters,
+
+    /// Inches
+    Inches,
+
+    /// Points (1/72 of an inch)
+    Points,
+}
+
+impl Unit {
+    /// Every unit, in the order they are listed in the UI
+    pub const ALL: [Self; 4] = [Self::Pixels, Self::Millimeters, Self::Inches, Self::Points];
+
+    /// Get the short lowercase abbreviation of the unit
+    #[inline]
+    pub const fn abbreviation(self) -> &'static str {
+        match self {
+            Self::Pixels => "px",
+            Self::Millimeters => "mm",
+            Self::Inches => "in",
+            Self::Points => "pt",
+        }
+    }
+
+    /// How many of this unit make up an inch, given `dpi` pixels per inch
+    #[inline]
+    pub const fn per_inch(self, dpi: f32) -> f32 {
+        match self {
+            Self::Pixels => dpi,
+            Self::Millimeters => 25.4,
+            Self::Inches => 1.0,
+            Self::Points => 72.0,
+        }
+    }
+
+    /// Convert a length in pixels (document units) to this unit
+    #[inline]
+    pub fn from_px(self, px: f32, dpi: f32) -> f32 {
+        px / dpi * self.per_inch(dpi)
+    }
+
+    /// Convert a length in this unit to pixels (document units)
+    #[inline]
+    pub fn to_px(self, value: f32, dpi: f32) -> f32 {
+        value / self.per_inch(dpi) * dpi
+    }
+
+    /// Format a length in pixels (document units) as this unit, like `12.7 mm`
+    ///
+    /// Rounded to at most 2 decimal places, without trailing zeroes
+    pub fn format(self, px: f32, dpi: f32) -> String {
+        format!("{} {}", format_value(self.from_px(px, dpi)), self.abbreviation())
+    }
+
+    /// Parse a length like `12.7mm`, `0.5 in`, or `36`, returning it in pixels (document units)
+    ///
+    /// Numbers without a unit are assumed to be in this unit
+    ///
+    /// [`None`] if the text isn't a number followed by an optional known unit
+    pub fn parse(self, text: &str, dpi: f32) -> Option<f32> {
+        let text = text.trim();
+        let number_end = text.find(|c: char| !(c.is_ascii_digit() || matches!(c, '.' | '-' | '+'))).unwrap_or(text.len());
+        let (number, unit) = text.split_at(number_end);
+        let value = number.parse::<f32>().ok()?;
+        let unit = match unit.trim() {
+            "" => self,
+            unit => Self::ALL.into_iter().find(|u| u.abbreviation().eq_ignore_ascii_case(unit))?,
+        };
+        Some(unit.to_px(value, dpi))
+    }
+}
+
+/// How coordinates are rounded to the document's pixel grid while drawing and editing,
+/// so that icons and UI assets stay crisp
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum PixelSnap {
+    /// Coordinates are left as-is
+    #[default]
+    Off,
+
+    /// Coordinates are rounded to whole pixels
+    Whole,
+
+    /// Coordinates are rounded to the nearest half pixel
+    Half,
+}
+
+impl PixelSnap {
+    /// Every mode, in the order they are listed in the UI
+    pub const ALL: [Self; 3] = [Self::Off, Self::Whole, Self::Half];
+
+    /// The mode after this one in [`PixelSnap::ALL`], wrapping around to the first
+    #[inline]
+    pub const fn next(self) -> Self {
+        match self {
+            Self::Off => Self::Whole,
+            Self::Whole => Self::Half,
+            Self::Half => Self::Off,
+        }
+    }
+
+    /// Round a coordinate in pixels (document units) to the grid
+    #[inline]
+    pub fn round(self, px: f32) -> f32 {
+        match self {
+            Self::Off => px,
+            Self::Whole => px.round(),
+            Self::Half => (px * 2.0).round() * 0.5,
+        }
+    }
+}
+
+/// Format a number rounded to at most 2 decimal places, without trailing zeroes
+pub fn format_value(value: f32) -> String {
+    let text = format!("{value:.2}");
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+    if text == "-0" { "0".to_owned() } else { text.to_owned() }
+}
+
+/// Get the smallest "nice" ruler step (1, 2, or 5 times a power of 10) that is at least `min`
+pub fn nice_step(min: f32) -> f32 {
+    if !(min > 0.0 && min.is_finite()) {
+        return 1.0;
+    }
+    let magnitude = 10.0f32.powf(min.log10().floor());
+    [1.0, 2.0, 5.0, 10.0].into_iter()
+        .map(|n| n * magnitude)
+        .find(|&step| step >= min)
+        .unwrap_or(magnitude * 10.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unit_round_trip() {
+        for unit in Unit::ALL {
+            let px = unit.to_px(12.5, 96.0);
+            assert!((unit.from_px(px, 96.0) - 12.5).abs() < 1e-4, "{unit:?} did not round trip");
+        }
+        assert_eq!(Unit::Inches.to_px(1.0, 96.0), 96.0);
+        assert_eq!(Unit::Points.to_px(72.0, 300.0), 300.0);
+    }
+
+    #[test]
+    fn test_unit_parse() {
+        assert_eq!(Unit::Pixels.parse("1in", 96.0), Some(96.0));
+        assert_eq!(Unit::Inches.parse(" 2 ", 96.0), Some(192.0));
+        assert_eq!(Unit::Pixels.parse("72 PT", 96.0), Some(96.0));
+        assert_eq!(Unit::Millimeters.format(Unit::Millimeters.to_px(12.7, 96.0), 96.0), "12.7 mm");
+        assert_eq!(Unit::Pixels.parse("12 furlongs", 96.0), None);
+        assert_eq!(Unit::Pixels.parse("", 96.0), None);
+    }
+
+    #[test]
+    fn test_nice_step() {
+        assert_eq!(nice_step(13.0), 20.0);
+        assert_eq!(nice_step(30.0), 50.0);
+        assert_eq!(nice_step(0.0), 1.0);
+        assert_eq!(format_value(2.50), "2.5");
+        assert_eq!(format_value(-0.001), "0");
+    }
+
+    #[test]
+    fn test_pixel_snap() {
+        assert_eq!(PixelSnap::Off.round(1.3), 1.3);
+        assert_eq!(PixelSnap::Whole.round(1.3), 1.0);
+        assert_eq!(PixelSnap::Half.round(1.3), 1.5);
+        assert_eq!(PixelSnap::Half.round(-0.2), 0.0);
+        assert_eq!(PixelSnap::Half.next().next(), PixelSnap::Whole);
+    }
+}
@@ -0,0 +1,189 @@
+use std::{path::PathBuf, sync::Arc};
+use serde::{Deserialize, Serialize};
+use crate::{document::Document, preferences, style::{WeakWidthProfile, WidthProfile, WidthProfileControl, WidthProfileVertex}};
+
+/// A named width profile that can be given to strokes in any document
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WidthPreset {
+    /// The display name of the preset
+    pub name: String,
+
+    /// The thickness curve, normalized so its thickest side is 1 thick and its controls run from t=0 to t=1
+    pub profile: WidthProfile,
+}
+
+impl WidthPreset {
+    /// Construct a preset from a copy of `profile`, normalized to unit thickness and length
+    pub fn new(name: String, profile: &WidthProfile) -> Self {
+        let mut profile = profile.clone();
+        let thickness = profile.max_thickness();
+        if thickness > 0.0 {
+            profile.scale(thickness.recip());
+        }
+        if let WidthProfile::Variable(controls) = &mut profile {
+            let length = controls.iter().map(|control| control.t).fold(0.0, f32::max);
+            if length > 0.0 {
+                for control in controls {
+                    control.t /= length;
+                }
+            }
+        }
+        Self { name, profile }
+    }
+
+    /// A copy of the profile `thickness` thick at its thickest, with its controls spread over `length` segments
+    pub fn fit(&self, thickness: f32, length: f32) -> WidthProfile {
+        let mut profile = self.profile.clone();
+        profile.scale(thickness);
+        if let WidthProfile::Variable(controls) = &mut profile {
+            for control in controls {
+                control.t *= length;
+            }
+        }
+        profile
+    }
+}
+
+/// The [width presets][`WidthPreset`] strokes can be given: a built-in set, followed by the presets the user saved
+#[derive(Debug)]
+pub struct WidthPresetLibrary {
+    /// The file the user's presets are read from and written to
+    ///
+    /// [`None`] if there is no file, so saved presets only last until the application exits
+    pub path: Option<PathBuf>,
+
+    /// The built-in presets, then the user's presets in the order they were saved
+    pub presets: Vec<WidthPreset>,
+}
+
+impl Default for WidthPresetLibrary {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WidthPresetLibrary {
+    /// The names of the built-in presets, each generated by [`built_in_profile`]
+    const BUILT_IN: [&str; 4] = ["Uniform", "Taper both ends", "Brush pen", "Calligraphic"];
+
+    /// Construct a library without any presets, before [loading][`WidthPresetLibrary::load`] is possible
+    pub const fn new() -> Self {
+        Self {
+            path: None,
+            presets: Vec::new(),
+        }
+    }
+
+    /// The file used when the user hasn't chosen one
+    ///
+    /// [`None`] if the home folder can't be determined
+    pub fn default_path() -> Option<PathBuf> {
+        Some(preferences::app_directory()?.join("width_presets.json"))
+    }
+
+    /// Generate the built-in presets and read the user's presets from `path`
+    ///
+    /// A missing file just means the user hasn't saved any presets yet
+    pub fn load(path: Option<PathBuf>) -> Result<Self, String> {
+        let mut presets = Self::BUILT_IN
+            .map(|name| WidthPreset { name: name.to_string(), profile: built_in_profile(name) })
+            .to_vec();
+        if let Some(path) = &path
+            && path.exists()
+        {
+            let json = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+            let saved: Vec<WidthPreset> = serde_json::from_str(&json).map_err(|e| e.to_string())?;
+            presets.extend(saved);
+        }
+        Ok(Self { path, presets })
+    }
+
+    /// Write the user's presets to the library's file, creating its folder if it doesn't exist
+    fn save(&self) -> Result<(), String> {
+        let Some(path) = &self.path else { return Ok(()) };
+        if let Some(directory) = path.parent() {
+            std::fs::create_dir_all(directory).map_err(|e| e.to_string())?;
+        }
+        let saved = self.presets.get(Self::BUILT_IN.len()..).unwrap_or_default();
+        let json = serde_json::to_string_pretty(saved).map_err(|e| e.to_string())?;
+        std::fs::write(path, json).map_err(|e| e.to_string())
+    }
+
+    /// Save a normalized copy of `profile` as a preset named `name`, replacing any saved preset with the same name
+    ///
+    /// Returns the index of the preset
+    pub fn add(&mut self, name: String, profile: &WidthProfile) -> Result<usize, String> {
+        let preset = WidthPreset::new(name, profile);
+        let built_in = Self::BUILT_IN.len().min(self.presets.len());
+        let index = match self.presets[built_in..].iter().position(|existing| existing.name == preset.name) {
+            Some(existing) => {
+                let index = built_in.saturating_add(existing);
+                self.presets[index] = preset;
+                index
+            }
+            None => {
+                self.presets.push(preset);
+                self.presets.len().saturating_sub(1)
+            }
+        };
+        self.save()?;
+        Ok(index)
+    }
+
+    /// The name for the next preset the user saves without naming it
+    pub fn next_name(&self) -> String {
+        let saved = self.presets.len().saturating_sub(Self::BUILT_IN.len());
+        format!("Custom {}", saved.saturating_add(1))
+    }
+
+    /// Copy the preset at `index` into `document`, fit to `thickness` and `length`; see [`WidthPreset::fit`]
+    pub fn apply(&self, index: usize, thickness: f32, length: f32, document: &mut Document) -> Result<WeakWidthProfile, String> {
+        let preset = self.presets.get(index).ok_or("width preset index out of bounds")?;
+        Ok(Arc::downgrade(document.create_width_profile(preset.fit(thickness, length))))
+    }
+}
+
+/// The profile of the built-in preset `name`, at unit thickness and length
+fn built_in_profile(name: &str) -> WidthProfile {
+    let even = |t: f32, thick: f32| WidthProfileControl::new_even(t, WidthProfileVertex::flat(thick));
+    match name {
+        "Uniform" => WidthProfile::new_flat(1.0),
+        "Taper both ends" => WidthProfile::Variable(vec![even(0.0, 0.0), even(0.5, 1.0), even(1.0, 0.0)]),
+        // presses down quickly, then lifts off slowly
+        "Brush pen" => WidthProfile::Variable(vec![even(0.0, 0.2), even(0.15, 1.0), even(0.6, 0.8), even(1.0, 0.0)]),
+        // broad on one side and thin on the other, like a flat nib held at an angle
+        "Calligraphic" => WidthProfile::Variable([(0.0, 0.3, 0.1), (0.5, 1.0, 0.3), (1.0, 0.3, 0.1)]
+            .map(|(t, inner, outer)| WidthProfileControl { t, inner: WidthProfileVertex::flat(inner), outer: WidthProfileVertex::flat(outer) })
+            .to_vec()),
+        _ => WidthProfile::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_and_fit() {
+        let profile = WidthProfile::Variable(vec![
+            WidthProfileControl::new_even(0.0, WidthProfileVertex::flat(2.0)),
+            WidthProfileControl::new_even(4.0, WidthProfileVertex::flat(8.0)),
+        ]);
+        let preset = WidthPreset::new("test".to_string(), &profile);
+        let WidthProfile::Variable(controls) = &preset.profile else { panic!("should stay variable") };
+        assert_eq!(controls.iter().map(|control| (control.t, control.inner.thick)).collect::<Vec<_>>(), [(0.0, 0.25), (1.0, 1.0)]);
+
+        let WidthProfile::Variable(controls) = preset.fit(3.0, 2.0) else { panic!("should stay variable") };
+        assert_eq!(controls.iter().map(|control| (control.t, control.outer.thick)).collect::<Vec<_>>(), [(0.0, 0.75), (2.0, 3.0)]);
+    }
+
+    #[test]
+    fn test_built_in_presets_are_normalized() {
+        let library = WidthPresetLibrary::load(None).unwrap();
+        assert_eq!(library.presets.len(), WidthPresetLibrary::BUILT_IN.len());
+        for preset in &library.presets {
+            assert_eq!(preset.profile.max_thickness(), 1.0, "{} should be unit thickness", preset.name);
+        }
+    }
+}
@@ -0,0 +1,246 @@
+use crate::export::crc32;
+
+/// The first bytes of every file entry in a zip archive, and so of the archive itself
+pub const LOCAL_HEADER_SIGNATURE: &[u8] = b"PK\x03\x04";
+
+/// Marks each entry of the central directory
+const CENTRAL_HEADER_SIGNATURE: u32 = 0x0201_4b50;
+
+/// Marks the end of the central directory, at the very end of the archive
+const END_SIGNATURE: u32 = 0x0605_4b50;
+
+/// Size of the end of central directory record, without its comment
+const END_LEN: usize = 22;
+
+/// Compression method of files stored as-is
+const METHOD_STORED: u16 = 0;
+
+/// Compression method of files compressed with DEFLATE
+const METHOD_DEFLATED: u16 = 8;
+
+/// Largest file that can be compressed, since raylib decompresses into a buffer of this fixed size
+const MAX_DEFLATED_LEN: usize = 64 * 1024 * 1024;
+
+/// Builds a zip archive, storing files as-is unless [compressed][`ZipWriter::add_compressed`]
+///
+/// PNGs are already compressed, so storing them as-is costs little space
+#[derive(Debug, Default)]
+pub struct ZipWriter {
+    bytes: Vec<u8>,
+    /// The central directory entry of each file added so far
+    directory: Vec<u8>,
+    count: u16,
+}
+
+impl ZipWriter {
+    /// Zip version 2.0, the first to support folders and DEFLATE
+    const VERSION: u16 = 20;
+
+    /// 1980-01-01 in MS-DOS date format; files get the earliest date zip can represent, since they have no meaningful one
+    const DATE: u16 = 0x21;
+
+    /// Append the file `name` containing `data`, uncompressed
+    pub fn add(&mut self, name: &str, data: &[u8]) {
+        self.add_stored_as(name, METHOD_STORED, data, crc32(data), data.len());
+    }
+
+    /// Append the file `name` containing `data`, compressed with DEFLATE
+    ///
+    /// Stored uncompressed instead if compressing doesn't make it smaller, or it is too large to decompress
+    pub fn add_compressed(&mut self, name: &str, data: &[u8]) {
+        match deflate(data) {
+            Some(deflated) if deflated.len() < data.len() => self.add_stored_as(name, METHOD_DEFLATED, &deflated, crc32(data), data.len()),
+            _ => self.add(name, data),
+        }
+    }
+
+    /// Append the file `name`, whose `len` bytes with the checksum `crc` are stored as `stored` with `method`
+    fn add_stored_as(&mut self, name: &str, method: u16, stored: &[u8], crc: u32, len: usize) {
+        let stored_size = u32::try_from(stored.len()).unwrap_or(u32::MAX);
+        let size = u32::try_from(len).unwrap_or(u32::MAX);
+        let name_length = u16::try_from(name.len()).unwrap_or(u16::MAX);
+        let offset = u32::try_from(self.bytes.len()).unwrap_or(u32::MAX);
+        // version needed, flags, method, time, date, crc, compressed and uncompressed size, name length, extra length
+        let mut fields = Vec::with_capacity(26);
+        fields.extend(Self::VERSION.to_le_bytes());
+        fields.extend(0u16.to_le_bytes());
+        fields.extend(method.to_le_bytes());
+        fields.extend(0u16.to_le_bytes());
+        fields.extend(Self::DATE.to_le_bytes());
+        fields.extend(crc.to_le_bytes());
+        fields.extend(stored_size.to_le_bytes());
+        fields.extend(size.to_le_bytes());
+        fields.extend(name_length.to_le_bytes());
+        fields.extend(0u16.to_le_bytes());
+
+        self.bytes.extend(LOCAL_HEADER_SIGNATURE);
+        self.bytes.extend(&fields);
+        self.bytes.extend(name.as_bytes());
+        self.bytes.extend(stored);
+
+        self.directory.extend(CENTRAL_HEADER_SIGNATURE.to_le_bytes());
+        self.directory.extend(Self::VERSION.to_le_bytes());
+        self.directory.extend(&fields);
+        // comment length, disk number, internal attributes, external attributes, offset of the local header
+        self.directory.extend(0u16.to_le_bytes());
+        self.directory.extend(0u16.to_le_bytes());
+        self.directory.extend(0u16.to_le_bytes());
+        self.directory.extend(0u32.to_le_bytes());
+        self.directory.extend(offset.to_le_bytes());
+        self.directory.extend(name.as_bytes());
+        self.count = self.count.saturating_add(1);
+    }
+
+    /// Append the central directory and return the finished archive
+    pub fn finish(mut self) -> Vec<u8> {
+        let offset = u32::try_from(self.bytes.len()).unwrap_or(u32::MAX);
+        let size = u32::try_from(self.directory.len()).unwrap_or(u32::MAX);
+        self.bytes.append(&mut self.directory);
+        self.bytes.extend(END_SIGNATURE.to_le_bytes());
+        // disk number, disk with the directory, entries on this disk, total entries, directory size and offset, comment length
+        self.bytes.extend(0u16.to_le_bytes());
+        self.bytes.extend(0u16.to_le_bytes());
+        self.bytes.extend(self.count.to_le_bytes());
+        self.bytes.extend(self.count.to_le_bytes());
+        self.bytes.extend(size.to_le_bytes());
+        self.bytes.extend(offset.to_le_bytes());
+        self.bytes.extend(0u16.to_le_bytes());
+        self.bytes
+    }
+}
+
+/// A file in a [`ZipArchive`]
+#[derive(Debug)]
+struct ZipEntry<'a> {
+    name: String,
+    method: u16,
+    crc: u32,
+    /// Size of the file once decompressed
+    len: usize,
+    /// The file as stored in the archive
+    stored: &'a [u8],
+}
+
+/// A zip archive in memory, whose files are decompressed as they are read
+#[derive(Debug)]
+pub struct ZipArchive<'a> {
+    entries: Vec<ZipEntry<'a>>,
+}
+
+impl<'a> ZipArchive<'a> {
+    /// List the files of the archive `bytes` from its central directory
+    ///
+    /// Only single-disk archives without zip64 extensions are supported
+    pub fn parse(bytes: &'a [u8]) -> Result<Self, String> {
+        let u16_at = |at: usize| bytes.get(at..at + 2).map(|b| u16::from_le_bytes([b[0], b[1]])).ok_or("zip archive is truncated");
+        let u32_at = |at: usize| bytes.get(at..at + 4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]])).ok_or("zip archive is truncated");
+
+        // the end record is followed by a comment of unknown length, so search backward for it
+        let end = (0..=bytes.len().saturating_sub(END_LEN)).rev()
+            .find(|&at| u32_at(at) == Ok(END_SIGNATURE))
+            .ok_or("not a zip archive")?;
+        let count = u16_at(end + 10)?;
+        let mut at = u32_at(end + 16)? as usize;
+        let mut entries = Vec::with_capacity(count.into());
+        for _ in 0..count {
+            if u32_at(at)? != CENTRAL_HEADER_SIGNATURE {
+                return Err("zip central directory is corrupt".to_owned());
+            }
+            let method = u16_at(at + 10)?;
+            let crc = u32_at(at + 16)?;
+            let stored_len = u32_at(at + 20)? as usize;
+            let len = u32_at(at + 24)? as usize;
+            let name_len = u16_at(at + 28)? as usize;
+            let extra_len = u16_at(at + 30)? as usize;
+            let comment_len = u16_at(at + 32)? as usize;
+            let local = u32_at(at + 42)? as usize;
+            let name = bytes.get(at + 46..at + 46 + name_len).ok_or("zip archive is truncated")?;
+            let name = String::from_utf8_lossy(name).into_owned();
+
+            // the local header's name and extra field can differ in length from the central directory's
+            let start = local + 30 + u16_at(local + 26)? as usize + u16_at(local + 28)? as usize;
+            let stored = bytes.get(start..start + stored_len).ok_or("zip archive is truncated")?;
+            entries.push(ZipEntry { name, method, crc, len, stored });
+            at += 46 + name_len + extra_len + comment_len;
+        }
+        Ok(Self { entries })
+    }
+
+    /// The contents of the file `name`, decompressed and checked for corruption
+    pub fn file(&self, name: &str) -> Result<Vec<u8>, String> {
+        let entry = self.entries.iter()
+            .find(|entry| entry.name == name)
+            .ok_or_else(|| format!("zip archive has no file \"{name}\""))?;
+        let data = match entry.method {
+            METHOD_STORED => entry.stored.to_vec(),
+            METHOD_DEFLATED => inflate(entry.stored)?,
+            method => return Err(format!("unsupported zip compression method {method} of \"{name}\"")),
+        };
+        if data.len() != entry.len || crc32(&data) != entry.crc {
+            return Err(format!("\"{name}\" is corrupt"));
+        }
+        Ok(data)
+    }
+}
+
+/// Compress `data` with raw DEFLATE, as stored in zip archives
+///
+/// [`None`] if it is too large to be [inflated][`inflate`] again or compression fails
+fn deflate(data: &[u8]) -> Option<Vec<u8>> {
+    if data.len() > MAX_DEFLATED_LEN {
+        return None;
+    }
+    let mut size = 0;
+    // SAFETY: the data is only read, and the compressed buffer is copied before raylib frees it
+    unsafe {
+        let compressed = raylib::ffi::CompressData(data.as_ptr().cast_mut(), i32::try_from(data.len()).ok()?, &mut size);
+        if compressed.is_null() {
+            return None;
+        }
+        let deflated = std::slice::from_raw_parts(compressed, size.max(0) as usize).to_vec();
+        raylib::ffi::MemFree(compressed.cast());
+        Some(deflated)
+    }
+}
+
+/// Decompress raw DEFLATE `data`
+fn inflate(data: &[u8]) -> Result<Vec<u8>, String> {
+    let mut size = 0;
+    let len = i32::try_from(data.len()).map_err(|e| e.to_string())?;
+    // SAFETY: the data is only read, and the decompressed buffer is copied before raylib frees it
+    unsafe {
+        let decompressed = raylib::ffi::DecompressData(data.as_ptr().cast_mut(), len, &mut size);
+        if decompressed.is_null() {
+            return Err("failed to decompress".to_owned());
+        }
+        let inflated = std::slice::from_raw_parts(decompressed, size.max(0) as usize).to_vec();
+        raylib::ffi::MemFree(decompressed.cast());
+        Ok(inflated)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zip_round_trip() {
+        let text = "the quick brown fox jumps over the lazy dog ".repeat(100);
+        let mut zip = ZipWriter::default();
+        zip.add("stored.bin", &[1, 2, 3]);
+        zip.add_compressed("folder/compressed.txt", text.as_bytes());
+        zip.add_compressed("tiny", &[4]);
+        let bytes = zip.finish();
+        assert!(bytes.len() < text.len(), "repetitive text should compress");
+
+        let archive = ZipArchive::parse(&bytes).unwrap();
+        assert_eq!(archive.file("stored.bin").unwrap(), [1, 2, 3]);
+        assert_eq!(archive.file("folder/compressed.txt").unwrap(), text.as_bytes());
+        assert_eq!(archive.file("tiny").unwrap(), [4]);
+        assert!(archive.file("missing").is_err());
+
+        let mut corrupt = bytes.clone();
+        corrupt[31 + "stored.bin".len()] ^= 0xff;
+        assert!(ZipArchive::parse(&corrupt).unwrap().file("stored.bin").is_err(), "corruption should fail the checksum");
+    }
+}